@@ -0,0 +1,673 @@
+# English, also used for anything missing from other languages.
+#
+# Each message is `key = value`, indented lines continue the previous value, and placeholders
+# are written as { $name }.
+
+## Navigation
+nav-mic = Mic
+nav-studio = Studio
+nav-mix = Mix
+nav-mix-create = Mix Create
+nav-pin = Pin
+nav-pin-hover = Show this device alongside the selected one
+nav-unpin = Unpin
+nav-unpin-hover = Stop showing this device alongside the selected one
+nav-identify-hover = Click to flash this device's lights, to tell which one it is
+nav-pipeweaver = Pipeweaver
+nav-integrations = Integrations
+nav-settings = Settings
+
+## Pages
+page-lighting = Lighting
+page-about = About
+page-error = Error
+page-developer = Developer Console
+page-audio = Audio
+page-routing = Routing
+page-button-lighting = Button Lighting
+page-preview = Display Preview
+page-interactions = Input Monitor
+page-display = Display and Buttons
+page-screen-image = Screen Image
+page-dials = Dial Assignment
+page-session = Session Statistics
+page-gestures = Dial Gestures
+
+## Application
+app-no-devices = No Devices Detected
+app-pipeweaver-launched = Pipeweaver UI Launched, check your task bar or app grid for notifications
+
+device-mic = BEACN Mic
+device-studio = BEACN Studio
+device-mix = BEACN Mix
+device-mix-create = BEACN Mix Create
+
+flapping-title = { $device } is flapping, check the cable
+flapping-body = The device keeps connecting and disconnecting, this is usually caused by a faulty
+    cable or USB port. It will be reconnected once it has stayed connected for a while.
+
+safe-mode-title = Running in Safe Mode
+safe-mode-body = Pipeweaver, OBS and Companion are turned off, the image cache isn't used and the
+    UI scale has been reset. Restart the Beacn Utility without --safe-mode to bring them back.
+
+failed-title = The { $name } handler has stopped working
+failed-body = It crashed repeatedly and won't be restarted, restart the Beacn Utility to get it
+    back. The logs will have more details on what went wrong.
+
+conflict-title = Another application appears to be controlling this device
+conflict-body = Settings on the device keep changing without being changed here. This usually
+    means the official BEACN app is running in a virtual machine with the device passed through
+    to it, or another copy of this utility is running under a different user.
+conflict-guidance = Close the other application, or disconnect the device from the virtual
+    machine, otherwise changes made here may be overwritten.
+conflict-dismiss = Dismiss
+
+firmware-title = Some settings on this page need newer firmware
+firmware-feature = { $feature } ({ $version })
+firmware-hint = These can be unlocked by updating the device's firmware using the official BEACN
+    app, the device will need reconnecting afterwards.
+
+## Settings
+settings-heading = About Beacn Utility
+settings-version = Version:
+settings-autostart = Auto-Start the Beacn Utility on Login
+settings-autostart-unavailable = Unable to Handle Auto-Start
+settings-developer-mode = Show the Developer Console
+settings-battery-saver = Save Power when the Battery is Low
+settings-battery-saver-hover = Dims the Mix / Mix Create screen, and reduces how often it's updated
+settings-battery-threshold = Battery Threshold:
+settings-language = Language:
+settings-language-system = System Default
+settings-ui-scale = UI Scale:
+settings-theme = Theme:
+settings-theme-system = Follow System
+settings-theme-dark = Dark
+settings-theme-light = Light
+settings-accent = Accent Colour:
+settings-accent-custom = Custom
+settings-accessibility = Accessibility
+settings-accessibility-screen-reader = Screen Reader Support
+settings-accessibility-screen-reader-hover = Lets screen readers see the controls, takes effect the next time the window is opened
+settings-accessibility-large-targets = Larger Buttons and Sliders
+settings-accessibility-high-contrast = High Contrast
+settings-shortcuts = Keyboard Shortcuts
+settings-shortcuts-page = Switch to a page of the current device
+settings-shortcuts-next = Next device
+settings-shortcuts-previous = Previous device
+settings-shortcuts-fx = Bypass all mic processing, or restore it
+settings-shortcuts-mute = Mute everything
+settings-shortcuts-tab = Tab and Shift+Tab move between controls, Space or Enter activates them.
+settings-hooks = Hooks
+settings-hooks-intro = Run a command, or trigger an action, when something happens. Commands are given
+    BEACN_EVENT, BEACN_SERIAL, BEACN_DEVICE and BEACN_PROFILE where they apply.
+settings-hooks-connected = Device Connected
+settings-hooks-removed = Device Removed
+settings-hooks-mic-muted = Mic Muted
+settings-hooks-mic-unmuted = Mic Unmuted
+settings-hooks-muted = Everything Muted
+settings-hooks-unmuted = Everything Unmuted
+settings-hooks-profile = Profile Switched
+settings-hooks-command = Run Command
+settings-hooks-panic-mute = Toggle Mute Everything
+settings-hooks-suppressor = Suppressor Profile
+settings-hooks-timer = Stream Timer
+settings-hooks-add = Add Hook
+settings-hooks-remove = Remove
+settings-schedule = Profile Schedule
+settings-schedule-intro = Switch suppressor profiles at set times of the day. Each device with a profile of that name switches to it.
+settings-schedule-profile = Profile Name
+settings-schedule-from = from
+settings-schedule-to = to
+settings-schedule-add = Add Schedule
+settings-schedule-remove = Remove
+settings-schedule-active = It's { $time }, using { $profile }
+settings-schedule-none = It's { $time }, nothing is scheduled
+settings-display-sleep = Screen Sleep
+settings-display-sleep-intro = Turns the Mix and Mix Create screens off overnight. Touching a dial or button
+    wakes the screen until the next night when using Pipeweaver.
+settings-display-sleep-enabled = Turn Screens Off
+settings-support-create = Create Support Bundle
+settings-support-hover = Gathers the logs, device settings and device state into a single file to
+    attach to a bug report. Personal details such as your user name are removed.
+settings-support-creating = Creating Support Bundle..
+settings-support-saved = Support Bundle saved to { $path }
+settings-support-copy = Copy Path
+settings-support-failed = Unable to create the Support Bundle: { $error }
+settings-logs = Logs
+settings-logs-all = Everything
+settings-logs-devices = Device Manager
+settings-logs-pipeweaver = Pipeweaver
+settings-logs-ipc = IPC
+settings-logs-other = Other
+settings-logs-search = Search..
+settings-logs-follow = Follow
+settings-logs-copy = Copy
+settings-logs-empty = Nothing has been logged yet
+settings-onboarding = Run the Setup Guide Again
+
+## Setup Guide
+onboarding-heading = Setting up the Beacn Utility
+onboarding-progress = Step { $step } of { $total }
+onboarding-skip = Skip
+onboarding-back = Back
+onboarding-next = Next
+onboarding-finish = Finish
+onboarding-welcome = Welcome!
+onboarding-welcome-intro = This will walk through a few things worth setting up before you start, and
+    show you around. Everything here can be changed later from the settings.
+onboarding-autostart = Starting on Login
+onboarding-autostart-intro = The Beacn Utility keeps your devices' lighting and displays running, and applies
+    their settings when they're plugged in, so it works best when it's started with your desktop.
+    Closing the window leaves it running in the tray.
+onboarding-permissions = Device Permissions
+onboarding-permissions-intro = udev rules are needed to allow the Beacn Utility to access your devices without
+    running as root.
+onboarding-permissions-found = The rules are installed ({ $path })
+onboarding-permissions-missing = No udev rules were found for Beacn devices
+onboarding-permissions-flatpak = The Beacn Utility is running inside a Flatpak, so the rules need to be
+    installed on the host system. If you haven't already, run the following in a terminal:
+onboarding-permissions-install = Install Rules
+onboarding-permissions-installing = Installing, you may be prompted for your password...
+onboarding-permissions-failed = Installation failed: { $error }. The rules can be installed manually by
+    running the following in a terminal:
+onboarding-permissions-copy = Copy to Clipboard
+onboarding-pipeweaver = Pipeweaver
+onboarding-pipeweaver-intro = If you have a Mix / Mix Create, the Beacn Utility can talk to Pipeweaver to show
+    and control its channels on the device. Without it, the dials control the volume of
+    applications through PipeWire instead.
+onboarding-pipeweaver-enabled = Use Pipeweaver for the Mix / Mix Create
+onboarding-pipeweaver-running = Pipeweaver is running.
+onboarding-pipeweaver-missing = Pipeweaver isn't running right now, it'll be picked up as soon as it starts.
+onboarding-tour = A Quick Tour
+onboarding-tour-devices = Your devices are listed down the left, click a device's name to flash its lights
+    and find out which one it is.
+onboarding-tour-pages = Each device has its own pages below its name, for its settings, lighting, display
+    and more.
+onboarding-tour-pin = 'Pin' shows a second device alongside the one you're looking at.
+onboarding-tour-pipeweaver = The Pipeweaver button at the top opens Pipeweaver's own mixer.
+onboarding-tour-integrations = The plug at the bottom sets up OBS Studio and control surfaces such as a
+    Stream Deck.
+onboarding-tour-settings = The gear at the bottom has the app settings, shortcuts, hooks and logs, and can
+    run this guide again.
+onboarding-tour-tray = Profiles, lighting and mute can also be changed from the tray icon.
+
+## Integrations
+integrations-heading = Integrations
+integrations-obs = OBS Studio
+integrations-obs-intro = Connects to the OBS WebSocket server (Tools -> WebSocket Server Settings),
+    so your scenes can change the Mic lighting, mute everything, and be shown on the Mix.
+integrations-obs-enabled = Connect to OBS
+integrations-obs-address = Address:
+integrations-obs-password = Password:
+integrations-obs-mute-scene = Mute Scene:
+integrations-obs-mute-scene-hover = Everything is muted while this scene is live, leave empty to never mute
+integrations-obs-disabled = Not Connected
+integrations-obs-connecting = Connecting..
+integrations-obs-connected = Connected
+integrations-obs-connected-scene = Connected, the current scene is { $scene }
+integrations-obs-failed = Unable to connect to OBS: { $error }
+integrations-obs-paused = Disconnected while saving power
+integrations-obs-colours = Scene Colours
+integrations-obs-colours-intro = The primary lighting colour to switch to when a scene goes live
+integrations-obs-remove = Remove
+integrations-obs-add = Add Scene
+integrations-obs-add-current = Add Current Scene
+integrations-companion = Control Surfaces
+integrations-companion-intro = Lets control surfaces such as a Stream Deck connect over WebSocket to mute everything,
+    switch profiles and lighting, and show the current state on their buttons.
+integrations-companion-enabled = Allow Control Surfaces to Connect
+integrations-companion-port = Port:
+integrations-companion-token = Token:
+integrations-companion-copy = Copy
+integrations-companion-regenerate = Regenerate
+integrations-companion-regenerate-hover = Anything already set up will need the new token
+integrations-companion-disabled = Not Listening
+integrations-companion-listening = Listening on localhost:{ $port }, { $clients } connected
+integrations-companion-failed = Unable to start the endpoint: { $error }
+integrations-events = Overlay Event Stream
+integrations-events-intro = A read-only WebSocket for overlays (such as an OBS browser source) to follow the mute,
+    volumes, lighting and connected devices. Nothing can be changed through it.
+integrations-events-enabled = Allow Overlays to Connect
+integrations-events-test = Open Test Page
+integrations-header-meter = Mix Display
+integrations-header-meter-intro = Shows the level of the main Pipeweaver output (the first in its order, normally
+    your headphones) in a strip along the top of the Mix and Mix Create displays.
+integrations-header-meter-enabled = Show an Output Meter in the Header
+
+## Pipeweaver
+pipeweaver-heading = Enhance your Beacn on Linux experience with Pipeweaver
+pipeweaver-intro = Pipeweaver brings streaming-focused audio control to Linux, with mixing,
+    routing, and separate personal and stream outputs.
+pipeweaver-controllers = If you have a Mix / Mix Create, the Beacn Utility will talk to
+    Pipeweaver to bring volume and mix control to your devices, similar to how you've used them
+    on Windows.
+pipeweaver-not-running = Pipeweaver isn’t running right now. If you’ve already installed it, just
+    start it up. If not, hit the button below and give it an install!
+pipeweaver-get = Get Pipeweaver
+
+## About Pages
+about-mic = About Beacn Mic
+about-studio = About Beacn Studio
+about-mix = About Beacn Mix
+about-mix-create = About Beacn Mix Create
+about-location = USB Location:
+about-serial = Serial:
+about-version = Version:
+about-port2-compliancy = Enable Port 2 Compliancy Mode
+about-mic-compliancy = Enable Mic Compliancy Mode
+about-mic-compliancy-hover = Learn more about Mic Compliancy Mode
+about-mic-compliancy-note = Note: When changing this value, the Beacn Mic will reboot.
+identity-heading = Name and Colour
+identity-nickname = Name:
+identity-accent = Accent Colour:
+identity-accent-reset = Reset
+ignore-device = Ignore This Device
+ignore-device-hover = Stops the utility opening this device, for when something else manages it. This can be undone
+    from the settings.
+ignore-heading = Ignored Devices
+ignore-none = No devices are being ignored.
+ignore-stop = Stop Ignoring
+about-resync = Resync from Device
+about-resync-hover = Re-reads every setting from the device, for if what's shown here doesn't
+    match what it's doing
+about-audit = Changes This Session
+about-audit-empty = Nothing has changed on this device yet.
+about-audit-time = Time
+about-audit-setting = Setting
+about-audit-change = Change
+about-audit-export = Export to CSV
+about-audit-exported = Saved to { $path }
+about-audit-export-failed = Unable to export the changes: { $error }
+
+## Routing
+link-compliancy = Port 2 Compliancy Mode
+link-compliancy-hover = Lets the PC2 port work without the Beacn drivers, turning off Link
+link-headphones = Link Headphone Channels
+link-headphones-hover = Keeps the left and right headphone levels together
+link-compliancy-on = The Link submixes are only available while Port 2 Compliancy Mode is off.
+link-requirements = This requires the PC2 USB port to be plugged into a Windows PC with the Beacn Link
+    app running.
+link-no-apps = No Apps playing audio detected
+link-unavailable = Unable to communicate with the Beacn Link App
+link-refresh = Refresh
+link-app = App
+link-system = System
+link-submix = Link { $number }
+
+## Configuration
+config-output-gain = Output Gain
+config-snapshot = Snapshot:
+config-snapshot-switch = Switch to Snapshot { $label }
+config-snapshot-store = Store the current settings as Snapshot { $label }
+config-snapshot-note = Changes made while a snapshot is active are kept in that snapshot
+config-apply-complete = { $name }: { $sent } changes sent, { $unchanged } already set
+config-apply-partial = { $name } stopped after { $sent } of { $total } changes
+config-tab-mic-setup = Mic Setup
+config-tab-suppressor = Noise Suppression
+config-tab-expander = Expander
+config-tab-compressor = Compressor
+config-tab-headphones = Headphones
+config-tab-headphone-eq = Headphone EQ
+config-tab-autoeq = AutoEQ
+config-tab-compare = Before / After
+config-tab-gain-wizard = Gain Wizard
+config-tab-windows-import = Windows Import
+config-phantom = +48V Phantom Power
+config-phantom-active = +48V is on
+config-phantom-confirm-title = Turn on Phantom Power?
+config-phantom-confirm-body = Phantom power sends 48 volts down the XLR cable. Condenser mics need it, but it can
+    permanently damage some ribbon mics, and older or faulty cables. Only turn it on if you know
+    your mic needs it.
+config-phantom-confirm-enable = Turn On
+config-phantom-confirm-cancel = Cancel
+config-mute-sync = Mute Sync
+config-mute-sync-muted = Mic Muted
+config-mute-sync-live = Mic Live
+config-mute-sync-unknown = Unable to find the Mic in PipeWire
+config-mute-sync-no-pipeweaver = Pipeweaver isn't running
+config-mute-sync-off = Off
+config-mute-sync-unknown-source = Unknown Source
+config-mute-sync-hover = Muting the Mic mutes this source in Pipeweaver, and back again
+
+## Noise Suppression
+suppressor-measure = Measure Room Noise
+suppressor-listening = Listening, please stay quiet..
+suppressor-listen = Listen
+suppressor-listen-hover = Records { $seconds } seconds of background noise, then suggests settings for this room
+suppressor-noise-floor = Noise Floor: { $level }dB
+suppressor-suggested = Suggested: { $amount }% Amount, { $sense }% Sensitivity
+suppressor-apply = Apply
+suppressor-measure-failed = Unable to measure: { $error }
+suppressor-profiles = Room Profiles
+suppressor-profile-name = Profile Name
+suppressor-profile-save = Save
+suppressor-profile-save-hover = Store the current settings as a new profile
+suppressor-profile-replace = Replace this profile with the current settings
+suppressor-profile-remove = Remove
+suppressor-no-profiles = No profiles saved yet
+
+## Headphones
+headphones-max-level = Max Level
+headphones-max-level-hover = Limit how loud the headphones can be set
+headphones-amp-power = Amp Power
+headphones-in-ear = In Ear Monitors
+headphones-line-level = Line Level
+headphones-normal-power = Normal Power
+headphones-high-impedance = High Impedance Mode
+headphones-meter = Monitor Meter
+headphones-meter-show = Show Level
+headphones-meter-every = Every { $millis }ms
+headphones-meter-interval-hover = How often the meter updates
+headphones-meter-failed = Unable to follow the mic: { $error }
+headphones-meter-finding = Finding the mic..
+headphones-meter-peak = Peak: { $peak }dB
+headphones-sync = Pipeweaver Sync
+headphones-sync-no-pipeweaver = Pipeweaver isn't running
+headphones-sync-off = Off
+headphones-sync-unknown = Unknown Output
+headphones-sync-hover = Turning the headphone knob changes this output's volume, and back again
+
+## Dynamics Presets
+dynamics-presets = Presets
+dynamics-broadcast = Broadcast
+dynamics-voiceover = Voiceover
+dynamics-raw = Raw
+dynamics-compressor-broadcast-hover = Firm and even, keeps your voice at the same level whatever you're doing
+dynamics-compressor-voiceover-hover = Gentler, smooths out the peaks while keeping the natural dynamics
+dynamics-compressor-raw-hover = Barely there, only catches the loudest moments
+dynamics-expander-broadcast-hover = Quickly pulls down the background between words
+dynamics-expander-voiceover-hover = Softer, lets the ends of words fade out naturally
+dynamics-expander-raw-hover = Only takes the edge off a quiet room
+dynamics-link = Link Modes
+dynamics-link-hover = Copies changes made in Simple mode over to Advanced mode
+
+## Quick Filters
+quick-filters = Quick Filters
+quick-filters-low-cut = Low Cut
+quick-filters-hum = Hum
+quick-filters-75-hover = Cuts low rumble, such as desk bumps and traffic
+quick-filters-150-hover = A stronger cut, for noisy rooms or when sitting close to the mic
+quick-filters-50-hover = Removes mains hum in Europe, Asia, Africa and Australia
+quick-filters-60-hover = Removes mains hum in North America and parts of South America and Asia
+quick-filters-advanced-only = Quick filters use the Advanced EQ, switch to it on the EQ page
+quick-filters-bands-full = All EQ bands are in use
+quick-filters-overlap = { $filter } overlaps EQ band { $bands }
+
+## AutoEQ
+autoeq-intro = Paste an AutoEQ ParametricEQ.txt, or load one from disk:
+autoeq-path-hint = /path/to/ParametricEQ.txt
+autoeq-load = Load
+autoeq-read-failed = Unable to read file: { $error }
+autoeq-nothing-imported = Nothing imported yet
+autoeq-summary = { $filters } filters, Preamp { $preamp }dB
+autoeq-bass = Bass: { $value }
+autoeq-mids = Mids: { $value }
+autoeq-treble = Treble: { $value }
+autoeq-fit-error = Fit Error: { $error }dB
+autoeq-fit-error-hover = The average difference between the profile and what the EQ can achieve
+autoeq-apply = Apply to Headphones
+autoeq-fx-disabled = Headphone FX are currently disabled
+autoeq-legend-profile = Profile
+autoeq-legend-eq = Headphone EQ
+
+## Gain Wizard
+gain-wizard-prepare = Prepare
+gain-wizard-speak = Speak
+gain-wizard-review = Review
+gain-wizard-position = Sit where you normally would, at your usual distance from the mic.
+gain-wizard-talk = When you're ready, talk for { $seconds } seconds at the volume you'd normally use.
+gain-wizard-start = Start
+gain-wizard-finding = Finding the mic..
+gain-wizard-keep-talking = Keep talking normally..
+gain-wizard-levels = Speech: { $speech }dB, Loudest: { $peak }dB, Background: { $noise }dB
+gain-wizard-gain = Mic Gain: { $gain }dB (currently { $current }dB)
+gain-wizard-expander = Expander Threshold: { $threshold }dB
+gain-wizard-compressor = Compressor Threshold: { $threshold }dB
+gain-wizard-apply = Apply
+gain-wizard-restart = Start Again
+gain-wizard-applied = Applied, the Mic Setup, Expander and Compressor tabs have been updated.
+gain-wizard-failed = Unable to measure the mic: { $error }
+
+## Before / After
+compare-intro = Records { $seconds } seconds with the mic processing turned off, then { $seconds } seconds
+    with your current settings.
+compare-record = Record Comparison
+compare-recording = Recording: { $clip }
+compare-keep-speaking = Keep speaking normally, the processing will be restored afterwards.
+compare-before = Before (No Processing)
+compare-after = After (Current Settings)
+compare-play = Play
+compare-stop = Stop
+compare-stopped = Recording stopped
+compare-failed = Comparison Failed: { $error }
+
+## Windows Import
+import-intro = Select a preset exported from the Beacn app on Windows:
+import-path-hint = /path/to/preset.json
+import-browse = Browse…
+import-load = Load
+import-nothing-loaded = Nothing loaded yet
+import-summary = { $changed } settings will change, { $unchanged } are already the same
+import-setting = Setting
+import-current = Current
+import-imported = Imported
+import-clamped = The file has { $value }, which is outside what the device accepts
+import-skipped = { $count } values not recognised
+import-apply = Apply
+import-on = On
+import-off = Off
+import-chooser-title = Import Beacn Preset
+import-chooser-filter = Beacn Presets
+import-chooser-closed = File chooser closed
+import-chooser-failed = Unable to open file chooser: { $error }
+
+## Display and Buttons
+display-screen-brightness = Screen Brightness:
+display-button-brightness = Button Brightness:
+display-dim = Dim Screen After:
+display-orientation = Screen Orientation:
+display-screen-enabled = Screen Enabled:
+display-screen-enabled-text = Turn the screen on (the dials and buttons work either way)
+display-smooth = Smooth Updates:
+display-smooth-text = Combine screen updates to reduce tearing (adds slight latency)
+display-performance = Performance Mode:
+display-performance-text = Send fewer, lower quality screen updates, for slow USB hubs
+display-burn-in = Burn-in Protection:
+display-burn-in-text = Move the screen slightly every so often, and fade the header when idle
+display-burn-in-shift = Move Screen Every:
+display-burn-in-fade = Fade Header After:
+display-keep-alive = Keepalive:
+display-keep-alive-text = Keep the device awake while the utility is running
+display-keep-alive-interval = Send Every:
+display-keep-alive-sleep = Sleep After Idle:
+display-header = Header ({ $slot }):
+display-backend = Dial Integration:
+display-backend-pipewire = Dials control the volume of applications currently playing audio, requires pw-dump
+    and wpctl.
+display-backend-demo = Cycles through sample channels without controlling any audio, useful for testing the
+    display.
+display-saved = These settings are saved per device, and restored when it's reconnected.
+display-button = { $button } Button:
+display-button-reset = Reset
+display-button-default = Integration Default
+display-button-note = A button's colour is only used while it's lit, buttons which are off stay off.
+display-pipeweaver-address = Pipeweaver Address:
+display-pipeweaver-apply = Apply
+display-pipeweaver-search = Search Network
+display-pipeweaver-searching = Searching the network...
+display-pipeweaver-none = No Pipeweaver instances found, the address can be entered manually.
+display-pipeweaver-use = Use
+display-pipeweaver-failed = Network search failed: { $error }
+display-never = Never
+display-seconds = { $count } Seconds
+display-minute = 1 Minute
+display-minutes = { $count } Minutes
+display-hour = 1 Hour
+display-hours = { $count } Hours
+
+## Button Lighting
+lighting-pipeweaver = Pipeweaver is managing the button lights, these colours are used when it isn't.
+lighting-button = { $button } Button
+lighting-reset = Reset
+lighting-default = Integration Default
+lighting-all-off = Turn All Off
+
+## Dial Assignment
+dials-pipeweaver-only = Dial assignment is only available when using the Pipeweaver integration.
+dials-follow-order = Follow the Pipeweaver channel order
+dials-follow-order-note = Dials show any pinned channels, followed by the current page of channels.
+dials-drag = Drag a channel onto a dial to assign it, or between dials to swap them:
+dials-dial = Dial { $number }
+dials-empty = Empty
+dials-unknown = Unknown Channel
+dials-waiting = Waiting for channels from Pipeweaver..
+dials-assignment-note = While an assignment is active, the page buttons only apply to outputs.
+dials-colours = Channel Colours
+dials-colours-intro = Colours set here replace the Pipeweaver colour on the screen and dial buttons.
+dials-inputs = Inputs
+dials-outputs = Outputs
+dials-reset = Reset
+dials-volume = Volume Display
+dials-volume-intro = How the volume is written on each channel's dial, decibels follow Pipeweaver.
+dials-layout = Screen Layout
+dials-layout-intro = The compact layout fits twice as many channels on a page, with smaller dials.
+dials-layout-note = The dials control the top row of channels.
+
+## Dial Gestures
+gestures-unavailable = Dial gestures aren't available with the Demo integration.
+gestures-default-volume = Default Volume
+gestures-crossfade = Mix Crossfade
+gestures-crossfade-hover = Holding the Mix button fades what you hear over to the levels of Mix B, and back
+    again when it's released. Set to 0 to disable.
+gestures-curve = Volume Curve
+gestures-step = Step Size
+gestures-step-hover = How far the volume moves for each click of a dial.
+gestures-max-speed = Maximum Speed
+gestures-max-speed-hover = The most a fast spin can multiply the step size by.
+gestures-fine = Fine Mode
+gestures-fine-text = Hold a dial's button while turning it for 1% steps
+gestures-note = Gestures apply to all four dials, and act on the channel shown on that dial.
+gestures-pipewire-note = Applications only have one volume, so the audience mute, mix and channel switching
+    actions do nothing here.
+gestures-double-press-note = Single presses are slightly delayed while a double press is assigned.
+gestures-reset = Reset to Defaults
+
+## Screen Image
+image-hint = Drag the image to move it, and scroll over it to change its size.
+image-browse = Browse…
+image-drop = Drop a JPEG or PNG here
+image-size = { $width }x{ $height }, shown at { $shown_width }x{ $shown_height }
+image-scale = Scale:
+image-position = Position:
+image-fit = Fit
+image-fill = Fill
+image-centre = Centre
+image-send = Send to Screen
+image-sent = Sent a { $width }x{ $height } image to the screen
+image-send-failed = Unable to send the image: { $error }
+image-note = The image stays until the display integration redraws that part of the screen.
+image-chooser-title = Send Image to Screen
+image-chooser-filter = Images
+image-chooser-closed = File chooser closed
+image-chooser-failed = Unable to open file chooser: { $error }
+image-read-failed = Unable to read { $path }: { $error }
+image-unknown-format = Not a JPEG or PNG image
+image-unsupported-format = Only JPEG and PNG images are supported
+image-too-large = The image is too large ({ $width }x{ $height }), the limit is { $limit }px
+image-too-small = The image is too small to display
+image-off-screen = The image isn't on the screen
+
+## Connection Statistics
+stats-reconnect = Reconnect
+stats-reconnect-hover = Closes the device and opens it again, without unplugging it
+
+## Session Statistics
+session-collect = Collect Session Statistics
+session-collect-hover = Nothing is kept once the utility closes, export it to keep it
+session-disabled = Turn this on to track talk time, dial use and volumes from now on.
+session-length = Session Length:
+session-talk-time = Talk Time:
+session-muted-time = Muted Time:
+session-mutes = Times Muted:
+session-dials = Dial Activity
+session-no-dials = No dials have been moved yet.
+session-dial = Dial
+session-moves = Moves
+session-steps = Steps
+session-volumes = Volume History
+session-no-volumes = No volume changes yet
+session-export = Export to CSV
+session-reset = Start New Session
+session-exported = Saved to { $path }
+session-export-failed = Unable to export the session: { $error }
+
+## Permission Diagnostics
+permissions-heading = Permission Diagnostics
+permissions-back = Back
+permissions-rules = udev Rules
+permissions-rules-found = Found in { $path }
+permissions-rules-sandboxed = Unable to check from inside the Flatpak
+permissions-rules-missing = Not found
+permissions-node = Device Access
+permissions-node-missing = { $node } (missing)
+permissions-group = Device Group
+permissions-group-member = You are a member of '{ $group }'
+permissions-group-not-member = The device is owned by '{ $group }', which you are not a member of
+permissions-accessible = The device can now be accessed, use 'Retry Device' to open it.
+permissions-flatpak = The Beacn Utility is running inside a Flatpak, so the udev rules need to be installed
+    on the host system. Run the following in a terminal:
+permissions-still-denied = The rules are installed, but the device still can't be opened. Try unplugging the
+    device and plugging it back in, or logging out and back in to refresh your session.
+permissions-install-intro = No udev rules were found for Beacn devices, these are needed to allow the Beacn
+    Utility to access them without running as root.
+permissions-install = Install Rules
+permissions-installing = Installing, you may be prompted for your password...
+permissions-installed = Rules installed, use 'Retry Device' to try opening the device again.
+permissions-install-failed = Installation failed: { $error }
+permissions-manual = You can install the rules manually by running the following in a terminal:
+permissions-copy = Copy to Clipboard
+permissions-check = Check Again
+permissions-retry = Retry Device
+
+## Display Preview
+preview-waiting = Waiting for the display..
+preview-note = This shows exactly what's on the device's screen, and updates as it changes.
+
+## Input Monitor
+interactions-intro = Button presses and dial movements from the device, newest first, as they arrive.
+interactions-waiting = Press a button or turn a dial on the device..
+interactions-pause = Pause
+interactions-clear = Clear
+interactions-time = Time
+interactions-input = Input
+interactions-event = Event
+interactions-pressed = Pressed
+interactions-released = Released
+
+## Developer Console
+developer-warning = Messages are sent to the device as-is, without any validation. Use with care!
+developer-template = Load Template..
+developer-enabled = Enabled
+developer-send = Send
+developer-clear = Clear History
+developer-ok = Ok
+developer-invalid = Invalid Message: { $error }
+developer-raw-sent = Sent: { $bytes }
+developer-raw-received = Received: { $bytes }
+developer-empty = Nothing has been sent yet.
+developer-show-raw = Show Raw
+developer-edit = Edit
+
+## Tray
+tray-description = A Tool for Configuring Beacn Devices
+tray-show = Show
+tray-noise-profiles = Noise Profiles
+tray-lighting = Lighting
+tray-timer = Stream Timer
+tray-timer-toggle = Start / Stop
+tray-timer-reset = Reset
+tray-panic-mute = Mute Everything
+tray-panic-muted = Everything is Muted
+tray-device-error = Something went wrong with { $devices }
+tray-no-devices = No devices are connected
+tray-quit = Quit
@@ -1,23 +1,52 @@
 /*
   This file primarily manages hot-plugging opening devices, and device messaging.
 
-  When a device appears, we open it, create a message handler and throw it upstream. We then
-  listen on all the message handlers, and when one pops up, handle the message.
+  When a device appears, we open it, create a message handler and throw it upstream. Each open
+  device is then handed to its own worker thread, which listens on that device's handler, so a
+  slow transfer to one device (such as an image to a Mix) doesn't hold up messages to another.
+  The manager itself only looks after the lifecycle, telling the workers when to sleep, wake,
+  save power or stop.
 
-  If a device disappears, we simply drop its channel, upstream should pick up on that and
-  handle it appropriately.
+  If a device disappears, we simply drop its worker, which drops its channel. Upstream should
+  pick up on that and handle it appropriately.
+
+  Devices the user has chosen to ignore (by serial) are never handed upstream. Where possible
+  they're identified before being opened, so we don't touch them at all, otherwise they're closed
+  again as soon as their serial has been read. Either way they're remembered until they go away,
+  so they can be opened if the user changes their mind.
 
   For the moment, for the Beacn Mic + Beacn Studio we're going to have a single message type,
   same applies for the Mix and Mix Create. The devices are too similar to have to worry about
   differences.
 */
-use crate::integrations::pipeweaver::spawn_pipeweaver_handler;
+use crate::diagnostics::ReprobeKind;
+use crate::hooks::{self, HookEvent};
+use crate::integrations::burn_in::BurnInSettings;
+use crate::integrations::events::{self, Event};
+use crate::integrations::gestures::DialGestures;
+use crate::integrations::header_widgets::HeaderWidgets;
+use crate::integrations::keep_alive::KeepAliveSettings;
+use crate::integrations::orientation::Orientation;
+use crate::integrations::pipeweaver::jpeg_as_img;
+use crate::integrations::pipeweaver::layout::{BG_COLOUR, DISPLAY_DIMENSIONS, ScreenLayout};
+use crate::integrations::{
+    AssignableChannel, ButtonColours, ColourOverrides, DialAssignment, DisplayBackend,
+    DisplayHandles, StaticButton, StaticLighting, VolumeUnits, spawn_display_handler,
+};
 use crate::managers::login::{LoginEventTriggers, spawn_login_handler};
-use crate::{ManagerMessages, ToMainMessages, runtime};
+use crate::managers::power::{PowerEventTriggers, spawn_power_handler};
+use crate::managers::schedule::{self, ScheduleTime};
+use crate::stats::track;
+use crate::{
+    ManagerMessages, ToMainMessages, devices, diagnostics, runtime, safe_mode, settings, stats,
+    storage,
+};
 use anyhow::anyhow;
 use beacn_lib::audio::messages::Message;
-use beacn_lib::audio::{BeacnAudioDevice, LinkedApp, open_audio_device};
-use beacn_lib::controller::{BeacnControlDevice, ButtonLighting, open_control_device};
+use beacn_lib::audio::{BeacnAudioDevice, LinkedApp};
+use beacn_lib::controller::{
+    BeacnControlDevice, ButtonLighting, ButtonState, Buttons, Dials, Interactions,
+};
 use beacn_lib::crossbeam::channel;
 use beacn_lib::crossbeam::channel::internal::SelectHandle;
 use beacn_lib::crossbeam::channel::{Receiver, Select, Sender};
@@ -27,15 +56,68 @@ use beacn_lib::manager::{
 use beacn_lib::types::RGBA;
 use beacn_lib::version::VersionNumber;
 use beacn_lib::{BeacnError, UsbError};
-use log::{debug, error};
-use std::collections::HashMap;
+use chrono::{DateTime, Local};
+use image::imageops::replace;
+use image::{ImageBuffer, RgbaImage};
+use log::{debug, error, warn};
+use serde::Deserialize;
 use std::panic::catch_unwind;
+use std::sync::{LazyLock, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
 use strum_macros::Display;
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
-use tokio::time::sleep;
+
+// When saving power, the display won't be brighter than this
+const POWER_SAVE_BRIGHTNESS: u8 = 10;
+
+// If the UI stops asking for a preview for this long, we stop building it
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Newly attached devices are left this long before being opened, in case they vanish again
+const SETTLE_TIME: Duration = Duration::from_millis(750);
+
+// A device attaching this many times within the window is considered to be flapping, and won't
+// be opened again until it's stayed connected for the cooldown
+const FLAP_THRESHOLD: usize = 4;
+const FLAP_WINDOW: Duration = Duration::from_secs(30);
+const FLAP_COOLDOWN: Duration = Duration::from_secs(30);
+
+// Devices which are busy (normally claimed by something else) are retried, starting after the
+// first delay and doubling each time up to the maximum, until they open or are removed
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+// How long to wait for the display handlers to draw their final screen when shutting down
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Devices which are attached, but being left alone because the user asked us to
+static IGNORED: LazyLock<Mutex<Vec<IgnoredDevice>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Clone)]
+pub struct IgnoredDevice {
+    pub location: DeviceLocation,
+    pub device_type: DeviceType,
+    pub serial: String,
+}
+
+/// The ignored devices which are currently attached
+pub fn ignored_devices() -> Vec<IgnoredDevice> {
+    IGNORED.lock().map(|i| i.clone()).unwrap_or_default()
+}
+
+fn is_ignored(serial: &str) -> bool {
+    settings::get().ignored_devices.iter().any(|s| s == serial)
+}
+
+fn forget_ignored(location: DeviceLocation) {
+    if let Ok(mut ignored) = IGNORED.lock() {
+        ignored.retain(|device| device.location != location);
+    }
+}
+
 //const TEMP_SPLASH: &[u8] = include_bytes!("../resources/screens/beacn-splash.jpg");
 
 pub fn spawn_device_manager(
@@ -47,15 +129,35 @@ pub fn spawn_device_manager(
     let (manage_tx, manage_rx) = channel::unbounded();
     let (login_tx, login_rx) = channel::bounded(5);
     let (login_stop_tx, login_stop_rx) = tokio::sync::mpsc::channel(1);
+    let (power_tx, power_rx) = channel::bounded(5);
+    let (power_stop_tx, power_stop_rx) = tokio::sync::mpsc::channel(1);
 
-    // We need a hashmap that'll map a receiver to an object
-    let mut receiver_map: Vec<DeviceMap> = vec![];
+    // The worker for each device that's currently open
+    let mut workers: Vec<DeviceWorker> = vec![];
 
+    // Anything the hotplug handler won't find goes through the same path as a real device
+    for (location, device_type) in devices::factory().simulated_devices() {
+        let (health_tx, _) = channel::bounded(1);
+        let _ = plug_tx.send(HotPlugMessage::DeviceAttached(
+            location,
+            device_type,
+            health_tx,
+        ));
+    }
     spawn_hotplug_handler(plug_tx, manage_rx).expect("Failed to Spawn HotPlug Handler");
     thread::spawn(|| spawn_login_handler(login_tx, login_stop_rx));
 
+    // Not every system runs UPower, so this handler may stop early. We hold onto a sender
+    // to stop the channel disconnecting, which would otherwise wake the selector forever.
+    let power_handler_tx = power_tx.clone();
+    thread::spawn(|| spawn_power_handler(power_handler_tx, power_stop_rx));
+
     let mut suspended = false;
+    let mut power_saving = false;
     let mut pending_attachments: Vec<(DeviceLocation, DeviceType, Sender<()>)> = vec![];
+    let mut hotplug_guard = HotplugGuard::default();
+    let mut open_retry = OpenRetry::default();
+    let sleep_ticker = channel::tick(schedule::CHECK_INTERVAL);
 
     loop {
         let mut selector = Select::new();
@@ -68,22 +170,72 @@ pub fn spawn_device_manager(
         // Add the Lock Detector
         let lock_index = selector.recv(&login_rx);
 
+        // And the Battery Monitor
+        let power_index = selector.recv(&power_rx);
+
         // Next, the hotplug receiver
         let hotplug_index = selector.recv(&plug_rx);
 
-        // Finally, we'll follow up with the 'known' devices, we'll map the crossbeam index with
-        // their index in the receiver_map.
-        let mut device_indices: HashMap<usize, usize> = HashMap::new();
-        for (i, device) in receiver_map.iter().enumerate() {
-            let index = match device {
-                DeviceMap::Audio(_, _, rx) => selector.recv(rx),
-                DeviceMap::Control(_, _, rx, _, _, _) => selector.recv(rx),
-            };
-            device_indices.insert(index, i);
-        }
+        // Requests to retry opening a device (normally after fixing permissions)
+        let reprobe_rx = diagnostics::reprobe_receiver();
+        let reprobe_index = selector.recv(reprobe_rx);
 
-        // Run the Selector
-        let operation = selector.select();
+        // The screens' sleep schedule
+        let sleep_index = selector.recv(&sleep_ticker);
+
+        // Run the Selector, waking up if a settling or busy device is due to be opened
+        let deadline = [hotplug_guard.next_deadline(), open_retry.next_deadline()];
+        let operation = match deadline.into_iter().flatten().min() {
+            None => selector.select(),
+            Some(deadline) => match selector.select_deadline(deadline) {
+                Ok(operation) => operation,
+                Err(_) => {
+                    let (ready, settled) = hotplug_guard.take_ready();
+                    for device_type in settled {
+                        debug!("{device_type:?} has stopped flapping");
+                        let _ = event_tx.send(DeviceMessage::Flapping(device_type, false));
+                    }
+                    for (location, device_type, health_tx) in ready {
+                        if suspended {
+                            pending_attachments.push((location, device_type, health_tx));
+                        } else {
+                            let state = handle_device_attached(
+                                location,
+                                device_type,
+                                &health_tx,
+                                &mut workers,
+                                &event_tx,
+                                &self_tx,
+                                power_saving,
+                            );
+                            open_retry.opened(location, device_type, health_tx, &state);
+                        }
+                    }
+                    for (location, device_type, health_tx) in open_retry.take_ready() {
+                        debug!("Retrying busy {device_type:?}");
+                        if suspended {
+                            pending_attachments.push((location, device_type, health_tx));
+                            continue;
+                        }
+
+                        // Clear out the failed device before it comes back
+                        let _ = event_tx.send(DeviceMessage::DeviceRemoved(location));
+                        let state = handle_device_attached(
+                            location,
+                            device_type,
+                            &health_tx,
+                            &mut workers,
+                            &event_tx,
+                            &self_tx,
+                            power_saving,
+                        );
+                        open_retry.opened(location, device_type, health_tx, &state);
+                    }
+                    let _ = self_tx.send(ToMainMessages::RequestRedraw);
+                    continue;
+                }
+            },
+        };
 
         // Ok, something's triggered us in some way, find out what.
         match operation.index() {
@@ -91,6 +243,13 @@ pub fn spawn_device_manager(
                 if let Ok(msg) = operation.recv(&self_rx) {
                     match msg {
                         ManagerMessages::Quit => break,
+                        ManagerMessages::AnnounceDevices => {
+                            announce_devices(&workers, &event_tx, &self_tx);
+                        }
+                        ManagerMessages::TrayDevices(_) => {}
+                        ManagerMessages::DeviceHealth(_) => {}
+                        ManagerMessages::LanguageChanged => {}
+                        ManagerMessages::PanicMute(_) => {}
                     }
                 }
             }
@@ -101,35 +260,48 @@ pub fn spawn_device_manager(
                     match msg {
                         LoginEventTriggers::Sleep(tx) => {
                             suspended = true;
-                            set_pipeweaver_draw_suspended(&receiver_map, true);
-                            enable_devices(&receiver_map, false);
+                            set_pipeweaver_draw_suspended(&workers, true);
+                            enable_devices(&workers, false);
                             let _ = tx.send(());
                         }
                         LoginEventTriggers::Wake(tx) => {
                             suspended = false;
                             for (location, device_type, health_tx) in pending_attachments.drain(..)
                             {
-                                handle_device_attached(
+                                let state = handle_device_attached(
                                     location,
                                     device_type,
-                                    health_tx,
-                                    &mut receiver_map,
+                                    &health_tx,
+                                    &mut workers,
                                     &event_tx,
                                     &self_tx,
+                                    power_saving,
                                 );
+                                open_retry.opened(location, device_type, health_tx, &state);
                             }
 
-                            set_pipeweaver_draw_suspended(&receiver_map, false);
-                            enable_devices(&receiver_map, true);
+                            set_pipeweaver_draw_suspended(&workers, false);
+                            enable_devices(&workers, true);
                             let _ = tx.send(());
                         }
                         LoginEventTriggers::Lock => {
-                            set_pipeweaver_draw_suspended(&receiver_map, true);
-                            enable_devices(&receiver_map, false);
+                            set_pipeweaver_draw_suspended(&workers, true);
+                            enable_devices(&workers, false);
                         }
                         LoginEventTriggers::Unlock => {
-                            set_pipeweaver_draw_suspended(&receiver_map, false);
-                            enable_devices(&receiver_map, true);
+                            set_pipeweaver_draw_suspended(&workers, false);
+                            enable_devices(&workers, true);
+                        }
+                    }
+                }
+            }
+            i if i == power_index => {
+                if let Ok(msg) = operation.recv(&power_rx) {
+                    debug!("Received Power State Message: {msg:?}");
+                    match msg {
+                        PowerEventTriggers::PowerSaving(saving) => {
+                            power_saving = saving;
+                            set_power_saving(&workers, saving);
                         }
                     }
                 }
@@ -137,28 +309,30 @@ pub fn spawn_device_manager(
             i if i == hotplug_index => match operation.recv(&plug_rx) {
                 Ok(m) => match m {
                     HotPlugMessage::DeviceAttached(location, device_type, health_tx) => {
-                        if suspended {
-                            pending_attachments.push((location, device_type, health_tx));
-                        } else {
-                            handle_device_attached(
-                                location,
-                                device_type,
-                                health_tx,
-                                &mut receiver_map,
-                                &event_tx,
-                                &self_tx,
-                            );
+                        // This will be opened once it's settled
+                        if hotplug_guard.attached(location, device_type, health_tx) {
+                            warn!("{device_type:?} is repeatedly reconnecting, holding it back");
+                            let _ = event_tx.send(DeviceMessage::Flapping(device_type, true));
+                            let _ = self_tx.send(ToMainMessages::RequestRedraw);
                         }
                     }
                     HotPlugMessage::DeviceRemoved(location) => {
                         // Drop any pending attachment for this location before it's ever opened
                         pending_attachments.retain(|(loc, _, _)| *loc != location);
+                        hotplug_guard.removed(location);
+                        open_retry.removed(location);
+                        forget_ignored(location);
 
                         let _ = event_tx.send(DeviceMessage::DeviceRemoved(location));
-                        receiver_map.retain(|e| match e {
-                            DeviceMap::Audio(_, d, _) => d.location != location,
-                            DeviceMap::Control(_, d, _, _, _, _) => d.location != location,
-                        });
+                        let (removed, remaining) = workers
+                            .into_iter()
+                            .partition(|w| w.definition.location == location);
+                        workers = remaining;
+
+                        for worker in removed {
+                            fire_device_hook(HookEvent::DeviceRemoved, &worker.definition);
+                            worker.stop();
+                        }
 
                         let _ = self_tx.send(ToMainMessages::RequestRedraw);
                     }
@@ -166,134 +340,636 @@ pub fn spawn_device_manager(
                 },
                 Err(_) => break,
             },
-            i => {
-                // Find the specific device for this index
-                #[allow(clippy::collapsible_if)]
-                if let Some(device) = device_indices.get(&i) {
-                    if let Some(device) = receiver_map.get(*device) {
-                        match device {
-                            DeviceMap::Audio(dev, _, rx) => {
-                                if let Ok(msg) = operation.recv(rx) {
-                                    match msg {
-                                        AudioMessage::Handle(msg, resp) => {
-                                            let response = catch_unwind(|| dev.handle_message(msg));
-                                            if let Err(panic) = response {
-                                                // Downcast this to a standard error
-                                                let error = panic
-                                                    .downcast_ref::<String>()
-                                                    .cloned()
-                                                    .unwrap_or(String::from("Unknown Error"));
-                                                let _ = resp.send(Err(anyhow!(error).into()));
-                                            } else {
-                                                // Send back the original response
-                                                let _ = resp.send(response.unwrap());
-                                            }
-                                        }
-                                        AudioMessage::Linked(command) => {
-                                            // This code doesn't panic, just fails.
-                                            match command {
-                                                LinkedCommands::GetLinked(tx) => {
-                                                    let _ = tx.send(dev.get_linked_app_list());
-                                                }
-                                                LinkedCommands::SetLinked(app, tx) => {
-                                                    let _ = tx.send(dev.set_linked_app(app));
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            DeviceMap::Control(dev, _, rx, _, _, _) => {
-                                if let Ok(msg) = operation.recv(rx) {
-                                    match msg {
-                                        ControlMessage::SendImage(img, x, y, tx) => {
-                                            let _ = tx.send(dev.set_image(x, y, &img));
-                                        }
-                                        ControlMessage::DisplayBrightness(brightness, tx) => {
-                                            let _ = tx.send(dev.set_display_brightness(brightness));
-                                        }
-                                        ControlMessage::ButtonBrightness(brightness, tx) => {
-                                            let _ = tx.send(dev.set_button_brightness(brightness));
-                                        }
-                                        ControlMessage::DimTimeout(timeout, tx) => {
-                                            let _ = tx.send(dev.set_dim_timeout(timeout));
-                                        }
-                                        ControlMessage::ButtonColour(button, colour, tx) => {
-                                            let _ = tx.send(dev.set_button_colour(button, colour));
-                                        }
-                                        ControlMessage::Enabled(enabled, tx) => {
-                                            let _ = tx.send(dev.set_enabled(enabled));
-                                        }
-                                        ControlMessage::KeepAlive(tx) => {
-                                            let _ = tx.send(dev.send_keepalive());
-                                        }
-                                    };
-                                }
-                            }
+            i if i == reprobe_index => {
+                if let Ok((location, device_type, kind)) = operation.recv(reprobe_rx) {
+                    // A busy device is already being retried, so just bring the attempt forward
+                    if kind == ReprobeKind::RetryBusy && open_retry.retry_now(location) {
+                        debug!("Retrying busy {device_type:?} now");
+                        continue;
+                    }
+
+                    // A reconnect drops the device first, so it's opened again from scratch. The
+                    // worker has to have let go of it before it can be claimed again, so wait
+                    // for it to stop.
+                    if kind == ReprobeKind::Reconnect {
+                        debug!("Reconnecting {device_type:?}");
+                        let (stopping, remaining) = workers
+                            .into_iter()
+                            .partition(|w| w.definition.location == location);
+                        workers = remaining;
+
+                        for worker in stopping {
+                            worker.stop();
+                        }
+                    }
+
+                    let is_open = workers.iter().any(|w| w.definition.location == location);
+
+                    // Only devices which failed to open need another go
+                    if !is_open {
+                        debug!("Attempting to reopen device");
+
+                        // Clear out the failed device before it comes back
+                        let _ = event_tx.send(DeviceMessage::DeviceRemoved(location));
+
+                        // The hotplug handler isn't watching this one, but will still tell us
+                        // when the device goes away.
+                        let (health_tx, _) = channel::bounded(1);
+                        if suspended {
+                            pending_attachments.push((location, device_type, health_tx));
+                        } else {
+                            let state = handle_device_attached(
+                                location,
+                                device_type,
+                                &health_tx,
+                                &mut workers,
+                                &event_tx,
+                                &self_tx,
+                                power_saving,
+                            );
+                            open_retry.opened(location, device_type, health_tx, &state);
                         }
                     }
                 }
             }
+            i if i == sleep_index => {
+                if operation.recv(&sleep_ticker).is_ok() {
+                    let asleep = settings::get().display_sleep.is_asleep(ScheduleTime::now());
+                    set_display_asleep(&mut workers, asleep);
+                }
+            }
+            _ => {}
         }
     }
 
-    // Stop the dbus login handler
+    // Stop the dbus login and power handlers
     let _ = login_stop_tx.blocking_send(());
+    let _ = power_stop_tx.try_send(());
 
-    // Stop any control devices which may be active
-    for device in receiver_map.iter_mut() {
-        if let DeviceMap::Control(_, _, _, stop, _, _) = device {
-            let _ = stop.send(());
+    // Tell every worker to stop first, so they all tidy up their devices at the same time
+    for worker in &workers {
+        let _ = worker.commands.send(WorkerCommand::Shutdown);
+    }
+    for worker in workers {
+        if worker.thread.join().is_err() {
+            warn!(
+                "Worker for {} panicked",
+                worker.definition.device_info.serial
+            );
         }
     }
 
-    // Drain the devices until they're finished.
-    runtime().block_on(async {
-        loop {
-            let all_done = receiver_map.iter().all(|d| match d {
-                DeviceMap::Control(_, _, _, _, _, task) => task.is_finished(),
-                _ => true,
-            });
-            if all_done {
-                break;
+    // For some reason, we're stopping. If the manager channel is still open, tell it to stop.
+    if manage_tx.is_ready() {
+        let _ = manage_tx.send(HotPlugThreadManagement::Quit);
+    }
+
+    debug!("Device Manager Stopped");
+}
+
+// The manager's handle on an open device, whose messages are handled by its own worker thread
+struct DeviceWorker {
+    definition: DeviceDefinition,
+    commands: Sender<WorkerCommand>,
+    thread: thread::JoinHandle<()>,
+
+    // Whether the sleep schedule has put the screen to sleep, new devices catch up on the
+    // next check
+    display_asleep: bool,
+}
+
+impl DeviceWorker {
+    fn spawn(device: OpenDevice) -> Self {
+        let definition = device.definition().clone();
+        let (commands, command_rx) = channel::unbounded();
+        let thread = thread::spawn(move || match device {
+            OpenDevice::Audio(dev, def, rx) => run_audio_worker(dev, def, rx, command_rx),
+            OpenDevice::Control(dev, def, rx, display) => {
+                run_control_worker(dev, def, rx, display, command_rx)
             }
+        });
 
-            for device in receiver_map.iter_mut() {
-                if let DeviceMap::Control(dev, _, rx, _, _, _) = device {
-                    match rx.try_recv() {
-                        Ok(ControlMessage::SendImage(img, x, y, tx)) => {
-                            let _ = tx.send(dev.set_image(x, y, &img));
-                        }
-                        Ok(ControlMessage::ButtonColour(button, colour, tx)) => {
-                            let _ = tx.send(dev.set_button_colour(button, colour));
-                        }
-                        _ => {}
+        Self {
+            definition,
+            commands,
+            thread,
+            display_asleep: false,
+        }
+    }
+
+    // Waits for the worker to let go of the device, so it can be claimed again straight away
+    fn stop(self) {
+        let DeviceWorker {
+            definition,
+            commands,
+            thread,
+            ..
+        } = self;
+        stats::record_disconnect(&definition.device_info.serial);
+
+        // Dropping the sender stops the worker, which drops the device
+        drop(commands);
+        if thread.join().is_err() {
+            warn!("Worker for {} panicked", definition.device_info.serial);
+        }
+    }
+}
+
+// Lifecycle changes from the manager, everything else comes straight from the UI. If the manager
+// drops the worker (because the device has gone), the worker stops and drops the device.
+enum WorkerCommand {
+    /// Re-sends the device to a rebuilt UI
+    Announce(oneshot::Sender<DeviceArriveMessage>),
+
+    /// Turns the device on or off, replying once it's done
+    SetEnabled(bool, oneshot::Sender<()>),
+    PowerSaving(bool),
+    Suspended(bool),
+
+    /// Turns the screen off (or back on) on the sleep schedule
+    DisplaySleep(bool),
+
+    /// Lets the display handler finish, and leaves the device tidy before stopping
+    Shutdown,
+}
+
+fn run_audio_worker(
+    dev: Box<dyn BeacnAudioDevice>,
+    def: DeviceDefinition,
+    mut rx: Receiver<AudioMessage>,
+    commands: Receiver<WorkerCommand>,
+) {
+    let serial = def.device_info.serial.as_str();
+    loop {
+        // The receiver is swapped out when the device is announced again, so select on a copy
+        let messages = rx.clone();
+        let mut selector = Select::new();
+        let command_index = selector.recv(&commands);
+        let message_index = selector.recv(&messages);
+
+        let operation = selector.select();
+        match operation.index() {
+            i if i == command_index => match operation.recv(&commands) {
+                Ok(WorkerCommand::Announce(tx)) => {
+                    // The old UI took the only sender with it, so the new one gets a fresh channel
+                    let (sender, new_rx) = channel::unbounded();
+                    rx = new_rx;
+                    let _ = tx.send(DeviceArriveMessage::Audio(def.clone(), sender));
+                }
+                Ok(WorkerCommand::SetEnabled(_, tx)) => {
+                    let _ = tx.send(());
+                }
+                Ok(WorkerCommand::PowerSaving(_))
+                | Ok(WorkerCommand::Suspended(_))
+                | Ok(WorkerCommand::DisplaySleep(_)) => {}
+                Ok(WorkerCommand::Shutdown) => {
+                    drain_audio(&*dev, &rx);
+                    break;
+                }
+                Err(_) => break,
+            },
+            i if i == message_index => match operation.recv(&messages) {
+                Ok(msg) => handle_audio_message(&*dev, serial, msg),
+
+                // The UI has gone, so wait to be announced to the next one
+                Err(_) => rx = channel::never(),
+            },
+            _ => {}
+        }
+    }
+    debug!("Worker for {serial} Stopped");
+}
+
+fn handle_audio_message(dev: &dyn BeacnAudioDevice, serial: &str, msg: AudioMessage) {
+    match msg {
+        AudioMessage::Handle(msg, resp) => {
+            let response = catch_unwind(|| dev.handle_message(msg));
+            if let Err(panic) = response {
+                stats::record_error(serial);
+
+                // Downcast this to a standard error
+                let error = panic
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .unwrap_or(String::from("Unknown Error"));
+                let _ = resp.send(Err(anyhow!(error).into()));
+            } else {
+                // Send back the original response
+                let _ = resp.send(track(serial, response.unwrap()));
+            }
+        }
+        AudioMessage::Linked(command) => {
+            // This code doesn't panic, just fails.
+            match command {
+                LinkedCommands::GetLinked(tx) => {
+                    let _ = tx.send(dev.get_linked_app_list());
+                }
+                LinkedCommands::SetLinked(app, tx) => {
+                    let _ = tx.send(dev.set_linked_app(app));
+                }
+            }
+        }
+    }
+}
+
+fn run_control_worker(
+    dev: Box<dyn BeacnControlDevice>,
+    def: DeviceDefinition,
+    rx: Receiver<ControlMessage>,
+    mut display: DisplayTask,
+    commands: Receiver<WorkerCommand>,
+) {
+    let serial = def.device_info.serial.as_str();
+    restore_control_settings(&*dev, &mut display, serial);
+
+    loop {
+        // The display task holds a sender, so this receiver never disconnects
+        let mut selector = Select::new();
+        let command_index = selector.recv(&commands);
+        let message_index = selector.recv(&rx);
+
+        let operation = selector.select();
+        match operation.index() {
+            i if i == command_index => match operation.recv(&commands) {
+                Ok(WorkerCommand::Announce(tx)) => {
+                    let arrived = DeviceArriveMessage::Control(def.clone(), display.sender.clone());
+                    let _ = tx.send(arrived);
+                }
+                Ok(WorkerCommand::SetEnabled(enabled, tx)) => {
+                    // Unlocking shouldn't wake a screen the schedule has put to sleep
+                    let _ = dev.set_enabled(enabled && !display.scheduled_sleep);
+                    let _ = tx.send(());
+                }
+                Ok(WorkerCommand::PowerSaving(saving)) => {
+                    display.power_saving.send_replace(saving);
+                    if let Some(brightness) = display.display_brightness() {
+                        let _ = dev.set_display_brightness(brightness);
                     }
                 }
+                Ok(WorkerCommand::Suspended(suspended)) => {
+                    display.session_suspended = suspended;
+                    display.update_suspended();
+                }
+                Ok(WorkerCommand::DisplaySleep(asleep)) => {
+                    debug!(
+                        "Sleep schedule turning the screen for {serial} on: {}",
+                        !asleep
+                    );
+                    display.scheduled_sleep = asleep;
+                    display.update_suspended();
+
+                    // The handler has nothing to do with this, so it's left wanting the screen on
+                    display.handler_enabled = true;
+                    let enabled = !asleep && !display.session_suspended && display.screen_enabled;
+                    let _ = track(serial, dev.set_enabled(enabled));
+                }
+                Ok(WorkerCommand::Shutdown) => {
+                    shutdown_control(&*dev, &def, &rx, &mut display);
+                    break;
+                }
+                Err(_) => break,
+            },
+            i if i == message_index => {
+                if let Ok(msg) = operation.recv(&rx) {
+                    handle_control_message(&*dev, &def, &mut display, msg);
+                }
             }
-            sleep(Duration::from_millis(10)).await;
+            _ => {}
         }
-    });
+    }
+    debug!("Worker for {serial} Stopped");
+}
 
-    // For some reason, we're stopping. If the manager channel is still open, tell it to stop.
-    if manage_tx.is_ready() {
-        let _ = manage_tx.send(HotPlugThreadManagement::Quit);
+fn handle_control_message(
+    dev: &dyn BeacnControlDevice,
+    def: &DeviceDefinition,
+    display: &mut DisplayTask,
+    msg: ControlMessage,
+) {
+    let serial = def.device_info.serial.as_str();
+    match msg {
+        ControlMessage::SendImage(img, x, y, tx) => {
+            display.update_preview(&img, x, y);
+            let _ = tx.send(track(serial, dev.set_image(x, y, &img)));
+        }
+        ControlMessage::DisplayBrightness(brightness, tx) => {
+            // Remember this, so it can be restored after saving power
+            display.brightness = Some(brightness);
+            let brightness = display.display_brightness().unwrap_or(brightness);
+            let _ = tx.send(track(serial, dev.set_display_brightness(brightness)));
+        }
+        ControlMessage::ButtonBrightness(brightness, tx) => {
+            let _ = tx.send(track(serial, dev.set_button_brightness(brightness)));
+        }
+        ControlMessage::DimTimeout(timeout, tx) => {
+            let _ = tx.send(track(serial, dev.set_dim_timeout(timeout)));
+        }
+        ControlMessage::ButtonColour(button, colour, tx) => {
+            let colour = display.button_colour(button, colour);
+            let result = dev.set_button_colour(button, colour);
+            let _ = tx.send(track(serial, result));
+        }
+        ControlMessage::Enabled(enabled, tx) => {
+            // The handler only turns on a sleeping screen when it's touched, which wakes it
+            // until the schedule next puts it to sleep
+            if enabled && display.scheduled_sleep {
+                debug!("Screen for {serial} woken before the end of the sleep schedule");
+                display.scheduled_sleep = false;
+                display.update_suspended();
+            }
+
+            // The user can keep the screen off, whatever the
+            // handler wants
+            display.handler_enabled = enabled;
+            let enabled = display.screen_enabled && enabled;
+            let _ = tx.send(track(serial, dev.set_enabled(enabled)));
+        }
+        ControlMessage::SetScreenEnabled(enabled, tx) => {
+            display.screen_enabled = enabled;
+            let enabled = display.handler_enabled && enabled;
+            let _ = tx.send(track(serial, dev.set_enabled(enabled)));
+        }
+        ControlMessage::SetButtonColours(colours, tx) => {
+            // Repaint anything that's currently lit
+            display.button_colours = colours;
+            let result = display.lighting.clone().into_iter().try_for_each(
+                |(button, [red, green, blue])| {
+                    let colour = RGBA {
+                        red,
+                        green,
+                        blue,
+                        alpha: 255,
+                    };
+                    let colour = display.button_colour(button, colour);
+                    dev.set_button_colour(button, colour)
+                },
+            );
+            let _ = tx.send(track(serial, result));
+        }
+        ControlMessage::SetStaticLighting(lighting, tx) => {
+            display.static_lighting = lighting;
+            let result = display.apply_static_lighting(dev);
+            let _ = tx.send(track(serial, result));
+        }
+        ControlMessage::KeepAlive(tx) => {
+            let result = track(serial, dev.send_keepalive());
+            stats::record_keepalive(serial, result.is_ok());
+            let _ = tx.send(result);
+        }
+        ControlMessage::SetBackend(backend, tx) => {
+            if display.backend != backend {
+                display.switch_backend(backend, def.device_type);
+                let _ = display.apply_static_lighting(dev);
+            }
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetDialAssignment(assignment, tx) => {
+            display.assignment.send_replace(assignment);
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetDialGestures(gestures, tx) => {
+            display.gestures.send_replace(gestures);
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetBurnIn(settings, tx) => {
+            display.burn_in.send_if_modified(|current| {
+                let changed = *current != settings;
+                *current = settings;
+                changed
+            });
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetHeaderWidgets(widgets, tx) => {
+            display.header_widgets.send_if_modified(|current| {
+                let changed = *current != widgets;
+                *current = widgets;
+                changed
+            });
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetKeepAlive(settings, tx) => {
+            display.keep_alive.send_if_modified(|current| {
+                let changed = *current != settings;
+                *current = settings;
+                changed
+            });
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::CoalesceUpdates(enabled, tx) => {
+            display.coalesce.send_replace(enabled);
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::PerformanceMode(enabled, tx) => {
+            display.performance.send_if_modified(|current| {
+                let changed = *current != enabled;
+                *current = enabled;
+                changed
+            });
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::PipeweaverAddress(address, tx) => {
+            display.address.send_if_modified(|current| {
+                let changed = *current != address;
+                *current = address;
+                changed
+            });
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetOrientation(orientation, tx) => {
+            display.orientation.send_if_modified(|current| {
+                let changed = *current != orientation;
+                *current = orientation;
+                changed
+            });
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetColourOverrides(colours, tx) => {
+            display.colours.send_replace(colours);
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetVolumeUnits(units, tx) => {
+            display.volume_units.send_replace(units);
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::SetScreenLayout(layout, tx) => {
+            display.screen_layout.send_replace(layout);
+            let _ = tx.send(Ok(()));
+        }
+        ControlMessage::GetPreview(generation, tx) => {
+            let _ = tx.send(display.get_preview(generation));
+        }
+        ControlMessage::GetAssignableChannels(tx) => {
+            let _ = tx.send(display.channels.borrow().clone());
+        }
+        ControlMessage::MonitorInteractions(monitor) => {
+            debug!("Interaction Monitor: {}", monitor.is_some());
+            display.monitor.send_replace(monitor);
+        }
     }
+}
 
-    debug!("Device Manager Stopped");
+fn shutdown_control(
+    dev: &dyn BeacnControlDevice,
+    def: &DeviceDefinition,
+    rx: &Receiver<ControlMessage>,
+    display: &mut DisplayTask,
+) {
+    let _ = display.stop.send(());
+
+    // The handler draws its 'stopped' screen on the way out, so keep servicing the device until
+    // it's finished, or we've given up waiting on it.
+    let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+    loop {
+        drain_control(dev, rx, display);
+        if display.task.is_finished() || Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // Leave everything in a known state, whether or not the handler managed to tidy up
+    if !display.task.is_finished() {
+        warn!(
+            "Display handler for {} didn't stop in time, aborting",
+            def.device_info.serial
+        );
+        display.task.abort();
+
+        let off = RGBA {
+            red: 0,
+            green: 0,
+            blue: 0,
+            alpha: 255,
+        };
+        for (button, _) in &display.lighting {
+            let _ = dev.set_button_colour(*button, off);
+        }
+    }
+
+    // Don't leave the screen dimmed from power saving after we've gone
+    if *display.power_saving.borrow()
+        && let Some(brightness) = display.brightness
+    {
+        let _ = dev.set_display_brightness(brightness);
+    }
+}
+
+// Handles anything still queued for a device while shutting down. Changes which reach the device
+// are applied so it isn't left half configured, anything else is cancelled.
+fn drain_audio(dev: &dyn BeacnAudioDevice, rx: &Receiver<AudioMessage>) {
+    let cancelled = || BeacnError::from(anyhow!("Device Manager is shutting down"));
+    while let Ok(msg) = rx.try_recv() {
+        match msg {
+            AudioMessage::Handle(msg, resp) => {
+                let response = catch_unwind(|| dev.handle_message(msg));
+                let _ = resp.send(response.unwrap_or_else(|_| Err(cancelled())));
+            }
+            AudioMessage::Linked(LinkedCommands::GetLinked(tx)) => {
+                let _ = tx.send(dev.get_linked_app_list());
+            }
+            AudioMessage::Linked(LinkedCommands::SetLinked(app, tx)) => {
+                let _ = tx.send(dev.set_linked_app(app));
+            }
+        }
+    }
+}
+
+fn drain_control(
+    dev: &dyn BeacnControlDevice,
+    rx: &Receiver<ControlMessage>,
+    display: &mut DisplayTask,
+) {
+    let cancelled = || BeacnError::from(anyhow!("Device Manager is shutting down"));
+    while let Ok(msg) = rx.try_recv() {
+        match msg {
+            ControlMessage::SendImage(img, x, y, tx) => {
+                let _ = tx.send(dev.set_image(x, y, &img));
+            }
+            ControlMessage::ButtonColour(button, colour, tx) => {
+                let colour = display.button_colour(button, colour);
+                let _ = tx.send(dev.set_button_colour(button, colour));
+            }
+            ControlMessage::Enabled(enabled, tx) => {
+                display.handler_enabled = enabled;
+                let enabled = display.screen_enabled && enabled;
+                let _ = tx.send(dev.set_enabled(enabled));
+            }
+            ControlMessage::DisplayBrightness(brightness, tx) => {
+                display.brightness = Some(brightness);
+                let _ = tx.send(dev.set_display_brightness(brightness));
+            }
+            ControlMessage::ButtonBrightness(brightness, tx) => {
+                let _ = tx.send(dev.set_button_brightness(brightness));
+            }
+            ControlMessage::GetPreview(_, tx) => {
+                let _ = tx.send(None);
+            }
+            ControlMessage::GetAssignableChannels(tx) => {
+                let _ = tx.send(vec![]);
+            }
+            ControlMessage::MonitorInteractions(_) => {}
+            ControlMessage::KeepAlive(tx)
+            | ControlMessage::DimTimeout(_, tx)
+            | ControlMessage::SetBackend(_, tx)
+            | ControlMessage::SetDialAssignment(_, tx)
+            | ControlMessage::SetDialGestures(_, tx)
+            | ControlMessage::SetBurnIn(_, tx)
+            | ControlMessage::SetHeaderWidgets(_, tx)
+            | ControlMessage::SetKeepAlive(_, tx)
+            | ControlMessage::CoalesceUpdates(_, tx)
+            | ControlMessage::PerformanceMode(_, tx)
+            | ControlMessage::PipeweaverAddress(_, tx)
+            | ControlMessage::SetOrientation(_, tx)
+            | ControlMessage::SetColourOverrides(_, tx)
+            | ControlMessage::SetVolumeUnits(_, tx)
+            | ControlMessage::SetScreenLayout(_, tx)
+            | ControlMessage::SetButtonColours(_, tx)
+            | ControlMessage::SetStaticLighting(_, tx)
+            | ControlMessage::SetScreenEnabled(_, tx) => {
+                let _ = tx.send(Err(cancelled()));
+            }
+        }
+    }
+}
+
+// Re-sends every open device to the UI, used when the UI has been rebuilt after a crash. Devices
+// which failed to open aren't tracked here, so they won't reappear until they're reprobed.
+fn announce_devices(
+    workers: &[DeviceWorker],
+    event_tx: &Sender<DeviceMessage>,
+    self_tx: &Sender<ToMainMessages>,
+) {
+    for worker in workers {
+        let (tx, rx) = oneshot::channel();
+        if worker.commands.send(WorkerCommand::Announce(tx)).is_err() {
+            continue;
+        }
+        if let Ok(arrived) = rx.recv() {
+            let _ = event_tx.send(DeviceMessage::DeviceArrived(arrived));
+        }
+    }
+    let _ = self_tx.send(ToMainMessages::RequestRedraw);
 }
 
 fn handle_device_attached(
     location: DeviceLocation,
     device_type: DeviceType,
-    health_tx: Sender<()>,
-    receiver_map: &mut Vec<DeviceMap>,
+    health_tx: &Sender<()>,
+    workers: &mut Vec<DeviceWorker>,
     event_tx: &Sender<DeviceMessage>,
     self_tx: &Sender<ToMainMessages>,
-) {
-    match device_type {
+    power_saving: bool,
+) -> DefinitionState {
+    forget_ignored(location);
+    if let Some(serial) = devices::factory().serial(location)
+        && is_ignored(&serial)
+    {
+        return ignore_device(location, device_type, serial, self_tx);
+    }
+
+    let state = match device_type {
         DeviceType::BeacnMic | DeviceType::BeacnStudio => {
-            let (device, state) = match open_audio_device(location) {
+            let (device, state) = match devices::factory().open_audio(location) {
                 Ok(d) => (Some(d), DefinitionState::Running),
                 Err(e) => {
                     error!("Failed to open audio device: {e}");
@@ -314,6 +990,11 @@ fn handle_device_attached(
                 None => ("Unknown".to_string(), VersionNumber(0, 0, 0, 0)),
             };
 
+            // We couldn't tell which device this was until it was opened
+            if device.is_some() && is_ignored(&serial) {
+                return ignore_device(location, device_type, serial, self_tx);
+            }
+
             // Firstly, build the device definition
             let data = DeviceDefinition {
                 state,
@@ -325,14 +1006,21 @@ fn handle_device_attached(
             // Create a Message Bus for it
             let (tx, rx) = channel::unbounded();
 
-            // Add this into our receiver array
+            // Hand it to a worker of its own
             if let Some(device) = device {
-                receiver_map.push(DeviceMap::Audio(device, data.clone(), rx));
+                stats::record_connect(&data.device_info.serial);
+                fire_device_hook(HookEvent::DeviceConnected, &data);
+                let device = OpenDevice::Audio(device, data.clone(), rx);
+                workers.push(DeviceWorker::spawn(device));
+            } else {
+                stats::record_error(&data.device_info.serial);
             }
 
+            let state = data.state.clone();
             let arrived = DeviceArriveMessage::Audio(data, tx);
             let message = DeviceMessage::DeviceArrived(arrived);
             let _ = event_tx.send(message);
+            state
         }
         DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
             // This is relatively similar, but the code paths are different. In
@@ -340,28 +1028,34 @@ fn handle_device_attached(
             // connection and management.
             let (input_tx, input_rx) = channel::unbounded();
 
-            let (device, state) = match open_control_device(location, Some(input_tx), health_tx) {
-                Ok(d) => (Some(d), DefinitionState::Running),
-                Err(e) => {
-                    error!("Failed to open control device: {e}");
+            let factory = devices::factory();
+            let (device, state) =
+                match factory.open_control(location, Some(input_tx), health_tx.clone()) {
+                    Ok(d) => (Some(d), DefinitionState::Running),
+                    Err(e) => {
+                        error!("Failed to open control device: {e}");
 
-                    (
-                        None,
-                        DefinitionState::Error(match e {
-                            BeacnError::Usb(UsbError::Access) => ErrorType::PermissionDenied,
-                            BeacnError::Usb(UsbError::Busy) => ErrorType::ResourceBusy,
-                            BeacnError::Usb(e) => ErrorType::Other(e.to_string()),
-                            BeacnError::Other(e) => ErrorType::Other(e.to_string()),
-                        }),
-                    )
-                }
-            };
+                        (
+                            None,
+                            DefinitionState::Error(match e {
+                                BeacnError::Usb(UsbError::Access) => ErrorType::PermissionDenied,
+                                BeacnError::Usb(UsbError::Busy) => ErrorType::ResourceBusy,
+                                BeacnError::Usb(e) => ErrorType::Other(e.to_string()),
+                                BeacnError::Other(e) => ErrorType::Other(e.to_string()),
+                            }),
+                        )
+                    }
+                };
 
             let (serial, version) = match &device {
                 Some(d) => (d.get_serial(), d.get_version()),
                 None => ("Unknown".to_string(), "Unknown".to_string()),
             };
 
+            if device.is_some() && is_ignored(&serial) {
+                return ignore_device(location, device_type, serial, self_tx);
+            }
+
             let data = DeviceDefinition {
                 state,
                 location,
@@ -373,56 +1067,323 @@ fn handle_device_attached(
             };
 
             let (tx, rx) = channel::unbounded();
-            let (stop_tx, stop_rx) = watch::channel(());
-            let (suspended_tx, suspended_rx) = watch::channel(false);
-            let img_tx = tx.clone();
-            let task =
-                spawn_pipeweaver_handler(img_tx, device_type, input_rx, stop_rx, suspended_rx);
+
+            // We always start with Pipeweaver, the UI will switch this if the user has
+            // selected something different for this device.
+            let display = DisplayTask::spawn(
+                DisplayBackend::default(),
+                tx.clone(),
+                device_type,
+                input_rx,
+                false,
+                power_saving,
+            );
 
             if let Some(device) = device {
-                receiver_map.push(DeviceMap::Control(
-                    device,
-                    data.clone(),
-                    rx,
-                    stop_tx,
-                    suspended_tx,
-                    task,
-                ));
+                stats::record_connect(&data.device_info.serial);
+                fire_device_hook(HookEvent::DeviceConnected, &data);
+                let device = OpenDevice::Control(device, data.clone(), rx, display);
+                workers.push(DeviceWorker::spawn(device));
+            } else {
+                stats::record_error(&data.device_info.serial);
             }
 
             // Use the async runtime for this
             debug!("Starting PipeWeaver Handler");
 
+            let state = data.state.clone();
             let arrived = DeviceArriveMessage::Control(data, tx);
             let message = DeviceMessage::DeviceArrived(arrived);
             let _ = event_tx.send(message);
+            state
         }
+    };
+    let _ = self_tx.send(ToMainMessages::RequestRedraw);
+    state
+}
+
+// Remembers a device we've been asked to leave alone, closing it if it had to be opened to find
+// out which one it was
+fn ignore_device(
+    location: DeviceLocation,
+    device_type: DeviceType,
+    serial: String,
+    self_tx: &Sender<ToMainMessages>,
+) -> DefinitionState {
+    debug!("Ignoring {device_type:?} {serial}");
+    if let Ok(mut ignored) = IGNORED.lock() {
+        ignored.push(IgnoredDevice {
+            location,
+            device_type,
+            serial,
+        });
     }
     let _ = self_tx.send(ToMainMessages::RequestRedraw);
+
+    // As far as retrying goes, there's nothing wrong with it
+    DefinitionState::Running
 }
 
-#[allow(unused)]
-fn enable_devices(receiver_map: &Vec<DeviceMap>, enabled: bool) {
-    for device in receiver_map {
-        #[allow(clippy::single_match)]
-        match device {
-            DeviceMap::Control(dev, _, _, _, _, _) => {
-                let _ = dev.set_enabled(enabled);
+fn fire_device_hook(event: HookEvent, definition: &DeviceDefinition) {
+    let device = format!("{:?}", definition.device_type);
+    let variables = [
+        ("BEACN_SERIAL", definition.device_info.serial.as_str()),
+        ("BEACN_DEVICE", device.as_str()),
+    ];
+    hooks::fire(event, &variables);
+
+    events::publish(Event::Device {
+        serial: definition.device_info.serial.clone(),
+        device,
+        connected: event == HookEvent::DeviceConnected,
+    });
+}
+
+// The parts of a Mix's saved settings that are applied as soon as it's opened, so they survive a
+// replug without waiting for the UI. This reads the same per-serial file the UI saves to, which
+// will apply everything else (and these again) once it's loaded.
+#[derive(Deserialize)]
+struct RestoredSettings {
+    display_brightness: u8,
+    display_dim: Duration,
+    button_brightness: u8,
+
+    #[serde(default)]
+    button_colours: ButtonColours,
+
+    #[serde(default)]
+    static_lighting: StaticLighting,
+}
+
+fn restore_control_settings(dev: &dyn BeacnControlDevice, display: &mut DisplayTask, serial: &str) {
+    let file = storage::config_path(&format!("{serial}.json"));
+    let settings: RestoredSettings = match file.and_then(storage::load_json) {
+        Ok(settings) => settings,
+        Err(e) => {
+            debug!("No settings to restore for {serial}: {e}");
+            return;
+        }
+    };
+
+    debug!("Restoring Saved Settings for {serial}");
+    display.brightness = Some(settings.display_brightness.min(100));
+    display.button_colours = settings.button_colours;
+    display.static_lighting = settings.static_lighting;
+    if let Some(brightness) = display.display_brightness() {
+        let _ = track(serial, dev.set_display_brightness(brightness));
+    }
+    let _ = track(
+        serial,
+        dev.set_button_brightness(settings.button_brightness),
+    );
+    let _ = track(serial, dev.set_dim_timeout(settings.display_dim));
+}
+
+// Waits for each device to be done, so they're off before the system is allowed to sleep
+fn enable_devices(workers: &[DeviceWorker], enabled: bool) {
+    let replies: Vec<_> = workers
+        .iter()
+        .filter_map(|worker| {
+            let (tx, rx) = oneshot::channel();
+            let command = WorkerCommand::SetEnabled(enabled, tx);
+            worker.commands.send(command).ok().map(|_| rx)
+        })
+        .collect();
+    for reply in replies {
+        let _ = reply.recv();
+    }
+}
+
+fn set_power_saving(workers: &[DeviceWorker], saving: bool) {
+    for worker in workers {
+        let _ = worker.commands.send(WorkerCommand::PowerSaving(saving));
+    }
+}
+
+// Only workers the schedule hasn't caught up with yet are told, so a screen woken early by the
+// user stays awake until the next night
+fn set_display_asleep(workers: &mut [DeviceWorker], asleep: bool) {
+    for worker in workers.iter_mut().filter(|w| w.display_asleep != asleep) {
+        worker.display_asleep = asleep;
+        let _ = worker.commands.send(WorkerCommand::DisplaySleep(asleep));
+    }
+}
+
+fn set_pipeweaver_draw_suspended(workers: &[DeviceWorker], suspended: bool) {
+    for worker in workers {
+        let _ = worker.commands.send(WorkerCommand::Suspended(suspended));
+    }
+}
+
+// Faulty cables can cause a device to rapidly connect and disconnect, opening and tearing down
+// everything attached to it each time. New devices are given a moment to settle before being
+// opened, and if one keeps reappearing it's held back until things have calmed down.
+#[derive(Default)]
+struct HotplugGuard {
+    settling: Vec<(DeviceLocation, DeviceType, Sender<()>, Instant)>,
+
+    // The USB address changes on every reconnect, so devices are matched on their bus and type
+    history: Vec<(DeviceLocation, DeviceType, Instant)>,
+    cooldowns: Vec<(DeviceLocation, DeviceType, Instant)>,
+}
+
+impl HotplugGuard {
+    /// Queues a newly attached device, returning true if this has caused it to start flapping
+    fn attached(
+        &mut self,
+        location: DeviceLocation,
+        device_type: DeviceType,
+        health_tx: Sender<()>,
+    ) -> bool {
+        let now = Instant::now();
+        let same = |l: &DeviceLocation, t: &DeviceType| {
+            l.bus_number == location.bus_number && *t == device_type
+        };
+
+        self.history.retain(|(_, _, at)| now - *at < FLAP_WINDOW);
+        self.history.push((location, device_type, now));
+        let attempts = self.history.iter().filter(|(l, t, _)| same(l, t)).count();
+
+        let mut started = false;
+        if attempts >= FLAP_THRESHOLD {
+            // Keep pushing the cooldown back for as long as it keeps happening
+            let until = now + FLAP_COOLDOWN;
+            match self.cooldowns.iter_mut().find(|(l, t, _)| same(l, t)) {
+                Some(cooldown) => cooldown.2 = until,
+                None => {
+                    self.cooldowns.push((location, device_type, until));
+                    started = true;
+                }
             }
-            _ => {}
         }
+
+        let cooldown = self.cooldowns.iter().find(|(l, t, _)| same(l, t));
+        let ready = cooldown.map_or(now + SETTLE_TIME, |(_, _, until)| *until);
+        self.settling
+            .push((location, device_type, health_tx, ready));
+        started
     }
+
+    /// Forgets a device which went away before it was opened
+    fn removed(&mut self, location: DeviceLocation) {
+        self.settling.retain(|(l, _, _, _)| *l != location);
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let settling = self.settling.iter().map(|(_, _, _, ready)| *ready);
+        let cooldowns = self.cooldowns.iter().map(|(_, _, until)| *until);
+        settling.chain(cooldowns).min()
+    }
+
+    /// Returns the devices ready to be opened, and the types which are no longer flapping
+    fn take_ready(
+        &mut self,
+    ) -> (
+        Vec<(DeviceLocation, DeviceType, Sender<()>)>,
+        Vec<DeviceType>,
+    ) {
+        let now = Instant::now();
+
+        let mut ready = vec![];
+        self.settling
+            .retain(|(location, device_type, health_tx, at)| {
+                if *at > now {
+                    return true;
+                }
+                ready.push((*location, *device_type, health_tx.clone()));
+                false
+            });
+
+        let mut settled = vec![];
+        self.cooldowns.retain(|(_, device_type, until)| {
+            if *until > now {
+                return true;
+            }
+            settled.push(*device_type);
+            false
+        });
+
+        (ready, settled)
+    }
+}
+
+// Tracks devices which couldn't be opened because they were busy, so they can be tried again
+// without needing to be replugged. Each failed attempt waits twice as long as the last.
+#[derive(Default)]
+struct OpenRetry {
+    // The device, how many attempts have failed, and when to next try (None while being opened)
+    pending: Vec<(DeviceLocation, DeviceType, Sender<()>, u32, Option<Instant>)>,
 }
 
-fn set_pipeweaver_draw_suspended(receiver_map: &Vec<DeviceMap>, suspended: bool) {
-    for device in receiver_map {
-        if let DeviceMap::Control(_, _, _, _, draw_suspend, _) = device {
-            let _ = draw_suspend.send(suspended);
+impl OpenRetry {
+    /// Records the result of opening a device, scheduling another attempt if it was busy
+    fn opened(
+        &mut self,
+        location: DeviceLocation,
+        device_type: DeviceType,
+        health_tx: Sender<()>,
+        state: &DefinitionState,
+    ) {
+        if *state != DefinitionState::Error(ErrorType::ResourceBusy) {
+            self.removed(location);
+            return;
+        }
+
+        let failures = match self.pending.iter().position(|(l, ..)| *l == location) {
+            Some(index) => self.pending.remove(index).3 + 1,
+            None => 1,
+        };
+        let delay = RETRY_DELAY
+            .saturating_mul(2u32.saturating_pow(failures - 1))
+            .min(RETRY_MAX_DELAY);
+
+        warn!(
+            "{device_type:?} is busy, trying again in {}s",
+            delay.as_secs()
+        );
+        let next = Some(Instant::now() + delay);
+        self.pending
+            .push((location, device_type, health_tx, failures, next));
+    }
+
+    /// Brings the next attempt on a busy device forward, returning false if it isn't being retried
+    fn retry_now(&mut self, location: DeviceLocation) -> bool {
+        let Some((.., next)) = self.pending.iter_mut().find(|(l, ..)| *l == location) else {
+            return false;
+        };
+
+        // Nothing to do if it's being opened right now
+        if next.is_some() {
+            *next = Some(Instant::now());
+        }
+        true
+    }
+
+    /// Stops retrying a device which has been unplugged
+    fn removed(&mut self, location: DeviceLocation) {
+        self.pending.retain(|(l, ..)| *l != location);
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.pending.iter().filter_map(|(.., next)| *next).min()
+    }
+
+    /// Returns the devices due another attempt, these should be passed back to `opened`
+    fn take_ready(&mut self) -> Vec<(DeviceLocation, DeviceType, Sender<()>)> {
+        let now = Instant::now();
+        let mut ready = vec![];
+        for (location, device_type, health_tx, _, next) in self.pending.iter_mut() {
+            if next.is_some_and(|next| next <= now) {
+                *next = None;
+                ready.push((*location, *device_type, health_tx.clone()));
+            }
         }
+        ready
     }
 }
 
-enum DeviceMap {
+// An open device, along with everything its worker needs to handle its messages
+enum OpenDevice {
     Audio(
         Box<dyn BeacnAudioDevice>,
         DeviceDefinition,
@@ -432,16 +1393,376 @@ enum DeviceMap {
         Box<dyn BeacnControlDevice>,
         DeviceDefinition,
         Receiver<ControlMessage>,
-        watch::Sender<()>,
-        watch::Sender<bool>,
-        JoinHandle<()>,
+        DisplayTask,
     ),
 }
 
+impl OpenDevice {
+    fn definition(&self) -> &DeviceDefinition {
+        match self {
+            OpenDevice::Audio(_, def, _) => def,
+            OpenDevice::Control(_, def, _, _) => def,
+        }
+    }
+}
+
+// Tracks the integration task which is currently driving a Mix / Mix Create display
+struct DisplayTask {
+    backend: DisplayBackend,
+    sender: Sender<ControlMessage>,
+    input_rx: Receiver<Interactions>,
+    stop: watch::Sender<()>,
+    suspended: watch::Sender<bool>,
+    assignment: watch::Sender<DialAssignment>,
+    gestures: watch::Sender<DialGestures>,
+    burn_in: watch::Sender<BurnInSettings>,
+    header_widgets: watch::Sender<HeaderWidgets>,
+    keep_alive: watch::Sender<KeepAliveSettings>,
+    coalesce: watch::Sender<bool>,
+    performance: watch::Sender<bool>,
+    address: watch::Sender<Option<String>>,
+    orientation: watch::Sender<Orientation>,
+    power_saving: watch::Sender<bool>,
+    colours: watch::Sender<ColourOverrides>,
+    volume_units: watch::Sender<VolumeUnits>,
+    screen_layout: watch::Sender<ScreenLayout>,
+    redraw: watch::Sender<()>,
+    channels: watch::Receiver<Vec<AssignableChannel>>,
+    monitor: watch::Sender<Option<Sender<MonitoredInteraction>>>,
+    task: JoinHandle<()>,
+
+    // The brightness last requested by the UI
+    brightness: Option<u8>,
+
+    // The colour the handler last gave each button, before the user's colours are applied
+    lighting: Vec<(ButtonLighting, [u8; 3])>,
+    button_colours: ButtonColours,
+    static_lighting: StaticLighting,
+
+    // The screen is only on if both the handler and the user want it to be
+    handler_enabled: bool,
+    screen_enabled: bool,
+
+    // The handler is told to stop drawing while the session is locked (or the system is asleep),
+    // or while the sleep schedule has turned the screen off
+    session_suspended: bool,
+    scheduled_sleep: bool,
+
+    preview: Option<DisplayPreview>,
+}
+
+// A copy of what's currently on the screen, built from the images sent to it while the UI
+// is showing a preview.
+struct DisplayPreview {
+    image: RgbaImage,
+    generation: u64,
+    last_request: Instant,
+}
+
+// Passes the device's interactions on to the display handler, copying each one to the UI while
+// its monitor is open. Stops when the device goes away and drops its sender.
+fn tee_interactions(
+    device_rx: Receiver<Interactions>,
+    handler_tx: Sender<Interactions>,
+    monitor: watch::Sender<Option<Sender<MonitoredInteraction>>>,
+) {
+    for interaction in device_rx {
+        let target = monitor.borrow().clone();
+        if let Some(target) = target {
+            let event = match &interaction {
+                Interactions::ButtonPress(button, state) => {
+                    InteractionEvent::Button(*button, matches!(state, ButtonState::Press))
+                }
+                Interactions::DialChanged(dial, change) => InteractionEvent::Dial(*dial, *change),
+            };
+            let time = Local::now();
+
+            // The page has closed and dropped its receiver, so stop copying until it's opened
+            // again (unless it already has, with a new one)
+            if target.send(MonitoredInteraction { time, event }).is_err() {
+                monitor.send_if_modified(|current| {
+                    let closed = current.as_ref().is_some_and(|c| c.same_channel(&target));
+                    if closed {
+                        *current = None;
+                    }
+                    closed
+                });
+            }
+        }
+
+        if handler_tx.send(interaction).is_err() {
+            break;
+        }
+    }
+}
+
+impl DisplayTask {
+    fn spawn(
+        backend: DisplayBackend,
+        sender: Sender<ControlMessage>,
+        device_type: DeviceType,
+        device_rx: Receiver<Interactions>,
+        suspended: bool,
+        power_saving: bool,
+    ) -> Self {
+        // The handlers get the device's interactions through the tee, so the UI can have a copy
+        let monitor = watch::Sender::new(None);
+        let (handler_tx, input_rx) = channel::unbounded();
+        let tee_monitor = monitor.clone();
+        thread::spawn(move || tee_interactions(device_rx, handler_tx, tee_monitor));
+
+        let (stop, stop_rx) = watch::channel(());
+        let session_suspended = suspended;
+        let (suspended, suspended_rx) = watch::channel(suspended);
+        let (assignment, assignment_rx) = watch::channel(None);
+        let (gestures, gestures_rx) = watch::channel(DialGestures::default());
+        let (burn_in, burn_in_rx) = watch::channel(BurnInSettings::default());
+        let (header_widgets, header_widgets_rx) = watch::channel(HeaderWidgets::default());
+        let (keep_alive, keep_alive_rx) = watch::channel(KeepAliveSettings::default());
+        let (coalesce, coalesce_rx) = watch::channel(false);
+        let (performance, performance_rx) = watch::channel(false);
+        let (address, address_rx) = watch::channel(None);
+        let (orientation, orientation_rx) = watch::channel(Orientation::default());
+        let (power_saving, power_saving_rx) = watch::channel(power_saving);
+        let (colours, colours_rx) = watch::channel(ColourOverrides::new());
+        let (volume_units, volume_units_rx) = watch::channel(VolumeUnits::new());
+        let (screen_layout, screen_layout_rx) = watch::channel(ScreenLayout::default());
+        let (redraw, redraw_rx) = watch::channel(());
+        let (channels_tx, channels) = watch::channel(vec![]);
+        let handles = DisplayHandles {
+            assignment_rx,
+            gestures_rx,
+            burn_in_rx,
+            header_widgets_rx,
+            keep_alive_rx,
+            coalesce_rx,
+            performance_rx,
+            address_rx,
+            orientation_rx,
+            power_saving_rx,
+            colours_rx,
+            volume_units_rx,
+            screen_layout_rx,
+            redraw_rx,
+            channels_tx,
+        };
+
+        let task = spawn_display_handler(
+            backend,
+            sender.clone(),
+            device_type,
+            input_rx.clone(),
+            stop_rx,
+            suspended_rx,
+            handles,
+        );
+
+        Self {
+            backend,
+            sender,
+            input_rx,
+            stop,
+            suspended,
+            assignment,
+            gestures,
+            burn_in,
+            header_widgets,
+            keep_alive,
+            coalesce,
+            performance,
+            address,
+            orientation,
+            power_saving,
+            colours,
+            volume_units,
+            screen_layout,
+            redraw,
+            channels,
+            monitor,
+            task,
+            brightness: None,
+            lighting: vec![],
+            button_colours: ButtonColours::new(),
+            static_lighting: StaticLighting::new(),
+            handler_enabled: true,
+            screen_enabled: true,
+            session_suspended,
+            scheduled_sleep: false,
+            preview: None,
+        }
+    }
+
+    fn update_suspended(&self) {
+        let suspended = self.session_suspended || self.scheduled_sleep;
+        self.suspended.send_if_modified(|current| {
+            let changed = *current != suspended;
+            *current = suspended;
+            changed
+        });
+    }
+
+    /// The brightness the display should be set to, dimmed if we're saving power
+    fn display_brightness(&self) -> Option<u8> {
+        match *self.power_saving.borrow() {
+            true => Some(
+                self.brightness
+                    .map_or(POWER_SAVE_BRIGHTNESS, |b| b.min(POWER_SAVE_BRIGHTNESS)),
+            ),
+            false => self.brightness,
+        }
+    }
+
+    /// The colour a button should be lit, the user's choice replaces anything but 'off'
+    fn button_colour(&mut self, button: ButtonLighting, colour: RGBA) -> RGBA {
+        let rgb = [colour.red, colour.green, colour.blue];
+        match self.lighting.iter_mut().find(|(b, _)| *b == button) {
+            Some((_, last)) => *last = rgb,
+            None => self.lighting.push((button, rgb)),
+        }
+
+        if let Some(lighting) = self.static_button(button) {
+            return lighting.rgba();
+        }
+        match self.button_colours.get(&format!("{button:?}")) {
+            Some(&[red, green, blue]) if rgb != [0, 0, 0] => RGBA {
+                red,
+                green,
+                blue,
+                alpha: colour.alpha,
+            },
+            _ => colour,
+        }
+    }
+
+    /// The user's fixed lighting for a button, only used when Pipeweaver isn't managing them
+    fn static_button(&self, button: ButtonLighting) -> Option<StaticButton> {
+        if self.backend == DisplayBackend::Pipeweaver && !safe_mode() {
+            return None;
+        }
+        self.static_lighting.get(&format!("{button:?}")).copied()
+    }
+
+    // Repaints the buttons with a fixed colour, along with anything the handler has lit so
+    // buttons which no longer have one go back to what the handler wanted
+    fn apply_static_lighting(&mut self, dev: &dyn BeacnControlDevice) -> Result<(), BeacnError> {
+        for button in ButtonLighting::iter() {
+            let [red, green, blue] = match self.lighting.iter().find(|(b, _)| *b == button) {
+                Some((_, rgb)) => *rgb,
+                None if self.static_button(button).is_some() => [0, 0, 0],
+                None => continue,
+            };
+            let colour = RGBA {
+                red,
+                green,
+                blue,
+                alpha: 255,
+            };
+            let colour = self.button_colour(button, colour);
+            dev.set_button_colour(button, colour)?;
+        }
+        Ok(())
+    }
+
+    fn update_preview(&mut self, jpeg: &[u8], x: u32, y: u32) {
+        let Some(preview) = &mut self.preview else {
+            return;
+        };
+
+        // Nobody's looking anymore, so don't waste time decoding images
+        if preview.last_request.elapsed() > PREVIEW_TIMEOUT {
+            self.preview = None;
+            return;
+        }
+
+        if let Ok(image) = jpeg_as_img(jpeg) {
+            replace(&mut preview.image, &image, x as i64, y as i64);
+            preview.generation += 1;
+        }
+    }
+
+    /// Returns the current screen contents, if they've changed since the given generation
+    fn get_preview(&mut self, generation: u64) -> Option<(u64, RgbaImage)> {
+        let Some(preview) = &mut self.preview else {
+            // Start building a preview, the handler needs to redraw everything to fill it
+            let (width, height) = DISPLAY_DIMENSIONS;
+            self.preview = Some(DisplayPreview {
+                image: ImageBuffer::from_pixel(width, height, BG_COLOUR),
+                generation: 0,
+                last_request: Instant::now(),
+            });
+            self.redraw.send_replace(());
+            return None;
+        };
+
+        preview.last_request = Instant::now();
+        if preview.generation == generation {
+            return None;
+        }
+        Some((preview.generation, preview.image.clone()))
+    }
+
+    fn switch_backend(&mut self, backend: DisplayBackend, device_type: DeviceType) {
+        debug!(
+            "Switching Display Backend from {} to {}",
+            self.backend, backend
+        );
+
+        // Stop the existing handler, it may still need to send some images to the device on the
+        // way out (which we need to be around to service) so we can't wait for it here. Instead,
+        // the new handler waits for the old one to finish before starting.
+        let _ = self.stop.send(());
+        let (stop, stop_rx) = watch::channel(());
+        let (suspended, suspended_rx) = watch::channel(*self.suspended.borrow());
+        let (channels_tx, channels) = watch::channel(vec![]);
+        let handles = DisplayHandles {
+            assignment_rx: self.assignment.subscribe(),
+            gestures_rx: self.gestures.subscribe(),
+            burn_in_rx: self.burn_in.subscribe(),
+            header_widgets_rx: self.header_widgets.subscribe(),
+            keep_alive_rx: self.keep_alive.subscribe(),
+            coalesce_rx: self.coalesce.subscribe(),
+            performance_rx: self.performance.subscribe(),
+            address_rx: self.address.subscribe(),
+            orientation_rx: self.orientation.subscribe(),
+            power_saving_rx: self.power_saving.subscribe(),
+            colours_rx: self.colours.subscribe(),
+            volume_units_rx: self.volume_units.subscribe(),
+            screen_layout_rx: self.screen_layout.subscribe(),
+            redraw_rx: self.redraw.subscribe(),
+            channels_tx,
+        };
+
+        let sender = self.sender.clone();
+        let input_rx = self.input_rx.clone();
+        let previous = std::mem::replace(&mut self.task, runtime().spawn(async {}));
+        self.task = runtime().spawn(async move {
+            let _ = previous.await;
+            let handler = spawn_display_handler(
+                backend,
+                sender,
+                device_type,
+                input_rx,
+                stop_rx,
+                suspended_rx,
+                handles,
+            );
+            let _ = handler.await;
+        });
+
+        self.backend = backend;
+        self.stop = stop;
+        self.suspended = suspended;
+        self.channels = channels;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum DeviceMessage {
     DeviceArrived(DeviceArriveMessage),
     DeviceRemoved(DeviceLocation),
+
+    // A device is repeatedly connecting and disconnecting, and is being held back
+    Flapping(DeviceType, bool),
 }
 
 #[derive(Debug, Clone)]
@@ -473,6 +1794,41 @@ pub enum ControlMessage {
         RGBA,
         oneshot::Sender<Result<(), BeacnError>>,
     ),
+    SetBackend(DisplayBackend, oneshot::Sender<Result<(), BeacnError>>),
+    SetDialAssignment(DialAssignment, oneshot::Sender<Result<(), BeacnError>>),
+    SetDialGestures(DialGestures, oneshot::Sender<Result<(), BeacnError>>),
+    SetBurnIn(BurnInSettings, oneshot::Sender<Result<(), BeacnError>>),
+    SetHeaderWidgets(HeaderWidgets, oneshot::Sender<Result<(), BeacnError>>),
+    SetKeepAlive(KeepAliveSettings, oneshot::Sender<Result<(), BeacnError>>),
+    CoalesceUpdates(bool, oneshot::Sender<Result<(), BeacnError>>),
+    PerformanceMode(bool, oneshot::Sender<Result<(), BeacnError>>),
+    PipeweaverAddress(Option<String>, oneshot::Sender<Result<(), BeacnError>>),
+    SetOrientation(Orientation, oneshot::Sender<Result<(), BeacnError>>),
+    SetColourOverrides(ColourOverrides, oneshot::Sender<Result<(), BeacnError>>),
+    SetVolumeUnits(VolumeUnits, oneshot::Sender<Result<(), BeacnError>>),
+    SetScreenLayout(ScreenLayout, oneshot::Sender<Result<(), BeacnError>>),
+    SetButtonColours(ButtonColours, oneshot::Sender<Result<(), BeacnError>>),
+    SetStaticLighting(StaticLighting, oneshot::Sender<Result<(), BeacnError>>),
+    SetScreenEnabled(bool, oneshot::Sender<Result<(), BeacnError>>),
+    GetPreview(u64, oneshot::Sender<Option<(u64, RgbaImage)>>),
+    GetAssignableChannels(oneshot::Sender<Vec<AssignableChannel>>),
+    MonitorInteractions(Option<Sender<MonitoredInteraction>>),
+}
+
+/// A button or dial event from a Mix / Mix Create, as copied to the UI's interaction monitor
+#[derive(Debug, Copy, Clone)]
+pub struct MonitoredInteraction {
+    pub time: DateTime<Local>,
+    pub event: InteractionEvent,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum InteractionEvent {
+    /// A button, and whether it was pressed (rather than released)
+    Button(Buttons, bool),
+
+    /// A dial, and how far it turned
+    Dial(Dials, i8),
 }
 
 #[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
@@ -0,0 +1,300 @@
+// Simulated devices, for exercising the UI, state loading and the display integrations without
+// any hardware attached. Which devices appear is set with BEACN_MOCK_DEVICES, a comma separated
+// list of 'mic', 'studio', 'mix' and 'mix-create' (all four if it's not set).
+//
+// The audio devices echo back every message they're sent, so values read back are whatever the
+// UI asked for (or the defaults for a fetch). In --demo mode the first fetch of a setting is
+// answered with a realistic value instead, so screenshots look like a configured device. The control
+// devices accept everything and discard it, the display preview still works as that's built by
+// the device manager.
+
+use crate::demo_mode;
+use crate::devices::{DeviceFactory, UsbDevices};
+use beacn_lib::BeacnError;
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::equaliser::{
+    EQBand, EQBandType, EQFrequency, EQGain, EQMode, EQQ, Equaliser,
+};
+use beacn_lib::audio::messages::headphones::{HPLevel, Headphones};
+use beacn_lib::audio::messages::lighting::{
+    Lighting, LightingBrightness, LightingMode, LightingSpeed, StudioLightingMode,
+};
+use beacn_lib::audio::messages::mic_setup::{MicGain, MicSetup, StudioMicGain};
+use beacn_lib::audio::messages::suppressor::Suppressor;
+use beacn_lib::audio::{BeacnAudioDevice, LinkedApp};
+use beacn_lib::controller::{BeacnControlDevice, ButtonLighting, Interactions};
+use beacn_lib::crossbeam::channel::Sender;
+use beacn_lib::manager::{DeviceLocation, DeviceType};
+use beacn_lib::types::{Percent, RGBA};
+use beacn_lib::version::VersionNumber;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const DEVICES_VAR: &str = "BEACN_MOCK_DEVICES";
+
+// USB buses are numbered from 1, so nothing real will ever be found here
+const MOCK_BUS: u8 = 0;
+
+pub struct MockDevices {
+    devices: Vec<(DeviceLocation, DeviceType)>,
+}
+
+impl MockDevices {
+    pub fn from_env() -> Self {
+        let list = env::var(DEVICES_VAR).unwrap_or(String::from("mic,studio,mix,mix-create"));
+        let mut devices = vec![];
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let device_type = match name.to_lowercase().as_str() {
+                "mic" => DeviceType::BeacnMic,
+                "studio" => DeviceType::BeacnStudio,
+                "mix" => DeviceType::BeacnMix,
+                "mix-create" => DeviceType::BeacnMixCreate,
+                _ => {
+                    warn!("Unknown mock device '{name}' in {DEVICES_VAR}");
+                    continue;
+                }
+            };
+            let location = DeviceLocation {
+                bus_number: MOCK_BUS,
+                address: devices.len() as u8 + 1,
+            };
+            devices.push((location, device_type));
+        }
+        debug!("Simulating Devices: {devices:?}");
+        Self { devices }
+    }
+
+    fn find(&self, location: DeviceLocation) -> Option<(usize, DeviceType)> {
+        let position = self.devices.iter().position(|(l, _)| *l == location)?;
+        Some((position, self.devices[position].1))
+    }
+}
+
+impl DeviceFactory for MockDevices {
+    fn open_audio(
+        &self,
+        location: DeviceLocation,
+    ) -> Result<Box<dyn BeacnAudioDevice>, BeacnError> {
+        match self.find(location) {
+            Some((index, device_type)) => {
+                let seeds = match demo_mode() {
+                    true => demo_values(device_type),
+                    false => HashMap::new(),
+                };
+                Ok(Box::new(MockAudioDevice {
+                    serial: serial(device_type, index),
+                    device_type,
+                    seeds: Mutex::new(seeds),
+                }))
+            }
+            None => UsbDevices.open_audio(location),
+        }
+    }
+
+    fn open_control(
+        &self,
+        location: DeviceLocation,
+        input_tx: Option<Sender<Interactions>>,
+        health_tx: Sender<()>,
+    ) -> Result<Box<dyn BeacnControlDevice>, BeacnError> {
+        match self.find(location) {
+            Some((index, device_type)) => Ok(Box::new(MockControlDevice {
+                serial: serial(device_type, index),
+                _input_tx: input_tx,
+                _health_tx: health_tx,
+            })),
+            None => UsbDevices.open_control(location, input_tx, health_tx),
+        }
+    }
+
+    fn serial(&self, location: DeviceLocation) -> Option<String> {
+        match self.find(location) {
+            Some((index, device_type)) => Some(serial(device_type, index)),
+            None => UsbDevices.serial(location),
+        }
+    }
+
+    fn simulated_devices(&self) -> Vec<(DeviceLocation, DeviceType)> {
+        self.devices.clone()
+    }
+}
+
+fn serial(device_type: DeviceType, index: usize) -> String {
+    format!("MOCK{device_type:?}{index:04}").to_uppercase()
+}
+
+// Identifies which setting a message is for, ignoring its value. Every message is shaped like
+// Category(Setting(.., value)), so this is everything up to that last argument.
+fn setting_key(message: &Message) -> String {
+    let text = format!("{message:?}");
+    let Some(start) = text.match_indices('(').nth(1).map(|(i, _)| i + 1) else {
+        return text;
+    };
+
+    let mut depth = 0;
+    let mut last = start;
+    for (index, char) in text[start..].char_indices() {
+        match char {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => last = start + index + 1,
+            _ => {}
+        }
+    }
+    text[..last].to_string()
+}
+
+// A mic set up the way someone might have it for streaming, with a few EQ bands, the noise
+// suppressor on, and a coloured ring
+fn demo_values(device_type: DeviceType) -> HashMap<String, Message> {
+    let mode = EQMode::Advanced;
+    let bands = [
+        (EQBand::Band1, EQBandType::HighPassFilter, 80.0, 0.0, 0.7),
+        (EQBand::Band2, EQBandType::BellBand, 250.0, -2.5, 1.2),
+        (EQBand::Band3, EQBandType::BellBand, 3200.0, 3.0, 0.9),
+        (EQBand::Band4, EQBandType::HighShelf, 10000.0, 2.0, 0.7),
+    ];
+    let mut messages = vec![
+        Message::Equaliser(Equaliser::Mode(mode)),
+        Message::Headphones(Headphones::HeadphoneLevel(HPLevel(-18.0))),
+        Message::Suppressor(Suppressor::Enabled(true)),
+        Message::Suppressor(Suppressor::Amount(Percent(45.0))),
+        Message::Lighting(Lighting::Brightness(LightingBrightness(80))),
+        Message::Lighting(Lighting::Colour1(RGBA {
+            red: 79,
+            green: 215,
+            blue: 255,
+            alpha: 0,
+        })),
+        Message::Lighting(Lighting::Colour2(RGBA {
+            red: 170,
+            green: 60,
+            blue: 255,
+            alpha: 0,
+        })),
+    ];
+    for (band, band_type, frequency, gain, q) in bands {
+        messages.extend([
+            Message::Equaliser(Equaliser::Type(mode, band, band_type)),
+            Message::Equaliser(Equaliser::Frequency(mode, band, EQFrequency(frequency))),
+            Message::Equaliser(Equaliser::Gain(mode, band, EQGain(gain))),
+            Message::Equaliser(Equaliser::Q(mode, band, EQQ(q))),
+            Message::Equaliser(Equaliser::Enabled(mode, band, true)),
+        ]);
+    }
+    match device_type {
+        DeviceType::BeacnStudio => {
+            messages.push(Message::Lighting(Lighting::StudioMode(
+                StudioLightingMode::PeakMeter,
+            )));
+            messages.push(Message::MicSetup(MicSetup::StudioMicGain(StudioMicGain(
+                38,
+            ))));
+        }
+        _ => {
+            messages.push(Message::Lighting(Lighting::Mode(LightingMode::Gradient)));
+            messages.push(Message::Lighting(Lighting::Speed(LightingSpeed(4))));
+            messages.push(Message::MicSetup(MicSetup::MicGain(MicGain(12))));
+        }
+    }
+
+    messages
+        .into_iter()
+        .map(|message| (setting_key(&message), message))
+        .collect()
+}
+
+struct MockAudioDevice {
+    serial: String,
+
+    device_type: DeviceType,
+
+    // Values to answer the first fetch of each setting with, keyed by `setting_key`
+    seeds: Mutex<HashMap<String, Message>>,
+}
+
+impl BeacnAudioDevice for MockAudioDevice {
+    fn get_serial(&self) -> String {
+        self.serial.clone()
+    }
+
+    fn get_version(&self) -> VersionNumber {
+        VersionNumber(1, 2, 0, 0)
+    }
+
+    fn handle_message(&self, message: Message) -> Result<Message, BeacnError> {
+        let Ok(mut seeds) = self.seeds.lock() else {
+            return Ok(message);
+        };
+
+        // A fetch is the setting's default value, which is only swapped for the seed the first
+        // time, after that the UI's changes (including back to the default) are echoed as usual
+        let Some(seed) = seeds.remove(&setting_key(&message)) else {
+            return Ok(message);
+        };
+        let fetch = Message::generate_fetch_message(self.device_type)
+            .into_iter()
+            .any(|fetch| format!("{fetch:?}") == format!("{message:?}"));
+        match fetch {
+            true => Ok(seed),
+            false => Ok(message),
+        }
+    }
+
+    fn get_linked_app_list(&self) -> Result<Option<Vec<LinkedApp>>, BeacnError> {
+        Ok(None)
+    }
+
+    fn set_linked_app(&self, _app: LinkedApp) -> Result<(), BeacnError> {
+        Ok(())
+    }
+}
+
+struct MockControlDevice {
+    serial: String,
+
+    // Held so the device manager doesn't see the device as gone, interactions aren't simulated
+    _input_tx: Option<Sender<Interactions>>,
+    _health_tx: Sender<()>,
+}
+
+impl BeacnControlDevice for MockControlDevice {
+    fn get_serial(&self) -> String {
+        self.serial.clone()
+    }
+
+    fn get_version(&self) -> String {
+        String::from("1.0.0.0")
+    }
+
+    fn set_image(&self, _x: u32, _y: u32, _image: &[u8]) -> Result<(), BeacnError> {
+        Ok(())
+    }
+
+    fn set_display_brightness(&self, _brightness: u8) -> Result<(), BeacnError> {
+        Ok(())
+    }
+
+    fn set_button_brightness(&self, _brightness: u8) -> Result<(), BeacnError> {
+        Ok(())
+    }
+
+    fn set_dim_timeout(&self, _timeout: Duration) -> Result<(), BeacnError> {
+        Ok(())
+    }
+
+    fn set_button_colour(&self, _button: ButtonLighting, _colour: RGBA) -> Result<(), BeacnError> {
+        Ok(())
+    }
+
+    fn set_enabled(&self, _enabled: bool) -> Result<(), BeacnError> {
+        Ok(())
+    }
+
+    fn send_keepalive(&self) -> Result<(), BeacnError> {
+        Ok(())
+    }
+}
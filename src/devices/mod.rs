@@ -0,0 +1,91 @@
+// Opening devices goes through a factory, so the device manager doesn't need to care whether it's
+// talking to real hardware. Normally this goes straight to beacn-lib, but with the mock-devices
+// feature (or in --demo mode), simulated devices are announced on startup and opened in place of
+// the real thing.
+
+use crate::demo_mode;
+use beacn_lib::BeacnError;
+use beacn_lib::audio::{BeacnAudioDevice, open_audio_device};
+use beacn_lib::controller::{BeacnControlDevice, Interactions, open_control_device};
+use beacn_lib::crossbeam::channel::Sender;
+use beacn_lib::manager::{DeviceLocation, DeviceType};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+mod mock;
+
+pub trait DeviceFactory: Send + Sync {
+    fn open_audio(&self, location: DeviceLocation)
+    -> Result<Box<dyn BeacnAudioDevice>, BeacnError>;
+
+    fn open_control(
+        &self,
+        location: DeviceLocation,
+        input_tx: Option<Sender<Interactions>>,
+        health_tx: Sender<()>,
+    ) -> Result<Box<dyn BeacnControlDevice>, BeacnError>;
+
+    /// The serial of the device at this location, if it can be found without opening it
+    fn serial(&self, _location: DeviceLocation) -> Option<String> {
+        None
+    }
+
+    /// Devices which won't be found by the hotplug handler, and need announcing on startup
+    fn simulated_devices(&self) -> Vec<(DeviceLocation, DeviceType)> {
+        vec![]
+    }
+}
+
+/// Opens real devices over USB
+pub struct UsbDevices;
+
+impl DeviceFactory for UsbDevices {
+    fn open_audio(
+        &self,
+        location: DeviceLocation,
+    ) -> Result<Box<dyn BeacnAudioDevice>, BeacnError> {
+        open_audio_device(location)
+    }
+
+    fn open_control(
+        &self,
+        location: DeviceLocation,
+        input_tx: Option<Sender<Interactions>>,
+        health_tx: Sender<()>,
+    ) -> Result<Box<dyn BeacnControlDevice>, BeacnError> {
+        open_control_device(location, input_tx, health_tx)
+    }
+
+    fn serial(&self, location: DeviceLocation) -> Option<String> {
+        // sysfs lists every USB device with its bus, address and serial, and reading them
+        // doesn't need (or disturb) a claim on the device
+        let read = |path: &Path, name: &str| {
+            let value = fs::read_to_string(path.join(name)).ok()?;
+            Some(value.trim().to_string())
+        };
+        let matches = |path: &Path, name: &str, value: u8| {
+            read(path, name).and_then(|v| v.parse::<u8>().ok()) == Some(value)
+        };
+
+        fs::read_dir("/sys/bus/usb/devices")
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| {
+                matches(path, "busnum", location.bus_number)
+                    && matches(path, "devnum", location.address)
+            })
+            .and_then(|path| read(&path, "serial"))
+    }
+}
+
+static FACTORY: LazyLock<Box<dyn DeviceFactory>> =
+    LazyLock::new(|| match cfg!(feature = "mock-devices") || demo_mode() {
+        true => Box::new(mock::MockDevices::from_env()),
+        false => Box::new(UsbDevices),
+    });
+
+pub fn factory() -> &'static dyn DeviceFactory {
+    FACTORY.as_ref()
+}
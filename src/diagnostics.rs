@@ -0,0 +1,263 @@
+/*
+  Helps people work out why a device can't be opened. On Linux this is almost always down to
+  missing udev rules, so we check for those, look at who owns the device node, and offer to
+  install the rules we ship. Once sorted, the device manager can be asked to try again.
+*/
+
+use anyhow::{Result, bail};
+use beacn_lib::crossbeam::channel::{self, Receiver, Sender};
+use beacn_lib::manager::{DeviceLocation, DeviceType};
+use log::{debug, info, warn};
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+const RULES: &str = include_str!("../50-beacn.rules");
+const RULES_TARGET: &str = "/etc/udev/rules.d/50-beacn.rules";
+const VENDOR_ID: &str = "33ae";
+
+const INSTALL_SCRIPT: &str = "install -m 0644 /dev/stdin \"$1\" && udevadm control --reload-rules && udevadm trigger --subsystem-match=usb";
+
+const RULES_PATHS: [&str; 4] = [
+    "/etc/udev/rules.d",
+    "/run/udev/rules.d",
+    "/usr/lib/udev/rules.d",
+    "/lib/udev/rules.d",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReprobeKind {
+    /// Have another go at opening a device which failed
+    Open,
+
+    /// Close the device first if it's already open
+    Reconnect,
+
+    /// Skip the wait before the next attempt on a busy device
+    RetryBusy,
+}
+
+type ReprobeRequest = (DeviceLocation, DeviceType, ReprobeKind);
+
+// Requests from the UI to have another go at opening (or to reconnect) a device
+static REPROBE: LazyLock<(Sender<ReprobeRequest>, Receiver<ReprobeRequest>)> =
+    LazyLock::new(channel::unbounded);
+
+#[derive(Debug, Clone)]
+pub struct PermissionReport {
+    /// Running under Flatpak, where we can't see or install the host's udev rules
+    pub sandboxed: bool,
+
+    /// The first rules file found which mentions Beacn's vendor ID
+    pub rules_file: Option<PathBuf>,
+
+    pub device_node: PathBuf,
+    pub node_exists: bool,
+    pub node_accessible: bool,
+    pub node_group: Option<String>,
+    pub user_groups: Vec<String>,
+}
+
+impl PermissionReport {
+    pub fn in_node_group(&self) -> bool {
+        self.node_group
+            .as_ref()
+            .is_some_and(|group| self.user_groups.contains(group))
+    }
+}
+
+pub fn check_permissions(location: DeviceLocation) -> PermissionReport {
+    let device_node = PathBuf::from(format!(
+        "/dev/bus/usb/{:03}/{:03}",
+        location.bus_number, location.address
+    ));
+
+    let node_exists = device_node.exists();
+    let node_accessible = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&device_node)
+        .is_ok();
+    let node_group = node_group(&device_node);
+
+    PermissionReport {
+        sandboxed: ashpd::is_sandboxed(),
+        rules_file: find_rules(),
+        device_node,
+        node_exists,
+        node_accessible,
+        node_group,
+        user_groups: user_groups(),
+    }
+}
+
+/// The first rules file which mentions Beacn's vendor ID, if there is one
+pub fn find_rules() -> Option<PathBuf> {
+    for dir in RULES_PATHS {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "rules")
+                && let Ok(content) = fs::read_to_string(&path)
+                && content.to_lowercase().contains(VENDOR_ID)
+            {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(unix)]
+fn node_group(device_node: &Path) -> Option<String> {
+    let meta = fs::metadata(device_node).ok()?;
+    group_name(meta.gid())
+}
+
+// Device nodes don't have an owning group outside of unix
+#[cfg(not(unix))]
+fn node_group(_device_node: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(unix)]
+fn group_name(gid: u32) -> Option<String> {
+    let groups = fs::read_to_string("/etc/group").ok()?;
+    groups.lines().find_map(|line| {
+        let mut parts = line.split(':');
+        let name = parts.next()?;
+        let id = parts.nth(1)?.parse::<u32>().ok()?;
+        (id == gid).then(|| name.to_string())
+    })
+}
+
+fn user_groups() -> Vec<String> {
+    match Command::new("id").arg("-Gn").output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .map(String::from)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The commands someone would need to run to install the rules by hand
+pub fn manual_instructions() -> String {
+    format!(
+        "sudo tee {RULES_TARGET} > /dev/null << 'EOF'\n{}\nEOF\nsudo udevadm control --reload-rules\nsudo udevadm trigger",
+        RULES.trim()
+    )
+}
+
+/// Installs the udev rules via pkexec. This blocks while the user is prompted for their
+/// password, so should be run away from the UI thread.
+pub fn install_rules() -> Result<()> {
+    if ashpd::is_sandboxed() {
+        bail!("Rules can't be installed from inside the Flatpak");
+    }
+
+    // The rules go in through stdin, so there's no file for anyone else to swap out while the
+    // password prompt is open. The script is fixed, with the target passed as an argument.
+    debug!("Installing udev rules via pkexec");
+    let status = Command::new("pkexec")
+        .args(["sh", "-c", INSTALL_SCRIPT, "sh", RULES_TARGET])
+        .stdin(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                // If the prompt is cancelled the pipe closes, which shows up in the exit status
+                let _ = stdin.write_all(RULES.as_bytes());
+            }
+            child.wait()
+        });
+
+    match status {
+        Ok(status) if status.success() => {
+            info!("udev rules installed to {RULES_TARGET}");
+            Ok(())
+        }
+        Ok(status) => bail!("pkexec exited with {status}"),
+        Err(e) => bail!("Unable to run pkexec: {e}"),
+    }
+}
+
+/// Asks the device manager to try opening a device again
+pub fn request_reprobe(location: DeviceLocation, device_type: DeviceType) {
+    if let Err(e) = REPROBE.0.send((location, device_type, ReprobeKind::Open)) {
+        warn!("Failed to request device reprobe: {e}");
+    }
+}
+
+/// Asks the device manager to close a device and open it again
+pub fn request_reconnect(location: DeviceLocation, device_type: DeviceType) {
+    if let Err(e) = REPROBE
+        .0
+        .send((location, device_type, ReprobeKind::Reconnect))
+    {
+        warn!("Failed to request device reconnect: {e}");
+    }
+}
+
+/// Asks the device manager to try opening a busy device now, rather than waiting
+pub fn request_busy_retry(location: DeviceLocation, device_type: DeviceType) {
+    if let Err(e) = REPROBE
+        .0
+        .send((location, device_type, ReprobeKind::RetryBusy))
+    {
+        warn!("Failed to request busy device retry: {e}");
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UsbPort {
+    /// The physical path through any hubs, for example 1-2.3
+    pub path: String,
+
+    /// The negotiated speed in Mbps
+    pub speed: Option<f32>,
+}
+
+impl UsbPort {
+    pub fn speed_name(&self) -> String {
+        let Some(speed) = self.speed else {
+            return String::from("Unknown");
+        };
+        let name = match speed {
+            s if s <= 1.5 => "Low Speed",
+            s if s <= 12.0 => "Full Speed",
+            s if s <= 480.0 => "High Speed",
+            _ => "Super Speed",
+        };
+        format!("{speed} Mbps ({name})")
+    }
+}
+
+/// Finds where a device is plugged in, by matching its bus and address against sysfs
+pub fn usb_port(location: DeviceLocation) -> Option<UsbPort> {
+    let read = |path: PathBuf| fs::read_to_string(path).ok().map(|v| v.trim().to_string());
+
+    for entry in fs::read_dir("/sys/bus/usb/devices").ok()?.flatten() {
+        let path = entry.path();
+        let bus = read(path.join("busnum")).and_then(|b| b.parse::<u8>().ok());
+        let address = read(path.join("devnum")).and_then(|d| d.parse::<u8>().ok());
+        if bus != Some(location.bus_number) || address != Some(location.address) {
+            continue;
+        }
+        return Some(UsbPort {
+            path: entry.file_name().to_string_lossy().to_string(),
+            speed: read(path.join("speed")).and_then(|s| s.parse().ok()),
+        });
+    }
+    None
+}
+
+pub fn reprobe_receiver() -> &'static Receiver<ReprobeRequest> {
+    &REPROBE.1
+}
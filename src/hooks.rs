@@ -0,0 +1,147 @@
+/*
+  User configured actions which run when something happens, such as a device being connected or
+  everything being muted. An action is either a shell command, or one of the messages the command
+  line can send to a running instance (which are sent over the IPC socket in the same way).
+
+  Hooks are run on their own thread, and commands are left running detached (with their output
+  going to the log), so a slow or stuck command never holds up whatever fired it, or the next hook.
+  Commands are given details of the event through environment variables:
+    * BEACN_EVENT - The event which triggered the hook
+    * BEACN_SERIAL / BEACN_DEVICE - The device involved, if there is one
+    * BEACN_PROFILE - The profile or snapshot switched to
+*/
+
+use crate::managers::ipc::{send_panic_mute, send_stream_timer, send_suppressor_profile};
+use crate::settings;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::thread;
+use strum_macros::EnumIter;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum HookEvent {
+    DeviceConnected,
+    DeviceRemoved,
+    MicMuted,
+    MicUnmuted,
+    PanicMuted,
+    PanicUnmuted,
+    ProfileSwitched,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HookAction {
+    /// Run through 'sh -c', so pipes and redirects work
+    Command(String),
+
+    /// The same as --panic-mute
+    TogglePanicMute,
+
+    /// The same as --suppressor-profile
+    SuppressorProfile(String),
+
+    /// The same as --stream-timer
+    StreamTimer(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hook {
+    pub enabled: bool,
+    pub event: HookEvent,
+    pub action: HookAction,
+}
+
+impl Default for Hook {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            event: HookEvent::DeviceConnected,
+            action: HookAction::Command(String::new()),
+        }
+    }
+}
+
+/// Runs every enabled hook for this event, the variables are passed to any commands
+pub fn fire(event: HookEvent, variables: &[(&'static str, &str)]) {
+    let hooks: Vec<Hook> = settings::get()
+        .hooks
+        .into_iter()
+        .filter(|h| h.enabled && h.event == event)
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+
+    debug!("Running {} hooks for {event:?}", hooks.len());
+    let mut variables: Vec<(&str, String)> =
+        variables.iter().map(|(k, v)| (*k, v.to_string())).collect();
+    variables.push(("BEACN_EVENT", format!("{event:?}")));
+
+    thread::spawn(move || {
+        for hook in hooks {
+            run(&hook.action, &variables);
+        }
+    });
+}
+
+fn run(action: &HookAction, variables: &[(&str, String)]) {
+    let sent = match action {
+        HookAction::Command(command) => return run_command(command, variables),
+        HookAction::TogglePanicMute => send_panic_mute(),
+        HookAction::SuppressorProfile(name) => send_suppressor_profile(name),
+        HookAction::StreamTimer(action) => send_stream_timer(action),
+    };
+    if !sent {
+        warn!("Hook Failed, unable to send {action:?}");
+    }
+}
+
+fn run_command(command: &str, variables: &[(&str, String)]) {
+    if command.trim().is_empty() {
+        return;
+    }
+
+    info!("Running Hook: {command}");
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(variables.iter().map(|(k, v)| (*k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("Unable to run hook '{command}': {e}");
+            return;
+        }
+    };
+
+    // Nothing waits on the command, it's left to finish (or not) on its own, while its output is
+    // logged as it arrives
+    let command = command.to_string();
+    if let Some(stdout) = child.stdout.take() {
+        log_output(&command, stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        log_output(&command, stderr);
+    }
+    thread::spawn(move || match child.wait() {
+        Ok(status) if status.success() => debug!("Hook Finished: {command}"),
+        Ok(status) => warn!("Hook '{command}' failed with {status}"),
+        Err(e) => warn!("Unable to wait for hook '{command}': {e}"),
+    });
+}
+
+fn log_output(command: &str, output: impl Read + Send + 'static) {
+    let command = command.to_string();
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            info!("Hook '{command}': {line}");
+        }
+    });
+}
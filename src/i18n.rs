@@ -0,0 +1,169 @@
+/*
+  Translations for the UI and tray.
+
+  Strings are looked up by key in a catalog written in a small subset of the Fluent format
+  (https://projectfluent.org/): one `key = value` per message, `#` comments, indented lines
+  continuing the previous value, and `{ $name }` placeholders which are filled in by the caller.
+  Catalogs live in resources/locales and are compiled in, anything missing from the selected
+  language falls back to English.
+
+  The language comes from the app settings, or the environment if it's not been chosen. It can
+  be changed at any time, the UI picks it up on the next frame, and the tray when it's told to.
+
+  To add a language, copy en.ftl, translate the values, and add it to CATALOGS.
+*/
+
+use crate::settings;
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt::Display;
+use std::sync::{LazyLock, Mutex, RwLock};
+
+const FALLBACK: &str = "en";
+
+// Every catalog compiled into the app, as (code, native name, source)
+const CATALOGS: &[(&str, &str, &str)] =
+    &[("en", "English", include_str!("../resources/locales/en.ftl"))];
+
+static FALLBACK_STRINGS: LazyLock<HashMap<String, String>> =
+    LazyLock::new(|| parse(catalog(FALLBACK).unwrap_or_default()));
+
+// Keys which have already been warned about, lookups happen every frame
+static MISSING: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(Default::default);
+
+static ACTIVE: LazyLock<RwLock<Translations>> =
+    LazyLock::new(|| RwLock::new(Translations::load(settings::get().language)));
+
+struct Translations {
+    language: &'static str,
+    strings: HashMap<String, String>,
+}
+
+impl Translations {
+    fn load(language: Option<String>) -> Self {
+        let requested = language.or_else(system_language);
+        let language = requested
+            .as_deref()
+            .and_then(|code| CATALOGS.iter().find(|(c, _, _)| *c == code))
+            .map_or(FALLBACK, |(code, _, _)| *code);
+
+        debug!("Using the '{language}' translations");
+        let strings = match language {
+            FALLBACK => HashMap::new(),
+            _ => parse(catalog(language).unwrap_or_default()),
+        };
+        Self { language, strings }
+    }
+}
+
+/// Looks up a string in the current language, see the `tr!` macro
+pub fn lookup(key: &str) -> String {
+    if let Ok(active) = ACTIVE.read()
+        && let Some(value) = active.strings.get(key)
+    {
+        return value.clone();
+    }
+    match FALLBACK_STRINGS.get(key) {
+        Some(value) => value.clone(),
+        None => {
+            if let Ok(mut missing) = MISSING.lock()
+                && missing.insert(key.to_string())
+            {
+                warn!("Missing translation for '{key}'");
+            }
+            key.to_string()
+        }
+    }
+}
+
+/// Looks up a string in the current language, and fills in its placeholders
+pub fn lookup_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut value = lookup(key);
+    for (name, arg) in args {
+        value = value.replace(&format!("{{ ${name} }}"), &arg.to_string());
+    }
+    value
+}
+
+/// The available languages, as (code, native name)
+pub fn languages() -> impl Iterator<Item = (&'static str, &'static str)> {
+    CATALOGS.iter().map(|(code, name, _)| (*code, *name))
+}
+
+/// The code of the language currently in use
+pub fn current() -> &'static str {
+    ACTIVE.read().map_or(FALLBACK, |active| active.language)
+}
+
+/// Switches language and stores the choice, None follows the system language
+pub fn set_language(language: Option<String>) {
+    settings::update(|s| s.language = language.clone());
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = Translations::load(language);
+    }
+}
+
+fn catalog(language: &str) -> Option<&'static str> {
+    let catalog = CATALOGS.iter().find(|(code, _, _)| *code == language);
+    catalog.map(|(_, _, source)| *source)
+}
+
+// LANG and friends look like en_GB.UTF-8, we only care about the language part
+fn system_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| value.split(['_', '.', '@']).next().map(str::to_lowercase))
+}
+
+fn parse(source: &str) -> HashMap<String, String> {
+    let mut strings = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        // Indented lines carry on the previous value
+        if line.starts_with(char::is_whitespace)
+            && let Some((_, value)) = &mut current
+        {
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(trimmed);
+            continue;
+        }
+
+        if let Some((key, value)) = current.take() {
+            strings.insert(key, value);
+        }
+        match trimmed.split_once('=') {
+            Some((key, value)) => current = Some((key.trim().into(), value.trim().into())),
+            None => warn!("Invalid line in translation catalog: {trimmed}"),
+        }
+    }
+
+    if let Some((key, value)) = current {
+        strings.insert(key, value);
+    }
+    strings
+}
+
+/// Looks up a translated string by key, with optional `name = value` placeholders
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::i18n::lookup($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::lookup_args(
+            $key,
+            &[$((stringify!($name), &$value as &dyn std::fmt::Display)),+],
+        )
+    };
+}
+pub(crate) use tr;
@@ -0,0 +1,110 @@
+/*
+  Controls how far the volume moves for each detent of a dial. By default every detent is a
+  single step, but spinning a dial quickly can be made to cover more ground, so going from
+  silent to full doesn't take a hundred clicks.
+
+  Holding a dial's button down while turning it switches that dial to fine mode, where every
+  detent is a single step regardless of the curve, for small precise changes. The press is then
+  ignored, so it doesn't also trigger the dial's gesture when released.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use strum_macros::{Display, EnumIter};
+
+// Detents closer together than this count as part of the same spin
+const SPIN_WINDOW: Duration = Duration::from_millis(80);
+
+// How many detents of a spin pass before the multiplier goes up by one
+const DETENTS_PER_STEP: u16 = 3;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Display, EnumIter, Serialize, Deserialize)]
+pub enum DialCurve {
+    #[default]
+    #[strum(to_string = "Linear")]
+    Linear,
+
+    #[strum(to_string = "Faster when Spun")]
+    Accelerated,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DialAcceleration {
+    pub curve: DialCurve,
+
+    /// How far a single detent moves the volume
+    pub step: u8,
+
+    /// The most a fast spin can multiply the step by
+    pub max_multiplier: u8,
+
+    /// Whether holding the dial's button while turning switches to single steps
+    pub fine_mode: bool,
+}
+
+impl Default for DialAcceleration {
+    // Matches how the dials behaved before they could be configured
+    fn default() -> Self {
+        Self {
+            curve: DialCurve::Linear,
+            step: 1,
+            max_multiplier: 4,
+            fine_mode: true,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Spin {
+    last: Instant,
+    direction: i8,
+    detents: u16,
+}
+
+#[derive(Debug, Default)]
+pub struct DialAccelerator {
+    spins: [Option<Spin>; 4],
+}
+
+impl DialAccelerator {
+    /// Turns the detents reported by a dial into a volume change
+    pub fn apply(
+        &mut self,
+        dial: usize,
+        change: i8,
+        settings: &DialAcceleration,
+        fine: bool,
+    ) -> i16 {
+        let Some(spin) = self.spins.get_mut(dial) else {
+            return change as i16;
+        };
+
+        if fine && settings.fine_mode {
+            *spin = None;
+            return change as i16;
+        }
+
+        // Carry on the current spin if this detent follows closely in the same direction
+        let now = Instant::now();
+        let direction = change.signum();
+        let previous = spin.filter(|s| s.direction == direction && now - s.last <= SPIN_WINDOW);
+        let detents = previous.map_or(0, |s| s.detents);
+        let detents = detents.saturating_add(change.unsigned_abs() as u16);
+        *spin = Some(Spin {
+            last: now,
+            direction,
+            detents,
+        });
+
+        let step = settings.step.max(1) as i16;
+        let multiplier = match settings.curve {
+            DialCurve::Linear => 1,
+            DialCurve::Accelerated => {
+                let ramp = 1 + (detents.saturating_sub(1) / DETENTS_PER_STEP) as i16;
+                ramp.min(settings.max_multiplier.max(1) as i16)
+            }
+        };
+        change as i16 * step * multiplier
+    }
+}
@@ -0,0 +1,188 @@
+// Burn-in protection for the Mix screen, which can show the same layout for hours at a time.
+//
+// Every so often the whole screen is moved by a pixel or two, cycling around a small square so
+// nothing drifts far from where it was drawn. The header at the top of the screen never changes,
+// so if the device hasn't been touched for a while, it's also faded towards the background until
+// the next interaction.
+//
+// This is applied to each image as it's sent (see FrameBuffer), so the handlers only need to
+// check in periodically, and perform a full redraw when asked.
+
+use crate::integrations::pipeweaver::layout::{BG_COLOUR, DISPLAY_DIMENSIONS, POSITION_ROOT};
+use image::imageops::{crop_imm, overlay};
+use image::{ImageBuffer, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// How often handlers should call `check`
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// The offsets cycled through, as (x, y)
+const SHIFT_PATTERN: [(u32, u32); 8] = [
+    (0, 0),
+    (1, 0),
+    (2, 0),
+    (2, 1),
+    (2, 2),
+    (1, 2),
+    (0, 2),
+    (0, 1),
+];
+
+// How much of the header's brightness is kept once faded
+const FADE_LEVEL: f32 = 0.3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BurnInSettings {
+    pub enabled: bool,
+
+    /// Minutes between each move of the screen
+    pub shift_minutes: u32,
+
+    /// Minutes without any interaction before the header fades, 0 never fades it
+    pub fade_minutes: u32,
+}
+
+impl Default for BurnInSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shift_minutes: 10,
+            fade_minutes: 30,
+        }
+    }
+}
+
+pub struct BurnInGuard {
+    settings_rx: watch::Receiver<BurnInSettings>,
+    settings: BurnInSettings,
+
+    step: usize,
+    last_shift: Instant,
+    last_activity: Instant,
+    faded: bool,
+}
+
+impl BurnInGuard {
+    pub fn new(settings_rx: watch::Receiver<BurnInSettings>) -> Self {
+        let settings = *settings_rx.borrow();
+        Self {
+            settings_rx,
+            settings,
+            step: 0,
+            last_shift: Instant::now(),
+            last_activity: Instant::now(),
+            faded: false,
+        }
+    }
+
+    /// Moves things along if it's time to, returning true if the screen needs a full redraw
+    pub fn check(&mut self) -> bool {
+        let mut changed = false;
+        if self.settings_rx.has_changed().unwrap_or(false) {
+            self.settings = *self.settings_rx.borrow_and_update();
+            self.last_shift = Instant::now();
+            self.last_activity = Instant::now();
+            if !self.settings.enabled {
+                self.step = 0;
+                self.faded = false;
+            }
+            changed = true;
+        }
+
+        if !self.settings.enabled {
+            return changed;
+        }
+
+        let shift_time = minutes(self.settings.shift_minutes.max(1));
+        if self.last_shift.elapsed() >= shift_time {
+            self.step = (self.step + 1) % SHIFT_PATTERN.len();
+            self.last_shift = Instant::now();
+            changed = true;
+        }
+
+        let fade = self.settings.fade_minutes;
+        if fade > 0 && !self.faded && self.last_activity.elapsed() >= minutes(fade) {
+            self.faded = true;
+            changed = true;
+        }
+        changed
+    }
+
+    /// Notes the user touching the device, returning true if the screen needs a full redraw
+    pub fn activity(&mut self) -> bool {
+        self.last_activity = Instant::now();
+        let was_faded = self.faded;
+        self.faded = false;
+        was_faded
+    }
+
+    /// Whether images need changing before they're sent
+    pub fn is_active(&self) -> bool {
+        self.offset() != (0, 0) || self.faded
+    }
+
+    fn offset(&self) -> (u32, u32) {
+        match self.settings.enabled {
+            true => SHIFT_PATTERN[self.step],
+            false => (0, 0),
+        }
+    }
+
+    /// Fades and moves an image positioned in the normal layout, returns None if it's been
+    /// moved entirely off the screen.
+    pub fn apply(&self, mut image: RgbaImage, x: u32, y: u32) -> Option<(RgbaImage, u32, u32)> {
+        if self.faded {
+            fade_header(&mut image, y);
+        }
+
+        let (dx, dy) = self.offset();
+        if (dx, dy) == (0, 0) {
+            return Some((image, x, y));
+        }
+
+        // A full frame also needs the gap it leaves behind filling in
+        let (width, height) = DISPLAY_DIMENSIONS;
+        if (x, y) == (0, 0) && image.dimensions() == (width, height) {
+            let mut base = ImageBuffer::from_pixel(width, height, BG_COLOUR);
+            overlay(&mut base, &image, dx as i64, dy as i64);
+            return Some((base, 0, 0));
+        }
+
+        let (x, y) = (x + dx, y + dy);
+        if x >= width || y >= height {
+            return None;
+        }
+        let visible = (image.width().min(width - x), image.height().min(height - y));
+        if visible != image.dimensions() {
+            image = crop_imm(&image, 0, 0, visible.0, visible.1).to_image();
+        }
+        Some((image, x, y))
+    }
+}
+
+// Blends any part of the image which covers the header towards the background
+fn fade_header(image: &mut RgbaImage, y: u32) {
+    let header_height = POSITION_ROOT.1;
+    if y >= header_height {
+        return;
+    }
+
+    let rows = (header_height - y).min(image.height());
+    for row in 0..rows {
+        for column in 0..image.width() {
+            let pixel = image.get_pixel_mut(column, row);
+            for channel in 0..3 {
+                let background = BG_COLOUR[channel] as f32;
+                let value = pixel[channel] as f32;
+                pixel[channel] = (background + (value - background) * FADE_LEVEL).round() as u8;
+            }
+        }
+    }
+}
+
+fn minutes(minutes: u32) -> Duration {
+    Duration::from_secs(minutes as u64 * 60)
+}
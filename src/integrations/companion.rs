@@ -0,0 +1,323 @@
+/* A small WebSocket server for external control surfaces (such as a Stream Deck plugin), so they
+   can trigger actions and show the current state on their buttons.
+
+   It's off by default, and only listens on localhost. Every connection has to present the token
+   shown in the settings before anything else, and is closed if it doesn't. All messages are JSON
+   text frames, each with a 'type':
+
+   Client -> Server:
+     {"type":"auth","token":"..."}
+     {"type":"action","id":1,"action":"toggle_mute"}
+     {"type":"action","id":2,"action":"set_mute","muted":true}
+     {"type":"action","id":3,"action":"suppressor_profile","serial":null,"name":"Office"}
+     {"type":"action","id":4,"action":"lighting_mode","serial":"...","name":"Rainbow"}
+     {"type":"action","id":5,"action":"mic_colour","colour":[255,0,0]}
+
+   Server -> Client:
+     {"type":"hello","version":1,"actions":["toggle_mute",...]}
+     {"type":"result","id":1,"ok":true,"error":null}
+     {"type":"mute","muted":true}
+     {"type":"devices","devices":[{"serial":"...","suppressor_profiles":[..],"lighting_modes":[..]}]}
+
+   The 'id' on an action is optional, and is handed back in its result so a client can match them
+   up. Once authenticated the current state is sent straight away, then again whenever it changes.
+*/
+
+use crate::integrations::websocket::{self, Server, ServerSettings, ServerStatus, send};
+use crate::{ToMainMessages, TrayDeviceList, panic_mute, settings};
+use anyhow::{Result, bail};
+use beacn_lib::crossbeam;
+use futures_util::StreamExt;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, IntoStaticStr};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc as tokio_mpsc;
+use tokio::sync::watch;
+use tokio::time;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use ulid::Ulid;
+
+// How long a new connection has to authenticate before it's dropped
+const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Bumped whenever a change would break an existing client
+const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompanionSettings {
+    pub enabled: bool,
+    pub port: u16,
+
+    /// Has to be sent by every connection before it can do anything
+    pub token: String,
+}
+
+impl Default for CompanionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 4460,
+            token: String::new(),
+        }
+    }
+}
+
+impl ServerSettings for CompanionSettings {
+    fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+/// A new random token, for the first time the endpoint is enabled or when it's regenerated
+pub fn generate_token() -> String {
+    Ulid::new().to_string().to_lowercase()
+}
+
+static SERVER: Server = Server::new("Companion Endpoint");
+
+// The devices as last published to the tray, forwarded here by the main loop
+static DEVICES: LazyLock<watch::Sender<TrayDeviceList>> =
+    LazyLock::new(|| watch::Sender::new(vec![]));
+
+/// The state of the endpoint, for display in the UI
+pub fn status() -> ServerStatus {
+    SERVER.status()
+}
+
+/// How many authenticated clients are connected
+pub fn clients() -> usize {
+    SERVER.clients()
+}
+
+/// Called when the profiles or lighting modes on offer change
+pub fn publish_devices(devices: TrayDeviceList) {
+    DEVICES.send_replace(devices);
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Auth {
+        token: String,
+    },
+    Action {
+        #[serde(default)]
+        id: Option<u64>,
+        #[serde(flatten)]
+        action: Action,
+    },
+}
+
+#[derive(Debug, Deserialize, EnumIter, IntoStaticStr)]
+#[serde(tag = "action", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+enum Action {
+    ToggleMute,
+    SetMute {
+        muted: bool,
+    },
+
+    /// Applies to every device with a profile of this name when no serial is given
+    SuppressorProfile {
+        #[serde(default)]
+        serial: Option<String>,
+        name: String,
+    },
+    LightingMode {
+        serial: String,
+        name: String,
+    },
+    MicColour {
+        colour: [u8; 3],
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Event {
+    Hello {
+        version: u32,
+        actions: Vec<&'static str>,
+    },
+    Result {
+        id: Option<u64>,
+        ok: bool,
+        error: Option<String>,
+    },
+    Mute {
+        muted: bool,
+    },
+    Devices {
+        devices: Vec<DeviceState>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct DeviceState {
+    serial: String,
+    suppressor_profiles: Vec<String>,
+    lighting_modes: Vec<LightingModeState>,
+}
+
+#[derive(Debug, Serialize)]
+struct LightingModeState {
+    name: String,
+    active: bool,
+}
+
+impl Event {
+    fn devices(devices: &TrayDeviceList) -> Self {
+        let devices = devices
+            .iter()
+            .map(|device| DeviceState {
+                serial: device.serial.clone(),
+                suppressor_profiles: device.suppressor_profiles.clone(),
+                lighting_modes: device
+                    .lighting_modes
+                    .iter()
+                    .map(|(name, active)| LightingModeState {
+                        name: name.clone(),
+                        active: *active,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Event::Devices { devices }
+    }
+}
+
+pub fn spawn_companion_handler(
+    tx: crossbeam::channel::Sender<ToMainMessages>,
+    stop_rx: tokio_mpsc::Receiver<()>,
+) -> Result<()> {
+    let settings = || {
+        let companion = settings::get().companion;
+        Some(companion).filter(|c| c.enabled && !c.token.is_empty())
+    };
+    SERVER.run(stop_rx, settings, |stream, companion| {
+        let tx = tx.clone();
+        async move { handle_connection(stream, &companion.token, &tx).await }
+    })
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    token: &str,
+    tx: &crossbeam::channel::Sender<ToMainMessages>,
+) -> Result<()> {
+    let mut socket = websocket::accept(stream).await?;
+
+    // Nothing is sent until the client has proven it's allowed to be here
+    let auth = time::timeout(AUTH_TIMEOUT, read_message(&mut socket)).await??;
+    match auth {
+        Some(ClientMessage::Auth { token: sent }) if sent == token => {}
+        _ => {
+            send(&mut socket, &result(None, Err("Not Authenticated".into()))).await?;
+            let _ = socket.close(None).await;
+            bail!("Failed to authenticate");
+        }
+    }
+
+    let _client = SERVER.client();
+    run_connection(&mut socket, tx).await
+}
+
+async fn run_connection(
+    socket: &mut WebSocketStream<TcpStream>,
+    tx: &crossbeam::channel::Sender<ToMainMessages>,
+) -> Result<()> {
+    let mut mute_rx = panic_mute::subscribe();
+    let mut devices_rx = DEVICES.subscribe();
+
+    let hello = Event::Hello {
+        version: PROTOCOL_VERSION,
+        actions: Action::iter().map(|a| a.into()).collect(),
+    };
+    send(socket, &hello).await?;
+
+    let muted = *mute_rx.borrow_and_update();
+    send(socket, &Event::Mute { muted }).await?;
+    let devices = Event::devices(&devices_rx.borrow_and_update());
+    send(socket, &devices).await?;
+
+    loop {
+        tokio::select! {
+            message = read_message(socket) => {
+                let Some(message) = message? else {
+                    return Ok(());
+                };
+                let event = match message {
+                    ClientMessage::Auth { .. } => result(None, Err("Already Authenticated".into())),
+                    ClientMessage::Action { id, action } => {
+                        result(id, perform(action, tx).map_err(|e| e.to_string()))
+                    }
+                };
+                send(socket, &event).await?;
+            }
+            Ok(()) = mute_rx.changed() => {
+                let muted = *mute_rx.borrow_and_update();
+                send(socket, &Event::Mute { muted }).await?;
+            }
+            Ok(()) = devices_rx.changed() => {
+                let devices = Event::devices(&devices_rx.borrow_and_update());
+                send(socket, &devices).await?;
+            }
+        }
+    }
+}
+
+fn perform(action: Action, tx: &crossbeam::channel::Sender<ToMainMessages>) -> Result<()> {
+    debug!("Companion Action: {action:?}");
+    let message = match action {
+        Action::ToggleMute => ToMainMessages::TogglePanicMute,
+        Action::SetMute { muted } => ToMainMessages::SetPanicMute(muted),
+        Action::SuppressorProfile { serial, name } => {
+            ToMainMessages::ApplySuppressorProfile(serial, name)
+        }
+        Action::LightingMode { serial, name } => ToMainMessages::ApplyLightingMode(serial, name),
+        Action::MicColour { colour } => ToMainMessages::SetMicColour(colour),
+    };
+    tx.send(message)?;
+    Ok(())
+}
+
+fn result(id: Option<u64>, result: Result<(), String>) -> Event {
+    match result {
+        Ok(()) => Event::Result {
+            id,
+            ok: true,
+            error: None,
+        },
+        Err(error) => Event::Result {
+            id,
+            ok: false,
+            error: Some(error),
+        },
+    }
+}
+
+/// Reads the next message from the client, returning None once it's gone
+async fn read_message(socket: &mut WebSocketStream<TcpStream>) -> Result<Option<ClientMessage>> {
+    loop {
+        let text = match socket.next().await {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | None => return Ok(None),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(e.into()),
+        };
+
+        match serde_json::from_str(text.as_str()) {
+            Ok(message) => return Ok(Some(message)),
+            Err(e) => {
+                let error = result(None, Err(format!("Invalid Message: {e}")));
+                send(socket, &error).await?;
+            }
+        }
+    }
+}
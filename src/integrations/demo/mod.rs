@@ -0,0 +1,497 @@
+// A self-contained demo for the Mix / Mix Create display. This doesn't talk to any audio
+// daemon, instead it cycles through a set of sample channels, animating their volumes and
+// meters, and toggling mutes. It's useful for testing hardware, showing the device off, and
+// checking rendering changes without needing Pipeweaver running.
+//
+// The dials and buttons still work, touching anything pauses the animation for a while so
+// changes can be seen.
+
+use crate::device_manager::ControlMessage;
+use crate::device_manager::ControlMessage::ButtonColour;
+use crate::integrations::DisplayHandles;
+use crate::integrations::burn_in;
+use crate::integrations::header_widgets::{self, HeaderWidgetState};
+use crate::integrations::keep_alive::KeepAlive;
+use crate::integrations::orientation::Orientation;
+use crate::integrations::pipeweaver::channel::{ChannelChangedProperty, ChannelRenderer};
+use crate::integrations::pipeweaver::encoder::Tile;
+use crate::integrations::pipeweaver::frame::FrameBuffer;
+use crate::integrations::pipeweaver::layout::{
+    BG_COLOUR, CHANNEL_DIMENSIONS, DISPLAY_DIMENSIONS, DrawingUtils, HEADER, HeaderWidgetRenderer,
+    POSITION_ROOT,
+};
+use crate::integrations::pipeweaver::{jpeg_as_img, sync_to_async};
+use crate::{integrations, runtime, session};
+use anyhow::{Result, bail};
+use beacn_lib::controller::{ButtonLighting, ButtonState, Buttons, Dials, Interactions};
+use beacn_lib::crossbeam;
+use beacn_lib::crossbeam::channel::{Receiver, Sender};
+use beacn_lib::manager::DeviceType;
+use beacn_lib::types::RGBA;
+use image::{ImageBuffer, Rgba};
+use log::{info, warn};
+use pipeweaver_shared::{Mix, MuteTarget};
+use std::time::{Duration, Instant};
+use tokio::select;
+use tokio::sync::mpsc::channel;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+const CHANNELS_PER_PAGE: usize = 4;
+const FRAME_RATE: Duration = Duration::from_millis(50);
+const MUTE_RATE: Duration = Duration::from_secs(2);
+const PAGE_RATE: Duration = Duration::from_secs(10);
+
+// How long to hold off the animation after the user touches something
+const INTERACTION_PAUSE: Duration = Duration::from_secs(10);
+
+const SAMPLE_CHANNELS: [(&str, Rgba<u8>); 8] = [
+    ("System", Rgba([89, 177, 182, 255])),
+    ("Browser", Rgba([244, 124, 36, 255])),
+    ("Game", Rgba([155, 89, 182, 255])),
+    ("Music", Rgba([46, 204, 113, 255])),
+    ("Chat", Rgba([231, 76, 60, 255])),
+    ("Microphone", Rgba([241, 196, 15, 255])),
+    ("Stream Mix", Rgba([52, 152, 219, 255])),
+    ("Headphones", Rgba([236, 240, 241, 255])),
+];
+
+const COLOUR_WHITE: RGBA = RGBA {
+    red: 255,
+    green: 255,
+    blue: 255,
+    alpha: 255,
+};
+
+const COLOUR_BLACK: RGBA = RGBA {
+    red: 0,
+    green: 0,
+    blue: 0,
+    alpha: 0,
+};
+
+struct DemoChannel {
+    renderer: ChannelRenderer,
+
+    // Offsets the animation, so the channels don't all move together
+    phase: f32,
+}
+
+struct DemoHandler {
+    sender: Sender<ControlMessage>,
+    input_rx: Receiver<Interactions>,
+    stop_rx: watch::Receiver<()>,
+    suspended_rx: watch::Receiver<bool>,
+    orientation_rx: watch::Receiver<Orientation>,
+    power_saving_rx: watch::Receiver<bool>,
+    redraw_rx: watch::Receiver<()>,
+
+    channels: Vec<DemoChannel>,
+    active_page: usize,
+    mute_index: usize,
+    frame: FrameBuffer,
+    header_widgets: HeaderWidgetState,
+    keep_alive: KeepAlive,
+
+    started: Instant,
+    last_interaction: Option<Instant>,
+}
+
+impl DemoHandler {
+    pub fn new(
+        device_type: DeviceType,
+        sender: Sender<ControlMessage>,
+        input_rx: Receiver<Interactions>,
+        stop_rx: watch::Receiver<()>,
+        suspended_rx: watch::Receiver<bool>,
+        handles: DisplayHandles,
+    ) -> Self {
+        let orientation = *handles.orientation_rx.borrow();
+        let channels = SAMPLE_CHANNELS
+            .iter()
+            .enumerate()
+            .map(|(index, (name, colour))| {
+                let mut renderer =
+                    ChannelRenderer::new_target(name.to_string(), *colour, 50, false);
+                renderer.set_beacn_device(device_type);
+                DemoChannel {
+                    renderer,
+                    phase: index as f32 * 1.7,
+                }
+            })
+            .collect();
+
+        Self {
+            sender,
+            input_rx,
+            stop_rx,
+            suspended_rx,
+            orientation_rx: handles.orientation_rx,
+            power_saving_rx: handles.power_saving_rx,
+            redraw_rx: handles.redraw_rx,
+
+            channels,
+            active_page: 0,
+            mute_index: 0,
+            frame: FrameBuffer::new(false, false, orientation, handles.burn_in_rx),
+            header_widgets: HeaderWidgetState::new(handles.header_widgets_rx),
+            keep_alive: KeepAlive::new(handles.keep_alive_rx),
+
+            started: Instant::now(),
+            last_interaction: None,
+        }
+    }
+
+    pub async fn run_handler(&mut self) {
+        info!("Starting Demo Display");
+        if let Err(e) = self.run_message_loop().await {
+            warn!("Demo Display Error: {e}");
+        }
+        info!("Demo Display Terminated");
+    }
+
+    async fn run_message_loop(&mut self) -> Result<()> {
+        let sync_receiver = self.input_rx.clone();
+        let (interaction_tx, mut interaction_rx) = channel(10);
+
+        let (_stop_tx, stop_rx) = crossbeam::channel::bounded::<()>(0);
+        runtime().spawn_blocking(move || sync_to_async(sync_receiver, interaction_tx, stop_rx));
+
+        // Starting up (or reconnecting) shouldn't wake a screen that's been put to sleep
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(ControlMessage::Enabled(!self.is_suspended(), tx))?;
+        rx.recv()??;
+
+        self.refresh_page()?;
+
+        let mut frame = time::interval(FRAME_RATE);
+        let mut mutes = time::interval(MUTE_RATE);
+        let mut pages = time::interval(PAGE_RATE);
+        let mut burn_in = time::interval(burn_in::CHECK_INTERVAL);
+        let mut header = time::interval(header_widgets::CHECK_INTERVAL);
+
+        // The first tick of an interval is immediate, we've already drawn the first page
+        pages.tick().await;
+
+        loop {
+            select! {
+                Ok(_) = self.stop_rx.changed() => {
+                    return Ok(());
+                }
+                Ok(_) = self.suspended_rx.changed() => {
+                    if !self.is_suspended() {
+                        self.refresh_page()?;
+                    }
+                }
+                Ok(_) = self.redraw_rx.changed() => {
+                    self.refresh_page()?;
+                }
+                Ok(_) = self.orientation_rx.changed() => {
+                    self.frame.set_orientation(*self.orientation_rx.borrow());
+                    self.refresh_page()?;
+                }
+                Ok(_) = self.power_saving_rx.changed() => {
+                    // The demo isn't doing anything useful, so everything stops to save power
+                    for channel in &mut self.channels {
+                        channel.renderer.meter = 0;
+                        channel.renderer.meter_target = 0.0;
+                    }
+                    self.refresh_page()?;
+                }
+                _ = frame.tick(), if !self.is_power_saving() => {
+                    self.animate()?;
+                }
+                _ = mutes.tick(), if self.is_animating() => {
+                    self.toggle_next_mute()?;
+                }
+                _ = pages.tick(), if self.is_animating() => {
+                    self.active_page = (self.active_page + 1) % self.get_page_count();
+                    self.refresh_page()?;
+                }
+                _ = burn_in.tick() => {
+                    if self.frame.check_burn_in() {
+                        self.refresh_page()?;
+                    }
+                }
+                _ = header.tick(), if !self.is_suspended() => {
+                    self.redraw_header_widgets()?;
+                }
+                _ = self.keep_alive.tick() => {
+                    if self.keep_alive.should_send() {
+                        let (tx, rx) = oneshot::channel();
+                        self.sender.send(ControlMessage::KeepAlive(tx))?;
+                        rx.recv()??;
+                    }
+                }
+                message = interaction_rx.recv() => {
+                    if message.is_some() {
+                        self.keep_alive.activity();
+                    }
+                    if message.is_some() && self.frame.note_activity() {
+                        self.refresh_page()?;
+                    }
+                    match message {
+                        Some(Interactions::ButtonPress(button, ButtonState::Press)) => {
+                            self.last_interaction = Some(Instant::now());
+                            self.handle_button(button)?;
+                        }
+                        Some(Interactions::ButtonPress(_, ButtonState::Release)) => {}
+                        Some(Interactions::DialChanged(dial, change)) => {
+                            self.last_interaction = Some(Instant::now());
+                            self.handle_dial(dial, change)?;
+                        }
+                        None => bail!("Receive Handler Closed!")
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_animating(&self) -> bool {
+        !self.is_power_saving()
+            && self
+                .last_interaction
+                .is_none_or(|time| time.elapsed() > INTERACTION_PAUSE)
+    }
+
+    fn get_page_count(&self) -> usize {
+        self.channels.len().div_ceil(CHANNELS_PER_PAGE)
+    }
+
+    fn get_page_range(&self) -> std::ops::Range<usize> {
+        let start = self.active_page * CHANNELS_PER_PAGE;
+        start..(start + CHANNELS_PER_PAGE).min(self.channels.len())
+    }
+
+    fn animate(&mut self) -> Result<()> {
+        let elapsed = self.started.elapsed().as_secs_f32();
+        let animating = self.is_animating();
+        let suspended = self.is_suspended();
+
+        let start = self.get_page_range().start;
+        for index in self.get_page_range() {
+            let channel = &mut self.channels[index];
+            let renderer = &mut channel.renderer;
+
+            let mut changed = false;
+            if animating {
+                // Slowly sweep the volume, it's enough to show the dials moving
+                let sweep = (elapsed * 0.4 + channel.phase).sin();
+                let volume = (60.0 + sweep * 35.0).round() as u8;
+                if renderer.volumes[Mix::A] != volume {
+                    renderer.volumes[Mix::A] = volume;
+                    changed = true;
+                }
+            }
+
+            // The meter bounces around regardless, muted channels don't produce any audio
+            renderer.meter_target = match renderer.mute_states[MuteTarget::TargetA].is_active {
+                true => 0.0,
+                false => {
+                    let level = (elapsed * 5.0 + channel.phase).sin().abs();
+                    let wobble = (elapsed * 13.0 + channel.phase * 2.0).sin().abs();
+                    (level * 0.7 + wobble * 0.3) * 100.0
+                }
+            };
+            let meter = renderer.meter;
+            if renderer.tick_meter(FRAME_RATE.as_secs_f32()) != meter {
+                changed = true;
+            }
+
+            if changed && !suspended {
+                let image = renderer.get_volume(Mix::A)?;
+                let (x, y) = image.position;
+                self.send_channel_image(index - start, image.image, x, y)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn toggle_next_mute(&mut self) -> Result<()> {
+        let range = self.get_page_range();
+        let index = range.start + (self.mute_index % range.len());
+        self.mute_index += 1;
+        self.toggle_mute(index)
+    }
+
+    fn toggle_mute(&mut self, index: usize) -> Result<()> {
+        let start = self.get_page_range().start;
+        let renderer = &mut self.channels[index].renderer;
+
+        let (title, colour) = (renderer.title.clone(), renderer.colour);
+        let muted = !renderer.mute_states[MuteTarget::TargetA].is_active;
+        let volume = renderer.volumes[Mix::A];
+        let updates = renderer.update_target(&title, colour, volume, muted);
+        if self.is_suspended() {
+            return Ok(());
+        }
+
+        for update in updates {
+            if let ChannelChangedProperty::MuteState(_) = update {
+                let renderer = &mut self.channels[index].renderer;
+                for image in renderer.render_damaged(update, Mix::A) {
+                    let (x, y) = image.position;
+                    self.send_channel_image(index - start, image.image, x, y)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn refresh_page(&mut self) -> Result<()> {
+        if self.is_suspended() {
+            return Ok(());
+        }
+        self.perform_full_redraw()?;
+        self.load_button_colours()
+    }
+
+    fn perform_full_redraw(&mut self) -> Result<()> {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        let mut base = ImageBuffer::from_pixel(width, height, BG_COLOUR);
+        DrawingUtils::composite_from_pos(&mut base, &jpeg_as_img(HEADER)?, (0, 0));
+        HeaderWidgetRenderer::draw_all(&mut base, self.header_widgets.current());
+
+        let (ch_w, _) = CHANNEL_DIMENSIONS;
+        for (position, index) in self.get_page_range().enumerate() {
+            let drawing = self.channels[index].renderer.full_render(Mix::A);
+            let x = ch_w * position as u32;
+            DrawingUtils::composite_from_pos(&mut base, &drawing.image, (x, POSITION_ROOT.1));
+        }
+
+        self.frame.send_full(&self.sender, base)
+    }
+
+    fn redraw_header_widgets(&mut self) -> Result<()> {
+        for (slot, text) in self.header_widgets.changed() {
+            let (image, (x, y)) = HeaderWidgetRenderer::draw(slot, &text);
+            self.frame.send(&self.sender, image, x, y)?;
+        }
+        Ok(())
+    }
+
+    fn send_channel_image(&self, index: usize, img: impl Into<Tile>, x: u32, y: u32) -> Result<()> {
+        let (ch_w, _) = CHANNEL_DIMENSIONS;
+        let (root_x, root_y) = POSITION_ROOT;
+        let x = ch_w * index as u32 + x + root_x;
+        let y = y + root_y;
+        self.frame.send_now(&self.sender, img, x, y)
+    }
+
+    fn load_button_colours(&self) -> Result<()> {
+        let dials = [
+            ButtonLighting::Dial1,
+            ButtonLighting::Dial2,
+            ButtonLighting::Dial3,
+            ButtonLighting::Dial4,
+        ];
+
+        let mut range = self.get_page_range();
+        for button in dials {
+            let colour = match range.next() {
+                Some(index) => {
+                    let colour = self.channels[index].renderer.colour;
+                    RGBA {
+                        red: colour[0],
+                        green: colour[1],
+                        blue: colour[2],
+                        alpha: colour[3],
+                    }
+                }
+                None => COLOUR_BLACK,
+            };
+            self.set_button_colour(button, colour)?;
+        }
+
+        // Pages wrap around in the demo, so both buttons are always available
+        self.set_button_colour(ButtonLighting::Left, COLOUR_WHITE)?;
+        self.set_button_colour(ButtonLighting::Right, COLOUR_WHITE)?;
+        self.set_button_colour(ButtonLighting::Mix, COLOUR_BLACK)?;
+        Ok(())
+    }
+
+    fn set_button_colour(&self, button: ButtonLighting, colour: RGBA) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.sender.send(ButtonColour(button, colour, tx))?;
+        rx.recv()??;
+        Ok(())
+    }
+
+    fn handle_button(&mut self, button: Buttons) -> Result<()> {
+        let pages = self.get_page_count();
+        match button {
+            Buttons::PageLeft => {
+                self.active_page = (self.active_page + pages - 1) % pages;
+                self.refresh_page()?;
+            }
+            Buttons::PageRight => {
+                self.active_page = (self.active_page + 1) % pages;
+                self.refresh_page()?;
+            }
+            Buttons::Dial1 | Buttons::Dial2 | Buttons::Dial3 | Buttons::Dial4 => {
+                let offset = match button {
+                    Buttons::Dial1 => 0,
+                    Buttons::Dial2 => 1,
+                    Buttons::Dial3 => 2,
+                    _ => 3,
+                };
+                let index = self.get_page_range().start + offset;
+                if index < self.channels.len() {
+                    self.toggle_mute(index)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_dial(&mut self, dial: Dials, change: i8) -> Result<()> {
+        session::record_dial(dial, change);
+
+        let offset = match dial {
+            Dials::Dial1 => 0,
+            Dials::Dial2 => 1,
+            Dials::Dial3 => 2,
+            Dials::Dial4 => 3,
+        };
+
+        let index = self.get_page_range().start + offset;
+        let Some(channel) = self.channels.get_mut(index) else {
+            return Ok(());
+        };
+
+        let renderer = &mut channel.renderer;
+        let volume = (renderer.volumes[Mix::A] as i16 + change as i16).clamp(0, 100) as u8;
+        renderer.volumes[Mix::A] = volume;
+        integrations::volume_changed(&renderer.title, volume);
+
+        if !self.is_suspended() {
+            let image = self.channels[index].renderer.get_volume(Mix::A)?;
+            let (x, y) = image.position;
+            self.send_channel_image(offset, image.image, x, y)?;
+        }
+        Ok(())
+    }
+
+    fn is_suspended(&self) -> bool {
+        *self.suspended_rx.borrow()
+    }
+
+    fn is_power_saving(&self) -> bool {
+        *self.power_saving_rx.borrow()
+    }
+}
+
+pub fn spawn_demo_handler(
+    sender: Sender<ControlMessage>,
+    device: DeviceType,
+    input_rx: Receiver<Interactions>,
+    stop_rx: watch::Receiver<()>,
+    suspended_rx: watch::Receiver<bool>,
+    handles: DisplayHandles,
+) -> JoinHandle<()> {
+    let mut handler = DemoHandler::new(device, sender, input_rx, stop_rx, suspended_rx, handles);
+    runtime().spawn(async move { handler.run_handler().await })
+}
@@ -0,0 +1,251 @@
+/* A read-only stream of state changes, for overlays such as an OBS browser source which want to
+   show what the mic is doing. Nothing can be changed through it, so unlike the control surface
+   endpoint it doesn't need a token, but it's still off by default and only listens on localhost.
+
+   Connecting with a WebSocket gets a stream of JSON text frames, each with a 'type':
+     {"type":"hello","version":1}
+     {"type":"mute","muted":true}
+     {"type":"device","serial":"...","device":"BeacnMic","connected":true}
+     {"type":"volume","channel":"Music","volume":75}
+     {"type":"lighting","serial":"...","mode":"Solid","colour":[255,0,0],"brightness":100}
+
+   'mute' is whether the mic is muted, either by the panic mute or with a Mic's own mute button.
+   A new connection is sent the current state of everything straight away, then each change as it
+   happens. Anything else on the same port (such as opening it in a browser) gets a small test
+   page which shows the events as they arrive.
+*/
+
+use crate::integrations::websocket::{self, Server, ServerSettings, ServerStatus, send};
+use crate::{integrations, settings};
+use anyhow::{Result, bail};
+use futures_util::StreamExt;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc as tokio_mpsc;
+use tokio::time;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+
+// How long a new connection has to send its request before it's dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Enough for a burst of volume changes, a client that falls further behind skips ahead
+const EVENT_BUFFER: usize = 256;
+
+/// Bumped whenever a change would break an existing client
+const PROTOCOL_VERSION: u32 = 1;
+
+const TEST_PAGE: &str = include_str!("../../resources/events/test.html");
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventStreamSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for EventStreamSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 4461,
+        }
+    }
+}
+
+impl ServerSettings for EventStreamSettings {
+    fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Hello {
+        version: u32,
+    },
+    Mute {
+        muted: bool,
+    },
+    Device {
+        serial: String,
+        device: String,
+        connected: bool,
+    },
+    Volume {
+        channel: String,
+        volume: u8,
+    },
+    Lighting {
+        serial: String,
+        mode: String,
+        colour: [u8; 3],
+        brightness: i32,
+    },
+}
+
+impl Event {
+    // Events with the same key replace each other in the current state
+    fn key(&self) -> Option<String> {
+        match self {
+            Event::Hello { .. } => None,
+            Event::Mute { .. } => Some(String::from("mute")),
+            Event::Device { serial, .. } => Some(format!("device:{serial}")),
+            Event::Volume { channel, .. } => Some(format!("volume:{channel}")),
+            Event::Lighting { serial, .. } => Some(format!("lighting:{serial}")),
+        }
+    }
+}
+
+static SERVER: Server = Server::new("Event Stream");
+
+static EVENTS: LazyLock<broadcast::Sender<Event>> =
+    LazyLock::new(|| broadcast::channel(EVENT_BUFFER).0);
+
+// The latest event for everything we know about, sent to each client as it connects
+static CURRENT: LazyLock<Mutex<BTreeMap<String, Event>>> = LazyLock::new(Default::default);
+
+/// The state of the stream, for display in the UI
+pub fn status() -> ServerStatus {
+    SERVER.status()
+}
+
+/// How many clients are connected
+pub fn clients() -> usize {
+    SERVER.clients()
+}
+
+/// Sends an event to every client, unless it's the same as the last one for that thing
+pub fn publish(event: Event) {
+    if let Some(key) = event.key()
+        && let Ok(mut current) = CURRENT.lock()
+    {
+        if current.get(&key) == Some(&event) {
+            return;
+        }
+
+        // There's no point telling new clients about a device which has gone
+        if let Event::Device {
+            serial,
+            connected: false,
+            ..
+        } = &event
+        {
+            current.remove(&key);
+            current.remove(&format!("lighting:{serial}"));
+        } else {
+            current.insert(key, event.clone());
+        }
+    }
+
+    // This only fails if nobody is listening
+    let _ = EVENTS.send(event);
+}
+
+pub fn spawn_event_stream_handler(stop_rx: tokio_mpsc::Receiver<()>) -> Result<()> {
+    let settings = || Some(settings::get().event_stream).filter(|events| events.enabled);
+    SERVER.run(stop_rx, settings, |stream, _| handle_connection(stream))
+}
+
+async fn handle_connection(stream: TcpStream) -> Result<()> {
+    if !time::timeout(REQUEST_TIMEOUT, is_websocket(&stream)).await?? {
+        return serve_test_page(stream).await;
+    }
+
+    let mut socket = websocket::accept(stream).await?;
+    let _client = SERVER.client();
+    run_connection(&mut socket).await
+}
+
+// Looks at the request without reading it, so the WebSocket handshake still gets all of it
+async fn is_websocket(stream: &TcpStream) -> Result<bool> {
+    let mut buffer = [0; 4096];
+    loop {
+        let read = stream.peek(&mut buffer).await?;
+        if read == 0 {
+            bail!("Connection closed before sending a request");
+        }
+
+        let request = String::from_utf8_lossy(&buffer[..read]).to_lowercase();
+        if request.contains("\r\n\r\n") || read == buffer.len() {
+            return Ok(request.contains("upgrade: websocket"));
+        }
+
+        // Peeking doesn't wait for more than what's already arrived
+        time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+async fn serve_test_page(mut stream: TcpStream) -> Result<()> {
+    let mut buffer = [0; 4096];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+    let (status, body) = match path {
+        "/" | "/index.html" => ("200 OK", TEST_PAGE),
+        _ => ("404 Not Found", "Not Found"),
+    };
+    let content_type = match status {
+        "200 OK" => "text/html; charset=utf-8",
+        _ => "text/plain; charset=utf-8",
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+async fn run_connection(socket: &mut WebSocketStream<TcpStream>) -> Result<()> {
+    // Subscribe before reading the current state, so nothing can be missed in between
+    let mut events_rx = EVENTS.subscribe();
+    let current: Vec<Event> = match CURRENT.lock() {
+        Ok(current) => current.values().cloned().collect(),
+        Err(_) => vec![],
+    };
+    let muted = integrations::is_muted();
+
+    let hello = Event::Hello {
+        version: PROTOCOL_VERSION,
+    };
+    send(socket, &hello).await?;
+
+    // The mute is always sent, even if it's not changed since the app started
+    send(socket, &Event::Mute { muted }).await?;
+    for event in current.iter().filter(|e| !matches!(e, Event::Mute { .. })) {
+        send(socket, event).await?;
+    }
+
+    loop {
+        tokio::select! {
+            message = socket.next() => {
+                // Nothing the client sends means anything, other than it going away
+                match message {
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.into()),
+                }
+            }
+            event = events_rx.recv() => {
+                match event {
+                    Ok(event) => send(socket, &event).await?,
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("Event Stream client fell behind, skipped {skipped} events");
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+/*
+  Turns presses of the dial buttons into gestures (a press, a double press, or a press and
+  hold), each of which can be bound to an action by the user.
+
+  A single press normally fires as soon as the button is released, but if something is bound
+  to a double press, we have to wait a moment to see whether a second press follows.
+*/
+
+use crate::integrations::acceleration::DialAcceleration;
+use beacn_lib::controller::Buttons;
+use enum_map::EnumMap;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use strum_macros::{Display, EnumIter};
+
+// How long a button needs to be held for it to count as a long press
+pub const LONG_PRESS_TIME: Duration = Duration::from_millis(500);
+
+// How soon the second press needs to follow the first to count as a double press
+pub const DOUBLE_PRESS_TIME: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display, EnumIter)]
+pub enum DialGesture {
+    Press,
+    #[strum(to_string = "Double Press")]
+    DoublePress,
+    #[strum(to_string = "Long Press")]
+    LongPress,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Display, EnumIter, Serialize, Deserialize)]
+pub enum DialAction {
+    #[default]
+    #[strum(to_string = "Nothing")]
+    None,
+
+    #[strum(to_string = "Toggle Mute")]
+    Mute,
+
+    #[strum(to_string = "Toggle Audience Mute")]
+    MuteAudience,
+
+    #[strum(to_string = "Set to Default Volume")]
+    DefaultVolume,
+
+    #[strum(to_string = "Switch Mix")]
+    SwitchMix,
+
+    #[strum(to_string = "Switch Inputs / Outputs")]
+    SwitchChannels,
+
+    #[strum(to_string = "Next Page")]
+    NextPage,
+
+    #[strum(to_string = "Previous Page")]
+    PreviousPage,
+
+    #[strum(to_string = "First Page")]
+    FirstPage,
+}
+
+/// The actions bound to each dial gesture, shared by all four dials
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DialGestures {
+    pub press: DialAction,
+    pub double_press: DialAction,
+    pub long_press: DialAction,
+
+    /// The volume used by the 'Set to Default Volume' action
+    pub default_volume: u8,
+
+    /// How long (in milliseconds) holding the Mix button takes to fade Mix A to Mix B, 0 to
+    /// disable the fade
+    pub mix_crossfade: u16,
+
+    /// How far the volume moves as the dials are turned
+    pub acceleration: DialAcceleration,
+}
+
+impl Default for DialGestures {
+    // Matches how the dials behaved before they could be configured
+    fn default() -> Self {
+        Self {
+            press: DialAction::Mute,
+            double_press: DialAction::None,
+            long_press: DialAction::SwitchChannels,
+            default_volume: 75,
+            mix_crossfade: 1000,
+            acceleration: DialAcceleration::default(),
+        }
+    }
+}
+
+impl DialGestures {
+    pub fn action(&self, gesture: DialGesture) -> DialAction {
+        match gesture {
+            DialGesture::Press => self.press,
+            DialGesture::DoublePress => self.double_press,
+            DialGesture::LongPress => self.long_press,
+        }
+    }
+
+    pub fn action_mut(&mut self, gesture: DialGesture) -> &mut DialAction {
+        match gesture {
+            DialGesture::Press => &mut self.press,
+            DialGesture::DoublePress => &mut self.double_press,
+            DialGesture::LongPress => &mut self.long_press,
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+struct PressState {
+    pressed_at: Option<Instant>,
+    long_handled: bool,
+
+    // Set when a press has been released, and we're waiting to see if another follows
+    released_at: Option<Instant>,
+    second_press: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct GestureDetector {
+    states: EnumMap<Buttons, PressState>,
+}
+
+impl GestureDetector {
+    /// Whether this button is one we detect gestures on
+    pub fn handles(button: Buttons) -> bool {
+        matches!(
+            button,
+            Buttons::Dial1 | Buttons::Dial2 | Buttons::Dial3 | Buttons::Dial4
+        )
+    }
+
+    pub fn press(&mut self, button: Buttons) {
+        let state = &mut self.states[button];
+        let now = Instant::now();
+
+        state.second_press = state
+            .released_at
+            .take()
+            .is_some_and(|released| now - released <= DOUBLE_PRESS_TIME);
+        state.pressed_at = Some(now);
+        state.long_handled = false;
+    }
+
+    /// Whether this button is currently held down
+    pub fn is_held(&self, button: Buttons) -> bool {
+        self.states[button].pressed_at.is_some()
+    }
+
+    /// Stops the current press from triggering anything, for when the button is being used to
+    /// change how something else behaves
+    pub fn cancel(&mut self, button: Buttons) {
+        let state = &mut self.states[button];
+        state.long_handled = true;
+        state.second_press = false;
+    }
+
+    pub fn release(&mut self, button: Buttons, bindings: &DialGestures) -> Option<DialGesture> {
+        let state = &mut self.states[button];
+        state.pressed_at.take()?;
+
+        if state.long_handled {
+            return None;
+        }
+        if state.second_press {
+            state.second_press = false;
+            return Some(DialGesture::DoublePress);
+        }
+
+        // Nothing to wait for, so don't delay the press
+        if bindings.double_press == DialAction::None {
+            return Some(DialGesture::Press);
+        }
+        state.released_at = Some(Instant::now());
+        None
+    }
+
+    /// Checks for buttons which have been held, or single presses which weren't followed up
+    pub fn tick(&mut self, bindings: &DialGestures) -> Vec<(Buttons, DialGesture)> {
+        let mut gestures = vec![];
+        for (button, state) in self.states.iter_mut() {
+            if let Some(pressed) = state.pressed_at
+                && !state.long_handled
+                && pressed.elapsed() > LONG_PRESS_TIME
+                && bindings.long_press != DialAction::None
+            {
+                state.long_handled = true;
+                state.second_press = false;
+                gestures.push((button, DialGesture::LongPress));
+            }
+
+            if let Some(released) = state.released_at
+                && released.elapsed() > DOUBLE_PRESS_TIME
+            {
+                state.released_at = None;
+                gestures.push((button, DialGesture::Press));
+            }
+        }
+        gestures
+    }
+}
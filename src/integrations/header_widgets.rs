@@ -0,0 +1,293 @@
+// Small widgets which can be shown either side of the logo in the Mix header: a clock, a stream
+// timer, whatever's currently playing, and the live OBS scene.
+//
+// The stream timer is shared by every device, and is started and stopped from the tray or over
+// IPC. Now playing comes from MPRIS, which we read through playerctl rather than talking to DBus
+// directly, it's polled in the background only while something is showing it.
+//
+// While the battery saver is active, the widgets are only checked (and Now Playing polled) every
+// POWER_SAVE_INTERVAL, so the clock and timer don't keep waking the screen.
+//
+// Each handler keeps a HeaderWidgetState, which works out what each widget should say, and only
+// reports the ones which have changed so they can be drawn as partial updates.
+
+use crate::integrations::obs;
+use crate::managers::power;
+use crate::tools;
+use chrono::Local;
+use enum_map::{Enum, EnumMap};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use strum_macros::{Display, EnumIter, EnumString};
+use tokio::sync::watch;
+
+/// How often handlers should check whether the widgets need redrawing
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// How often playerctl is asked what's playing, and how long we keep asking after the last reader
+const NOW_PLAYING_INTERVAL: Duration = Duration::from_secs(2);
+const NOW_PLAYING_IDLE: Duration = Duration::from_secs(10);
+
+// How often the widgets are updated while saving power
+const POWER_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Display, EnumIter, Serialize, Deserialize)]
+pub enum HeaderWidget {
+    #[default]
+    #[strum(to_string = "Nothing")]
+    None,
+
+    Clock,
+
+    #[strum(to_string = "Stream Timer")]
+    Timer,
+
+    #[strum(to_string = "Now Playing")]
+    NowPlaying,
+
+    #[strum(to_string = "OBS Scene")]
+    ObsScene,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Enum, Display, EnumIter)]
+pub enum HeaderSlot {
+    Left,
+    Right,
+}
+
+/// The widget shown in each side of the header
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeaderWidgets {
+    pub left: HeaderWidget,
+    pub right: HeaderWidget,
+}
+
+impl HeaderWidgets {
+    pub fn get(&self, slot: HeaderSlot) -> HeaderWidget {
+        match slot {
+            HeaderSlot::Left => self.left,
+            HeaderSlot::Right => self.right,
+        }
+    }
+
+    pub fn get_mut(&mut self, slot: HeaderSlot) -> &mut HeaderWidget {
+        match slot {
+            HeaderSlot::Left => &mut self.left,
+            HeaderSlot::Right => &mut self.right,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum TimerAction {
+    Start,
+    Stop,
+    Toggle,
+    Reset,
+}
+
+#[derive(Debug, Default)]
+struct StreamTimer {
+    started: Option<Instant>,
+
+    // Time from previous runs, so stopping and starting again carries on where it left off
+    elapsed: Duration,
+}
+
+static STREAM_TIMER: LazyLock<Mutex<StreamTimer>> = LazyLock::new(Default::default);
+
+/// Starts, stops or resets the stream timer
+pub fn timer_action(action: TimerAction) {
+    debug!("Stream Timer: {action:?}");
+    let Ok(mut timer) = STREAM_TIMER.lock() else {
+        return;
+    };
+
+    let running = timer.started.is_some();
+    match action {
+        TimerAction::Start if !running => timer.started = Some(Instant::now()),
+        TimerAction::Stop if running => {
+            let started = timer.started.take().unwrap_or_else(Instant::now);
+            timer.elapsed += started.elapsed();
+        }
+        TimerAction::Toggle => {
+            let action = if running {
+                TimerAction::Stop
+            } else {
+                TimerAction::Start
+            };
+            drop(timer);
+            timer_action(action);
+        }
+        TimerAction::Reset => {
+            timer.elapsed = Duration::ZERO;
+            timer.started = timer.started.map(|_| Instant::now());
+        }
+        _ => {}
+    }
+}
+
+fn timer_text() -> String {
+    let elapsed = STREAM_TIMER.lock().map_or(Duration::ZERO, |timer| {
+        timer.elapsed + timer.started.map_or(Duration::ZERO, |s| s.elapsed())
+    });
+    let seconds = elapsed.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+#[derive(Debug, Default)]
+struct NowPlaying {
+    text: Option<String>,
+    last_read: Option<Instant>,
+    polling: bool,
+}
+
+static NOW_PLAYING: LazyLock<RwLock<NowPlaying>> = LazyLock::new(Default::default);
+
+// Only worth warning about once, it'll still be missing on the next poll
+static PLAYERCTL_MISSING: AtomicBool = AtomicBool::new(false);
+
+fn now_playing_text() -> String {
+    let mut start = false;
+    if let Ok(mut playing) = NOW_PLAYING.write() {
+        playing.last_read = Some(Instant::now());
+        start = !playing.polling;
+        playing.polling = true;
+    }
+    if start {
+        thread::spawn(poll_now_playing);
+    }
+
+    let playing = NOW_PLAYING.read();
+    playing
+        .ok()
+        .and_then(|p| p.text.clone())
+        .unwrap_or_default()
+}
+
+fn poll_now_playing() {
+    debug!("Starting Now Playing polling");
+    loop {
+        let format = "{{artist}} - {{title}}";
+        let output = Command::new("playerctl")
+            .args(["metadata", "--format", format])
+            .output();
+        let text = match output {
+            Ok(output) if output.status.success() => {
+                let text = String::from_utf8_lossy(&output.stdout);
+                let text = text.trim().trim_start_matches("- ");
+                (!text.is_empty()).then(|| text.to_string())
+            }
+
+            // Say so on the screen, otherwise the widget just looks like nothing's playing
+            Err(e) if tools::is_missing(&e) => {
+                if !PLAYERCTL_MISSING.swap(true, Ordering::Relaxed) {
+                    warn!("Unable to show what's playing, playerctl isn't installed");
+                }
+                Some(String::from("playerctl isn't installed"))
+            }
+            _ => None,
+        };
+
+        let Ok(mut playing) = NOW_PLAYING.write() else {
+            return;
+        };
+        playing.text = text;
+
+        // Nobody's looking, so stop until someone asks again
+        if playing
+            .last_read
+            .is_none_or(|r| r.elapsed() > NOW_PLAYING_IDLE)
+        {
+            debug!("Stopping Now Playing polling");
+            playing.polling = false;
+            return;
+        }
+        drop(playing);
+        thread::sleep(match power::is_saving() {
+            true => POWER_SAVE_INTERVAL,
+            false => NOW_PLAYING_INTERVAL,
+        });
+    }
+}
+
+pub struct HeaderWidgetState {
+    settings_rx: watch::Receiver<HeaderWidgets>,
+    settings: HeaderWidgets,
+
+    // What was last drawn in each slot, and when they were last checked
+    shown: EnumMap<HeaderSlot, Option<String>>,
+    last_check: Option<Instant>,
+}
+
+impl HeaderWidgetState {
+    pub fn new(settings_rx: watch::Receiver<HeaderWidgets>) -> Self {
+        let settings = *settings_rx.borrow();
+        Self {
+            settings_rx,
+            settings,
+            shown: EnumMap::default(),
+            last_check: None,
+        }
+    }
+
+    /// The text for every slot, for use when the whole screen is being drawn
+    pub fn current(&mut self) -> Vec<(HeaderSlot, String)> {
+        self.settings = *self.settings_rx.borrow_and_update();
+
+        let mut widgets = vec![];
+        for (slot, shown) in self.shown.iter_mut() {
+            let text = Self::text(self.settings.get(slot));
+            *shown = Some(text.clone());
+            widgets.push((slot, text));
+        }
+        widgets
+    }
+
+    /// Returns the slots whose text has changed since they were last drawn, a slot which has
+    /// been turned off comes back with empty text, so the header can be drawn back over it
+    pub fn changed(&mut self) -> Vec<(HeaderSlot, String)> {
+        if self.settings_rx.has_changed().unwrap_or(false) {
+            self.settings = *self.settings_rx.borrow_and_update();
+        } else if power::is_saving()
+            && self
+                .last_check
+                .is_some_and(|last| last.elapsed() < POWER_SAVE_INTERVAL)
+        {
+            return vec![];
+        }
+        self.last_check = Some(Instant::now());
+
+        let mut widgets = vec![];
+        for (slot, shown) in self.shown.iter_mut() {
+            let text = Self::text(self.settings.get(slot));
+            if shown.as_ref() != Some(&text) {
+                *shown = Some(text.clone());
+                widgets.push((slot, text));
+            }
+        }
+        widgets
+    }
+
+    fn text(widget: HeaderWidget) -> String {
+        match widget {
+            HeaderWidget::None => String::new(),
+            HeaderWidget::Clock => Local::now().format("%H:%M").to_string(),
+            HeaderWidget::Timer => timer_text(),
+            HeaderWidget::NowPlaying => now_playing_text(),
+            HeaderWidget::ObsScene => obs::current_scene().unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,308 @@
+/*
+  Keeps the Studio's headphone level and the volume of a Pipeweaver target in step, so turning
+  the knob on the Studio moves the target in Pipeweaver, and moving the target in Pipeweaver
+  turns the headphones up or down.
+
+  Neither side tells us when it changes, so both are read on an interval and compared against
+  the last values seen. To stop the two bouncing a change back and forth, whenever one side is
+  changed to match the other, the value it was changed to is recorded as 'seen'. When it's read
+  back on the next poll it matches, so isn't treated as a new change. The device may round the
+  level it's given slightly, so small differences in level are ignored.
+
+  The sync runs on its own thread, so it keeps going while the window is closed to the tray.
+*/
+
+use crate::device_manager::AudioMessage;
+use crate::integrations::pipeweaver::{send_daemon_command, send_daemon_request};
+use crate::{run_async_blocking, safe_mode};
+use anyhow::{Result, bail};
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::headphones::{HPLevel, Headphones};
+use beacn_lib::crossbeam::channel::{self, RecvTimeoutError, Sender};
+use beacn_lib::manager::DeviceType;
+use beacn_lib::types::ToInner;
+use log::{debug, warn};
+use pipeweaver_ipc::commands::APICommand::SetTargetVolume;
+use pipeweaver_ipc::commands::{DaemonRequest, DaemonResponse};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::watch;
+use ulid::Ulid;
+
+// How often both sides are read while syncing, and how often the list of targets is refreshed
+// while not
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const IDLE_INTERVAL: Duration = Duration::from_secs(5);
+
+// The range of the headphone level, in dB
+const MIN_LEVEL: f32 = -70.0;
+const MAX_LEVEL: f32 = 0.0;
+
+// Level changes smaller than this are the device rounding what it was sent
+const LEVEL_TOLERANCE: f32 = 0.5;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct HeadphoneSyncSettings {
+    /// The Pipeweaver target kept in step with the headphones, `None` turns the sync off
+    pub target: Option<Ulid>,
+
+    /// The loudest the headphones are allowed to go, changes from Pipeweaver are capped at this
+    pub max_level: Option<f32>,
+}
+
+/// A Pipeweaver target the headphones can be synced with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncTarget {
+    pub id: Ulid,
+    pub name: String,
+}
+
+/// A running sync for a single device, which stops when the last copy is dropped
+#[derive(Debug, Default, Clone)]
+pub struct HeadphoneSync {
+    worker: Option<Arc<SyncWorker>>,
+    level: Option<watch::Receiver<Option<f32>>>,
+}
+
+#[derive(Debug)]
+struct SyncWorker {
+    // Never sent on, the worker stops when this is dropped
+    _stop: Sender<()>,
+    settings: watch::Sender<HeadphoneSyncSettings>,
+    targets: watch::Receiver<Option<Vec<SyncTarget>>>,
+}
+
+impl HeadphoneSync {
+    pub fn spawn(
+        sender: Sender<AudioMessage>,
+        device_type: DeviceType,
+        settings: HeadphoneSyncSettings,
+    ) -> Self {
+        let Some(fetch) = Message::generate_fetch_message(device_type)
+            .into_iter()
+            .find(|m| matches!(m, Message::Headphones(Headphones::HeadphoneLevel(_))))
+        else {
+            warn!("{device_type:?} has no headphone level, unable to sync it");
+            return Self::default();
+        };
+
+        let (stop, stop_rx) = channel::bounded(0);
+        let (settings, settings_rx) = watch::channel(settings);
+        let (targets_tx, targets) = watch::channel(None);
+        let (level_tx, level) = watch::channel(None);
+
+        thread::spawn(move || {
+            let mut worker = Worker {
+                sender,
+                fetch,
+                settings_rx,
+                targets_tx,
+                level_tx,
+                state: SyncState::default(),
+            };
+            loop {
+                let interval = match worker.settings_rx.borrow().target {
+                    Some(_) => POLL_INTERVAL,
+                    None => IDLE_INTERVAL,
+                };
+                match stop_rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => worker.poll(),
+                    _ => break,
+                }
+            }
+            debug!("Headphone Sync Stopped");
+        });
+
+        Self {
+            worker: Some(Arc::new(SyncWorker {
+                _stop: stop,
+                settings,
+                targets,
+            })),
+            level: Some(level),
+        }
+    }
+
+    pub fn set_settings(&self, settings: HeadphoneSyncSettings) {
+        if let Some(worker) = &self.worker {
+            worker.settings.send_if_modified(|current| {
+                let changed = *current != settings;
+                *current = settings;
+                changed
+            });
+        }
+    }
+
+    /// The targets available in Pipeweaver, or `None` if it couldn't be reached
+    pub fn targets(&self) -> Option<Vec<SyncTarget>> {
+        self.worker.as_ref()?.targets.borrow().clone()
+    }
+
+    /// The headphone level, if it's been changed since this was last called
+    pub fn changed_level(&mut self) -> Option<f32> {
+        let level = self.level.as_mut()?;
+        match level.has_changed() {
+            Ok(true) => *level.borrow_and_update(),
+            _ => None,
+        }
+    }
+}
+
+struct Worker {
+    sender: Sender<AudioMessage>,
+    fetch: Message,
+    settings_rx: watch::Receiver<HeadphoneSyncSettings>,
+    targets_tx: watch::Sender<Option<Vec<SyncTarget>>>,
+    level_tx: watch::Sender<Option<f32>>,
+    state: SyncState,
+}
+
+impl Worker {
+    fn poll(&mut self) {
+        // A new target starts again, matching it to the headphones
+        if self.settings_rx.has_changed().unwrap_or_default() {
+            self.state = SyncState::default();
+        }
+        let settings = *self.settings_rx.borrow_and_update();
+
+        let targets = match safe_mode() {
+            true => None,
+            false => run_async_blocking(fetch_targets())
+                .inspect_err(|e| debug!("Unable to fetch Pipeweaver targets: {e}"))
+                .ok(),
+        };
+        let volume = targets.as_ref().zip(settings.target).and_then(|(t, id)| {
+            t.iter()
+                .find(|(target, _)| target.id == id)
+                .map(|(_, volume)| *volume)
+        });
+        let targets: Option<Vec<_>> = targets.map(|t| t.into_iter().map(|(t, _)| t).collect());
+        self.targets_tx.send_if_modified(|current| {
+            let changed = *current != targets;
+            *current = targets;
+            changed
+        });
+
+        let (Some(target), Some(volume)) = (settings.target, volume) else {
+            return;
+        };
+        let level = match self.send(self.fetch) {
+            Ok(level) => level,
+            Err(e) => {
+                debug!("Unable to read headphone level: {e}");
+                return;
+            }
+        };
+
+        match self.state.update(level, volume, settings.max_level) {
+            Some(Change::Pipeweaver(volume)) => {
+                debug!("Headphones moved to {level}dB, setting Pipeweaver to {volume}%");
+                let request = DaemonRequest::Pipewire(SetTargetVolume(target, volume));
+                if let Err(e) = run_async_blocking(send_daemon_command(request)) {
+                    warn!("Unable to set Pipeweaver target volume: {e}");
+                }
+            }
+            Some(Change::Device(level)) => {
+                debug!("Pipeweaver moved to {volume}%, setting headphones to {level}dB");
+                let message = Message::Headphones(Headphones::HeadphoneLevel(HPLevel(level)));
+                if let Err(e) = self.send(message) {
+                    warn!("Unable to set headphone level: {e}");
+                }
+            }
+            None => {}
+        }
+        self.level_tx.send_if_modified(|current| {
+            let level = Some(self.state.level.unwrap_or(level));
+            let changed = *current != level;
+            *current = level;
+            changed
+        });
+    }
+
+    // Sends a message to the device, returning the headphone level it responded with
+    fn send(&self, message: Message) -> Result<f32> {
+        let (tx, rx) = oneshot::channel();
+        self.sender.send(AudioMessage::Handle(message, tx))?;
+        match rx.recv()?? {
+            Message::Headphones(Headphones::HeadphoneLevel(level)) => Ok(level.to_inner()),
+            other => bail!("Unexpected response: {other:?}"),
+        }
+    }
+}
+
+async fn fetch_targets() -> Result<Vec<(SyncTarget, u8)>> {
+    let DaemonResponse::Status(status) = send_daemon_request(DaemonRequest::GetStatus).await?
+    else {
+        bail!("Unexpected response to status request");
+    };
+
+    let targets = &status.audio.profile.devices.targets;
+    let physical = targets
+        .physical_devices
+        .iter()
+        .map(|d| (&d.description, d.volume));
+    let virtual_devices = targets
+        .virtual_devices
+        .iter()
+        .map(|d| (&d.description, d.volume));
+    let targets = physical.chain(virtual_devices).map(|(desc, volume)| {
+        let target = SyncTarget {
+            id: desc.id,
+            name: desc.name.clone(),
+        };
+        (target, volume)
+    });
+    Ok(targets.collect())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Change {
+    Pipeweaver(u8),
+    Device(f32),
+}
+
+// The last values seen on each side, including ones we set to match the other
+#[derive(Debug, Default)]
+struct SyncState {
+    level: Option<f32>,
+    volume: Option<u8>,
+}
+
+impl SyncState {
+    fn update(&mut self, level: f32, volume: u8, max_level: Option<f32>) -> Option<Change> {
+        let level_changed = self
+            .level
+            .is_none_or(|last| (last - level).abs() >= LEVEL_TOLERANCE);
+        let volume_changed = self.volume.is_some_and(|last| last != volume);
+
+        // If both have moved, or we're just starting, the headphones win
+        if level_changed {
+            let target = level_to_volume(level);
+            self.level = Some(level);
+            self.volume = Some(target);
+            return (target != volume).then_some(Change::Pipeweaver(target));
+        }
+
+        if volume_changed {
+            let mut target = volume_to_level(volume);
+            if let Some(max) = max_level {
+                target = target.min(max);
+            }
+            self.volume = Some(volume);
+            self.level = Some(target);
+            return Some(Change::Device(target));
+        }
+        None
+    }
+}
+
+fn level_to_volume(level: f32) -> u8 {
+    let amount = (level.clamp(MIN_LEVEL, MAX_LEVEL) - MIN_LEVEL) / (MAX_LEVEL - MIN_LEVEL);
+    (amount * 100.0).round() as u8
+}
+
+fn volume_to_level(volume: u8) -> f32 {
+    let amount = volume.min(100) as f32 / 100.0;
+    MIN_LEVEL + amount * (MAX_LEVEL - MIN_LEVEL)
+}
@@ -0,0 +1,94 @@
+// The Mix and Mix Create go to sleep if they don't hear from us for a while, so handlers send a
+// keepalive on an interval. The interval can be changed per device, keepalives can be turned off
+// entirely, or stopped after a period without interaction so the device can sleep on its own.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::select;
+use tokio::sync::watch;
+use tokio::time::{self, Interval, MissedTickBehavior};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeepAliveSettings {
+    pub enabled: bool,
+
+    /// Seconds between each keepalive
+    pub interval_secs: u32,
+
+    /// Minutes without any interaction before keepalives stop, 0 keeps them going forever
+    pub sleep_minutes: u32,
+}
+
+impl Default for KeepAliveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 10,
+            sleep_minutes: 0,
+        }
+    }
+}
+
+impl KeepAliveSettings {
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs.max(1) as u64)
+    }
+}
+
+pub struct KeepAlive {
+    settings_rx: watch::Receiver<KeepAliveSettings>,
+    settings: KeepAliveSettings,
+    interval: Interval,
+    last_activity: Instant,
+}
+
+impl KeepAlive {
+    pub fn new(settings_rx: watch::Receiver<KeepAliveSettings>) -> Self {
+        let settings = *settings_rx.borrow();
+        Self {
+            settings_rx,
+            settings,
+            interval: Self::create_interval(settings),
+            last_activity: Instant::now(),
+        }
+    }
+
+    /// Waits until the next keepalive is due, this may not mean one should be sent (see
+    /// `should_send`), but the interval is kept running so it picks up again straight away.
+    pub async fn tick(&mut self) {
+        loop {
+            select! {
+                _ = self.interval.tick() => return,
+                Ok(_) = self.settings_rx.changed() => {
+                    let settings = *self.settings_rx.borrow_and_update();
+                    if settings.interval() != self.settings.interval() {
+                        self.interval = Self::create_interval(settings);
+                    }
+                    self.settings = settings;
+                }
+            }
+        }
+    }
+
+    /// Whether a keepalive should be sent on this tick
+    pub fn should_send(&self) -> bool {
+        if !self.settings.enabled {
+            return false;
+        }
+
+        let sleep = self.settings.sleep_minutes;
+        sleep == 0 || self.last_activity.elapsed() < Duration::from_secs(sleep as u64 * 60)
+    }
+
+    /// Notes the user touching the device, which restarts keepalives if they'd stopped
+    pub fn activity(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    fn create_interval(settings: KeepAliveSettings) -> Interval {
+        let mut interval = time::interval(settings.interval());
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        interval
+    }
+}
@@ -1 +1,191 @@
+use crate::device_manager::ControlMessage;
+use crate::integrations::burn_in::BurnInSettings;
+use crate::integrations::gestures::DialGestures;
+use crate::integrations::header_widgets::HeaderWidgets;
+use crate::integrations::keep_alive::KeepAliveSettings;
+use crate::integrations::orientation::Orientation;
+use crate::integrations::pipeweaver::layout::{ScreenLayout, VolumeUnit};
+use crate::{demo_mode, panic_mute, safe_mode, session};
+use beacn_lib::controller::Interactions;
+use beacn_lib::crossbeam::channel::{Receiver, Sender};
+use beacn_lib::manager::DeviceType;
+use beacn_lib::types::RGBA;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum_macros::{Display, EnumIter};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use ulid::Ulid;
+
+pub mod acceleration;
+pub mod burn_in;
+pub mod companion;
+pub mod demo;
+pub mod events;
+pub mod gestures;
+pub mod header_widgets;
+pub mod headphone_sync;
+pub mod keep_alive;
+pub mod mute_sync;
+pub mod obs;
+pub mod orientation;
 pub mod pipeweaver;
+pub mod pipewire;
+pub mod websocket;
+
+/// Which integration is responsible for driving the Mix / Mix Create display and dials
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Display, EnumIter, Serialize, Deserialize,
+)]
+pub enum DisplayBackend {
+    #[default]
+    Pipeweaver,
+
+    #[strum(to_string = "PipeWire (Applications)")]
+    Pipewire,
+
+    #[strum(to_string = "Demo (No Audio)")]
+    Demo,
+}
+
+/// The channel shown on each dial, `None` simply follows the Pipeweaver channel order
+pub type DialAssignment = Option<[Option<Ulid>; 4]>;
+
+/// Colours chosen by the user which replace the Pipeweaver colour of a channel, stored as RGB
+pub type ColourOverrides = HashMap<Ulid, [u8; 3]>;
+
+/// Channels which show their volume in something other than a percentage on the dial
+pub type VolumeUnits = HashMap<Ulid, VolumeUnit>;
+
+/// Colours chosen by the user for the button lights, keyed by the button's name, stored as RGB
+pub type ButtonColours = HashMap<String, [u8; 3]>;
+
+/// Fixed lighting for buttons while Pipeweaver isn't managing them, keyed by the button's name
+pub type StaticLighting = HashMap<String, StaticButton>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticButton {
+    pub colour: [u8; 3],
+
+    /// How bright the button is, as a percentage of the colour
+    pub brightness: u8,
+}
+
+impl Default for StaticButton {
+    fn default() -> Self {
+        Self {
+            colour: [255, 255, 255],
+            brightness: 100,
+        }
+    }
+}
+
+impl StaticButton {
+    pub fn rgba(&self) -> RGBA {
+        let scale = |value: u8| (value as u32 * self.brightness.min(100) as u32 / 100) as u8;
+        RGBA {
+            red: scale(self.colour[0]),
+            green: scale(self.colour[1]),
+            blue: scale(self.colour[2]),
+            alpha: 255,
+        }
+    }
+}
+
+/// A channel which the user can assign to a dial, or recolour
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignableChannel {
+    pub id: Ulid,
+    pub name: String,
+
+    /// The colour of the channel in Pipeweaver, ignoring any override
+    pub colour: [u8; 3],
+
+    /// Outputs can be recoloured, but not assigned to a dial
+    pub is_output: bool,
+}
+
+/// Settings and state shared between the UI and the running display handler. The handler
+/// publishes the channels it knows about, and receives the user's settings.
+pub struct DisplayHandles {
+    pub assignment_rx: watch::Receiver<DialAssignment>,
+    pub gestures_rx: watch::Receiver<DialGestures>,
+    pub burn_in_rx: watch::Receiver<BurnInSettings>,
+    pub header_widgets_rx: watch::Receiver<HeaderWidgets>,
+    pub keep_alive_rx: watch::Receiver<KeepAliveSettings>,
+    pub coalesce_rx: watch::Receiver<bool>,
+    pub performance_rx: watch::Receiver<bool>,
+    pub address_rx: watch::Receiver<Option<String>>,
+    pub orientation_rx: watch::Receiver<Orientation>,
+    pub power_saving_rx: watch::Receiver<bool>,
+    pub colours_rx: watch::Receiver<ColourOverrides>,
+    pub volume_units_rx: watch::Receiver<VolumeUnits>,
+    pub screen_layout_rx: watch::Receiver<ScreenLayout>,
+    pub redraw_rx: watch::Receiver<()>,
+    pub channels_tx: watch::Sender<Vec<AssignableChannel>>,
+}
+
+pub fn spawn_display_handler(
+    backend: DisplayBackend,
+    sender: Sender<ControlMessage>,
+    device: DeviceType,
+    input_rx: Receiver<Interactions>,
+    stop_rx: watch::Receiver<()>,
+    suspended_rx: watch::Receiver<bool>,
+    handles: DisplayHandles,
+) -> JoinHandle<()> {
+    // Safe mode doesn't talk to Pipeweaver, fall back to the applications view instead
+    let backend = match backend {
+        _ if demo_mode() => DisplayBackend::Demo,
+        DisplayBackend::Pipeweaver if safe_mode() => {
+            debug!("Safe Mode, using the PipeWire backend in place of Pipeweaver");
+            DisplayBackend::Pipewire
+        }
+        backend => backend,
+    };
+
+    match backend {
+        DisplayBackend::Pipeweaver => pipeweaver::spawn_pipeweaver_handler(
+            sender,
+            device,
+            input_rx,
+            stop_rx,
+            suspended_rx,
+            handles,
+        ),
+        // Application streams come and go, so these ignore the dial assignment and colours
+        DisplayBackend::Pipewire => pipewire::spawn_pipewire_handler(
+            sender,
+            device,
+            input_rx,
+            stop_rx,
+            suspended_rx,
+            handles,
+        ),
+        DisplayBackend::Demo => {
+            demo::spawn_demo_handler(sender, device, input_rx, stop_rx, suspended_rx, handles)
+        }
+    }
+}
+
+/// Whether the mic is muted, either by the panic mute or by a Mic's own mute
+pub fn is_muted() -> bool {
+    panic_mute::is_active() || mute_sync::any_muted()
+}
+
+/// Called whenever the panic mute or a Mic's own mute changes
+pub fn mute_changed() {
+    let muted = is_muted();
+    session::record_mute(muted);
+    events::publish(events::Event::Mute { muted });
+}
+
+/// Called by the display integrations whenever a channel's volume changes, however it happened
+pub fn volume_changed(channel: &str, volume: u8) {
+    session::record_volume(channel, volume);
+    events::publish(events::Event::Volume {
+        channel: channel.to_string(),
+        volume,
+    });
+}
@@ -0,0 +1,347 @@
+/*
+  Keeps the Mic's hardware mute and the mute on a Pipeweaver source in step, so tapping the mute
+  on the Mic mutes the source in Pipeweaver, and muting the source in Pipeweaver mutes the Mic.
+
+  The Mic's mute isn't one of its settings messages, it shows up as the mute on its PipeWire
+  source, so like the recording tools we find and read it with pw-dump, and change it with wpctl.
+  On the Pipeweaver side it's the source's first mute, which is the same one the panic mute uses.
+
+  The Mic's mute is read even when it isn't being synced, so the UI, session statistics and event
+  stream can follow it, and the mute hooks fire whenever it changes, however it was changed.
+
+  Neither side tells us when it changes, so both are polled, using the same approach as the
+  headphone sync to stop a change bouncing back and forth: whenever one side is changed to match
+  the other, the state it was changed to is recorded as 'seen', so it isn't picked up as a new
+  change when it's read back.
+*/
+
+use crate::hooks::{self, HookEvent};
+use crate::integrations;
+use crate::integrations::pipeweaver::channel::SourceDevice;
+use crate::integrations::pipeweaver::{send_daemon_command, send_daemon_request};
+use crate::tools::tool_error;
+use crate::{run_async_blocking, safe_mode};
+use anyhow::{Result, anyhow, bail};
+use beacn_lib::crossbeam::channel::{self, RecvTimeoutError, Sender};
+use log::{debug, warn};
+use pipeweaver_ipc::commands::APICommand::{AddSourceMuteTarget, DelSourceMuteTarget};
+use pipeweaver_ipc::commands::{DaemonRequest, DaemonResponse};
+use pipeweaver_shared::MuteTarget;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::watch;
+use ulid::Ulid;
+
+// How often both sides are read while syncing, and how often the mute is checked (for the UI)
+// while not
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const IDLE_INTERVAL: Duration = Duration::from_secs(5);
+
+// Serials of the Mics which are currently muted
+static MUTED_MICS: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(Default::default);
+
+/// Whether any connected Mic is muted
+pub fn any_muted() -> bool {
+    MUTED_MICS.lock().is_ok_and(|mics| !mics.is_empty())
+}
+
+fn set_mic_muted(serial: &str, muted: bool) {
+    let changed = match MUTED_MICS.lock() {
+        Ok(mut mics) if muted => mics.insert(serial.to_string()),
+        Ok(mut mics) => mics.remove(serial),
+        Err(_) => false,
+    };
+    if changed {
+        integrations::mute_changed();
+    }
+}
+
+/// A Pipeweaver source the Mic's mute can be synced with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncSource {
+    pub id: Ulid,
+    pub name: String,
+}
+
+/// A running sync for a single Mic, which stops when the last copy is dropped
+#[derive(Debug, Default, Clone)]
+pub struct MuteSync {
+    worker: Option<Arc<SyncWorker>>,
+}
+
+#[derive(Debug)]
+struct SyncWorker {
+    // Never sent on, the worker stops when this is dropped
+    _stop: Sender<()>,
+    source: watch::Sender<Option<Ulid>>,
+    sources: watch::Receiver<Option<Vec<SyncSource>>>,
+    muted: watch::Receiver<Option<bool>>,
+}
+
+impl MuteSync {
+    pub fn spawn(serial: String, source: Option<Ulid>) -> Self {
+        let (stop, stop_rx) = channel::bounded(0);
+        let (source, source_rx) = watch::channel(source);
+        let (sources_tx, sources) = watch::channel(None);
+        let (muted_tx, muted) = watch::channel(None);
+
+        thread::spawn(move || {
+            let mut worker = Worker {
+                serial,
+                source_rx,
+                sources_tx,
+                muted_tx,
+                state: SyncState::default(),
+            };
+            loop {
+                let interval = match *worker.source_rx.borrow() {
+                    Some(_) => POLL_INTERVAL,
+                    None => IDLE_INTERVAL,
+                };
+                match stop_rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => worker.poll(),
+                    _ => break,
+                }
+            }
+            // The Mic's gone, so it no longer counts as muted
+            set_mic_muted(&worker.serial, false);
+            debug!("Mute Sync Stopped");
+        });
+
+        Self {
+            worker: Some(Arc::new(SyncWorker {
+                _stop: stop,
+                source,
+                sources,
+                muted,
+            })),
+        }
+    }
+
+    pub fn set_source(&self, source: Option<Ulid>) {
+        if let Some(worker) = &self.worker {
+            worker.source.send_if_modified(|current| {
+                let changed = *current != source;
+                *current = source;
+                changed
+            });
+        }
+    }
+
+    /// The sources available in Pipeweaver, or `None` if it couldn't be reached
+    pub fn sources(&self) -> Option<Vec<SyncSource>> {
+        self.worker.as_ref()?.sources.borrow().clone()
+    }
+
+    /// Whether the Mic is muted, or `None` if it couldn't be found in PipeWire
+    pub fn is_muted(&self) -> Option<bool> {
+        *self.worker.as_ref()?.muted.borrow()
+    }
+}
+
+struct Worker {
+    serial: String,
+    source_rx: watch::Receiver<Option<Ulid>>,
+    sources_tx: watch::Sender<Option<Vec<SyncSource>>>,
+    muted_tx: watch::Sender<Option<bool>>,
+    state: SyncState,
+}
+
+impl Worker {
+    fn poll(&mut self) {
+        // A new source starts again, matching it to the Mic
+        if self.source_rx.has_changed().unwrap_or_default() {
+            self.state = SyncState::default();
+        }
+        let source = *self.source_rx.borrow_and_update();
+
+        let sources = match safe_mode() {
+            true => None,
+            false => run_async_blocking(fetch_sources())
+                .inspect_err(|e| debug!("Unable to fetch Pipeweaver sources: {e}"))
+                .ok(),
+        };
+        let source_muted = sources.as_ref().zip(source).and_then(|(s, id)| {
+            s.iter()
+                .find(|(source, _)| source.id == id)
+                .map(|(_, muted)| *muted)
+        });
+        let sources: Option<Vec<_>> = sources.map(|s| s.into_iter().map(|(s, _)| s).collect());
+        self.sources_tx.send_if_modified(|current| {
+            let changed = *current != sources;
+            *current = sources;
+            changed
+        });
+
+        let mic = read_mic(&self.serial)
+            .inspect_err(|e| debug!("Unable to read the Mic's mute: {e}"))
+            .ok();
+        self.set_muted(mic.map(|(_, muted)| muted));
+
+        let (Some(source), Some(source_muted), Some((node, mic_muted))) =
+            (source, source_muted, mic)
+        else {
+            return;
+        };
+
+        match self.state.update(mic_muted, source_muted) {
+            Some(Change::Pipeweaver(muted)) => {
+                debug!("Mic mute changed to {muted}, updating Pipeweaver");
+                let command = match muted {
+                    true => AddSourceMuteTarget(source, MuteTarget::TargetA),
+                    false => DelSourceMuteTarget(source, MuteTarget::TargetA),
+                };
+                let request = DaemonRequest::Pipewire(command);
+                if let Err(e) = run_async_blocking(send_daemon_command(request)) {
+                    warn!("Unable to set Pipeweaver source mute: {e}");
+                }
+            }
+            Some(Change::Mic(muted)) => {
+                debug!("Pipeweaver mute changed to {muted}, updating the Mic");
+                let value = if muted { "1" } else { "0" };
+                match wpctl(&["set-mute", &node.to_string(), value]) {
+                    Ok(()) => self.set_muted(Some(muted)),
+                    Err(e) => warn!("Unable to set the Mic's mute: {e}"),
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn set_muted(&self, muted: Option<bool>) {
+        let mut previous = None;
+        let changed = self.muted_tx.send_if_modified(|current| {
+            previous = *current;
+            let changed = *current != muted;
+            *current = muted;
+            changed
+        });
+        if changed {
+            set_mic_muted(&self.serial, muted == Some(true));
+        }
+
+        // Only an actual change counts, not finding the Mic (again) after a replug
+        if let (Some(previous), Some(muted)) = (previous, muted)
+            && previous != muted
+        {
+            let event = match muted {
+                true => HookEvent::MicMuted,
+                false => HookEvent::MicUnmuted,
+            };
+            hooks::fire(
+                event,
+                &[("BEACN_SERIAL", &self.serial), ("BEACN_DEVICE", "Mic")],
+            );
+        }
+    }
+}
+
+async fn fetch_sources() -> Result<Vec<(SyncSource, bool)>> {
+    let DaemonResponse::Status(status) = send_daemon_request(DaemonRequest::GetStatus).await?
+    else {
+        bail!("Unexpected response to status request");
+    };
+
+    let sources = &status.audio.profile.devices.sources;
+    let physical = sources
+        .physical_devices
+        .iter()
+        .map(|d| d as &dyn SourceDevice);
+    let virtual_devices = sources
+        .virtual_devices
+        .iter()
+        .map(|d| d as &dyn SourceDevice);
+    let sources = physical.chain(virtual_devices).map(|device| {
+        let desc = device.description();
+        let source = SyncSource {
+            id: desc.id,
+            name: desc.name.clone(),
+        };
+        let muted = device
+            .mute_states()
+            .mute_state
+            .contains(&MuteTarget::TargetA);
+        (source, muted)
+    });
+    Ok(sources.collect())
+}
+
+// Finds the Mic's PipeWire source, returning its id and whether it's muted
+fn read_mic(serial: &str) -> Result<(u32, bool)> {
+    let output = Command::new("pw-dump")
+        .output()
+        .map_err(tool_error("pw-dump"))?;
+    if !output.status.success() {
+        bail!("pw-dump exited with {}", output.status);
+    }
+    let dump: Value = serde_json::from_slice(&output.stdout)?;
+    let objects = dump
+        .as_array()
+        .ok_or(anyhow!("Unexpected pw-dump output"))?;
+
+    // ALSA nodes are named after the USB device, which includes the serial
+    let node = objects
+        .iter()
+        .filter(|o| o["type"] == "PipeWire:Interface:Node")
+        .filter(|o| o["info"]["props"]["media.class"] == "Audio/Source")
+        .find(|o| {
+            let name = o["info"]["props"]["node.name"].as_str().unwrap_or_default();
+            name.contains(serial)
+        })
+        .ok_or(anyhow!("Unable to find the Mic in PipeWire"))?;
+
+    let id = node["id"].as_u64().ok_or(anyhow!("Mic source has no id"))?;
+    let muted = node["info"]["params"]["Props"][0]["mute"]
+        .as_bool()
+        .unwrap_or(false);
+    Ok((id as u32, muted))
+}
+
+fn wpctl(args: &[&str]) -> Result<()> {
+    let status = Command::new("wpctl")
+        .args(args)
+        .status()
+        .map_err(tool_error("wpctl"))?;
+    if !status.success() {
+        bail!("wpctl {} failed with {status}", args.join(" "));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Change {
+    Pipeweaver(bool),
+    Mic(bool),
+}
+
+// The last mute seen on each side, including ones we set to match the other
+#[derive(Debug, Default)]
+struct SyncState {
+    mic: Option<bool>,
+    source: Option<bool>,
+}
+
+impl SyncState {
+    fn update(&mut self, mic: bool, source: bool) -> Option<Change> {
+        let mic_changed = self.mic != Some(mic);
+        let source_changed = self.source.is_some_and(|last| last != source);
+
+        // If both have changed, or we're just starting, the Mic wins
+        if mic_changed {
+            self.mic = Some(mic);
+            self.source = Some(mic);
+            return (mic != source).then_some(Change::Pipeweaver(mic));
+        }
+
+        if source_changed {
+            self.source = Some(source);
+            self.mic = Some(source);
+            return Some(Change::Mic(source));
+        }
+        None
+    }
+}
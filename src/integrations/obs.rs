@@ -0,0 +1,303 @@
+/* An optional client for OBS Studio's WebSocket server (5.x), which lets the current scene drive
+   a few behaviours:
+     * The primary lighting colour on the Mic / Studio can be set per scene
+     * Switching to a 'BRB' scene mutes everything, and switching away unmutes it again
+     * The current scene can be shown in the Mix header
+
+   Only the small part of the protocol needed for this is implemented, we identify (with a
+   password if OBS requires one), subscribe to scene events, and ask for the current scene once
+   connected. OBS may not be running, so we keep retrying in the background, except while the
+   battery saver is active, when the connection is dropped until it's back on AC power.
+
+   Refs:
+   https://github.com/obsproject/obs-websocket/blob/master/docs/generated/protocol.md
+*/
+
+use crate::managers::power;
+use crate::{ToMainMessages, panic_mute, settings};
+use anyhow::{Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use beacn_lib::crossbeam;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc as tokio_mpsc;
+use tokio::time;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+
+// Settings are changed from the UI, so need to be checked periodically
+const SETTINGS_CHECK: Duration = Duration::from_secs(5);
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+const RPC_VERSION: u64 = 1;
+
+// The 'Scenes' event subscription, we don't need anything else
+const SCENE_EVENTS: u64 = 1 << 2;
+
+const OP_HELLO: u64 = 0;
+const OP_IDENTIFY: u64 = 1;
+const OP_IDENTIFIED: u64 = 2;
+const OP_EVENT: u64 = 5;
+const OP_REQUEST: u64 = 6;
+const OP_RESPONSE: u64 = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObsSettings {
+    pub enabled: bool,
+
+    /// The host and port of the OBS WebSocket server
+    pub address: String,
+    pub password: String,
+
+    /// Switching to this scene mutes everything, empty to never mute
+    pub mute_scene: String,
+
+    pub scene_colours: Vec<SceneColour>,
+}
+
+impl Default for ObsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: String::from("localhost:4455"),
+            password: String::new(),
+            mute_scene: String::from("BRB"),
+            scene_colours: vec![],
+        }
+    }
+}
+
+/// The primary lighting colour to use while a scene is live
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SceneColour {
+    pub scene: String,
+    pub colour: [u8; 3],
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ObsStatus {
+    #[default]
+    Disabled,
+    Connecting,
+    Connected,
+    Failed(String),
+
+    /// Disconnected while the battery saver is active
+    Paused,
+}
+
+static STATUS: LazyLock<RwLock<ObsStatus>> = LazyLock::new(Default::default);
+static SCENE: LazyLock<RwLock<Option<String>>> = LazyLock::new(Default::default);
+
+/// The state of the connection to OBS, for display in the UI
+pub fn status() -> ObsStatus {
+    STATUS.read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// The scene currently live in OBS, if we're connected
+pub fn current_scene() -> Option<String> {
+    SCENE.read().ok().and_then(|s| s.clone())
+}
+
+fn set_status(status: ObsStatus) {
+    if let Ok(mut current) = STATUS.write() {
+        *current = status;
+    }
+}
+
+fn set_scene(scene: Option<String>) {
+    if let Ok(mut current) = SCENE.write() {
+        *current = scene;
+    }
+}
+
+pub fn spawn_obs_handler(
+    tx: crossbeam::channel::Sender<ToMainMessages>,
+    stop_rx: tokio_mpsc::Receiver<()>,
+) -> Result<()> {
+    debug!("Starting OBS Handler with dedicated runtime..");
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()?;
+
+    rt.block_on(run_internal(tx, stop_rx));
+    Ok(())
+}
+
+async fn run_internal(
+    tx: crossbeam::channel::Sender<ToMainMessages>,
+    mut stop_rx: tokio_mpsc::Receiver<()>,
+) {
+    let mut handler = SceneHandler {
+        tx,
+        muted_by_scene: false,
+    };
+
+    let mut power_saving = power::subscribe();
+    loop {
+        let obs = settings::get().obs;
+        let mut delay = SETTINGS_CHECK;
+
+        if obs.enabled && *power_saving.borrow_and_update() {
+            set_status(ObsStatus::Paused);
+        } else if obs.enabled {
+            set_status(ObsStatus::Connecting);
+            tokio::select! {
+                result = run_session(&obs, &mut handler) => {
+                    if let Err(e) = result {
+                        warn!("OBS Connection Failed: {e}");
+                        set_status(ObsStatus::Failed(e.to_string()));
+                        delay = RECONNECT_DELAY;
+                    } else {
+                        // The settings changed, so reconnect straight away
+                        delay = Duration::ZERO;
+                    }
+                }
+                _ = power_saving.changed() => delay = Duration::ZERO,
+                _ = stop_rx.recv() => break,
+            }
+            set_scene(None);
+        } else {
+            set_status(ObsStatus::Disabled);
+        }
+
+        tokio::select! {
+            _ = time::sleep(delay) => {}
+            _ = power_saving.changed() => {}
+            _ = stop_rx.recv() => break,
+        }
+    }
+
+    debug!("Received stop signal, shutting down OBS handler");
+    set_status(ObsStatus::Disabled);
+    set_scene(None);
+}
+
+/// Runs a single connection to OBS, returning Ok if it should be restarted with new settings
+async fn run_session(obs: &ObsSettings, handler: &mut SceneHandler) -> Result<()> {
+    let url = format!("ws://{}", obs.address);
+    debug!("Connecting to OBS at {url}");
+    let (mut stream, _) = connect_async(url).await?;
+
+    let mut check = time::interval(SETTINGS_CHECK);
+    check.tick().await;
+
+    loop {
+        tokio::select! {
+            message = stream.next() => {
+                let message = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(Some(frame)))) => {
+                        bail!("OBS closed the connection: {}", frame.reason);
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e.into()),
+                    None => bail!("OBS closed the connection"),
+                };
+
+                let message: Value = serde_json::from_str(message.as_str())?;
+                let data = &message["d"];
+                match message["op"].as_u64() {
+                    Some(OP_HELLO) => {
+                        let identify = identify(obs, data)?;
+                        stream.send(Message::text(identify.to_string())).await?;
+                    }
+                    Some(OP_IDENTIFIED) => {
+                        info!("Connected to OBS");
+                        set_status(ObsStatus::Connected);
+                        let request = json!({
+                            "op": OP_REQUEST,
+                            "d": {
+                                "requestType": "GetCurrentProgramScene",
+                                "requestId": "current-scene",
+                            }
+                        });
+                        stream.send(Message::text(request.to_string())).await?;
+                    }
+                    Some(OP_RESPONSE) => {
+                        let scene = &data["responseData"]["currentProgramSceneName"];
+                        if let Some(scene) = scene.as_str() {
+                            handler.scene_changed(obs, scene);
+                        }
+                    }
+                    Some(OP_EVENT) if data["eventType"] == "CurrentProgramSceneChanged" => {
+                        if let Some(scene) = data["eventData"]["sceneName"].as_str() {
+                            handler.scene_changed(obs, scene);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ = check.tick() => {
+                if settings::get().obs != *obs {
+                    debug!("OBS Settings Changed, reconnecting");
+                    let _ = stream.close(None).await;
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn identify(obs: &ObsSettings, hello: &Value) -> Result<Value> {
+    let mut data = json!({
+        "rpcVersion": RPC_VERSION,
+        "eventSubscriptions": SCENE_EVENTS,
+    });
+
+    // OBS only sends an authentication challenge if it needs a password
+    let auth = &hello["authentication"];
+    if auth.is_object() {
+        if obs.password.is_empty() {
+            bail!("OBS requires a password");
+        }
+        let salt = auth["salt"].as_str().ok_or(anyhow!("Missing salt"))?;
+        let challenge = auth["challenge"]
+            .as_str()
+            .ok_or(anyhow!("Missing challenge"))?;
+        data["authentication"] = Value::from(authenticate(&obs.password, salt, challenge));
+    }
+
+    Ok(json!({ "op": OP_IDENTIFY, "d": data }))
+}
+
+fn authenticate(password: &str, salt: &str, challenge: &str) -> String {
+    let secret = STANDARD.encode(Sha256::digest(format!("{password}{salt}")));
+    STANDARD.encode(Sha256::digest(format!("{secret}{challenge}")))
+}
+
+struct SceneHandler {
+    tx: crossbeam::channel::Sender<ToMainMessages>,
+
+    // Whether the current mute came from the mute scene, so we only undo our own
+    muted_by_scene: bool,
+}
+
+impl SceneHandler {
+    fn scene_changed(&mut self, obs: &ObsSettings, scene: &str) {
+        debug!("OBS Scene: {scene}");
+        set_scene(Some(scene.to_string()));
+
+        if let Some(colour) = obs.scene_colours.iter().find(|c| c.scene == scene) {
+            let _ = self.tx.send(ToMainMessages::SetMicColour(colour.colour));
+        }
+
+        let mute = !obs.mute_scene.is_empty() && obs.mute_scene == scene;
+        if mute && !panic_mute::is_active() {
+            self.muted_by_scene = true;
+            let _ = self.tx.send(ToMainMessages::SetPanicMute(true));
+        } else if !mute && self.muted_by_scene {
+            self.muted_by_scene = false;
+            let _ = self.tx.send(ToMainMessages::SetPanicMute(false));
+        }
+    }
+}
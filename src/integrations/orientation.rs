@@ -0,0 +1,87 @@
+// Allows the Mix to be mounted sideways or upside down. Everything is drawn using the normal
+// landscape layout, then each image is rotated (and moved) just before it's encoded and sent.
+//
+// When on its side the display is taller than it is wide, so the landscape layout is scaled
+// down to fit the width, and centred vertically.
+
+use crate::integrations::pipeweaver::layout::{BG_COLOUR, DISPLAY_DIMENSIONS, DrawingUtils};
+use image::imageops::{FilterType, resize, rotate90, rotate180, rotate270};
+use image::{ImageBuffer, RgbaImage};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Display, EnumIter, Serialize, Deserialize,
+)]
+pub enum Orientation {
+    #[default]
+    #[strum(to_string = "Normal")]
+    Normal,
+
+    #[strum(to_string = "90° Clockwise")]
+    Rotate90,
+
+    #[strum(to_string = "Upside Down")]
+    Rotate180,
+
+    #[strum(to_string = "90° Anti-Clockwise")]
+    Rotate270,
+}
+
+impl Orientation {
+    /// Takes an image positioned in the landscape layout, and returns it rotated, along with
+    /// where it should be drawn on the physical display.
+    pub fn transform(&self, image: RgbaImage, x: u32, y: u32) -> (RgbaImage, u32, u32) {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        match self {
+            Orientation::Normal => (image, x, y),
+            Orientation::Rotate180 => {
+                let x = width.saturating_sub(x + image.width());
+                let y = height.saturating_sub(y + image.height());
+                (rotate180(&image), x, y)
+            }
+            Orientation::Rotate90 | Orientation::Rotate270 => {
+                let (image, x, y) = Self::scale_to_portrait(image, x, y);
+                let (w, h) = (image.width(), image.height());
+
+                // The portrait canvas is height wide, and width tall
+                match self {
+                    Orientation::Rotate90 => (rotate90(&image), width.saturating_sub(y + h), x),
+                    _ => (rotate270(&image), y, height.saturating_sub(x + w)),
+                }
+            }
+        }
+    }
+
+    // Scales an image down into the portrait layout, returning its new position
+    fn scale_to_portrait(image: RgbaImage, x: u32, y: u32) -> (RgbaImage, u32, u32) {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        let scale = height as f32 / width as f32;
+        let offset = (width - (height as f32 * scale) as u32) / 2;
+
+        // A full screen image needs the space above and below filling in
+        if image.dimensions() == DISPLAY_DIMENSIONS {
+            let scaled_height = (height as f32 * scale) as u32;
+            let scaled = resize(&image, height, scaled_height, FilterType::Triangle);
+
+            let mut portrait = ImageBuffer::from_pixel(height, width, BG_COLOUR);
+            DrawingUtils::composite_from_pos(&mut portrait, &scaled, (0, offset));
+            return (portrait, 0, 0);
+        }
+
+        // Work from the edges, so neighbouring images still line up after scaling
+        let x1 = (x as f32 * scale).floor() as u32;
+        let y1 = (y as f32 * scale).floor() as u32;
+        let x2 = ((x + image.width()) as f32 * scale).ceil() as u32;
+        let y2 = ((y + image.height()) as f32 * scale).ceil() as u32;
+
+        let w = (x2 - x1).max(1);
+        let h = (y2 - y1).max(1);
+        (resize(&image, w, h, FilterType::Triangle), x1, y1 + offset)
+    }
+
+    /// Whether images need decoding and transforming before they can be sent
+    pub fn needs_transform(&self) -> bool {
+        *self != Orientation::Normal
+    }
+}
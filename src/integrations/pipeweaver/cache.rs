@@ -0,0 +1,322 @@
+/*
+  An on-disk cache for the composited dial JPEGs.
+
+  Rather than a single file holding every image, each asset (a dial at a specific volume) is
+  stored in its own file, containing a JPEG for every meter level. Files are named after a hash
+  of everything which affects how they're rendered (the layout, colours, text and JPEG quality),
+  so if any of that changes, only the assets it touches stop matching and get regenerated.
+  Anything left in the cache directory which isn't currently needed is removed on load.
+
+  Files are written to a temporary file and renamed into place, so a crash mid-save leaves the
+  old file (or nothing) rather than half a file. In case something else goes wrong (a full disk,
+  or a filesystem which doesn't honour the rename), each file also starts with a magic number and
+  the cache version, records how many images it holds, and ends with a SHA-256 of everything
+  before it. A file which doesn't check out is removed, and the asset regenerated.
+*/
+
+use crate::storage::AtomicWriter;
+use crate::{APP_NAME, safe_mode};
+use anyhow::{Result, anyhow, bail};
+use log::{debug, warn};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use xdg::BaseDirectories;
+
+// Bump this if the file format changes, or rendering changes in a way the fingerprint can't see
+pub(crate) const CACHE_VERSION: u16 = 4;
+const CACHE_DIR: &str = "pipeweaver_mixer_cache";
+
+// The start of every cache file, followed by the version and image count
+const MAGIC: &[u8; 4] = b"BCNC";
+const HEADER_LEN: usize = MAGIC.len() + 2 + 4;
+
+// Each image is preceded by its meter level and length
+const ENTRY_HEADER_LEN: usize = 1 + 4;
+const CHECKSUM_LEN: usize = 32;
+
+// Previous monolithic caches, which are removed if found
+const LEGACY_CACHE_FILES: [&str; 2] = ["image_cache.bin", "pipeweaver_mixer_cache.bin"];
+
+/// The images for a single asset, keyed by meter level
+pub(crate) type AssetImages = HashMap<u8, Vec<u8>>;
+
+/// Identifies a single asset in the cache, and the parameters it was rendered with
+pub(crate) struct AssetKey(String);
+
+impl AssetKey {
+    pub fn new(name: &str, parameters: impl Hash) -> Self {
+        let mut hasher = StableHasher::default();
+        CACHE_VERSION.hash(&mut hasher);
+        parameters.hash(&mut hasher);
+        Self(format!("{name}-{:016x}.bin", hasher.finish()))
+    }
+}
+
+/// A Hasher backed by SHA-256. The standard library makes no promise that DefaultHasher gives
+/// the same result between Rust releases, which would quietly throw the whole cache away.
+#[derive(Default)]
+pub(crate) struct StableHasher(Sha256);
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        let digest = self.0.clone().finalize();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
+
+pub(crate) struct AssetCache {
+    path: Option<PathBuf>,
+}
+
+impl AssetCache {
+    pub fn open() -> Self {
+        // In safe mode, everything is regenerated and nothing is read from (or written to) disk
+        if safe_mode() {
+            debug!("Safe Mode, bypassing the image cache");
+            return Self { path: None };
+        }
+
+        let xdg_dirs = BaseDirectories::with_prefix(APP_NAME);
+        for legacy in LEGACY_CACHE_FILES {
+            if let Some(file) = xdg_dirs.find_cache_file(legacy) {
+                if let Err(e) = fs::remove_file(&file) {
+                    warn!("Failed to remove old cache file: {e}");
+                } else {
+                    debug!("Removed old cache file: {file:?}");
+                }
+            }
+        }
+
+        // If we can't create the directory, we simply regenerate everything each time
+        let path = match xdg_dirs.create_cache_directory(CACHE_DIR) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                warn!("Unable to create image cache directory: {e}");
+                None
+            }
+        };
+        Self { path }
+    }
+
+    pub fn load(&self, key: &AssetKey) -> Option<AssetImages> {
+        let path = self.path.as_ref()?.join(&key.0);
+        if !path.exists() {
+            return None;
+        }
+
+        match fs::read(&path)
+            .map_err(Into::into)
+            .and_then(|d| Self::parse(&d))
+        {
+            Ok(images) => Some(images),
+            Err(e) => {
+                // Get rid of it now, rather than tripping over it again if it can't be replaced
+                warn!("Discarding corrupt cached asset {}: {e}", key.0);
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, key: &AssetKey, images: &AssetImages) -> Result<()> {
+        let path = self.path.as_ref().ok_or(anyhow!("No cache directory"))?;
+
+        // We don't want a partially written file lying around if we're killed mid-write
+        let file = AtomicWriter::create(path.join(&key.0), 0)?;
+        let mut writer = BufWriter::new(file);
+        let mut hasher = Sha256::new();
+        let mut write = |bytes: &[u8]| -> Result<()> {
+            hasher.update(bytes);
+            writer.write_all(bytes)?;
+            Ok(())
+        };
+
+        write(MAGIC)?;
+        write(&CACHE_VERSION.to_le_bytes())?;
+        write(&(images.len() as u32).to_le_bytes())?;
+        for (&meter, data) in images {
+            write(&[meter])?;
+            write(&(data.len() as u32).to_le_bytes())?;
+            write(data)?;
+        }
+
+        writer.write_all(&hasher.finalize())?;
+        writer.flush()?;
+        let writer = writer.into_inner().map_err(|e| anyhow!("{}", e.error()))?;
+        writer.commit()
+    }
+
+    /// Removes everything from the cache which isn't in the provided list of keys
+    pub fn retain(&self, keys: &[AssetKey]) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+
+        let keep: HashSet<&str> = keys.iter().map(|key| key.0.as_str()).collect();
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if keep.contains(name.to_string_lossy().as_ref()) {
+                continue;
+            }
+            if fs::remove_file(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            debug!("Removed {removed} stale cached assets");
+        }
+    }
+
+    fn parse(data: &[u8]) -> Result<AssetImages> {
+        if data.len() < HEADER_LEN + CHECKSUM_LEN {
+            bail!("File is too short");
+        }
+        let (body, checksum) = data.split_at(data.len() - CHECKSUM_LEN);
+        if body[..MAGIC.len()] != *MAGIC {
+            bail!("Not a cache file");
+        }
+        if Sha256::digest(body).as_slice() != checksum {
+            bail!("Checksum mismatch, the file is incomplete or damaged");
+        }
+
+        let version = u16::from_le_bytes([body[4], body[5]]);
+        if version != CACHE_VERSION {
+            bail!("Cache version {version} doesn't match {CACHE_VERSION}");
+        }
+        let count = u32::from_le_bytes([body[6], body[7], body[8], body[9]]) as usize;
+
+        let mut images = AssetImages::new();
+        let mut remaining = &body[HEADER_LEN..];
+        while !remaining.is_empty() {
+            if remaining.len() < ENTRY_HEADER_LEN {
+                bail!("Incomplete image header");
+            }
+            let meter = remaining[0];
+            let len = u32::from_le_bytes([remaining[1], remaining[2], remaining[3], remaining[4]]);
+            let end = ENTRY_HEADER_LEN + len as usize;
+            if remaining.len() < end {
+                bail!("Incomplete image for meter level {meter}");
+            }
+
+            images.insert(meter, remaining[ENTRY_HEADER_LEN..end].to_vec());
+            remaining = &remaining[end..];
+        }
+
+        if images.len() != count {
+            bail!("Expected {count} images, found {}", images.len());
+        }
+        Ok(images)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    // Lays out a file the same way save() does, so the header can be given any values
+    fn build(version: u16, count: u32, images: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        data.extend(version.to_le_bytes());
+        data.extend(count.to_le_bytes());
+        for (meter, image) in images {
+            data.push(*meter);
+            data.extend((image.len() as u32).to_le_bytes());
+            data.extend(*image);
+        }
+        let checksum = Sha256::digest(&data);
+        data.extend(checksum);
+        data
+    }
+
+    fn images() -> AssetImages {
+        AssetImages::from([(0, vec![1, 2, 3]), (50, vec![4, 5]), (100, vec![])])
+    }
+
+    #[test]
+    fn saved_assets_load_back() {
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let path = env::temp_dir().join(format!("{APP_NAME}-cache-{}", stamp.as_nanos()));
+        fs::create_dir_all(&path).unwrap();
+
+        let cache = AssetCache {
+            path: Some(path.clone()),
+        };
+        let key = AssetKey::new("dial", 42);
+        cache.save(&key, &images()).unwrap();
+        let loaded = cache.load(&key);
+        fs::remove_dir_all(&path).unwrap();
+
+        assert_eq!(loaded, Some(images()));
+    }
+
+    #[test]
+    fn valid_files_are_parsed() {
+        let data = build(CACHE_VERSION, 2, &[(0, &[1, 2, 3]), (50, &[4, 5])]);
+        let images = AssetCache::parse(&data).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[&0], [1, 2, 3]);
+        assert_eq!(images[&50], [4, 5]);
+    }
+
+    #[test]
+    fn damaged_files_are_rejected() {
+        let mut data = build(CACHE_VERSION, 1, &[(0, &[1, 2, 3])]);
+        data[HEADER_LEN + ENTRY_HEADER_LEN] ^= 0xff;
+        assert!(AssetCache::parse(&data).is_err());
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        let data = build(CACHE_VERSION, 1, &[(0, &[1, 2, 3])]);
+        for len in [0, HEADER_LEN, data.len() - 1] {
+            assert!(
+                AssetCache::parse(&data[..len]).is_err(),
+                "{len} bytes parsed"
+            );
+        }
+    }
+
+    #[test]
+    fn other_files_are_rejected() {
+        let mut data = build(CACHE_VERSION, 0, &[]);
+        data[..MAGIC.len()].copy_from_slice(b"JFIF");
+        assert!(AssetCache::parse(&data).is_err());
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let data = build(CACHE_VERSION - 1, 1, &[(0, &[1, 2, 3])]);
+        assert!(AssetCache::parse(&data).is_err());
+    }
+
+    #[test]
+    fn missing_images_are_rejected() {
+        let data = build(CACHE_VERSION, 2, &[(0, &[1, 2, 3])]);
+        assert!(AssetCache::parse(&data).is_err());
+    }
+
+    #[test]
+    fn keys_only_change_with_their_parameters() {
+        let key = |name, parameters: (u8, &str)| AssetKey::new(name, parameters).0;
+        assert_eq!(key("dial", (1, "Music")), key("dial", (1, "Music")));
+        assert_ne!(key("dial", (1, "Music")), key("dial", (2, "Music")));
+        assert_ne!(key("dial", (1, "Music")), key("header", (1, "Music")));
+    }
+}
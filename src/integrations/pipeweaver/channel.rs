@@ -4,9 +4,10 @@
 use crate::integrations::pipeweaver::ChannelType;
 use crate::integrations::pipeweaver::layout::GradientDirection::{BottomToTop, TopToBottom};
 use crate::integrations::pipeweaver::layout::*;
+use crate::integrations::pipeweaver::text::TextStyle;
 use anyhow::{Result, anyhow};
 use beacn_lib::manager::DeviceType;
-use enum_map::{EnumMap, enum_map};
+use enum_map::{Enum, EnumMap, enum_map};
 use image::imageops::{crop, crop_imm};
 use image::{ImageBuffer, Rgba, RgbaImage, load_from_memory};
 use pipeweaver_profile::{
@@ -14,7 +15,9 @@ use pipeweaver_profile::{
     VirtualTargetDevice, Volumes,
 };
 use pipeweaver_shared::{Mix, MuteTarget};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 
 // This trait is primarily here to ease the difference between a Physical and Virtual Source. In
 // the context of this app, these see identical usage.
@@ -92,12 +95,74 @@ pub(crate) enum ChannelChangedProperty {
     MuteState(MuteTarget),
 }
 
+impl ChannelChangedProperty {
+    /// The parts of the channel which may look different after this property has changed
+    pub fn regions(&self) -> &'static [ChannelRegion] {
+        match self {
+            ChannelChangedProperty::Title => &[ChannelRegion::Header],
+            ChannelChangedProperty::Colour => &[
+                ChannelRegion::Header,
+                ChannelRegion::HeaderBar,
+                ChannelRegion::MuteBar,
+                ChannelRegion::MuteA,
+                ChannelRegion::MuteGap,
+                ChannelRegion::MuteB,
+            ],
+            ChannelChangedProperty::Volumes(_) => &[ChannelRegion::Dial],
+            ChannelChangedProperty::MuteState(MuteTarget::TargetA) => &[ChannelRegion::MuteA],
+            ChannelChangedProperty::MuteState(MuteTarget::TargetB) => &[ChannelRegion::MuteB],
+        }
+    }
+}
+
+// The rectangles of a channel which can be redrawn on their own. Between them they cover
+// everything inside the content box, so a partial redraw never leaves anything stale.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Enum, EnumIter)]
+pub(crate) enum ChannelRegion {
+    Header,
+    HeaderBar,
+    Dial,
+    MuteBar,
+    MuteA,
+
+    // The Mix Create has a strip of the mute background between the two mute boxes
+    MuteGap,
+    MuteB,
+}
+
+impl ChannelRegion {
+    fn bounds(&self, layout: &LayoutTheme, has_mute_b: bool) -> Option<(Position, Dimension)> {
+        let bounds = match self {
+            ChannelRegion::Header => (layout.header_position, layout.header),
+            ChannelRegion::HeaderBar => (layout.header_bar_position, layout.bar),
+            ChannelRegion::Dial => (layout.volume_position, layout.volume),
+            ChannelRegion::MuteBar => (layout.mute_bar_position, layout.bar),
+            ChannelRegion::MuteA => (
+                layout.mute_position(MuteTarget::TargetA),
+                layout.mute_size(has_mute_b),
+            ),
+            ChannelRegion::MuteGap if has_mute_b => return layout.mute_gap(),
+            ChannelRegion::MuteB if has_mute_b => {
+                let position = layout.mute_position(MuteTarget::TargetB);
+                let area_end = layout.mute_area_position.1 + layout.mute_area.1;
+                let (width, _) = layout.mute_button;
+                (position, (width, area_end - position.1))
+            }
+            ChannelRegion::MuteGap | ChannelRegion::MuteB => return None,
+        };
+        Some(bounds)
+    }
+}
+
 #[allow(unused)]
 pub(crate) struct ChannelRenderer {
     beacn_type: DeviceType,
+    layout: &'static LayoutTheme,
 
     pub(crate) title: String,
     pub(crate) colour: Rgba<u8>,
+    colour_override: Option<Rgba<u8>>,
+    volume_unit: VolumeUnit,
 
     pub(crate) volumes: EnumMap<Mix, u8>,
 
@@ -108,6 +173,24 @@ pub(crate) struct ChannelRenderer {
     pub(crate) channel_type: ChannelType,
 
     pub(crate) mute_states: EnumMap<MuteTarget, MuteState>,
+
+    // A hash of what was last drawn in each region, so unchanged regions don't get resent
+    drawn: EnumMap<ChannelRegion, Option<u64>>,
+
+    // A change made by another Pipeweaver client, which is eased in rather than jumping
+    external: Option<ExternalChange>,
+}
+
+// How many animation frames an external volume change is eased over, and how many the
+// highlight takes to fade out
+const EASE_FRAMES: u8 = 5;
+const HIGHLIGHT_FRAMES: u8 = 15;
+
+#[derive(Debug, Copy, Clone)]
+struct ExternalChange {
+    // The mix and volume the dial is easing from, if the volume changed
+    from: Option<(Mix, u8)>,
+    frame: u8,
 }
 
 pub(crate) struct MuteState {
@@ -133,8 +216,11 @@ impl ChannelRenderer {
 
         Self {
             beacn_type: DeviceType::BeacnMixCreate,
+            layout: &STANDARD_LAYOUT,
             title: desc.name.clone(),
             colour: Rgba([desc.colour.red, desc.colour.green, desc.colour.blue, 255]),
+            colour_override: None,
+            volume_unit: VolumeUnit::default(),
             volumes: vols.volume,
             meter: 0,
             meter_target: 0.0,
@@ -149,6 +235,8 @@ impl ChannelRenderer {
                     is_mute_to_all: mutes.mute_targets[MuteTarget::TargetB].is_empty(),
                 }
             },
+            drawn: EnumMap::default(),
+            external: None,
         }
     }
     fn from_target_device(device: &impl TargetDevice) -> Self {
@@ -161,10 +249,20 @@ impl ChannelRenderer {
             pipeweaver_shared::MuteState::Muted => true,
         };
 
+        let colour = Rgba([desc.colour.red, desc.colour.green, desc.colour.blue, 255]);
+        Self::new_target(desc.name.clone(), colour, volume, is_muted)
+    }
+
+    /// Creates a single volume, single mute channel. This is used for Pipeweaver targets, but
+    /// also for other integrations which don't have the concept of a Mix.
+    pub fn new_target(title: String, colour: Rgba<u8>, volume: u8, is_muted: bool) -> Self {
         Self {
             beacn_type: DeviceType::BeacnMixCreate,
-            title: desc.name.clone(),
-            colour: Rgba([desc.colour.red, desc.colour.green, desc.colour.blue, 255]),
+            layout: &STANDARD_LAYOUT,
+            title,
+            colour,
+            colour_override: None,
+            volume_unit: VolumeUnit::default(),
             volumes: enum_map! { Mix::A => volume, Mix::B => 0 },
             meter: 0,
             meter_target: 0.0,
@@ -179,6 +277,8 @@ impl ChannelRenderer {
                     is_mute_to_all: false,
                 }
             },
+            drawn: EnumMap::default(),
+            external: None,
         }
     }
 
@@ -186,6 +286,44 @@ impl ChannelRenderer {
         self.beacn_type = device_type;
     }
 
+    /// Sets how the channel is laid out, returning true if it's changed. Everything will need
+    /// redrawing, so what was last drawn is forgotten.
+    pub fn set_layout(&mut self, layout: &'static LayoutTheme) -> bool {
+        if std::ptr::eq(self.layout, layout) {
+            return false;
+        }
+        self.layout = layout;
+        self.drawn = EnumMap::default();
+        true
+    }
+
+    fn has_mute_b(&self) -> bool {
+        self.beacn_type == DeviceType::BeacnMixCreate
+    }
+
+    /// The colour to draw the channel in, a user override takes priority over Pipeweaver
+    pub fn colour(&self) -> Rgba<u8> {
+        self.colour_override.unwrap_or(self.colour)
+    }
+
+    /// Sets (or clears) the override, returning true if the displayed colour has changed
+    pub fn set_colour_override(&mut self, colour: Option<Rgba<u8>>) -> bool {
+        let previous = self.colour();
+        self.colour_override = colour;
+        previous != self.colour()
+    }
+
+    /// Sets how the volume is written on the dial, returning true if it's changed
+    pub fn set_volume_unit(&mut self, unit: VolumeUnit) -> bool {
+        let changed = self.volume_unit != unit;
+        self.volume_unit = unit;
+        changed
+    }
+
+    pub fn volume_unit(&self) -> VolumeUnit {
+        self.volume_unit
+    }
+
     pub fn update_from_source_device(
         &mut self,
         device: &impl SourceDevice,
@@ -241,12 +379,27 @@ impl ChannelRenderer {
         let volume = device.volume();
         let muted = device.mute_state();
 
+        let is_muted = match muted {
+            pipeweaver_shared::MuteState::Unmuted => false,
+            pipeweaver_shared::MuteState::Muted => true,
+        };
+
+        let colour = Rgba([desc.colour.red, desc.colour.green, desc.colour.blue, 255]);
+        self.update_target(&desc.name, colour, volume, is_muted)
+    }
+
+    pub fn update_target(
+        &mut self,
+        title: &str,
+        colour: Rgba<u8>,
+        volume: u8,
+        is_muted: bool,
+    ) -> Vec<ChannelChangedProperty> {
         let mut updates = vec![];
-        if desc.name != self.title {
-            self.title = desc.name.clone();
+        if title != self.title {
+            self.title = title.to_string();
             updates.push(ChannelChangedProperty::Title);
         }
-        let colour = Rgba([desc.colour.red, desc.colour.green, desc.colour.blue, 255]);
         if self.colour != colour {
             self.colour = colour;
             updates.push(ChannelChangedProperty::Colour);
@@ -259,11 +412,6 @@ impl ChannelRenderer {
             updates.push(ChannelChangedProperty::Volumes(Mix::A));
         }
 
-        let is_muted = match muted {
-            pipeweaver_shared::MuteState::Unmuted => false,
-            pipeweaver_shared::MuteState::Muted => true,
-        };
-
         if self.mute_states[MuteTarget::TargetA].is_active != is_muted {
             self.mute_states[MuteTarget::TargetA].is_active = is_muted;
             updates.push(ChannelChangedProperty::MuteState(MuteTarget::TargetA));
@@ -272,18 +420,72 @@ impl ChannelRenderer {
         updates
     }
 
-    pub fn full_render(&self, active_mix: Mix) -> BeacnImage {
+    pub fn full_render(&mut self, active_mix: Mix) -> BeacnImage {
+        let image = self.compose(Some(active_mix));
+
+        // Everything has been drawn, so remember what each region now looks like
+        for region in ChannelRegion::iter() {
+            if let Some(((x, y), (w, h))) = region.bounds(self.layout, self.has_mute_b()) {
+                let pixels = crop_imm(&image, x, y, w, h).to_image();
+                self.drawn[region] = Some(Self::hash_pixels(&pixels));
+            }
+        }
+
+        BeacnImage {
+            position: (0, 0),
+            image,
+        }
+    }
+
+    /// Redraws the parts of the channel affected by a change, returning only the regions which
+    /// actually look different to what was last drawn
+    pub fn render_damaged(
+        &mut self,
+        change: ChannelChangedProperty,
+        active_mix: Mix,
+    ) -> Vec<BeacnImage> {
+        let regions = change.regions();
+
+        // Decoding the dial is the slowest part, so skip it unless it's needed
+        let dial = regions.contains(&ChannelRegion::Dial).then_some(active_mix);
+        let image = self.compose(dial);
+
+        let mut damaged = vec![];
+        for region in regions {
+            let Some(((x, y), (w, h))) = region.bounds(self.layout, self.has_mute_b()) else {
+                continue;
+            };
+
+            let pixels = crop_imm(&image, x, y, w, h).to_image();
+            let hash = Some(Self::hash_pixels(&pixels));
+            if self.drawn[*region] != hash {
+                self.drawn[*region] = hash;
+                damaged.push(BeacnImage {
+                    position: (x, y),
+                    image: pixels,
+                });
+            }
+        }
+        damaged
+    }
+
+    fn hash_pixels(image: &RgbaImage) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        image.as_raw().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn compose(&self, dial_mix: Option<Mix>) -> RgbaImage {
         // Firstly, lets grab some fixed dimensions
-        let (w, h) = CHANNEL_DIMENSIONS;
+        let (w, h) = self.layout.channel;
 
         // Draw all the elements
         let mut base = ImageBuffer::from_pixel(w, h, BG_COLOUR);
         let content = self.draw_content_box();
         let header = self.draw_header();
-        let header_bar = self.draw_bar(HEADER_BAR_POSITION);
-        let mute_bar = self.draw_bar(MUTE_BAR_POSITION);
+        let header_bar = self.draw_bar(self.layout.header_bar_position);
+        let mute_bar = self.draw_bar(self.layout.mute_bar_position);
         let mute_bg = self.draw_mute_background();
-        let dial = self.draw_volume(active_mix);
         let mute_a = self.draw_mute_box(MuteTarget::TargetA);
 
         // Composite all the elements together
@@ -292,19 +494,68 @@ impl ChannelRenderer {
         DrawingUtils::composite_from_pos(&mut base, &header_bar.image, header_bar.position);
         DrawingUtils::composite_from_pos(&mut base, &mute_bar.image, mute_bar.position);
         DrawingUtils::composite_from_pos(&mut base, &mute_bg.image, mute_bg.position);
-        DrawingUtils::composite_from_pos(&mut base, &dial.image, dial.position);
+        if let Some(mix) = dial_mix {
+            let dial = self.draw_volume(mix);
+            DrawingUtils::composite_from_pos(&mut base, &dial.image, dial.position);
+        }
         DrawingUtils::composite_from_pos(&mut base, &mute_a.image, mute_a.position);
 
-        if self.beacn_type == DeviceType::BeacnMixCreate {
+        if self.has_mute_b() {
             let mute_b = self.draw_mute_box(MuteTarget::TargetB);
             DrawingUtils::composite_from_pos(&mut base, &mute_b.image, mute_b.position);
         }
 
-        // Return the result
-        BeacnImage {
-            position: (0, 0),
-            image: base,
+        base
+    }
+
+    /// Starts highlighting the channel after another client has changed it, easing the dial
+    /// over from the volume that was being shown if a volume changed
+    pub fn start_external_change(&mut self, from: Option<(Mix, u8)>) {
+        // Don't lose an ease that's already running because of a mute change
+        let from = from.or(self.external.and_then(|e| e.from));
+        self.external = Some(ExternalChange { from, frame: 0 });
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.external.is_some()
+    }
+
+    /// Moves the animation on a frame, returning true if the dial needs redrawing
+    pub fn advance_animation(&mut self) -> bool {
+        let Some(external) = &mut self.external else {
+            return false;
+        };
+        external.frame += 1;
+
+        let easing = external.from.is_some() && external.frame <= EASE_FRAMES;
+        if external.frame >= EASE_FRAMES {
+            external.from = None;
+        }
+        if external.frame >= HIGHLIGHT_FRAMES {
+            self.external = None;
+        }
+        easing
+    }
+
+    /// The volume currently drawn on the dial, which trails the real one while easing
+    pub fn shown_volume(&self, mix: Mix) -> u8 {
+        let volume = self.volumes[mix];
+        let Some(ExternalChange {
+            from: Some((from_mix, from)),
+            frame,
+        }) = self.external
+        else {
+            return volume;
+        };
+        if from_mix != mix {
+            return volume;
         }
+
+        // Ease out, so the dial slows as it lands
+        let progress = (frame as f32 / EASE_FRAMES as f32).min(1.0);
+        let progress = 1.0 - (1.0 - progress).powi(2);
+        let distance = volume as f32 - from as f32;
+        (from as f32 + distance * progress).round() as u8
     }
 
     pub fn tick_meter(&mut self, delta_secs: f32) -> u8 {
@@ -335,29 +586,27 @@ impl ChannelRenderer {
     }
 
     pub fn get_volume(&self, mix: Mix) -> Result<RawImage> {
-        let volume = self.volumes[mix];
-        let meter = Self::scale_meter(self.volumes[mix], self.meter);
-        let raw_image = DIAL_VOLUME_JPEG[mix]
-            .get(&volume)
-            .and_then(|m| m.get(&meter))
+        let volume = self.shown_volume(mix);
+        let meter = Self::scale_meter(volume, self.meter);
+        let raw_image = self
+            .layout
+            .dial_image(self.volume_unit, mix, volume, meter)
             .ok_or(anyhow!("Image Missing"))?;
 
         Ok(RawImage {
-            position: VOLUME_POSITION,
+            position: self.layout.volume_position,
             image: raw_image.clone(),
         })
     }
 
     pub fn draw_volume(&self, mix: Mix) -> BeacnImage {
-        let volume = self.volumes[mix];
-        let meter = Self::scale_meter(self.volumes[mix], self.meter);
-        if let Some(jpeg_data) = DIAL_VOLUME_JPEG[mix]
-            .get(&volume)
-            .and_then(|m| m.get(&meter))
+        let volume = self.shown_volume(mix);
+        let meter = Self::scale_meter(volume, self.meter);
+        if let Some(jpeg_data) = self.layout.dial_image(self.volume_unit, mix, volume, meter)
             && let Ok(img) = load_from_memory(jpeg_data)
         {
             return BeacnImage {
-                position: VOLUME_POSITION,
+                position: self.layout.volume_position,
                 image: img.into_rgba8(),
             };
         }
@@ -372,15 +621,15 @@ impl ChannelRenderer {
     fn draw_content_box(&self) -> BeacnImage {
         let channel_inner = match self.channel_type {
             ChannelType::Source => match self.beacn_type {
-                DeviceType::BeacnMixCreate => CHANNEL_INNER_DIMENSIONS,
-                DeviceType::BeacnMix => CHANNEL_INNER_DIMENSIONS_MIX,
+                DeviceType::BeacnMixCreate => self.layout.inner,
+                DeviceType::BeacnMix => self.layout.inner_mix,
                 _ => panic!("Bad Device Type"),
             },
-            ChannelType::Target => CHANNEL_INNER_DIMENSIONS_MIX,
+            ChannelType::Target => self.layout.inner_mix,
         };
 
         BeacnImage {
-            position: CHANNEL_INNER_POSITION,
+            position: self.layout.inner_position,
             image: DrawingUtils::draw_box(
                 channel_inner.0,
                 channel_inner.1,
@@ -394,18 +643,17 @@ impl ChannelRenderer {
     }
 
     pub fn draw_header(&self) -> BeacnImage {
-        let mut colour = self.colour;
+        let mut colour = self.colour();
         colour[3] = 100;
 
-        let (width, height) = HEADER_DIMENSIONS;
-        let (text_width, text_height) = HEADER_TEXT_DIMENSIONS;
+        let (width, height) = self.layout.header;
+        let (text_width, text_height) = self.layout.header_text;
         let mut base = DrawingUtils::draw_gradient(width, height, colour, TopToBottom);
         let text = DrawingUtils::draw_text(
             self.title.to_string(),
             text_width,
             text_height,
-            HEADER_FONT,
-            HEADER_FONT_SIZE,
+            TextStyle::Header,
             TEXT_COLOUR,
             TextAlign::Center,
         );
@@ -415,7 +663,7 @@ impl ChannelRenderer {
 
         // Return it
         BeacnImage {
-            position: HEADER_POSITION,
+            position: self.layout.header_position,
             image: base,
         }
     }
@@ -423,15 +671,32 @@ impl ChannelRenderer {
     fn draw_bar(&self, position: Position) -> BeacnImage {
         BeacnImage {
             position,
-            image: ImageBuffer::from_pixel(BAR_DIMENSIONS.0, BAR_DIMENSIONS.1, self.colour),
+            image: ImageBuffer::from_pixel(self.layout.bar.0, self.layout.bar.1, self.bar_colour()),
         }
     }
 
+    // The bars are lightened after an external change, fading back to the channel colour
+    fn bar_colour(&self) -> Rgba<u8> {
+        let colour = self.colour();
+        let Some(external) = self.external else {
+            return colour;
+        };
+
+        let strength = 0.6 * (1.0 - external.frame as f32 / HIGHLIGHT_FRAMES as f32);
+        let lighten = |c: u8| (c as f32 + (255.0 - c as f32) * strength).round() as u8;
+        Rgba([
+            lighten(colour[0]),
+            lighten(colour[1]),
+            lighten(colour[2]),
+            colour[3],
+        ])
+    }
+
     fn draw_mute_background(&self) -> BeacnImage {
-        let (w, h) = MUTE_AREA_DIMENSIONS;
-        let (m1, h1) = MUTE_AREA_DIMENSIONS_MIX;
+        let (w, h) = self.layout.mute_area;
+        let (m1, h1) = self.layout.mute_area_mix;
 
-        let mut colour = self.colour;
+        let mut colour = self.colour();
         colour[3] = 120;
 
         let mut gradient_base = DrawingUtils::draw_gradient(w, h, colour, BottomToTop);
@@ -445,7 +710,7 @@ impl ChannelRenderer {
         };
 
         BeacnImage {
-            position: MUTE_AREA_POSITION,
+            position: self.layout.mute_area_position,
             image: gradient,
         }
     }
@@ -461,12 +726,8 @@ impl ChannelRenderer {
             ChannelType::Target => "Mute",
         };
 
-        let border_draw = match target {
-            MuteTarget::TargetA => MUTE_A_BORDER,
-            MuteTarget::TargetB => MUTE_B_BORDER,
-        };
-
-        let (width, height) = MUTE_BUTTON_DIMENSIONS;
+        let border_draw = self.layout.mute_border(target);
+        let (width, height) = self.layout.mute_size(self.has_mute_b());
 
         let (colour, icon) = match self.mute_states[target].is_active {
             true => (MUTE_COLOUR_ON, &*MUTE_MUTED_ICON),
@@ -483,15 +744,24 @@ impl ChannelRenderer {
             colour,
         );
 
-        let relative_position = match target {
-            MuteTarget::TargetA => MUTE_LOCAL_POSITION_A,
-            MuteTarget::TargetB => MUTE_LOCAL_POSITION_B,
-        };
-        let (x, y) = relative_position;
+        let (x, y) = self.layout.mute_local(target);
 
         // Draw the box onto the background
         DrawingUtils::composite_from(&mut background, &mute_box, x, y);
 
+        // Without room for the text, the icon is centred in the box
+        let middle = height / 2;
+        if !self.layout.mute_labels {
+            let icon_x = x + (width - icon.width()) / 2;
+            let icon_y = middle - icon.height() / 2 + y + border_draw.0;
+            DrawingUtils::composite_from(&mut background, icon, icon_x, icon_y);
+
+            return BeacnImage {
+                image: crop_imm(&background, x, y, width, height).to_image(),
+                position: self.layout.mute_position(target),
+            };
+        }
+
         // The text size needs to be shrunk based on the icon size
         let (mut text_width, text_height) = MUTE_TEXT_DIMENSIONS;
         text_width = text_width - icon.width() - (ICON_MARGIN * 2);
@@ -501,14 +771,11 @@ impl ChannelRenderer {
             text.to_string(),
             text_width,
             text_height,
-            MUTE_FONT,
-            MUTE_FONT_SIZE,
+            TextStyle::Mute,
             TEXT_COLOUR,
             TextAlign::Left,
         );
 
-        let (_, h) = MUTE_BUTTON_DIMENSIONS;
-        let middle = h / 2;
         let text_middle = text.height() / 2;
         let icon_middle = icon.height() / 2;
 
@@ -525,14 +792,9 @@ impl ChannelRenderer {
         // Grab the specific area from the Mute Box
         let cropped = crop_imm(&background, x, y, width, height).to_image();
 
-        let position = match target {
-            MuteTarget::TargetA => MUTE_POSITION_A,
-            MuteTarget::TargetB => MUTE_POSITION_B,
-        };
-
         BeacnImage {
             image: cropped,
-            position,
+            position: self.layout.mute_position(target),
         }
     }
 }
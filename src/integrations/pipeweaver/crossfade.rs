@@ -0,0 +1,82 @@
+/*
+  Holding the Mix button fades what's heard on Mix A over to the levels set for Mix B, so the
+  audience mix can be checked without moving any outputs around. Releasing it fades back again,
+  and once the fade has finished the original Mix A volumes are put back exactly.
+*/
+
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+use ulid::Ulid;
+
+pub struct Crossfade {
+    // The Mix A and Mix B volume of each source when the fade started
+    volumes: Vec<(Ulid, u8, u8)>,
+    duration: Duration,
+
+    // Where the fade is heading (0.0 is Mix A, 1.0 is Mix B), and where it was coming from
+    from: f32,
+    to: f32,
+    started: Instant,
+
+    // The last volume sent for each source, so unchanged values aren't sent again
+    sent: HashMap<Ulid, u8>,
+}
+
+impl Crossfade {
+    pub fn new(volumes: Vec<(Ulid, u8, u8)>, duration: Duration) -> Self {
+        let sent = volumes.iter().map(|(id, a, _)| (*id, *a)).collect();
+        Self {
+            volumes,
+            duration,
+            from: 0.0,
+            to: 1.0,
+            started: Instant::now(),
+            sent,
+        }
+    }
+
+    /// Heads towards Mix B when true, or back to Mix A, carrying on from the current position
+    pub fn fade_to(&mut self, mix_b: bool) {
+        let to = if mix_b { 1.0 } else { 0.0 };
+        if to != self.to {
+            self.from = self.position();
+            self.to = to;
+            self.started = Instant::now();
+        }
+    }
+
+    pub fn is_returning(&self) -> bool {
+        self.to == 0.0
+    }
+
+    /// Whether we've faded all the way back to Mix A
+    pub fn is_finished(&self) -> bool {
+        self.is_returning() && self.position() <= 0.0
+    }
+
+    /// The Mix A volumes which have changed since the last step
+    pub fn step(&mut self) -> Vec<(Ulid, u8)> {
+        let position = self.position();
+        let mut changes = vec![];
+        for (id, a, b) in &self.volumes {
+            let volume = (*a as f32 + (*b as f32 - *a as f32) * position).round() as u8;
+            if self.sent.insert(*id, volume) != Some(volume) {
+                changes.push((*id, volume));
+            }
+        }
+        changes
+    }
+
+    fn position(&self) -> f32 {
+        // A partial fade only takes as long as the distance it has left to cover
+        let distance = (self.to - self.from).abs();
+        let duration = self.duration.mul_f32(distance);
+        if duration.is_zero() {
+            return self.to;
+        }
+
+        let progress = (self.started.elapsed().as_secs_f32() / duration.as_secs_f32()).min(1.0);
+        self.from + (self.to - self.from) * progress
+    }
+}
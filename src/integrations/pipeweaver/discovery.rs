@@ -0,0 +1,60 @@
+/*
+  Finds Pipeweaver daemons on the local network via mDNS. Only daemons which have been
+  configured to listen on the network (and advertise themselves) will show up here, so the
+  address can always be entered manually as well.
+*/
+
+use anyhow::Result;
+use log::debug;
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use std::time::{Duration, Instant};
+
+const SERVICE_TYPE: &str = "_pipeweaver._tcp.local.";
+
+/// The address used when nothing has been configured
+pub const DEFAULT_ADDRESS: &str = "localhost:14565";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredDaemon {
+    pub name: String,
+    pub address: String,
+}
+
+/// Browses the network for the given duration, returning every daemon which responded. This
+/// blocks, so shouldn't be called from the UI thread.
+pub fn discover(duration: Duration) -> Result<Vec<DiscoveredDaemon>> {
+    let mdns = ServiceDaemon::new()?;
+    let receiver = mdns.browse(SERVICE_TYPE)?;
+
+    let mut found: Vec<DiscoveredDaemon> = vec![];
+    let deadline = Instant::now() + duration;
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        let Ok(event) = receiver.recv_timeout(remaining) else {
+            break;
+        };
+
+        if let ServiceEvent::ServiceResolved(info) = event {
+            // Prefer an IP, as .local hostnames aren't resolvable everywhere
+            let host = info
+                .get_addresses_v4()
+                .into_iter()
+                .next()
+                .map(|ip| ip.to_string())
+                .unwrap_or_else(|| info.get_hostname().trim_end_matches('.').to_string());
+
+            let name = info.get_fullname().trim_end_matches(SERVICE_TYPE);
+            let daemon = DiscoveredDaemon {
+                name: name.trim_end_matches('.').to_string(),
+                address: format!("{host}:{}", info.get_port()),
+            };
+
+            debug!("Discovered Pipeweaver: {daemon:?}");
+            if !found.contains(&daemon) {
+                found.push(daemon);
+            }
+        }
+    }
+
+    let _ = mdns.shutdown();
+    Ok(found)
+}
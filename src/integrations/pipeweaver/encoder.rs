@@ -0,0 +1,244 @@
+// Encoding images as JPEGs and waiting for the device to take them is the slowest part of drawing
+// to the Mix, and when it happened on the handler's task, a burst of changes (a profile load, or
+// lots of meters moving) would hold up everything else, including reacting to the dials.
+//
+// Instead, images are handed to a small pool of threads which encode them in parallel, then
+// sent to the device strictly in the order they were queued, so overlapping images still end up
+// with the newest on top. If an image is queued for exactly the same region as one which hasn't
+// been started yet (and nothing queued since overlaps it), the older one is simply replaced, so
+// a region that's changing quickly only sends its latest state.
+//
+// The queue is bounded, if the device can't keep up the handler waits for room rather than
+// building up a backlog. Anything queued is still sent when the encoder is dropped, so the last
+// images a handler draws on the way out aren't lost.
+
+use crate::device_manager::ControlMessage;
+use crate::device_manager::ControlMessage::SendImage;
+use crate::integrations::pipeweaver::layout::{BG_COLOUR, DrawingUtils, JPEG_QUALITY};
+use anyhow::{Result, anyhow};
+use beacn_lib::crossbeam::channel::Sender;
+use image::RgbaImage;
+use log::warn;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+const WORKERS: usize = 2;
+
+// How many images can be waiting to be encoded before the handler has to wait
+const MAX_PENDING: usize = 16;
+
+// A region of the display, stored as (x, y, width, height)
+type Region = (u32, u32, u32, u32);
+
+/// Something to draw on the display, either already encoded or still to be
+pub(crate) enum Tile {
+    Jpeg(Vec<u8>),
+    Image(RgbaImage, u8),
+}
+
+impl From<Vec<u8>> for Tile {
+    fn from(jpeg: Vec<u8>) -> Self {
+        Tile::Jpeg(jpeg)
+    }
+}
+
+impl From<RgbaImage> for Tile {
+    fn from(image: RgbaImage) -> Self {
+        Tile::Image(image, JPEG_QUALITY)
+    }
+}
+
+struct Job {
+    sequence: u64,
+    sender: Sender<ControlMessage>,
+    tile: Tile,
+    x: u32,
+    y: u32,
+
+    // Only known for raw images, JPEGs can't be coalesced without decoding them
+    region: Option<Region>,
+}
+
+#[derive(Default)]
+struct Queue {
+    pending: VecDeque<Job>,
+    next_sequence: u64,
+    stopping: bool,
+    error: Option<String>,
+}
+
+// Encoded images waiting for their turn to be sent
+#[derive(Default)]
+struct Outbox {
+    ready: BTreeMap<u64, Option<(Sender<ControlMessage>, Vec<u8>, u32, u32)>>,
+    next: u64,
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    work: Condvar,
+    space: Condvar,
+    outbox: Mutex<Outbox>,
+}
+
+pub(crate) struct Encoder {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        let shared = Arc::new(Shared::default());
+        let workers = (0..WORKERS)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || run_worker(&shared))
+            })
+            .collect();
+        Self { shared, workers }
+    }
+
+    /// Queues a tile to be sent, waiting for room if the queue is full. This only fails if the
+    /// device has stopped accepting images.
+    pub fn queue(&self, sender: &Sender<ControlMessage>, tile: Tile, x: u32, y: u32) -> Result<()> {
+        let region = match &tile {
+            Tile::Image(image, _) => Some((x, y, image.width(), image.height())),
+            Tile::Jpeg(_) => None,
+        };
+
+        let Ok(mut queue) = self.shared.queue.lock() else {
+            return Err(anyhow!("Image encoder has stopped"));
+        };
+        if let Some(error) = &queue.error {
+            return Err(anyhow!("{error}"));
+        }
+
+        if let Some(region) = region
+            && let Some(job) = coalesce_target(&mut queue.pending, region)
+        {
+            job.tile = tile;
+            return Ok(());
+        }
+
+        while queue.pending.len() >= MAX_PENDING && queue.error.is_none() {
+            queue = match self.shared.space.wait(queue) {
+                Ok(queue) => queue,
+                Err(_) => return Err(anyhow!("Image encoder has stopped")),
+            };
+        }
+
+        let sequence = queue.next_sequence;
+        queue.next_sequence += 1;
+        queue.pending.push_back(Job {
+            sequence,
+            sender: sender.clone(),
+            tile,
+            x,
+            y,
+            region,
+        });
+        self.shared.work.notify_one();
+        Ok(())
+    }
+}
+
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        if let Ok(mut queue) = self.shared.queue.lock() {
+            queue.stopping = true;
+        }
+        self.shared.work.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+// The most recent pending job for exactly this region, as long as nothing after it overlaps
+fn coalesce_target(pending: &mut VecDeque<Job>, region: Region) -> Option<&mut Job> {
+    for job in pending.iter_mut().rev() {
+        let Some(other) = job.region else {
+            // We don't know where a JPEG goes until it's decoded, so play it safe
+            return None;
+        };
+        if other == region {
+            return Some(job);
+        }
+        if overlaps(other, region) {
+            return None;
+        }
+    }
+    None
+}
+
+fn overlaps(a: Region, b: Region) -> bool {
+    a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+}
+
+fn run_worker(shared: &Shared) {
+    loop {
+        let job = {
+            let Ok(mut queue) = shared.queue.lock() else {
+                return;
+            };
+            loop {
+                if let Some(job) = queue.pending.pop_front() {
+                    shared.space.notify_one();
+                    break job;
+                }
+                if queue.stopping {
+                    return;
+                }
+                queue = match shared.work.wait(queue) {
+                    Ok(queue) => queue,
+                    Err(_) => return,
+                };
+            }
+        };
+
+        let jpeg = match job.tile {
+            Tile::Jpeg(jpeg) => Some(jpeg),
+            Tile::Image(image, quality) => DrawingUtils::image_as_jpeg(image, BG_COLOUR, quality)
+                .inspect_err(|e| warn!("Failed to encode image: {e}"))
+                .ok(),
+        };
+        let ready = jpeg.map(|jpeg| (job.sender, jpeg, job.x, job.y));
+        if let Err(e) = send_in_order(shared, job.sequence, ready) {
+            if let Ok(mut queue) = shared.queue.lock() {
+                queue.error.get_or_insert(e.to_string());
+            }
+            shared.space.notify_all();
+        }
+    }
+}
+
+// Sends this image, and any queued after it which are ready, once everything before it has gone.
+// The outbox lock is held while sending, so only one thread talks to the device at a time.
+fn send_in_order(
+    shared: &Shared,
+    sequence: u64,
+    ready: Option<(Sender<ControlMessage>, Vec<u8>, u32, u32)>,
+) -> Result<()> {
+    let mut outbox = shared
+        .outbox
+        .lock()
+        .map_err(|_| anyhow!("Image encoder has stopped"))?;
+    outbox.ready.insert(sequence, ready);
+
+    loop {
+        let next = outbox.next;
+        let Some(entry) = outbox.ready.remove(&next) else {
+            break;
+        };
+        outbox.next += 1;
+        let Some((sender, jpeg, x, y)) = entry else {
+            continue;
+        };
+        let (tx, rx) = oneshot::channel();
+        sender.send(SendImage(jpeg, x, y, tx))?;
+        rx.recv()??;
+    }
+    Ok(())
+}
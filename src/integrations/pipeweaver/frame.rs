@@ -0,0 +1,234 @@
+// When lots of small parts of the display change at once (for example, a mute box and a volume
+// dial), sending each as a separate image can cause visible tearing as the Mix draws them one at
+// a time. When coalescing is enabled, partial updates are drawn onto a local copy of the screen
+// instead, and the changed region is sent as a single image once per frame interval.
+//
+// Everything sent to the display passes through here, so this is also where images are rotated
+// to match how the device is mounted, and moved around to protect against burn-in.
+//
+// Nothing is encoded or sent here, finished images are handed to the encoder, which does that in
+// the background (see encoder.rs).
+//
+// Performance mode is for setups (usually older USB hubs) where the bandwidth used by the display
+// causes lag. Updates are always coalesced, sent less often and at a lower quality, and separate
+// changed regions are sent separately, rather than as one image covering all of them.
+
+use crate::device_manager::ControlMessage;
+use crate::integrations::burn_in::{BurnInGuard, BurnInSettings};
+use crate::integrations::orientation::Orientation;
+use crate::integrations::pipeweaver::encoder::{Encoder, Tile};
+use crate::integrations::pipeweaver::jpeg_as_img;
+use crate::integrations::pipeweaver::layout::{
+    BG_COLOUR, DISPLAY_DIMENSIONS, DrawingUtils, JPEG_QUALITY, PERFORMANCE_JPEG_QUALITY,
+};
+use anyhow::Result;
+use beacn_lib::crossbeam::channel::Sender;
+use image::imageops::crop_imm;
+use image::{ImageBuffer, RgbaImage};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How often coalesced updates are sent to the device
+pub(crate) const FRAME_INTERVAL: Duration = Duration::from_millis(40);
+
+/// The frame interval used when saving power, updates are always coalesced in this mode
+pub(crate) const POWER_SAVE_FRAME_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The frame interval used in performance mode
+pub(crate) const PERFORMANCE_FRAME_INTERVAL: Duration = Duration::from_millis(80);
+
+// Past this many separate regions, it's cheaper to send a single image covering them all
+const MAX_DIRTY_REGIONS: usize = 6;
+
+// A region of the display, stored as (x1, y1, x2, y2), where x2 and y2 are exclusive
+type Region = (u32, u32, u32, u32);
+
+pub(crate) struct FrameBuffer {
+    enabled: bool,
+    orientation: Orientation,
+    performance: bool,
+    canvas: RgbaImage,
+    dirty: Vec<Region>,
+    burn_in: BurnInGuard,
+    encoder: Encoder,
+}
+
+impl FrameBuffer {
+    pub fn new(
+        enabled: bool,
+        performance: bool,
+        orientation: Orientation,
+        burn_in_rx: watch::Receiver<BurnInSettings>,
+    ) -> Self {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        Self {
+            enabled,
+            orientation,
+            performance,
+            canvas: ImageBuffer::from_pixel(width, height, BG_COLOUR),
+            dirty: vec![],
+            burn_in: BurnInGuard::new(burn_in_rx),
+            encoder: Encoder::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Changes the coalescing mode, the caller should follow this with a full redraw so the
+    /// canvas matches what's actually on the screen.
+    pub fn set_enabled(&mut self, sender: &Sender<ControlMessage>, enabled: bool) -> Result<()> {
+        self.flush(sender)?;
+        self.enabled = enabled;
+        Ok(())
+    }
+
+    /// Changes performance mode, the caller should follow this with a full redraw so the screen
+    /// is sent at the new quality
+    pub fn set_performance(
+        &mut self,
+        sender: &Sender<ControlMessage>,
+        enabled: bool,
+    ) -> Result<()> {
+        self.flush(sender)?;
+        self.performance = enabled;
+        Ok(())
+    }
+
+    /// Changes the display orientation, the caller should follow this with a full redraw
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    /// Checks whether the burn-in protection wants to move things, returns true if the caller
+    /// should follow this with a full redraw
+    pub fn check_burn_in(&mut self) -> bool {
+        self.burn_in.check()
+    }
+
+    /// Notes an interaction with the device, returns true if a full redraw is needed
+    pub fn note_activity(&mut self) -> bool {
+        self.burn_in.activity()
+    }
+
+    /// Sends an image (or JPEG) to be drawn at the given position, or queues it for the next
+    /// frame
+    pub fn send(
+        &mut self,
+        sender: &Sender<ControlMessage>,
+        tile: impl Into<Tile>,
+        x: u32,
+        y: u32,
+    ) -> Result<()> {
+        if !self.enabled {
+            return self.send_now(sender, tile, x, y);
+        }
+
+        let image = match tile.into() {
+            Tile::Jpeg(jpeg) => jpeg_as_img(&jpeg)?,
+            Tile::Image(image, _) => image,
+        };
+        DrawingUtils::composite_from_pos(&mut self.canvas, &image, (x, y));
+        self.mark_dirty((x, y, x + image.width(), y + image.height()));
+        Ok(())
+    }
+
+    /// Sends an image covering the entire display, this is never delayed
+    pub fn send_full(&mut self, sender: &Sender<ControlMessage>, image: RgbaImage) -> Result<()> {
+        if self.enabled {
+            self.canvas = image.clone();
+            self.dirty.clear();
+        }
+        self.send_image(sender, image, 0, 0)
+    }
+
+    /// Sends any pending changes to the device, as a single image unless in performance mode
+    pub fn flush(&mut self, sender: &Sender<ControlMessage>) -> Result<()> {
+        for (x1, y1, x2, y2) in std::mem::take(&mut self.dirty) {
+            if x2 <= x1 || y2 <= y1 {
+                continue;
+            }
+
+            let region = crop_imm(&self.canvas, x1, y1, x2 - x1, y2 - y1).to_image();
+            self.send_image(sender, region, x1, y1)?;
+        }
+        Ok(())
+    }
+
+    fn mark_dirty(&mut self, region: Region) {
+        // Clamp to the display, just in case something is drawn over the edge
+        let (width, height) = DISPLAY_DIMENSIONS;
+        let (x1, y1, x2, y2) = region;
+        let mut region = (x1.min(width), y1.min(height), x2.min(width), y2.min(height));
+
+        if !self.performance {
+            // Everything goes out in one image, so the screen changes all at once
+            if let Some(current) = self.dirty.pop() {
+                region = union(current, region);
+            }
+            self.dirty.push(region);
+            return;
+        }
+
+        // Absorb anything this overlaps, which may then overlap something else
+        while let Some(index) = self.dirty.iter().position(|r| overlaps(*r, region)) {
+            region = union(self.dirty.swap_remove(index), region);
+        }
+        self.dirty.push(region);
+
+        if self.dirty.len() > MAX_DIRTY_REGIONS {
+            let all = self.dirty.drain(..).reduce(union);
+            self.dirty.extend(all);
+        }
+    }
+
+    /// Sends an image (or JPEG) straight to the device, skipping any coalescing
+    pub fn send_now(
+        &self,
+        sender: &Sender<ControlMessage>,
+        tile: impl Into<Tile>,
+        x: u32,
+        y: u32,
+    ) -> Result<()> {
+        match tile.into() {
+            // Pre-rendered images can only be rotated or moved once they're decoded
+            Tile::Jpeg(jpeg) if self.orientation.needs_transform() || self.burn_in.is_active() => {
+                self.send_image(sender, jpeg_as_img(&jpeg)?, x, y)
+            }
+            Tile::Jpeg(jpeg) => self.encoder.queue(sender, Tile::Jpeg(jpeg), x, y),
+            Tile::Image(image, _) => self.send_image(sender, image, x, y),
+        }
+    }
+
+    fn send_image(
+        &self,
+        sender: &Sender<ControlMessage>,
+        image: RgbaImage,
+        x: u32,
+        y: u32,
+    ) -> Result<()> {
+        let Some((image, x, y)) = self.burn_in.apply(image, x, y) else {
+            return Ok(());
+        };
+        let (image, x, y) = self.orientation.transform(image, x, y);
+        let quality = match self.performance {
+            true => PERFORMANCE_JPEG_QUALITY,
+            false => JPEG_QUALITY,
+        };
+        self.encoder
+            .queue(sender, Tile::Image(image, quality), x, y)
+    }
+}
+
+fn union(a: Region, b: Region) -> Region {
+    (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3))
+}
+
+fn overlaps(a: Region, b: Region) -> bool {
+    a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3
+}
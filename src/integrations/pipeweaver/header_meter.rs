@@ -0,0 +1,63 @@
+/*
+  A thin meter along the bottom of the header, showing the level of the main output (the first
+  output in Pipeweaver's order, normally the headphones) so it's clear at a glance whether
+  anything is playing, whichever channels are on the dials. Pipeweaver only reports a single
+  level for each channel, so it's one bar rather than a left and right pair.
+
+  Meter messages arrive far more often than the display needs, so the latest level is kept here
+  and only drawn (as a partial update) a few times a second, and only when it's changed.
+*/
+
+use crate::settings;
+use std::time::Duration;
+
+/// How often the meter is redrawn while it's shown
+pub const UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default)]
+pub struct HeaderMeter {
+    enabled: bool,
+    level: u8,
+
+    // What was last drawn, `None` when the header is showing through
+    shown: Option<u8>,
+}
+
+impl HeaderMeter {
+    pub fn new() -> Self {
+        Self {
+            enabled: settings::get().header_meter,
+            ..Default::default()
+        }
+    }
+
+    /// Picks up the setting, it's changed from the integrations page
+    pub fn check_enabled(&mut self) {
+        self.enabled = settings::get().header_meter;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_level(&mut self, level: u8) {
+        self.level = level.min(100);
+    }
+
+    /// The level to draw when the whole screen is being drawn
+    pub fn current(&mut self) -> Option<u8> {
+        self.shown = self.enabled.then_some(self.level);
+        self.shown
+    }
+
+    /// What to draw if it's changed since it was last drawn, where `Some(None)` means the meter
+    /// has been turned off, and the header should be drawn back over it
+    pub fn changed(&mut self) -> Option<Option<u8>> {
+        let level = self.enabled.then_some(self.level);
+        if level == self.shown {
+            return None;
+        }
+        self.shown = level;
+        Some(level)
+    }
+}
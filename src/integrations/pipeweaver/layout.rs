@@ -1,29 +1,29 @@
 // This file is a mess, and it's mostly intentional for the first pass, it primarily informs
 // on how to render everything, positions, shapes, etc... I'll keep some level of documentation
 
-use crate::APP_NAME;
-use anyhow::{Context, Result, anyhow, bail};
-use enum_map::{EnumMap, enum_map};
-use fontdue::Font;
+use crate::integrations::header_widgets::HeaderSlot;
+use crate::integrations::pipeweaver::cache::{AssetCache, AssetImages, AssetKey};
+use crate::integrations::pipeweaver::text::{DISPLAY_TEXT, TextStyle};
+use anyhow::{Result, anyhow};
+use enum_map::{Enum, EnumMap, enum_map};
 use image::codecs::jpeg::JpegEncoder;
+use image::imageops::{FilterType, crop_imm, resize};
 use image::{ExtendedColorType, ImageBuffer, Rgb, RgbImage, Rgba, RgbaImage, load_from_memory};
 use log::{debug, info, warn};
-use pipeweaver_shared::Mix;
+use pipeweaver_shared::{Mix, MuteTarget};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::f32::consts::PI;
-use std::fs;
-use std::fs::File;
-use std::io::ErrorKind::UnexpectedEof;
-use std::io::{BufReader, BufWriter, Cursor, Read, Write};
-use std::path::PathBuf;
+use std::hash::Hash;
+use std::io::Cursor;
 use std::sync::LazyLock;
 use std::time::Instant;
 use strum::IntoEnumIterator;
-use xdg::BaseDirectories;
+use strum_macros::{Display, EnumIter};
 
-// First thing we need, is to device the font used for rendering on the screen
-#[allow(unused)]
+// First thing we need, is to device the font used for rendering on the screen, these can be
+// overridden by the user, see text.rs
 pub(crate) static FONT: &[u8] =
     include_bytes!("../../../resources/fonts/noto/NotoSans-SemiBold.ttf");
 pub(crate) static FONT_BOLD: &[u8] =
@@ -33,14 +33,13 @@ pub(crate) static HEADER: &[u8] = include_bytes!("../../../resources/screens/hea
 
 pub(crate) static JPEG_QUALITY: u8 = 70;
 
+// Used in performance mode, where the USB bandwidth matters more than how the screen looks
+pub(crate) static PERFORMANCE_JPEG_QUALITY: u8 = 40;
+
 // Now, for sanity's sake, we're going to define some basic types
 pub(crate) type Dimension = (u32, u32);
 pub(crate) type Position = (u32, u32);
 
-// Cache helpers
-pub(crate) const CACHE_VERSION: u16 = 1;
-pub(crate) const CACHE_PATH: &str = "pipeweaver_mixer_cache.bin";
-
 type Lazy<T> = LazyLock<T>;
 
 // These types are used for rendering the Dials, and are mostly related to precaching images
@@ -48,10 +47,12 @@ type Lazy<T> = LazyLock<T>;
 type DistanceAngleMap = Lazy<(Vec<Vec<f32>>, Vec<Vec<f32>>)>;
 type DialBaseImage = Lazy<RgbaImage>;
 type DialValueImage = Lazy<EnumMap<Mix, HashMap<u8, RgbaImage>>>;
-type DialTextImage = Lazy<HashMap<u8, RgbaImage>>;
+type DialTextImage = Lazy<EnumMap<VolumeUnit, HashMap<u8, RgbaImage>>>;
 type DialVolumeJPEG = Lazy<EnumMap<Mix, HashMap<u8, HashMap<u8, Vec<u8>>>>>;
 type DialMeterImage = Lazy<EnumMap<Mix, HashMap<u8, RgbaImage>>>;
-type DialMeterData = EnumMap<Mix, HashMap<u8, HashMap<u8, Vec<u8>>>>;
+type DialMeterData = EnumMap<Mix, HashMap<u8, AssetImages>>;
+type DialCompactData = EnumMap<Mix, HashMap<u8, Vec<u8>>>;
+type DialCompactJPEG = Lazy<DialCompactData>;
 
 // Resolution of the Beacn Mix / Mix Create Screens, and how many channels to display
 pub(crate) static DISPLAY_DIMENSIONS: Dimension = (800, 480);
@@ -64,16 +65,82 @@ pub(crate) static POSITION_ROOT: Position = (0, 80);
 // as well as a base circle. All of these then get composited and cached into about 200 "final"
 // JPEGs which can be sent as-is to the Mix / Mix Create
 //
-// This list may go up in the future when Metering is added, which will throw us into the thousands
-// of cached images region, at which point I'll be ensuring that disk caching is an option, until
-// then though, it takes 6 seconds in DEBUG mode to generate these images, and 0.6s in RELEASE
-// mode, which is good enough for now.
+// With metering this is in the thousands of images, so they're cached on disk per-asset (see
+// cache.rs), generating them all takes 6 seconds in DEBUG mode, and 0.6s in RELEASE mode.
 pub(crate) static DISTANCE_ANGLE_MAP: DistanceAngleMap = Lazy::new(DialHandler::precompute_maps);
 pub(crate) static DIAL_BASE_IMAGE: DialBaseImage = Lazy::new(DialHandler::precompute_dial_bg);
 pub(crate) static DIAL_MIX_IMAGES: DialValueImage = Lazy::new(DialHandler::precompute_dial_volumes);
 pub(crate) static DIAL_TEXT_IMAGES: DialTextImage = Lazy::new(DialHandler::precompute_dial_text);
 pub(crate) static DIAL_METER_IMAGES: DialMeterImage = Lazy::new(DialHandler::precompute_meters);
-pub(crate) static DIAL_VOLUME_JPEG: DialVolumeJPEG = Lazy::new(DialHandler::composite_dials);
+pub(crate) static DIAL_VOLUME_JPEG: DialVolumeJPEG =
+    Lazy::new(|| DialHandler::composite_dials(VolumeUnit::Percent));
+
+// Only built if a Mix is set to show decibels, so it doesn't slow down everyone else's startup
+pub(crate) static DIAL_VOLUME_DB_JPEG: DialVolumeJPEG =
+    Lazy::new(|| DialHandler::composite_dials(VolumeUnit::Decibels));
+
+// The shrunken dials for the compact layout, only built once a device switches to it
+pub(crate) static COMPACT_DIAL_JPEG: DialCompactJPEG =
+    Lazy::new(|| DialHandler::composite_compact_dials(VolumeUnit::Percent));
+pub(crate) static COMPACT_DIAL_DB_JPEG: DialCompactJPEG =
+    Lazy::new(|| DialHandler::composite_compact_dials(VolumeUnit::Decibels));
+
+/// How the volume is written in the middle of each dial
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Display,
+    EnumIter,
+    Enum,
+    Serialize,
+    Deserialize,
+)]
+pub enum VolumeUnit {
+    #[default]
+    #[strum(to_string = "Percent")]
+    Percent,
+
+    #[strum(to_string = "Decibels")]
+    Decibels,
+}
+
+impl VolumeUnit {
+    pub(crate) fn format(&self, volume: u8) -> String {
+        match (self, volume) {
+            (VolumeUnit::Percent, volume) => DISPLAY_TEXT.format_percent(volume),
+            (VolumeUnit::Decibels, 0) => String::from("-inf\u{00A0}dB"),
+            (VolumeUnit::Decibels, volume) => {
+                format!("{}\u{00A0}dB", Self::decibels(volume).round() as i32)
+            }
+        }
+    }
+
+    // Pipeweaver (like the rest of PipeWire) treats volumes as cubic, so 50% is about -18dB
+    fn decibels(volume: u8) -> f32 {
+        60.0 * (volume as f32 / 100.0).log10()
+    }
+
+    /// The composited dial images for this unit, indexed by mix, volume, then meter level
+    pub(crate) fn dial_images(&self) -> &'static EnumMap<Mix, HashMap<u8, AssetImages>> {
+        match self {
+            VolumeUnit::Percent => &DIAL_VOLUME_JPEG,
+            VolumeUnit::Decibels => &DIAL_VOLUME_DB_JPEG,
+        }
+    }
+
+    /// The shrunken dial images used by the compact layout, indexed by mix then volume
+    pub(crate) fn compact_dial_images(&self) -> &'static DialCompactData {
+        match self {
+            VolumeUnit::Percent => &COMPACT_DIAL_JPEG,
+            VolumeUnit::Decibels => &COMPACT_DIAL_DB_JPEG,
+        }
+    }
+}
 
 // Next up, we define some colours, which will be used when generating components
 pub(crate) static TEXT_COLOUR: Rgba<u8> = Rgba([180, 180, 180, 255]);
@@ -129,7 +196,6 @@ pub(crate) static HEADER_POSITION: Position = (
     CONTENT_POSITION.1 + CHANNEL_INNER_RADIUS.0,
 );
 pub(crate) static HEADER_FONT_SIZE: f32 = 22.0;
-pub(crate) static HEADER_FONT: &[u8] = FONT_BOLD;
 pub(crate) static HEADER_TEXT_DIMENSIONS: Dimension = (CONTENT_DIMENSIONS.0, 30);
 
 // Generic Bar Layout
@@ -144,8 +210,7 @@ static VOLUME_CROP: u32 = 10;
 pub(crate) static VOLUME_DIMENSIONS: Dimension = (CONTENT_DIMENSIONS.0, CONTENT_DIMENSIONS.0);
 pub(crate) static VOLUME_POSITION: Position =
     (CONTENT_POSITION.0, HEADER_BAR_POSITION.1 + BAR_DIMENSIONS.1);
-static VOLUME_FONT: &[u8] = FONT_BOLD;
-static VOLUME_FONT_SIZE: f32 = 34.0;
+pub(crate) static VOLUME_FONT_SIZE: f32 = 34.0;
 
 // Next a coloured bar before the mute buttons
 pub(crate) static MUTE_BAR_POSITION: Position = (
@@ -162,7 +227,6 @@ pub(crate) static MUTE_AREA_POSITION: Position =
 
 pub(crate) static MUTE_BUTTON_DIMENSIONS: Dimension = (CONTENT_DIMENSIONS.0, 37);
 
-pub(crate) static MUTE_POSITION_A: Position = MUTE_AREA_POSITION;
 pub(crate) static MUTE_LOCAL_POSITION_A: Position = (0, 0);
 pub(crate) static MUTE_A_BORDER: BorderThickness = BorderThickness(0, 0, 2, 0);
 
@@ -172,18 +236,217 @@ pub(crate) static ICON_MARGIN: u32 = 10;
 
 pub(crate) static MUTE_TEXT_DIMENSIONS: Dimension = (CONTENT_DIMENSIONS.0, 30);
 pub(crate) static MUTE_FONT_SIZE: f32 = 20.0;
-pub(crate) static MUTE_FONT: &[u8] = FONT;
 
-pub(crate) static MUTE_POSITION_B: Position = (
-    MUTE_POSITION_A.0,
-    MUTE_POSITION_A.1 + MUTE_BUTTON_DIMENSIONS.1 + MUTE_A_BORDER.3 + MUTE_GAP.1,
-);
+// Status messages (Loading, Connection Lost, etc) drawn across the whole display
+pub(crate) static STATUS_FONT_SIZE: f32 = 28.0;
+
 pub(crate) static MUTE_LOCAL_POSITION_B: Position = (
     MUTE_LOCAL_POSITION_A.0,
     MUTE_LOCAL_POSITION_A.1 + MUTE_BUTTON_DIMENSIONS.1 + MUTE_A_BORDER.3 + MUTE_GAP.1,
 );
 pub(crate) static MUTE_B_BORDER: BorderThickness = BorderThickness(2, 0, 0, 0);
 
+// The statics above describe the standard layout, four full height channels across the screen.
+// Everything the channel renderer needs to know about where things go is pulled together here,
+// so the screen can also be laid out differently (see ScreenLayout).
+#[derive(Debug)]
+pub(crate) struct LayoutTheme {
+    pub(crate) columns: u32,
+    pub(crate) rows: u32,
+    pub(crate) channel: Dimension,
+
+    // The inner box is shorter on the Mix (and for outputs), as there's only one mute button
+    pub(crate) inner_position: Position,
+    pub(crate) inner: Dimension,
+    pub(crate) inner_mix: Dimension,
+
+    pub(crate) header_position: Position,
+    pub(crate) header: Dimension,
+    pub(crate) header_text: Dimension,
+    pub(crate) header_bar_position: Position,
+    pub(crate) bar: Dimension,
+
+    // The dial as drawn, after the bottom has been cropped off by the mute bar
+    pub(crate) volume_position: Position,
+    pub(crate) volume: Dimension,
+    pub(crate) mute_bar_position: Position,
+
+    pub(crate) mute_area_position: Position,
+    pub(crate) mute_area: Dimension,
+    pub(crate) mute_area_mix: Dimension,
+
+    // The size of each mute button when there are two, when there's only one it fills the width
+    pub(crate) mute_button: Dimension,
+    pub(crate) mute_local_a: Position,
+    pub(crate) mute_local_b: Position,
+    pub(crate) mute_a_border: BorderThickness,
+    pub(crate) mute_b_border: BorderThickness,
+
+    // Whether the mute buttons have room for 'Mute to All' next to the icon
+    pub(crate) mute_labels: bool,
+
+    // Whether the dial is shrunk down from the standard images, which drops the meter
+    pub(crate) scaled_dial: bool,
+}
+
+pub(crate) static STANDARD_LAYOUT: LayoutTheme = LayoutTheme {
+    columns: CHANNEL_COUNT,
+    rows: 1,
+    channel: CHANNEL_DIMENSIONS,
+    inner_position: CHANNEL_INNER_POSITION,
+    inner: CHANNEL_INNER_DIMENSIONS,
+    inner_mix: CHANNEL_INNER_DIMENSIONS_MIX,
+    header_position: HEADER_POSITION,
+    header: HEADER_DIMENSIONS,
+    header_text: HEADER_TEXT_DIMENSIONS,
+    header_bar_position: HEADER_BAR_POSITION,
+    bar: BAR_DIMENSIONS,
+    volume_position: VOLUME_POSITION,
+    volume: (VOLUME_DIMENSIONS.0, VOLUME_DIMENSIONS.1 - VOLUME_CROP),
+    mute_bar_position: MUTE_BAR_POSITION,
+    mute_area_position: MUTE_AREA_POSITION,
+    mute_area: MUTE_AREA_DIMENSIONS,
+    mute_area_mix: MUTE_AREA_DIMENSIONS_MIX,
+    mute_button: MUTE_BUTTON_DIMENSIONS,
+    mute_local_a: MUTE_LOCAL_POSITION_A,
+    mute_local_b: MUTE_LOCAL_POSITION_B,
+    mute_a_border: MUTE_A_BORDER,
+    mute_b_border: MUTE_B_BORDER,
+    mute_labels: true,
+    scaled_dial: false,
+};
+
+// Two rows of four, each half the height of a standard channel. The header stays the same size
+// so names are still readable, the dial shrinks and the mute buttons sit side by side.
+static COMPACT_CHANNEL: Dimension = (CHANNEL_DIMENSIONS.0, CHANNEL_DIMENSIONS.1 / 2);
+static COMPACT_DIAL_HEIGHT: u32 = 94;
+static COMPACT_MUTE_HEIGHT: u32 = 30;
+static COMPACT_MUTE_BAR_POSITION: Position =
+    (CONTENT_POSITION.0, VOLUME_POSITION.1 + COMPACT_DIAL_HEIGHT);
+static COMPACT_MUTE_AREA_POSITION: Position = (
+    CONTENT_POSITION.0,
+    COMPACT_MUTE_BAR_POSITION.1 + BAR_DIMENSIONS.1,
+);
+static COMPACT_INNER: Dimension = (
+    CHANNEL_INNER_DIMENSIONS.0,
+    COMPACT_MUTE_AREA_POSITION.1 + COMPACT_MUTE_HEIGHT - CHANNEL_INNER_POSITION.1,
+);
+
+pub(crate) static COMPACT_LAYOUT: LayoutTheme = LayoutTheme {
+    columns: CHANNEL_COUNT,
+    rows: 2,
+    channel: COMPACT_CHANNEL,
+    inner_position: CHANNEL_INNER_POSITION,
+    inner: COMPACT_INNER,
+    inner_mix: COMPACT_INNER,
+    header_position: HEADER_POSITION,
+    header: HEADER_DIMENSIONS,
+    header_text: HEADER_TEXT_DIMENSIONS,
+    header_bar_position: HEADER_BAR_POSITION,
+    bar: BAR_DIMENSIONS,
+    volume_position: VOLUME_POSITION,
+    volume: (CONTENT_DIMENSIONS.0, COMPACT_DIAL_HEIGHT),
+    mute_bar_position: COMPACT_MUTE_BAR_POSITION,
+    mute_area_position: COMPACT_MUTE_AREA_POSITION,
+    mute_area: (CONTENT_DIMENSIONS.0, COMPACT_MUTE_HEIGHT),
+    mute_area_mix: (CONTENT_DIMENSIONS.0, COMPACT_MUTE_HEIGHT),
+    mute_button: (CONTENT_DIMENSIONS.0 / 2, COMPACT_MUTE_HEIGHT),
+    mute_local_a: (0, 0),
+    mute_local_b: (CONTENT_DIMENSIONS.0 / 2, 0),
+    mute_a_border: BorderThickness(0, 1, 0, 0),
+    mute_b_border: BorderThickness(0, 0, 0, 1),
+    mute_labels: false,
+    scaled_dial: true,
+};
+
+impl LayoutTheme {
+    /// How many channels fit on a page
+    pub(crate) fn channel_count(&self) -> u32 {
+        self.columns * self.rows
+    }
+
+    /// Where the channel at this index on the page is drawn on the display
+    pub(crate) fn channel_position(&self, index: u32) -> Position {
+        let (width, height) = self.channel;
+        let (column, row) = (index % self.columns, index / self.columns);
+        (
+            POSITION_ROOT.0 + column * width,
+            POSITION_ROOT.1 + row * height,
+        )
+    }
+
+    pub(crate) fn mute_local(&self, target: MuteTarget) -> Position {
+        match target {
+            MuteTarget::TargetA => self.mute_local_a,
+            MuteTarget::TargetB => self.mute_local_b,
+        }
+    }
+
+    pub(crate) fn mute_position(&self, target: MuteTarget) -> Position {
+        let (x, y) = self.mute_local(target);
+        (self.mute_area_position.0 + x, self.mute_area_position.1 + y)
+    }
+
+    pub(crate) fn mute_border(&self, target: MuteTarget) -> BorderThickness {
+        match target {
+            MuteTarget::TargetA => self.mute_a_border,
+            MuteTarget::TargetB => self.mute_b_border,
+        }
+    }
+
+    /// The size of a mute button, a lone button takes the whole width of the channel
+    pub(crate) fn mute_size(&self, has_mute_b: bool) -> Dimension {
+        match has_mute_b {
+            true => self.mute_button,
+            false => (self.mute_area.0, self.mute_button.1),
+        }
+    }
+
+    /// The strip of background between stacked mute buttons, if they're stacked
+    pub(crate) fn mute_gap(&self) -> Option<(Position, Dimension)> {
+        let (x, y) = self.mute_position(MuteTarget::TargetA);
+        let y = y + self.mute_button.1;
+        let (_, b_y) = self.mute_position(MuteTarget::TargetB);
+        (b_y > y).then_some(((x, y), (self.mute_area.0, b_y - y)))
+    }
+
+    /// The dial image for a volume, only the standard layout has room to show the meter
+    pub(crate) fn dial_image(
+        &self,
+        unit: VolumeUnit,
+        mix: Mix,
+        volume: u8,
+        meter: u8,
+    ) -> Option<&'static Vec<u8>> {
+        match self.scaled_dial {
+            true => unit.compact_dial_images()[mix].get(&volume),
+            false => unit.dial_images()[mix].get(&volume)?.get(&meter),
+        }
+    }
+}
+
+/// How the channels are arranged on the Mix / Mix Create screen
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Display, EnumIter, Serialize, Deserialize,
+)]
+pub enum ScreenLayout {
+    #[default]
+    #[strum(to_string = "Standard (4 Channels)")]
+    Standard,
+
+    #[strum(to_string = "Compact (8 Channels)")]
+    Compact,
+}
+
+impl ScreenLayout {
+    pub(crate) fn theme(&self) -> &'static LayoutTheme {
+        match self {
+            ScreenLayout::Standard => &STANDARD_LAYOUT,
+            ScreenLayout::Compact => &COMPACT_LAYOUT,
+        }
+    }
+}
+
 pub(crate) static MUTE_COLOUR_OFF: Rgba<u8> = Rgba([25, 33, 43, 220]);
 pub(crate) static MUTE_COLOUR_ON: Rgba<u8> = Rgba([120, 0, 0, 255]);
 
@@ -204,6 +467,43 @@ pub(crate) static MUTE_MUTED_ICON: Lazy<RgbaImage> = Lazy::new(|| {
 
 pub(crate) static BORDER_RADIUS_NONE: BorderRadius = BorderRadius(0, 0, 0, 0);
 
+// Widgets (clock, timer, etc) can be drawn in the header, either side of the logo. They're drawn
+// over a copy of the header, so a widget can be updated without redrawing the rest of it.
+pub(crate) static HEADER_WIDGET_DIMENSIONS: Dimension = (250, 60);
+pub(crate) static HEADER_WIDGET_MARGIN: u32 = 15;
+pub(crate) static HEADER_WIDGET_MAX_CHARS: usize = 24;
+
+// While everything is muted, a banner is drawn over the logo in the middle of the header
+pub(crate) static PANIC_MUTE_DIMENSIONS: Dimension = (240, 50);
+pub(crate) static PANIC_MUTE_POSITION: Position = (
+    (DISPLAY_DIMENSIONS.0 - PANIC_MUTE_DIMENSIONS.0) / 2,
+    (POSITION_ROOT.1 - PANIC_MUTE_DIMENSIONS.1) / 2,
+);
+pub(crate) static PANIC_MUTE_COLOUR: Rgba<u8> = Rgba([200, 40, 40, 255]);
+pub(crate) static PANIC_MUTE_RADIUS: BorderRadius = BorderRadius(10, 10, 10, 10);
+
+// The output meter runs along the bottom of the header, below the widgets and the mute banner
+pub(crate) static HEADER_METER_DIMENSIONS: Dimension =
+    (DISPLAY_DIMENSIONS.0 - HEADER_WIDGET_MARGIN * 2, 6);
+pub(crate) static HEADER_METER_POSITION: Position = (HEADER_WIDGET_MARGIN, POSITION_ROOT.1 - 10);
+pub(crate) static HEADER_METER_TRACK: Rgba<u8> = Rgba([50, 54, 68, 255]);
+
+// Turning a dial briefly shows the channel and its new volume in a box over the middle of the
+// channels, so the value can be read at a glance without finding the right dial
+pub(crate) static VOLUME_OVERLAY_DIMENSIONS: Dimension = (360, 140);
+pub(crate) static VOLUME_OVERLAY_POSITION: Position = (
+    (DISPLAY_DIMENSIONS.0 - VOLUME_OVERLAY_DIMENSIONS.0) / 2,
+    POSITION_ROOT.1 + (DISPLAY_DIMENSIONS.1 - POSITION_ROOT.1 - VOLUME_OVERLAY_DIMENSIONS.1) / 2,
+);
+pub(crate) static VOLUME_OVERLAY_BORDER: BorderThickness = BorderThickness(4, 4, 4, 4);
+pub(crate) static VOLUME_OVERLAY_RADIUS: BorderRadius = BorderRadius(12, 12, 12, 12);
+
+pub(crate) static HEADER_IMAGE: Lazy<RgbaImage> = Lazy::new(|| {
+    load_from_memory(HEADER)
+        .expect("Failed to Load Image")
+        .to_rgba8()
+});
+
 // Helper Structs
 /// Top left, Top right, Bottom left, Bottom right
 #[derive(Debug, Copy, Clone)]
@@ -230,6 +530,186 @@ pub(crate) enum TextAlign {
     Right,
 }
 
+pub(crate) struct HeaderWidgetRenderer;
+impl HeaderWidgetRenderer {
+    pub(crate) fn position(slot: HeaderSlot) -> Position {
+        let (width, _) = HEADER_WIDGET_DIMENSIONS;
+        match slot {
+            HeaderSlot::Left => (HEADER_WIDGET_MARGIN, 0),
+            HeaderSlot::Right => (DISPLAY_DIMENSIONS.0 - HEADER_WIDGET_MARGIN - width, 0),
+        }
+    }
+
+    /// Draws a widget over its part of the header, empty text just restores the header
+    pub(crate) fn draw(slot: HeaderSlot, text: &str) -> (RgbaImage, Position) {
+        let (x, y) = Self::position(slot);
+        let (width, height) = HEADER_WIDGET_DIMENSIONS;
+        let mut image = crop_imm(&*HEADER_IMAGE, x, y, width, height).to_image();
+        if text.is_empty() {
+            return (image, (x, y));
+        }
+
+        // Long titles would run into the logo
+        let text = match text.chars().count() > HEADER_WIDGET_MAX_CHARS {
+            true => {
+                let text: String = text.chars().take(HEADER_WIDGET_MAX_CHARS - 1).collect();
+                format!("{}…", text.trim_end())
+            }
+            false => text.to_string(),
+        };
+
+        let align = match slot {
+            HeaderSlot::Left => TextAlign::Left,
+            HeaderSlot::Right => TextAlign::Right,
+        };
+        let text =
+            DrawingUtils::draw_text(text, width, height, TextStyle::Mute, TEXT_COLOUR, align);
+        DrawingUtils::composite_from(&mut image, &text, 0, 0);
+        (image, (x, y))
+    }
+
+    /// Draws every widget onto a full screen image
+    pub(crate) fn draw_all(base: &mut RgbaImage, widgets: Vec<(HeaderSlot, String)>) {
+        for (slot, text) in widgets {
+            let (image, position) = Self::draw(slot, &text);
+            DrawingUtils::composite_from_pos(base, &image, position);
+        }
+    }
+}
+
+pub(crate) struct PanicMuteRenderer;
+impl PanicMuteRenderer {
+    /// Draws the banner, or the header behind it when nothing is muted
+    pub(crate) fn draw(active: bool) -> (RgbaImage, Position) {
+        let (x, y) = PANIC_MUTE_POSITION;
+        let (width, height) = PANIC_MUTE_DIMENSIONS;
+        let mut image = crop_imm(&*HEADER_IMAGE, x, y, width, height).to_image();
+        if !active {
+            return (image, (x, y));
+        }
+
+        let banner = DrawingUtils::draw_box(
+            width,
+            height,
+            BorderThickness(0, 0, 0, 0),
+            PANIC_MUTE_RADIUS,
+            PANIC_MUTE_COLOUR,
+            Rgba([0, 0, 0, 0]),
+            PANIC_MUTE_COLOUR,
+        );
+        DrawingUtils::composite_from(&mut image, &banner, 0, 0);
+
+        let text = String::from("ALL MUTED");
+        let colour = Rgba([255, 255, 255, 255]);
+        let text = DrawingUtils::draw_text(
+            text,
+            width,
+            height,
+            TextStyle::Header,
+            colour,
+            TextAlign::Center,
+        );
+        DrawingUtils::composite_from(&mut image, &text, 0, 0);
+        (image, (x, y))
+    }
+
+    /// Draws the banner onto a full screen image, if everything is muted
+    pub(crate) fn draw_onto(base: &mut RgbaImage, active: bool) {
+        if active {
+            let (image, position) = Self::draw(true);
+            DrawingUtils::composite_from_pos(base, &image, position);
+        }
+    }
+}
+
+pub(crate) struct HeaderMeterRenderer;
+impl HeaderMeterRenderer {
+    /// Draws the meter filled to a level (0 - 100), or the header behind it when there's no level
+    pub(crate) fn draw(level: Option<u8>, colour: Rgba<u8>) -> (RgbaImage, Position) {
+        let (x, y) = HEADER_METER_POSITION;
+        let (width, height) = HEADER_METER_DIMENSIONS;
+        let mut image = crop_imm(&*HEADER_IMAGE, x, y, width, height).to_image();
+        let Some(level) = level else {
+            return (image, (x, y));
+        };
+
+        let filled = width * level.min(100) as u32 / 100;
+        for (px, _, pixel) in image.enumerate_pixels_mut() {
+            *pixel = if px < filled {
+                colour
+            } else {
+                HEADER_METER_TRACK
+            };
+        }
+        (image, (x, y))
+    }
+
+    /// Draws the meter onto a full screen image, if it's shown
+    pub(crate) fn draw_onto(base: &mut RgbaImage, level: Option<u8>, colour: Rgba<u8>) {
+        if level.is_some() {
+            let (image, position) = Self::draw(level, colour);
+            DrawingUtils::composite_from_pos(base, &image, position);
+        }
+    }
+}
+
+pub(crate) struct VolumeOverlayRenderer;
+impl VolumeOverlayRenderer {
+    /// Draws the overlay for a channel, showing where the volume has moved from during this turn
+    pub(crate) fn draw(
+        title: &str,
+        from: u8,
+        to: u8,
+        unit: VolumeUnit,
+        colour: Rgba<u8>,
+    ) -> (RgbaImage, Position) {
+        let change = to as i16 - from as i16;
+        let (width, height) = VOLUME_OVERLAY_DIMENSIONS;
+        let mut image = DrawingUtils::draw_box(
+            width,
+            height,
+            VOLUME_OVERLAY_BORDER,
+            VOLUME_OVERLAY_RADIUS,
+            colour,
+            BG_COLOUR,
+            CHANNEL_INNER_COLOUR,
+        );
+
+        let line = height / 2;
+        let title = DrawingUtils::draw_text(
+            title.to_string(),
+            width,
+            line,
+            TextStyle::Header,
+            TEXT_COLOUR,
+            TextAlign::Center,
+        );
+        DrawingUtils::composite_from(&mut image, &title, 0, 10);
+
+        let value = match (unit, change) {
+            (_, 0) => unit.format(to),
+            (VolumeUnit::Percent, change) => format!("{} ({change:+})", unit.format(to)),
+
+            // A change in decibels from silence doesn't mean much, so just show where it is
+            (VolumeUnit::Decibels, _) if from == 0 || to == 0 => unit.format(to),
+            (VolumeUnit::Decibels, _) => {
+                let change = VolumeUnit::decibels(to) - VolumeUnit::decibels(from);
+                format!("{} ({change:+.1})", unit.format(to))
+            }
+        };
+        let value = DrawingUtils::draw_text(
+            value,
+            width,
+            line,
+            TextStyle::Volume,
+            Rgba([255, 255, 255, 255]),
+            TextAlign::Center,
+        );
+        DrawingUtils::composite_from(&mut image, &value, 0, line - 10);
+        (image, VOLUME_OVERLAY_POSITION)
+    }
+}
+
 pub(crate) struct DrawingUtils;
 impl DrawingUtils {
     // Generates a box with custom borders, and corners
@@ -399,17 +879,19 @@ impl DrawingUtils {
         text: String,
         width: u32,
         height: u32,
-        font: &[u8],
-        font_size: f32,
+        style: TextStyle,
         colour: Rgba<u8>,
         align: TextAlign,
     ) -> RgbaImage {
-        let font = Font::from_bytes(font, fontdue::FontSettings::default()).unwrap();
+        let fonts = DISPLAY_TEXT.fonts(style);
+        let font_size = DISPLAY_TEXT.size(style);
+        let primary = &fonts[0];
+
         let (font_r, font_g, font_b) = (colour[0], colour[1], colour[2]);
         let mut img = RgbaImage::new(width, height);
 
-        // Font-wide vertical metrics
-        let line_metrics = font.horizontal_line_metrics(font_size).unwrap();
+        // Font-wide vertical metrics, these always come from the primary font
+        let line_metrics = primary.horizontal_line_metrics(font_size).unwrap();
         let ascent = line_metrics.ascent;
         let descent = line_metrics.descent;
         let total_font_height = ascent - descent;
@@ -422,6 +904,11 @@ impl DrawingUtils {
         let mut glyphs = Vec::new();
 
         for c in text.chars() {
+            // Use the first font in the chain which actually has this character
+            let font = fonts
+                .iter()
+                .find(|f| f.lookup_glyph_index(c) != 0)
+                .unwrap_or(primary);
             let (metrics, bitmap) = font.rasterize(c, font_size);
             text_width += metrics.advance_width as usize;
             glyphs.push((metrics, bitmap));
@@ -569,7 +1056,7 @@ impl DrawingUtils {
         ])
     }
 
-    pub fn get_volume_image(volume: u8, meter: u8, mix: Mix) -> Result<Vec<u8>> {
+    pub fn get_volume_image(volume: u8, meter: u8, mix: Mix, unit: VolumeUnit) -> Result<Vec<u8>> {
         let mut base = DIAL_BASE_IMAGE.clone();
         let volume_arc = DIAL_MIX_IMAGES[mix]
             .get(&volume)
@@ -577,7 +1064,7 @@ impl DrawingUtils {
         let meter_arc = DIAL_METER_IMAGES[mix]
             .get(&meter)
             .ok_or(anyhow!("Meter Arc Not Found"))?;
-        let text = DIAL_TEXT_IMAGES
+        let text = DIAL_TEXT_IMAGES[unit]
             .get(&volume)
             .ok_or(anyhow!("Text Not Found"))?;
 
@@ -595,80 +1082,143 @@ impl DrawingUtils {
 
 struct DialHandler;
 impl DialHandler {
-    pub fn composite_dials() -> DialMeterData {
+    // Rather than drawing them again, the compact dials are the standard ones (without a meter)
+    // scaled down to fit, and centred in the dial area
+    pub fn composite_compact_dials(unit: VolumeUnit) -> DialCompactData {
         let start = Instant::now();
+        let (width, height) = COMPACT_LAYOUT.volume;
 
-        let file_name = CACHE_PATH.to_string();
-        let xdg_dirs = BaseDirectories::with_prefix(APP_NAME);
-
-        // Attempt to delete old cache files if it exists
-        let old_file_name = "image_cache.bin".to_string(); // whatever the old name was
-        if let Some(old_file) = xdg_dirs.find_cache_file(old_file_name) {
-            if let Err(e) = fs::remove_file(&old_file) {
-                warn!("Failed to remove old cache file: {e}");
-            } else {
-                debug!("Removed old cache file: {old_file:?}");
-            }
+        let mut map: DialCompactData = EnumMap::default();
+        for mix in Mix::iter() {
+            map[mix] = (0..=100u8)
+                .into_par_iter()
+                .filter_map(|volume| {
+                    let jpeg = unit.dial_images()[mix].get(&volume)?.get(&0)?;
+                    let dial = load_from_memory(jpeg).ok()?.into_rgba8();
+
+                    let dial_width = dial.width() * height / dial.height();
+                    let dial = resize(&dial, dial_width, height, FilterType::Triangle);
+                    let mut base = ImageBuffer::from_pixel(width, height, CHANNEL_INNER_COLOUR);
+                    DrawingUtils::composite_from(&mut base, &dial, (width - dial_width) / 2, 0);
+
+                    let jpeg =
+                        DrawingUtils::image_as_jpeg(base, CHANNEL_INNER_COLOUR, JPEG_QUALITY);
+                    jpeg.ok().map(|jpeg| (volume, jpeg))
+                })
+                .collect();
         }
+        debug!("Compact dials ({unit}) built in {:?}", start.elapsed());
+        map
+    }
 
-        // Attempt to Load the Cache file
-        let cache_file = xdg_dirs.find_cache_file(file_name.clone());
-        if let Some(file) = cache_file {
-            debug!("Attempting to load Cache from {file:?}");
+    pub fn composite_dials(unit: VolumeUnit) -> DialMeterData {
+        let start = Instant::now();
+        let cache = AssetCache::open();
 
-            match Self::load_cache(file) {
-                Ok(map) => {
-                    info!("Loaded Cache in {:?}", start.elapsed());
-                    return map;
-                }
-                Err(e) => {
-                    warn!("Cache Load Failed: {e}");
+        // Each dial volume is a separate asset in the cache, pull in whatever's still valid
+        let mut map: DialMeterData = EnumMap::default();
+        let mut keys = vec![];
+        let mut missing = vec![];
+        for mix in Mix::iter() {
+            for volume in 0..=100u8 {
+                let key = Self::asset_key(mix, volume, unit);
+
+                // We need a meter image for every level up to the volume
+                match cache.load(&key) {
+                    Some(images) if images.len() == volume as usize + 1 => {
+                        map[mix].insert(volume, images);
+                    }
+                    _ => missing.push((mix, volume, keys.len())),
                 }
+                keys.push(key);
             }
         }
 
-        debug!("Generating Images (This will take a few seconds..)");
+        // Don't throw away the other unit's assets, they're still valid if it's used later
+        let mut valid = keys.clone();
+        for other in VolumeUnit::iter().filter(|u| *u != unit) {
+            for mix in Mix::iter() {
+                valid.extend((0..=100u8).map(|volume| Self::asset_key(mix, volume, other)));
+            }
+        }
+        cache.retain(&valid);
 
-        // Create a workload to generate the images
-        let work: Vec<(Mix, u8, u8)> = Mix::iter()
-            .flat_map(|mix| {
-                (0..=100u8)
-                    .flat_map(move |volume| (0..=volume).map(move |meter| (mix, volume, meter)))
-            })
-            .collect();
+        if missing.is_empty() {
+            info!("Loaded Cache in {:?}", start.elapsed());
+            return map;
+        }
+
+        debug!(
+            "Generating {} Assets (This may take a few seconds..)",
+            missing.len()
+        );
 
         // Get rayon to handle the work in a threaded way
-        let results: Vec<(Mix, u8, u8, Vec<u8>)> = work
-            .par_iter()
-            .filter_map(|&(mix, volume, meter)| {
-                DrawingUtils::get_volume_image(volume, meter, mix)
-                    .ok()
-                    .map(|img| (mix, volume, meter, img))
+        let results: Vec<(Mix, u8, usize, AssetImages)> = missing
+            .into_par_iter()
+            .map(|(mix, volume, key)| {
+                let images = (0..=volume)
+                    .filter_map(|meter| {
+                        DrawingUtils::get_volume_image(volume, meter, mix, unit)
+                            .ok()
+                            .map(|img| (meter, img))
+                    })
+                    .collect();
+                (mix, volume, key, images)
             })
             .collect();
 
-        // Pull and map the results when done
-        let mut map: DialMeterData = EnumMap::default();
-        let count = results.len();
-        for (mix, volume, meter, img) in results {
-            map[mix].entry(volume).or_default().insert(meter, img);
-        }
-
-        debug!("Generated {} images in {:?}", count, start.elapsed());
+        debug!(
+            "Generated {} assets in {:?}",
+            results.len(),
+            start.elapsed()
+        );
 
-        debug!("Attempting to Save to Cache");
         let time = Instant::now();
-        let cache_file = xdg_dirs.place_cache_file(file_name);
-        if let Ok(file) = cache_file {
-            if let Err(e) = Self::save_cache(file, &map) {
-                warn!("Cache Saving Failed: {e}");
-            } else {
-                info!("Cache Saved in {:?}", time.elapsed());
+        let mut failed = 0;
+        for (mix, volume, key, images) in results {
+            if let Err(e) = cache.save(&keys[key], &images) {
+                debug!("Failed to cache {mix:?}-{volume}: {e}");
+                failed += 1;
             }
+            map[mix].insert(volume, images);
+        }
+
+        if failed > 0 {
+            warn!("Failed to save {failed} assets to the cache");
+        } else {
+            info!("Cache Saved in {:?}", time.elapsed());
         }
         map
     }
 
+    // Percentages keep the original asset names, so existing caches stay valid
+    fn asset_key(mix: Mix, volume: u8, unit: VolumeUnit) -> AssetKey {
+        let name = match unit {
+            VolumeUnit::Percent => format!("{mix:?}-{volume}"),
+            VolumeUnit::Decibels => format!("{mix:?}-{volume}-dB"),
+        };
+        AssetKey::new(&name, Self::render_parameters(mix))
+    }
+
+    // Everything which affects how a dial for this mix looks, if any of these change the cached
+    // images for this mix are no longer valid.
+    fn render_parameters(mix: Mix) -> impl Hash {
+        let colours = match mix {
+            Mix::A => (MIX_A_DIAL, METER_A_DIAL),
+            Mix::B => (MIX_B_DIAL, METER_B_DIAL),
+        };
+
+        (
+            JPEG_QUALITY,
+            VOLUME_DIMENSIONS,
+            VOLUME_CROP,
+            [colours.0.0, colours.1.0],
+            [DIAL_INACTIVE.0, TEXT_COLOUR.0, CHANNEL_INNER_COLOUR.0],
+            DISPLAY_TEXT.fingerprint(),
+        )
+    }
+
     fn precompute_dial_bg() -> RgbaImage {
         let (width, height) = VOLUME_DIMENSIONS;
         Self::generate_dial(width, height, 100, DIAL_INACTIVE)
@@ -703,23 +1253,25 @@ impl DialHandler {
         enum_map
     }
 
-    fn precompute_dial_text() -> HashMap<u8, RgbaImage> {
+    fn precompute_dial_text() -> EnumMap<VolumeUnit, HashMap<u8, RgbaImage>> {
         let (width, height) = VOLUME_DIMENSIONS;
-        let mut map = HashMap::new();
-        for i in 0..=100 {
-            let text = format!("{i:.0}%");
-            let img = DrawingUtils::draw_text(
-                text,
-                width,
-                height,
-                VOLUME_FONT,
-                VOLUME_FONT_SIZE,
-                TEXT_COLOUR,
-                TextAlign::Center,
-            );
-            map.insert(i, img);
+        let mut enum_map = EnumMap::default();
+        for unit in VolumeUnit::iter() {
+            let mut map = HashMap::new();
+            for i in 0..=100 {
+                let img = DrawingUtils::draw_text(
+                    unit.format(i),
+                    width,
+                    height,
+                    TextStyle::Volume,
+                    TEXT_COLOUR,
+                    TextAlign::Center,
+                );
+                map.insert(i, img);
+            }
+            enum_map[unit] = map;
         }
-        map
+        enum_map
     }
 
     fn precompute_maps() -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
@@ -805,66 +1357,4 @@ impl DialHandler {
         }
         img
     }
-
-    fn save_cache(path: PathBuf, map: &DialMeterData) -> Result<()> {
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
-
-        writer.write_all(&CACHE_VERSION.to_le_bytes())?;
-
-        for (mix, volume_map) in map.iter() {
-            let mix_id = mix as u8;
-            for (&volume, meter_map) in volume_map {
-                for (&meter, data) in meter_map {
-                    writer.write_all(&[mix_id, volume, meter])?;
-                    let len = data.len() as u32;
-                    writer.write_all(&len.to_le_bytes())?;
-                    writer.write_all(data)?;
-                }
-            }
-        }
-        Ok(())
-    }
-
-    fn load_cache(path: PathBuf) -> Result<DialMeterData> {
-        let file = File::open(&path)?;
-        let mut reader = BufReader::new(file);
-        let mut map: DialMeterData = EnumMap::default();
-
-        let mut version_bytes = [0u8; 2];
-        reader.read_exact(&mut version_bytes)?;
-        let version = u16::from_le_bytes(version_bytes);
-        if version != CACHE_VERSION {
-            bail!("Cache version mismatch: expected {CACHE_VERSION}, got {version}");
-        }
-
-        loop {
-            // mix + volume + meter + 4 len bytes
-            let mut header = [0u8; 7];
-            if let Err(e) = reader.read_exact(&mut header) {
-                if e.kind() == UnexpectedEof {
-                    break;
-                }
-                bail!("Failed to read header from cache file");
-            }
-
-            let mix = match header[0] {
-                0 => Mix::A,
-                1 => Mix::B,
-                _ => bail!("Invalid mix identifier: {}", header[0]),
-            };
-            let volume = header[1];
-            let meter = header[2];
-            let len = u32::from_le_bytes([header[3], header[4], header[5], header[6]]) as usize;
-
-            let mut data = vec![0u8; len];
-            reader.read_exact(&mut data).with_context(|| {
-                format!("Failed to read image data for mix {mix:?}, volume {volume}, meter {meter}")
-            })?;
-
-            map[mix].entry(volume).or_default().insert(meter, data);
-        }
-
-        Ok(map)
-    }
 }
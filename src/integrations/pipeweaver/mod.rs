@@ -1,13 +1,33 @@
 use crate::device_manager::ControlMessage;
-use crate::device_manager::ControlMessage::{ButtonColour, SendImage};
+use crate::device_manager::ControlMessage::ButtonColour;
+use crate::integrations::acceleration::DialAccelerator;
+use crate::integrations::burn_in;
+use crate::integrations::gestures::{
+    DialAction, DialGesture, DialGestures, GestureDetector, LONG_PRESS_TIME,
+};
+use crate::integrations::header_widgets::{self, HeaderWidgetState};
+use crate::integrations::keep_alive::KeepAlive;
+use crate::integrations::orientation::Orientation;
 use crate::integrations::pipeweaver::channel::{
-    ChannelChangedProperty, ChannelRenderer, UpdateFrom,
+    ChannelChangedProperty, ChannelRenderer, SourceDevice, UpdateFrom,
+};
+use crate::integrations::pipeweaver::crossfade::Crossfade;
+use crate::integrations::pipeweaver::discovery::DEFAULT_ADDRESS;
+use crate::integrations::pipeweaver::encoder::Tile;
+use crate::integrations::pipeweaver::frame::{
+    FRAME_INTERVAL, FrameBuffer, PERFORMANCE_FRAME_INTERVAL, POWER_SAVE_FRAME_INTERVAL,
 };
+use crate::integrations::pipeweaver::header_meter::{self, HeaderMeter};
 use crate::integrations::pipeweaver::layout::{
-    BG_COLOUR, CHANNEL_DIMENSIONS, DISPLAY_DIMENSIONS, DrawingUtils, FONT_BOLD, HEADER,
-    JPEG_QUALITY, POSITION_ROOT, TEXT_COLOUR, TextAlign,
+    BG_COLOUR, CHANNEL_COUNT, DISPLAY_DIMENSIONS, DrawingUtils, HEADER, HeaderMeterRenderer,
+    HeaderWidgetRenderer, JPEG_QUALITY, LayoutTheme, METER_A_DIAL, METER_B_DIAL, PanicMuteRenderer,
+    Position, ScreenLayout, TEXT_COLOUR, TextAlign, VolumeOverlayRenderer, VolumeUnit,
+};
+use crate::integrations::pipeweaver::text::TextStyle;
+use crate::integrations::{
+    AssignableChannel, ColourOverrides, DialAssignment, DisplayHandles, VolumeUnits,
 };
-use crate::runtime;
+use crate::{integrations, panic_mute, runtime, session};
 use anyhow::{Context, Error, Result, anyhow, bail};
 use beacn_lib::controller::{ButtonLighting, ButtonState, Buttons, Dials, Interactions};
 use beacn_lib::crossbeam;
@@ -32,31 +52,29 @@ use pipeweaver_ipc::commands::{
     WebsocketResponse,
 };
 use pipeweaver_profile::{
-    PhysicalSourceDevice, PhysicalTargetDevice, SourceDevices, TargetDevices, VirtualSourceDevice,
-    VirtualTargetDevice,
+    DeviceDescription, PhysicalSourceDevice, PhysicalTargetDevice, SourceDevices, TargetDevices,
+    VirtualSourceDevice, VirtualTargetDevice,
 };
 use pipeweaver_shared::{Mix, MuteState, MuteTarget, OrderGroup};
 use serde::Deserialize;
 use serde_json::Value;
 use std::cmp::PartialEq;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::ErrorKind;
 use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use std::{env, fs};
 use strum::IntoEnumIterator;
 use tokio::net::TcpStream;
 use tokio::sync::mpsc::channel;
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
-use tokio::time::sleep;
+use tokio::time::{MissedTickBehavior, sleep};
 use tokio::{select, time};
 use tokio_tungstenite::tungstenite::{Message, Utf8Bytes};
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite};
 use ulid::Ulid;
 
-const HELD_TIME: Duration = Duration::from_millis(500);
-
 const PW_SPLASH: &[u8] = include_bytes!("../../../resources/screens/beacn-pipeweaver.jpg");
 const PIPEWEAVER_APP_NAME: &str = "PipeWeaver";
 const PIPEWEAVER_APP_NAME_ID: &str = "pipeweaver";
@@ -91,6 +109,24 @@ pub fn launch_pipeweaver_ui() -> bool {
     false
 }
 
+/// Sends a single request to the local Pipeweaver daemon, returning its response
+pub async fn send_daemon_request(request: DaemonRequest) -> Result<DaemonResponse> {
+    let file_name = get_pipeweaver_socket_path()?.to_fs_name::<GenericFilePath>()?;
+    let stream = LocalSocketStream::connect(file_name).await?;
+    let socket: Socket<DaemonResponse, DaemonRequest> = Socket::new(stream);
+
+    let mut client = IPCClient::new(socket);
+    client.send(&request).await
+}
+
+/// Sends a request to the local Pipeweaver daemon, failing if the daemon rejects it
+pub async fn send_daemon_command(request: DaemonRequest) -> Result<()> {
+    if let DaemonResponse::Err(e) = send_daemon_request(request).await? {
+        bail!("{e}");
+    }
+    Ok(())
+}
+
 pub fn get_pipeweaver_socket_path() -> Result<PathBuf> {
     let path = BaseDirs::new()
         .and_then(|base| base.runtime_dir().map(|p| p.to_path_buf()))
@@ -107,8 +143,15 @@ pub fn get_pipeweaver_socket_path() -> Result<PathBuf> {
     Ok(socket_path)
 }
 
-mod channel;
-mod layout;
+pub(crate) mod cache;
+pub(crate) mod channel;
+pub(crate) mod crossfade;
+pub(crate) mod discovery;
+pub(crate) mod encoder;
+pub(crate) mod frame;
+pub(crate) mod header_meter;
+pub(crate) mod layout;
+pub(crate) mod text;
 
 const COLOUR_MIX_A: RGBA = RGBA {
     red: 89,
@@ -123,6 +166,14 @@ const COLOUR_MIX_B: RGBA = RGBA {
     alpha: 255,
 };
 
+// The Audience buttons on the Mix Create light up while the channel is muted to the audience
+const COLOUR_AUDIENCE_MUTED: RGBA = RGBA {
+    red: 255,
+    green: 0,
+    blue: 0,
+    alpha: 255,
+};
+
 const COLOUR_WHITE: RGBA = RGBA {
     red: 255,
     green: 255,
@@ -137,6 +188,29 @@ const COLOUR_BLACK: RGBA = RGBA {
     alpha: 0,
 };
 
+// The number of physical dials, which control the first channels on the page
+const DIAL_COUNT: usize = 4;
+
+// How long the volume overlay stays up after the last turn of a dial
+const VOLUME_OVERLAY_DURATION: Duration = Duration::from_millis(1500);
+
+// Only enough history to cover a single turn of a dial is needed
+const DIAL_HISTORY_LENGTH: usize = 32;
+
+// Changes arriving this soon after a channel was changed from the device are assumed to be ours
+const LOCAL_CHANGE_WINDOW: Duration = Duration::from_secs(1);
+
+// How often changes made by other Pipeweaver clients are animated
+const ANIMATION_FRAME: Duration = Duration::from_millis(40);
+
+// How long to wait between connection attempts, a restarting daemon is usually back quickly so
+// the first few retries come sooner
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+];
+
 // This is a mapping for the meter messages
 #[derive(Debug, Deserialize)]
 struct MeterMessage {
@@ -144,15 +218,6 @@ struct MeterMessage {
     percent: u8,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct ButtonHoldState {
-    pub(crate) press_time: Option<Instant>,
-
-    pub(crate) skip_hold: bool,
-    pub(crate) skip_release: bool,
-    pub(crate) hold_handled: bool,
-}
-
 // This is so we can more cleanly Map Physical / Virtual devices, because the data we need from
 // them is the same regardless, and ChannelRenderer has From<> for Both
 #[derive(Debug)]
@@ -169,6 +234,15 @@ pub enum ChannelType {
     Target,
 }
 
+// A single volume change made from a dial
+#[derive(Debug, Copy, Clone)]
+struct DialChange {
+    device: Ulid,
+    from: u8,
+    to: u8,
+    at: time::Instant,
+}
+
 type WebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
 type Renderers = HashMap<Ulid, ChannelRenderer>;
 
@@ -178,10 +252,25 @@ struct PipeweaverHandler {
     input_rx: Receiver<Interactions>,
     stop_rx: watch::Receiver<()>,
     suspended_rx: watch::Receiver<bool>,
+    dial_assignment: watch::Receiver<DialAssignment>,
+    coalesce_rx: watch::Receiver<bool>,
+    performance_rx: watch::Receiver<bool>,
+    address_rx: watch::Receiver<Option<String>>,
+    orientation_rx: watch::Receiver<Orientation>,
+    power_saving_rx: watch::Receiver<bool>,
+    colours_rx: watch::Receiver<ColourOverrides>,
+    volume_units_rx: watch::Receiver<VolumeUnits>,
+    screen_layout_rx: watch::Receiver<ScreenLayout>,
+    redraw_rx: watch::Receiver<()>,
+    channels_tx: watch::Sender<Vec<AssignableChannel>>,
     temporary_active: bool,
 
     has_connected: bool,
     displaying_error: bool,
+    failed_attempts: usize,
+
+    // Bumped every time the status is fetched from scratch, to tell apart logs from each sync
+    status_generation: u64,
 
     command_index: u64,
     raw_status: Value,
@@ -190,9 +279,33 @@ struct PipeweaverHandler {
     active_page: u8,
     channel_type: ChannelType,
     active_mix: Mix,
-    devices_shown: Vec<Ulid>,
+    devices_shown: Vec<Option<Ulid>>,
     renderers: Renderers,
-    button_down_states: EnumMap<Buttons, Option<ButtonHoldState>>,
+    frame: FrameBuffer,
+    header_widgets: HeaderWidgetState,
+    header_meter: HeaderMeter,
+    keep_alive: KeepAlive,
+    panic_rx: watch::Receiver<bool>,
+
+    // Sources muted by the panic mute, which get unmuted when it's turned off
+    panic_muted: Vec<Ulid>,
+
+    // Recent dial changes, and the channel shown in the volume overlay with when it clears
+    dial_history: VecDeque<DialChange>,
+    volume_overlay: Option<(Ulid, time::Instant)>,
+
+    // When each channel was last changed from the device, so other clients' changes stand out
+    local_changes: HashMap<Ulid, time::Instant>,
+
+    // In performance mode, dial changes are held here and sent once per frame
+    pending_volumes: HashMap<Ulid, (ChannelType, Mix, u8)>,
+    gestures_rx: watch::Receiver<DialGestures>,
+    gestures: GestureDetector,
+    accelerator: DialAccelerator,
+
+    // When the Mix button was pressed, and the fade between mixes while it's being held
+    mix_pressed_at: Option<time::Instant>,
+    crossfade: Option<Crossfade>,
 }
 
 impl PipeweaverHandler {
@@ -202,17 +315,35 @@ impl PipeweaverHandler {
         input_rx: Receiver<Interactions>,
         stop_rx: watch::Receiver<()>,
         suspended_rx: watch::Receiver<bool>,
+        handles: DisplayHandles,
     ) -> Self {
+        let performance = *handles.performance_rx.borrow();
+        let coalesce =
+            *handles.coalesce_rx.borrow() || *handles.power_saving_rx.borrow() || performance;
+        let orientation = *handles.orientation_rx.borrow();
         Self {
             device_type,
             sender,
             input_rx,
             stop_rx,
             suspended_rx,
+            dial_assignment: handles.assignment_rx,
+            coalesce_rx: handles.coalesce_rx,
+            performance_rx: handles.performance_rx,
+            address_rx: handles.address_rx,
+            orientation_rx: handles.orientation_rx,
+            power_saving_rx: handles.power_saving_rx,
+            colours_rx: handles.colours_rx,
+            volume_units_rx: handles.volume_units_rx,
+            screen_layout_rx: handles.screen_layout_rx,
+            redraw_rx: handles.redraw_rx,
+            channels_tx: handles.channels_tx,
             temporary_active: false,
 
             has_connected: false,
             displaying_error: false,
+            failed_attempts: 0,
+            status_generation: 0,
 
             command_index: 0,
             raw_status: Value::Null,
@@ -221,17 +352,28 @@ impl PipeweaverHandler {
             active_page: 0,
             channel_type: ChannelType::Source,
             active_mix: Mix::A,
-            devices_shown: Vec::with_capacity(4),
+            devices_shown: Vec::with_capacity(CHANNEL_COUNT as usize),
             renderers: HashMap::new(),
-            button_down_states: EnumMap::default(),
+            frame: FrameBuffer::new(coalesce, performance, orientation, handles.burn_in_rx),
+            header_widgets: HeaderWidgetState::new(handles.header_widgets_rx),
+            header_meter: HeaderMeter::new(),
+            keep_alive: KeepAlive::new(handles.keep_alive_rx),
+            panic_rx: panic_mute::subscribe(),
+            panic_muted: vec![],
+            dial_history: VecDeque::with_capacity(DIAL_HISTORY_LENGTH),
+            volume_overlay: None,
+            local_changes: HashMap::new(),
+            pending_volumes: HashMap::new(),
+            gestures_rx: handles.gestures_rx,
+            gestures: GestureDetector::default(),
+            accelerator: DialAccelerator::default(),
+            mix_pressed_at: None,
+            crossfade: None,
         }
     }
 
     pub async fn run_handler(&mut self) {
         info!("Starting Pipeweaver Manager");
-        let url = "ws://localhost:14565/api/websocket";
-        let meter = "ws://localhost:14565/api/websocket/meter";
-
         let mut clean_stop = true;
 
         // Send the Pipeweaver Splash
@@ -244,7 +386,7 @@ impl PipeweaverHandler {
 
         // We need to handle this in a loop, if something goes bad just make sure we're disconnencted
         // and try again after 5 seconds,
-        'connect: while let Err(e) = self.handle_connection(url, meter).await {
+        'connect: while let Err(e) = self.handle_connection().await {
             // It doesn't matter if we lose an input here, we're not handling them anyway.
             if matches!(self.input_rx.try_recv(), Err(TryRecvError::Disconnected)) {
                 warn!("Interaction Handler Terminated, Bailing!");
@@ -258,12 +400,15 @@ impl PipeweaverHandler {
                     self.disable_buttons();
                 } else {
                     self.draw_splash();
-                    self.draw_status("Connection to Pipeweaver lost");
+                    self.draw_status("Connection to Pipeweaver lost, reconnecting...");
                     self.disable_buttons();
                 }
             }
             self.displaying_error = true;
 
+            let retry_delay = RETRY_DELAYS[self.failed_attempts.min(RETRY_DELAYS.len() - 1)];
+            self.failed_attempts += 1;
+
             // We only suppress 'Connection Refused' errors, as they're expected to happen
             let is_connection_refused = e
                 .downcast_ref::<tungstenite::Error>()
@@ -297,8 +442,27 @@ impl PipeweaverHandler {
                     Ok(_) = self.stop_rx.changed() => {
                         break 'connect;
                     }
-                    _ = sleep(Duration::from_secs(5)) => {
-                        // 5 Seconds have elapsed, break this loop to reconnect
+                    Ok(_) = self.address_rx.changed() => {
+                        // Don't wait around, try the new address straight away
+                        drop(stop_tx);
+                        continue 'connect;
+                    }
+                    Ok(_) = self.orientation_rx.changed() => {
+                        // Redraw the error in the new orientation if we still can't connect
+                        self.frame.set_orientation(*self.orientation_rx.borrow());
+                        self.draw_splash();
+                        self.displaying_error = false;
+                        drop(stop_tx);
+                        continue 'connect;
+                    }
+                    Ok(_) = self.redraw_rx.changed() => {
+                        self.draw_splash();
+                        self.displaying_error = false;
+                        drop(stop_tx);
+                        continue 'connect;
+                    }
+                    _ = sleep(retry_delay) => {
+                        // Time's up, break this loop to reconnect
                         drop(stop_tx);
                         continue 'connect;
                     }
@@ -315,9 +479,9 @@ impl PipeweaverHandler {
     }
 
     fn draw_splash(&self) {
-        let (tx, rx) = oneshot::channel();
-        let _ = self.sender.send(SendImage(Vec::from(PW_SPLASH), 0, 0, tx));
-        let _ = rx.recv();
+        let _ = self
+            .frame
+            .send_now(&self.sender, Vec::from(PW_SPLASH), 0, 0);
     }
 
     fn draw_status(&self, text: &str) {
@@ -325,16 +489,13 @@ impl PipeweaverHandler {
             text.into(),
             800,
             30,
-            FONT_BOLD,
-            28.,
+            TextStyle::Status,
             TEXT_COLOUR,
             TextAlign::Center,
         );
 
         if let Ok(img) = img_as_jpeg(text, Rgba([0, 0, 0, 255])) {
-            let (tx, rx) = oneshot::channel();
-            let _ = self.sender.send(SendImage(img, 0, 330, tx));
-            let _ = rx.recv();
+            let _ = self.frame.send_now(&self.sender, img, 0, 330);
         }
     }
 
@@ -346,14 +507,23 @@ impl PipeweaverHandler {
         }
     }
 
-    async fn handle_connection(&mut self, url: &str, meter: &str) -> Result<()> {
+    async fn handle_connection(&mut self) -> Result<()> {
+        let address = self.address_rx.borrow_and_update().clone();
+        let address = address.unwrap_or_else(|| String::from(DEFAULT_ADDRESS));
+
+        let url = format!("ws://{address}/api/websocket");
+        let meter = format!("ws://{address}/api/websocket/meter");
+
         let (mut stream, _) = connect_async(url).await?;
         let (mut meter, _) = connect_async(meter).await?;
-        info!("Successfully connected to Pipeweaver");
+        info!("Successfully connected to Pipeweaver at {address}");
 
         self.has_connected = true;
         self.displaying_error = false;
+        self.failed_attempts = 0;
 
+        // If Pipeweaver restarted, nothing from the old connection can be trusted
+        self.reset_channel_state();
         self.load_status(&mut stream).await?;
         self.load_initial_state().await?;
         self.run_message_loop(&mut stream, &mut meter).await?;
@@ -399,6 +569,13 @@ impl PipeweaverHandler {
 
                         let raw = self.raw_status.clone();
                         self.status = serde_json::from_value::<DaemonStatus>(raw)?;
+                        self.publish_channels();
+
+                        self.status_generation += 1;
+                        debug!(
+                            "Loaded Pipeweaver Status (generation {})",
+                            self.status_generation
+                        );
                         break;
                     }
                 }
@@ -423,6 +600,30 @@ impl PipeweaverHandler {
         Ok(())
     }
 
+    // Fetches the status from scratch after our copy has drifted from Pipeweaver's, and rebuilds
+    // everything drawn from it
+    async fn resync(&mut self, stream: &mut WebSocket) -> Result<()> {
+        self.reset_channel_state();
+        self.load_status(stream).await?;
+
+        self.devices_shown = self.get_channels_on_page();
+        self.update_renderers()?;
+        if !self.is_suspended() || self.temporary_active {
+            self.perform_full_refresh()?;
+        }
+        Ok(())
+    }
+
+    // Drops everything built from the previous status, so it gets rebuilt from the next one
+    fn reset_channel_state(&mut self) {
+        self.renderers.clear();
+        self.pending_volumes.clear();
+        self.dial_history.clear();
+        self.local_changes.clear();
+        self.volume_overlay = None;
+        self.crossfade = None;
+    }
+
     async fn load_initial_state(&mut self) -> Result<()> {
         let devices_shown = self.get_channels_on_page();
         self.devices_shown = devices_shown;
@@ -452,10 +653,10 @@ impl PipeweaverHandler {
         let (_stop_tx, stop_rx) = crossbeam::channel::bounded::<()>(0);
         runtime().spawn_blocking(move || sync_to_async(sync_receiver, interaction_tx, stop_rx));
 
-        let mut keep_alive = time::interval(Duration::from_secs(10));
-
+        // Starting up (or reconnecting) shouldn't wake a screen that's been put to sleep
         let (tx, rx) = oneshot::channel();
-        self.sender.send(ControlMessage::Enabled(true, tx))?;
+        self.sender
+            .send(ControlMessage::Enabled(!self.is_suspended(), tx))?;
         rx.recv()??;
 
         let mut last_channel_count = 0;
@@ -468,8 +669,17 @@ impl PipeweaverHandler {
         let suspend_sleep = tokio::time::sleep(Duration::MAX);
         tokio::pin!(suspend_sleep);
 
+        let animation_sleep = tokio::time::sleep(Duration::MAX);
+        tokio::pin!(animation_sleep);
+
         let mut ticker = time::interval(Duration::from_millis(20));
 
+        let mut frame_ticker = self.frame_ticker();
+        let mut volume_ticker = time::interval(PERFORMANCE_FRAME_INTERVAL);
+        let mut burn_in = time::interval(burn_in::CHECK_INTERVAL);
+        let mut header = time::interval(header_widgets::CHECK_INTERVAL);
+        let mut meter_ticker = time::interval(header_meter::UPDATE_INTERVAL);
+
         debug!("Starting Pipeweaver Message Loop");
         loop {
             let is_suspended = self.is_suspended();
@@ -482,6 +692,7 @@ impl PipeweaverHandler {
                 Ok(_) = self.suspended_rx.changed() => {
                     // We've woken up from a suspension, so redraw everything
                     if !self.is_suspended() {
+                        self.temporary_active = false;
                         self.refresh_page()?;
                     }
 
@@ -489,14 +700,162 @@ impl PipeweaverHandler {
                     continue;
                }
 
+                Ok(_) = self.coalesce_rx.changed() => {
+                    let enabled = self.should_coalesce();
+                    if enabled != self.frame.is_enabled() {
+                        self.frame.set_enabled(&self.sender, enabled)?;
+
+                        // Make sure the canvas matches the screen before drawing onto it
+                        if enabled && (!self.is_suspended() || self.temporary_active) {
+                            self.perform_full_redraw()?;
+                        }
+                    }
+                }
+
+                Ok(_) = self.power_saving_rx.changed() => {
+                    // Updates are always coalesced while saving power, just less often
+                    frame_ticker = self.frame_ticker();
+                    let enabled = self.should_coalesce();
+                    if enabled != self.frame.is_enabled() {
+                        self.frame.set_enabled(&self.sender, enabled)?;
+                    }
+
+                    // Meters are paused while saving power, so don't leave them frozen mid-level
+                    sub_tick = None;
+                    for renderer in self.renderers.values_mut() {
+                        renderer.meter = 0;
+                        renderer.meter_target = 0.0;
+                    }
+                    self.header_meter.set_level(0);
+
+                    if !self.is_suspended() || self.temporary_active {
+                        self.perform_full_redraw()?;
+                    }
+                }
+
+                Ok(_) = self.performance_rx.changed() => {
+                    let performance = self.is_performance_mode();
+                    frame_ticker = self.frame_ticker();
+                    self.frame.set_performance(&self.sender, performance)?;
+                    self.frame.set_enabled(&self.sender, self.should_coalesce())?;
+                    if !performance {
+                        self.send_pending_volumes(stream).await?;
+                    }
+
+                    if !self.is_suspended() || self.temporary_active {
+                        self.perform_full_redraw()?;
+                    }
+                }
+
+                _ = volume_ticker.tick(), if !self.pending_volumes.is_empty() => {
+                    self.send_pending_volumes(stream).await?;
+                }
+
+                Ok(_) = self.orientation_rx.changed() => {
+                    self.frame.set_orientation(*self.orientation_rx.borrow());
+                    if !self.is_suspended() || self.temporary_active {
+                        self.perform_full_redraw()?;
+                    }
+                }
+
+                Ok(_) = self.redraw_rx.changed() => {
+                    if !self.is_suspended() || self.temporary_active {
+                        self.perform_full_redraw()?;
+                    }
+                }
+
+                Ok(_) = self.colours_rx.changed() => {
+                    let mut changed = false;
+                    for (id, renderer) in self.renderers.iter_mut() {
+                        changed |= renderer.set_colour_override(Self::colour_override(&self.colours_rx, id));
+                    }
+                    if changed && (!self.is_suspended() || self.temporary_active) {
+                        self.perform_full_refresh()?;
+                    }
+                }
+
+                Ok(_) = self.volume_units_rx.changed() => {
+                    let mut changed = false;
+                    for (id, renderer) in self.renderers.iter_mut() {
+                        changed |= renderer.set_volume_unit(Self::volume_unit(&self.volume_units_rx, id));
+                    }
+                    if changed && (!self.is_suspended() || self.temporary_active) {
+                        self.perform_full_refresh()?;
+                    }
+                }
+
+                Ok(_) = self.screen_layout_rx.changed() => {
+                    let layout = self.layout();
+                    let mut changed = false;
+                    for renderer in self.renderers.values_mut() {
+                        changed |= renderer.set_layout(layout);
+                    }
+
+                    // The number of channels on a page has changed, so they need working out again
+                    if changed {
+                        self.active_page = 0;
+                        self.devices_shown = self.get_channels_on_page();
+                        self.update_renderers()?;
+                        self.load_all_dial_button_colours()?;
+                        self.load_page_button()?;
+                        if !self.is_suspended() || self.temporary_active {
+                            self.perform_full_refresh()?;
+                        }
+                    }
+                }
+
+                Ok(_) = self.panic_rx.changed() => {
+                    self.apply_panic_mute(stream).await?;
+                    if !self.is_suspended() || self.temporary_active {
+                        self.redraw_panic_mute()?;
+                    }
+                }
+
+                Ok(_) = self.address_rx.changed() => {
+                    // Drop this connection, the connect loop will pick up the new address
+                    bail!("Pipeweaver address changed");
+                }
+
+                _ = frame_ticker.tick(), if self.frame.is_dirty() => {
+                    self.frame.flush(&self.sender)?;
+                }
+
+                _ = burn_in.tick() => {
+                    if self.frame.check_burn_in() && (!self.is_suspended() || self.temporary_active) {
+                        self.perform_full_redraw()?;
+                    }
+                }
+                _ = header.tick(), if !self.is_suspended() || self.temporary_active => {
+                    self.header_meter.check_enabled();
+                    self.redraw_header_widgets()?;
+                }
+                _ = meter_ticker.tick(), if !self.is_suspended() || self.temporary_active => {
+                    self.redraw_header_meter()?;
+                }
+
+                Ok(_) = self.dial_assignment.changed() => {
+                    // Assignments only affect the sources view
+                    if self.channel_type == ChannelType::Source {
+                        self.active_page = 0;
+                        if !self.is_suspended() || self.temporary_active {
+                            self.refresh_page()?;
+                        }
+                    }
+                }
+
                 message = stream.next() => {
                     match message {
                         Some(Ok(Message::Text(text))) => {
                             let result = serde_json::from_str::<WebsocketResponse>(&text)?;
                             if let DaemonResponse::Patch(patch) = result.data {
-                                // Update the raw status for the change
-                                json_patch::patch(&mut self.raw_status, &patch)?;
-                                self.status = serde_json::from_value::<DaemonStatus>(self.raw_status.clone())?;
+                                // Update the raw status for the change, if it doesn't fit then we've
+                                // missed something, so start again from a fresh status
+                                if let Err(e) = self.apply_patch(&patch) {
+                                    warn!("Pipeweaver status out of sync ({e}), resyncing");
+                                    self.resync(stream).await?;
+                                    last_channel_count = 0;
+                                    continue;
+                                }
 
                                 // Count all channels that aren't hidden
                                 let count = {
@@ -526,9 +885,18 @@ impl PipeweaverHandler {
                                     self.load_all_dial_button_colours()?;
                                     self.perform_full_redraw()?;
                                 } else {
+                                    let mut drawn = false;
+
                                     // Check whether any existing devices have changed
                                     for (index, device) in self.devices_shown.iter().enumerate() {
+                                        let Some(device) = device else {
+                                            continue;
+                                        };
                                         let mut refresh_button_colour = false;
+                                        let mut refresh_audience_colour = false;
+
+                                        // Animations are skipped when drawing is being kept to a minimum
+                                        let animate = self.is_external_change(*device) && !self.is_power_saving() && !self.is_performance_mode();
 
                                         let dev_ref = match self.channel_type {
                                             ChannelType::Source => self.get_source_device_ref(device, sources)?,
@@ -536,6 +904,7 @@ impl PipeweaverHandler {
                                         };
 
                                         let render = self.renderers.get_mut(device).ok_or_else(|| anyhow!("Failed to get renderer"))?;
+                                        let shown_volume = render.shown_volume(self.active_mix);
 
                                         let update = match dev_ref {
                                             DeviceRef::PhysicalSource(d) => render.update_from(d.clone()),
@@ -544,50 +913,51 @@ impl PipeweaverHandler {
                                             DeviceRef::VirtualTarget(d) => render.update_from(d.clone()),
                                         };
 
-                                        for part in update {
-                                            let (img, x, y) = match part {
-                                                ChannelChangedProperty::Title => {
-                                                    let img = render.draw_header();
-
-                                                    let (x, y) = img.position;
-                                                    let img = img_as_jpeg(img.image, BG_COLOUR)?;
-
-                                                    (img, x, y)
-                                                }
-                                                ChannelChangedProperty::Colour => {
-                                                    // Set the Button Colour to Refresh
-                                                    refresh_button_colour = true;
-
-                                                    // We need to redraw the entire channel
-                                                    let img = render.full_render(self.active_mix);
-
-                                                    let (x, y) = img.position;
-                                                    let img = img_as_jpeg(img.image, BG_COLOUR)?;
+                                        // Another client changed this channel, so ease the dial over and highlight it
+                                        let volume_changed = update.contains(&ChannelChangedProperty::Volumes(self.active_mix));
+                                        if volume_changed {
+                                            integrations::volume_changed(&render.title, render.volumes[self.active_mix]);
+                                        }
+                                        let mute_changed = update.iter().any(|p| matches!(p, ChannelChangedProperty::MuteState(_)));
+                                        if animate && (volume_changed || mute_changed) {
+                                            render.start_external_change(volume_changed.then_some((self.active_mix, shown_volume)));
+
+                                            // Leave the next frame alone if one's already coming up
+                                            let next_frame = time::Instant::now() + ANIMATION_FRAME;
+                                            if animation_sleep.is_elapsed() || animation_sleep.deadline() > next_frame {
+                                                animation_sleep.as_mut().reset(next_frame);
+                                            }
+                                        }
 
-                                                    (img, x, y)
-                                                }
+                                        for part in update {
+                                            let images = match part {
                                                 ChannelChangedProperty::Volumes(mix) => {
-                                                    if mix != self.active_mix {
+                                                    // The animation will get there when the dial is easing
+                                                    if mix != self.active_mix || render.shown_volume(mix) != render.volumes[mix] {
                                                         continue
                                                     }
 
                                                     let img = render.get_volume(self.active_mix)?;
-                                                    let (x, y) = img.position;
-
-                                                    (img.image, x, y)
+                                                    vec![(Tile::from(img.image), img.position)]
                                                 }
-                                                ChannelChangedProperty::MuteState(target) => {
+                                                ChannelChangedProperty::MuteState(MuteTarget::TargetB) if self.device_type == DeviceType::BeacnMix => {
                                                     // Don't draw MixB Mute updates on the Beacn Mix
-                                                    if target == MuteTarget::TargetB && self.device_type == DeviceType::BeacnMix {
-                                                        continue;
+                                                    continue;
+                                                }
+                                                part => {
+                                                    match part {
+                                                        // Set the Button Colour to Refresh
+                                                        ChannelChangedProperty::Colour => refresh_button_colour = true,
+                                                        ChannelChangedProperty::MuteState(_) => refresh_audience_colour = true,
+                                                        _ => {}
                                                     }
 
-                                                    let img = render.draw_mute_box(target);
-
-                                                    let (x, y) = img.position;
-                                                    let img = img_as_jpeg(img.image, BG_COLOUR)?;
-
-                                                    (img, x, y)
+                                                    // Only send the parts of the channel which actually look different
+                                                    let mut images = vec![];
+                                                    for img in render.render_damaged(part, self.active_mix) {
+                                                        images.push((Tile::from(img.image), img.position));
+                                                    }
+                                                    images
                                                 }
                                             };
 
@@ -597,18 +967,15 @@ impl PipeweaverHandler {
                                             }
 
                                             // Determine the 'start' position of this channel
-                                            let (ch_w, _) = CHANNEL_DIMENSIONS;
-                                            let base_x = ch_w * index as u32;
-
-                                            // Get the position relative to the main image root
-                                            let (root_x, root_y) = POSITION_ROOT;
-                                            let x = base_x + x + root_x;
-                                            let y = y + root_y;
-
-                                            // Send it
-                                            let (tx,rx) = oneshot::channel();
-                                            self.sender.send(SendImage(img, x, y, tx))?;
-                                            rx.recv()??;
+                                            let (base_x, base_y) = self.layout().channel_position(index as u32);
+                                            for (img, (x, y)) in images {
+                                                let x = base_x + x;
+                                                let y = base_y + y;
+
+                                                // Send it
+                                                self.frame.send(&self.sender, img, x, y)?;
+                                                drawn = true;
+                                            }
                                         };
 
                                         // We split this out because there's a lot of borrowing going on
@@ -617,6 +984,14 @@ impl PipeweaverHandler {
                                         if refresh_button_colour {
                                             self.load_dial_button_colour(index)?;
                                         }
+                                        if refresh_audience_colour {
+                                            self.load_audience_button_colour(index)?;
+                                        }
+                                    }
+
+                                    // Keep the volume overlay above anything drawn underneath it
+                                    if drawn && self.volume_overlay.is_some() {
+                                        self.draw_volume_overlay()?;
                                     }
                                 }
                             }
@@ -633,10 +1008,15 @@ impl PipeweaverHandler {
                 }
                 message = meter.next() => {
                     match message {
+                        // Meters aren't essential, so they're dropped while saving power
+                        Some(Ok(Message::Text(_))) if self.is_power_saving() => {}
                         Some(Ok(Message::Text(text))) => {
                         let result = serde_json::from_str::<MeterMessage>(&text)?;
+                        if self.header_meter.is_enabled() && self.main_target() == Some(result.id) {
+                            self.header_meter.set_level(result.percent);
+                        }
 
-                        if let Some(index) = self.devices_shown.iter().position(|id| *id == result.id) &&
+                        if let Some(index) = self.devices_shown.iter().position(|id| *id == Some(result.id)) &&
                             let Some(renderer) = self.renderers.get_mut(&result.id) {
                                 renderer.meter_target = result.percent.into();
 
@@ -649,7 +1029,7 @@ impl PipeweaverHandler {
                                     continue;
                                 }
 
-                                if is_suspended && !self.temporary_active {
+                                if (is_suspended && !self.temporary_active) || self.volume_overlay.is_some() {
                                     // We'll tick the subtick, but wont draw this time
                                     sub_tick = Some((result.id, index));
                                     sub_sleep.as_mut().reset(time::Instant::now() + Duration::from_millis(METER_HALF_TICK_MS));
@@ -659,16 +1039,11 @@ impl PipeweaverHandler {
                                 let drawing = renderer.get_volume(self.active_mix)?;
                                 let (x, y) = drawing.position;
 
-                                let (ch_w, _) = CHANNEL_DIMENSIONS;
-                                let base_x = ch_w * index as u32;
+                                let (base_x, base_y) = self.layout().channel_position(index as u32);
+                                let x = base_x + x;
+                                let y = base_y + y;
 
-                                let (root_x, root_y) = POSITION_ROOT;
-                                let x = base_x + x + root_x;
-                                let y = y + root_y;
-
-                                let (tx, rx) = oneshot::channel();
-                                self.sender.send(SendImage(drawing.image, x, y, tx))?;
-                                rx.recv()??;
+                                self.frame.send(&self.sender, drawing.image, x, y)?;
 
                                 sub_tick = Some((result.id, index));
                                 sub_sleep.as_mut().reset(time::Instant::now() + Duration::from_millis(METER_HALF_TICK_MS));
@@ -695,8 +1070,8 @@ impl PipeweaverHandler {
                             continue;
                         }
 
-                        // Drawing is suspended, we'll re-tick, but wont draw.
-                        if is_suspended && !self.temporary_active {
+                        // Drawing is suspended (or covered by the overlay), we'll re-tick, but wont draw.
+                        if (is_suspended && !self.temporary_active) || self.volume_overlay.is_some() {
                             sub_tick = Some((id, index));
                             sub_sleep.as_mut().reset(time::Instant::now() + Duration::from_millis(METER_HALF_TICK_MS));
                             continue;
@@ -705,14 +1080,11 @@ impl PipeweaverHandler {
                         let drawing = renderer.get_volume(self.active_mix)?;
                         let (x, y) = drawing.position;
 
-                        let (ch_w, _) = CHANNEL_DIMENSIONS;
-                        let (root_x, root_y) = POSITION_ROOT;
-                        let x = ch_w * index as u32 + x + root_x;
-                        let y = y + root_y;
+                        let (base_x, base_y) = self.layout().channel_position(index as u32);
+                        let x = base_x + x;
+                        let y = base_y + y;
 
-                        let (tx, rx) = oneshot::channel();
-                        self.sender.send(SendImage(drawing.image, x, y, tx))?;
-                        rx.recv()??;
+                        self.frame.send(&self.sender, drawing.image, x, y)?;
 
                         // Keep ticking until meter hits zero
                         if renderer.meter > 0 {
@@ -722,6 +1094,21 @@ impl PipeweaverHandler {
                     }
                 }
 
+                _ = &mut animation_sleep, if self.renderers.values().any(|r| r.is_animating()) => {
+                    self.draw_animation_frame()?;
+                    if self.renderers.values().any(|r| r.is_animating()) {
+                        animation_sleep.as_mut().reset(time::Instant::now() + ANIMATION_FRAME);
+                    }
+                }
+
+                _ = sleep(self.volume_overlay_remaining()), if self.volume_overlay.is_some() => {
+                    // Clear the overlay, and bring back everything that was drawn underneath it
+                    self.volume_overlay = None;
+                    if !self.is_suspended() || self.temporary_active {
+                        self.perform_full_redraw()?;
+                    }
+                }
+
                 _ = &mut suspend_sleep, if self.is_suspended() => {
                     // We should be sleeping, and something woke us up, so put us back to sleep
                     let (tx, rx) = oneshot::channel();
@@ -734,6 +1121,7 @@ impl PipeweaverHandler {
                 maybe_msg = interaction_rx.recv() => {
                     match maybe_msg {
                         Some(msg) => {
+                            self.keep_alive.activity();
                             if is_suspended {
                                 // Reset the timer in all cases
                                 suspend_sleep.as_mut().reset(time::Instant::now() + Duration::from_secs(5));
@@ -748,12 +1136,17 @@ impl PipeweaverHandler {
                                 }
                             }
 
+                            // Bring back the header if it's been faded
+                            if self.frame.note_activity() && (!is_suspended || self.temporary_active) {
+                                self.perform_full_redraw()?;
+                            }
+
                             match self.device_type {
                                 DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
                                     match msg {
                                         Interactions::ButtonPress(button, state) => {
                                             match state {
-                                                ButtonState::Press => self.on_button_down(button).await?,
+                                                ButtonState::Press => self.on_button_down(button),
                                                 ButtonState::Release => self.on_button_up(button, stream).await?,
                                             }
                                         }
@@ -768,14 +1161,17 @@ impl PipeweaverHandler {
                         None => bail!("Receive Handler Closed!")
                     }
                 }
-                _instant = keep_alive.tick() => {
-                    let (tx,rx) = oneshot::channel();
-                    self.sender.send(ControlMessage::KeepAlive(tx))?;
-                    rx.recv()??;
+                _ = self.keep_alive.tick() => {
+                    if self.keep_alive.should_send() {
+                        let (tx, rx) = oneshot::channel();
+                        self.sender.send(ControlMessage::KeepAlive(tx))?;
+                        rx.recv()??;
+                    }
                 }
 
                 _ = ticker.tick() => {
-                    self.check_held().await?;
+                    self.check_gestures(stream).await?;
+                    self.check_crossfade(stream).await?;
                 }
             }
         }
@@ -791,7 +1187,7 @@ impl PipeweaverHandler {
     }
 
     fn update_renderers(&mut self) -> Result<()> {
-        for device in &self.devices_shown {
+        for device in self.devices_shown.iter().flatten() {
             if !self.renderers.contains_key(device) {
                 let render = self.get_channel_renderer(device)?;
                 self.renderers.insert(*device, render);
@@ -799,62 +1195,242 @@ impl PipeweaverHandler {
         }
         // Remove configs which aren't shown anymore
         self.renderers
-            .retain(|id, _| self.devices_shown.contains(id));
+            .retain(|id, _| self.devices_shown.contains(&Some(*id)));
         Ok(())
     }
 
-    fn perform_full_redraw(&self) -> Result<()> {
+    fn perform_full_redraw(&mut self) -> Result<()> {
         let (width, height) = DISPLAY_DIMENSIONS;
         let mut base = ImageBuffer::from_pixel(width, height, BG_COLOUR);
 
         DrawingUtils::composite_from_pos(&mut base, &jpeg_as_img(HEADER)?, (0, 0));
+        HeaderWidgetRenderer::draw_all(&mut base, self.header_widgets.current());
+        PanicMuteRenderer::draw_onto(&mut base, *self.panic_rx.borrow());
+        let colour = self.header_meter_colour();
+        HeaderMeterRenderer::draw_onto(&mut base, self.header_meter.current(), colour);
 
         for (index, item) in self.devices_shown.iter().enumerate() {
+            // Empty dials are left as background
+            let Some(item) = item else {
+                continue;
+            };
             let error = anyhow!("No Such Render Object");
-            let renderer = self.renderers.get(item).ok_or(error)?;
+            let renderer = self.renderers.get_mut(item).ok_or(error)?;
             let drawing = renderer.full_render(self.active_mix);
-            let (width, _) = CHANNEL_DIMENSIONS;
-            let x = width * index as u32;
-            let y = POSITION_ROOT.1;
-            DrawingUtils::composite_from_pos(&mut base, &drawing.image, (x, y));
+            let position = self.layout().channel_position(index as u32);
+            DrawingUtils::composite_from_pos(&mut base, &drawing.image, position);
         }
 
-        let (tx, rx) = oneshot::channel();
-        let img = img_as_jpeg(base, BG_COLOUR)?;
-        self.sender.send(SendImage(img, 0, 0, tx))?;
-        rx.recv()??;
+        if let Some((image, position)) = self.render_volume_overlay() {
+            DrawingUtils::composite_from_pos(&mut base, &image, position);
+        }
+
+        self.frame.send_full(&self.sender, base)?;
+        Ok(())
+    }
+
+    fn redraw_header_widgets(&mut self) -> Result<()> {
+        for (slot, text) in self.header_widgets.changed() {
+            let (image, (x, y)) = HeaderWidgetRenderer::draw(slot, &text);
+            self.frame.send(&self.sender, image, x, y)?;
+        }
+        Ok(())
+    }
+
+    fn redraw_header_meter(&mut self) -> Result<()> {
+        if let Some(level) = self.header_meter.changed() {
+            let colour = self.header_meter_colour();
+            let (image, (x, y)) = HeaderMeterRenderer::draw(level, colour);
+            self.frame.send(&self.sender, image, x, y)?;
+        }
+        Ok(())
+    }
+
+    // The meter follows the colour of the mix being shown on the dials
+    fn header_meter_colour(&self) -> Rgba<u8> {
+        match self.active_mix {
+            Mix::A => METER_A_DIAL,
+            Mix::B => METER_B_DIAL,
+        }
+    }
+
+    // The first output in Pipeweaver's order (pinned outputs first), normally the headphones
+    fn main_target(&self) -> Option<Ulid> {
+        let order = &self.status.audio.profile.devices.targets.device_order;
+        let pinned = order[OrderGroup::Pinned].iter();
+        pinned.chain(&order[OrderGroup::Default]).next().copied()
+    }
+
+    fn render_volume_overlay(&self) -> Option<(RgbaImage, Position)> {
+        let (device, _) = self.volume_overlay?;
+        let renderer = self.renderers.get(&device)?;
+
+        // Work back through this turn of the dial, to find where the volume started
+        let mut changes = self
+            .dial_history
+            .iter()
+            .rev()
+            .take_while(|c| c.device == device);
+        let last = changes.next()?;
+        let mut first = last;
+        for change in changes {
+            if first.at.duration_since(change.at) > VOLUME_OVERLAY_DURATION {
+                break;
+            }
+            first = change;
+        }
 
+        let title = &renderer.title;
+        Some(VolumeOverlayRenderer::draw(
+            title,
+            first.from,
+            last.to,
+            renderer.volume_unit(),
+            renderer.colour,
+        ))
+    }
+
+    fn apply_patch(&mut self, patch: &json_patch::Patch) -> Result<()> {
+        json_patch::patch(&mut self.raw_status, patch)?;
+        self.status = serde_json::from_value::<DaemonStatus>(self.raw_status.clone())?;
+        self.publish_channels();
+        Ok(())
+    }
+
+    // Moves every animating channel on a frame, sending the dial while it's easing, and the bars
+    // as their highlight fades
+    fn draw_animation_frame(&mut self) -> Result<()> {
+        let draw = !self.is_suspended() || self.temporary_active;
+        let mut drawn = false;
+
+        for (device, renderer) in self.renderers.iter_mut() {
+            if !renderer.is_animating() {
+                continue;
+            }
+            let dial = renderer.advance_animation();
+
+            let mut images = vec![];
+            for img in renderer.render_damaged(ChannelChangedProperty::Colour, self.active_mix) {
+                images.push((Tile::from(img.image), img.position));
+            }
+            if dial {
+                let img = renderer.get_volume(self.active_mix)?;
+                images.push((Tile::from(img.image), img.position));
+            }
+
+            let index = self.devices_shown.iter().position(|d| *d == Some(*device));
+            let Some(index) = index.filter(|_| draw) else {
+                continue;
+            };
+
+            let (base_x, base_y) = self.layout().channel_position(index as u32);
+            for (img, (x, y)) in images {
+                self.frame.send(&self.sender, img, base_x + x, base_y + y)?;
+                drawn = true;
+            }
+        }
+
+        // Keep the volume overlay above anything drawn underneath it
+        if drawn && self.volume_overlay.is_some() {
+            self.draw_volume_overlay()?;
+        }
+        Ok(())
+    }
+
+    fn is_external_change(&self, device: Ulid) -> bool {
+        match self.local_changes.get(&device) {
+            Some(at) => at.elapsed() > LOCAL_CHANGE_WINDOW,
+            None => true,
+        }
+    }
+
+    fn draw_volume_overlay(&mut self) -> Result<()> {
+        if let Some((image, (x, y))) = self.render_volume_overlay() {
+            self.frame.send(&self.sender, image, x, y)?;
+        }
+        Ok(())
+    }
+
+    fn volume_overlay_remaining(&self) -> Duration {
+        match self.volume_overlay {
+            Some((_, until)) => until.saturating_duration_since(time::Instant::now()),
+            None => Duration::MAX,
+        }
+    }
+
+    fn redraw_panic_mute(&mut self) -> Result<()> {
+        let (image, (x, y)) = PanicMuteRenderer::draw(*self.panic_rx.borrow());
+        self.frame.send(&self.sender, image, x, y)?;
         Ok(())
     }
 
-    fn redraw_volumes(&self) -> Result<()> {
+    // Mutes every source which isn't already muted to Mix A, or unmutes the ones we muted
+    async fn apply_panic_mute(&mut self, stream: &mut WebSocket) -> Result<()> {
+        let commands: Vec<_> = match *self.panic_rx.borrow_and_update() {
+            true => {
+                let sources = &self.status.audio.profile.devices.sources;
+                let physical = sources
+                    .physical_devices
+                    .iter()
+                    .map(|d| d as &dyn SourceDevice);
+                let virtual_devices = sources
+                    .virtual_devices
+                    .iter()
+                    .map(|d| d as &dyn SourceDevice);
+                self.panic_muted = physical
+                    .chain(virtual_devices)
+                    .filter(|d| !d.mute_states().mute_state.contains(&MuteTarget::TargetA))
+                    .map(|d| d.description().id)
+                    .collect();
+                self.panic_muted
+                    .iter()
+                    .map(|id| APICommand::AddSourceMuteTarget(*id, MuteTarget::TargetA))
+                    .collect()
+            }
+            false => self
+                .panic_muted
+                .drain(..)
+                .map(|id| APICommand::DelSourceMuteTarget(id, MuteTarget::TargetA))
+                .collect(),
+        };
+
+        for message in commands {
+            let command = serde_json::to_string(&WebsocketRequest {
+                id: self.get_command_index(),
+                data: DaemonRequest::Pipewire(message),
+            })?;
+            stream.send(Message::Text(Utf8Bytes::from(command))).await?;
+        }
+        Ok(())
+    }
+
+    fn redraw_volumes(&mut self) -> Result<()> {
         for (index, item) in self.devices_shown.iter().enumerate() {
+            let Some(item) = item else {
+                continue;
+            };
             let error = anyhow!("No Such Render Object");
             let renderer = self.renderers.get(item).ok_or(error)?;
             let drawing = renderer.get_volume(self.active_mix)?;
             let (x, y) = drawing.position;
 
             // Determine the 'start' position of this channel
-            let (ch_w, _) = CHANNEL_DIMENSIONS;
-            let base_x = ch_w * index as u32;
-
-            // Get the position relative to the main image root
-            let (root_x, root_y) = POSITION_ROOT;
-            let x = base_x + x + root_x;
-            let y = y + root_y;
+            let (base_x, base_y) = self.layout().channel_position(index as u32);
+            let x = base_x + x;
+            let y = base_y + y;
 
             // Send it
-            let (tx, rx) = oneshot::channel();
-            self.sender.send(SendImage(drawing.image, x, y, tx))?;
-            rx.recv()??;
+            self.frame.send(&self.sender, drawing.image, x, y)?;
         }
 
         Ok(())
     }
 
     fn load_all_dial_button_colours(&self) -> Result<()> {
-        for index in 0..self.devices_shown.len() {
+        // In the compact layout, the dials only reach the top row
+        for index in 0..self.devices_shown.len().min(DIAL_COUNT) {
             self.load_dial_button_colour(index)?;
+            self.load_audience_button_colour(index)?;
         }
         Ok(())
     }
@@ -904,9 +1480,6 @@ impl PipeweaverHandler {
         let error = anyhow!("No Such Index");
         let device_id = self.devices_shown.get(index).ok_or(error)?;
 
-        let error = anyhow!("Failed to Fetch Renderer");
-        let render = self.renderers.get(device_id).ok_or(error)?;
-
         let dial_button = match index {
             0 => ButtonLighting::Dial1,
             1 => ButtonLighting::Dial2,
@@ -915,7 +1488,14 @@ impl PipeweaverHandler {
             _ => bail!("Invalid Dial Index"),
         };
 
-        let colour = render.colour;
+        let Some(device_id) = device_id else {
+            return self.set_button_colour(dial_button, COLOUR_BLACK);
+        };
+
+        let error = anyhow!("Failed to Fetch Renderer");
+        let render = self.renderers.get(device_id).ok_or(error)?;
+
+        let colour = render.colour();
         let beacn_colour = RGBA {
             red: colour[0],
             green: colour[1],
@@ -927,6 +1507,43 @@ impl PipeweaverHandler {
         Ok(())
     }
 
+    fn load_audience_button_colour(&self, index: usize) -> Result<()> {
+        // Only the Mix Create has Audience buttons
+        if self.device_type != DeviceType::BeacnMixCreate {
+            return Ok(());
+        }
+
+        let error = anyhow!("No Such Index");
+        let device_id = self.devices_shown.get(index).ok_or(error)?;
+
+        let audience_button = match index {
+            0 => ButtonLighting::Audience1,
+            1 => ButtonLighting::Audience2,
+            2 => ButtonLighting::Audience3,
+            3 => ButtonLighting::Audience4,
+            _ => bail!("Invalid Dial Index"),
+        };
+
+        let Some(device_id) = device_id else {
+            return self.set_button_colour(audience_button, COLOUR_BLACK);
+        };
+
+        let error = anyhow!("Failed to Fetch Renderer");
+        let render = self.renderers.get(device_id).ok_or(error)?;
+
+        // This follows whichever mute the button toggles, for outputs there's only the one
+        let target = match render.channel_type {
+            ChannelType::Source => MuteTarget::TargetB,
+            ChannelType::Target => MuteTarget::TargetA,
+        };
+        let colour = match render.mute_states[target].is_active {
+            true => COLOUR_AUDIENCE_MUTED,
+            false => COLOUR_BLACK,
+        };
+
+        self.set_button_colour(audience_button, colour)
+    }
+
     fn get_command_index(&mut self) -> u64 {
         let result = self.command_index;
         self.command_index += 1;
@@ -948,9 +1565,32 @@ impl PipeweaverHandler {
             DeviceRef::VirtualTarget(d) => ChannelRenderer::from(d.clone()),
         };
         renderer.set_beacn_device(self.device_type);
+        renderer.set_colour_override(Self::colour_override(&self.colours_rx, device));
+        renderer.set_volume_unit(Self::volume_unit(&self.volume_units_rx, device));
+        renderer.set_layout(self.layout());
         Ok(renderer)
     }
 
+    fn layout(&self) -> &'static LayoutTheme {
+        self.screen_layout_rx.borrow().theme()
+    }
+
+    fn channels_per_page(&self) -> usize {
+        self.layout().channel_count() as usize
+    }
+
+    fn colour_override(
+        colours: &watch::Receiver<ColourOverrides>,
+        device: &Ulid,
+    ) -> Option<Rgba<u8>> {
+        let [red, green, blue] = *colours.borrow().get(device)?;
+        Some(Rgba([red, green, blue, 255]))
+    }
+
+    fn volume_unit(units: &watch::Receiver<VolumeUnits>, device: &Ulid) -> VolumeUnit {
+        units.borrow().get(device).copied().unwrap_or_default()
+    }
+
     fn refresh_page(&mut self) -> Result<()> {
         self.devices_shown = self.get_channels_on_page();
         self.update_renderers()?;
@@ -959,21 +1599,39 @@ impl PipeweaverHandler {
     }
 
     fn get_page_count(&self) -> u8 {
+        // A fixed assignment only ever has one page
+        if self.has_dial_assignment() {
+            return 1;
+        }
+
         let order = self.get_channel_order();
 
         // If we can't display any other channels because we're populated with pins, send 1 page.
-        if order[OrderGroup::Pinned].len() >= 4 || order[OrderGroup::Default].is_empty() {
+        let per_page = self.channels_per_page();
+        if order[OrderGroup::Pinned].len() >= per_page || order[OrderGroup::Default].is_empty() {
             return 1;
         }
 
-        let channels_per_page = 4 - order[OrderGroup::Pinned].len() as u8;
+        let channels_per_page = (per_page - order[OrderGroup::Pinned].len()) as u8;
         let channel_count = order[OrderGroup::Default].len() as u8;
         (channels_per_page + channel_count - 1) / channels_per_page
     }
 
-    fn get_channels_on_page(&self) -> Vec<Ulid> {
+    fn get_channels_on_page(&self) -> Vec<Option<Ulid>> {
+        if self.has_dial_assignment()
+            && let Some(assignment) = *self.dial_assignment.borrow()
+        {
+            // Channels which have been removed from Pipeweaver are treated as empty
+            let sources = &self.status.audio.profile.devices.sources;
+            return assignment
+                .into_iter()
+                .map(|slot| slot.filter(|id| self.get_source_device_ref(id, sources).is_ok()))
+                .collect();
+        }
+
         let order = self.get_channel_order();
-        let mut channels = Vec::with_capacity(4);
+        let per_page = self.channels_per_page();
+        let mut channels = Vec::with_capacity(per_page);
 
         // This is a little complicated, we need to check the pinned channels and add them first
         let pinned = &order[OrderGroup::Pinned];
@@ -985,21 +1643,21 @@ impl PipeweaverHandler {
         }
 
         // The pinned options should appear on all the pages
-        for channel in pinned.iter().take(channels.capacity() - channels.len()) {
-            channels.push(*channel);
+        for channel in pinned.iter().take(per_page) {
+            channels.push(Some(*channel));
         }
 
-        // If the user has 4 pinned channels, we really can't do paging
-        if channels.len() == channels.capacity() {
+        // If the page is filled with pinned channels, we really can't do paging
+        if channels.len() == per_page {
             return channels;
         }
 
         // Ok, now we need to work out how many non-pinned channels per page we can have
-        let channels_per_page = 4 - pinned.len() as u8;
+        let channels_per_page = (per_page - pinned.len()) as u8;
 
         if others.len() < channels_per_page as usize {
             for other in others {
-                channels.push(*other);
+                channels.push(Some(*other));
             }
             return channels;
         }
@@ -1013,14 +1671,52 @@ impl PipeweaverHandler {
         };
 
         for channel in others.iter().skip(start) {
-            if channels.len() != channels.capacity() {
-                channels.push(*channel);
+            if channels.len() != per_page {
+                channels.push(Some(*channel));
             }
         }
 
         channels
     }
 
+    fn has_dial_assignment(&self) -> bool {
+        self.channel_type == ChannelType::Source && self.dial_assignment.borrow().is_some()
+    }
+
+    // Lets the UI know which channels are available to be assigned to the dials, or recoloured
+    fn publish_channels(&self) {
+        let channel = |desc: &DeviceDescription, is_output| AssignableChannel {
+            id: desc.id,
+            name: desc.name.clone(),
+            colour: [desc.colour.red, desc.colour.green, desc.colour.blue],
+            is_output,
+        };
+
+        let sources = &self.status.audio.profile.devices.sources;
+        let targets = &self.status.audio.profile.devices.targets;
+        let physical = sources.physical_devices.iter();
+        let virtual_devices = sources.virtual_devices.iter();
+        let inputs = physical
+            .map(|d| channel(&d.description, false))
+            .chain(virtual_devices.map(|d| channel(&d.description, false)));
+
+        let physical = targets.physical_devices.iter();
+        let virtual_devices = targets.virtual_devices.iter();
+        let outputs = physical
+            .map(|d| channel(&d.description, true))
+            .chain(virtual_devices.map(|d| channel(&d.description, true)));
+
+        let channels: Vec<AssignableChannel> = inputs.chain(outputs).collect();
+
+        self.channels_tx.send_if_modified(|current| {
+            if *current == channels {
+                return false;
+            }
+            *current = channels;
+            true
+        });
+    }
+
     fn get_channel_order(&self) -> &EnumMap<OrderGroup, Vec<Ulid>> {
         match self.channel_type {
             ChannelType::Source => &self.status.audio.profile.devices.sources.device_order,
@@ -1071,103 +1767,161 @@ impl PipeweaverHandler {
         Ok(())
     }
 
-    async fn on_button_down(&mut self, button: Buttons) -> Result<()> {
+    fn on_button_down(&mut self, button: Buttons) {
         debug!("Button Down: {:?}", button);
 
-        // Register this button down, assume normal behaviour
-        self.button_down_states[button].replace(ButtonHoldState {
-            press_time: Some(Instant::now()),
-            skip_hold: false,
-            skip_release: false,
-            hold_handled: false,
-        });
+        // The dial buttons act on release, or when held, depending on the gesture
+        if GestureDetector::handles(button) {
+            self.gestures.press(button);
+        }
 
-        Ok(())
+        if button == Buttons::AudienceMix {
+            self.mix_pressed_at = Some(time::Instant::now());
+        }
     }
 
     async fn on_button_up(&mut self, button: Buttons, stream: &mut WebSocket) -> Result<()> {
         debug!("Button Up: {:?}", button);
 
-        // Have we been instructed to skip release behaviour for this button?
-        if let Some(state) = self.button_down_states[button]
-            && state.skip_release
-        {
-            debug!("State: {:?}", state);
-            debug!("Skipping Release Behaviour for Button: {:?}", button);
-
-            // Take the handler, and return.
-            self.button_down_states[button].take();
-            return Ok(());
+        // A held Mix button fades back rather than switching the mix
+        if button == Buttons::AudienceMix {
+            self.mix_pressed_at = None;
+            if let Some(crossfade) = &mut self.crossfade {
+                crossfade.fade_to(false);
+                return Ok(());
+            }
         }
 
-        debug!("Button Up handling normally: {}..", button);
+        if !GestureDetector::handles(button) {
+            return self.handle_button(button, stream).await;
+        }
 
-        // Handle the button up normally
-        self.handle_button(button, stream).await?;
-        if self.button_down_states[button].is_some() {
-            self.button_down_states[button].take();
+        let bindings = *self.gestures_rx.borrow();
+        if let Some(gesture) = self.gestures.release(button, &bindings) {
+            self.handle_gesture(button, gesture, stream).await?;
         }
+        Ok(())
+    }
 
+    async fn check_gestures(&mut self, stream: &mut WebSocket) -> Result<()> {
+        let bindings = *self.gestures_rx.borrow();
+        for (button, gesture) in self.gestures.tick(&bindings) {
+            self.handle_gesture(button, gesture, stream).await?;
+        }
         Ok(())
     }
 
-    async fn on_button_held(&mut self, button: Buttons) -> Result<()> {
-        debug!("Button Held: {:?}", button);
+    async fn check_crossfade(&mut self, stream: &mut WebSocket) -> Result<()> {
+        let held = self
+            .mix_pressed_at
+            .is_some_and(|pressed| pressed.elapsed() >= LONG_PRESS_TIME);
 
-        // Handle holding should only occur once per button press, so regardless of what happens
-        // below, flag this as handled so we don't try to handle it again, and if it's already
-        // been handled, we can just return.
-        if let Some(state) = &mut self.button_down_states[button] {
-            if state.hold_handled {
-                return Ok(());
-            } else {
-                state.hold_handled = true;
+        if held {
+            match &mut self.crossfade {
+                Some(crossfade) => crossfade.fade_to(true),
+                None => self.start_crossfade(),
             }
         }
 
-        // Button has been held, handle hold behaviour here.
-        match button {
-            Buttons::Dial1 | Buttons::Dial2 | Buttons::Dial3 | Buttons::Dial4 => {
-                // Switch from Sources to Targets
-                self.channel_type = match self.channel_type {
-                    ChannelType::Source => ChannelType::Target,
-                    ChannelType::Target => ChannelType::Source,
-                };
-
-                // We need to reload from scratch, so load the new initial state
-                self.active_page = 0;
-                self.active_mix = Mix::A;
+        let Some(crossfade) = &mut self.crossfade else {
+            return Ok(());
+        };
+        let changes = crossfade.step();
+        if crossfade.is_finished() {
+            debug!("Mix Crossfade Finished");
+            self.crossfade = None;
+        }
 
-                let _ = self.load_initial_state().await;
+        for (id, volume) in changes {
+            self.send_volume(id, ChannelType::Source, Mix::A, volume, stream)
+                .await?;
+        }
+        Ok(())
+    }
 
-                // Don't handle the release for this button, it's already handled.
-                if let Some(state) = &mut self.button_down_states[button] {
-                    state.skip_release = true;
-                }
-            }
-            _ => {}
+    fn start_crossfade(&mut self) {
+        let duration = Duration::from_millis(self.gestures_rx.borrow().mix_crossfade as u64);
+        if duration.is_zero() || self.channel_type != ChannelType::Source {
+            return;
         }
 
-        // If this isn't handled, do nothing.
-        Ok(())
+        let sources = &self.status.audio.profile.devices.sources;
+        let physical = sources
+            .physical_devices
+            .iter()
+            .map(|d| d as &dyn SourceDevice);
+        let virtual_devices = sources
+            .virtual_devices
+            .iter()
+            .map(|d| d as &dyn SourceDevice);
+        let volumes = physical
+            .chain(virtual_devices)
+            .map(|d| {
+                let volume = &d.volumes().volume;
+                (d.description().id, volume[Mix::A], volume[Mix::B])
+            })
+            .collect();
+
+        debug!("Starting Mix Crossfade over {duration:?}");
+        self.crossfade = Some(Crossfade::new(volumes, duration));
     }
 
-    async fn check_held(&mut self) -> Result<()> {
-        for button in Buttons::iter() {
-            if let Some(state) = self.button_down_states[button] {
-                // If we don't have a press time, there's nothing to handle.
-                if let Some(time) = state.press_time
-                    && !state.hold_handled
-                    && !state.skip_hold
-                    && time.elapsed() > HELD_TIME
-                {
-                    self.on_button_held(button).await?;
+    async fn handle_gesture(
+        &mut self,
+        button: Buttons,
+        gesture: DialGesture,
+        stream: &mut WebSocket,
+    ) -> Result<()> {
+        let bindings = *self.gestures_rx.borrow();
+        let action = bindings.action(gesture);
+        debug!("{gesture} on {button:?}: {action}");
+
+        let (index, audience) = match button {
+            Buttons::Dial1 => (0, Buttons::Audience1),
+            Buttons::Dial2 => (1, Buttons::Audience2),
+            Buttons::Dial3 => (2, Buttons::Audience3),
+            Buttons::Dial4 => (3, Buttons::Audience4),
+            _ => bail!("Gestures are only available on the dials"),
+        };
+
+        match action {
+            DialAction::None => {}
+            DialAction::Mute => self.handle_button(button, stream).await?,
+            DialAction::MuteAudience => self.handle_button(audience, stream).await?,
+            DialAction::DefaultVolume => {
+                self.set_volume(index, bindings.default_volume, stream)
+                    .await?;
+            }
+            DialAction::SwitchMix => self.handle_button(Buttons::AudienceMix, stream).await?,
+            DialAction::SwitchChannels => self.switch_channel_type().await,
+            DialAction::NextPage => self.handle_button(Buttons::PageRight, stream).await?,
+            DialAction::PreviousPage => self.handle_button(Buttons::PageLeft, stream).await?,
+            DialAction::FirstPage => {
+                if self.active_page != 0 {
+                    self.active_page = 0;
+                    if !self.is_suspended() || self.temporary_active {
+                        self.refresh_page()?;
+                    }
                 }
             }
         }
         Ok(())
     }
 
+    async fn switch_channel_type(&mut self) {
+        // Switch from Sources to Targets
+        self.channel_type = match self.channel_type {
+            ChannelType::Source => ChannelType::Target,
+            ChannelType::Target => ChannelType::Source,
+        };
+
+        // We need to reload from scratch, so load the new initial state
+        self.active_page = 0;
+        self.active_mix = Mix::A;
+
+        let _ = self.load_initial_state().await;
+    }
+
     // Handle Button Presses
     async fn handle_button(&mut self, button: Buttons, stream: &mut WebSocket) -> Result<()> {
         match button {
@@ -1229,7 +1983,8 @@ impl PipeweaverHandler {
                     _ => bail!("This shouldn't happen."),
                 };
 
-                if let Some(device) = self.devices_shown.get(index) {
+                if let Some(Some(device)) = self.devices_shown.get(index) {
+                    self.local_changes.insert(*device, time::Instant::now());
                     let error = anyhow!("Failed to get Renderer");
                     let current = self.renderers.get_mut(device).ok_or(error)?;
 
@@ -1264,40 +2019,133 @@ impl PipeweaverHandler {
     }
 
     async fn handle_dial(&mut self, dial: Dials, change: i8, stream: &mut WebSocket) -> Result<()> {
-        let device_index = match dial {
-            Dials::Dial1 => 0,
-            Dials::Dial2 => 1,
-            Dials::Dial3 => 2,
-            Dials::Dial4 => 3,
+        session::record_dial(dial, change);
+
+        let (device_index, button) = match dial {
+            Dials::Dial1 => (0, Buttons::Dial1),
+            Dials::Dial2 => (1, Buttons::Dial2),
+            Dials::Dial3 => (2, Buttons::Dial3),
+            Dials::Dial4 => (3, Buttons::Dial4),
         };
 
-        let command_index = self.get_command_index();
-        if let Some(device) = self.devices_shown.get(device_index) {
+        // Turning a dial while its button is held is fine mode, rather than a press
+        let acceleration = self.gestures_rx.borrow().acceleration;
+        let fine = acceleration.fine_mode && self.gestures.is_held(button);
+        if fine {
+            self.gestures.cancel(button);
+        }
+        let change = self
+            .accelerator
+            .apply(device_index, change, &acceleration, fine);
+
+        if let Some(Some(device)) = self.devices_shown.get(device_index) {
             let error = anyhow!("Failed to get Renderer");
             let current = self.renderers.get(device).ok_or(error)?;
 
-            let volume = current.volumes[self.active_mix] as i16;
-            let new_volume = (volume + change as i16).clamp(0, 100) as u8;
-
-            let message = match self.channel_type {
-                ChannelType::Source => SetSourceVolume(*device, self.active_mix, new_volume),
-                ChannelType::Target => SetTargetVolume(*device, new_volume),
+            // Changes waiting to be sent haven't reached the renderer yet, so build on them
+            let volume = match self.pending_volumes.get(device) {
+                Some((_, mix, volume)) if *mix == self.active_mix => *volume,
+                _ => current.volumes[self.active_mix],
             };
+            let new_volume = (volume as i16 + change).clamp(0, 100) as u8;
+            let device = *device;
+            self.local_changes.insert(device, time::Instant::now());
+            if self.is_performance_mode() {
+                let pending = (self.channel_type, self.active_mix, new_volume);
+                self.pending_volumes.insert(device, pending);
+            } else {
+                self.set_volume(device_index, new_volume, stream).await?;
+            }
 
-            let command = serde_json::to_string(&WebsocketRequest {
-                id: command_index,
-                data: DaemonRequest::Pipewire(message),
-            })?;
+            if self.dial_history.len() == DIAL_HISTORY_LENGTH {
+                self.dial_history.pop_front();
+            }
+            let now = time::Instant::now();
+            self.dial_history.push_back(DialChange {
+                device,
+                from: volume,
+                to: new_volume,
+                at: now,
+            });
+
+            self.volume_overlay = Some((device, now + VOLUME_OVERLAY_DURATION));
+            if !self.is_suspended() || self.temporary_active {
+                self.draw_volume_overlay()?;
+            }
+        }
 
-            stream.send(Message::Text(Utf8Bytes::from(command))).await?;
+        Ok(())
+    }
+
+    async fn set_volume(&mut self, index: usize, volume: u8, stream: &mut WebSocket) -> Result<()> {
+        let Some(Some(device)) = self.devices_shown.get(index).copied() else {
+            return Ok(());
+        };
+        let (channel_type, mix) = (self.channel_type, self.active_mix);
+        self.send_volume(device, channel_type, mix, volume, stream)
+            .await
+    }
+
+    async fn send_pending_volumes(&mut self, stream: &mut WebSocket) -> Result<()> {
+        let pending: Vec<_> = self.pending_volumes.drain().collect();
+        for (device, (channel_type, mix, volume)) in pending {
+            self.send_volume(device, channel_type, mix, volume, stream)
+                .await?;
         }
+        Ok(())
+    }
+
+    async fn send_volume(
+        &mut self,
+        device: Ulid,
+        channel_type: ChannelType,
+        mix: Mix,
+        volume: u8,
+        stream: &mut WebSocket,
+    ) -> Result<()> {
+        let message = match channel_type {
+            ChannelType::Source => SetSourceVolume(device, mix, volume),
+            ChannelType::Target => SetTargetVolume(device, volume),
+        };
+
+        let command = serde_json::to_string(&WebsocketRequest {
+            id: self.get_command_index(),
+            data: DaemonRequest::Pipewire(message),
+        })?;
 
+        stream.send(Message::Text(Utf8Bytes::from(command))).await?;
         Ok(())
     }
 
     fn is_suspended(&self) -> bool {
         *self.suspended_rx.borrow()
     }
+
+    fn is_power_saving(&self) -> bool {
+        *self.power_saving_rx.borrow()
+    }
+
+    fn is_performance_mode(&self) -> bool {
+        *self.performance_rx.borrow()
+    }
+
+    // Performance mode and power saving both depend on updates being coalesced
+    fn should_coalesce(&self) -> bool {
+        *self.coalesce_rx.borrow() || self.is_power_saving() || self.is_performance_mode()
+    }
+
+    fn frame_ticker(&self) -> time::Interval {
+        let interval = if self.is_power_saving() {
+            POWER_SAVE_FRAME_INTERVAL
+        } else if self.is_performance_mode() {
+            PERFORMANCE_FRAME_INTERVAL
+        } else {
+            FRAME_INTERVAL
+        };
+        let mut ticker = time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker
+    }
 }
 
 pub fn spawn_pipeweaver_handler(
@@ -1306,23 +2154,25 @@ pub fn spawn_pipeweaver_handler(
     input_rx: Receiver<Interactions>,
     stop_rx: watch::Receiver<()>,
     suspended_rx: watch::Receiver<bool>,
+    handles: DisplayHandles,
 ) -> JoinHandle<()> {
-    let mut handler = PipeweaverHandler::new(device, sender, input_rx, stop_rx, suspended_rx);
+    let mut handler =
+        PipeweaverHandler::new(device, sender, input_rx, stop_rx, suspended_rx, handles);
     runtime().spawn(async move { handler.run_handler().await })
 }
 
-fn img_as_jpeg(image: RgbaImage, background: Rgba<u8>) -> Result<Vec<u8>> {
+pub(crate) fn img_as_jpeg(image: RgbaImage, background: Rgba<u8>) -> Result<Vec<u8>> {
     DrawingUtils::image_as_jpeg(image, background, JPEG_QUALITY)
 }
 
-fn jpeg_as_img(image: &[u8]) -> Result<RgbaImage> {
+pub(crate) fn jpeg_as_img(image: &[u8]) -> Result<RgbaImage> {
     if let Ok(img) = load_from_memory(image) {
         return Ok(img.into_rgba8());
     }
     bail!("Failed to load image");
 }
 
-fn sync_to_async(
+pub(crate) fn sync_to_async(
     rx: Receiver<Interactions>,
     tx: tokio::sync::mpsc::Sender<Interactions>,
     cancel: Receiver<()>,
@@ -0,0 +1,239 @@
+// Text configuration for the Mix / Mix Create displays. By default we use the bundled Noto
+// fonts, but users can provide their own font (and a fallback chain for scripts the primary
+// font doesn't cover), tweak the sizes of the various text elements, and select a locale
+// which controls how percentages are formatted on the dials.
+//
+// This is all loaded once from 'display_text.json' in the config directory, a restart is
+// required for changes to take effect.
+
+use crate::APP_NAME;
+use crate::integrations::pipeweaver::cache::StableHasher;
+use crate::integrations::pipeweaver::layout::{
+    FONT, FONT_BOLD, HEADER_FONT_SIZE, MUTE_FONT_SIZE, STATUS_FONT_SIZE, VOLUME_FONT_SIZE,
+};
+use crate::storage;
+use anyhow::{Result, bail};
+use fontdue::{Font, FontSettings};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use xdg::BaseDirectories;
+
+const CONFIG_FILE: &str = "display_text.json";
+
+pub(crate) static DISPLAY_TEXT: LazyLock<DisplayText> = LazyLock::new(DisplayText::load);
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayTextConfig {
+    /// A TTF / OTF file to use in place of the regular display font
+    pub font_path: Option<PathBuf>,
+
+    /// A TTF / OTF file to use in place of the bold display font
+    pub bold_font_path: Option<PathBuf>,
+
+    /// Fonts to check (in order) when a character isn't present in the primary font
+    pub fallback_fonts: Vec<PathBuf>,
+
+    pub sizes: SizeOverrides,
+
+    /// Locale used for number formatting (eg. 'fr_FR'), if not set we'll check the environment
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizeOverrides {
+    pub header: Option<f32>,
+    pub volume: Option<f32>,
+    pub mute: Option<f32>,
+    pub status: Option<f32>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum TextStyle {
+    Header,
+    Volume,
+    Mute,
+    Status,
+}
+
+impl TextStyle {
+    fn is_bold(&self) -> bool {
+        !matches!(self, TextStyle::Mute)
+    }
+
+    fn default_size(&self) -> f32 {
+        match self {
+            TextStyle::Header => HEADER_FONT_SIZE,
+            TextStyle::Volume => VOLUME_FONT_SIZE,
+            TextStyle::Mute => MUTE_FONT_SIZE,
+            TextStyle::Status => STATUS_FONT_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum PercentStyle {
+    /// 50%
+    Suffix,
+
+    /// 50 %
+    SpacedSuffix,
+
+    /// %50
+    Prefix,
+}
+
+impl PercentStyle {
+    fn from_locale(locale: &str) -> Self {
+        // We only care about the language part, so 'de_DE.UTF-8' becomes 'de'
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match language.as_str() {
+            "tr" | "eu" => PercentStyle::Prefix,
+            "fr" | "de" | "es" | "sv" | "nb" | "nn" | "no" | "da" | "fi" | "cs" | "sk" | "pl"
+            | "ru" | "uk" => PercentStyle::SpacedSuffix,
+            _ => PercentStyle::Suffix,
+        }
+    }
+}
+
+pub(crate) struct DisplayText {
+    config: DisplayTextConfig,
+    regular: Vec<Font>,
+    bold: Vec<Font>,
+    percent_style: PercentStyle,
+}
+
+impl DisplayText {
+    fn load() -> Self {
+        let config = Self::load_config().unwrap_or_else(|e| {
+            debug!("Using default display text config: {e}");
+            DisplayTextConfig::default()
+        });
+
+        // Build the font chains, user fonts first, then our bundled font, then any fallbacks
+        let fallbacks: Vec<Font> = config
+            .fallback_fonts
+            .iter()
+            .filter_map(Self::load_font)
+            .collect();
+
+        let mut regular: Vec<Font> = config
+            .font_path
+            .iter()
+            .filter_map(Self::load_font)
+            .collect();
+        regular.push(Self::builtin_font(FONT));
+        regular.extend(fallbacks.iter().cloned());
+
+        let mut bold: Vec<Font> = config
+            .bold_font_path
+            .iter()
+            .chain(config.font_path.iter())
+            .take(1)
+            .filter_map(Self::load_font)
+            .collect();
+        bold.push(Self::builtin_font(FONT_BOLD));
+        bold.extend(fallbacks);
+
+        let locale = config
+            .locale
+            .clone()
+            .or_else(|| env::var("LC_ALL").ok().filter(|l| !l.is_empty()))
+            .or_else(|| env::var("LC_NUMERIC").ok().filter(|l| !l.is_empty()))
+            .or_else(|| env::var("LANG").ok())
+            .unwrap_or_default();
+        let percent_style = PercentStyle::from_locale(&locale);
+        debug!("Display Locale: '{locale}', Percent Style: {percent_style:?}");
+
+        Self {
+            config,
+            regular,
+            bold,
+            percent_style,
+        }
+    }
+
+    fn load_config() -> Result<DisplayTextConfig> {
+        let xdg_dirs = BaseDirectories::with_prefix(APP_NAME);
+        let Some(path) = xdg_dirs.find_config_file(CONFIG_FILE) else {
+            bail!("{CONFIG_FILE} not present");
+        };
+
+        info!("Loading Display Text config from {path:?}");
+        storage::load_json(path)
+    }
+
+    fn load_font(path: &PathBuf) -> Option<Font> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Unable to read font {path:?}: {e}");
+                return None;
+            }
+        };
+
+        match Font::from_bytes(bytes, FontSettings::default()) {
+            Ok(font) => Some(font),
+            Err(e) => {
+                warn!("Unable to parse font {path:?}: {e}");
+                None
+            }
+        }
+    }
+
+    fn builtin_font(bytes: &[u8]) -> Font {
+        Font::from_bytes(bytes, FontSettings::default()).expect("Failed to load bundled font")
+    }
+
+    /// Returns the font chain for this style, the first entry is always present
+    pub(crate) fn fonts(&self, style: TextStyle) -> &[Font] {
+        match style.is_bold() {
+            true => &self.bold,
+            false => &self.regular,
+        }
+    }
+
+    pub(crate) fn size(&self, style: TextStyle) -> f32 {
+        let sizes = &self.config.sizes;
+        let size = match style {
+            TextStyle::Header => sizes.header,
+            TextStyle::Volume => sizes.volume,
+            TextStyle::Mute => sizes.mute,
+            TextStyle::Status => sizes.status,
+        };
+
+        // Don't let a typo in the config produce something unreadable or silly
+        size.map(|s| s.clamp(8.0, 72.0))
+            .unwrap_or(style.default_size())
+    }
+
+    pub(crate) fn format_percent(&self, value: u8) -> String {
+        match self.percent_style {
+            PercentStyle::Suffix => format!("{value}%"),
+            PercentStyle::SpacedSuffix => format!("{value}\u{00A0}%"),
+            PercentStyle::Prefix => format!("%{value}"),
+        }
+    }
+
+    /// A value which changes whenever the config would change how text is rendered, this is
+    /// used to invalidate the on-disk image cache.
+    pub(crate) fn fingerprint(&self) -> u64 {
+        let mut hasher = StableHasher::default();
+        serde_json::to_string(&self.config)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        self.percent_style.hash(&mut hasher);
+        hasher.finish()
+    }
+}
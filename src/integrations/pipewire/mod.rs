@@ -0,0 +1,717 @@
+// A simple fallback integration for people who don't run Pipeweaver. Rather than pulling in
+// libpipewire, we use the tools that ship with any PipeWire install (pw-dump and wpctl) to list
+// application streams, and adjust their volumes.
+//
+// This is intentionally basic, each dial controls the volume of an application that's playing
+// audio, the dial buttons act on the same gestures as with Pipeweaver (toggling mute by
+// default), and the page buttons cycle through applications. There are no mixes or outputs, so
+// the gestures which switch between those do nothing here.
+
+use crate::device_manager::ControlMessage;
+use crate::device_manager::ControlMessage::ButtonColour;
+use crate::integrations::DisplayHandles;
+use crate::integrations::acceleration::DialAccelerator;
+use crate::integrations::burn_in;
+use crate::integrations::gestures::{DialAction, DialGesture, DialGestures, GestureDetector};
+use crate::integrations::header_widgets::{self, HeaderWidgetState};
+use crate::integrations::keep_alive::KeepAlive;
+use crate::integrations::orientation::Orientation;
+use crate::integrations::pipeweaver::channel::{ChannelChangedProperty, ChannelRenderer};
+use crate::integrations::pipeweaver::encoder::Tile;
+use crate::integrations::pipeweaver::frame::FrameBuffer;
+use crate::integrations::pipeweaver::layout::{
+    BG_COLOUR, CHANNEL_DIMENSIONS, DISPLAY_DIMENSIONS, DrawingUtils, HEADER, HeaderWidgetRenderer,
+    POSITION_ROOT, PanicMuteRenderer, TEXT_COLOUR, TextAlign,
+};
+use crate::integrations::pipeweaver::text::TextStyle;
+use crate::integrations::pipeweaver::{img_as_jpeg, jpeg_as_img, sync_to_async};
+use crate::integrations::pipewire::wpctl::{Wpctl, WpctlCommand};
+use crate::tools::tool_error;
+use crate::{integrations, panic_mute, runtime, session};
+use anyhow::{Result, anyhow, bail};
+use beacn_lib::controller::{ButtonLighting, ButtonState, Buttons, Dials, Interactions};
+use beacn_lib::crossbeam;
+use beacn_lib::crossbeam::channel::{Receiver, Sender};
+use beacn_lib::manager::DeviceType;
+use beacn_lib::types::RGBA;
+use image::{ImageBuffer, Rgba};
+use log::{debug, info, warn};
+use pipeweaver_shared::Mix;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Duration;
+use tokio::select;
+use tokio::sync::mpsc::channel;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+mod wpctl;
+
+const POLL_RATE: Duration = Duration::from_millis(500);
+const POWER_SAVE_POLL_RATE: Duration = Duration::from_secs(3);
+const CHANNELS_PER_PAGE: usize = 4;
+
+// Often enough for a long press to fire as soon as it's been held long enough
+const GESTURE_TICK: Duration = Duration::from_millis(20);
+
+// Applications don't have colours in PipeWire, so we'll pick from a palette based on the name
+const PALETTE: [Rgba<u8>; 6] = [
+    Rgba([89, 177, 182, 255]),
+    Rgba([244, 124, 36, 255]),
+    Rgba([155, 89, 182, 255]),
+    Rgba([46, 204, 113, 255]),
+    Rgba([231, 76, 60, 255]),
+    Rgba([241, 196, 15, 255]),
+];
+
+const COLOUR_WHITE: RGBA = RGBA {
+    red: 255,
+    green: 255,
+    blue: 255,
+    alpha: 255,
+};
+
+const COLOUR_BLACK: RGBA = RGBA {
+    red: 0,
+    green: 0,
+    blue: 0,
+    alpha: 0,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct AppStream {
+    id: u32,
+    name: String,
+    volume: u8,
+    muted: bool,
+}
+
+impl AppStream {
+    fn colour(&self) -> Rgba<u8> {
+        let sum: usize = self.name.bytes().map(|b| b as usize).sum();
+        PALETTE[sum % PALETTE.len()]
+    }
+}
+
+struct PipewireHandler {
+    device_type: DeviceType,
+    sender: Sender<ControlMessage>,
+    input_rx: Receiver<Interactions>,
+    stop_rx: watch::Receiver<()>,
+    suspended_rx: watch::Receiver<bool>,
+    orientation_rx: watch::Receiver<Orientation>,
+    power_saving_rx: watch::Receiver<bool>,
+    redraw_rx: watch::Receiver<()>,
+    gestures_rx: watch::Receiver<DialGestures>,
+
+    gestures: GestureDetector,
+    accelerator: DialAccelerator,
+    streams: Vec<AppStream>,
+    active_page: usize,
+    devices_shown: Vec<u32>,
+    renderers: HashMap<u32, ChannelRenderer>,
+    frame: FrameBuffer,
+    header_widgets: HeaderWidgetState,
+    keep_alive: KeepAlive,
+    panic_rx: watch::Receiver<bool>,
+
+    // Streams muted by the panic mute, which get unmuted when it's turned off
+    panic_muted: Vec<u32>,
+
+    wpctl: Wpctl,
+}
+
+impl PipewireHandler {
+    pub fn new(
+        device_type: DeviceType,
+        sender: Sender<ControlMessage>,
+        input_rx: Receiver<Interactions>,
+        stop_rx: watch::Receiver<()>,
+        suspended_rx: watch::Receiver<bool>,
+        handles: DisplayHandles,
+    ) -> Self {
+        // Updates here are infrequent, so there's no need to coalesce them
+        let orientation = *handles.orientation_rx.borrow();
+        Self {
+            device_type,
+            sender,
+            input_rx,
+            stop_rx,
+            suspended_rx,
+            orientation_rx: handles.orientation_rx,
+            power_saving_rx: handles.power_saving_rx,
+            redraw_rx: handles.redraw_rx,
+            gestures_rx: handles.gestures_rx,
+
+            gestures: GestureDetector::default(),
+            accelerator: DialAccelerator::default(),
+            streams: vec![],
+            active_page: 0,
+            devices_shown: Vec::with_capacity(CHANNELS_PER_PAGE),
+            renderers: HashMap::new(),
+            frame: FrameBuffer::new(false, false, orientation, handles.burn_in_rx),
+            header_widgets: HeaderWidgetState::new(handles.header_widgets_rx),
+            keep_alive: KeepAlive::new(handles.keep_alive_rx),
+            panic_rx: panic_mute::subscribe(),
+            panic_muted: vec![],
+            wpctl: Wpctl::spawn(),
+        }
+    }
+
+    pub async fn run_handler(&mut self) {
+        info!("Starting PipeWire Manager");
+        while let Err(e) = self.run_message_loop().await {
+            warn!("PipeWire Manager Error: {e}");
+            self.draw_status("Unable to communicate with PipeWire");
+
+            // Wait a bit before trying again, discarding any input which happened in between
+            select! {
+                Ok(_) = self.stop_rx.changed() => break,
+                _ = time::sleep(Duration::from_secs(5)) => {}
+            }
+            while self.input_rx.try_recv().is_ok() {}
+        }
+        info!("PipeWire Manager Terminated");
+    }
+
+    async fn run_message_loop(&mut self) -> Result<()> {
+        let sync_receiver = self.input_rx.clone();
+        let (interaction_tx, mut interaction_rx) = channel(10);
+
+        let (_stop_tx, stop_rx) = crossbeam::channel::bounded::<()>(0);
+        runtime().spawn_blocking(move || sync_to_async(sync_receiver, interaction_tx, stop_rx));
+
+        // Starting up (or reconnecting) shouldn't wake a screen that's been put to sleep
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(ControlMessage::Enabled(!self.is_suspended(), tx))?;
+        rx.recv()??;
+
+        self.streams = Self::fetch_streams().await?;
+        self.refresh_page()?;
+
+        let mut poll = self.poll_interval();
+        let mut burn_in = time::interval(burn_in::CHECK_INTERVAL);
+        let mut header = time::interval(header_widgets::CHECK_INTERVAL);
+        let mut gestures = time::interval(GESTURE_TICK);
+
+        loop {
+            select! {
+                Ok(_) = self.stop_rx.changed() => {
+                    return Ok(());
+                }
+                Ok(_) = self.suspended_rx.changed() => {
+                    if !self.is_suspended() {
+                        self.refresh_page()?;
+                    }
+                }
+                Ok(_) = self.power_saving_rx.changed() => {
+                    // Spawning pw-dump isn't free, so check for new streams less often
+                    poll = self.poll_interval();
+                }
+                Ok(_) = self.redraw_rx.changed() => {
+                    self.refresh_page()?;
+                }
+                Ok(_) = self.orientation_rx.changed() => {
+                    self.frame.set_orientation(*self.orientation_rx.borrow());
+                    self.refresh_page()?;
+                }
+                Ok(_) = self.panic_rx.changed() => {
+                    self.apply_panic_mute();
+                    if !self.is_suspended() {
+                        self.redraw_panic_mute()?;
+                    }
+                }
+                _ = poll.tick() => {
+                    let streams = Self::fetch_streams().await?;
+                    if streams != self.streams {
+                        self.streams = streams;
+                        self.apply_changes()?;
+                    }
+                }
+                _ = burn_in.tick() => {
+                    if self.frame.check_burn_in() {
+                        self.refresh_page()?;
+                    }
+                }
+                _ = header.tick(), if !self.is_suspended() => {
+                    self.redraw_header_widgets()?;
+                }
+                _ = gestures.tick() => {
+                    self.check_gestures()?;
+                }
+                _ = self.keep_alive.tick() => {
+                    if self.keep_alive.should_send() {
+                        let (tx, rx) = oneshot::channel();
+                        self.sender.send(ControlMessage::KeepAlive(tx))?;
+                        rx.recv()??;
+                    }
+                }
+                message = interaction_rx.recv() => {
+                    if message.is_some() {
+                        self.keep_alive.activity();
+                    }
+                    if message.is_some() && self.frame.note_activity() {
+                        self.refresh_page()?;
+                    }
+                    match message {
+                        Some(Interactions::ButtonPress(button, ButtonState::Press)) => {
+                            self.on_button_down(button)?;
+                        }
+                        Some(Interactions::ButtonPress(button, ButtonState::Release)) => {
+                            self.on_button_up(button)?;
+                        }
+                        Some(Interactions::DialChanged(dial, change)) => {
+                            self.handle_dial(dial, change)?;
+                        }
+                        None => bail!("Receive Handler Closed!")
+                    }
+                }
+            }
+        }
+    }
+
+    async fn fetch_streams() -> Result<Vec<AppStream>> {
+        runtime()
+            .spawn_blocking(|| {
+                let output = Command::new("pw-dump")
+                    .output()
+                    .map_err(tool_error("pw-dump"))?;
+                if !output.status.success() {
+                    bail!("pw-dump exited with {}", output.status);
+                }
+                let value: Value = serde_json::from_slice(&output.stdout)?;
+                Ok(Self::parse_streams(&value))
+            })
+            .await?
+    }
+
+    fn parse_streams(dump: &Value) -> Vec<AppStream> {
+        let mut streams = vec![];
+        let Some(objects) = dump.as_array() else {
+            return streams;
+        };
+
+        for object in objects {
+            if object["type"] != "PipeWire:Interface:Node" {
+                continue;
+            }
+
+            let info = &object["info"];
+            let props = &info["props"];
+            if props["media.class"] != "Stream/Output/Audio" {
+                continue;
+            }
+
+            let Some(id) = object["id"].as_u64() else {
+                continue;
+            };
+
+            let name = ["application.name", "node.description", "node.name"]
+                .iter()
+                .find_map(|key| props[*key].as_str())
+                .unwrap_or("Unknown")
+                .to_string();
+
+            // PipeWire stores a cubic volume per channel, wpctl (and everything else) shows
+            // the cube root of this as the 'volume', so we'll do the same.
+            let params = &info["params"]["Props"][0];
+            let volume = params["channelVolumes"]
+                .as_array()
+                .and_then(|v| v.iter().filter_map(|v| v.as_f64()).reduce(f64::max))
+                .unwrap_or(1.0);
+            let volume = (volume.cbrt() * 100.0).round().clamp(0.0, 100.0) as u8;
+            let muted = params["mute"].as_bool().unwrap_or(false);
+
+            streams.push(AppStream {
+                id: id as u32,
+                name,
+                volume,
+                muted,
+            });
+        }
+
+        // Keep things in a stable order, so channels don't jump around between polls
+        streams.sort_by_key(|s| s.id);
+        streams
+    }
+
+    fn get_page_count(&self) -> usize {
+        self.streams.len().div_ceil(CHANNELS_PER_PAGE).max(1)
+    }
+
+    fn get_channels_on_page(&self) -> Vec<u32> {
+        self.streams
+            .iter()
+            .skip(self.active_page * CHANNELS_PER_PAGE)
+            .take(CHANNELS_PER_PAGE)
+            .map(|s| s.id)
+            .collect()
+    }
+
+    fn get_stream(&self, id: u32) -> Result<&AppStream> {
+        self.streams
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or(anyhow!("Stream {id} not found"))
+    }
+
+    fn refresh_page(&mut self) -> Result<()> {
+        if self.active_page >= self.get_page_count() {
+            self.active_page = self.get_page_count() - 1;
+        }
+
+        self.devices_shown = self.get_channels_on_page();
+        self.renderers.clear();
+        for id in &self.devices_shown {
+            let stream = self.get_stream(*id)?;
+            let mut renderer = ChannelRenderer::new_target(
+                stream.name.clone(),
+                stream.colour(),
+                stream.volume,
+                stream.muted,
+            );
+            renderer.set_beacn_device(self.device_type);
+            self.renderers.insert(*id, renderer);
+        }
+
+        if self.is_suspended() {
+            return Ok(());
+        }
+
+        self.perform_full_redraw()?;
+        self.load_button_colours()?;
+        Ok(())
+    }
+
+    fn apply_changes(&mut self) -> Result<()> {
+        // If the set of applications on this page has changed, just redraw everything
+        if self.get_channels_on_page() != self.devices_shown
+            || self.active_page >= self.get_page_count()
+        {
+            return self.refresh_page();
+        }
+
+        for (index, id) in self.devices_shown.clone().iter().enumerate() {
+            let stream = self.get_stream(*id)?.clone();
+            let renderer = self
+                .renderers
+                .get_mut(id)
+                .ok_or(anyhow!("Failed to get renderer"))?;
+
+            let updates =
+                renderer.update_target(&stream.name, stream.colour(), stream.volume, stream.muted);
+            integrations::volume_changed(&stream.name, stream.volume);
+            if self.is_suspended() {
+                continue;
+            }
+
+            for update in updates {
+                let images = match update {
+                    ChannelChangedProperty::Volumes(_) => {
+                        let img = renderer.get_volume(Mix::A)?;
+                        vec![(Tile::from(img.image), img.position)]
+                    }
+                    update => {
+                        let mut images = vec![];
+                        for img in renderer.render_damaged(update, Mix::A) {
+                            images.push((Tile::from(img.image), img.position));
+                        }
+                        images
+                    }
+                };
+                for (img, (x, y)) in images {
+                    self.send_channel_image(index, img, x, y)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn perform_full_redraw(&mut self) -> Result<()> {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        let mut base = ImageBuffer::from_pixel(width, height, BG_COLOUR);
+        DrawingUtils::composite_from_pos(&mut base, &jpeg_as_img(HEADER)?, (0, 0));
+        HeaderWidgetRenderer::draw_all(&mut base, self.header_widgets.current());
+        PanicMuteRenderer::draw_onto(&mut base, *self.panic_rx.borrow());
+
+        if self.devices_shown.is_empty() {
+            let text = DrawingUtils::draw_text(
+                "No applications are playing audio".into(),
+                width,
+                30,
+                TextStyle::Status,
+                TEXT_COLOUR,
+                TextAlign::Center,
+            );
+            DrawingUtils::composite_from_pos(&mut base, &text, (0, 250));
+        }
+
+        for (index, id) in self.devices_shown.iter().enumerate() {
+            let renderer = self.renderers.get_mut(id).ok_or(anyhow!("No Renderer"))?;
+            let drawing = renderer.full_render(Mix::A);
+            let (ch_w, _) = CHANNEL_DIMENSIONS;
+            DrawingUtils::composite_from_pos(
+                &mut base,
+                &drawing.image,
+                (ch_w * index as u32, POSITION_ROOT.1),
+            );
+        }
+
+        self.frame.send_full(&self.sender, base)
+    }
+
+    fn redraw_panic_mute(&mut self) -> Result<()> {
+        let (image, (x, y)) = PanicMuteRenderer::draw(*self.panic_rx.borrow());
+        self.frame.send(&self.sender, image, x, y)
+    }
+
+    // Mutes every application which isn't already muted, or unmutes the ones we muted
+    fn apply_panic_mute(&mut self) {
+        if *self.panic_rx.borrow_and_update() {
+            let unmuted = self.streams.iter().filter(|s| !s.muted);
+            self.panic_muted = unmuted.map(|s| s.id).collect();
+            for id in &self.panic_muted {
+                self.wpctl.send(WpctlCommand::Mute(*id, true));
+            }
+        } else {
+            for id in self.panic_muted.drain(..) {
+                self.wpctl.send(WpctlCommand::Mute(id, false));
+            }
+        }
+    }
+
+    fn redraw_header_widgets(&mut self) -> Result<()> {
+        for (slot, text) in self.header_widgets.changed() {
+            let (image, (x, y)) = HeaderWidgetRenderer::draw(slot, &text);
+            self.frame.send(&self.sender, image, x, y)?;
+        }
+        Ok(())
+    }
+
+    fn send_channel_image(&self, index: usize, img: impl Into<Tile>, x: u32, y: u32) -> Result<()> {
+        let (ch_w, _) = CHANNEL_DIMENSIONS;
+        let (root_x, root_y) = POSITION_ROOT;
+        let x = ch_w * index as u32 + x + root_x;
+        let y = y + root_y;
+        self.frame.send_now(&self.sender, img, x, y)
+    }
+
+    fn load_button_colours(&self) -> Result<()> {
+        let dials = [
+            ButtonLighting::Dial1,
+            ButtonLighting::Dial2,
+            ButtonLighting::Dial3,
+            ButtonLighting::Dial4,
+        ];
+        for (index, button) in dials.into_iter().enumerate() {
+            let colour = match self.devices_shown.get(index) {
+                Some(id) => {
+                    let colour = self.get_stream(*id)?.colour();
+                    RGBA {
+                        red: colour[0],
+                        green: colour[1],
+                        blue: colour[2],
+                        alpha: colour[3],
+                    }
+                }
+                None => COLOUR_BLACK,
+            };
+            self.set_button_colour(button, colour)?;
+        }
+
+        let pages = self.get_page_count();
+        let left = match self.active_page == 0 {
+            true => COLOUR_BLACK,
+            false => COLOUR_WHITE,
+        };
+        let right = match self.active_page + 1 >= pages {
+            true => COLOUR_BLACK,
+            false => COLOUR_WHITE,
+        };
+        self.set_button_colour(ButtonLighting::Left, left)?;
+        self.set_button_colour(ButtonLighting::Right, right)?;
+
+        // There's no concept of mixes here, so the Mix button stays dark
+        self.set_button_colour(ButtonLighting::Mix, COLOUR_BLACK)?;
+        Ok(())
+    }
+
+    fn set_button_colour(&self, button: ButtonLighting, colour: RGBA) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.sender.send(ButtonColour(button, colour, tx))?;
+        rx.recv()??;
+        Ok(())
+    }
+
+    fn draw_status(&self, text: &str) {
+        let text = DrawingUtils::draw_text(
+            text.into(),
+            800,
+            30,
+            TextStyle::Status,
+            TEXT_COLOUR,
+            TextAlign::Center,
+        );
+
+        if let Ok(img) = img_as_jpeg(text, Rgba([0, 0, 0, 255])) {
+            let _ = self.frame.send_now(&self.sender, img, 0, 330);
+        }
+    }
+
+    // The dial buttons act on release, or when held, depending on the gesture
+    fn on_button_down(&mut self, button: Buttons) -> Result<()> {
+        if GestureDetector::handles(button) {
+            self.gestures.press(button);
+            return Ok(());
+        }
+        self.handle_button(button)
+    }
+
+    fn on_button_up(&mut self, button: Buttons) -> Result<()> {
+        let bindings = *self.gestures_rx.borrow();
+        if let Some(gesture) = self.gestures.release(button, &bindings) {
+            self.handle_gesture(button, gesture)?;
+        }
+        Ok(())
+    }
+
+    fn check_gestures(&mut self) -> Result<()> {
+        let bindings = *self.gestures_rx.borrow();
+        for (button, gesture) in self.gestures.tick(&bindings) {
+            self.handle_gesture(button, gesture)?;
+        }
+        Ok(())
+    }
+
+    fn handle_gesture(&mut self, button: Buttons, gesture: DialGesture) -> Result<()> {
+        let bindings = *self.gestures_rx.borrow();
+        let action = bindings.action(gesture);
+        debug!("{gesture} on {button:?}: {action}");
+
+        let index = match button {
+            Buttons::Dial1 => 0,
+            Buttons::Dial2 => 1,
+            Buttons::Dial3 => 2,
+            Buttons::Dial4 => 3,
+            _ => bail!("Gestures are only available on the dials"),
+        };
+
+        match action {
+            DialAction::None => {}
+            DialAction::Mute => {
+                if let Some(id) = self.devices_shown.get(index) {
+                    self.wpctl.send(WpctlCommand::ToggleMute(*id));
+                }
+            }
+            DialAction::DefaultVolume => self.set_volume(index, bindings.default_volume)?,
+            DialAction::NextPage => self.handle_button(Buttons::PageRight)?,
+            DialAction::PreviousPage => self.handle_button(Buttons::PageLeft)?,
+            DialAction::FirstPage => {
+                if self.active_page != 0 {
+                    self.active_page = 0;
+                    self.refresh_page()?;
+                }
+            }
+
+            // Applications only have the one volume, with no audience mix or outputs to switch to
+            DialAction::MuteAudience | DialAction::SwitchMix | DialAction::SwitchChannels => {
+                debug!("{action} isn't available without Pipeweaver");
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_button(&mut self, button: Buttons) -> Result<()> {
+        match button {
+            Buttons::PageLeft if self.active_page > 0 => {
+                self.active_page -= 1;
+                self.refresh_page()?;
+            }
+            Buttons::PageRight if self.active_page + 1 < self.get_page_count() => {
+                self.active_page += 1;
+                self.refresh_page()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_dial(&mut self, dial: Dials, change: i8) -> Result<()> {
+        session::record_dial(dial, change);
+
+        let (index, button) = match dial {
+            Dials::Dial1 => (0, Buttons::Dial1),
+            Dials::Dial2 => (1, Buttons::Dial2),
+            Dials::Dial3 => (2, Buttons::Dial3),
+            Dials::Dial4 => (3, Buttons::Dial4),
+        };
+
+        // Turning a dial while its button is held is fine mode, rather than a press
+        let acceleration = self.gestures_rx.borrow().acceleration;
+        let fine = acceleration.fine_mode && self.gestures.is_held(button);
+        if fine {
+            self.gestures.cancel(button);
+        }
+        let change = self.accelerator.apply(index, change, &acceleration, fine);
+
+        let Some(id) = self.devices_shown.get(index) else {
+            return Ok(());
+        };
+        let volume = self.get_stream(*id)?.volume as i16 + change;
+        self.set_volume(index, volume.clamp(0, 100) as u8)
+    }
+
+    fn set_volume(&mut self, index: usize, volume: u8) -> Result<()> {
+        let Some(id) = self.devices_shown.get(index).copied() else {
+            return Ok(());
+        };
+
+        // Update our local state immediately, so fast dial turns don't fight the poller
+        let stream = self
+            .streams
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or(anyhow!("Stream not found"))?;
+        stream.volume = volume;
+
+        self.wpctl.send(WpctlCommand::Volume(id, volume));
+
+        if let Some(renderer) = self.renderers.get_mut(&id) {
+            renderer.volumes[Mix::A] = volume;
+            integrations::volume_changed(&renderer.title, volume);
+            if !self.is_suspended() {
+                let img = renderer.get_volume(Mix::A)?;
+                let (x, y) = img.position;
+                self.send_channel_image(index, img.image, x, y)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_suspended(&self) -> bool {
+        *self.suspended_rx.borrow()
+    }
+
+    fn poll_interval(&self) -> time::Interval {
+        match *self.power_saving_rx.borrow() {
+            true => time::interval(POWER_SAVE_POLL_RATE),
+            false => time::interval(POLL_RATE),
+        }
+    }
+}
+
+pub fn spawn_pipewire_handler(
+    sender: Sender<ControlMessage>,
+    device: DeviceType,
+    input_rx: Receiver<Interactions>,
+    stop_rx: watch::Receiver<()>,
+    suspended_rx: watch::Receiver<bool>,
+    handles: DisplayHandles,
+) -> JoinHandle<()> {
+    let mut handler =
+        PipewireHandler::new(device, sender, input_rx, stop_rx, suspended_rx, handles);
+    runtime().spawn(async move { handler.run_handler().await })
+}
@@ -0,0 +1,96 @@
+/*
+  Runs wpctl on a thread of its own, so waiting for it doesn't hold up the display. A dial that's
+  spun quickly asks for a new volume on every detent, and by the time wpctl has finished with one
+  the next few have usually arrived, so only the latest volume for each stream is set.
+*/
+
+use crate::tools::tool_error;
+use anyhow::{Result, bail};
+use beacn_lib::crossbeam::channel::{self, Receiver, Sender};
+use log::{debug, warn};
+use std::process::Command;
+use std::thread;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WpctlCommand {
+    Volume(u32, u8),
+    Mute(u32, bool),
+    ToggleMute(u32),
+}
+
+impl WpctlCommand {
+    fn args(&self) -> Vec<String> {
+        match self {
+            WpctlCommand::Volume(id, volume) => {
+                vec!["set-volume".into(), id.to_string(), format!("{volume}%")]
+            }
+            WpctlCommand::Mute(id, muted) => {
+                let muted = if *muted { "1" } else { "0" };
+                vec!["set-mute".into(), id.to_string(), muted.into()]
+            }
+            WpctlCommand::ToggleMute(id) => {
+                vec!["set-mute".into(), id.to_string(), "toggle".into()]
+            }
+        }
+    }
+
+    // Whether a later command makes this one pointless
+    fn replaced_by(&self, later: &WpctlCommand) -> bool {
+        matches!(
+            (self, later),
+            (WpctlCommand::Volume(id, _), WpctlCommand::Volume(other, _)) if id == other
+        )
+    }
+}
+
+/// Hands commands to the wpctl thread, which stops once this is dropped
+pub struct Wpctl {
+    sender: Sender<WpctlCommand>,
+}
+
+impl Wpctl {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = channel::unbounded();
+        thread::spawn(move || run(receiver));
+        Self { sender }
+    }
+
+    pub fn send(&self, command: WpctlCommand) {
+        if self.sender.send(command).is_err() {
+            warn!("wpctl thread has stopped, unable to run {command:?}");
+        }
+    }
+}
+
+fn run(receiver: Receiver<WpctlCommand>) {
+    while let Ok(first) = receiver.recv() {
+        let mut batch = vec![first];
+        batch.extend(receiver.try_iter());
+
+        for (index, command) in batch.iter().enumerate() {
+            if batch[index + 1..]
+                .iter()
+                .any(|later| command.replaced_by(later))
+            {
+                continue;
+            }
+
+            if let Err(e) = wpctl(&command.args()) {
+                warn!("Unable to run {command:?}: {e}");
+            }
+        }
+    }
+    debug!("wpctl thread stopped");
+}
+
+fn wpctl(args: &[String]) -> Result<()> {
+    debug!("Running wpctl {args:?}");
+    let status = Command::new("wpctl")
+        .args(args)
+        .status()
+        .map_err(tool_error("wpctl"))?;
+    if !status.success() {
+        bail!("wpctl exited with {status}");
+    }
+    Ok(())
+}
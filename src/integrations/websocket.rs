@@ -0,0 +1,294 @@
+/* The parts shared by the localhost WebSocket endpoints (the control surface endpoint and the
+   event stream). Each one only listens while it's enabled, restarts when its settings change, and
+   keeps a status and client count for the UI.
+
+   Browsers don't apply the same origin policy to WebSockets, so without checking the Origin any
+   website open in a browser could connect to these through localhost. Clients which aren't
+   browsers don't send one, and are let through as before.
+*/
+
+use anyhow::Result;
+use futures_util::SinkExt;
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::future::Future;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc as tokio_mpsc;
+use tokio::task::JoinSet;
+use tokio::time;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::accept_hdr_async;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+
+// Settings are changed from the UI, so need to be checked periodically
+const SETTINGS_CHECK: Duration = Duration::from_secs(5);
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+// Hosts a page is allowed to connect from, anything running on this machine could connect
+// without a browser anyway
+const ALLOWED_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ServerStatus {
+    #[default]
+    Disabled,
+    Listening,
+    Failed(String),
+}
+
+/// The settings a server is started with, it restarts whenever they change
+pub trait ServerSettings: Clone + PartialEq + Send + 'static {
+    fn port(&self) -> u16;
+}
+
+pub struct Server {
+    name: &'static str,
+    status: RwLock<ServerStatus>,
+    clients: AtomicUsize,
+}
+
+impl Server {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            status: RwLock::new(ServerStatus::Disabled),
+            clients: AtomicUsize::new(0),
+        }
+    }
+
+    /// The state of the server, for display in the UI
+    pub fn status(&self) -> ServerStatus {
+        self.status.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// How many clients are connected
+    pub fn clients(&self) -> usize {
+        self.clients.load(Ordering::Relaxed)
+    }
+
+    /// Counts a client until the returned value is dropped, which also covers the connection
+    /// being aborted
+    pub fn client(&'static self) -> ClientCount {
+        self.clients.fetch_add(1, Ordering::Relaxed);
+        ClientCount(self)
+    }
+
+    fn set_status(&self, status: ServerStatus) {
+        if let Ok(mut current) = self.status.write() {
+            *current = status;
+        }
+    }
+
+    /// Runs the server on a dedicated runtime until told to stop. `settings` gives the current
+    /// settings, or None while the server should be off, and `handle` is given each connection.
+    pub fn run<S, H, F>(
+        &'static self,
+        stop_rx: tokio_mpsc::Receiver<()>,
+        settings: impl Fn() -> Option<S>,
+        handle: H,
+    ) -> Result<()>
+    where
+        S: ServerSettings,
+        H: Fn(TcpStream, S) -> F,
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
+        debug!("Starting {} Handler with dedicated runtime..", self.name);
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()?;
+
+        rt.block_on(self.run_internal(stop_rx, settings, handle));
+        Ok(())
+    }
+
+    async fn run_internal<S, H, F>(
+        &self,
+        mut stop_rx: tokio_mpsc::Receiver<()>,
+        settings: impl Fn() -> Option<S>,
+        handle: H,
+    ) where
+        S: ServerSettings,
+        H: Fn(TcpStream, S) -> F,
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
+        loop {
+            let mut delay = SETTINGS_CHECK;
+
+            if let Some(current) = settings() {
+                tokio::select! {
+                    result = self.run_server(&current, &settings, &handle) => {
+                        if let Err(e) = result {
+                            warn!("{} Failed: {e}", self.name);
+                            self.set_status(ServerStatus::Failed(e.to_string()));
+                            delay = RETRY_DELAY;
+                        } else {
+                            // The settings changed, so restart straight away
+                            delay = Duration::ZERO;
+                        }
+                    }
+                    _ = stop_rx.recv() => break,
+                }
+            } else {
+                self.set_status(ServerStatus::Disabled);
+            }
+
+            tokio::select! {
+                _ = time::sleep(delay) => {}
+                _ = stop_rx.recv() => break,
+            }
+        }
+
+        debug!("Received stop signal, shutting down {} handler", self.name);
+        self.set_status(ServerStatus::Disabled);
+    }
+
+    /// Listens until the settings change, dropping every connection when they do
+    async fn run_server<S, H, F>(
+        &self,
+        current: &S,
+        settings: &impl Fn() -> Option<S>,
+        handle: &H,
+    ) -> Result<()>
+    where
+        S: ServerSettings,
+        H: Fn(TcpStream, S) -> F,
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, current.port()));
+        let listener = TcpListener::bind(address).await?;
+        info!("{} listening on {address}", self.name);
+        self.set_status(ServerStatus::Listening);
+
+        // Dropped when we return, which aborts every connection
+        let mut connections = JoinSet::new();
+
+        let mut check = time::interval(SETTINGS_CHECK);
+        check.tick().await;
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, peer) = accepted?;
+                    let connection = handle(stream, current.clone());
+                    let name = self.name;
+                    connections.spawn(async move {
+                        if let Err(e) = connection.await {
+                            debug!("{name} client {peer} disconnected: {e}");
+                        }
+                    });
+                }
+                Some(_) = connections.join_next() => {}
+                _ = check.tick() => {
+                    if settings().as_ref() != Some(current) {
+                        debug!("{} Settings Changed, restarting", self.name);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct ClientCount(&'static Server);
+
+impl Drop for ClientCount {
+    fn drop(&mut self) {
+        self.0.clients.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Completes the WebSocket handshake, turning away pages from other websites
+pub async fn accept(stream: TcpStream) -> Result<WebSocketStream<TcpStream>> {
+    let check_origin = |request: &Request, response: Response| {
+        let origin = request.headers().get("origin");
+        let origin = origin.map(|o| o.to_str().unwrap_or_default());
+        if is_allowed_origin(origin) {
+            return Ok(response);
+        }
+
+        debug!(
+            "Refusing WebSocket connection from {}",
+            origin.unwrap_or_default()
+        );
+        let mut error = ErrorResponse::new(Some("Origin not allowed".to_string()));
+        *error.status_mut() = StatusCode::FORBIDDEN;
+        Err(error)
+    };
+    Ok(accept_hdr_async(stream, check_origin).await?)
+}
+
+fn is_allowed_origin(origin: Option<&str>) -> bool {
+    let Some(origin) = origin else {
+        return true;
+    };
+
+    // Pages opened straight from a file. Browsers also send 'null' for sandboxed frames, which
+    // any website can create, so that isn't enough on its own.
+    if origin.starts_with("file://") {
+        return true;
+    }
+
+    let Some(rest) = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    else {
+        return false;
+    };
+    let host = match rest.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next(),
+        None => rest.split(':').next(),
+    };
+    ALLOWED_HOSTS.contains(&host.unwrap_or_default())
+}
+
+pub async fn send(socket: &mut WebSocketStream<TcpStream>, event: &impl Serialize) -> Result<()> {
+    let json = serde_json::to_string(event)?;
+    socket.send(Message::text(json)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clients_without_an_origin_are_allowed() {
+        assert!(is_allowed_origin(None));
+    }
+
+    #[test]
+    fn local_pages_are_allowed() {
+        for origin in [
+            "http://localhost:4461",
+            "http://127.0.0.1:4461",
+            "http://[::1]:4461",
+            "https://localhost",
+            "file://",
+        ] {
+            assert!(is_allowed_origin(Some(origin)), "{origin} was refused");
+        }
+    }
+
+    #[test]
+    fn other_origins_are_refused() {
+        for origin in [
+            "null",
+            "",
+            "https://example.com",
+            "http://localhost.example.com",
+            "http://127.0.0.1.example.com:4461",
+            "http://absolute",
+            "chrome-extension://abcdef",
+        ] {
+            assert!(!is_allowed_origin(Some(origin)), "{origin} was allowed");
+        }
+    }
+}
@@ -0,0 +1,138 @@
+/*
+  Keeps the most recent log lines in memory, alongside the console and file loggers, so they can
+  be shown in the app without needing to go and find the log file. Only our own logs (and those
+  from beacn-lib) are kept, everything else is left to the file.
+*/
+
+use chrono::Local;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use simplelog::{Config, SharedLogger};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use strum_macros::EnumIter;
+
+const CAPACITY: usize = 2000;
+
+static LINES: LazyLock<Mutex<VecDeque<LogLine>>> = LazyLock::new(Default::default);
+
+// Bumped for every line, so the viewer can tell when there's something new to show
+static WRITTEN: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumIter)]
+pub enum LogSubsystem {
+    DeviceManager,
+    Pipeweaver,
+    Ipc,
+    Other,
+}
+
+impl LogSubsystem {
+    // Works out where a line came from by the module which logged it
+    fn from_target(target: &str) -> Self {
+        let module = target.strip_prefix("beacn_utility::").unwrap_or(target);
+        let starts = |prefixes: &[&str]| prefixes.iter().any(|p| module.starts_with(p));
+
+        if starts(&[
+            "beacn_lib",
+            "device_manager",
+            "devices",
+            "diagnostics",
+            "supervisor",
+        ]) {
+            LogSubsystem::DeviceManager
+        } else if starts(&["integrations::pipeweaver", "integrations::pipewire"]) {
+            LogSubsystem::Pipeweaver
+        } else if starts(&[
+            "managers::ipc",
+            "integrations::companion",
+            "integrations::events",
+        ]) {
+            LogSubsystem::Ipc
+        } else {
+            LogSubsystem::Other
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub time: String,
+    pub level: Level,
+    pub subsystem: LogSubsystem,
+    pub message: String,
+}
+
+impl LogLine {
+    /// The line as it would appear in the log file
+    pub fn to_text(&self) -> String {
+        format!("{} [{}] {}", self.time, self.level, self.message)
+    }
+}
+
+/// Every line currently held which matches the filter
+pub fn lines(filter: impl Fn(&LogLine) -> bool) -> Vec<LogLine> {
+    match LINES.lock() {
+        Ok(lines) => lines.iter().filter(|l| filter(l)).cloned().collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Goes up every time a line is logged
+pub fn written() -> u64 {
+    WRITTEN.load(Ordering::Relaxed)
+}
+
+pub struct BufferLogger {
+    level: LevelFilter,
+    config: Config,
+}
+
+impl BufferLogger {
+    pub fn new(level: LevelFilter, config: Config) -> Box<Self> {
+        Box::new(Self { level, config })
+    }
+}
+
+impl Log for BufferLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level && metadata.target().starts_with("beacn")
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = LogLine {
+            time: Local::now().format("%H:%M:%S%.3f").to_string(),
+            level: record.level(),
+            subsystem: LogSubsystem::from_target(record.target()),
+            message: record.args().to_string(),
+        };
+
+        if let Ok(mut lines) = LINES.lock() {
+            if lines.len() == CAPACITY {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+        WRITTEN.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn flush(&self) {}
+}
+
+impl SharedLogger for BufferLogger {
+    fn level(&self) -> LevelFilter {
+        self.level
+    }
+
+    fn config(&self) -> Option<&Config> {
+        Some(&self.config)
+    }
+
+    fn as_log(self: Box<Self>) -> Box<dyn Log> {
+        Box::new(*self)
+    }
+}
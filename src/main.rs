@@ -1,7 +1,16 @@
 use crate::device_manager::spawn_device_manager;
-use crate::managers::ipc::{handle_active_instance, handle_ipc};
+use crate::integrations::companion::{self, spawn_companion_handler};
+use crate::integrations::events::spawn_event_stream_handler;
+use crate::integrations::obs::spawn_obs_handler;
+use crate::managers::ipc::{
+    handle_active_instance, handle_ipc, send_panic_mute, send_stream_timer, send_suppressor_profile,
+};
+use crate::managers::schedule::spawn_schedule_handler;
+use crate::managers::shortcuts::spawn_shortcuts_handler;
+use crate::supervisor::Supervisor;
 use crate::ui::app::BeacnMicApp;
-use crate::window_handle::{App, UserEvent, WindowRunner, send_user_event};
+use crate::ui::theme::watch_system_scheme;
+use crate::window_handle::{App, UserEvent, WindowRunner, send_user_event, window_visible};
 use anyhow::Result;
 use anyhow::bail;
 use beacn_lib::crossbeam::channel::unbounded;
@@ -21,16 +30,33 @@ use signal_hook::iterator::Signals;
 use simplelog::{
     ColorChoice, CombinedLogger, ConfigBuilder, SharedLogger, TermLogger, TerminalMode, WriteLogger,
 };
+use std::panic::{AssertUnwindSafe, catch_unwind};
 use std::path::PathBuf;
+use std::process::Command;
 use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 use std::{env, thread};
 use tokio::runtime::{Builder, Runtime};
 use xdg::BaseDirectories;
 
 mod device_manager;
+mod devices;
+mod diagnostics;
+mod hooks;
+mod i18n;
 mod integrations;
+mod log_buffer;
 mod managers;
+mod panic_mute;
+mod recording;
+mod session;
+mod settings;
+mod stats;
+mod storage;
+mod supervisor;
+mod support;
+mod tools;
 mod ui;
 mod window_handle;
 
@@ -39,6 +65,16 @@ const HASH: &str = env!("GIT_HASH");
 
 const BACKGROUND_PARAM: &str = "--background";
 const LEGACY_BACKGROUND_PARAM: &str = "--startup";
+const SUPPRESSOR_PROFILE_PARAM: &str = "--suppressor-profile";
+const STREAM_TIMER_PARAM: &str = "--stream-timer";
+const PANIC_MUTE_PARAM: &str = "--panic-mute";
+const SAFE_MODE_PARAM: &str = "--safe-mode";
+const DEMO_PARAM: &str = "--demo";
+
+// How many times we'll try to bring the UI back, and how long it needs to stay up before we
+// consider it recovered
+const UI_RECOVERY_ATTEMPTS: u32 = 3;
+const UI_RECOVERY_PERIOD: Duration = Duration::from_secs(30);
 
 const APP_TLD: &str = "io.github.beacn_on_linux";
 const APP_NAME: &str = "beacn-utility";
@@ -50,6 +86,18 @@ static TOKIO_RUNTIME: OnceLock<Runtime> = OnceLock::new();
 pub fn runtime() -> &'static Runtime {
     TOKIO_RUNTIME.get_or_init(|| Builder::new_multi_thread().enable_all().build().unwrap())
 }
+// Set by --safe-mode, this skips anything which might be stopping the utility from starting
+static SAFE_MODE: AtomicBool = AtomicBool::new(false);
+pub fn safe_mode() -> bool {
+    SAFE_MODE.load(Ordering::Relaxed)
+}
+
+// Set by --demo, this shows simulated devices in place of real ones
+static DEMO_MODE: AtomicBool = AtomicBool::new(false);
+pub fn demo_mode() -> bool {
+    DEMO_MODE.load(Ordering::Relaxed)
+}
+
 pub fn run_async_blocking<F: Future>(future: F) -> F::Output {
     runtime().block_on(future)
 }
@@ -98,6 +146,12 @@ fn main() -> Result<()> {
         ));
     }
 
+    // Keep the latest lines around for the log viewer
+    log_targets.push(log_buffer::BufferLogger::new(
+        LevelFilter::Debug,
+        config.build(),
+    ));
+
     CombinedLogger::init(log_targets)?;
 
     info!("Starting {} v{} - {}", APP_NAME, VERSION, HASH);
@@ -112,6 +166,51 @@ fn main() -> Result<()> {
     // Firstly, create a message bus which allows threads to message back to here
     let (main_tx, main_rx) = channel::unbounded();
 
+    // Switching suppressor profiles is handled by the running instance, so it can be bound to a
+    // hotkey in the desktop environment
+    if let Some(index) = args.iter().position(|arg| arg == SUPPRESSOR_PROFILE_PARAM) {
+        let Some(name) = args.get(index + 1) else {
+            bail!("{SUPPRESSOR_PROFILE_PARAM} requires a profile name");
+        };
+        if !send_suppressor_profile(name) {
+            bail!("{APP_TITLE} is not running");
+        }
+        return Ok(());
+    }
+
+    // Likewise for the stream timer shown on the Mix display
+    if let Some(index) = args.iter().position(|arg| arg == STREAM_TIMER_PARAM) {
+        let Some(action) = args.get(index + 1) else {
+            bail!("{STREAM_TIMER_PARAM} requires an action (start, stop, toggle or reset)");
+        };
+        if !send_stream_timer(action) {
+            bail!("{APP_TITLE} is not running");
+        }
+        return Ok(());
+    }
+
+    // And muting everything, for desktops without the global shortcuts portal
+    if args.contains(&PANIC_MUTE_PARAM.to_string()) {
+        if !send_panic_mute() {
+            bail!("{APP_TITLE} is not running");
+        }
+        return Ok(());
+    }
+
+    // Safe mode starts without Pipeweaver, the OBS / Companion integrations or the image caches,
+    // and ignores the UI scale, so a bad setting or broken cache file can be fixed from the UI
+    if args.contains(&SAFE_MODE_PARAM.to_string()) {
+        info!("Starting in Safe Mode");
+        SAFE_MODE.store(true, Ordering::Relaxed);
+    }
+
+    // Demo mode fills the UI with one of each device, for screenshots and trying things out
+    // without any hardware
+    if args.contains(&DEMO_PARAM.to_string()) {
+        info!("Starting in Demo Mode");
+        DEMO_MODE.store(true, Ordering::Relaxed);
+    }
+
     // Check whether an existing instance is running, and bail if so
     if handle_active_instance() {
         return Ok(());
@@ -127,20 +226,62 @@ fn main() -> Result<()> {
         }
     });
 
+    // The IPC and Tray handlers are restarted if they panic
+    let mut supervisor = Supervisor::default();
+
     // Spawn up the IPC handler
     let (ipc_tx, ipc_rx) = channel::unbounded();
     let ipc_main_tx = main_tx.clone();
-    let ipc = thread::spawn(|| handle_ipc(ipc_rx, ipc_main_tx));
+    supervisor.spawn("IPC", move || {
+        let _ = handle_ipc(ipc_rx.clone(), ipc_main_tx.clone());
+    });
 
     // Ok, spawn up the Tray Handler
     let (tray_tx, tray_rx) = channel::unbounded();
     let tray_main_tx = main_tx.clone();
-    let tray = thread::spawn(|| {
-        if let Err(e) = handle_tray(tray_rx, tray_main_tx) {
+    supervisor.spawn("Tray", move || {
+        if let Err(e) = handle_tray(tray_rx.clone(), tray_main_tx.clone()) {
             error!("Failed to Spawn Tray: {e}");
         }
     });
 
+    // Global shortcuts go through the portal, which may not be available
+    let (shortcuts_stop_tx, shortcuts_stop_rx) = tokio::sync::mpsc::channel(1);
+    let shortcuts_main_tx = main_tx.clone();
+    thread::spawn(|| spawn_shortcuts_handler(shortcuts_main_tx, shortcuts_stop_rx));
+
+    // The session starts when the app does, if stats are being collected
+    session::set_enabled(settings::get().session_stats);
+
+    // Switches suppressor profiles at the times the user has set
+    let (schedule_stop_tx, schedule_stop_rx) = channel::bounded(1);
+    let schedule_main_tx = main_tx.clone();
+    thread::spawn(|| spawn_schedule_handler(schedule_main_tx, schedule_stop_rx));
+
+    // OBS is only connected to if it's been turned on in the settings, but the handler always
+    // runs so it can pick that up
+    let (obs_stop_tx, obs_stop_rx) = tokio::sync::mpsc::channel(1);
+    let obs_main_tx = main_tx.clone();
+    if !safe_mode() {
+        thread::spawn(|| spawn_obs_handler(obs_main_tx, obs_stop_rx));
+    }
+
+    // Likewise for the endpoint control surfaces connect to, which is off by default
+    let (companion_stop_tx, companion_stop_rx) = tokio::sync::mpsc::channel(1);
+    let companion_main_tx = main_tx.clone();
+    if !safe_mode() {
+        thread::spawn(|| spawn_companion_handler(companion_main_tx, companion_stop_rx));
+    }
+
+    // And the read-only event stream for overlays
+    let (events_stop_tx, events_stop_rx) = tokio::sync::mpsc::channel(1);
+    if !safe_mode() {
+        thread::spawn(|| spawn_event_stream_handler(events_stop_rx));
+    }
+
+    // The desktop's dark / light preference, so the UI can follow it
+    runtime().spawn(watch_system_scheme(main_tx.clone()));
+
     // Ok, we need to spawn up the device manager, first lets create some channels
     // The first channel is for us to be able to tell the manager to shut down, or reconfigure
     let (manage_tx, manage_rx) = channel::unbounded();
@@ -164,8 +305,12 @@ fn main() -> Result<()> {
     // Ok, spawn up the thread responsible for the UI
     let device_rx_inner = device_rx.clone();
     let window_main_tx = main_tx.clone();
+    let window_manage_tx = manage_tx.clone();
     let window = thread::spawn(move || {
-        let mut app: Box<dyn App> = Box::new(BeacnMicApp::new(device_rx_inner));
+        let mut app: Box<dyn App> = Box::new(BeacnMicApp::new(
+            device_rx_inner.clone(),
+            window_main_tx.clone(),
+        ));
         let mut hide_initial = hide_initial;
 
         // This is used for trying to respawn the window on error
@@ -181,42 +326,66 @@ fn main() -> Result<()> {
             // Create the Window Runner
             let runner = WindowRunner::new(app, window_main_tx.clone(), window_attributes.clone());
 
-            // Run and check for return
-            match runner.run(&mut event_loop, hide_initial) {
-                Ok(()) => break,
-                Err((recovered_app, was_hidden, e)) => {
+            // Run and check for return, a panic in here shouldn't take the devices down with it
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                runner.run(&mut event_loop, hide_initial)
+            }));
+            match result {
+                Ok(Ok(())) => break,
+                Ok(Err((recovered_app, was_hidden, e))) => {
                     error!("UI has Crashed: {e}");
-
-                    // Something crashed it, could be wayland, or X11, either way, we're lost.
-                    // Check the last time this happened (have we successfully respawned before?)
-                    if last_error.elapsed() < Duration::from_secs(5) {
-                        attempts = 0;
-                    }
-
-                    // Refresh the last error time, increment the attempt account
-                    last_error = Instant::now();
-                    attempts += 1;
-
-                    // Yea, there's nothing we can do here, we're just going to have to bail.
-                    // TODO: This should probably quit the app
-                    if attempts > 3 {
-                        error!("Failed to recover UI after {attempts} attempts, bailing");
-                        break;
-                    }
-
                     app = recovered_app;
                     hide_initial = was_hidden;
-                    thread::sleep(Duration::from_millis(500));
                 }
+                Err(_) => {
+                    // The panic handler has already logged this, but the app's state went with
+                    // it, so start fresh and have the device manager tell us about the devices
+                    error!("UI has Panicked, rebuilding");
+                    app = Box::new(BeacnMicApp::new(
+                        device_rx_inner.clone(),
+                        window_main_tx.clone(),
+                    ));
+                    let _ = window_manage_tx.send(ManagerMessages::AnnounceDevices);
+                }
+            }
+
+            // Something crashed it, could be wayland, or X11. If it's been a while since the
+            // last crash, we assume the previous recovery worked and start counting again.
+            if last_error.elapsed() > UI_RECOVERY_PERIOD {
+                attempts = 0;
+            }
+
+            // Refresh the last error time, increment the attempt account
+            last_error = Instant::now();
+            attempts += 1;
+
+            // We can't create a new event loop in this process, so if it's not coming back,
+            // carry on without a window. The devices keep running, and main will hand over to
+            // a new instance if the window is requested again.
+            if attempts > UI_RECOVERY_ATTEMPTS {
+                error!("Failed to recover UI after {attempts} attempts, running without a window");
+                let _ = window_main_tx.send(ToMainMessages::WindowLost);
+                break;
             }
+
+            thread::sleep(Duration::from_millis(500));
         }
     });
 
     // Wait for a message to do stuff
     debug!("Running Message Handler...");
     let mut context = Context::default();
+    let mut window_lost = false;
+    let mut restart = false;
+    let supervise = channel::tick(supervisor::CHECK_INTERVAL);
     loop {
         select! {
+            recv(supervise) -> _ => {
+                if supervisor.check() && window_visible() {
+                    // Let the UI know something's gone for good
+                    send_user_event(&context, UserEvent::RequestRedraw);
+                }
+            }
             recv(main_rx) -> msg => {
                 match msg {
                     Ok(msg) => {
@@ -227,12 +396,55 @@ fn main() -> Result<()> {
                                 context = new_ctx;
                             }
                             ToMainMessages::SpawnWindow => {
+                                if window_lost {
+                                    // There's no event loop left to open a window on, so shut
+                                    // down cleanly and let a new instance take over.
+                                    info!("Window requested after the UI was lost, restarting");
+                                    restart = true;
+                                    break;
+                                }
+
                                 // Window Re-Open requested
                                 send_user_event(&context, UserEvent::FocusWindow);
                             }
+                            ToMainMessages::WindowLost => {
+                                window_lost = true;
+                            }
+                            ToMainMessages::TrayDevices(devices) => {
+                                companion::publish_devices(devices.clone());
+                                let _ = tray_tx.send(ManagerMessages::TrayDevices(devices));
+                            }
+                            ToMainMessages::DeviceHealth(health) => {
+                                let _ = tray_tx.send(ManagerMessages::DeviceHealth(health));
+                            }
+                            ToMainMessages::LanguageChanged => {
+                                let _ = tray_tx.send(ManagerMessages::LanguageChanged);
+                            }
+                            ToMainMessages::ApplySuppressorProfile(serial, name) => {
+                                let event = UserEvent::SuppressorProfile(serial, name);
+                                send_user_event(&context, event);
+                            }
+                            ToMainMessages::ApplyLightingMode(serial, name) => {
+                                let event = UserEvent::LightingMode(serial, name);
+                                send_user_event(&context, event);
+                            }
+                            ToMainMessages::TogglePanicMute => {
+                                let active = panic_mute::toggle();
+                                let _ = tray_tx.send(ManagerMessages::PanicMute(active));
+                            }
+                            ToMainMessages::SetPanicMute(active) => {
+                                panic_mute::set(active);
+                                let _ = tray_tx.send(ManagerMessages::PanicMute(active));
+                            }
+                            ToMainMessages::SetMicColour(colour) => {
+                                send_user_event(&context, UserEvent::MicColour(colour));
+                            }
                             ToMainMessages::RequestRedraw => {
-                                // Repaint requested
-                                send_user_event(&context, UserEvent::RequestRedraw);
+                                // Repaint requested, nothing to do while closed to the tray,
+                                // the window draws everything fresh when it's reopened
+                                if window_visible() {
+                                    send_user_event(&context, UserEvent::RequestRedraw);
+                                }
                             }
                             ToMainMessages::Quit => {
                                 // Break out and Close
@@ -285,14 +497,27 @@ fn main() -> Result<()> {
     let _ = manage_tx.send(ManagerMessages::Quit);
     let _ = ipc_tx.send(ManagerMessages::Quit);
     let _ = tray_tx.send(ManagerMessages::Quit);
+    let _ = shortcuts_stop_tx.try_send(());
+    let _ = schedule_stop_tx.try_send(());
+    let _ = obs_stop_tx.try_send(());
+    let _ = companion_stop_tx.try_send(());
+    let _ = events_stop_tx.try_send(());
 
     let _ = window.join();
-    let _ = tray.join();
     let _ = device_manager.join();
-    let _ = ipc.join();
+    supervisor.join();
 
     debug!("Shutdown Complete");
 
+    if restart {
+        // Drop the background flag, the whole point of this is to show the window
+        let args = args
+            .iter()
+            .skip(1)
+            .filter(|arg| *arg != BACKGROUND_PARAM && *arg != LEGACY_BACKGROUND_PARAM);
+        Command::new(env::current_exe()?).args(args).spawn()?;
+    }
+
     Ok(())
 }
 
@@ -365,11 +590,57 @@ pub fn get_autostart_file() -> Result<PathBuf> {
 // trigger events on the UI
 pub enum ManagerMessages {
     Quit,
+
+    // Sent to the device manager when the UI has been rebuilt, and needs the devices again
+    AnnounceDevices,
+
+    // Sent to the tray when the profiles or lighting modes it offers change
+    TrayDevices(TrayDeviceList),
+
+    // Sent to the tray when devices connect, disconnect, or go into (or out of) an error
+    DeviceHealth(DeviceHealth),
+
+    // Sent to the tray when the UI language has been changed
+    LanguageChanged,
+
+    // Sent to the tray when everything is muted, or unmuted
+    PanicMute(bool),
+}
+
+// What the tray can switch on each device, so it doesn't need the window open
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TrayDevice {
+    pub serial: String,
+
+    // The names of the stored suppressor profiles
+    pub suppressor_profiles: Vec<String>,
+
+    // The lighting modes available, and whether each is the one in use
+    pub lighting_modes: Vec<(String, bool)>,
+}
+pub type TrayDeviceList = Vec<TrayDevice>;
+
+// How the connected devices are doing overall, so the tray can show when something's wrong
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeviceHealth {
+    pub connected: usize,
+
+    // The serials of devices which failed to open, or have stopped responding
+    pub errors: Vec<String>,
 }
 
 pub enum ToMainMessages {
     SpawnWindow,
     RequestRedraw,
     UpdateContext(Context),
+    WindowLost,
+    TrayDevices(TrayDeviceList),
+    DeviceHealth(DeviceHealth),
+    ApplySuppressorProfile(Option<String>, String),
+    ApplyLightingMode(String, String),
+    TogglePanicMute,
+    SetPanicMute(bool),
+    SetMicColour([u8; 3]),
+    LanguageChanged,
     Quit,
 }
@@ -0,0 +1,210 @@
+use crate::managers::ipc::get_socket_file_path;
+use crate::managers::ipc::protocol::{Command, Request, Response, read_frame, write_frame};
+use anyhow::{Result, bail};
+use log::{debug, warn};
+use std::fs;
+use std::io::Write;
+use std::net::Shutdown;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+#[cfg(windows)]
+use uds_windows::UnixStream;
+
+// How long to wait for the running instance to answer
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A connection to an already running instance
+pub struct IpcClient {
+    stream: UnixStream,
+
+    // What the running instance said about itself, or None if it's an older version which
+    // doesn't speak the protocol, and only knows how to show its window
+    server: Option<ServerInfo>,
+}
+
+struct ServerInfo {
+    app_version: String,
+    capabilities: Vec<String>,
+}
+
+impl IpcClient {
+    /// Connects to the running instance and finds out what it supports, returning None if there
+    /// isn't one
+    pub fn connect() -> Option<Self> {
+        let socket_path = get_socket_file_path();
+        debug!("Looking for Socket at {socket_path:?}");
+
+        if !socket_path.exists() {
+            debug!("Existing socket is not present");
+            // The socket file doesn't exist, so the socket can't exist.
+            return None;
+        }
+
+        debug!("Attempting to Connect to Existing Socket");
+        // The socket exists, let's see if we can connect to it
+        let mut stream = match UnixStream::connect(&socket_path) {
+            Ok(stream) => stream,
+            Err(e) => {
+                debug!("Failed to Connect to Socket: {e}");
+                debug!("Removing Stale Socket File");
+                let _ = fs::remove_file(socket_path);
+                return None;
+            }
+        };
+
+        // Something is listening, so there's an instance running whether or not it says Hello
+        debug!("Connected to Existing Socket at {socket_path:?}");
+        let server = match Self::read_hello(&mut stream) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                debug!("No Hello from the running instance, assuming an older version: {e}");
+                None
+            }
+        };
+        Some(Self { stream, server })
+    }
+
+    fn read_hello(stream: &mut UnixStream) -> Result<ServerInfo> {
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+
+        match read_frame(stream)? {
+            Some(Response::Hello {
+                version,
+                app_version,
+                capabilities,
+            }) => {
+                debug!("Running instance is v{app_version} (protocol version {version})");
+                Ok(ServerInfo {
+                    app_version,
+                    capabilities,
+                })
+            }
+            Some(response) => bail!("Unexpected response: {response:?}"),
+            None => bail!("Connection closed"),
+        }
+    }
+
+    /// Whether the running instance understands this command
+    pub fn supports(&self, command: &Command) -> bool {
+        match &self.server {
+            Some(server) => server.capabilities.iter().any(|c| c == command.name()),
+
+            // Versions from before the protocol only ever understood 'TRIGGER'
+            None => matches!(command, Command::ShowWindow),
+        }
+    }
+
+    /// Sends a command, and waits for the running instance to say whether it worked. Older
+    /// versions don't reply, so for them it's only sent.
+    pub fn send(&mut self, command: Command) -> Result<()> {
+        let Some(server) = &self.server else {
+            // These read everything up to the end of the connection as a single message, and
+            // stop listening altogether if it isn't 'TRIGGER' (even if nothing was sent). So
+            // that's sent either way, and anything else is reported as unsupported.
+            let supported = self.supports(&command);
+            let legacy = Command::ShowWindow.to_legacy();
+            self.stream.write_all(legacy.as_bytes())?;
+            self.stream.shutdown(Shutdown::Write)?;
+
+            if !supported {
+                bail!(
+                    "Running instance is too old to support '{}', update or restart it",
+                    command.name()
+                );
+            }
+            debug!("Sent '{legacy}' to the running instance as a legacy message");
+            return Ok(());
+        };
+
+        if !self.supports(&command) {
+            bail!(
+                "Running instance (v{}) doesn't support '{}'",
+                server.app_version,
+                command.name()
+            );
+        }
+
+        write_frame(&mut self.stream, &Request::Command(command))?;
+        match read_frame(&mut self.stream)? {
+            Some(Response::Ok) => Ok(()),
+            Some(Response::Error { message }) => bail!("{message}"),
+            Some(response) => bail!("Unexpected response: {response:?}"),
+            None => bail!("Connection closed"),
+        }
+    }
+}
+
+pub fn handle_active_instance() -> bool {
+    send_to_active_instance(Command::ShowWindow)
+}
+
+/// Asks the running instance to switch every device with this suppressor profile over to it
+pub fn send_suppressor_profile(name: &str) -> bool {
+    send_to_active_instance(Command::SuppressorProfile {
+        name: name.to_string(),
+    })
+}
+
+/// Asks the running instance to start, stop or reset the stream timer
+pub fn send_stream_timer(action: &str) -> bool {
+    send_to_active_instance(Command::StreamTimer {
+        action: action.to_string(),
+    })
+}
+
+/// Asks the running instance to mute (or unmute) everything
+pub fn send_panic_mute() -> bool {
+    send_to_active_instance(Command::PanicMute)
+}
+
+/// Returns true if an instance is running, even if it couldn't carry out the command
+fn send_to_active_instance(command: Command) -> bool {
+    let Some(mut client) = IpcClient::connect() else {
+        return false;
+    };
+
+    let name = command.name();
+    if let Err(e) = client.send(command) {
+        warn!("Running instance failed to handle '{name}': {e}");
+    }
+    true
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn client(server: Option<ServerInfo>) -> IpcClient {
+        let (stream, _) = UnixStream::pair().unwrap();
+        IpcClient { stream, server }
+    }
+
+    fn profile() -> Command {
+        Command::SuppressorProfile {
+            name: String::from("Office"),
+        }
+    }
+
+    #[test]
+    fn legacy_servers_only_support_showing_the_window() {
+        let client = client(None);
+        assert!(client.supports(&Command::ShowWindow));
+        assert!(!client.supports(&Command::PanicMute));
+        assert!(!client.supports(&profile()));
+        assert!(!client.supports(&Command::StreamTimer {
+            action: String::from("start")
+        }));
+    }
+
+    #[test]
+    fn servers_support_what_they_list() {
+        let client = client(Some(ServerInfo {
+            app_version: String::from("0.2.5"),
+            capabilities: vec![String::from("show_window"), String::from("panic_mute")],
+        }));
+        assert!(client.supports(&Command::ShowWindow));
+        assert!(client.supports(&Command::PanicMute));
+        assert!(!client.supports(&profile()));
+    }
+}
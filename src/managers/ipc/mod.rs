@@ -0,0 +1,202 @@
+use crate::integrations::header_widgets::{TimerAction, timer_action};
+use crate::managers::ipc::protocol::{
+    Command, Opening, PROTOCOL_VERSION, Request, Response, read_frame, read_opening, write_frame,
+};
+use crate::{APP_NAME, ManagerMessages, ToMainMessages, VERSION};
+use anyhow::{Result, bail};
+use beacn_lib::crossbeam::channel::{Receiver, Sender};
+use beacn_lib::crossbeam::select;
+use directories::BaseDirs;
+use log::{debug, warn};
+use std::io::ErrorKind;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs, path::PathBuf, thread};
+#[cfg(windows)]
+use uds_windows::{UnixListener, UnixStream};
+
+pub mod client;
+pub mod protocol;
+
+pub use client::{
+    handle_active_instance, send_panic_mute, send_stream_timer, send_suppressor_profile,
+};
+
+// How long a new client has to send its first message before it's dropped. Once it's spoken it
+// can stay connected, and quiet, for as long as it likes.
+const OPENING_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub fn handle_ipc(
+    manager_rx: Receiver<ManagerMessages>,
+    main_tx: Sender<ToMainMessages>,
+) -> Result<()> {
+    debug!("Spawning IPC Socket");
+
+    let socket_path = get_socket_file_path();
+    if let Some(parent) = socket_path.parent()
+        && let Err(e) = fs::create_dir_all(parent)
+    {
+        warn!("Failed to create socket directory {parent:?}: {e}");
+        bail!("Failed to Open IPC Socket");
+    }
+
+    if socket_path.exists() {
+        let _ = fs::remove_file(&socket_path);
+    }
+
+    let listener = match UnixListener::bind(&socket_path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Failed to bind to socket: {e}");
+            bail!("Failed to bind to socket: {e}");
+        }
+    };
+
+    if let Err(e) = listener.set_nonblocking(true) {
+        warn!("Failed to set socket non-blocking: {e}");
+        bail!("Failed to set socket non-blocking: {e}");
+    }
+
+    let poll_duration = Duration::from_millis(50);
+
+    debug!("IPC listener started at {socket_path:?}");
+    loop {
+        select! {
+            recv(manager_rx) -> msg => {
+                match msg {
+                    Ok(msg) => {
+                        match msg {
+                            ManagerMessages::Quit => break,
+                            ManagerMessages::AnnounceDevices => {}
+                            ManagerMessages::TrayDevices(_) => {}
+                            ManagerMessages::DeviceHealth(_) => {}
+                            ManagerMessages::LanguageChanged => {}
+                            ManagerMessages::PanicMute(_) => {}
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Message Handler channel Broken, bailing: {e}");
+                        break;
+                    }
+                }
+            }
+
+            default(poll_duration) => {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        // Clients like Stream Deck plugins stay connected, so each gets its own
+                        // thread rather than holding up the rest (and a broken one shouldn't take
+                        // the socket down with it)
+                        let main_tx = main_tx.clone();
+                        thread::spawn(move || {
+                            if let Err(e) = handle_client(stream, &main_tx) {
+                                warn!("Failed to handle IPC client: {e}");
+                            }
+                        });
+                    }
+                    Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                        // No client, do nothing
+                    }
+                    Err(e) => {
+                        warn!("Unexpected socket error: {e}");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = fs::remove_file(&socket_path);
+    debug!("IPC Socket closed");
+    Ok(())
+}
+
+fn handle_client(mut stream: UnixStream, main_tx: &Sender<ToMainMessages>) -> Result<()> {
+    stream.set_nonblocking(false)?;
+
+    // Introduce ourselves straight away, which is how clients tell us apart from an older
+    // version. A legacy client may have already written its message and gone, which is fine.
+    let _ = write_frame(&mut stream, &hello());
+
+    stream.set_read_timeout(Some(OPENING_TIMEOUT))?;
+    let request = match read_opening(&mut stream)? {
+        Opening::Frame(request) => request,
+        Opening::Legacy(message) => {
+            match Command::from_legacy(&message) {
+                Some(command) => {
+                    if let Err(e) = dispatch(command, main_tx) {
+                        debug!("Failed to handle legacy message {message}: {e}");
+                    }
+                }
+                None => debug!("Unknown Message, ignoring: {message}"),
+            }
+            return Ok(());
+        }
+        Opening::Closed => return Ok(()),
+    };
+    stream.set_read_timeout(None)?;
+
+    let mut next = Some(request);
+    while let Some(request) = next {
+        let response = match request {
+            Request::Hello { version, client } => {
+                debug!("IPC Hello from {client} (protocol version {version})");
+                hello()
+            }
+            Request::Command(command) => match dispatch(command, main_tx) {
+                Ok(()) => Response::Ok,
+                Err(e) => Response::Error {
+                    message: e.to_string(),
+                },
+            },
+        };
+        write_frame(&mut stream, &response)?;
+        next = read_frame(&mut stream)?;
+    }
+    Ok(())
+}
+
+fn hello() -> Response {
+    Response::Hello {
+        version: PROTOCOL_VERSION,
+        app_version: VERSION.to_string(),
+        capabilities: Command::capabilities(),
+    }
+}
+
+fn dispatch(command: Command, main_tx: &Sender<ToMainMessages>) -> Result<()> {
+    let message = match command {
+        Command::ShowWindow => ToMainMessages::SpawnWindow,
+        Command::PanicMute => ToMainMessages::TogglePanicMute,
+        Command::SuppressorProfile { name } => ToMainMessages::ApplySuppressorProfile(None, name),
+        Command::StreamTimer { action } => {
+            let Ok(action) = TimerAction::from_str(&action) else {
+                bail!("Unknown Stream Timer action: {action}");
+            };
+            timer_action(action);
+            return Ok(());
+        }
+    };
+    main_tx.send(message)?;
+    Ok(())
+}
+
+pub(crate) fn get_socket_file_path() -> PathBuf {
+    let base_path = BaseDirs::new()
+        .and_then(|base| base.runtime_dir().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| {
+            let tmp_dir = env::temp_dir();
+            if !tmp_dir.exists() {
+                let _ = fs::create_dir_all(&tmp_dir);
+            }
+            tmp_dir
+        });
+
+    base_path.join(APP_NAME).join(get_socket_file_name())
+}
+
+fn get_socket_file_name() -> String {
+    format!("{APP_NAME}.socket")
+}
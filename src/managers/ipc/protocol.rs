@@ -0,0 +1,186 @@
+/*
+  The protocol spoken over the IPC socket, used by the command line and by anything else which
+  wants to control a running instance (such as a Stream Deck plugin).
+
+  Every message is a JSON object, sent as a 4 byte big-endian length followed by that many bytes
+  of JSON. As soon as a client connects, the server sends a Hello with its protocol version and
+  the commands it understands, so a client can check before sending something an older version
+  of the app won't support. After that, each Command gets exactly one Response. A client may also
+  send a Hello naming itself, which is answered with the same Hello.
+
+  For example, a client switching suppressor profile would send:
+    {"type":"hello","version":1,"client":"my-plugin"}
+    {"type":"command","command":"suppressor_profile","name":"Office"}
+
+  Older versions wrote a bare string (such as 'TRIGGER') and closed the socket. These are still
+  accepted, and are told apart from a framed message by their first four bytes being far too
+  large to be a length. Servers from before the protocol never send a Hello, and only understand
+  'TRIGGER' (show the window). Anything else, even closing without sending anything, stops them
+  listening for good, so a client which doesn't get a Hello always sends 'TRIGGER', and reports
+  any other command as unsupported.
+*/
+
+use anyhow::{Result, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Read, Write};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, IntoStaticStr};
+
+/// Bumped whenever a change would break an existing client
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// Nothing we send comes close to this, anything larger is either a legacy message or garbage
+pub const MAX_FRAME_SIZE: u32 = 64 * 1024;
+
+// The commands understood by older versions, which didn't use framing
+const LEGACY_SHOW_WINDOW: &str = "TRIGGER";
+const LEGACY_PANIC_MUTE: &str = "PANIC_MUTE";
+const LEGACY_SUPPRESSOR_PROFILE: &str = "SUPPRESSOR_PROFILE:";
+const LEGACY_STREAM_TIMER: &str = "STREAM_TIMER:";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Hello { version: u32, client: String },
+    Command(Command),
+}
+
+/// Something for the running instance to do. The name of each command (in snake case) is what's
+/// listed in the server's capabilities.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, EnumIter, IntoStaticStr)]
+#[serde(tag = "command", rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Command {
+    /// Brings the main window to the front, opening it if needed
+    ShowWindow,
+
+    /// Toggles muting everything
+    PanicMute,
+
+    /// Switches every device with this suppressor profile over to it
+    SuppressorProfile { name: String },
+
+    /// Starts, stops, toggles or resets the stream timer
+    StreamTimer { action: String },
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+
+    /// The names of every command this version understands
+    pub fn capabilities() -> Vec<String> {
+        Command::iter().map(|c| c.name().to_string()).collect()
+    }
+
+    /// The message a version which didn't use framing expects for this command
+    pub fn to_legacy(&self) -> String {
+        match self {
+            Command::ShowWindow => LEGACY_SHOW_WINDOW.to_string(),
+            Command::PanicMute => LEGACY_PANIC_MUTE.to_string(),
+            Command::SuppressorProfile { name } => format!("{LEGACY_SUPPRESSOR_PROFILE}{name}"),
+            Command::StreamTimer { action } => format!("{LEGACY_STREAM_TIMER}{action}"),
+        }
+    }
+
+    /// Parses a message from a version which didn't use framing
+    pub fn from_legacy(message: &str) -> Option<Self> {
+        let command = match message {
+            LEGACY_SHOW_WINDOW => Command::ShowWindow,
+            LEGACY_PANIC_MUTE => Command::PanicMute,
+            message => {
+                if let Some(name) = message.strip_prefix(LEGACY_SUPPRESSOR_PROFILE) {
+                    Command::SuppressorProfile {
+                        name: name.to_string(),
+                    }
+                } else if let Some(action) = message.strip_prefix(LEGACY_STREAM_TIMER) {
+                    Command::StreamTimer {
+                        action: action.to_string(),
+                    }
+                } else {
+                    return None;
+                }
+            }
+        };
+        Some(command)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Hello {
+        version: u32,
+        app_version: String,
+        capabilities: Vec<String>,
+    },
+    Ok,
+    Error {
+        message: String,
+    },
+}
+
+/// The start of a connection, either a framed message, or the whole of a legacy one
+pub enum Opening<T> {
+    Frame(T),
+    Legacy(String),
+    Closed,
+}
+
+pub fn write_frame<T: Serialize>(stream: &mut impl Write, message: &T) -> Result<()> {
+    let json = serde_json::to_vec(message)?;
+    if json.len() > MAX_FRAME_SIZE as usize {
+        bail!("Message too large ({} bytes)", json.len());
+    }
+    stream.write_all(&(json.len() as u32).to_be_bytes())?;
+    stream.write_all(&json)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Reads the next message, returning None if the other end has closed the connection
+pub fn read_frame<T: DeserializeOwned>(stream: &mut impl Read) -> Result<Option<T>> {
+    let mut length = [0; 4];
+    match stream.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    read_body(stream, u32::from_be_bytes(length)).map(Some)
+}
+
+/// Reads the first message on a new connection, which may be from an older client
+pub fn read_opening<T: DeserializeOwned>(stream: &mut impl Read) -> Result<Opening<T>> {
+    let mut length = [0; 4];
+    let mut read = 0;
+    while read < length.len() {
+        match stream.read(&mut length[read..])? {
+            0 => break,
+            count => read += count,
+        }
+    }
+
+    let prefix = u32::from_be_bytes(length);
+    if read == 0 {
+        return Ok(Opening::Closed);
+    }
+    if read == length.len() && prefix <= MAX_FRAME_SIZE {
+        return Ok(Opening::Frame(read_body(stream, prefix)?));
+    }
+
+    // Legacy clients write their message and close the socket, so read everything
+    let mut message = length[..read].to_vec();
+    stream.read_to_end(&mut message)?;
+    Ok(Opening::Legacy(String::from_utf8(message)?))
+}
+
+fn read_body<T: DeserializeOwned>(stream: &mut impl Read, length: u32) -> Result<T> {
+    if length > MAX_FRAME_SIZE {
+        bail!("Message too large ({length} bytes)");
+    }
+    let mut body = vec![0; length as usize];
+    stream.read_exact(&mut body)?;
+    Ok(serde_json::from_slice(&body)?)
+}
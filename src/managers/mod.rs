@@ -1,3 +1,6 @@
 pub mod ipc;
 pub mod login;
+pub mod power;
+pub mod schedule;
+pub mod shortcuts;
 pub mod tray;
@@ -0,0 +1,139 @@
+/* Watches UPower for the system's battery state, so that when a laptop is running low we can
+   ease off on the Mix / Mix Create, dimming the screen and slowing down updates until it's back
+   on AC power, or has charged back up past the threshold.
+
+   UPower provides a 'DisplayDevice', which combines all the system batteries into a single
+   percentage, which is exactly what we want here.
+
+   The device manager is told directly so it can pass it on to each display handler, and the
+   app-wide integrations (OBS, the header widgets) follow it through `subscribe`.
+
+   Refs:
+   https://upower.freedesktop.org/docs/UPower.html
+   https://upower.freedesktop.org/docs/Device.html
+*/
+
+use crate::settings;
+use anyhow::Result;
+use beacn_lib::crossbeam;
+use futures_util::StreamExt;
+use log::{debug, info, warn};
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::sync::mpsc as tokio_mpsc;
+use tokio::sync::watch;
+use tokio::time;
+use zbus::{Connection, proxy};
+
+// Settings changes don't come through dbus, so we need to periodically check them
+const SETTINGS_CHECK: Duration = Duration::from_secs(5);
+
+#[proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower"
+)]
+trait UPower {
+    #[zbus(property)]
+    fn on_battery(&self) -> zbus::Result<bool>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.UPower.Device",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower/devices/DisplayDevice"
+)]
+trait DisplayDevice {
+    #[zbus(property)]
+    fn percentage(&self) -> zbus::Result<f64>;
+}
+
+static SAVING: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::Sender::new(false));
+
+/// Whether the battery saver is currently active
+pub fn is_saving() -> bool {
+    *SAVING.borrow()
+}
+
+/// Used by integrations which pause themselves while saving power
+pub fn subscribe() -> watch::Receiver<bool> {
+    SAVING.subscribe()
+}
+
+#[derive(Debug)]
+pub enum PowerEventTriggers {
+    PowerSaving(bool),
+}
+
+pub fn spawn_power_handler(
+    tx: crossbeam::channel::Sender<PowerEventTriggers>,
+    stop_rx: tokio_mpsc::Receiver<()>,
+) -> Result<()> {
+    debug!("Starting Power Handler with dedicated runtime..");
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()?;
+
+    // Not every system has UPower (most desktops won't), that's fine, we just never save power
+    let result = rt.block_on(run_internal(tx, stop_rx));
+    SAVING.send_replace(false);
+    if let Err(e) = &result {
+        warn!("Battery monitoring unavailable: {e}");
+    }
+    result
+}
+
+async fn run_internal(
+    tx: crossbeam::channel::Sender<PowerEventTriggers>,
+    mut stop_rx: tokio_mpsc::Receiver<()>,
+) -> Result<()> {
+    let conn = Connection::system().await?;
+    let upower = UPowerProxy::new(&conn).await?;
+    let battery = DisplayDeviceProxy::new(&conn).await?;
+
+    let mut on_battery = upower.on_battery().await?;
+    let mut percentage = battery.percentage().await.unwrap_or(100.0);
+    debug!("Initial Power State, On Battery: {on_battery}, Percentage: {percentage}");
+
+    let mut battery_stream = upower.receive_on_battery_changed().await;
+    let mut percentage_stream = battery.receive_percentage_changed().await;
+    let mut settings_check = time::interval(SETTINGS_CHECK);
+
+    let mut power_saving = false;
+    loop {
+        tokio::select! {
+            Some(change) = battery_stream.next() => {
+                on_battery = change.get().await?;
+            }
+            Some(change) = percentage_stream.next() => {
+                percentage = change.get().await?;
+            }
+            _ = settings_check.tick() => {}
+            _ = stop_rx.recv() => {
+                debug!("Received stop signal, shutting down power handler");
+                break;
+            }
+        }
+
+        let saving = should_save_power(on_battery, percentage);
+        if saving != power_saving {
+            power_saving = saving;
+            info!(
+                "Battery Saving {}",
+                if saving { "Enabled" } else { "Disabled" }
+            );
+            SAVING.send_replace(saving);
+            let _ = tx.send(PowerEventTriggers::PowerSaving(saving));
+        }
+    }
+
+    debug!("End of Run");
+    Ok(())
+}
+
+fn should_save_power(on_battery: bool, percentage: f64) -> bool {
+    let settings = settings::get();
+    settings.battery_saver && on_battery && percentage <= settings.battery_threshold as f64
+}
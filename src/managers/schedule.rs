@@ -0,0 +1,158 @@
+/*
+  Switches suppressor profiles based on the time of day, so (for example) a 'Daytime' profile can
+  be used during working hours and a 'Streaming' one in the evening.
+
+  Each entry covers a range of the day, which can cross midnight. The handler checks the clock
+  periodically, and when the current time moves into a different entry, that entry's profile is
+  applied to every device which has one by that name. Outside any entry, whatever was last
+  applied is left alone.
+
+  Devices pick up the scheduled profile as they're connected (including when the app starts),
+  which is handled by the UI as it loads their settings.
+
+  The Mix screen can also be put to sleep for part of the day. That's driven by the device
+  manager, as it's the one which turns screens on and off, but uses the same ranges.
+*/
+
+use crate::{ToMainMessages, settings};
+use beacn_lib::crossbeam::channel::{self, Receiver, Sender};
+use beacn_lib::crossbeam::select;
+use chrono::{Local, Timelike};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+// Boundaries are to the minute, so there's no point checking much more often than this
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleTime {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl ScheduleTime {
+    pub fn new(hour: u8, minute: u8) -> Self {
+        Self {
+            hour: hour.min(23),
+            minute: minute.min(59),
+        }
+    }
+
+    pub fn now() -> Self {
+        let now = Local::now();
+        Self::new(now.hour() as u8, now.minute() as u8)
+    }
+
+    fn minutes(&self) -> u16 {
+        self.hour as u16 * 60 + self.minute as u16
+    }
+}
+
+impl Display for ScheduleTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledProfile {
+    pub enabled: bool,
+
+    /// The name of the suppressor profile to switch to
+    pub profile: String,
+
+    /// When the profile starts, and when it stops, the end isn't included
+    pub start: ScheduleTime,
+    pub end: ScheduleTime,
+}
+
+impl Default for ScheduledProfile {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            profile: String::new(),
+            start: ScheduleTime::new(9, 0),
+            end: ScheduleTime::new(17, 0),
+        }
+    }
+}
+
+impl ScheduledProfile {
+    pub fn contains(&self, time: ScheduleTime) -> bool {
+        in_range(self.start, self.end, time)
+    }
+}
+
+/// When the Mix / Mix Create screens should be turned off
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySleep {
+    pub enabled: bool,
+
+    /// When the screens turn off, and when they come back on
+    pub start: ScheduleTime,
+    pub end: ScheduleTime,
+}
+
+impl Default for DisplaySleep {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: ScheduleTime::new(0, 0),
+            end: ScheduleTime::new(8, 0),
+        }
+    }
+}
+
+impl DisplaySleep {
+    /// Whether the screens should be asleep at this time
+    pub fn is_asleep(&self, time: ScheduleTime) -> bool {
+        self.enabled && in_range(self.start, self.end, time)
+    }
+}
+
+// Whether a time falls in a range, the end isn't included
+fn in_range(start: ScheduleTime, end: ScheduleTime, time: ScheduleTime) -> bool {
+    let (start, end, time) = (start.minutes(), end.minutes(), time.minutes());
+    match start <= end {
+        true => start <= time && time < end,
+        // Crosses midnight, so it's everything but the gap in the middle
+        false => time >= start || time < end,
+    }
+}
+
+/// The profile which should be active at this time, if any. Where entries overlap, the first
+/// one in the list wins.
+pub fn scheduled_profile(time: ScheduleTime) -> Option<String> {
+    settings::get()
+        .profile_schedule
+        .into_iter()
+        .find(|entry| entry.enabled && !entry.profile.is_empty() && entry.contains(time))
+        .map(|entry| entry.profile)
+}
+
+pub fn spawn_schedule_handler(tx: Sender<ToMainMessages>, stop_rx: Receiver<()>) {
+    debug!("Starting Profile Schedule Handler");
+
+    // Whatever's active now is applied as devices appear, so only changes from here are sent
+    let mut current = scheduled_profile(ScheduleTime::now());
+    let ticker = channel::tick(CHECK_INTERVAL);
+    loop {
+        select! {
+            recv(ticker) -> _ => {
+                let scheduled = scheduled_profile(ScheduleTime::now());
+                if scheduled != current {
+                    if let Some(name) = &scheduled {
+                        info!("Switching to Scheduled Profile {name}");
+                        let _ = tx.send(ToMainMessages::ApplySuppressorProfile(None, name.clone()));
+                    }
+                    current = scheduled;
+                }
+            }
+            recv(stop_rx) -> _ => break,
+        }
+    }
+    debug!("Profile Schedule Handler Stopped");
+}
@@ -0,0 +1,81 @@
+/* Registers global shortcuts through the XDG GlobalShortcuts portal, so they work regardless of
+   which window has focus (and under Wayland, where we can't grab keys ourselves).
+
+   The portal lets the desktop decide the final key combination, we only suggest one. Not every
+   desktop implements the portal, in which case the same actions are available from the tray
+   and through the command line (which can be bound by hand).
+
+   Refs:
+   https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.GlobalShortcuts.html
+*/
+
+use crate::ToMainMessages;
+use anyhow::Result;
+use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
+use beacn_lib::crossbeam;
+use futures_util::StreamExt;
+use log::{debug, warn};
+use tokio::sync::mpsc as tokio_mpsc;
+
+const PANIC_MUTE_ID: &str = "panic-mute";
+const PANIC_MUTE_TRIGGER: &str = "CTRL+SHIFT+F12";
+
+pub fn spawn_shortcuts_handler(
+    tx: crossbeam::channel::Sender<ToMainMessages>,
+    stop_rx: tokio_mpsc::Receiver<()>,
+) -> Result<()> {
+    debug!("Starting Shortcuts Handler with dedicated runtime..");
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .enable_time()
+        .build()?;
+
+    let result = rt.block_on(run_internal(tx, stop_rx));
+    if let Err(e) = &result {
+        warn!("Global shortcuts unavailable: {e}");
+    }
+    result
+}
+
+async fn run_internal(
+    tx: crossbeam::channel::Sender<ToMainMessages>,
+    mut stop_rx: tokio_mpsc::Receiver<()>,
+) -> Result<()> {
+    let portal = GlobalShortcuts::new().await?;
+    let session = portal.create_session().await?;
+
+    let shortcuts = [NewShortcut::new(PANIC_MUTE_ID, "Mute Everything")
+        .preferred_trigger(Some(PANIC_MUTE_TRIGGER))];
+    let bound = portal
+        .bind_shortcuts(&session, &shortcuts, None)
+        .await?
+        .response()?;
+    for shortcut in bound.shortcuts() {
+        debug!(
+            "Bound Shortcut {}: {}",
+            shortcut.id(),
+            shortcut.trigger_description()
+        );
+    }
+
+    let mut activated = portal.receive_activated().await?;
+    loop {
+        tokio::select! {
+            Some(event) = activated.next() => {
+                debug!("Shortcut Activated: {}", event.shortcut_id());
+                if event.shortcut_id() == PANIC_MUTE_ID {
+                    let _ = tx.send(ToMainMessages::TogglePanicMute);
+                }
+            }
+            _ = stop_rx.recv() => {
+                debug!("Received stop signal, shutting down shortcuts handler");
+                break;
+            }
+        }
+    }
+
+    let _ = session.close().await;
+    debug!("End of Run");
+    Ok(())
+}
@@ -1,10 +1,15 @@
-use crate::{APP_NAME, APP_TITLE, ICON, ManagerMessages, ToMainMessages};
+use crate::i18n::tr;
+use crate::integrations::header_widgets::{TimerAction, timer_action};
+use crate::{
+    APP_NAME, APP_TITLE, DeviceHealth, ICON, ManagerMessages, ToMainMessages, TrayDevice,
+    TrayDeviceList, panic_mute,
+};
 use anyhow::Result;
 use beacn_lib::crossbeam::channel::{Receiver, Sender};
 use beacn_lib::crossbeam::{channel, select};
 use image::GenericImageView;
 use ksni::blocking::TrayMethods;
-use ksni::menu::StandardItem;
+use ksni::menu::{CheckmarkItem, StandardItem, SubMenu};
 use ksni::{Category, Icon, MenuItem, Status, ToolTip, Tray};
 use log::{debug, warn};
 use std::path::{Path, PathBuf};
@@ -13,6 +18,10 @@ use std::{env, fs};
 
 enum TrayMessages {
     Activate,
+    SuppressorProfile(String, String),
+    LightingMode(String, String),
+    StreamTimer(TimerAction),
+    PanicMute,
     Quit,
 }
 
@@ -54,6 +63,18 @@ pub fn handle_tray(
                                 let _ = tray_main_tx.send(ToMainMessages::SpawnWindow);
                                 debug!("Activate Triggered");
                             },
+                            TrayMessages::SuppressorProfile(serial, name) => {
+                                let message = ToMainMessages::ApplySuppressorProfile(Some(serial), name);
+                                let _ = tray_main_tx.send(message);
+                            }
+                            TrayMessages::LightingMode(serial, name) => {
+                                let message = ToMainMessages::ApplyLightingMode(serial, name);
+                                let _ = tray_main_tx.send(message);
+                            }
+                            TrayMessages::StreamTimer(action) => timer_action(action),
+                            TrayMessages::PanicMute => {
+                                let _ = tray_main_tx.send(ToMainMessages::TogglePanicMute);
+                            }
                             TrayMessages::Quit => {
                                 // If we have an active window, we need to close it first.
                                 // Tell the parent to immediately quit
@@ -74,6 +95,20 @@ pub fn handle_tray(
                             ManagerMessages::Quit => {
                                 break;
                             }
+                            ManagerMessages::AnnounceDevices => {}
+                            ManagerMessages::TrayDevices(devices) => {
+                                handle.update(|tray| tray.devices = devices);
+                            }
+                            ManagerMessages::DeviceHealth(health) => {
+                                handle.update(|tray| tray.health = health);
+                            }
+                            ManagerMessages::LanguageChanged => {
+                                // Nothing to change, but this rebuilds the menu
+                                handle.update(|_| {});
+                            }
+                            ManagerMessages::PanicMute(active) => {
+                                handle.update(|tray| tray.panic_muted = active);
+                            }
                         }
                     }
 
@@ -102,6 +137,31 @@ pub fn handle_tray(
 struct TrayIcon {
     icon: PathBuf,
     tx: Sender<TrayMessages>,
+    devices: TrayDeviceList,
+    health: DeviceHealth,
+    panic_muted: bool,
+}
+
+// What the icon shows, these can be combined (a muted device can also be in error)
+#[derive(Debug, Copy, Clone)]
+struct IconStyle {
+    muted: bool,
+    error: bool,
+    disconnected: bool,
+}
+
+impl IconStyle {
+    fn index(&self) -> usize {
+        self.muted as usize | (self.error as usize) << 1 | (self.disconnected as usize) << 2
+    }
+
+    fn from_index(index: usize) -> Self {
+        Self {
+            muted: index & 1 != 0,
+            error: index & 2 != 0,
+            disconnected: index & 4 != 0,
+        }
+    }
 }
 
 impl TrayIcon {
@@ -109,8 +169,125 @@ impl TrayIcon {
         Self {
             icon: icon.to_path_buf(),
             tx,
+            devices: vec![],
+            health: DeviceHealth::default(),
+            // The tray may have been restarted, so pick up where it was
+            panic_muted: panic_mute::is_active(),
         }
     }
+
+    fn profile_menu(&self) -> Option<MenuItem<Self>> {
+        let items = self.device_items(
+            |device| {
+                device
+                    .suppressor_profiles
+                    .iter()
+                    .map(|n| (n.clone(), false))
+                    .collect()
+            },
+            TrayMessages::SuppressorProfile,
+        );
+        Self::submenu(tr!("tray-noise-profiles"), items)
+    }
+
+    fn lighting_menu(&self) -> Option<MenuItem<Self>> {
+        let items = self.device_items(
+            |device| device.lighting_modes.clone(),
+            TrayMessages::LightingMode,
+        );
+        Self::submenu(tr!("tray-lighting"), items)
+    }
+
+    // Builds an entry for each option on each device, the message is sent with the device
+    // serial and option name when it's picked
+    fn device_items(
+        &self,
+        options: impl Fn(&TrayDevice) -> Vec<(String, bool)>,
+        message: fn(String, String) -> TrayMessages,
+    ) -> Vec<MenuItem<Self>> {
+        let devices: Vec<_> = self.devices.iter().map(|d| (d, options(d))).collect();
+
+        // Only name the device if there's more than one to pick from
+        let show_serial = devices.iter().filter(|(_, o)| !o.is_empty()).count() > 1;
+        let mut items = vec![];
+        for (device, options) in devices {
+            for (name, checked) in options {
+                let serial = device.serial.clone();
+                let label = match show_serial {
+                    true => format!("{serial}: {name}"),
+                    false => name.clone(),
+                };
+
+                let activate = Box::new(move |this: &mut TrayIcon| {
+                    let _ = this.tx.try_send(message(serial.clone(), name.clone()));
+                });
+                let item = match checked {
+                    true => CheckmarkItem {
+                        label,
+                        checked,
+                        activate,
+                        ..Default::default()
+                    }
+                    .into(),
+                    false => StandardItem {
+                        label,
+                        activate,
+                        ..Default::default()
+                    }
+                    .into(),
+                };
+                items.push(item);
+            }
+        }
+        items
+    }
+
+    fn timer_menu(&self) -> MenuItem<Self> {
+        let actions = [
+            (tr!("tray-timer-toggle"), TimerAction::Toggle),
+            (tr!("tray-timer-reset"), TimerAction::Reset),
+        ];
+        let items = actions
+            .into_iter()
+            .map(|(label, action)| {
+                StandardItem {
+                    label,
+                    activate: Box::new(move |this: &mut TrayIcon| {
+                        let _ = this.tx.try_send(TrayMessages::StreamTimer(action));
+                    }),
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect();
+
+        SubMenu {
+            label: tr!("tray-timer"),
+            submenu: items,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    fn icon_style(&self) -> IconStyle {
+        IconStyle {
+            muted: self.panic_muted,
+            error: !self.health.errors.is_empty(),
+            disconnected: self.health.connected == 0,
+        }
+    }
+
+    fn submenu(label: String, items: Vec<MenuItem<Self>>) -> Option<MenuItem<Self>> {
+        if items.is_empty() {
+            return None;
+        }
+        let menu = SubMenu {
+            label,
+            submenu: items,
+            ..Default::default()
+        };
+        Some(menu.into())
+    }
 }
 
 impl Tray for TrayIcon {
@@ -128,43 +305,57 @@ impl Tray for TrayIcon {
         APP_TITLE.to_string()
     }
     fn status(&self) -> Status {
-        Status::Active
+        let style = self.icon_style();
+        match style.muted || style.error {
+            true => Status::NeedsAttention,
+            false => Status::Active,
+        }
     }
 
     fn icon_pixmap(&self) -> Vec<Icon> {
-        static TRAY_ICON: LazyLock<Icon> = LazyLock::new(|| {
-            let img = image::load_from_memory_with_format(ICON, image::ImageFormat::Png)
-                .expect("Unable to Load Image");
-
-            let (width, height) = img.dimensions();
-            let mut data = img.into_rgba8().into_vec();
-
-            for pixel in data.chunks_exact_mut(4) {
-                pixel.rotate_right(1) // RGBA to ARGB
-            }
-
-            Icon {
-                width: width as i32,
-                height: height as i32,
-                data,
-            }
+        // Every combination is built up front, as they're asked for on every change
+        static ICONS: LazyLock<Vec<Icon>> = LazyLock::new(|| {
+            (0..8)
+                .map(|i| load_tray_icon(IconStyle::from_index(i)))
+                .collect()
         });
 
-        vec![TRAY_ICON.clone()]
+        // Not every tray shows the attention icon, so swap the main one too
+        vec![ICONS[self.icon_style().index()].clone()]
+    }
+
+    fn attention_icon_pixmap(&self) -> Vec<Icon> {
+        self.icon_pixmap()
     }
 
     fn tool_tip(&self) -> ToolTip {
+        let style = self.icon_style();
+        let mut lines = vec![];
+        if style.muted {
+            lines.push(tr!("tray-panic-muted"));
+        }
+        if style.error {
+            let devices = self.health.errors.join(", ");
+            lines.push(tr!("tray-device-error", devices = devices));
+        }
+        if style.disconnected {
+            lines.push(tr!("tray-no-devices"));
+        }
+        if lines.is_empty() {
+            lines.push(tr!("tray-description"));
+        }
+        let description = lines.join("\n");
         ToolTip {
             title: String::from(APP_TITLE),
-            description: String::from("A Tool for Configuring Beacn Devices"),
+            description,
             ..Default::default()
         }
     }
 
     fn menu(&self) -> Vec<MenuItem<Self>> {
-        vec![
+        let mut menu = vec![
             StandardItem {
-                label: String::from("Show"),
+                label: tr!("tray-show"),
                 activate: Box::new(|this: &mut TrayIcon| {
                     let _ = this.tx.try_send(TrayMessages::Activate);
                 }),
@@ -172,14 +363,88 @@ impl Tray for TrayIcon {
             }
             .into(),
             MenuItem::Separator,
+        ];
+
+        menu.push(
+            CheckmarkItem {
+                label: tr!("tray-panic-mute"),
+                checked: self.panic_muted,
+                activate: Box::new(|this: &mut TrayIcon| {
+                    let _ = this.tx.try_send(TrayMessages::PanicMute);
+                }),
+                ..Default::default()
+            }
+            .into(),
+        );
+        menu.push(MenuItem::Separator);
+
+        let submenus = [
+            self.profile_menu(),
+            self.lighting_menu(),
+            Some(self.timer_menu()),
+        ];
+        let submenus: Vec<_> = submenus.into_iter().flatten().collect();
+        if !submenus.is_empty() {
+            menu.extend(submenus);
+            menu.push(MenuItem::Separator);
+        }
+
+        menu.push(
             StandardItem {
-                label: String::from("Quit"),
+                label: tr!("tray-quit"),
                 activate: Box::new(|this: &mut TrayIcon| {
                     let _ = this.tx.try_send(TrayMessages::Quit);
                 }),
                 ..Default::default()
             }
             .into(),
-        ]
+        );
+        menu
+    }
+}
+
+// Loads the application icon for the tray, greyed out with nothing connected, tinted red while
+// everything is muted, and with a warning badge in the corner when a device is in error
+fn load_tray_icon(style: IconStyle) -> Icon {
+    let img = image::load_from_memory_with_format(ICON, image::ImageFormat::Png)
+        .expect("Unable to Load Image");
+
+    let (width, height) = img.dimensions();
+    let mut img = img.into_rgba8();
+
+    for pixel in img.pixels_mut() {
+        if style.disconnected {
+            let [r, g, b, a] = pixel.0;
+            let grey = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+            pixel.0 = [grey, grey, grey, a / 2];
+        }
+        if style.muted {
+            pixel[0] = pixel[0].saturating_add(120);
+            pixel[1] /= 3;
+            pixel[2] /= 3;
+        }
+    }
+
+    if style.error {
+        // An amber dot, a third of the icon's size, in the bottom right
+        let radius = width.min(height) as f32 / 6.0;
+        let (cx, cy) = (width as f32 - radius, height as f32 - radius);
+        for (x, y, pixel) in img.enumerate_pixels_mut() {
+            let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+            if distance <= radius {
+                pixel.0 = [255, 170, 0, 255];
+            }
+        }
+    }
+
+    let mut data = img.into_vec();
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.rotate_right(1) // RGBA to ARGB
+    }
+
+    Icon {
+        width: width as i32,
+        height: height as i32,
+        data,
     }
 }
@@ -0,0 +1,104 @@
+// A 'mute everything' switch, for when something's gone wrong on stream and there's no time to
+// find the right channel. It can be toggled from a global shortcut, the tray, or over IPC.
+//
+// Turning it on mutes the default microphone, and each display integration mutes whatever it
+// controls (Pipeweaver sources, or application streams). Turning it off only unmutes what we
+// muted, so anything the user had already muted stays that way.
+
+use crate::hooks::{self, HookEvent};
+use crate::integrations;
+use crate::tools::tool_error;
+use anyhow::{Result, bail};
+use log::{info, warn};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use tokio::sync::watch;
+
+const DEFAULT_SOURCE: &str = "@DEFAULT_AUDIO_SOURCE@";
+
+static ACTIVE: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::Sender::new(false));
+
+// Whether the default microphone was muted by us, and needs unmuting afterwards
+static MIC_MUTED: AtomicBool = AtomicBool::new(false);
+
+// Held while the microphone is being changed, so quick toggles can't apply out of order
+static APPLYING: Mutex<()> = Mutex::new(());
+
+pub fn is_active() -> bool {
+    *ACTIVE.borrow()
+}
+
+/// Used by the display integrations to follow the mute
+pub fn subscribe() -> watch::Receiver<bool> {
+    ACTIVE.subscribe()
+}
+
+/// Flips the mute, returning whether everything is now muted
+pub fn toggle() -> bool {
+    let active = !is_active();
+    set(active);
+    active
+}
+
+pub fn set(active: bool) {
+    let changed = ACTIVE.send_if_modified(|current| {
+        let changed = *current != active;
+        *current = active;
+        changed
+    });
+    if !changed {
+        return;
+    }
+
+    info!("Panic Mute: {active}");
+    let event = match active {
+        true => HookEvent::PanicMuted,
+        false => HookEvent::PanicUnmuted,
+    };
+    hooks::fire(event, &[]);
+    integrations::mute_changed();
+
+    // The flag is read again once it's our turn, so whichever thread runs last applies the
+    // latest state, and the rest have nothing left to change
+    thread::spawn(|| {
+        let _applying = APPLYING.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = mute_default_source(is_active()) {
+            warn!("Unable to change the microphone mute: {e}");
+        }
+    });
+}
+
+fn mute_default_source(mute: bool) -> Result<()> {
+    if !mute {
+        if MIC_MUTED.swap(false, Ordering::Relaxed) {
+            wpctl(&["set-mute", DEFAULT_SOURCE, "0"])?;
+        }
+        return Ok(());
+    }
+
+    // wpctl tags the volume when it's muted, leave it alone if that was the user
+    let output = Command::new("wpctl")
+        .args(["get-volume", DEFAULT_SOURCE])
+        .output()
+        .map_err(tool_error("wpctl"))?;
+    if String::from_utf8_lossy(&output.stdout).contains("[MUTED]") {
+        return Ok(());
+    }
+
+    wpctl(&["set-mute", DEFAULT_SOURCE, "1"])?;
+    MIC_MUTED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+fn wpctl(args: &[&str]) -> Result<()> {
+    let status = Command::new("wpctl")
+        .args(args)
+        .status()
+        .map_err(tool_error("wpctl"))?;
+    if !status.success() {
+        bail!("wpctl {} failed with {status}", args.join(" "));
+    }
+    Ok(())
+}
@@ -0,0 +1,249 @@
+/*
+  Records short clips from a mic, so the user can hear what the processing is actually doing, or
+  so we can measure how noisy the room is. The mic's level can also be followed live, for things
+  like setting the gain or watching the monitor level.
+
+  Like the PipeWire integration, we avoid linking libpipewire and use the tools which ship with
+  it instead: pw-dump to find the mic's source node, pw-record to capture it, and pw-play to
+  listen back.
+*/
+
+use crate::tools::tool_error;
+use anyhow::{Result, anyhow, bail};
+use beacn_lib::crossbeam::channel::{self, Receiver};
+use log::debug;
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+// The rate the level monitor records at
+const MONITOR_RATE: usize = 48000;
+
+/// How often the level monitor takes a reading, unless asked for something different
+pub const MONITOR_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The level of a recording, in dBFS
+#[derive(Debug, Copy, Clone)]
+pub struct ClipLevel {
+    pub rms: f32,
+    pub peak: f32,
+}
+
+/// Finds the PipeWire source for the mic with this serial
+pub fn find_source(serial: &str) -> Result<String> {
+    let output = Command::new("pw-dump")
+        .output()
+        .map_err(tool_error("pw-dump"))?;
+    if !output.status.success() {
+        bail!("pw-dump exited with {}", output.status);
+    }
+    let dump: Value = serde_json::from_slice(&output.stdout)?;
+    let objects = dump
+        .as_array()
+        .ok_or(anyhow!("Unexpected pw-dump output"))?;
+
+    // ALSA nodes are named after the USB device, which includes the serial
+    let sources: Vec<&str> = objects
+        .iter()
+        .filter(|o| o["type"] == "PipeWire:Interface:Node")
+        .map(|o| &o["info"]["props"])
+        .filter(|props| props["media.class"] == "Audio/Source")
+        .filter_map(|props| props["node.name"].as_str())
+        .collect();
+
+    sources
+        .iter()
+        .find(|name| name.contains(serial))
+        .or_else(|| sources.iter().find(|n| n.to_lowercase().contains("beacn")))
+        .map(|name| name.to_string())
+        .ok_or(anyhow!("Unable to find the mic in PipeWire"))
+}
+
+/// Records from the source into a 16bit WAV file, blocking until the clip is complete
+pub fn record_clip(source: &str, path: &Path, length: Duration) -> Result<()> {
+    debug!("Recording {source} to {path:?}");
+    let mut child = Command::new("pw-record")
+        .args(["--target", source, "--channels", "1", "--format", "s16"])
+        .arg(path)
+        .spawn()
+        .map_err(tool_error("pw-record"))?;
+
+    thread::sleep(length);
+    if let Ok(Some(status)) = child.try_wait() {
+        bail!("pw-record exited early with {status}");
+    }
+
+    // pw-record only finishes writing the WAV header when it's interrupted cleanly
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status();
+    if !status.is_ok_and(|s| s.success()) {
+        let _ = child.kill();
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Starts playing a clip, the returned child can be killed to stop it early
+pub fn play_clip(path: &Path) -> Result<Child> {
+    let child = Command::new("pw-play").arg(path).spawn();
+    Ok(child.map_err(tool_error("pw-play"))?)
+}
+
+/// Follows the level of a source, producing a reading every interval until it's dropped
+pub struct LevelMonitor {
+    // Simulated monitors don't record anything
+    child: Option<Child>,
+    levels: Receiver<ClipLevel>,
+}
+
+impl LevelMonitor {
+    pub fn start(source: &str, interval: Duration) -> Result<Self> {
+        debug!("Monitoring the level of {source} every {interval:?}");
+        let window = Self::window(interval);
+        let rate = MONITOR_RATE.to_string();
+        let mut child = Command::new("pw-record")
+            .args(["--target", source, "--channels", "1", "--format", "s16"])
+            .args(["--rate", &rate, "--raw", "-"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(tool_error("pw-record"))?;
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or(anyhow!("pw-record has no output"))?;
+
+        let (tx, rx) = channel::unbounded();
+        thread::spawn(move || {
+            let mut buffer = vec![0; window * 2];
+            while stdout.read_exact(&mut buffer).is_ok() {
+                let samples = buffer
+                    .chunks_exact(2)
+                    .map(|s| i16::from_le_bytes([s[0], s[1]]));
+                let samples: Vec<f32> = samples.map(|s| s as f32 / i16::MAX as f32).collect();
+                if let Some(level) = measure_samples(&samples)
+                    && tx.send(level).is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child: Some(child),
+            levels: rx,
+        })
+    }
+
+    /// A monitor which follows something that sounds a bit like someone talking, for --demo
+    pub fn simulated(interval: Duration) -> Self {
+        let (tx, rx) = channel::unbounded();
+        thread::spawn(move || {
+            let mut time = 0.0f32;
+            loop {
+                let syllables = ((time * 7.0).sin() / 2.0 + 0.5).powi(2);
+                let phrases = ((time * 0.9).sin() / 2.0 + 0.5).max(0.2);
+                let rms = -45.0 + syllables * phrases * 30.0;
+                if tx
+                    .send(ClipLevel {
+                        rms,
+                        peak: rms + 9.0,
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+                thread::sleep(interval);
+                time += interval.as_secs_f32();
+            }
+        });
+        Self {
+            child: None,
+            levels: rx,
+        }
+    }
+
+    // How many samples go into each reading
+    fn window(interval: Duration) -> usize {
+        let window = MONITOR_RATE as f32 * interval.as_secs_f32();
+        (window as usize).max(1)
+    }
+
+    /// The readings taken since this was last called
+    pub fn levels(&self) -> Vec<ClipLevel> {
+        self.levels.try_iter().collect()
+    }
+}
+
+impl Drop for LevelMonitor {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Measures the level of a clip made by `record_clip`
+pub fn measure_clip(path: &Path) -> Result<ClipLevel> {
+    let wav = fs::read(path)?;
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        bail!("Recording is not a WAV file");
+    }
+
+    // Walk the chunks until we find the samples, checking they're in the format we asked for
+    let mut offset = 12;
+    let mut samples = None;
+    while offset + 8 <= wav.len() {
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into()?) as usize;
+        let body = offset + 8;
+
+        match id {
+            b"fmt " if wav.len() >= body + 16 => {
+                let format = u16::from_le_bytes([wav[body], wav[body + 1]]);
+                let bits = u16::from_le_bytes([wav[body + 14], wav[body + 15]]);
+                if format != 1 || bits != 16 {
+                    bail!("Unexpected recording format ({format}, {bits} bit)");
+                }
+            }
+            b"data" => {
+                // If the header wasn't finished, the samples run to the end of the file
+                let end = body.saturating_add(size).min(wav.len());
+                let end = if size == 0 { wav.len() } else { end };
+                samples = Some(&wav[body..end]);
+                break;
+            }
+            _ => {}
+        }
+        offset = body.saturating_add(size).saturating_add(size % 2);
+    }
+
+    let samples: Vec<f32> = samples
+        .ok_or(anyhow!("Recording contains no audio"))?
+        .chunks_exact(2)
+        .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / i16::MAX as f32)
+        .collect();
+    measure_samples(&samples).ok_or(anyhow!("Recording contains no audio"))
+}
+
+fn measure_samples(samples: &[f32]) -> Option<ClipLevel> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let sum: f32 = samples.iter().map(|s| s * s).sum();
+    let rms = (sum / samples.len() as f32).sqrt();
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+    // Digital silence would be -inf, so floor it
+    let to_db = |value: f32| (20.0 * value.max(1e-6).log10()).max(-120.0);
+    Some(ClipLevel {
+        rms: to_db(rms),
+        peak: to_db(peak),
+    })
+}
@@ -0,0 +1,164 @@
+/*
+  Statistics about the current session, for streamers looking back over a show: how long the
+  mic was live (and muted), how much each dial was used, and how each channel's volume moved
+  over time.
+
+  The mic counts as muted while the panic mute is on, or while any Mic's own mute is (however it
+  was muted, as read by the mute sync).
+  Volumes are recorded by the display integrations, both when a dial turns them and when
+  something else changes them. Collection is off unless it's turned on in the settings, and
+  nothing is kept once the app closes, although it can be exported as a CSV.
+*/
+
+use crate::integrations;
+use beacn_lib::controller::Dials;
+use chrono::{DateTime, Local};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+// Plenty for a long stream, after this the oldest points for a channel are dropped
+const MAX_POINTS: usize = 5000;
+
+static SESSION: LazyLock<Mutex<Option<SessionStats>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct DialActivity {
+    /// How many times the dial was moved
+    pub moves: u32,
+
+    /// How far it was turned in total, in either direction
+    pub steps: u32,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct VolumePoint {
+    /// How far into the session the volume changed
+    pub offset: Duration,
+    pub volume: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    pub started: DateTime<Local>,
+    pub mutes: u32,
+    pub dials: BTreeMap<String, DialActivity>,
+    pub volumes: BTreeMap<String, VecDeque<VolumePoint>>,
+
+    /// Periods (from, to) where the mic was muted, as offsets into the session
+    pub mute_periods: Vec<(Duration, Duration)>,
+
+    start: Instant,
+    muted_since: Option<Duration>,
+}
+
+impl SessionStats {
+    fn new() -> Self {
+        let mut stats = Self {
+            started: Local::now(),
+            mutes: 0,
+            dials: BTreeMap::new(),
+            volumes: BTreeMap::new(),
+            mute_periods: vec![],
+            start: Instant::now(),
+            muted_since: None,
+        };
+        if integrations::is_muted() {
+            stats.muted_since = Some(Duration::ZERO);
+        }
+        stats
+    }
+
+    /// How long the session has been running
+    pub fn duration(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// The time spent muted, including the current mute if there is one
+    pub fn muted_time(&self) -> Duration {
+        let finished: Duration = self.mute_periods.iter().map(|(from, to)| *to - *from).sum();
+        let current = self.muted_since.map(|since| self.duration() - since);
+        finished + current.unwrap_or_default()
+    }
+
+    /// The time the mic was live
+    pub fn talk_time(&self) -> Duration {
+        self.duration().saturating_sub(self.muted_time())
+    }
+
+    /// When the mic was muted, if it still is
+    pub fn muted_since(&self) -> Option<Duration> {
+        self.muted_since
+    }
+}
+
+fn with_session(change: impl FnOnce(&mut SessionStats)) {
+    if let Ok(mut session) = SESSION.lock()
+        && let Some(session) = session.as_mut()
+    {
+        change(session);
+    }
+}
+
+/// Turns collection on or off, turning it on starts a fresh session
+pub fn set_enabled(enabled: bool) {
+    if let Ok(mut session) = SESSION.lock() {
+        if enabled == session.is_some() {
+            return;
+        }
+        *session = enabled.then(SessionStats::new);
+    }
+}
+
+/// Throws away everything collected so far, and starts again
+pub fn reset() {
+    if let Ok(mut session) = SESSION.lock()
+        && session.is_some()
+    {
+        *session = Some(SessionStats::new());
+    }
+}
+
+pub fn record_mute(muted: bool) {
+    with_session(|session| {
+        let now = session.duration();
+        match (muted, session.muted_since) {
+            (true, None) => {
+                session.mutes += 1;
+                session.muted_since = Some(now);
+            }
+            (false, Some(since)) => {
+                session.mute_periods.push((since, now));
+                session.muted_since = None;
+            }
+            _ => {}
+        }
+    });
+}
+
+pub fn record_dial(dial: Dials, change: i8) {
+    with_session(|session| {
+        let activity = session.dials.entry(format!("{dial:?}")).or_default();
+        activity.moves += 1;
+        activity.steps += change.unsigned_abs() as u32;
+    });
+}
+
+/// Records a channel's volume, which is only stored if it's different to the last one seen
+pub fn record_volume(channel: &str, volume: u8) {
+    with_session(|session| {
+        let offset = session.duration();
+        let history = session.volumes.entry(channel.to_string()).or_default();
+        if history.back().is_some_and(|point| point.volume == volume) {
+            return;
+        }
+        if history.len() == MAX_POINTS {
+            history.pop_front();
+        }
+        history.push_back(VolumePoint { offset, volume });
+    });
+}
+
+pub fn get() -> Option<SessionStats> {
+    SESSION.lock().ok().and_then(|session| session.clone())
+}
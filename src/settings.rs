@@ -0,0 +1,336 @@
+/*
+  Application wide settings, as opposed to the per-device settings stored alongside each
+  controller. These are loaded once on first access, and saved whenever they're changed.
+
+  The file carries a schema version. When a file from an older version is loaded, it's run
+  through each migration in turn (v1 -> v2 -> ..) as raw JSON before being parsed, so keys can be
+  renamed or reshaped without losing anyone's settings. The original file is copied aside first
+  (settings.json.v<version>) in case a migration gets something wrong.
+
+  A file from a newer version is still loaded, but never written back, as anything this build
+  doesn't know about would be dropped from it.
+*/
+
+use crate::hooks::Hook;
+use crate::integrations::DisplayBackend;
+use crate::integrations::companion::CompanionSettings;
+use crate::integrations::events::EventStreamSettings;
+use crate::integrations::obs::ObsSettings;
+use crate::managers::schedule::{DisplaySleep, ScheduledProfile};
+use crate::recording::MONITOR_INTERVAL;
+use crate::storage;
+use crate::ui::theme::{AccessibilitySettings, ThemeSettings};
+use anyhow::{Result, anyhow, bail};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+pub(crate) const SETTINGS_FILE: &str = "settings.json";
+
+/// The version of the settings file written by this build
+pub const SETTINGS_VERSION: u32 = 2;
+
+// Files written before the version was added are treated as version 0
+const VERSION_KEY: &str = "version";
+
+// Each entry upgrades a file from the version at its index to the next one, so a new version
+// means bumping SETTINGS_VERSION and adding its step to the end of this list.
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize] = [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// The UI scales which can be picked from the settings
+pub const UI_SCALES: [f32; 6] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
+static SETTINGS: LazyLock<RwLock<AppSettings>> = LazyLock::new(|| RwLock::new(AppSettings::load()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// The schema version these settings were written with
+    pub version: u32,
+
+    /// Whether the first run setup has been completed (or skipped)
+    pub onboarded: bool,
+
+    /// The integration a newly seen Mix / Mix Create starts out using for its display
+    pub default_backend: DisplayBackend,
+
+    /// Shows the developer console for each device
+    pub developer_mode: bool,
+
+    /// Dims the screen and slows down updates on the Mix / Mix Create when the battery is low
+    pub battery_saver: bool,
+
+    /// The battery percentage at (or below) which the battery saver kicks in
+    pub battery_threshold: u8,
+
+    /// The language code used for the UI, follows the system language if not set
+    pub language: Option<String>,
+
+    /// How much larger (or smaller) to draw the UI than the display's own scale
+    pub ui_scale: f32,
+
+    /// Dark or light mode, and the accent colour
+    pub theme: ThemeSettings,
+
+    /// Screen reader support, larger widgets and high contrast
+    pub accessibility: AccessibilitySettings,
+
+    /// The connection to OBS Studio, and what its scenes should do
+    pub obs: ObsSettings,
+
+    /// The endpoint control surfaces (such as a Stream Deck) can connect to
+    pub companion: CompanionSettings,
+
+    /// The read-only event stream overlays can connect to
+    pub event_stream: EventStreamSettings,
+
+    /// Commands and actions to run when devices connect, the mic is muted, etc
+    pub hooks: Vec<Hook>,
+
+    /// Suppressor profiles to switch to at different times of the day
+    pub profile_schedule: Vec<ScheduledProfile>,
+
+    /// Collects talk time, dial activity and volume history for the session page
+    pub session_stats: bool,
+
+    /// How often the mic monitor meter on the headphones page updates
+    pub monitor_meter_interval: Duration,
+
+    /// Serials of devices which are left alone, normally because something else manages them
+    pub ignored_devices: Vec<String>,
+
+    /// Shows the level of the main Pipeweaver output along the top of the Mix display
+    pub header_meter: bool,
+
+    /// When the Mix / Mix Create screens turn off for the night
+    pub display_sleep: DisplaySleep,
+
+    // Loaded from a newer version's file, which shouldn't be overwritten
+    #[serde(skip)]
+    read_only: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            onboarded: false,
+            default_backend: DisplayBackend::default(),
+            developer_mode: false,
+            battery_saver: false,
+            battery_threshold: 20,
+            language: None,
+            ui_scale: 1.0,
+            theme: ThemeSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+            obs: ObsSettings::default(),
+            companion: CompanionSettings::default(),
+            event_stream: EventStreamSettings::default(),
+            hooks: vec![],
+            profile_schedule: vec![],
+            session_stats: false,
+            monitor_meter_interval: MONITOR_INTERVAL,
+            ignored_devices: vec![],
+            header_meter: false,
+            display_sleep: DisplaySleep::default(),
+            read_only: false,
+        }
+    }
+}
+
+impl AppSettings {
+    fn load() -> Self {
+        let settings = storage::config_path(SETTINGS_FILE).and_then(Self::load_from);
+        settings.unwrap_or_else(|e| {
+            debug!("Using default application settings: {e}");
+            AppSettings::default()
+        })
+    }
+
+    fn load_from(path: PathBuf) -> Result<Self> {
+        let value: Value = storage::load_json(&path)?;
+        let version = file_version(&value)?;
+        let migrating = version < SETTINGS_VERSION;
+
+        // Keep the original around, so nothing's lost if a migration gets it wrong
+        if migrating && path.exists() {
+            let backup = storage::keep_copy(&path, &format!("v{version}"))?;
+            info!("Migrating settings from v{version}, original kept at {backup:?}");
+        }
+
+        let settings = Self::from_value(value)?;
+        if migrating {
+            settings.save();
+        }
+        Ok(settings)
+    }
+
+    // Parses the settings from a file of any version, migrating older ones
+    fn from_value(mut value: Value) -> Result<Self> {
+        let version = file_version(&value)?;
+        if version > SETTINGS_VERSION {
+            warn!("Settings were written by a newer version (v{version}), changes won't be saved");
+            let mut settings: AppSettings = serde_json::from_value(value)?;
+            settings.read_only = true;
+            return Ok(settings);
+        }
+
+        migrate(&mut value, version)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    fn save(&self) {
+        if self.read_only {
+            debug!("Not saving application settings over a newer version's file");
+            return;
+        }
+
+        let path = storage::config_path(SETTINGS_FILE);
+        if let Err(e) = path.and_then(|path| storage::save_json(path, self)) {
+            warn!("Failed to save application settings: {e}");
+        }
+    }
+}
+
+fn file_version(value: &Value) -> Result<u32> {
+    match value.get(VERSION_KEY) {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(anyhow!("Invalid settings version: {version}")),
+    }
+}
+
+/// Upgrades raw settings from `from` to the current version, one step at a time
+fn migrate(value: &mut Value, from: u32) -> Result<()> {
+    let Some(settings) = value.as_object_mut() else {
+        bail!("Settings file is not a JSON object");
+    };
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        debug!("Migrating settings v{version} -> v{}", version + 1);
+        migration(settings)?;
+        settings.insert(VERSION_KEY.to_string(), Value::from(version + 1));
+    }
+    Ok(())
+}
+
+// Nothing changed shape when the version was introduced, the file just needs stamping
+fn migrate_v0_to_v1(_: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+// Anyone with a settings file from before the setup guide existed doesn't need to see it
+fn migrate_v1_to_v2(settings: &mut Map<String, Value>) -> Result<()> {
+    settings.insert(String::from("onboarded"), Value::Bool(true));
+    Ok(())
+}
+
+/// Returns a copy of the current settings
+pub fn get() -> AppSettings {
+    SETTINGS.read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Applies a change to the settings, and saves them
+pub fn update(change: impl FnOnce(&mut AppSettings)) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        change(&mut settings);
+        settings.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn migrated(mut value: Value, from: u32) -> Map<String, Value> {
+        migrate(&mut value, from).expect("Migration failed");
+        value.as_object().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn every_version_has_a_migration() {
+        assert_eq!(MIGRATIONS.len(), SETTINGS_VERSION as usize);
+    }
+
+    #[test]
+    fn v0_to_v1_only_stamps_the_version() {
+        let mut settings = json!({"developer_mode": true})
+            .as_object()
+            .cloned()
+            .unwrap();
+        migrate_v0_to_v1(&mut settings).unwrap();
+        assert_eq!(
+            settings,
+            json!({"developer_mode": true})
+                .as_object()
+                .cloned()
+                .unwrap()
+        );
+
+        let settings = migrated(json!({"developer_mode": true}), 0);
+        assert_eq!(settings[VERSION_KEY], json!(SETTINGS_VERSION));
+        assert_eq!(settings["developer_mode"], json!(true));
+    }
+
+    #[test]
+    fn v1_to_v2_skips_onboarding() {
+        let mut settings = json!({"version": 1}).as_object().cloned().unwrap();
+        migrate_v1_to_v2(&mut settings).unwrap();
+        assert_eq!(settings["onboarded"], json!(true));
+
+        let settings = migrated(json!({"version": 1, "onboarded": false}), 1);
+        assert_eq!(settings[VERSION_KEY], json!(2));
+        assert_eq!(settings["onboarded"], json!(true));
+    }
+
+    #[test]
+    fn current_version_is_left_alone() {
+        let value = json!({"version": SETTINGS_VERSION, "onboarded": false});
+        let settings = migrated(value.clone(), SETTINGS_VERSION);
+        assert_eq!(Value::Object(settings), value);
+    }
+
+    #[test]
+    fn unversioned_files_are_version_0() {
+        assert_eq!(file_version(&json!({})).unwrap(), 0);
+        assert_eq!(file_version(&json!({"version": 2})).unwrap(), 2);
+    }
+
+    #[test]
+    fn invalid_versions_are_rejected() {
+        assert!(file_version(&json!({"version": "2"})).is_err());
+        assert!(file_version(&json!({"version": -1})).is_err());
+        assert!(file_version(&json!({"version": 1.5})).is_err());
+        assert!(file_version(&json!({"version": u64::MAX})).is_err());
+        assert!(AppSettings::from_value(json!({"version": "x"})).is_err());
+        assert!(AppSettings::from_value(json!([1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn newer_versions_are_loaded_read_only() {
+        let value = json!({
+            "version": SETTINGS_VERSION + 1,
+            "developer_mode": true,
+            "something_new": 42
+        });
+        let settings = AppSettings::from_value(value).unwrap();
+        assert!(settings.developer_mode);
+        assert!(settings.read_only);
+    }
+
+    #[test]
+    fn older_versions_are_migrated_when_parsed() {
+        let settings = AppSettings::from_value(json!({"developer_mode": true})).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert!(settings.onboarded);
+        assert!(settings.developer_mode);
+        assert!(!settings.read_only);
+    }
+}
@@ -0,0 +1,85 @@
+/*
+  Connection statistics for each device (by serial) over the current session. These help to
+  track down flaky cables and hubs, where a device repeatedly drops and reconnects, or fails
+  to respond to messages. Nothing here is persisted.
+*/
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+static STATS: LazyLock<Mutex<HashMap<String, DeviceStats>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Default, Clone)]
+pub struct DeviceStats {
+    pub connects: u32,
+    pub disconnects: u32,
+    pub errors: u32,
+
+    /// Messages the device didn't accept, a subset of the errors
+    pub failed_messages: u32,
+
+    /// When the last keepalive was sent, and whether the device responded
+    pub last_keepalive: Option<(Instant, bool)>,
+
+    connected_since: Option<Instant>,
+    previous_uptime: Duration,
+}
+
+impl DeviceStats {
+    /// The total time this device has been connected during this session
+    pub fn uptime(&self) -> Duration {
+        let current = self.connected_since.map(|since| since.elapsed());
+        self.previous_uptime + current.unwrap_or_default()
+    }
+}
+
+fn with_stats(serial: &str, change: impl FnOnce(&mut DeviceStats)) {
+    if let Ok(mut stats) = STATS.lock() {
+        change(stats.entry(serial.to_string()).or_default());
+    }
+}
+
+pub fn record_connect(serial: &str) {
+    with_stats(serial, |stats| {
+        stats.connects += 1;
+        stats.connected_since.get_or_insert_with(Instant::now);
+    });
+}
+
+pub fn record_disconnect(serial: &str) {
+    with_stats(serial, |stats| {
+        stats.disconnects += 1;
+        if let Some(since) = stats.connected_since.take() {
+            stats.previous_uptime += since.elapsed();
+        }
+    });
+}
+
+pub fn record_error(serial: &str) {
+    with_stats(serial, |stats| stats.errors += 1);
+}
+
+pub fn record_keepalive(serial: &str, success: bool) {
+    with_stats(serial, |stats| {
+        stats.last_keepalive = Some((Instant::now(), success))
+    });
+}
+
+/// Passes a result through, counting it against the device if it's an error
+pub fn track<T, E>(serial: &str, result: Result<T, E>) -> Result<T, E> {
+    if result.is_err() {
+        with_stats(serial, |stats| {
+            stats.errors += 1;
+            stats.failed_messages += 1;
+        });
+    }
+    result
+}
+
+pub fn get(serial: &str) -> Option<DeviceStats> {
+    STATS
+        .lock()
+        .ok()
+        .and_then(|stats| stats.get(serial).cloned())
+}
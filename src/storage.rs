@@ -0,0 +1,196 @@
+/*
+  Helpers for safely persisting files to disk.
+
+  Anything we write goes to a temporary file alongside the target first, which is synced and
+  then renamed over the original. A rename within the same directory is atomic, so if we crash
+  (or the power goes) part way through a write, we're left with either the old file or the new
+  one, never a half written mess.
+
+  For settings, we also keep a few previous versions around (file.json.1, file.json.2, ..), if
+  the primary file fails to load for any reason we'll fall back to the newest backup that works.
+  Settings are saved often, so these are only rotated on the first save of a session, then at
+  most once every BACKUP_INTERVAL, otherwise they'd all end up a moment apart.
+*/
+
+use crate::APP_NAME;
+use anyhow::{Result, anyhow, bail};
+use log::{debug, warn};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use xdg::BaseDirectories;
+
+/// The number of previous versions of a settings file to retain
+pub const BACKUP_COUNT: usize = 3;
+
+/// The least time between one backup of a file and the next
+pub const BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// When each file's backups were last rotated this session
+static LAST_ROTATED: LazyLock<Mutex<HashMap<PathBuf, Instant>>> = LazyLock::new(Default::default);
+
+/// A file writer which only replaces the target when `commit` is called
+pub struct AtomicWriter {
+    path: PathBuf,
+    temp_path: PathBuf,
+    file: Option<File>,
+    backups: usize,
+}
+
+impl AtomicWriter {
+    pub fn create(path: impl AsRef<Path>, backups: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let temp_path = Self::suffixed(&path, "tmp");
+        let file = File::create(&temp_path)?;
+
+        Ok(Self {
+            path,
+            temp_path,
+            file: Some(file),
+            backups,
+        })
+    }
+
+    pub fn commit(mut self) -> Result<()> {
+        let file = self
+            .file
+            .take()
+            .ok_or(anyhow!("Writer already committed"))?;
+        file.sync_all()?;
+        drop(file);
+
+        if self.backups > 0 && self.path.exists() && self.rotation_due() {
+            self.rotate_backups()?;
+        }
+        fs::rename(&self.temp_path, &self.path)?;
+
+        // Make sure the rename itself has hit the disk
+        if let Some(parent) = self.path.parent()
+            && let Ok(dir) = File::open(parent)
+        {
+            let _ = dir.sync_all();
+        }
+        Ok(())
+    }
+
+    fn rotation_due(&self) -> bool {
+        let Ok(mut rotated) = LAST_ROTATED.lock() else {
+            return true;
+        };
+        let due = rotated
+            .get(&self.path)
+            .is_none_or(|last| last.elapsed() >= BACKUP_INTERVAL);
+        if due {
+            rotated.insert(self.path.clone(), Instant::now());
+        }
+        due
+    }
+
+    fn rotate_backups(&self) -> Result<()> {
+        // Shuffle everything up one (dropping the oldest), then copy the current file into
+        // the first slot. We copy rather than rename so the target always exists.
+        for index in (1..self.backups).rev() {
+            let from = Self::suffixed(&self.path, &index.to_string());
+            if from.exists() {
+                fs::rename(&from, Self::suffixed(&self.path, &(index + 1).to_string()))?;
+            }
+        }
+        fs::copy(&self.path, Self::suffixed(&self.path, "1"))?;
+        Ok(())
+    }
+
+    fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{suffix}"));
+        PathBuf::from(name)
+    }
+}
+
+impl Write for AtomicWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.file {
+            Some(file) => file.write(buf),
+            None => Err(std::io::Error::other("Writer already committed")),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for AtomicWriter {
+    fn drop(&mut self) {
+        // If we were never committed, clean up the temporary file
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Locates (and creates the directory for) a file in our config directory
+pub fn config_path(file_name: &str) -> Result<PathBuf> {
+    let xdg_dirs = BaseDirectories::with_prefix(APP_NAME);
+    Ok(xdg_dirs.place_config_file(file_name)?)
+}
+
+/// Locates (and creates the directory for) a file in our cache directory
+pub fn cache_path(file_name: &str) -> Result<PathBuf> {
+    let xdg_dirs = BaseDirectories::with_prefix(APP_NAME);
+    Ok(xdg_dirs.place_cache_file(file_name)?)
+}
+
+/// Copies a file aside (as file.suffix), replacing any previous copy with the same suffix
+pub fn keep_copy(path: impl AsRef<Path>, suffix: &str) -> Result<PathBuf> {
+    let target = AtomicWriter::suffixed(path.as_ref(), suffix);
+    fs::copy(path, &target)?;
+    Ok(target)
+}
+
+pub fn save_json<T: Serialize>(path: impl AsRef<Path>, value: &T) -> Result<()> {
+    let mut writer = BufWriter::new(AtomicWriter::create(path, BACKUP_COUNT)?);
+    serde_json::to_writer_pretty(&mut writer, value)?;
+    writer.flush()?;
+
+    let writer = writer.into_inner().map_err(|e| anyhow!("{}", e.error()))?;
+    writer.commit()
+}
+
+/// Loads a JSON file, falling back to any available backups if the main file is broken
+pub fn load_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+    let path = path.as_ref();
+
+    let mut candidates = vec![path.to_path_buf()];
+    for index in 1..=BACKUP_COUNT {
+        candidates.push(AtomicWriter::suffixed(path, &index.to_string()));
+    }
+
+    for (index, candidate) in candidates.iter().enumerate() {
+        if !candidate.exists() {
+            continue;
+        }
+
+        match File::open(candidate)
+            .map_err(anyhow::Error::from)
+            .and_then(|f| serde_json::from_reader(f).map_err(anyhow::Error::from))
+        {
+            Ok(value) => {
+                if index > 0 {
+                    warn!("Loaded {path:?} from backup {candidate:?}");
+                }
+                return Ok(value);
+            }
+            Err(e) => debug!("Unable to load {candidate:?}: {e}"),
+        }
+    }
+    bail!("No usable copy of {path:?} found")
+}
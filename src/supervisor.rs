@@ -0,0 +1,116 @@
+/*
+  Keeps an eye on the manager threads (the tray and IPC handlers), and restarts any which panic,
+  so the rest of the app doesn't carry on without them unnoticed.
+
+  A thread which keeps on panicking is given up on rather than restarted forever, and is
+  reported to the UI so the user knows something is missing. Threads which finish normally
+  aren't restarted, they've either been told to stop, or decided they can't run here.
+*/
+
+use log::{debug, error, warn};
+use std::any::Any;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often the threads should be checked
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// A thread which panics this many times within the window is given up on
+const MAX_RESTARTS: usize = 3;
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+static FAILED: LazyLock<Mutex<Vec<&'static str>>> = LazyLock::new(Default::default);
+
+/// The threads which have been given up on, for display in the UI
+pub fn failed() -> Vec<&'static str> {
+    FAILED.lock().map(|f| f.clone()).unwrap_or_default()
+}
+
+struct Supervised {
+    name: &'static str,
+    start: Arc<dyn Fn() + Send + Sync>,
+    handle: Option<JoinHandle<()>>,
+    restarts: Vec<Instant>,
+}
+
+#[derive(Default)]
+pub struct Supervisor {
+    threads: Vec<Supervised>,
+}
+
+impl Supervisor {
+    /// Spawns a thread, the function is called again each time it needs restarting, so any
+    /// channels it needs should be cloned inside it.
+    pub fn spawn(&mut self, name: &'static str, start: impl Fn() + Send + Sync + 'static) {
+        let start: Arc<dyn Fn() + Send + Sync> = Arc::new(start);
+        let handle = Self::start(name, &start);
+        self.threads.push(Supervised {
+            name,
+            start,
+            handle,
+            restarts: vec![],
+        });
+    }
+
+    /// Restarts any thread which has panicked, returns true if one has been given up on
+    pub fn check(&mut self) -> bool {
+        let mut gave_up = false;
+        for thread in &mut self.threads {
+            let Some(handle) = thread.handle.take_if(|h| h.is_finished()) else {
+                continue;
+            };
+
+            let name = thread.name;
+            let Err(panic) = handle.join() else {
+                debug!("{name} Thread Finished");
+                continue;
+            };
+            error!("{name} Thread Panicked: {}", panic_message(&*panic));
+
+            thread.restarts.retain(|t| t.elapsed() < RESTART_WINDOW);
+            if thread.restarts.len() >= MAX_RESTARTS {
+                error!("{name} Thread has Panicked {MAX_RESTARTS} times, not restarting");
+                if let Ok(mut failed) = FAILED.lock() {
+                    failed.push(name);
+                }
+                gave_up = true;
+                continue;
+            }
+
+            warn!("Restarting {name} Thread");
+            thread.restarts.push(Instant::now());
+            thread.handle = Self::start(name, &thread.start);
+        }
+        gave_up
+    }
+
+    /// Waits for every thread to finish, they should already have been told to stop
+    pub fn join(self) {
+        for thread in self.threads {
+            if let Some(handle) = thread.handle {
+                let _ = handle.join();
+            }
+        }
+    }
+
+    fn start(name: &'static str, start: &Arc<dyn Fn() + Send + Sync>) -> Option<JoinHandle<()>> {
+        let start = start.clone();
+        let result = thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || start());
+        result
+            .inspect_err(|e| error!("Unable to start {name} Thread: {e}"))
+            .ok()
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        return message.to_string();
+    }
+    panic
+        .downcast_ref::<String>()
+        .cloned()
+        .unwrap_or(String::from("Unknown Error"))
+}
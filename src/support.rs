@@ -0,0 +1,320 @@
+/*
+  Builds a support bundle, a single archive holding everything we'd usually ask for when someone
+  opens an issue: the logs, the saved device settings, and a dump of each device's current state.
+
+  Before anything is written, obvious personal details (home directory, user and host names, and
+  local network addresses) are replaced with placeholders. The app settings (and their backups)
+  also have the OBS password, the companion token and any hook commands blanked out.
+
+  The archive is built here rather than by shelling out to tar and gzip, which aren't always
+  available (such as inside the Flatpak).
+*/
+
+use crate::settings::SETTINGS_FILE;
+use crate::{APP_NAME, APP_TLD, HASH, VERSION, storage};
+use anyhow::Result;
+use directories::UserDirs;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use log::{debug, info, warn};
+use serde_json::Value;
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use xdg::BaseDirectories;
+
+/// A description of a connected device, to be included in the bundle
+pub struct DeviceReport {
+    pub name: String,
+    pub summary: String,
+    pub state: String,
+}
+
+/// Creates the bundle, returning where it was saved
+pub fn create_bundle(devices: Vec<DeviceReport>) -> Result<PathBuf> {
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let name = format!("{APP_NAME}-support-{stamp}");
+
+    let staging = storage::cache_path(&format!("{name}/system.txt"))?;
+    let staging = staging.parent().map(Path::to_path_buf).unwrap_or_default();
+    let config = BaseDirectories::with_prefix(APP_NAME).create_config_directory("")?;
+    let logs = BaseDirectories::with_prefix(APP_TLD).create_data_directory("logs")?;
+
+    let result = populate(&staging, devices, &config, &logs)
+        .and_then(|_| archive(&staging, &bundle_dir(), &name));
+    let _ = fs::remove_dir_all(&staging);
+
+    let path = result?;
+    info!("Support Bundle saved to {path:?}");
+    Ok(path)
+}
+
+fn populate(staging: &Path, devices: Vec<DeviceReport>, config: &Path, logs: &Path) -> Result<()> {
+    let mut system = format!("{APP_NAME} v{VERSION} - {HASH}\n\n");
+    system.push_str(&format!("Kernel: {}\n", command_output("uname", &["-sr"])));
+    for var in ["XDG_CURRENT_DESKTOP", "XDG_SESSION_TYPE"] {
+        let value = env::var(var).unwrap_or_default();
+        system.push_str(&format!("{var}: {value}\n"));
+    }
+    system.push_str(&format!("Flatpak: {}\n", ashpd::is_sandboxed()));
+
+    system.push_str("\nDevices:\n");
+    if devices.is_empty() {
+        system.push_str("  None\n");
+    }
+    for device in &devices {
+        system.push_str(&format!("  {}\n", device.summary));
+    }
+    fs::write(staging.join("system.txt"), redact(&system))?;
+
+    let device_dir = staging.join("devices");
+    fs::create_dir_all(&device_dir)?;
+    for device in devices {
+        let file = device_dir.join(format!("{}.txt", device.name));
+        fs::write(file, redact(&device.state))?;
+    }
+
+    copy_dir(config, &staging.join("config"))?;
+    copy_dir(logs, &staging.join("logs"))?;
+    Ok(())
+}
+
+// Copies all the files in a directory, redacting them on the way
+fn copy_dir(source: &Path, target: &Path) -> Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)?.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        // Older logs are compressed when they're rotated, so they need unpacking to redact
+        let (name, content) = match name.strip_suffix(".gz") {
+            Some(stripped) => (stripped, read_gzip(&path)),
+            None => (name, fs::read(&path).map_err(Into::into)),
+        };
+        let content = match content {
+            Ok(content) => String::from_utf8_lossy(&content).into_owned(),
+            Err(e) => {
+                warn!("Unable to read {path:?} for the Support Bundle: {e}");
+                continue;
+            }
+        };
+
+        // This also catches the backups, which are the settings file name with a suffix
+        let content = if name.starts_with(SETTINGS_FILE) {
+            match redact_settings(&content) {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!("Leaving {name} out of the Support Bundle, unable to redact it: {e}");
+                    continue;
+                }
+            }
+        } else {
+            content
+        };
+        fs::write(target.join(name), redact(&content))?;
+    }
+    Ok(())
+}
+
+// Blanks anything in the app settings which would let someone else in, or which the user may
+// have put private details into
+fn redact_settings(content: &str) -> Result<String> {
+    let mut settings: Value = serde_json::from_str(content)?;
+    for pointer in ["/obs/password", "/companion/token"] {
+        if let Some(Value::String(value)) = settings.pointer_mut(pointer) {
+            *value = String::from("<redacted>");
+        }
+    }
+
+    if let Some(Value::Array(hooks)) = settings.get_mut("hooks") {
+        for hook in hooks {
+            if let Some(Value::String(command)) = hook.pointer_mut("/action/Command") {
+                *command = String::from("<redacted>");
+            }
+        }
+    }
+    Ok(serde_json::to_string_pretty(&settings)?)
+}
+
+// The Downloads folder, or wherever's closest if there isn't one
+fn bundle_dir() -> PathBuf {
+    let dirs = UserDirs::new();
+    dirs.as_ref()
+        .and_then(|d| d.download_dir().or(Some(d.home_dir())))
+        .map(Path::to_path_buf)
+        .unwrap_or_else(env::temp_dir)
+}
+
+fn archive(staging: &Path, target: &Path, name: &str) -> Result<PathBuf> {
+    let path = target.join(format!("{name}.tar.gz"));
+
+    debug!("Archiving {staging:?} to {path:?}");
+    let encoder = GzEncoder::new(File::create(&path)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder.append_dir_all(".", staging)?;
+    builder.into_inner()?.finish()?;
+    Ok(path)
+}
+
+fn read_gzip(path: &Path) -> Result<Vec<u8>> {
+    let mut content = vec![];
+    GzDecoder::new(File::open(path)?).read_to_end(&mut content)?;
+    Ok(content)
+}
+
+fn command_output(command: &str, args: &[&str]) -> String {
+    match Command::new(command).args(args).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        Err(e) => format!("Unable to run {command}: {e}"),
+    }
+}
+
+fn redact(text: &str) -> String {
+    let mut text = text.to_string();
+    if let Some(home) = env::var_os("HOME").map(|h| h.to_string_lossy().into_owned())
+        && home.len() > 1
+    {
+        text = text.replace(&home, "~");
+    }
+
+    // Very short names would replace all sorts of unrelated text
+    let user = env::var("USER").unwrap_or_default();
+    let host = fs::read_to_string("/proc/sys/kernel/hostname").unwrap_or_default();
+    for (value, placeholder) in [(user.trim(), "<user>"), (host.trim(), "<host>")] {
+        if value.len() > 2 {
+            text = text.replace(value, placeholder);
+        }
+    }
+    redact_addresses(&text)
+}
+
+// Replaces local network addresses, others are left alone, as version numbers look the same
+fn redact_addresses(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let token = &rest[..end];
+        let address = token.trim_end_matches('.').parse::<Ipv4Addr>();
+        match address {
+            Ok(ip) if ip.is_private() || ip.is_link_local() => {
+                output.push_str("<address>");
+                output.push_str(&token[token.trim_end_matches('.').len()..]);
+            }
+            _ => output.push_str(token),
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PASSWORD: &str = "obs-password-1234";
+    const TOKEN: &str = "companion-token-5678";
+    const COMMAND: &str = "curl https://example.com/hook?key=abcd";
+
+    // A fresh directory under the system temp dir, removed again by the test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let path = env::temp_dir().join(format!("{APP_NAME}-{name}-{}", stamp.as_nanos()));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn bundle_contents(path: &Path) -> String {
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(path).unwrap()));
+        let mut contents = String::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            entry.read_to_string(&mut contents).unwrap();
+        }
+        contents
+    }
+
+    #[test]
+    fn bundle_leaves_out_secrets() {
+        let root = scratch_dir("support");
+        let (config, logs, staging) = (root.join("config"), root.join("logs"), root.join("stage"));
+        fs::create_dir_all(&config).unwrap();
+        fs::create_dir_all(&logs).unwrap();
+
+        let settings = json!({
+            "obs": {"enabled": true, "address": "localhost:4455", "password": PASSWORD},
+            "companion": {"enabled": true, "port": 4460, "token": TOKEN},
+            "hooks": [
+                {"enabled": true, "event": "MicMuted", "action": {"Command": COMMAND}},
+                {"enabled": true, "event": "MicUnmuted", "action": "TogglePanicMute"},
+            ],
+        });
+        let settings = serde_json::to_string_pretty(&settings).unwrap();
+        fs::write(config.join(SETTINGS_FILE), &settings).unwrap();
+        fs::write(config.join(format!("{SETTINGS_FILE}.v1")), &settings).unwrap();
+        fs::write(logs.join("app.log"), "Started\n").unwrap();
+
+        populate(&staging, vec![], &config, &logs).unwrap();
+        let path = archive(&staging, &root, "bundle").unwrap();
+        let contents = bundle_contents(&path);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(contents.contains("<redacted>"));
+        assert!(contents.contains("TogglePanicMute"));
+        for secret in [PASSWORD, TOKEN, COMMAND] {
+            assert!(!contents.contains(secret), "{secret} found in the bundle");
+        }
+    }
+
+    #[test]
+    fn private_addresses_are_redacted() {
+        let text = "Connected to 192.168.1.20:4455, 10.0.0.1. and 169.254.3.4";
+        assert_eq!(
+            redact_addresses(text),
+            "Connected to <address>:4455, <address>. and <address>"
+        );
+    }
+
+    #[test]
+    fn public_addresses_and_versions_are_kept() {
+        let text = "Resolved 8.8.8.8 with v1.2.3 and 0.4.44";
+        assert_eq!(redact_addresses(text), text);
+    }
+
+    #[test]
+    fn home_directory_is_redacted() {
+        let Ok(home) = env::var("HOME") else {
+            return;
+        };
+        if home.len() > 1 {
+            let text = format!("Loaded {home}/.config/{APP_NAME}/settings.json");
+            assert_eq!(
+                redact(&text),
+                format!("Loaded ~/.config/{APP_NAME}/settings.json")
+            );
+        }
+    }
+
+    #[test]
+    fn unreadable_settings_are_not_redacted() {
+        assert!(redact_settings("not json").is_err());
+    }
+}
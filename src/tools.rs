@@ -0,0 +1,22 @@
+/*
+  Rather than linking their libraries, a few features drive command line tools: pw-dump, wpctl,
+  pw-record and pw-play from PipeWire, and playerctl for the Now Playing header. None of them are
+  hard dependencies, so when one is missing (which is likely inside a Flatpak) the error should
+  name the tool, rather than just saying a file couldn't be found.
+*/
+
+use anyhow::{Error, anyhow};
+use std::io::{self, ErrorKind};
+
+/// Turns a failure to start one of the tools into an error which names it, for `map_err`
+pub fn tool_error(program: &'static str) -> impl FnOnce(io::Error) -> Error {
+    move |e| match e.kind() {
+        ErrorKind::NotFound => anyhow!("{program} isn't installed, or isn't on the PATH"),
+        _ => anyhow!("Unable to run {program}: {e}"),
+    }
+}
+
+/// Whether a failure to start a tool was because it isn't installed
+pub fn is_missing(error: &io::Error) -> bool {
+    error.kind() == ErrorKind::NotFound
+}
@@ -1,17 +1,33 @@
 use crate::device_manager::{DeviceArriveMessage, DeviceDefinition, DeviceMessage};
+use crate::i18n::tr;
 use crate::integrations::pipeweaver::launch_pipeweaver_ui;
+use crate::managers::schedule::{ScheduleTime, scheduled_profile};
+use crate::support::DeviceReport;
 use crate::ui::audio_pages::AudioPage;
 use crate::ui::controller_pages::ControllerPage;
-use crate::ui::pages::{pipeweaver_ui, settings_ui};
-use crate::ui::states::LoadState;
+use crate::ui::log_viewer::LogViewer;
+use crate::ui::onboarding::Onboarding;
+use crate::ui::pages::{SupportBundle, integrations_ui, pipeweaver_ui, settings_ui};
+use crate::ui::shortcuts::{self, Shortcut};
 use crate::ui::states::audio_state::BeacnAudioState;
+use crate::ui::states::conflict;
 use crate::ui::states::controller_state::BeacnControllerState;
+use crate::ui::states::identify;
+use crate::ui::states::{DeviceIdentity, LoadState};
+use crate::ui::theme;
 use crate::ui::widgets::{pipeweaver_button, round_nav_button};
 use crate::ui::{audio_pages, controller_pages};
-use crate::window_handle::App;
+use crate::window_handle::{App, UserEvent, send_user_event};
+use crate::{APP_TITLE, DeviceHealth, ToMainMessages, TrayDevice, TrayDeviceList, safe_mode};
+use crate::{settings, supervisor};
 use beacn_lib::crossbeam::channel;
 use beacn_lib::manager::DeviceType;
-use egui::{Context, FontData, FontDefinitions, FontFamily, FontId, FontTweak, RichText, Ui};
+use beacn_lib::version::VersionNumber;
+use egui::{
+    Color32, Context, FontData, FontDefinitions, FontFamily, FontId, FontTweak, Label, RichText,
+    Sense, Ui,
+};
+use log::warn;
 use std::collections::HashMap;
 
 pub struct BeacnMicApp {
@@ -21,25 +37,49 @@ pub struct BeacnMicApp {
     audio_device_list: HashMap<DeviceDefinition, BeacnAudioState>,
     control_device_list: HashMap<DeviceDefinition, BeacnControllerState>,
 
-    audio_pages: Vec<Box<dyn AudioPage>>,
-    control_pages: Vec<Box<dyn ControllerPage>>,
+    audio_views: HashMap<DeviceDefinition, DevicePages<dyn AudioPage>>,
+    control_views: HashMap<DeviceDefinition, DevicePages<dyn ControllerPage>>,
+
+    // A second device shown alongside the active one
+    pinned_device: Option<DeviceDefinition>,
+
+    // The page each device was on when it went away, by serial, so a reconnect doesn't lose it
+    last_pages: HashMap<String, usize>,
+
+    // Devices being held back by the device manager, as they keep reconnecting
+    flapping: Vec<DeviceType>,
 
     device_recv: channel::Receiver<DeviceMessage>,
-    active_page: usize,
+    main_tx: channel::Sender<ToMainMessages>,
+
+    // The profiles, lighting modes and device health last sent to the tray
+    published_tray: TrayDeviceList,
+    published_health: DeviceHealth,
+    window_title: String,
 
     // We can probably do better here
     mixer_active: bool,
     settings_active: bool,
+    integrations_active: bool,
 
     // Happens on the initial load when selecting default pages
     needs_page_open: bool,
 
     // Toast state for Pipeweaver button
     pipeweaver_toast_timer: Option<std::time::Instant>,
+
+    support_bundle: SupportBundle,
+    log_viewer: LogViewer,
+
+    // The setup guide, shown in place of everything else while it's open
+    onboarding: Option<Onboarding>,
 }
 
 impl BeacnMicApp {
-    pub fn new(device_recv: channel::Receiver<DeviceMessage>) -> Self {
+    pub fn new(
+        device_recv: channel::Receiver<DeviceMessage>,
+        main_tx: channel::Sender<ToMainMessages>,
+    ) -> Self {
         Self {
             device_list: vec![],
             active_device: None,
@@ -47,30 +87,74 @@ impl BeacnMicApp {
             audio_device_list: HashMap::default(),
             control_device_list: HashMap::default(),
 
-            audio_pages: vec![
-                Box::new(audio_pages::config::Configuration::new()),
-                Box::new(audio_pages::lighting::LightingPage::new()),
-                Box::new(audio_pages::link::Linked::new()),
-                Box::new(audio_pages::about::About::new()),
-                Box::new(audio_pages::error::ErrorPage::new()),
-            ],
+            audio_views: HashMap::default(),
+            control_views: HashMap::default(),
 
-            control_pages: vec![
-                Box::new(controller_pages::about::About::new()),
-                Box::new(controller_pages::error::ErrorPage::new()),
-            ],
+            pinned_device: None,
+            last_pages: HashMap::default(),
+            flapping: vec![],
 
             device_recv,
-            active_page: 0,
+            main_tx,
+
+            published_tray: vec![],
+            published_health: DeviceHealth::default(),
+            window_title: String::new(),
 
             mixer_active: false,
             settings_active: false,
+            integrations_active: false,
 
             needs_page_open: false,
 
             pipeweaver_toast_timer: None,
+
+            support_bundle: SupportBundle::default(),
+            log_viewer: LogViewer::default(),
+
+            onboarding: (!settings::get().onboarded).then(Onboarding::default),
         }
     }
+
+    fn audio_pages() -> DevicePages<dyn AudioPage> {
+        DevicePages::new(vec![
+            Box::new(audio_pages::config::Configuration::new()),
+            Box::new(audio_pages::lighting::LightingPage::new()),
+            Box::new(audio_pages::link::Linked::new()),
+            Box::new(audio_pages::about::About::new()),
+            Box::new(audio_pages::developer::DeveloperPage::new()),
+            Box::new(audio_pages::error::ErrorPage::new()),
+        ])
+    }
+
+    fn control_pages() -> DevicePages<dyn ControllerPage> {
+        DevicePages::new(vec![
+            Box::new(controller_pages::display::DisplayPage::new()),
+            Box::new(controller_pages::lighting::LightingPage::new()),
+            Box::new(controller_pages::dials::DialsPage::new()),
+            Box::new(controller_pages::gestures::GesturesPage::new()),
+            Box::new(controller_pages::preview::PreviewPage::new()),
+            Box::new(controller_pages::interactions::InteractionsPage::new()),
+            Box::new(controller_pages::screen_image::ImagePage::new()),
+            Box::new(controller_pages::session::SessionPage::new()),
+            Box::new(controller_pages::about::About::new()),
+            Box::new(controller_pages::developer::DeveloperPage::new()),
+            Box::new(controller_pages::error::ErrorPage::new()),
+        ])
+    }
+}
+
+// Every device has its own set of pages, so two devices can be shown side by side, and each
+// remembers which page it was on.
+struct DevicePages<P: ?Sized> {
+    pages: Vec<Box<P>>,
+    active: usize,
+}
+
+impl<P: ?Sized> DevicePages<P> {
+    fn new(pages: Vec<Box<P>>) -> Self {
+        Self { pages, active: 0 }
+    }
 }
 
 impl App for BeacnMicApp {
@@ -80,17 +164,83 @@ impl App for BeacnMicApp {
     }
 
     fn update(&mut self, ui: &mut Ui) {
+        theme::apply(ui.ctx());
+
         // Grab any device information that's been sent since the last update
         let messages: Vec<DeviceMessage> = self.device_recv.try_iter().collect();
         for message in messages {
             self.handle_device_message(message);
         }
+        self.publish_tray();
+        self.update_title(ui.ctx());
+
+        // Settings changed by dragging a slider are saved once it's let go
+        let dragging = ui.ctx().dragged_id().is_some();
+        for state in self.audio_device_list.values_mut() {
+            state.poll_save(dragging);
+        }
+        for state in self.control_device_list.values_mut() {
+            state.poll_save(dragging);
+        }
+
+        // Keep an eye out for anything else changing the devices' settings
+        for state in self.audio_device_list.values_mut() {
+            state.poll_conflicts();
+            state.poll_headphone_sync();
+        }
+        if !self.audio_device_list.is_empty() {
+            ui.ctx().request_repaint_after(conflict::POLL_INTERVAL);
+        }
+
+        // Keep any identify flashing going
+        let mut identifying = false;
+        for state in self.audio_device_list.values_mut() {
+            state.poll_identify();
+            identifying |= state.identify.is_active();
+        }
+        for state in self.control_device_list.values_mut() {
+            state.poll_identify();
+            identifying |= state.identify.is_active();
+        }
+        if identifying {
+            ui.ctx().request_repaint_after(identify::FLASH_INTERVAL);
+        }
+
+        if safe_mode() {
+            egui::Panel::top("safe_mode").show(ui, |ui| {
+                ui.label(RichText::new(tr!("safe-mode-title")).strong());
+                ui.label(tr!("safe-mode-body"));
+                ui.add_space(5.0);
+            });
+        }
+
+        if !self.flapping.is_empty() {
+            egui::Panel::top("flapping_warning").show(ui, |ui| {
+                draw_flapping_warning(ui, &self.flapping);
+            });
+        }
+
+        let failed = supervisor::failed();
+        if !failed.is_empty() {
+            egui::Panel::top("failed_warning").show(ui, |ui| {
+                draw_failed_warning(ui, &failed);
+            });
+        }
+
+        if let Some(onboarding) = &mut self.onboarding {
+            let mut finished = false;
+            egui::CentralPanel::default().show(ui, |ui| finished = onboarding.ui(ui));
+            if finished {
+                self.onboarding = None;
+            }
+            return;
+        }
 
         // Is our Device List empty?
         if self.device_list.is_empty() {
             egui::CentralPanel::default().show(ui, |ui: &mut Ui| {
                 ui.add_sized(ui.available_size(), |ui: &mut Ui| {
-                    ui.label("No Devices Detected")
+                    ui.label(tr!("app-no-devices"))
                 });
             });
             return;
@@ -101,6 +251,7 @@ impl App for BeacnMicApp {
             self.open_current_page(ui.ctx());
             self.needs_page_open = false;
         }
+        self.handle_shortcuts(ui.ctx());
 
         egui::Panel::left("left_panel")
             .resizable(false)
@@ -108,10 +259,16 @@ impl App for BeacnMicApp {
             .show(ui, |ui| {
                 ui.vertical_centered(|ui| {
                     ui.add_space(5.0);
-                    let pipeweaver_btn = pipeweaver_button(ui, "pipeweaver", self.mixer_active);
+                    let pipeweaver_btn = pipeweaver_button(
+                        ui,
+                        "pipeweaver",
+                        &tr!("nav-pipeweaver"),
+                        self.mixer_active,
+                    );
 
                     if pipeweaver_btn.clicked() {
                         self.settings_active = false;
+                        self.integrations_active = false;
                         let should_toast = launch_pipeweaver_ui();
 
                         if should_toast {
@@ -120,6 +277,7 @@ impl App for BeacnMicApp {
                         } else {
                             self.close_current_page(ui.ctx());
                             self.settings_active = false;
+                            self.integrations_active = false;
                             self.mixer_active = true;
                             self.pipeweaver_toast_timer = None;
                         }
@@ -134,7 +292,7 @@ impl App for BeacnMicApp {
                                 .fixed_pos([pos.x + 8.0, pos.y - 16.0])
                                 .show(ui.ctx(), |ui| {
                                     egui::Frame::popup(ui.style()).show(ui, |ui| {
-                                        ui.label("Pipeweaver UI Launched, check your task bar or app grid for notifications");
+                                        ui.label(tr!("app-pipeweaver-launched"));
                                     });
                                 });
                         } else {
@@ -146,15 +304,31 @@ impl App for BeacnMicApp {
 
                     // Grab the device list, and reorder it based on type
                     let mut devices = self.device_list.clone();
-                    devices.sort_by_key(|d| d.device_type);
+                    devices.sort_by_key(sidebar_order);
                     for device in devices {
                         self.draw_device_buttons(ui, device);
                     }
-                    ui.add_space(ui.available_height() - 55.0);
+                    ui.add_space(ui.available_height() - 100.0);
                     ui.separator();
-                    if round_nav_button(ui, "gear", self.settings_active).clicked() {
+                    if round_nav_button(
+                        ui,
+                        "plug",
+                        &tr!("nav-integrations"),
+                        self.integrations_active,
+                    )
+                    .clicked()
+                    {
                         self.close_current_page(ui.ctx());
                         self.mixer_active = false;
+                        self.settings_active = false;
+                        self.integrations_active = true;
+                    }
+                    if round_nav_button(ui, "gear", &tr!("nav-settings"), self.settings_active)
+                        .clicked()
+                    {
+                        self.close_current_page(ui.ctx());
+                        self.mixer_active = false;
+                        self.integrations_active = false;
                         self.settings_active = true;
                     }
                 });
@@ -164,18 +338,66 @@ impl App for BeacnMicApp {
         self.render_content(ui);
     }
 
+    fn apply_suppressor_profile(&mut self, serial: Option<&str>, name: &str) {
+        for (definition, state) in &mut self.audio_device_list {
+            if serial.is_some_and(|serial| serial != definition.device_info.serial) {
+                continue;
+            }
+
+            let profiles = &state.saved_settings.suppressor_profiles;
+            if profiles.iter().any(|p| p.name == name)
+                && let Err(e) = state.apply_suppressor_profile(name)
+            {
+                warn!("Failed to apply Suppressor Profile {name}: {e}");
+            }
+        }
+    }
+
+    fn apply_lighting_mode(&mut self, serial: &str, name: &str) {
+        let device = self.audio_device_list.iter_mut();
+        let device = device.find(|(d, _)| d.device_info.serial == serial);
+        if let Some((_, state)) = device
+            && let Err(e) = state.apply_lighting_mode(name)
+        {
+            warn!("Failed to apply Lighting Mode {name}: {e}");
+        }
+
+        // The window may not be drawing, so let the tray know straight away
+        self.publish_tray();
+    }
+
+    fn apply_mic_colour(&mut self, colour: [u8; 3]) {
+        for (_, state) in &mut self.audio_device_list {
+            if let Err(e) = state.set_primary_colour(colour) {
+                warn!("Failed to set the Primary Colour: {e}");
+            }
+        }
+    }
+
     fn should_close(&mut self) -> bool {
         // TODO: This should prompt the user, and / or check the settings
         true
     }
 
     fn on_close(&mut self) {
-        for audio_page in &mut self.audio_pages {
+        for audio_page in self.audio_views.values_mut().flat_map(|v| &mut v.pages) {
             audio_page.on_close();
         }
 
-        for controller_pages in &mut self.control_pages {
-            controller_pages.on_close();
+        for controller_page in self.control_views.values_mut().flat_map(|v| &mut v.pages) {
+            controller_page.on_close();
+        }
+        self.log_viewer.clear_cache();
+
+        // Nothing will move the flashing along while there's no window, so finish it now
+        for state in self.audio_device_list.values_mut() {
+            state.stop_identify();
+            state.end_page_bypass();
+            state.poll_save(false);
+        }
+        for state in self.control_device_list.values_mut() {
+            state.stop_identify();
+            state.poll_save(false);
         }
     }
 
@@ -189,6 +411,13 @@ impl App for BeacnMicApp {
                     // Store the Device, and the device state
                     self.device_list.push(definition.clone());
                     self.audio_device_list.insert(definition.clone(), state);
+                    if let Some(name) = scheduled_profile(ScheduleTime::now()) {
+                        let serial = definition.device_info.serial.clone();
+                        self.apply_suppressor_profile(Some(&serial), &name);
+                    }
+                    let mut view = Self::audio_pages();
+                    view.active = self.last_page(&definition, view.pages.len());
+                    self.audio_views.insert(definition.clone(), view);
 
                     if self.active_device.is_none() {
                         self.active_device = Some(definition);
@@ -199,6 +428,9 @@ impl App for BeacnMicApp {
                     let state = BeacnControllerState::load_settings(definition.clone(), sender);
                     self.device_list.push(definition.clone());
                     self.control_device_list.insert(definition.clone(), state);
+                    let mut view = Self::control_pages();
+                    view.active = self.last_page(&definition, view.pages.len());
+                    self.control_views.insert(definition.clone(), view);
 
                     if self.active_device.is_none() {
                         self.active_device = Some(definition);
@@ -216,125 +448,197 @@ impl App for BeacnMicApp {
                         DeviceType::BeacnMic | DeviceType::BeacnStudio => {
                             // Remove this device from the audio device list
                             self.audio_device_list.remove(definition);
+                            if let Some(view) = self.audio_views.remove(definition)
+                                && !view.pages[view.active].show_on_error()
+                            {
+                                let serial = definition.device_info.serial.clone();
+                                self.last_pages.insert(serial, view.active);
+                            }
                         }
                         DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
                             self.control_device_list.remove(definition);
+                            if let Some(view) = self.control_views.remove(definition)
+                                && !view.pages[view.active].show_on_error()
+                            {
+                                let serial = definition.device_info.serial.clone();
+                                self.last_pages.insert(serial, view.active);
+                            }
                         }
                     }
 
                     // Now remove it from the main device list
                     self.device_list.retain(|d| d != definition);
 
+                    if self.pinned_device.as_ref() == Some(definition) {
+                        self.pinned_device = None;
+                    }
+
                     // Make sure we're not referencing this device as active
                     if let Some(active_device) = &self.active_device
                         && active_device == definition
                     {
-                        if self.device_list.is_empty() {
-                            self.active_device = None;
-                        } else {
-                            // Reset the State, set the active device as the first device
-                            let first = self.device_list.first().unwrap();
-                            self.active_device = Some(first.clone());
-                            self.active_page = 0;
+                        // Prefer a device which isn't already pinned alongside
+                        let pinned = self.pinned_device.as_ref();
+                        let next = self.device_list.iter().find(|d| Some(*d) != pinned);
+                        let next = next.or(self.device_list.first()).cloned();
+                        if next.is_some() && next == self.pinned_device {
+                            self.pinned_device = None;
                         }
+                        self.active_device = next;
                     }
                 }
             }
+            DeviceMessage::Flapping(device_type, true) => self.flapping.push(device_type),
+            DeviceMessage::Flapping(device_type, false) => {
+                if let Some(position) = self.flapping.iter().position(|t| *t == device_type) {
+                    self.flapping.remove(position);
+                }
+            }
         }
+        self.publish_tray();
     }
 }
 
 impl BeacnMicApp {
+    // Lets the tray know which profiles and lighting modes are available, if they've changed
+    fn publish_tray(&mut self) {
+        let mut devices: TrayDeviceList = self
+            .audio_device_list
+            .iter()
+            .filter(|(_, state)| state.device_state.state == LoadState::Running)
+            .map(|(definition, state)| {
+                let profiles = state.saved_settings.suppressor_profiles.iter();
+                let modes = state.lighting_modes().into_iter();
+                TrayDevice {
+                    serial: definition.device_info.serial.clone(),
+                    suppressor_profiles: profiles.map(|p| p.name.clone()).collect(),
+                    lighting_modes: modes.map(|(n, active)| (n.to_string(), active)).collect(),
+                }
+            })
+            .collect();
+        devices.sort();
+
+        if devices != self.published_tray {
+            self.published_tray = devices.clone();
+            let _ = self.main_tx.send(ToMainMessages::TrayDevices(devices));
+        }
+
+        // Anything which isn't loading or running has failed in some way
+        let audio = self
+            .audio_device_list
+            .iter()
+            .map(|(d, s)| (d, s.device_state.state));
+        let control = self
+            .control_device_list
+            .iter()
+            .map(|(d, s)| (d, s.device_state.state));
+        let states: Vec<_> = audio.chain(control).collect();
+
+        let mut errors: Vec<_> = states
+            .iter()
+            .filter(|(_, state)| !matches!(state, LoadState::Loading | LoadState::Running))
+            .map(|(definition, _)| definition.device_info.serial.clone())
+            .collect();
+        errors.sort();
+
+        let health = DeviceHealth {
+            connected: states.len(),
+            errors,
+        };
+        if health != self.published_health {
+            self.published_health = health.clone();
+            let _ = self.main_tx.send(ToMainMessages::DeviceHealth(health));
+        }
+    }
+
     fn draw_device_buttons(&mut self, ui: &mut Ui, device: DeviceDefinition) {
         if self.device_list.is_empty() || self.active_device.is_none() {
             return;
         }
 
         let active_device = &self.active_device.clone().unwrap();
+        let pinned = self.pinned_device.as_ref() == Some(&device);
+
+        // A device's page is highlighted if it's visible in either the main or pinned view
+        let visible = (*active_device == device || pinned) && !self.overlay_active();
+
+        ui.add_space(5.0);
+        let mut label = RichText::new(self.device_name(&device));
+        if let Some([red, green, blue]) = self.device_identity(&device).accent {
+            label = label.color(Color32::from_rgb(red, green, blue));
+        }
+        let name = ui.add(Label::new(label).sense(Sense::click()));
+        if name.on_hover_text(tr!("nav-identify-hover")).clicked() {
+            self.identify(&device);
+        }
+
+        let mut action = None;
         match device.device_type {
             // These are probably going to eventually need to be separated, when
             // Studio Link support is added, a new page will be needed
             DeviceType::BeacnMic | DeviceType::BeacnStudio => {
                 let device_state = self.audio_device_list.get(&device).unwrap();
-                ui.add_space(5.0);
-
-                match device.device_type {
-                    DeviceType::BeacnMic => ui.label("Mic"),
-                    DeviceType::BeacnStudio => ui.label("Studio"),
-                    _ => ui.label("ERROR"),
-                };
-
-                let mut action = None;
-                let audio_pages = self.audio_pages.iter_mut().enumerate();
-                for (index, page) in audio_pages {
-                    let selected = *active_device == device
-                        && self.active_page == index
-                        && !self.settings_active
-                        && !self.mixer_active;
-                    let error = matches!(
-                        device_state.device_state.state,
-                        LoadState::Error | LoadState::PermissionDenied | LoadState::ResourceBusy
-                    );
+                let view = self.audio_views.get(&device).unwrap();
+                let error = matches!(
+                    device_state.device_state.state,
+                    LoadState::Error | LoadState::PermissionDenied | LoadState::ResourceBusy
+                );
 
+                for (index, page) in view.pages.iter().enumerate() {
+                    let selected = visible && view.active == index;
                     if page.show_on_error() == error
                         && (page.should_show(device_state))
-                        && round_nav_button(ui, page.icon(), selected).clicked()
+                        && round_nav_button(ui, page.icon(), &page.title(), selected).clicked()
                         && !selected
                     {
-                        action = Some((device.clone(), index));
+                        action = Some(index);
                     }
                 }
-
-                if let Some((device, index)) = action {
-                    self.change_page(ui.ctx(), device, index);
-                }
-
-                ui.add_space(5.0);
-                ui.separator();
             }
             DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
                 // This is identical to the above, except with a BeacnControllerState and ControllerPages
                 // There's probably a way we can simplify this :p
                 let device_state = self.control_device_list.get(&device).unwrap();
-                ui.add_space(5.0);
-
-                match device.device_type {
-                    DeviceType::BeacnMix => ui.label("Mix"),
-                    DeviceType::BeacnMixCreate => ui.label("Mix Create"),
-                    _ => ui.label("ERROR"),
-                };
+                let view = self.control_views.get(&device).unwrap();
+                let error = matches!(
+                    device_state.device_state.state,
+                    LoadState::Error | LoadState::PermissionDenied | LoadState::ResourceBusy
+                );
 
-                let mut action = None;
-                let control_pages = self.control_pages.iter().enumerate();
-                for (index, page) in control_pages {
-                    let selected = *active_device == device
-                        && self.active_page == index
-                        && !self.settings_active
-                        && !self.mixer_active;
-
-                    let error = matches!(
-                        device_state.device_state.state,
-                        LoadState::Error | LoadState::PermissionDenied | LoadState::ResourceBusy
-                    );
+                for (index, page) in view.pages.iter().enumerate() {
+                    let selected = visible && view.active == index;
                     if page.show_on_error() == error
-                        && round_nav_button(ui, page.icon(), selected).clicked()
+                        && page.should_show(device_state)
+                        && round_nav_button(ui, page.icon(), &page.title(), selected).clicked()
                         && !selected
                     {
-                        action = Some((device.clone(), index));
+                        action = Some(index);
                     }
                 }
+            }
+        }
 
-                if let Some((device, index)) = action {
-                    self.change_page(ui.ctx(), device, index);
-                }
+        if let Some(index) = action {
+            self.change_page(ui.ctx(), device.clone(), index);
+        }
 
-                ui.add_space(5.0);
-                ui.separator();
+        // Pinning keeps a device on screen next to whichever device is selected
+        if device != *active_device {
+            let (label, hover) = match pinned {
+                true => (tr!("nav-unpin"), tr!("nav-unpin-hover")),
+                false => (tr!("nav-pin"), tr!("nav-pin-hover")),
+            };
+            if ui.small_button(label).on_hover_text(hover).clicked() {
+                self.toggle_pin(ui.ctx(), device);
             }
         }
+
+        ui.add_space(5.0);
+        ui.separator();
     }
+
     fn render_content(&mut self, ui: &mut Ui) {
-        if self.active_device.is_none() && !self.settings_active && !self.mixer_active {
+        if self.active_device.is_none() && !self.overlay_active() {
             return;
         }
 
@@ -347,20 +651,52 @@ impl BeacnMicApp {
 
         if self.settings_active {
             egui::CentralPanel::default().show(ui, |ui| {
-                settings_ui(ui);
+                let audio = &self.audio_device_list;
+                let control = &self.control_device_list;
+                let onboarding = &mut self.onboarding;
+                settings_ui(
+                    ui,
+                    &mut self.support_bundle,
+                    &mut self.log_viewer,
+                    onboarding,
+                    || device_reports(audio, control),
+                );
             });
             return;
         }
 
+        if self.integrations_active {
+            egui::CentralPanel::default().show(ui, |ui| {
+                integrations_ui(ui);
+            });
+            return;
+        }
+
+        if let Some(pinned) = self.pinned_device.clone() {
+            egui::Panel::right("pinned_device")
+                .resizable(true)
+                .default_size(ui.available_width() / 2.0)
+                .show(ui, |ui| {
+                    self.render_device(ui, &pinned);
+                });
+        }
+
         let definition = &self.active_device.clone().unwrap();
-        match definition.device_type {
+        egui::CentralPanel::default().show(ui, |ui| {
+            self.render_device(ui, definition);
+        });
+    }
+
+    fn render_device(&mut self, ui: &mut Ui, definition: &DeviceDefinition) {
+        // Both views may show the same page, so keep their IDs apart
+        ui.push_id(definition, |ui| match definition.device_type {
             DeviceType::BeacnMic | DeviceType::BeacnStudio => {
                 // Get the Settings from the definition
                 let settings = self.audio_device_list.get_mut(definition);
-                if settings.is_none() {
+                let view = self.audio_views.get_mut(definition);
+                let (Some(settings), Some(view)) = (settings, view) else {
                     return;
-                }
-                let settings = settings.unwrap();
+                };
 
                 let error = matches!(
                     settings.device_state.state,
@@ -369,81 +705,406 @@ impl BeacnMicApp {
 
                 // Are we in an error state, if so, show the error
                 if error {
-                    let position = self.audio_pages.iter().position(|p| p.show_on_error());
+                    let position = view.pages.iter().position(|p| p.show_on_error());
                     if let Some(page) = position {
-                        self.active_page = page;
+                        view.active = page;
+                    }
+                } else {
+                    // The device has recovered from its error (such as resending what failed)
+                    if view.pages[view.active].show_on_error() {
+                        let pages = view.pages.iter();
+                        let position =
+                            pages.position(|p| !p.show_on_error() && p.should_show(settings));
+                        view.active = position.unwrap_or_default();
+                        self.needs_page_open = true;
+                    }
+
+                    if settings.conflicts.detected() {
+                        draw_conflict_warning(ui, settings);
+                    }
+
+                    let page = &view.pages[view.active];
+                    let unavailable = settings.unavailable_features(|m| page.covers(m));
+                    if !unavailable.is_empty() {
+                        draw_firmware_notice(ui, &unavailable);
                     }
                 }
 
-                egui::CentralPanel::default().show(ui, |ui| {
-                    self.audio_pages[self.active_page].ui(ui, settings);
-                });
+                view.pages[view.active].ui(ui, settings);
             }
             DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
                 let settings = self.control_device_list.get_mut(definition);
-                if settings.is_none() {
+                let view = self.control_views.get_mut(definition);
+                let (Some(settings), Some(view)) = (settings, view) else {
                     return;
-                }
+                };
 
-                let settings = settings.unwrap();
-                egui::CentralPanel::default().show(ui, |ui| {
-                    self.control_pages[self.active_page].ui(ui, settings);
-                });
+                view.pages[view.active].ui(ui, settings);
             }
-        }
+        });
     }
 
     fn change_page(&mut self, ctx: &Context, device: DeviceDefinition, page: usize) {
+        // The pinned device changes page in place, without touching the main view
+        if self.pinned_device.as_ref() == Some(&device) {
+            self.close_page(ctx, &device);
+            self.set_page(&device, page);
+            self.open_page(ctx, &device);
+
+            if self.overlay_active() {
+                self.settings_active = false;
+                self.integrations_active = false;
+                self.mixer_active = false;
+                self.open_current_page(ctx);
+            }
+            return;
+        }
+
         self.close_current_page(ctx);
 
         // Update state
+        self.set_page(&device, page);
         self.active_device = Some(device);
-        self.active_page = page;
         self.settings_active = false;
+        self.integrations_active = false;
         self.mixer_active = false;
 
         self.open_current_page(ctx);
     }
 
-    fn close_current_page(&mut self, ctx: &Context) {
-        if self.settings_active || self.mixer_active {
-            return;
+    // Flashes the device's lights, so it can be told apart from others of the same type
+    fn identify(&mut self, device: &DeviceDefinition) {
+        match device.device_type {
+            DeviceType::BeacnMic | DeviceType::BeacnStudio => {
+                if let Some(state) = self.audio_device_list.get_mut(device) {
+                    state.identify.start();
+                }
+            }
+            DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
+                if let Some(state) = self.control_device_list.get_mut(device) {
+                    state.identify.start();
+                }
+            }
+        }
+    }
+
+    fn device_identity(&self, device: &DeviceDefinition) -> DeviceIdentity {
+        let identity = match device.device_type {
+            DeviceType::BeacnMic | DeviceType::BeacnStudio => self
+                .audio_device_list
+                .get(device)
+                .map(|s| s.saved_settings.identity.clone()),
+            DeviceType::BeacnMix | DeviceType::BeacnMixCreate => self
+                .control_device_list
+                .get(device)
+                .map(|s| s.saved_settings.identity.clone()),
+        };
+        identity.unwrap_or_default()
+    }
+
+    // The user's name for the device if they've given it one, otherwise its type
+    fn device_name(&self, device: &DeviceDefinition) -> String {
+        if let Some(nickname) = self.device_identity(device).nickname {
+            return nickname.trim().to_string();
         }
+        match device.device_type {
+            DeviceType::BeacnMic => tr!("nav-mic"),
+            DeviceType::BeacnStudio => tr!("nav-studio"),
+            DeviceType::BeacnMix => tr!("nav-mix"),
+            DeviceType::BeacnMixCreate => tr!("nav-mix-create"),
+        }
+    }
+
+    // Names the window after the device being shown, so it can be picked out in a task switcher
+    fn update_title(&mut self, ctx: &Context) {
+        let title = match &self.active_device {
+            Some(device) if !self.overlay_active() => {
+                format!("{APP_TITLE} - {}", self.device_name(device))
+            }
+            _ => APP_TITLE.to_string(),
+        };
+
+        if title != self.window_title {
+            self.window_title = title.clone();
+            send_user_event(ctx, UserEvent::SetTitle(title));
+        }
+    }
+
+    fn handle_shortcuts(&mut self, ctx: &Context) {
+        for shortcut in shortcuts::pressed(ctx) {
+            match shortcut {
+                Shortcut::Page(index) => {
+                    let Some(device) = self.active_device.clone() else {
+                        continue;
+                    };
+                    if let Some(page) = self.visible_pages(&device).get(index) {
+                        self.change_page(ctx, device, *page);
+                    }
+                }
+                Shortcut::NextDevice => self.cycle_device(ctx, 1),
+                Shortcut::PreviousDevice => self.cycle_device(ctx, -1),
+                Shortcut::ToggleFx => {
+                    let device = self.active_device.as_ref();
+                    if let Some(state) = device.and_then(|d| self.audio_device_list.get_mut(d))
+                        && let Err(e) = state.toggle_fx()
+                    {
+                        warn!("Failed to toggle processing: {e}");
+                    }
+                }
+                Shortcut::MicMute => {
+                    let _ = self.main_tx.send(ToMainMessages::TogglePanicMute);
+                }
+            }
+        }
+    }
+
+    // The indexes of the pages shown in the sidebar for a device, in the order they're shown
+    fn visible_pages(&self, device: &DeviceDefinition) -> Vec<usize> {
+        let is_error = |state: LoadState| {
+            matches!(
+                state,
+                LoadState::Error | LoadState::PermissionDenied | LoadState::ResourceBusy
+            )
+        };
+
+        match device.device_type {
+            DeviceType::BeacnMic | DeviceType::BeacnStudio => {
+                let state = self.audio_device_list.get(device);
+                let view = self.audio_views.get(device);
+                let (Some(state), Some(view)) = (state, view) else {
+                    return vec![];
+                };
+                let error = is_error(state.device_state.state);
+                let pages = view.pages.iter().enumerate();
+                pages
+                    .filter(|(_, p)| p.show_on_error() == error && p.should_show(state))
+                    .map(|(index, _)| index)
+                    .collect()
+            }
+            DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
+                let state = self.control_device_list.get(device);
+                let view = self.control_views.get(device);
+                let (Some(state), Some(view)) = (state, view) else {
+                    return vec![];
+                };
+                let error = is_error(state.device_state.state);
+                let pages = view.pages.iter().enumerate();
+                pages
+                    .filter(|(_, p)| p.show_on_error() == error && p.should_show(state))
+                    .map(|(index, _)| index)
+                    .collect()
+            }
+        }
+    }
 
-        let Some(device) = &self.active_device else {
+    // Moves to the next (or previous) device in the sidebar, skipping any pinned device
+    fn cycle_device(&mut self, ctx: &Context, step: isize) {
+        let mut devices: Vec<DeviceDefinition> = self
+            .device_list
+            .iter()
+            .filter(|d| Some(*d) != self.pinned_device.as_ref())
+            .cloned()
+            .collect();
+        devices.sort_by_key(sidebar_order);
+        if devices.is_empty() {
             return;
+        }
+
+        let current = devices
+            .iter()
+            .position(|d| Some(d) == self.active_device.as_ref());
+        let next = match current {
+            Some(index) => (index as isize + step).rem_euclid(devices.len() as isize) as usize,
+            None => 0,
+        };
+
+        let device = devices[next].clone();
+        let page = match device.device_type {
+            DeviceType::BeacnMic | DeviceType::BeacnStudio => {
+                self.audio_views.get(&device).map(|v| v.active)
+            }
+            DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
+                self.control_views.get(&device).map(|v| v.active)
+            }
         };
+        self.change_page(ctx, device, page.unwrap_or_default());
+    }
+
+    // Whether one of the app wide pages is showing, rather than a device
+    fn overlay_active(&self) -> bool {
+        self.settings_active || self.integrations_active || self.mixer_active
+    }
+
+    fn toggle_pin(&mut self, ctx: &Context, device: DeviceDefinition) {
+        if let Some(pinned) = self.pinned_device.take() {
+            self.close_page(ctx, &pinned);
+            if pinned == device {
+                return;
+            }
+        }
+
+        self.open_page(ctx, &device);
+        self.pinned_device = Some(device);
+    }
 
+    // The page a device was last on before it disconnected, or the first page if it's new
+    fn last_page(&self, device: &DeviceDefinition, pages: usize) -> usize {
+        let page = self.last_pages.get(&device.device_info.serial).copied();
+        page.filter(|page| *page < pages).unwrap_or_default()
+    }
+
+    fn set_page(&mut self, device: &DeviceDefinition, page: usize) {
         match device.device_type {
             DeviceType::BeacnMic | DeviceType::BeacnStudio => {
-                self.audio_pages[self.active_page].on_page_close(ctx);
+                if let Some(view) = self.audio_views.get_mut(device) {
+                    view.active = page;
+                }
             }
             DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
-                self.control_pages[self.active_page].on_page_close(ctx);
+                if let Some(view) = self.control_views.get_mut(device) {
+                    view.active = page;
+                }
             }
         }
     }
 
-    fn open_current_page(&mut self, ctx: &Context) {
-        if self.settings_active || self.mixer_active {
+    fn close_current_page(&mut self, ctx: &Context) {
+        if self.overlay_active() {
             return;
         }
 
-        let Some(device) = &self.active_device else {
+        if let Some(device) = self.active_device.clone() {
+            self.close_page(ctx, &device);
+        }
+    }
+
+    fn open_current_page(&mut self, ctx: &Context) {
+        if self.overlay_active() {
             return;
-        };
+        }
+
+        if let Some(device) = self.active_device.clone() {
+            self.open_page(ctx, &device);
+        }
+    }
 
+    fn close_page(&mut self, ctx: &Context, device: &DeviceDefinition) {
         match device.device_type {
             DeviceType::BeacnMic | DeviceType::BeacnStudio => {
-                self.audio_pages[self.active_page].on_page_open(ctx);
+                if let Some(view) = self.audio_views.get_mut(device) {
+                    view.pages[view.active].on_page_close(ctx);
+                }
+
+                // Anything the page was listening to the raw mic for won't be finished now
+                if let Some(state) = self.audio_device_list.get_mut(device) {
+                    state.end_page_bypass();
+                }
+            }
+            DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
+                if let Some(view) = self.control_views.get_mut(device) {
+                    view.pages[view.active].on_page_close(ctx);
+                }
+            }
+        }
+    }
+
+    fn open_page(&mut self, ctx: &Context, device: &DeviceDefinition) {
+        match device.device_type {
+            DeviceType::BeacnMic | DeviceType::BeacnStudio => {
+                if let Some(view) = self.audio_views.get_mut(device) {
+                    view.pages[view.active].on_page_open(ctx);
+                }
             }
             DeviceType::BeacnMix | DeviceType::BeacnMixCreate => {
-                self.control_pages[self.active_page].on_page_open(ctx);
+                if let Some(view) = self.control_views.get_mut(device) {
+                    view.pages[view.active].on_page_open(ctx);
+                }
             }
         }
     }
 }
 
+// Devices are grouped by type, then by serial, so several of the same type always appear in the
+// same order, no matter which was found first
+fn sidebar_order(device: &DeviceDefinition) -> (DeviceType, String) {
+    (device.device_type, device.device_info.serial.clone())
+}
+
+fn draw_flapping_warning(ui: &mut Ui, flapping: &[DeviceType]) {
+    for device_type in flapping {
+        let name = match device_type {
+            DeviceType::BeacnMic => tr!("device-mic"),
+            DeviceType::BeacnStudio => tr!("device-studio"),
+            DeviceType::BeacnMix => tr!("device-mix"),
+            DeviceType::BeacnMixCreate => tr!("device-mix-create"),
+        };
+        ui.label(RichText::new(tr!("flapping-title", device = name)).strong());
+    }
+    ui.label(tr!("flapping-body"));
+    ui.add_space(5.0);
+}
+
+fn draw_failed_warning(ui: &mut Ui, failed: &[&str]) {
+    for name in failed {
+        ui.label(RichText::new(tr!("failed-title", name = *name)).strong());
+    }
+    ui.label(tr!("failed-body"));
+    ui.add_space(5.0);
+}
+
+fn draw_conflict_warning(ui: &mut Ui, state: &mut BeacnAudioState) {
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        ui.label(RichText::new(tr!("conflict-title")).strong());
+        ui.label(tr!("conflict-body"));
+        ui.label(tr!("conflict-guidance"));
+        if ui.button(tr!("conflict-dismiss")).clicked() {
+            state.conflicts.dismiss();
+        }
+    });
+    ui.add_space(5.0);
+}
+
+fn device_reports(
+    audio: &HashMap<DeviceDefinition, BeacnAudioState>,
+    control: &HashMap<DeviceDefinition, BeacnControllerState>,
+) -> Vec<DeviceReport> {
+    let report = |definition: &DeviceDefinition, state: String| {
+        let info = &definition.device_info;
+        DeviceReport {
+            name: format!("{:?}-{}", definition.device_type, info.serial),
+            summary: format!(
+                "{:?} {} - Firmware {} - {:?}",
+                definition.device_type, info.serial, info.version, definition.state
+            ),
+            state,
+        }
+    };
+
+    let audio = audio
+        .iter()
+        .map(|(d, state)| report(d, format!("{state:#?}")));
+    let control = control
+        .iter()
+        .map(|(d, state)| report(d, format!("{state:#?}")));
+    audio.chain(control).collect()
+}
+
+fn draw_firmware_notice(ui: &mut Ui, features: &[(String, VersionNumber)]) {
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        ui.label(RichText::new(tr!("firmware-title")).strong());
+
+        let features: Vec<String> = features
+            .iter()
+            .map(|(feature, version)| tr!("firmware-feature", feature = feature, version = version))
+            .collect();
+        ui.label(features.join(", "));
+        ui.label(tr!("firmware-hint"));
+    });
+    ui.add_space(5.0);
+}
+
 pub fn setup_fonts(ctx: &egui::Context) {
     let mut fonts = FontDefinitions::default();
 
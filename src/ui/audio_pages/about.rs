@@ -1,16 +1,65 @@
+use crate::i18n::tr;
 use crate::ui::SVG;
 use crate::ui::audio_pages::AudioPage;
+use crate::ui::shared_pages::identity::identity_ui;
+use crate::ui::shared_pages::ignore::ignore_ui;
+use crate::ui::shared_pages::stats::display_stats;
 use crate::ui::states::audio_state::BeacnAudioState;
 use beacn_lib::audio::messages::Message;
 use beacn_lib::audio::messages::headphones::Headphones;
 use beacn_lib::manager::DeviceType;
-use egui::{Button, Image, RichText, Ui, vec2};
+use egui::{Button, CollapsingHeader, Grid, Image, RichText, ScrollArea, Ui, vec2};
+use log::warn;
+use std::path::PathBuf;
 
-pub struct About {}
+pub struct About {
+    // Where the change log was last exported to, or why it couldn't be
+    export: Option<Result<PathBuf, String>>,
+}
 
 impl About {
     pub fn new() -> Self {
-        Self {}
+        Self { export: None }
+    }
+
+    fn audit_ui(&mut self, ui: &mut Ui, state: &BeacnAudioState) {
+        if state.audit.is_empty() {
+            ui.label(tr!("about-audit-empty"));
+            return;
+        }
+
+        ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+            Grid::new("audit_trail").striped(true).show(ui, |ui| {
+                ui.label(RichText::new(tr!("about-audit-time")).strong());
+                ui.label(RichText::new(tr!("about-audit-setting")).strong());
+                ui.label(RichText::new(tr!("about-audit-change")).strong());
+                ui.end_row();
+
+                // Newest first, as that's usually what's being looked for
+                for entry in state.audit.entries().rev() {
+                    ui.label(entry.time.format("%H:%M:%S").to_string());
+                    ui.label(format!("{}: {}", entry.subsystem, entry.setting));
+                    ui.label(format!("{} → {}", entry.old, entry.new));
+                    ui.end_row();
+                }
+            });
+        });
+
+        ui.add_space(5.0);
+        if ui.button(tr!("about-audit-export")).clicked() {
+            let serial = &state.device_definition.device_info.serial;
+            self.export = Some(state.audit.export_csv(serial).map_err(|e| e.to_string()));
+        }
+        match &self.export {
+            Some(Ok(path)) => {
+                let path = path.display().to_string();
+                ui.label(tr!("about-audit-exported", path = path));
+            }
+            Some(Err(e)) => {
+                ui.label(tr!("about-audit-export-failed", error = e));
+            }
+            None => {}
+        }
     }
 }
 
@@ -19,6 +68,10 @@ impl AudioPage for About {
         "gear"
     }
 
+    fn title(&self) -> String {
+        tr!("page-about")
+    }
+
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
         let device_type = state.device_definition.device_type;
         let serial_text = state.device_definition.device_info.serial.clone();
@@ -28,14 +81,14 @@ impl AudioPage for About {
         let location_text = format!("{}:{}", location.bus_number, location.address);
 
         match device_type {
-            DeviceType::BeacnMic => ui.heading("About Beacn Mic"),
-            DeviceType::BeacnStudio => ui.heading("About Beacn Studio"),
+            DeviceType::BeacnMic => ui.heading(tr!("about-mic")),
+            DeviceType::BeacnStudio => ui.heading(tr!("about-studio")),
             _ => ui.heading("ERROR"),
         };
 
-        let location = RichText::new("USB Location: ").strong().size(14.0);
-        let serial = RichText::new("Serial: ").strong().size(14.0);
-        let version = RichText::new("Version: ").strong().size(14.0);
+        let location = RichText::new(tr!("about-location")).strong().size(14.0);
+        let serial = RichText::new(tr!("about-serial")).strong().size(14.0);
+        let version = RichText::new(tr!("about-version")).strong().size(14.0);
 
         let location_value = RichText::new(location_text).size(14.0);
         let serial_value = RichText::new(serial_text).size(14.0);
@@ -55,6 +108,26 @@ impl AudioPage for About {
             ui.label(version);
             ui.label(version_value)
         });
+        display_stats(ui, &state.device_definition);
+        if let Some(identity) = identity_ui(ui, &state.saved_settings.identity) {
+            state.set_identity(identity);
+        }
+        ignore_ui(ui, &state.device_definition);
+
+        ui.add_space(10.0);
+        if ui
+            .button(tr!("about-resync"))
+            .on_hover_text(tr!("about-resync-hover"))
+            .clicked()
+            && let Err(e) = state.resync(None)
+        {
+            warn!("Unable to resync from device: {e}");
+        }
+
+        ui.add_space(10.0);
+        CollapsingHeader::new(RichText::new(tr!("about-audit")).strong().size(14.0))
+            .id_salt("about_audit")
+            .show(ui, |ui| self.audit_ui(ui, state));
 
         ui.add_space(10.0);
         ui.separator();
@@ -62,8 +135,10 @@ impl AudioPage for About {
 
         if let Some(inner) = &state.headphones.studio_driverless {
             let mut inner = *inner;
-            const LABEL: &str = "Enable Port 2 Compliancy Mode";
-            if ui.checkbox(&mut inner, LABEL).changed() {
+            if ui
+                .checkbox(&mut inner, tr!("about-port2-compliancy"))
+                .changed()
+            {
                 state.headphones.studio_driverless = Some(inner);
 
                 let message = Message::Headphones(Headphones::StudioDriverless(inner));
@@ -73,9 +148,8 @@ impl AudioPage for About {
 
         if let Some(inner) = &state.headphones.mic_class_compliant {
             let mut inner = *inner;
-            const LABEL: &str = "Enable Mic Compliancy Mode";
             ui.horizontal(|ui| {
-                if ui.checkbox(&mut inner, LABEL).changed() {
+                if ui.checkbox(&mut inner, tr!("about-mic-compliancy")).changed() {
                     state.headphones.mic_class_compliant = Some(inner);
 
                     let message = Message::Headphones(Headphones::MicClassCompliant(inner));
@@ -98,11 +172,11 @@ impl AudioPage for About {
                         ));
                     }
 
-                    info_button.on_hover_text("Learn more about Mic Compliancy Mode");
+                    info_button.on_hover_text(tr!("about-mic-compliancy-hover"));
                 }
             });
             ui.add_space(5.0);
-            ui.label("Note: When changing this value, the Beacn Mic will reboot.");
+            ui.label(tr!("about-mic-compliancy-note"));
         }
     }
 }
@@ -1,16 +1,24 @@
+use crate::i18n::tr;
 use crate::ui::audio_pages::AudioPage;
 use crate::ui::audio_pages::config_pages::ConfigPage;
+use crate::ui::audio_pages::config_pages::autoeq::AutoEqPage;
+use crate::ui::audio_pages::config_pages::compare::ComparePage;
 use crate::ui::audio_pages::config_pages::compressor::CompressorPage;
 use crate::ui::audio_pages::config_pages::expander::ExpanderPage;
+use crate::ui::audio_pages::config_pages::gain_wizard::GainWizardPage;
+use crate::ui::audio_pages::config_pages::headphone_eq::HeadphoneEqPage;
 use crate::ui::audio_pages::config_pages::headphones::HeadphonesPage;
 use crate::ui::audio_pages::config_pages::mic_equaliser::MicEqualiser;
 use crate::ui::audio_pages::config_pages::mic_setup::MicSetupPage;
 use crate::ui::audio_pages::config_pages::suppressor::NoiseSuppressionPage;
+use crate::ui::audio_pages::config_pages::windows_import::WindowsImportPage;
 use crate::ui::states::audio_state::BeacnAudioState;
-use crate::ui::widgets::draw_range;
+use crate::ui::widgets::{apply_summary, draw_range};
+use beacn_lib::audio::messages::Message;
 use beacn_lib::audio::messages::headphones::HPMicOutputGain;
 use beacn_lib::types::HasRange;
-use egui::{Ui, vec2};
+use egui::{Align, Layout, Ui, vec2};
+use log::warn;
 
 pub struct Configuration {
     equaliser: Box<MicEqualiser>,
@@ -26,11 +34,16 @@ impl Configuration {
 
             selected_tab: 0,
             tab_pages: vec![
-                Box::new(MicSetupPage),
-                Box::new(NoiseSuppressionPage),
+                Box::new(MicSetupPage::default()),
+                Box::new(NoiseSuppressionPage::new()),
                 Box::new(ExpanderPage),
                 Box::new(CompressorPage),
-                Box::new(HeadphonesPage),
+                Box::new(HeadphonesPage::default()),
+                Box::new(HeadphoneEqPage::new()),
+                Box::new(AutoEqPage::new()),
+                Box::new(ComparePage::new()),
+                Box::new(GainWizardPage::new()),
+                Box::new(WindowsImportPage::new()),
             ],
         }
     }
@@ -41,6 +54,10 @@ impl AudioPage for Configuration {
         "mic"
     }
 
+    fn title(&self) -> String {
+        tr!("page-audio")
+    }
+
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
         let eq_size = vec2(ui.available_width(), ui.available_height() - 240.);
         ui.allocate_ui_with_layout(eq_size, *ui.layout(), |ui| {
@@ -51,6 +68,7 @@ impl AudioPage for Configuration {
 
         ui.separator();
 
+        let mut snapshot_changed = false;
         ui.vertical(|ui| {
             // Bottom half
             let total_available = ui.available_size();
@@ -67,10 +85,17 @@ impl AudioPage for Configuration {
                                 if ui
                                     .selectable_label(self.selected_tab == i, page.title())
                                     .clicked()
+                                    && self.selected_tab != i
                                 {
+                                    // Pages listening to the raw mic stop when they're left
+                                    state.end_page_bypass();
                                     self.selected_tab = i;
                                 }
                             }
+
+                            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                snapshot_changed = Self::draw_snapshots(ui, state);
+                            });
                         });
 
                         ui.separator();
@@ -91,15 +116,55 @@ impl AudioPage for Configuration {
                         ui,
                         &mut gain.output_gain,
                         HPMicOutputGain::range(),
-                        "Output Gain",
+                        &tr!("config-output-gain"),
                         "dB",
                     ) {}
                 });
             });
         });
+
+        if snapshot_changed {
+            // The EQ caches its curve, so make sure it picks up the new values
+            self.equaliser.clear();
+        }
+    }
+
+    fn covers(&self, message: &Message) -> bool {
+        !matches!(message, Message::Lighting(_))
     }
 
     fn on_close(&mut self) {
         self.equaliser.clear();
     }
 }
+
+impl Configuration {
+    fn draw_snapshots(ui: &mut Ui, state: &mut BeacnAudioState) -> bool {
+        let mut changed = false;
+
+        // Right to left, so B goes first
+        for (slot, label) in [(1, "B"), (0, "A")] {
+            let active = state.snapshots.active == Some(slot);
+            let hover = match state.snapshots.slots[slot] {
+                Some(_) => tr!("config-snapshot-switch", label = label),
+                None => tr!("config-snapshot-store", label = label),
+            };
+
+            let note = tr!("config-snapshot-note");
+            let response = ui
+                .selectable_label(active, label)
+                .on_hover_text(format!("{hover}\n\n{note}"));
+            if response.clicked() && !active {
+                if let Err(e) = state.switch_snapshot(slot) {
+                    warn!("Failed to switch to Snapshot {label}: {e}");
+                }
+                changed = true;
+            }
+        }
+        ui.label(tr!("config-snapshot"));
+        if let Some(progress) = &state.last_apply {
+            apply_summary(ui, progress);
+        }
+        changed
+    }
+}
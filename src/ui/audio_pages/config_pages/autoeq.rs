@@ -0,0 +1,200 @@
+use crate::i18n::tr;
+use crate::ui::audio_pages::config_pages::ConfigPage;
+use crate::ui::audio_pages::equaliser::eq_autoeq::{HeadphoneFit, ParametricProfile};
+use crate::ui::audio_pages::equaliser::eq_common::{EqGeometry, MAX_GAIN, MIN_GAIN};
+use crate::ui::states::audio_state::BeacnAudioState;
+use crate::ui::theme;
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::headphone_eq::HPEQType::{Bass, Mids, Treble};
+use beacn_lib::audio::messages::headphone_eq::{HPEQValue, HeadphoneEQ};
+use egui::{CornerRadius, Pos2, RichText, ScrollArea, Sense, Stroke, TextEdit, Ui, vec2};
+use log::warn;
+use std::fs;
+
+// Imports AutoEQ parametric profiles, and applies the closest match to the Headphone EQ
+#[derive(Default)]
+pub struct AutoEqPage {
+    path: String,
+    text: String,
+    result: Option<Result<(ParametricProfile, HeadphoneFit), String>>,
+}
+
+impl AutoEqPage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn import(&mut self) {
+        self.result = match ParametricProfile::parse(&self.text) {
+            Ok(profile) => {
+                let fit = HeadphoneFit::fit(&profile);
+                Some(Ok((profile, fit)))
+            }
+            Err(e) => Some(Err(e.to_string())),
+        };
+    }
+
+    fn draw_input(&mut self, ui: &mut Ui) {
+        ui.label(tr!("autoeq-intro"));
+        ui.horizontal(|ui| {
+            let path = TextEdit::singleline(&mut self.path).hint_text(tr!("autoeq-path-hint"));
+            ui.add(path);
+            if ui.button(tr!("autoeq-load")).clicked() {
+                match fs::read_to_string(self.path.trim()) {
+                    Ok(text) => {
+                        self.text = text;
+                        self.import();
+                    }
+                    Err(e) => self.result = Some(Err(tr!("autoeq-read-failed", error = e))),
+                }
+            }
+        });
+
+        ui.add_space(5.0);
+        ScrollArea::vertical().show(ui, |ui| {
+            let editor = TextEdit::multiline(&mut self.text)
+                .code_editor()
+                .desired_rows(6)
+                .desired_width(f32::INFINITY);
+            if ui.add(editor).changed() {
+                self.import();
+            }
+        });
+    }
+
+    fn draw_result(&self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        let Some(result) = &self.result else {
+            ui.label(tr!("autoeq-nothing-imported"));
+            return;
+        };
+
+        let (profile, fit) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let colour = theme::current(ui.ctx()).failure;
+                ui.label(RichText::new(e).color(colour));
+                return;
+            }
+        };
+
+        ui.label(tr!(
+            "autoeq-summary",
+            filters = profile.filters.len(),
+            preamp = format!("{:.1}", profile.preamp)
+        ));
+        ui.add_space(5.0);
+        ui.label(tr!(
+            "autoeq-bass",
+            value = format!("{:.1}", fit.values[Bass])
+        ));
+        ui.label(tr!(
+            "autoeq-mids",
+            value = format!("{:.1}", fit.values[Mids])
+        ));
+        ui.label(tr!(
+            "autoeq-treble",
+            value = format!("{:.1}", fit.values[Treble])
+        ));
+        ui.add_space(5.0);
+
+        let error = format!("{:.1}", fit.error);
+        ui.label(tr!("autoeq-fit-error", error = error))
+            .on_hover_text(tr!("autoeq-fit-error-hover"));
+        ui.add_space(5.0);
+
+        if ui.button(tr!("autoeq-apply")).clicked() {
+            for (band, value) in fit.values {
+                let message = Message::HeadphoneEQ(HeadphoneEQ::Amount(band, HPEQValue(value)));
+                if let Err(e) = state.handle_message(message) {
+                    warn!("Failed to apply Headphone EQ: {e}");
+                }
+            }
+        }
+        if !state.headphones.fx_enabled {
+            ui.label(tr!("autoeq-fx-disabled"));
+        }
+    }
+
+    fn draw_preview(&self, ui: &mut Ui) {
+        let size = ui.available_size();
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        let plot_rect = EqGeometry::plot_rect(rect);
+
+        let theme = theme::current(ui.ctx());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(plot_rect, CornerRadius::default(), theme.graph_background);
+
+        let zero = EqGeometry::db_to_y(0.0, plot_rect);
+        let grid = Stroke::new(1.0, theme.graph_grid);
+        painter.line_segment(
+            [
+                Pos2::new(plot_rect.min.x, zero),
+                Pos2::new(plot_rect.max.x, zero),
+            ],
+            grid,
+        );
+
+        let Some(Ok((_, fit))) = &self.result else {
+            return;
+        };
+
+        // The overall level isn't part of the fit, so line the target up with the result
+        let offset = fit.offset();
+
+        let points = |values: &[f32], offset: f32| -> Vec<Pos2> {
+            fit.frequencies
+                .iter()
+                .zip(values)
+                .map(|(&freq, &db)| {
+                    let db = (db - offset).clamp(MIN_GAIN, MAX_GAIN);
+                    let x = EqGeometry::freq_to_x(freq as u32, plot_rect);
+                    Pos2::new(x, EqGeometry::db_to_y(db, plot_rect))
+                })
+                .collect()
+        };
+
+        painter.line(
+            points(&fit.target, offset),
+            Stroke::new(1.5, theme.graph_text),
+        );
+        painter.line(points(&fit.fitted, 0.0), Stroke::new(2.0, theme.highlight));
+
+        let legend = plot_rect.left_top() + vec2(8.0, 6.0);
+        painter.text(
+            legend,
+            egui::Align2::LEFT_TOP,
+            tr!("autoeq-legend-profile"),
+            egui::FontId::proportional(12.0),
+            theme.graph_text,
+        );
+        painter.text(
+            legend + vec2(0.0, 14.0),
+            egui::Align2::LEFT_TOP,
+            tr!("autoeq-legend-eq"),
+            egui::FontId::proportional(12.0),
+            theme.highlight,
+        );
+    }
+}
+
+impl ConfigPage for AutoEqPage {
+    fn title(&self) -> String {
+        tr!("config-tab-autoeq")
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        ui.horizontal_top(|ui| {
+            ui.vertical(|ui| {
+                ui.set_width(320.0);
+                self.draw_input(ui);
+            });
+            ui.separator();
+            ui.vertical(|ui| {
+                ui.set_width(160.0);
+                self.draw_result(ui, state);
+            });
+            ui.separator();
+            self.draw_preview(ui);
+        });
+    }
+}
@@ -0,0 +1,227 @@
+use crate::i18n::tr;
+use crate::recording::{find_source, play_clip, record_clip};
+use crate::runtime;
+use crate::storage;
+use crate::ui::audio_pages::config_pages::ConfigPage;
+use crate::ui::states::audio_state::BeacnAudioState;
+use anyhow::{Result, anyhow};
+use egui::{Button, ProgressBar, Ui};
+use log::{debug, warn};
+use std::path::PathBuf;
+use std::process::Child;
+use std::time::{Duration, Instant};
+
+const CLIP_LENGTH: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Clip {
+    Bypassed,
+    Processed,
+}
+
+impl Clip {
+    fn label(&self) -> String {
+        match self {
+            Clip::Bypassed => tr!("compare-before"),
+            Clip::Processed => tr!("compare-after"),
+        }
+    }
+
+    fn path(&self, serial: &str) -> Result<PathBuf> {
+        let name = match self {
+            Clip::Bypassed => "bypassed",
+            Clip::Processed => "processed",
+        };
+        storage::cache_path(&format!("{serial}-{name}.wav"))
+    }
+}
+
+struct Recording {
+    clip: Clip,
+    started: Instant,
+
+    // Resolves to the PipeWire source which was recorded
+    result: oneshot::Receiver<Result<String>>,
+}
+
+// Records the mic with all processing bypassed, then again with the current settings, so the
+// two can be compared by ear.
+#[derive(Default)]
+pub struct ComparePage {
+    recording: Option<Recording>,
+    recorded: bool,
+    playing: Option<(Clip, Child)>,
+    error: Option<String>,
+}
+
+impl ComparePage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ConfigPage for ComparePage {
+    fn title(&self) -> String {
+        tr!("config-tab-compare")
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        self.check_bypass(state);
+        self.poll_recording(state);
+        self.poll_playback();
+        if self.playing.is_some() {
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+        }
+
+        ui.label(tr!("compare-intro", seconds = CLIP_LENGTH.as_secs()));
+        ui.add_space(5.0);
+
+        match &self.recording {
+            Some(recording) => {
+                let progress =
+                    recording.started.elapsed().as_secs_f32() / CLIP_LENGTH.as_secs_f32();
+                ui.label(tr!("compare-recording", clip = recording.clip.label()));
+                ui.add(ProgressBar::new(progress.min(1.0)).desired_width(300.0));
+                ui.label(tr!("compare-keep-speaking"));
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+            }
+            None => {
+                if ui.button(tr!("compare-record")).clicked() {
+                    self.start(state);
+                }
+            }
+        }
+
+        if self.recorded && self.recording.is_none() {
+            ui.add_space(10.0);
+            for clip in [Clip::Bypassed, Clip::Processed] {
+                ui.horizontal(|ui| {
+                    let playing = self.playing.as_ref().is_some_and(|(c, _)| *c == clip);
+                    let label = match playing {
+                        true => tr!("compare-stop"),
+                        false => tr!("compare-play"),
+                    };
+                    if ui.add_sized([60., 20.], Button::new(label)).clicked() {
+                        self.stop_playback();
+                        if !playing {
+                            self.play(clip, state);
+                        }
+                    }
+                    ui.label(clip.label());
+                });
+            }
+        }
+
+        if let Some(error) = &self.error {
+            ui.add_space(5.0);
+            ui.label(tr!("compare-failed", error = error));
+        }
+    }
+}
+
+impl ComparePage {
+    fn start(&mut self, state: &mut BeacnAudioState) {
+        self.stop_playback();
+        self.recorded = false;
+        self.error = None;
+
+        match state.begin_page_bypass() {
+            Ok(()) => self.record(Clip::Bypassed, None, state),
+            Err(e) => self.fail(e.to_string(), state),
+        }
+    }
+
+    fn record(&mut self, clip: Clip, source: Option<String>, state: &mut BeacnAudioState) {
+        let serial = state.device_definition.device_info.serial.clone();
+        let path = match clip.path(&serial) {
+            Ok(path) => path,
+            Err(e) => return self.fail(e.to_string(), state),
+        };
+
+        let (tx, rx) = oneshot::channel();
+        runtime().spawn_blocking(move || {
+            let result = || -> Result<String> {
+                let source = match source {
+                    Some(source) => source,
+                    None => find_source(&serial)?,
+                };
+                record_clip(&source, &path, CLIP_LENGTH)?;
+                Ok(source)
+            };
+            let _ = tx.send(result());
+        });
+
+        self.recording = Some(Recording {
+            clip,
+            started: Instant::now(),
+            result: rx,
+        });
+    }
+
+    fn poll_recording(&mut self, state: &mut BeacnAudioState) {
+        let Some(recording) = &self.recording else {
+            return;
+        };
+
+        let result = match recording.result.try_recv() {
+            Ok(result) => result,
+            Err(oneshot::TryRecvError::Empty) => return,
+            Err(oneshot::TryRecvError::Disconnected) => Err(anyhow!(tr!("compare-stopped"))),
+        };
+
+        let clip = recording.clip;
+        self.recording = None;
+        match result {
+            Ok(source) if clip == Clip::Bypassed => {
+                state.end_page_bypass();
+                self.record(Clip::Processed, Some(source), state);
+            }
+            Ok(_) => self.recorded = true,
+            Err(e) => self.fail(e.to_string(), state),
+        }
+    }
+
+    // The processing is put back if the page is left part way through the bypassed clip, in
+    // which case that recording can't be used
+    fn check_bypass(&mut self, state: &BeacnAudioState) {
+        let bypassed = self
+            .recording
+            .as_ref()
+            .is_some_and(|r| r.clip == Clip::Bypassed);
+        if bypassed && !state.is_page_bypassed() {
+            debug!("Comparison recording was interrupted");
+            self.recording = None;
+        }
+    }
+
+    fn fail(&mut self, error: String, state: &mut BeacnAudioState) {
+        warn!("Unable to record comparison: {error}");
+        state.end_page_bypass();
+        self.recording = None;
+        self.error = Some(error);
+    }
+
+    fn play(&mut self, clip: Clip, state: &BeacnAudioState) {
+        let serial = &state.device_definition.device_info.serial;
+        match clip.path(serial).and_then(|path| play_clip(&path)) {
+            Ok(child) => self.playing = Some((clip, child)),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    fn poll_playback(&mut self) {
+        if let Some((_, child)) = &mut self.playing {
+            match child.try_wait() {
+                Ok(None) => {}
+                _ => self.playing = None,
+            }
+        }
+    }
+
+    fn stop_playback(&mut self) {
+        if let Some((_, mut child)) = self.playing.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
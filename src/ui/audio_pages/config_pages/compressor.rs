@@ -1,3 +1,7 @@
+use crate::i18n::tr;
+use crate::ui::audio_pages::config_pages::dynamics::{
+    compressor_presets_ui, link_compressor, link_modes_ui,
+};
 use crate::ui::audio_pages::config_pages::{ConfigPage, map_to_range};
 use crate::ui::states::audio_state::BeacnAudioState;
 use crate::ui::widgets::{draw_range, get_slider, toggle_button};
@@ -13,13 +17,14 @@ use strum::IntoEnumIterator;
 pub struct CompressorPage;
 
 impl ConfigPage for CompressorPage {
-    fn title(&self) -> &'static str {
-        "Compressor"
+    fn title(&self) -> String {
+        tr!("config-tab-compressor")
     }
 
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
         ui.push_id("compressor", |ui| {
             let mut comp = state.compressor;
+            let before = comp.values[Simple];
 
             // Extract out all the current values
             let values = &mut comp.values[comp.mode];
@@ -54,6 +59,11 @@ impl ConfigPage for CompressorPage {
                             }
                         });
 
+                        ui.add_space(5.);
+                        link_modes_ui(ui, state);
+                        ui.add_space(5.);
+                        compressor_presets_ui(ui, state);
+
                         ui.add_space(5.);
 
                         // Threshold is a common slider
@@ -124,6 +134,7 @@ impl ConfigPage for CompressorPage {
                     state.handle_message(message).expect("Failed");
                 }
             });
+            link_compressor(state, before);
         });
     }
 }
@@ -0,0 +1,211 @@
+// Starting points for the compressor and expander, and keeping their Simple and Advanced modes
+// in step. Presets set everything the mode has in one go, the Advanced values are always set so
+// switching over afterwards shows the full preset.
+//
+// With the modes linked, changes made in Simple mode are copied over to Advanced, so anyone who
+// starts simple and later wants more control picks up where they left off. The attack and release
+// aren't part of Simple mode, so they're left as they are.
+
+use crate::i18n::tr;
+use crate::ui::states::audio_state::{BeacnAudioState, CompressorValue, ExpanderValue};
+use crate::ui::widgets::toggle_button;
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::compressor::{
+    Compressor, CompressorMode, CompressorRatio, CompressorThreshold,
+};
+use beacn_lib::audio::messages::expander::{
+    Expander, ExpanderMode, ExpanderRatio, ExpanderThreshold,
+};
+use beacn_lib::types::{MakeUpGain, TimeFrame};
+use egui::Ui;
+use log::warn;
+
+#[derive(Debug, Copy, Clone)]
+struct DynamicsPreset {
+    name: fn() -> String,
+    hover: fn() -> String,
+    threshold: i8,
+    ratio: f32,
+    attack: u16,
+    release: u16,
+
+    // Only used by the compressor
+    makeup: f32,
+}
+
+const COMPRESSOR_PRESETS: [DynamicsPreset; 3] = [
+    DynamicsPreset {
+        name: || tr!("dynamics-broadcast"),
+        hover: || tr!("dynamics-compressor-broadcast-hover"),
+        threshold: -24,
+        ratio: 4.0,
+        attack: 5,
+        release: 120,
+        makeup: 6.0,
+    },
+    DynamicsPreset {
+        name: || tr!("dynamics-voiceover"),
+        hover: || tr!("dynamics-compressor-voiceover-hover"),
+        threshold: -18,
+        ratio: 2.5,
+        attack: 15,
+        release: 200,
+        makeup: 3.0,
+    },
+    DynamicsPreset {
+        name: || tr!("dynamics-raw"),
+        hover: || tr!("dynamics-compressor-raw-hover"),
+        threshold: -8,
+        ratio: 1.5,
+        attack: 30,
+        release: 300,
+        makeup: 0.0,
+    },
+];
+
+const EXPANDER_PRESETS: [DynamicsPreset; 3] = [
+    DynamicsPreset {
+        name: || tr!("dynamics-broadcast"),
+        hover: || tr!("dynamics-expander-broadcast-hover"),
+        threshold: -45,
+        ratio: 3.0,
+        attack: 5,
+        release: 150,
+        makeup: 0.0,
+    },
+    DynamicsPreset {
+        name: || tr!("dynamics-voiceover"),
+        hover: || tr!("dynamics-expander-voiceover-hover"),
+        threshold: -55,
+        ratio: 2.0,
+        attack: 10,
+        release: 250,
+        makeup: 0.0,
+    },
+    DynamicsPreset {
+        name: || tr!("dynamics-raw"),
+        hover: || tr!("dynamics-expander-raw-hover"),
+        threshold: -70,
+        ratio: 1.2,
+        attack: 20,
+        release: 400,
+        makeup: 0.0,
+    },
+];
+
+pub(crate) fn compressor_presets_ui(ui: &mut Ui, state: &mut BeacnAudioState) {
+    if let Some(preset) = presets_ui(ui, &COMPRESSOR_PRESETS) {
+        let mut messages = vec![];
+        for mode in [CompressorMode::Simple, CompressorMode::Advanced] {
+            messages.extend([
+                Compressor::Enabled(mode, true),
+                Compressor::Threshold(mode, CompressorThreshold(preset.threshold as f32)),
+                Compressor::Ratio(mode, CompressorRatio(preset.ratio)),
+                Compressor::MakeupGain(mode, MakeUpGain(preset.makeup)),
+            ]);
+        }
+        let mode = CompressorMode::Advanced;
+        messages.extend([
+            Compressor::Attack(mode, TimeFrame(preset.attack as f32)),
+            Compressor::Release(mode, TimeFrame(preset.release as f32)),
+        ]);
+        send(state, messages.into_iter().map(Message::Compressor));
+    }
+}
+
+pub(crate) fn expander_presets_ui(ui: &mut Ui, state: &mut BeacnAudioState) {
+    if let Some(preset) = presets_ui(ui, &EXPANDER_PRESETS) {
+        let mut messages = vec![];
+        for mode in [ExpanderMode::Simple, ExpanderMode::Advanced] {
+            messages.extend([
+                Expander::Enabled(mode, true),
+                Expander::Threshold(mode, ExpanderThreshold(preset.threshold as f32)),
+                Expander::Ratio(mode, ExpanderRatio(preset.ratio)),
+            ]);
+        }
+        let mode = ExpanderMode::Advanced;
+        messages.extend([
+            Expander::Attack(mode, TimeFrame(preset.attack as f32)),
+            Expander::Release(mode, TimeFrame(preset.release as f32)),
+        ]);
+        send(state, messages.into_iter().map(Message::Expander));
+    }
+}
+
+fn presets_ui(ui: &mut Ui, presets: &[DynamicsPreset]) -> Option<DynamicsPreset> {
+    let mut chosen = None;
+    ui.label(tr!("dynamics-presets"));
+    ui.horizontal(|ui| {
+        for preset in presets {
+            let button = toggle_button(ui, false, &(preset.name)());
+            if ui
+                .add_sized([68., 20.], button)
+                .on_hover_text((preset.hover)())
+                .clicked()
+            {
+                chosen = Some(*preset);
+            }
+        }
+    });
+    chosen
+}
+
+pub(crate) fn link_modes_ui(ui: &mut Ui, state: &mut BeacnAudioState) {
+    let mut linked = state.saved_settings.link_dynamics;
+    if ui
+        .checkbox(&mut linked, tr!("dynamics-link"))
+        .on_hover_text(tr!("dynamics-link-hover"))
+        .changed()
+    {
+        state.set_link_dynamics(linked);
+    }
+}
+
+/// Copies any change made to the Simple compressor since `before` over to Advanced
+pub(crate) fn link_compressor(state: &mut BeacnAudioState, before: CompressorValue) {
+    let after = state.compressor.values[CompressorMode::Simple];
+    if !state.saved_settings.link_dynamics || state.compressor.mode != CompressorMode::Simple {
+        return;
+    }
+
+    let mode = CompressorMode::Advanced;
+    let mut messages = vec![];
+    if after.threshold != before.threshold {
+        let threshold = CompressorThreshold(after.threshold as f32);
+        messages.push(Compressor::Threshold(mode, threshold));
+    }
+    if after.ratio != before.ratio {
+        messages.push(Compressor::Ratio(mode, CompressorRatio(after.ratio)));
+    }
+    if after.makeup != before.makeup {
+        messages.push(Compressor::MakeupGain(mode, MakeUpGain(after.makeup)));
+    }
+    send(state, messages.into_iter().map(Message::Compressor));
+}
+
+/// Copies any change made to the Simple expander since `before` over to Advanced
+pub(crate) fn link_expander(state: &mut BeacnAudioState, before: ExpanderValue) {
+    let after = state.expander.values[ExpanderMode::Simple];
+    if !state.saved_settings.link_dynamics || state.expander.mode != ExpanderMode::Simple {
+        return;
+    }
+
+    let mode = ExpanderMode::Advanced;
+    let mut messages = vec![];
+    if after.threshold != before.threshold {
+        let threshold = ExpanderThreshold(after.threshold as f32);
+        messages.push(Expander::Threshold(mode, threshold));
+    }
+    if after.ratio != before.ratio {
+        messages.push(Expander::Ratio(mode, ExpanderRatio(after.ratio)));
+    }
+    send(state, messages.into_iter().map(Message::Expander));
+}
+
+fn send(state: &mut BeacnAudioState, messages: impl Iterator<Item = Message>) {
+    for message in messages {
+        if let Err(e) = state.handle_message(message) {
+            warn!("Failed to apply dynamics change: {e}");
+        }
+    }
+}
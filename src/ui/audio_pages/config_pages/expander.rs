@@ -1,3 +1,7 @@
+use crate::i18n::tr;
+use crate::ui::audio_pages::config_pages::dynamics::{
+    expander_presets_ui, link_expander, link_modes_ui,
+};
 use crate::ui::audio_pages::config_pages::{ConfigPage, map_to_range};
 use crate::ui::states::audio_state::BeacnAudioState;
 use crate::ui::widgets::{get_slider, toggle_button};
@@ -13,13 +17,14 @@ use strum::IntoEnumIterator;
 pub struct ExpanderPage;
 
 impl ConfigPage for ExpanderPage {
-    fn title(&self) -> &'static str {
-        "Expander"
+    fn title(&self) -> String {
+        tr!("config-tab-expander")
     }
 
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
         ui.push_id("expander", |ui| {
             let mut expander = state.expander;
+            let before = expander.values[Simple];
 
             // Extract out all the current values
             let values = &mut expander.values[expander.mode];
@@ -52,6 +57,11 @@ impl ConfigPage for ExpanderPage {
                         }
                     });
 
+                    ui.add_space(5.);
+                    link_modes_ui(ui, state);
+                    ui.add_space(5.);
+                    expander_presets_ui(ui, state);
+
                     ui.add_space(5.);
 
                     let s = get_slider(ui, "Threshold", "dB", &mut values.threshold, -90..=0);
@@ -121,6 +131,7 @@ impl ConfigPage for ExpanderPage {
                     }
                 });
             });
+            link_expander(state, before);
         });
     }
 }
@@ -0,0 +1,339 @@
+use crate::i18n::tr;
+use crate::recording::{ClipLevel, LevelMonitor, MONITOR_INTERVAL, find_source};
+use crate::ui::audio_pages::config_pages::{ConfigPage, map_to_range, mic_setup};
+use crate::ui::states::audio_state::BeacnAudioState;
+use crate::ui::widgets::stepper;
+use crate::{demo_mode, runtime};
+use anyhow::{Result, anyhow, bail};
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::compressor::{Compressor, CompressorMode, CompressorThreshold};
+use beacn_lib::audio::messages::expander::{Expander, ExpanderMode, ExpanderThreshold};
+use beacn_lib::audio::messages::mic_setup::{MicGain, MicSetup, StudioMicGain};
+use beacn_lib::manager::DeviceType;
+use egui::{ProgressBar, Ui};
+use log::{debug, warn};
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+
+const SPEAK_TIME: Duration = Duration::from_secs(10);
+
+// Where we'd like normal speech to sit, and the highest we'd let the loudest moments reach (dBFS)
+const TARGET_SPEECH: f32 = -20.0;
+const PEAK_CEILING: f32 = -6.0;
+
+// Readings this far above the quietest moments count as speech
+const SPEECH_MARGIN: f32 = 10.0;
+
+// The expander closes just above the noise between words, the compressor starts just above
+// normal speech so only the louder moments are evened out
+const EXPANDER_HEADROOM: f32 = 6.0;
+const COMPRESSOR_HEADROOM: f32 = 4.0;
+
+#[derive(Debug, Copy, Clone)]
+struct Recommendation {
+    speech: f32,
+    peak: f32,
+    noise: f32,
+
+    gain: u8,
+    expander: i8,
+    compressor: i8,
+}
+
+struct Listening {
+    started: Instant,
+
+    // Resolves once the mic has been found and the monitor is running
+    pending: Option<oneshot::Receiver<Result<LevelMonitor>>>,
+    monitor: Option<LevelMonitor>,
+    levels: Vec<ClipLevel>,
+}
+
+// Walks through setting the mic gain. The user speaks normally while we follow the level with the
+// processing bypassed, then we suggest a gain, plus expander and compressor thresholds to match.
+#[derive(Default)]
+pub struct GainWizardPage {
+    listening: Option<Listening>,
+    result: Option<Result<Recommendation, String>>,
+    applied: bool,
+}
+
+impl GainWizardPage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ConfigPage for GainWizardPage {
+    fn title(&self) -> String {
+        tr!("config-tab-gain-wizard")
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        self.poll(state);
+
+        let step = match (&self.listening, &self.result) {
+            (Some(_), _) => 1,
+            (None, Some(_)) => 2,
+            (None, None) => 0,
+        };
+        let steps = [
+            tr!("gain-wizard-prepare"),
+            tr!("gain-wizard-speak"),
+            tr!("gain-wizard-review"),
+        ];
+        stepper(ui, &steps, step);
+        ui.add_space(10.0);
+
+        match step {
+            0 => self.draw_prepare(ui, state),
+            1 => self.draw_speak(ui),
+            _ => self.draw_review(ui, state),
+        }
+    }
+}
+
+impl GainWizardPage {
+    fn draw_prepare(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        ui.label(tr!("gain-wizard-position"));
+        ui.label(tr!("gain-wizard-talk", seconds = SPEAK_TIME.as_secs()));
+        ui.add_space(5.0);
+        if ui.button(tr!("gain-wizard-start")).clicked() {
+            self.start(state);
+        }
+    }
+
+    fn draw_speak(&mut self, ui: &mut Ui) {
+        let Some(listening) = &self.listening else {
+            return;
+        };
+        ui.ctx().request_repaint_after(Duration::from_millis(50));
+
+        if listening.monitor.is_none() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(tr!("gain-wizard-finding"));
+            });
+            return;
+        }
+
+        let level = listening.levels.last().map_or(-120.0, |l| l.rms);
+        let meter = map_to_range(level.clamp(-60.0, 0.0), -60.0, 0.0, 0.0, 1.0);
+        ui.label(tr!("gain-wizard-keep-talking"));
+        ui.add(
+            ProgressBar::new(meter)
+                .desired_width(300.0)
+                .text(format!("{level:.0}dB")),
+        );
+
+        ui.add_space(5.0);
+        let progress = listening.started.elapsed().as_secs_f32() / SPEAK_TIME.as_secs_f32();
+        ui.add(ProgressBar::new(progress.min(1.0)).desired_width(300.0));
+    }
+
+    fn draw_review(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        match &self.result {
+            Some(Ok(result)) => {
+                let result = *result;
+                ui.label(tr!(
+                    "gain-wizard-levels",
+                    speech = format!("{:.1}", result.speech),
+                    peak = format!("{:.1}", result.peak),
+                    noise = format!("{:.1}", result.noise)
+                ));
+                ui.add_space(5.0);
+                ui.label(tr!(
+                    "gain-wizard-gain",
+                    gain = result.gain,
+                    current = state.mic_setup.gain
+                ));
+                ui.label(tr!("gain-wizard-expander", threshold = result.expander));
+                ui.label(tr!("gain-wizard-compressor", threshold = result.compressor));
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button(tr!("gain-wizard-apply")).clicked() {
+                        self.applied = Self::apply(state, result);
+                    }
+                    if ui.button(tr!("gain-wizard-restart")).clicked() {
+                        self.result = None;
+                    }
+                });
+                if self.applied {
+                    ui.label(tr!("gain-wizard-applied"));
+                }
+            }
+            Some(Err(error)) => {
+                ui.label(tr!("gain-wizard-failed", error = error));
+                ui.add_space(5.0);
+                if ui.button(tr!("gain-wizard-restart")).clicked() {
+                    self.result = None;
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn start(&mut self, state: &mut BeacnAudioState) {
+        self.result = None;
+        self.applied = false;
+
+        // The gain is measured before any processing, so the current settings don't skew it
+        if let Err(e) = state.begin_page_bypass() {
+            self.result = Some(Err(e.to_string()));
+            return;
+        }
+
+        let serial = state.device_definition.device_info.serial.clone();
+        let (tx, rx) = oneshot::channel();
+        runtime().spawn_blocking(move || {
+            let result = match demo_mode() {
+                true => Ok(LevelMonitor::simulated(MONITOR_INTERVAL)),
+                false => find_source(&serial)
+                    .and_then(|source| LevelMonitor::start(&source, MONITOR_INTERVAL)),
+            };
+            let _ = tx.send(result);
+        });
+
+        self.listening = Some(Listening {
+            started: Instant::now(),
+            pending: Some(rx),
+            monitor: None,
+            levels: vec![],
+        });
+    }
+
+    fn poll(&mut self, state: &mut BeacnAudioState) {
+        let Some(listening) = &mut self.listening else {
+            return;
+        };
+
+        // The processing is put back if the page is left part way through, so start again
+        if !state.is_page_bypassed() {
+            debug!("Gain Wizard was interrupted");
+            self.listening = None;
+            return;
+        }
+
+        if let Some(pending) = &listening.pending {
+            match pending.try_recv() {
+                Ok(Ok(monitor)) => {
+                    listening.monitor = Some(monitor);
+                    listening.pending = None;
+
+                    // Only start the clock once we can actually hear them
+                    listening.started = Instant::now();
+                }
+                Ok(Err(e)) => return self.finish(state, Err(e)),
+                Err(oneshot::TryRecvError::Disconnected) => {
+                    return self.finish(state, Err(anyhow!("Unable to start listening")));
+                }
+                Err(oneshot::TryRecvError::Empty) => return,
+            }
+        }
+
+        if let Some(monitor) = &listening.monitor {
+            listening.levels.extend(monitor.levels());
+        }
+        if listening.started.elapsed() >= SPEAK_TIME {
+            let current = state.mic_setup.gain;
+            let result = Self::recommend(&listening.levels, current, gain_range(state));
+            self.finish(state, result);
+        }
+    }
+
+    fn finish(&mut self, state: &mut BeacnAudioState, result: Result<Recommendation>) {
+        self.listening = None;
+        state.end_page_bypass();
+
+        debug!("Gain Wizard Result: {result:?}");
+        self.result = Some(result.map_err(|e| e.to_string()));
+    }
+
+    fn recommend(
+        levels: &[ClipLevel],
+        current: u8,
+        range: RangeInclusive<u8>,
+    ) -> Result<Recommendation> {
+        let mut quiet: Vec<f32> = levels.iter().map(|l| l.rms).collect();
+        quiet.sort_by(f32::total_cmp);
+        let Some(noise) = quiet.get(quiet.len() / 10).copied() else {
+            bail!("Nothing was heard from the mic");
+        };
+
+        // Ignore the gaps between words, otherwise they'd drag the speech level down
+        let speech: Vec<&ClipLevel> = levels
+            .iter()
+            .filter(|l| l.rms > noise + SPEECH_MARGIN)
+            .collect();
+        if speech.is_empty() || speech.len() < levels.len() / 5 {
+            bail!("Not enough speech was heard, try speaking a little louder or for longer");
+        }
+        let average = speech.iter().map(|l| l.rms).sum::<f32>() / speech.len() as f32;
+        let peak = speech.iter().fold(-120.0f32, |peak, l| peak.max(l.peak));
+
+        // Bring speech up (or down) to the target, without letting the peaks clip
+        let change = (TARGET_SPEECH - average).min(PEAK_CEILING - peak);
+        let gain = (current as f32 + change).round();
+        let gain = gain.clamp(*range.start() as f32, *range.end() as f32) as u8;
+        let change = gain as f32 - current as f32;
+
+        // Everything below moves with the gain
+        let expander = (noise + change + EXPANDER_HEADROOM)
+            .round()
+            .clamp(-90.0, 0.0) as i8;
+        let compressor = (average + change + COMPRESSOR_HEADROOM)
+            .round()
+            .clamp(-90.0, 0.0) as i8;
+
+        Ok(Recommendation {
+            speech: average,
+            peak,
+            noise,
+            gain,
+            expander,
+            compressor,
+        })
+    }
+
+    // Returns whether everything was applied
+    fn apply(state: &mut BeacnAudioState, result: Recommendation) -> bool {
+        let gain = match state.device_definition.device_type {
+            DeviceType::BeacnStudio => MicSetup::StudioMicGain(StudioMicGain(result.gain as u32)),
+            _ => MicSetup::MicGain(MicGain(result.gain as u32)),
+        };
+
+        let expander_mode = state.expander.mode;
+        let compressor_mode = state.compressor.mode;
+        let expander = ExpanderThreshold(result.expander as f32);
+        let compressor = CompressorThreshold(result.compressor as f32);
+
+        let mut messages = vec![
+            Message::MicSetup(gain),
+            Message::Expander(Expander::Threshold(expander_mode, expander)),
+            Message::Compressor(Compressor::Threshold(compressor_mode, compressor)),
+        ];
+
+        // Like the pages themselves, enabling applies to both modes
+        for mode in ExpanderMode::iter() {
+            messages.push(Message::Expander(Expander::Enabled(mode, true)));
+        }
+        for mode in CompressorMode::iter() {
+            messages.push(Message::Compressor(Compressor::Enabled(mode, true)));
+        }
+
+        for message in messages {
+            if let Err(e) = state.handle_message(message) {
+                warn!("Failed to apply Gain Wizard settings: {e}");
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn gain_range(state: &BeacnAudioState) -> RangeInclusive<u8> {
+    let device_type = state.device_definition.device_type;
+    mic_setup::gain_range(device_type).unwrap_or(mic_setup::MIC_GAIN)
+}
@@ -0,0 +1,157 @@
+use crate::i18n::tr;
+use crate::ui::audio_pages::config_pages::ConfigPage;
+use crate::ui::audio_pages::equaliser::eq_autoeq::{
+    HP_EQ_MAX, HP_EQ_MIN, headphone_response, response_frequencies,
+};
+use crate::ui::audio_pages::equaliser::eq_common::{EqGeometry, MAX_GAIN, MIN_GAIN};
+use crate::ui::states::audio_state::BeacnAudioState;
+use crate::ui::theme;
+use crate::ui::widgets::draw_range;
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::headphone_eq::HPEQType::{Bass, Mids, Treble};
+use beacn_lib::audio::messages::headphone_eq::{HPEQType, HPEQValue, HeadphoneEQ};
+use egui::{Align2, Color32, CornerRadius, FontId, Pos2, Sense, Stroke, Ui, vec2};
+use enum_map::EnumMap;
+use log::warn;
+
+const BANDS: [(HPEQType, &str, Color32); 3] = [
+    (Bass, "Bass", Color32::from_rgb(231, 76, 60)),
+    (Mids, "Mids", Color32::from_rgb(46, 204, 113)),
+    (Treble, "Treble", Color32::from_rgb(241, 196, 15)),
+];
+
+// An editor for the three Headphone EQ bands, with a preview of how they combine
+pub struct HeadphoneEqPage {
+    frequencies: Vec<f32>,
+}
+
+impl HeadphoneEqPage {
+    pub fn new() -> Self {
+        Self {
+            frequencies: response_frequencies(),
+        }
+    }
+
+    fn draw_bands(&self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        for (band, name, _) in BANDS {
+            let mut value = state.headphone_eq.eq[band];
+            ui.add_enabled_ui(value.enabled, |ui| {
+                let range = HP_EQ_MIN..=HP_EQ_MAX;
+                if draw_range(ui, &mut value.amount, range, name, "dB") {
+                    let value = HPEQValue(value.amount);
+                    let message = Message::HeadphoneEQ(HeadphoneEQ::Amount(band, value));
+                    if let Err(e) = state.handle_message(message) {
+                        warn!("Failed to set Headphone EQ: {e}");
+                    }
+                }
+            });
+
+            let hover = format!("Enable {name}");
+            if ui
+                .checkbox(&mut value.enabled, "")
+                .on_hover_text(hover)
+                .changed()
+            {
+                let message = Message::HeadphoneEQ(HeadphoneEQ::Enabled(band, value.enabled));
+                if let Err(e) = state.handle_message(message) {
+                    warn!("Failed to set Headphone EQ: {e}");
+                }
+            }
+        }
+    }
+
+    fn draw_response(&self, ui: &mut Ui, state: &BeacnAudioState) {
+        let size = ui.available_size();
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        let plot_rect = EqGeometry::plot_rect(rect);
+
+        let theme = theme::current(ui.ctx());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(plot_rect, CornerRadius::default(), theme.graph_background);
+
+        let zero = EqGeometry::db_to_y(0.0, plot_rect);
+        let grid = Stroke::new(1.0, theme.graph_grid);
+        painter.line_segment(
+            [
+                Pos2::new(plot_rect.min.x, zero),
+                Pos2::new(plot_rect.max.x, zero),
+            ],
+            grid,
+        );
+
+        let points = |values: &EnumMap<HPEQType, f32>| -> Vec<Pos2> {
+            headphone_response(values, &self.frequencies)
+                .iter()
+                .zip(&self.frequencies)
+                .map(|(&db, &freq)| {
+                    let db = db.clamp(MIN_GAIN, MAX_GAIN);
+                    let x = EqGeometry::freq_to_x(freq as u32, plot_rect);
+                    Pos2::new(x, EqGeometry::db_to_y(db, plot_rect))
+                })
+                .collect()
+        };
+
+        // Disabled bands (or all of them, if the FX are off) don't contribute
+        let active = |band: HPEQType| {
+            let eq = state.headphone_eq.eq[band];
+            match eq.enabled && state.headphones.fx_enabled {
+                true => eq.amount,
+                false => 0.0,
+            }
+        };
+
+        let mut combined = EnumMap::default();
+        for (band, _, colour) in BANDS {
+            combined[band] = active(band);
+
+            let mut values = EnumMap::default();
+            values[band] = active(band);
+            painter.line(
+                points(&values),
+                Stroke::new(1.0, colour.gamma_multiply(0.6)),
+            );
+        }
+        painter.line(points(&combined), Stroke::new(2.0, theme.highlight));
+
+        let legend = plot_rect.left_top() + vec2(8.0, 6.0);
+        for (index, (_, name, colour)) in BANDS.iter().enumerate() {
+            painter.text(
+                legend + vec2(0.0, 14.0 * index as f32),
+                Align2::LEFT_TOP,
+                *name,
+                FontId::proportional(12.0),
+                *colour,
+            );
+        }
+        if !state.headphones.fx_enabled {
+            painter.text(
+                plot_rect.center_top() + vec2(0.0, 6.0),
+                Align2::CENTER_TOP,
+                "Headphone FX are currently disabled",
+                FontId::proportional(12.0),
+                theme.graph_text,
+            );
+        }
+    }
+}
+
+impl ConfigPage for HeadphoneEqPage {
+    fn title(&self) -> String {
+        tr!("config-tab-headphone-eq")
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        let spacing = 10.0;
+
+        ui.horizontal_centered(|ui| {
+            ui.add_space(spacing);
+            self.draw_bands(ui, state);
+
+            ui.add_space(spacing);
+            ui.separator();
+            ui.add_space(spacing);
+
+            self.draw_response(ui, state);
+        });
+    }
+}
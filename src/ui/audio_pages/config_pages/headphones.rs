@@ -1,6 +1,11 @@
-use crate::ui::audio_pages::config_pages::ConfigPage;
+use crate::i18n::tr;
+use crate::recording::{ClipLevel, LevelMonitor, find_source};
+use crate::settings;
+use crate::ui::audio_pages::config_pages::{ConfigPage, map_to_range};
 use crate::ui::states::audio_state::BeacnAudioState;
 use crate::ui::widgets::draw_range;
+use crate::{demo_mode, runtime};
+use anyhow::{Result, anyhow};
 use beacn_lib::audio::messages::Message;
 use beacn_lib::audio::messages::headphone_eq::HPEQType::{Bass, Mids, Treble};
 use beacn_lib::audio::messages::headphone_eq::{HPEQValue, HeadphoneEQ};
@@ -10,18 +15,83 @@ use beacn_lib::audio::messages::headphones::HeadphoneTypes::{
 use beacn_lib::audio::messages::headphones::{HPLevel, HPMicMonitorLevel, Headphones};
 use beacn_lib::audio::messages::subwoofer::Subwoofer;
 use beacn_lib::manager::DeviceType;
-use egui::Ui;
+use beacn_lib::types::HasRange;
+use egui::{ComboBox, ProgressBar, Ui};
 use log::debug;
+use std::time::Duration;
 
-pub struct HeadphonesPage;
+// The choices for how often the monitor meter updates
+const METER_INTERVALS: [u64; 4] = [50, 100, 250, 500];
+
+#[derive(Default)]
+pub struct HeadphonesPage {
+    meter: Option<MonitorMeter>,
+}
+
+// Follows the mic's level, which with the monitor level gives a rough idea of how loud the mic
+// is in the headphones
+struct MonitorMeter {
+    interval: Duration,
+
+    // Resolves once the mic has been found and the monitor is running
+    pending: Option<oneshot::Receiver<Result<LevelMonitor>>>,
+    monitor: Option<LevelMonitor>,
+    level: Option<ClipLevel>,
+    error: Option<String>,
+}
+
+impl MonitorMeter {
+    fn start(serial: String, interval: Duration) -> Self {
+        let (tx, rx) = oneshot::channel();
+        runtime().spawn_blocking(move || {
+            let result = match demo_mode() {
+                true => Ok(LevelMonitor::simulated(interval)),
+                false => find_source(&serial).and_then(|s| LevelMonitor::start(&s, interval)),
+            };
+            let _ = tx.send(result);
+        });
+
+        Self {
+            interval,
+            pending: Some(rx),
+            monitor: None,
+            level: None,
+            error: None,
+        }
+    }
+
+    fn poll(&mut self) {
+        if let Some(pending) = &self.pending {
+            let result = match pending.try_recv() {
+                Ok(result) => result,
+                Err(oneshot::TryRecvError::Disconnected) => Err(anyhow!("Unable to start")),
+                Err(oneshot::TryRecvError::Empty) => return,
+            };
+            self.pending = None;
+            match result {
+                Ok(monitor) => self.monitor = Some(monitor),
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+
+        if let Some(monitor) = &self.monitor
+            && let Some(level) = monitor.levels().pop()
+        {
+            self.level = Some(level);
+        }
+    }
+}
 
 impl ConfigPage for HeadphonesPage {
-    fn title(&self) -> &'static str {
-        "Headphones"
+    fn title(&self) -> String {
+        tr!("config-tab-headphones")
     }
 
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
         let device_type = state.device_definition.device_type;
+        if let Some(meter) = &mut self.meter {
+            meter.poll();
+        }
 
         let spacing = 10.0;
 
@@ -56,7 +126,7 @@ impl ConfigPage for HeadphonesPage {
                     .handle_message(message)
                     .expect("Failed to Send Message");
             }
-            if draw_range(ui, &mut hp.level, -70.0..=0.0, "Headphones", "dB") {
+            if draw_range(ui, &mut hp.level, HPLevel::range(), "Headphones", "dB") {
                 debug!("HP Level Change: {:?}", hp.level);
                 let message = Message::Headphones(Headphones::HeadphoneLevel(HPLevel(hp.level)));
                 state
@@ -64,6 +134,21 @@ impl ConfigPage for HeadphonesPage {
                     .expect("Failed to Send Message");
             }
 
+            // Level changes above this (from anywhere) are pulled back down to it
+            let max_level = state.saved_settings.max_headphone_level;
+            let mut limited = max_level.is_some();
+            let mut limit = max_level.unwrap_or(0.0);
+            let title = tr!("headphones-max-level");
+            if limited && draw_range(ui, &mut limit, HPLevel::range(), &title, "dB") {
+                state.set_max_headphone_level(Some(limit));
+            }
+            let hover = tr!("headphones-max-level-hover");
+            if ui.checkbox(&mut limited, "").on_hover_text(hover).changed() {
+                // Start from the current level, so nothing changes until it's adjusted
+                let limit = limited.then_some(hp.level);
+                state.set_max_headphone_level(limit);
+            }
+
             ui.add_space(spacing);
             ui.separator();
             ui.add_space(spacing);
@@ -128,12 +213,28 @@ impl ConfigPage for HeadphonesPage {
                 // changed, rather than having .click or .change on each radio
                 let previous = hp.headphone_type;
 
-                ui.label("Amp Power");
+                ui.label(tr!("headphones-amp-power"));
                 ui.add_space(10.);
-                ui.radio_value(&mut hp.headphone_type, InEarMonitors, "In Ear Monitors");
-                ui.radio_value(&mut hp.headphone_type, LineLevel, "Line Level");
-                ui.radio_value(&mut hp.headphone_type, NormalPower, "Normal Power");
-                ui.radio_value(&mut hp.headphone_type, HighImpedance, "High Impedance Mode");
+                ui.radio_value(
+                    &mut hp.headphone_type,
+                    InEarMonitors,
+                    tr!("headphones-in-ear"),
+                );
+                ui.radio_value(
+                    &mut hp.headphone_type,
+                    LineLevel,
+                    tr!("headphones-line-level"),
+                );
+                ui.radio_value(
+                    &mut hp.headphone_type,
+                    NormalPower,
+                    tr!("headphones-normal-power"),
+                );
+                ui.radio_value(
+                    &mut hp.headphone_type,
+                    HighImpedance,
+                    tr!("headphones-high-impedance"),
+                );
 
                 if hp.headphone_type != previous {
                     let message = Message::Headphones(Headphones::HeadphoneType(hp.headphone_type));
@@ -141,7 +242,131 @@ impl ConfigPage for HeadphonesPage {
                         .handle_message(message)
                         .expect("Failed to Send Message");
                 }
-            })
+            });
+
+            ui.add_space(spacing);
+            ui.separator();
+            ui.add_space(spacing);
+            ui.vertical(|ui| self.meter_ui(ui, state));
+
+            if device_type == DeviceType::BeacnStudio {
+                ui.add_space(spacing);
+                ui.separator();
+                ui.add_space(spacing);
+                ui.vertical(|ui| sync_ui(ui, state));
+            }
         });
     }
 }
+
+impl HeadphonesPage {
+    // Shows roughly how loud the mic is in the headphones, so it can be balanced against
+    // everything else by eye
+    fn meter_ui(&mut self, ui: &mut Ui, state: &BeacnAudioState) {
+        ui.label(tr!("headphones-meter"));
+        ui.add_space(10.);
+
+        let mut enabled = self.meter.is_some();
+        if ui
+            .checkbox(&mut enabled, tr!("headphones-meter-show"))
+            .changed()
+            && !enabled
+        {
+            self.meter = None;
+        }
+
+        let mut interval = settings::get().monitor_meter_interval;
+        let mut millis = interval.as_millis() as u64;
+        ComboBox::from_id_salt("monitor_meter_interval")
+            .selected_text(tr!("headphones-meter-every", millis = millis))
+            .show_ui(ui, |ui| {
+                for option in METER_INTERVALS {
+                    ui.selectable_value(
+                        &mut millis,
+                        option,
+                        tr!("headphones-meter-every", millis = option),
+                    );
+                }
+            })
+            .response
+            .on_hover_text(tr!("headphones-meter-interval-hover"));
+        if millis != interval.as_millis() as u64 {
+            interval = Duration::from_millis(millis);
+            settings::update(|s| s.monitor_meter_interval = interval);
+        }
+
+        if !enabled {
+            return;
+        }
+
+        // (Re)start if we've just been turned on, or the rate has changed
+        if self.meter.as_ref().is_none_or(|m| m.interval != interval) {
+            let serial = state.device_definition.device_info.serial.clone();
+            self.meter = Some(MonitorMeter::start(serial, interval));
+        }
+        let Some(meter) = &self.meter else {
+            return;
+        };
+        ui.ctx().request_repaint_after(interval);
+
+        if let Some(error) = &meter.error {
+            ui.label(tr!("headphones-meter-failed", error = error));
+            return;
+        }
+        if meter.monitor.is_none() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(tr!("headphones-meter-finding"));
+            });
+            return;
+        }
+
+        // The mic's level, turned up or down by the monitor level
+        let monitor = state.headphones.mic_monitor;
+        let level = meter.level.map_or(-120.0, |l| l.rms + monitor);
+        let peak = meter.level.map_or(-120.0, |l| l.peak + monitor);
+        let fill = map_to_range(level.clamp(-60.0, 0.0), -60.0, 0.0, 0.0, 1.0);
+        ui.add(
+            ProgressBar::new(fill)
+                .desired_width(150.0)
+                .text(format!("{level:.0}dB")),
+        );
+        ui.label(tr!("headphones-meter-peak", peak = format!("{peak:.0}")));
+    }
+}
+
+// Keeps the headphone knob and a Pipeweaver output's volume in step
+fn sync_ui(ui: &mut Ui, state: &mut BeacnAudioState) {
+    ui.label(tr!("headphones-sync"));
+    ui.add_space(10.);
+
+    let current = state.saved_settings.headphone_sync_target;
+    let Some(targets) = state.headphone_sync.targets() else {
+        ui.label(tr!("headphones-sync-no-pipeweaver"));
+        return;
+    };
+
+    let selected = match current {
+        None => tr!("headphones-sync-off"),
+        Some(id) => targets
+            .iter()
+            .find(|t| t.id == id)
+            .map_or(tr!("headphones-sync-unknown"), |t| t.name.clone()),
+    };
+
+    let mut target = current;
+    ComboBox::from_id_salt("headphone_sync")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut target, None, tr!("headphones-sync-off"));
+            for sync_target in &targets {
+                ui.selectable_value(&mut target, Some(sync_target.id), &sync_target.name);
+            }
+        })
+        .response
+        .on_hover_text(tr!("headphones-sync-hover"));
+
+    if target != current {
+        state.set_headphone_sync_target(target);
+    }
+}
@@ -3,14 +3,16 @@ use crate::ui::audio_pages::equaliser::eq_common::{
     Bands, EqGeometry, MAX_FREQUENCY, MAX_GAIN, MIN_FREQUENCY, MIN_GAIN, band_type_has_gain,
 };
 use crate::ui::audio_pages::equaliser::eq_drawer::EqDrawView;
+use crate::ui::clipboard::{CopiedEqualiser, copy_equaliser, get_equaliser};
 use crate::ui::states::audio_state::EqualiserBandType::*;
 use crate::ui::states::audio_state::{BeacnAudioState, EqualiserBand, EqualiserBandType};
+use crate::ui::theme;
 use crate::ui::widgets::draw_draggable;
 use beacn_lib::audio::messages::Message;
 use beacn_lib::audio::messages::equaliser::{
     EQBand, EQBandType, EQFrequency, EQGain, EQMode, EQQ, Equaliser,
 };
-use egui::{Align, Button, Color32, CornerRadius, Image, Layout, Response, Ui, vec2};
+use egui::{Align, Button, CornerRadius, Image, Layout, Response, Ui, vec2};
 use log::{debug, warn};
 use strum::IntoEnumIterator;
 
@@ -373,10 +375,69 @@ impl MicEqualiser {
                     }
                 }
             }
+
+            ui.separator();
+            if ui.button("Copy").on_hover_text("Copy this EQ").clicked() {
+                copy_equaliser(CopiedEqualiser {
+                    serial: state.device_definition.device_info.serial.clone(),
+                    mode,
+                    bands: state.equaliser.bands[mode],
+                });
+            }
+
+            let copied = get_equaliser();
+            let hover = match &copied {
+                Some(copied) => format!("Paste the {:?} EQ from {}", copied.mode, copied.serial),
+                None => String::from("Nothing has been copied"),
+            };
+            let button = ui.add_enabled(copied.is_some(), Button::new("Paste"));
+            if button.on_hover_text(hover).clicked()
+                && let Some(copied) = copied
+            {
+                self.paste_equaliser(&copied, state);
+            }
         });
         response
     }
 
+    fn paste_equaliser(&mut self, copied: &CopiedEqualiser, state: &mut BeacnAudioState) {
+        let mode = state.equaliser.mode;
+        let current = state.equaliser.bands[mode];
+
+        let mut messages = vec![];
+        for (band, config) in copied.bands {
+            if mode == EQMode::Simple {
+                // The simple EQ has a fixed layout, so only the gain of each band carries over
+                if current[band].enabled && band_type_has_gain(current[band].band_type) {
+                    let gain = EQGain(config.gain);
+                    messages.push(Equaliser::Gain(mode, band.into(), gain));
+                }
+                continue;
+            }
+
+            messages.push(Equaliser::Enabled(mode, band.into(), config.enabled));
+            if !config.enabled {
+                continue;
+            }
+            if config.band_type != NotSet {
+                messages.push(Equaliser::Type(mode, band.into(), config.band_type.into()));
+            }
+            let frequency = EQFrequency(config.frequency as f32);
+            messages.push(Equaliser::Frequency(mode, band.into(), frequency));
+            messages.push(Equaliser::Gain(mode, band.into(), EQGain(config.gain)));
+            messages.push(Equaliser::Q(mode, band.into(), EQQ(config.q)));
+        }
+
+        for message in messages {
+            if let Err(e) = state.handle_message(Message::Equaliser(message)) {
+                warn!("Failed to paste EQ value: {e}");
+            }
+        }
+
+        self.view.invalidate_all();
+        self.active_band = None;
+    }
+
     /// Handle drag interactions with the control points
     fn handle_drag(
         &mut self,
@@ -456,10 +517,10 @@ pub enum ButtonPosition {
 pub fn eq_mode(ui: &mut Ui, img: &str, active: bool, pos: ButtonPosition) -> Response {
     let image = SVG.get(img).unwrap().clone();
 
-    let tint_colour = if active {
-        Color32::WHITE
-    } else {
-        Color32::from_rgb(120, 120, 120)
+    let theme = theme::current(ui.ctx());
+    let tint_colour = match active {
+        true => theme.nav_active,
+        false => theme.nav_inactive,
     };
 
     let corner_radius = match pos {
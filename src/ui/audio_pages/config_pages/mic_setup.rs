@@ -1,5 +1,8 @@
+use crate::i18n::tr;
 use crate::ui::audio_pages::config_pages::ConfigPage;
+use crate::ui::audio_pages::config_pages::quick_filters::quick_filters_ui;
 use crate::ui::states::audio_state::BeacnAudioState;
+use crate::ui::theme;
 use crate::ui::widgets::{draw_range, toggle_button};
 use beacn_lib::audio::messages::Message;
 use beacn_lib::audio::messages::bass_enhancement::BassPreset::{
@@ -11,14 +14,26 @@ use beacn_lib::audio::messages::exciter::{Exciter, ExciterFreq};
 use beacn_lib::audio::messages::mic_setup::{MicGain, MicSetup, StudioMicGain};
 use beacn_lib::manager::DeviceType;
 use beacn_lib::types::Percent;
-use egui::{Align, Label, Layout, Ui};
+use egui::{Align, ComboBox, Id, Label, Layout, Modal, RichText, Ui};
 use log::debug;
+use std::ops::RangeInclusive;
 
-pub struct MicSetupPage;
+// The ranges the device accepts, values outside these are rejected
+pub(crate) const MIC_GAIN: RangeInclusive<u8> = 3..=20;
+const STUDIO_MIC_GAIN: RangeInclusive<u8> = 0..=69; // Nice.
+const DE_ESSER_AMOUNT: RangeInclusive<u8> = 0..=100;
+const EXCITER_AMOUNT: RangeInclusive<u8> = 0..=100;
+pub(crate) const EXCITER_FREQ: RangeInclusive<u16> = 600..=5000;
+
+#[derive(Default)]
+pub struct MicSetupPage {
+    // Phantom power has been switched on, but not confirmed yet
+    confirm_phantom: bool,
+}
 
 impl ConfigPage for MicSetupPage {
-    fn title(&self) -> &'static str {
-        "Mic Setup"
+    fn title(&self) -> String {
+        tr!("config-tab-mic-setup")
     }
 
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
@@ -30,26 +45,17 @@ impl ConfigPage for MicSetupPage {
 
             let mut mic_setup = state.mic_setup;
 
-            // The Beacn Studio has a different range for the Mic Gain, so we'll set it here.
-            let range = match device_type {
-                DeviceType::BeacnMic => 3..=20,
-                DeviceType::BeacnStudio => 0..=69, // Nice.
-                _ => panic!("This shouldn't happen"),
-            };
-            if draw_range(ui, &mut mic_setup.gain, range, "Mic Gain", "dB") {
+            // The Beacn Studio has a different range for the Mic Gain, and its own message
+            if let Some(range) = gain_range(device_type)
+                && draw_range(ui, &mut mic_setup.gain, range.clone(), "Mic Gain", "dB")
+            {
+                let gain = clamp(mic_setup.gain, range) as u32;
                 let message = match device_type {
-                    DeviceType::BeacnMic => {
-                        let value = MicGain(mic_setup.gain as u32);
-                        Message::MicSetup(MicSetup::MicGain(value))
-                    }
-                    DeviceType::BeacnStudio => {
-                        let value = StudioMicGain(mic_setup.gain as u32);
-                        Message::MicSetup(MicSetup::StudioMicGain(value))
-                    }
-                    _ => panic!("This shouldn't happen"),
+                    DeviceType::BeacnStudio => MicSetup::StudioMicGain(StudioMicGain(gain)),
+                    _ => MicSetup::MicGain(MicGain(gain)),
                 };
                 state
-                    .handle_message(message)
+                    .handle_message(Message::MicSetup(message))
                     .expect("Failed to Send Message");
             }
 
@@ -57,15 +63,36 @@ impl ConfigPage for MicSetupPage {
             ui.separator();
             ui.add_space(spacing);
 
-            let mut de_esser = state.de_esser;
-            if draw_range(ui, &mut de_esser.amount, 0..=100, "De-Esser", "%") {
-                let value = Percent(de_esser.amount as f32);
-                let message = Message::DeEsser(DeEsser::Amount(value));
-                state
-                    .handle_message(message)
-                    .expect("Failed to Send Message");
-                debug!("DeEsser Change: {}", de_esser.amount);
-            }
+            ui.vertical(|ui| {
+                let mut de_esser = state.de_esser;
+                ui.add_sized([90., 0.], |ui: &mut Ui| {
+                    ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+                        if ui.checkbox(&mut de_esser.enabled, "De-Esser").changed() {
+                            let message = Message::DeEsser(DeEsser::Enabled(de_esser.enabled));
+                            state
+                                .handle_message(message)
+                                .expect("Failed to Send Message");
+                        }
+                        ui.add_space(2.0);
+                        ui.separator();
+                    })
+                    .response
+                });
+
+                ui.add_space(2.0);
+
+                ui.add_enabled_ui(de_esser.enabled, |ui| {
+                    if draw_range(ui, &mut de_esser.amount, DE_ESSER_AMOUNT, "Amount", "%") {
+                        let amount = clamp(de_esser.amount, DE_ESSER_AMOUNT);
+                        let value = Percent(amount as f32);
+                        let message = Message::DeEsser(DeEsser::Amount(value));
+                        state
+                            .handle_message(message)
+                            .expect("Failed to Send Message");
+                        debug!("DeEsser Change: {amount}");
+                    }
+                });
+            });
 
             ui.add_space(spacing);
             ui.separator();
@@ -160,9 +187,15 @@ impl ConfigPage for MicSetupPage {
             ui.add_space(spacing);
 
             ui.vertical(|ui| {
+                let mut excite = state.exciter;
                 ui.add_sized([169., 0.], |ui: &mut Ui| {
                     ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                        ui.label("Exciter");
+                        if ui.checkbox(&mut excite.enabled, "Exciter").changed() {
+                            let message = Message::Exciter(Exciter::Enabled(excite.enabled));
+                            state
+                                .handle_message(message)
+                                .expect("Failed to Send Message");
+                        }
                         ui.add_space(2.0);
                         ui.separator();
                     })
@@ -171,44 +204,158 @@ impl ConfigPage for MicSetupPage {
 
                 ui.add_space(2.0);
 
-                ui.horizontal_centered(|ui| {
-                    let mut excite = state.exciter;
-                    if draw_range(ui, &mut excite.amount, 0..=100, "Amount", "%") {
-                        let value = Percent(excite.amount as f32);
-                        let message = Message::Exciter(Exciter::Amount(value));
-                        state
-                            .handle_message(message)
-                            .expect("Failed to Send Message");
-                    }
+                ui.add_enabled_ui(excite.enabled, |ui| {
+                    ui.horizontal_centered(|ui| {
+                        if draw_range(ui, &mut excite.amount, EXCITER_AMOUNT, "Amount", "%") {
+                            let amount = clamp(excite.amount, EXCITER_AMOUNT);
+                            let value = Percent(amount as f32);
+                            let message = Message::Exciter(Exciter::Amount(value));
+                            state
+                                .handle_message(message)
+                                .expect("Failed to Send Message");
+                        }
 
-                    if draw_range(ui, &mut excite.freq, 600..=5000, "Freq", "Hz") {
-                        let value = ExciterFreq(excite.freq as f32);
-                        let message = Message::Exciter(Exciter::Frequency(value));
-                        state
-                            .handle_message(message)
-                            .expect("Failed to Send Message");
-                    }
-                })
+                        if draw_range(ui, &mut excite.freq, EXCITER_FREQ, "Freq", "Hz") {
+                            let freq = clamp(excite.freq, EXCITER_FREQ);
+                            let value = ExciterFreq(freq as f32);
+                            let message = Message::Exciter(Exciter::Frequency(value));
+                            state
+                                .handle_message(message)
+                                .expect("Failed to Send Message");
+                        }
+                    })
+                });
             });
 
+            ui.add_space(spacing);
+            ui.separator();
+            ui.add_space(spacing);
+
+            ui.vertical(|ui| quick_filters_ui(ui, state));
+
             ui.add_space(spacing);
             ui.separator();
 
-            // We should probably move this elsewhere later, but for now it's fine
+            // Only the Studio has an XLR input to power
             if device_type == DeviceType::BeacnStudio {
-                ui.vertical(|ui| {
-                    if ui
-                        .checkbox(&mut mic_setup.phantom, "Phantom Power")
-                        .changed()
-                    {
-                        let message =
-                            Message::MicSetup(MicSetup::StudioPhantomPower(mic_setup.phantom));
-                        state
-                            .handle_message(message)
-                            .expect("Failed to Send Message");
-                    }
-                });
+                ui.add_space(spacing);
+                self.phantom_ui(ui, state);
             };
+
+            // Only the Mic has a mute on the device
+            if device_type == DeviceType::BeacnMic {
+                ui.add_space(spacing);
+                ui.vertical(|ui| mute_sync_ui(ui, state));
+            }
         });
     }
 }
+
+impl MicSetupPage {
+    fn phantom_ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        let mut phantom = state.mic_setup.phantom;
+        ui.vertical(|ui| {
+            // Turning it on can damage some mics, so that needs confirming first
+            if ui.checkbox(&mut phantom, tr!("config-phantom")).changed() {
+                match phantom {
+                    true => self.confirm_phantom = true,
+                    false => set_phantom(state, false),
+                }
+            }
+            if state.mic_setup.phantom {
+                let colour = theme::current(ui.ctx()).failure;
+                ui.label(RichText::new(tr!("config-phantom-active")).color(colour));
+            }
+        });
+
+        if !self.confirm_phantom {
+            return;
+        }
+
+        let modal = Modal::new(Id::new("phantom_confirm")).show(ui.ctx(), |ui| {
+            ui.set_max_width(360.0);
+            ui.heading(tr!("config-phantom-confirm-title"));
+            ui.add_space(5.0);
+            ui.label(tr!("config-phantom-confirm-body"));
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if ui.button(tr!("config-phantom-confirm-enable")).clicked() {
+                    set_phantom(state, true);
+                    self.confirm_phantom = false;
+                }
+                if ui.button(tr!("config-phantom-confirm-cancel")).clicked() {
+                    self.confirm_phantom = false;
+                }
+            });
+        });
+        if modal.should_close() {
+            self.confirm_phantom = false;
+        }
+    }
+}
+
+// Keeps the Mic's hardware mute and a Pipeweaver source's mute in step
+fn mute_sync_ui(ui: &mut Ui, state: &mut BeacnAudioState) {
+    ui.label(tr!("config-mute-sync"));
+    ui.add_space(10.);
+
+    let theme = theme::current(ui.ctx());
+    match state.mute_sync.is_muted() {
+        Some(true) => ui.label(RichText::new(tr!("config-mute-sync-muted")).color(theme.failure)),
+        Some(false) => ui.label(RichText::new(tr!("config-mute-sync-live")).color(theme.success)),
+        None => ui.label(tr!("config-mute-sync-unknown")),
+    };
+    ui.add_space(5.);
+
+    let current = state.saved_settings.mute_sync_source;
+    let Some(sources) = state.mute_sync.sources() else {
+        ui.label(tr!("config-mute-sync-no-pipeweaver"));
+        return;
+    };
+
+    let selected = match current {
+        None => tr!("config-mute-sync-off"),
+        Some(id) => sources
+            .iter()
+            .find(|s| s.id == id)
+            .map_or(tr!("config-mute-sync-unknown-source"), |s| s.name.clone()),
+    };
+
+    let mut source = current;
+    ComboBox::from_id_salt("mute_sync")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut source, None, tr!("config-mute-sync-off"));
+            for sync_source in &sources {
+                ui.selectable_value(&mut source, Some(sync_source.id), &sync_source.name);
+            }
+        })
+        .response
+        .on_hover_text(tr!("config-mute-sync-hover"));
+
+    if source != current {
+        state.set_mute_sync_source(source);
+    }
+}
+
+fn set_phantom(state: &mut BeacnAudioState, enabled: bool) {
+    let message = Message::MicSetup(MicSetup::StudioPhantomPower(enabled));
+    state
+        .handle_message(message)
+        .expect("Failed to Send Message");
+}
+
+/// The gain range of a device's mic input, None if it doesn't have one
+pub fn gain_range(device_type: DeviceType) -> Option<RangeInclusive<u8>> {
+    match device_type {
+        DeviceType::BeacnMic => Some(MIC_GAIN),
+        DeviceType::BeacnStudio => Some(STUDIO_MIC_GAIN),
+        _ => None,
+    }
+}
+
+// Sliders can be dragged (or typed) past their range, so make sure we never send those values
+fn clamp<T: Ord + Copy>(value: T, range: RangeInclusive<T>) -> T {
+    value.clamp(*range.start(), *range.end())
+}
@@ -1,15 +1,22 @@
+pub(crate) mod autoeq;
+pub(crate) mod compare;
 pub(crate) mod compressor;
+pub(crate) mod dynamics;
 pub(crate) mod expander;
+pub(crate) mod gain_wizard;
+pub(crate) mod headphone_eq;
 pub(crate) mod headphones;
 pub(crate) mod mic_equaliser;
 pub(crate) mod mic_setup;
+pub(crate) mod quick_filters;
 pub(crate) mod suppressor;
+pub(crate) mod windows_import;
 
 use crate::ui::states::audio_state::BeacnAudioState;
 use egui::Ui;
 
 pub trait ConfigPage {
-    fn title(&self) -> &'static str;
+    fn title(&self) -> String;
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState);
 }
 
@@ -0,0 +1,224 @@
+// One click filters for the most common problems, a low cut for rumble and a notch for mains hum.
+// There's no dedicated setting for these on the device, they're simply Advanced EQ bands set up
+// with the right type and frequency, so they show up (and can be tweaked) on the EQ page too.
+
+use crate::i18n::tr;
+use crate::ui::states::audio_state::{
+    BeacnAudioState, EqualiserBand, EqualiserBandConfig, EqualiserBandType,
+};
+use crate::ui::theme;
+use crate::ui::widgets::toggle_button;
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::equaliser::{EQFrequency, EQGain, EQMode, EQQ, Equaliser};
+use egui::{Label, RichText, Ui};
+use enum_map::EnumMap;
+use log::warn;
+use strum::IntoEnumIterator;
+
+// Bands within this many Hz of a filter's frequency are treated as that filter
+const FREQUENCY_TOLERANCE: u32 = 1;
+
+// A notch wants to be as narrow as possible, so it only takes out the hum
+const LOW_CUT_Q: f32 = 0.7;
+const NOTCH_Q: f32 = 10.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum FilterGroup {
+    LowCut,
+    HumNotch,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct QuickFilter {
+    label: &'static str,
+    hover: fn() -> String,
+    group: FilterGroup,
+    band_type: EqualiserBandType,
+    frequency: u32,
+    q: f32,
+}
+
+// Only one filter from each group can be on at a time
+const FILTERS: [QuickFilter; 4] = [
+    QuickFilter {
+        label: "75Hz",
+        hover: || tr!("quick-filters-75-hover"),
+        group: FilterGroup::LowCut,
+        band_type: EqualiserBandType::HighPassFilter,
+        frequency: 75,
+        q: LOW_CUT_Q,
+    },
+    QuickFilter {
+        label: "150Hz",
+        hover: || tr!("quick-filters-150-hover"),
+        group: FilterGroup::LowCut,
+        band_type: EqualiserBandType::HighPassFilter,
+        frequency: 150,
+        q: LOW_CUT_Q,
+    },
+    QuickFilter {
+        label: "50Hz",
+        hover: || tr!("quick-filters-50-hover"),
+        group: FilterGroup::HumNotch,
+        band_type: EqualiserBandType::NotchFilter,
+        frequency: 50,
+        q: NOTCH_Q,
+    },
+    QuickFilter {
+        label: "60Hz",
+        hover: || tr!("quick-filters-60-hover"),
+        group: FilterGroup::HumNotch,
+        band_type: EqualiserBandType::NotchFilter,
+        frequency: 60,
+        q: NOTCH_Q,
+    },
+];
+
+type Bands = EnumMap<EqualiserBand, EqualiserBandConfig>;
+
+impl QuickFilter {
+    fn matches(&self, config: &EqualiserBandConfig) -> bool {
+        config.enabled
+            && config.band_type == self.band_type
+            && config.frequency.abs_diff(self.frequency) <= FREQUENCY_TOLERANCE
+    }
+
+    fn band(&self, bands: &Bands) -> Option<EqualiserBand> {
+        EqualiserBand::iter().find(|band| self.matches(&bands[*band]))
+    }
+
+    // Bands the user has set up which work in the same area as this filter
+    fn conflicts(&self, bands: &Bands) -> Vec<EqualiserBand> {
+        EqualiserBand::iter()
+            .filter(|band| {
+                let config = &bands[*band];
+                if !config.enabled || FILTERS.iter().any(|f| f.matches(config)) {
+                    return false;
+                }
+                match self.group {
+                    // Anything below the cut is mostly gone, as is the point of a second cut
+                    FilterGroup::LowCut => {
+                        config.band_type == EqualiserBandType::HighPassFilter
+                            || config.frequency <= self.frequency
+                    }
+                    FilterGroup::HumNotch => {
+                        let range = self.frequency * 4 / 5..=self.frequency * 5 / 4;
+                        range.contains(&config.frequency)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn messages(&self, band: EqualiserBand) -> Vec<Message> {
+        let mode = EQMode::Advanced;
+        let band = band.into();
+        [
+            Equaliser::Type(mode, band, self.band_type.into()),
+            Equaliser::Frequency(mode, band, EQFrequency(self.frequency as f32)),
+            Equaliser::Gain(mode, band, EQGain(0.0)),
+            Equaliser::Q(mode, band, EQQ(self.q)),
+            Equaliser::Enabled(mode, band, true),
+        ]
+        .into_iter()
+        .map(Message::Equaliser)
+        .collect()
+    }
+}
+
+pub(crate) fn quick_filters_ui(ui: &mut Ui, state: &mut BeacnAudioState) {
+    let advanced = state.equaliser.mode == EQMode::Advanced;
+    let bands = state.equaliser.bands[EQMode::Advanced];
+
+    ui.label(tr!("quick-filters"));
+    ui.add_space(5.0);
+
+    let mut toggled = None;
+    ui.add_enabled_ui(advanced, |ui| {
+        for (label, group) in [
+            (tr!("quick-filters-low-cut"), FilterGroup::LowCut),
+            (tr!("quick-filters-hum"), FilterGroup::HumNotch),
+        ] {
+            ui.horizontal(|ui| {
+                ui.add_sized([55.0, 0.0], Label::new(label));
+                for filter in FILTERS.iter().filter(|f| f.group == group) {
+                    let active = filter.band(&bands).is_some();
+                    let button = toggle_button(ui, active, filter.label);
+                    if ui.add(button).on_hover_text((filter.hover)()).clicked() {
+                        toggled = Some((*filter, active));
+                    }
+                }
+            });
+        }
+    })
+    .response
+    .on_disabled_hover_text(tr!("quick-filters-advanced-only"));
+
+    // Let the user know if these are likely to be fighting with their own EQ
+    let colour = theme::current(ui.ctx()).highlight;
+    if advanced && EqualiserBand::iter().all(|b| bands[b].enabled) {
+        ui.label(
+            RichText::new(tr!("quick-filters-bands-full"))
+                .color(colour)
+                .small(),
+        );
+    }
+    for filter in FILTERS.iter().filter(|f| f.band(&bands).is_some()) {
+        let conflicts = filter.conflicts(&bands);
+        if !conflicts.is_empty() {
+            let bands: Vec<_> = conflicts.iter().map(|b| band_number(*b)).collect();
+            let text = tr!(
+                "quick-filters-overlap",
+                filter = filter.label,
+                bands = bands.join(", ")
+            );
+            ui.label(RichText::new(text).color(colour).small());
+        }
+    }
+
+    if let Some((filter, active)) = toggled {
+        toggle_filter(state, &bands, filter, active);
+    }
+}
+
+fn toggle_filter(state: &mut BeacnAudioState, bands: &Bands, filter: QuickFilter, active: bool) {
+    let mode = EQMode::Advanced;
+    let messages = match active {
+        true => filter.band(bands).map_or(vec![], |band| {
+            vec![Message::Equaliser(Equaliser::Enabled(
+                mode,
+                band.into(),
+                false,
+            ))]
+        }),
+        false => {
+            // Take over the other filter in the group if it's on, otherwise use a spare band,
+            // working from the top so the bands the user is most likely using are left alone
+            let sibling = FILTERS
+                .iter()
+                .filter(|f| f.group == filter.group)
+                .find_map(|f| f.band(bands));
+            let spare = || EqualiserBand::iter().rev().find(|b| !bands[*b].enabled);
+            match sibling.or_else(spare) {
+                Some(band) => filter.messages(band),
+                None => {
+                    warn!("No spare EQ bands for the {} filter", filter.label);
+                    vec![]
+                }
+            }
+        }
+    };
+
+    for message in messages {
+        if let Err(e) = state.handle_message(message) {
+            warn!("Failed to set quick filter: {e}");
+        }
+    }
+}
+
+fn band_number(band: EqualiserBand) -> String {
+    let index = EqualiserBand::iter()
+        .position(|b| b == band)
+        .unwrap_or_default();
+    (index + 1).to_string()
+}
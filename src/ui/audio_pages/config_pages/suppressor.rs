@@ -1,18 +1,48 @@
-use crate::ui::audio_pages::config_pages::ConfigPage;
+use crate::i18n::tr;
+use crate::recording::{ClipLevel, find_source, measure_clip, record_clip};
+use crate::ui::audio_pages::config_pages::{ConfigPage, map_to_range};
 use crate::ui::states::audio_state::BeacnAudioState;
-use crate::ui::widgets::{get_slider, toggle_button};
+use crate::ui::widgets::{apply_summary, get_slider, toggle_button};
+use crate::{runtime, storage};
+use anyhow::{Result, anyhow};
 use beacn_lib::audio::messages::Message;
 use beacn_lib::audio::messages::suppressor::SuppressorStyle::{Adaptive, Snapshot};
 use beacn_lib::audio::messages::suppressor::{Suppressor, SuppressorSensitivity};
 use beacn_lib::types::Percent;
-use egui::Button;
 use egui::Ui;
+use egui::{Button, ProgressBar, ScrollArea, TextEdit};
+use log::{debug, warn};
+use std::time::{Duration, Instant};
 
-pub struct NoiseSuppressionPage;
+// How long to listen to the room for
+const LISTEN_TIME: Duration = Duration::from_secs(5);
+
+// How far above the noise floor the sensitivity is placed, so quiet speech isn't suppressed
+const SENSITIVITY_HEADROOM: f32 = 6.0;
+
+struct Listening {
+    started: Instant,
+    result: oneshot::Receiver<Result<ClipLevel>>,
+}
+
+#[derive(Default)]
+pub struct NoiseSuppressionPage {
+    // The name being typed for a new room profile
+    profile_name: String,
+
+    listening: Option<Listening>,
+    measured: Option<Result<ClipLevel, String>>,
+}
+
+impl NoiseSuppressionPage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
 impl ConfigPage for NoiseSuppressionPage {
-    fn title(&self) -> &'static str {
-        "Noise Suppression"
+    fn title(&self) -> String {
+        tr!("config-tab-suppressor")
     }
 
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
@@ -84,6 +114,205 @@ impl ConfigPage for NoiseSuppressionPage {
                     );
                 }
             });
+
+            ui.separator();
+            ui.vertical(|ui| self.draw_profiles(ui, state));
+            ui.separator();
+            ui.vertical(|ui| self.draw_listen(ui, state));
+        });
+    }
+}
+
+impl NoiseSuppressionPage {
+    // Listens to the room with the processing off, and suggests settings based on how noisy it is
+    fn draw_listen(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        self.poll_listen(state);
+
+        ui.label(tr!("suppressor-measure"));
+        ui.add_space(5.0);
+
+        if let Some(listening) = &self.listening {
+            let progress = listening.started.elapsed().as_secs_f32() / LISTEN_TIME.as_secs_f32();
+            ui.label(tr!("suppressor-listening"));
+            ui.add(ProgressBar::new(progress.min(1.0)).desired_width(180.0));
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+            return;
+        }
+
+        let hover = tr!("suppressor-listen-hover", seconds = LISTEN_TIME.as_secs());
+        if ui
+            .button(tr!("suppressor-listen"))
+            .on_hover_text(hover)
+            .clicked()
+        {
+            self.start_listen(state);
+        }
+
+        ui.add_space(5.0);
+        match &self.measured {
+            Some(Ok(level)) => {
+                let (amount, sense) = Self::suggest(*level);
+                ui.label(tr!(
+                    "suppressor-noise-floor",
+                    level = format!("{:.1}", level.rms)
+                ));
+                ui.label(tr!("suppressor-suggested", amount = amount, sense = sense));
+                if ui.button(tr!("suppressor-apply")).clicked() {
+                    Self::apply_suggestion(state, amount, sense);
+                }
+            }
+            Some(Err(e)) => {
+                ui.label(tr!("suppressor-measure-failed", error = e));
+            }
+            None => {}
+        }
+    }
+
+    fn start_listen(&mut self, state: &mut BeacnAudioState) {
+        self.measured = None;
+
+        // Measure the raw mic, otherwise we'd just hear what the current settings leave behind
+        if let Err(e) = state.begin_page_bypass() {
+            self.measured = Some(Err(e.to_string()));
+            return;
+        }
+
+        let serial = state.device_definition.device_info.serial.clone();
+        let (tx, rx) = oneshot::channel();
+        runtime().spawn_blocking(move || {
+            let result = || -> Result<ClipLevel> {
+                let path = storage::cache_path(&format!("{serial}-noise.wav"))?;
+                let source = find_source(&serial)?;
+                record_clip(&source, &path, LISTEN_TIME)?;
+                measure_clip(&path)
+            };
+            let _ = tx.send(result());
+        });
+
+        self.listening = Some(Listening {
+            started: Instant::now(),
+            result: rx,
+        });
+    }
+
+    fn poll_listen(&mut self, state: &mut BeacnAudioState) {
+        let Some(listening) = &self.listening else {
+            return;
+        };
+
+        // The processing is put back if the page is left part way through, so the measurement
+        // would include it
+        if !state.is_page_bypassed() {
+            debug!("Room noise measurement was interrupted");
+            self.listening = None;
+            return;
+        }
+
+        let result = match listening.result.try_recv() {
+            Ok(result) => result,
+            Err(oneshot::TryRecvError::Empty) => return,
+            Err(oneshot::TryRecvError::Disconnected) => Err(anyhow!("Listening stopped")),
+        };
+
+        self.listening = None;
+        state.end_page_bypass();
+
+        debug!("Room Noise Measured: {result:?}");
+        self.measured = Some(result.map_err(|e| e.to_string()));
+    }
+
+    // Quiet rooms only need a little suppression, noisier rooms need more. The sensitivity sits
+    // just above the noise floor, so the noise is caught but speech isn't.
+    fn suggest(level: ClipLevel) -> (u8, u8) {
+        let amount = map_to_range(level.rms.clamp(-80.0, -40.0), -80.0, -40.0, 30.0, 90.0);
+        let threshold = (level.rms + SENSITIVITY_HEADROOM).clamp(-120.0, -60.0);
+        let sense = map_to_range(threshold, -120.0, -60.0, 0.0, 100.0);
+        (amount.round() as u8, sense.round() as u8)
+    }
+
+    fn apply_suggestion(state: &mut BeacnAudioState, amount: u8, sense: u8) {
+        // The sensitivity is set the same way the slider does, so the two agree
+        let sensitivity = -120.0 + (60.0 * (sense as f32 / 100.0));
+        let messages = [
+            Message::Suppressor(Suppressor::Style(Adaptive)),
+            Message::Suppressor(Suppressor::Amount(Percent(amount as f32))),
+            Message::Suppressor(Suppressor::Sensitivity(SuppressorSensitivity(sensitivity))),
+            Message::Suppressor(Suppressor::Enabled(true)),
+        ];
+        for message in messages {
+            if let Err(e) = state.handle_message(message) {
+                warn!("Failed to apply suggested suppression: {e}");
+                return;
+            }
+        }
+    }
+
+    // Room profiles store the whole suppressor setup, so it's quick to switch between
+    // environments (AC On, AC Off, etc), they can also be switched from the tray
+    fn draw_profiles(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        ui.label(tr!("suppressor-profiles"));
+        ui.add_space(5.0);
+
+        ui.horizontal(|ui| {
+            let name = TextEdit::singleline(&mut self.profile_name)
+                .hint_text(tr!("suppressor-profile-name"))
+                .desired_width(140.0);
+            ui.add(name);
+
+            let name = self.profile_name.trim().to_string();
+            let exists = state
+                .saved_settings
+                .suppressor_profiles
+                .iter()
+                .any(|p| p.name == name);
+            let hover = match exists {
+                true => tr!("suppressor-profile-replace"),
+                false => tr!("suppressor-profile-save-hover"),
+            };
+
+            let button = ui.add_enabled(
+                !name.is_empty(),
+                Button::new(tr!("suppressor-profile-save")),
+            );
+            if button.on_hover_text(hover).clicked() {
+                if let Err(e) = state.save_suppressor_profile(&name) {
+                    warn!("Failed to save Suppressor Profile: {e}");
+                }
+                self.profile_name.clear();
+            }
         });
+
+        ui.add_space(5.0);
+        let names: Vec<String> = state
+            .saved_settings
+            .suppressor_profiles
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+
+        if names.is_empty() {
+            ui.label(tr!("suppressor-no-profiles"));
+            return;
+        }
+
+        ScrollArea::vertical().show(ui, |ui| {
+            for name in names {
+                ui.horizontal(|ui| {
+                    if ui.add_sized([140.0, 20.0], Button::new(&name)).clicked()
+                        && let Err(e) = state.apply_suppressor_profile(&name)
+                    {
+                        warn!("Failed to apply Suppressor Profile: {e}");
+                    }
+                    if ui.small_button(tr!("suppressor-profile-remove")).clicked() {
+                        state.remove_suppressor_profile(&name);
+                    }
+                });
+            }
+        });
+
+        if let Some(progress) = &state.last_apply {
+            ui.add_space(5.0);
+            apply_summary(ui, progress);
+        }
     }
 }
@@ -0,0 +1,332 @@
+use crate::i18n::tr;
+use crate::runtime;
+use crate::ui::audio_pages::config_pages::ConfigPage;
+use crate::ui::audio_pages::windows_profile::{ImportTarget, WindowsProfile};
+use crate::ui::states::audio_state::BeacnAudioState;
+use crate::ui::theme;
+use anyhow::{Result, anyhow};
+use ashpd::desktop::file_chooser::{FileFilter, SelectedFiles};
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::compressor::{Compressor, CompressorThreshold};
+use beacn_lib::audio::messages::deesser::DeEsser;
+use beacn_lib::audio::messages::equaliser::{EQFrequency, EQGain, EQQ, Equaliser};
+use beacn_lib::audio::messages::exciter::{Exciter, ExciterFreq};
+use beacn_lib::audio::messages::expander::{Expander, ExpanderThreshold};
+use beacn_lib::audio::messages::headphone_eq::{HPEQValue, HeadphoneEQ};
+use beacn_lib::audio::messages::headphones::{HPLevel, Headphones};
+use beacn_lib::audio::messages::mic_setup::{MicGain, MicSetup, StudioMicGain};
+use beacn_lib::audio::messages::suppressor::Suppressor;
+use beacn_lib::types::{MakeUpGain, Percent};
+use egui::{Grid, RichText, ScrollArea, TextEdit, Ui};
+use log::warn;
+use std::fs;
+use std::mem;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// A single setting which the import would change
+struct Change {
+    name: String,
+    current: String,
+    imported: String,
+    message: Message,
+
+    // The value in the file, if it was out of range
+    clamped_from: Option<f32>,
+}
+
+struct Preview {
+    changes: Vec<Change>,
+    unchanged: usize,
+    skipped: Vec<String>,
+}
+
+// Loads a preset from the Windows Beacn app, and shows what it would change before applying it
+#[derive(Default)]
+pub struct WindowsImportPage {
+    path: String,
+    browsing: Option<oneshot::Receiver<Result<Option<PathBuf>>>>,
+    result: Option<Result<Preview, String>>,
+}
+
+impl WindowsImportPage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn browse(&mut self) {
+        let (tx, rx) = oneshot::channel();
+        runtime().spawn(async move {
+            let result = async {
+                let title = tr!("import-chooser-title");
+                let response = SelectedFiles::open_file()
+                    .title(title.as_str())
+                    .modal(true)
+                    .multiple(false)
+                    .filter(FileFilter::new(&tr!("import-chooser-filter")).glob("*.json"))
+                    .send()
+                    .await?
+                    .response()?;
+
+                let uri = response.uris().first().cloned();
+                Ok(uri.and_then(|uri| uri.to_file_path().ok()))
+            };
+            let _ = tx.send(result.await);
+        });
+        self.browsing = Some(rx);
+    }
+
+    fn poll_browse(&mut self, ui: &Ui, state: &mut BeacnAudioState) {
+        let Some(browsing) = &self.browsing else {
+            return;
+        };
+
+        let result = match browsing.try_recv() {
+            Ok(result) => result,
+            Err(oneshot::TryRecvError::Empty) => {
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+                return;
+            }
+            Err(oneshot::TryRecvError::Disconnected) => Err(anyhow!(tr!("import-chooser-closed"))),
+        };
+
+        self.browsing = None;
+        match result {
+            Ok(Some(path)) => {
+                self.path = path.to_string_lossy().to_string();
+                self.load(state);
+            }
+            Ok(None) => {}
+            Err(e) => self.result = Some(Err(tr!("import-chooser-failed", error = e))),
+        }
+    }
+
+    fn load(&mut self, state: &mut BeacnAudioState) {
+        self.result = Some(Self::preview(self.path.trim(), state).map_err(|e| e.to_string()));
+    }
+
+    fn preview(path: &str, state: &mut BeacnAudioState) -> Result<Preview> {
+        let text = fs::read_to_string(path)?;
+        let target = ImportTarget {
+            device_type: state.device_definition.device_type,
+            compressor_mode: state.compressor.mode,
+            expander_mode: state.expander.mode,
+        };
+        let profile = WindowsProfile::parse(&text, &target)?;
+
+        let imported: Vec<Message> = profile.settings.iter().map(|s| s.message).collect();
+        let current = state.capture_matching(&imported)?;
+
+        let mut preview = Preview {
+            changes: vec![],
+            unchanged: 0,
+            skipped: profile.skipped,
+        };
+        for setting in profile.settings {
+            let message = setting.message;
+            if current.contains(&message) {
+                preview.unchanged += 1;
+                continue;
+            }
+
+            // Settings the firmware can't fetch are still applied, we just can't show the old value
+            let current = current.iter().find(|c| same_setting(c, &message));
+            preview.changes.push(Change {
+                name: setting.name,
+                current: current.map_or(String::from("?"), value_text),
+                imported: value_text(&message),
+                message,
+                clamped_from: setting.clamped_from,
+            });
+        }
+        Ok(preview)
+    }
+
+    fn draw_input(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        ui.label(tr!("import-intro"));
+        ui.horizontal(|ui| {
+            let path = TextEdit::singleline(&mut self.path).hint_text(tr!("import-path-hint"));
+            ui.add(path);
+            let browse = ui.add_enabled(
+                self.browsing.is_none(),
+                egui::Button::new(tr!("import-browse")),
+            );
+            if browse.clicked() {
+                self.browse();
+            }
+            if ui.button(tr!("import-load")).clicked() {
+                self.load(state);
+            }
+        });
+    }
+
+    fn draw_preview(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        let Some(result) = &self.result else {
+            ui.label(tr!("import-nothing-loaded"));
+            return;
+        };
+
+        let theme = theme::current(ui.ctx());
+        let preview = match result {
+            Ok(preview) => preview,
+            Err(e) => {
+                ui.label(RichText::new(e).color(theme.failure));
+                return;
+            }
+        };
+
+        ui.label(tr!(
+            "import-summary",
+            changed = preview.changes.len(),
+            unchanged = preview.unchanged
+        ));
+        ui.add_space(5.0);
+
+        ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+            Grid::new("windows_import_changes")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    ui.strong(tr!("import-setting"));
+                    ui.strong(tr!("import-current"));
+                    ui.strong(tr!("import-imported"));
+                    ui.end_row();
+
+                    for change in &preview.changes {
+                        ui.label(&change.name);
+                        ui.label(&change.current);
+                        let imported = RichText::new(&change.imported).color(theme.highlight);
+                        match change.clamped_from {
+                            Some(value) => {
+                                let hover = tr!("import-clamped", value = value);
+                                ui.label(imported.color(theme.failure)).on_hover_text(hover);
+                            }
+                            None => {
+                                ui.label(imported);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            if !preview.skipped.is_empty() {
+                ui.add_space(5.0);
+                let title = tr!("import-skipped", count = preview.skipped.len());
+                ui.collapsing(title, |ui| {
+                    for name in &preview.skipped {
+                        ui.label(name);
+                    }
+                });
+            }
+        });
+
+        ui.add_space(5.0);
+        let apply = ui.add_enabled(
+            !preview.changes.is_empty(),
+            egui::Button::new(tr!("import-apply")),
+        );
+        if !apply.clicked() {
+            return;
+        }
+        for change in &preview.changes {
+            if let Err(e) = state.handle_message(change.message) {
+                warn!("Failed to apply {}: {e}", change.name);
+            }
+        }
+
+        // Reload, so the preview reflects what the device actually took
+        self.load(state);
+    }
+}
+
+// Whether two messages change the same setting, including the mode or band it belongs to. This
+// only needs to cover the messages an import can produce.
+fn same_setting(a: &Message, b: &Message) -> bool {
+    use mem::discriminant as kind;
+
+    match (a, b) {
+        (Message::MicSetup(a), Message::MicSetup(b)) => kind(a) == kind(b),
+        (Message::Headphones(a), Message::Headphones(b)) => kind(a) == kind(b),
+        (Message::Suppressor(a), Message::Suppressor(b)) => kind(a) == kind(b),
+        (Message::DeEsser(a), Message::DeEsser(b)) => kind(a) == kind(b),
+        (Message::Exciter(a), Message::Exciter(b)) => kind(a) == kind(b),
+        (Message::Compressor(a), Message::Compressor(b)) => match (a, b) {
+            (Compressor::Enabled(a, _), Compressor::Enabled(b, _))
+            | (Compressor::Threshold(a, _), Compressor::Threshold(b, _))
+            | (Compressor::MakeupGain(a, _), Compressor::MakeupGain(b, _)) => a == b,
+            _ => false,
+        },
+        (Message::Expander(a), Message::Expander(b)) => match (a, b) {
+            (Expander::Enabled(a, _), Expander::Enabled(b, _))
+            | (Expander::Threshold(a, _), Expander::Threshold(b, _)) => a == b,
+            _ => false,
+        },
+        (Message::HeadphoneEQ(HeadphoneEQ::Amount(a, _)), Message::HeadphoneEQ(b)) => {
+            matches!(b, HeadphoneEQ::Amount(b, _) if a == b)
+        }
+        (Message::Equaliser(a), Message::Equaliser(b)) => {
+            let target = |message: &Equaliser| match *message {
+                Equaliser::Enabled(mode, band, _)
+                | Equaliser::Frequency(mode, band, _)
+                | Equaliser::Gain(mode, band, _)
+                | Equaliser::Q(mode, band, _)
+                | Equaliser::Type(mode, band, _) => Some((mode, band)),
+                _ => None,
+            };
+            kind(a) == kind(b) && target(a).is_some() && target(a) == target(b)
+        }
+        _ => false,
+    }
+}
+
+// The value a message sets, for the preview
+fn value_text(message: &Message) -> String {
+    let flag = |enabled: bool| match enabled {
+        true => tr!("import-on"),
+        false => tr!("import-off"),
+    };
+    match *message {
+        Message::MicSetup(MicSetup::MicGain(MicGain(gain)))
+        | Message::MicSetup(MicSetup::StudioMicGain(StudioMicGain(gain))) => format!("{gain}dB"),
+        Message::Headphones(Headphones::HeadphoneLevel(HPLevel(level))) => format!("{level:.1}dB"),
+
+        Message::Suppressor(Suppressor::Enabled(enabled))
+        | Message::DeEsser(DeEsser::Enabled(enabled))
+        | Message::Exciter(Exciter::Enabled(enabled))
+        | Message::Compressor(Compressor::Enabled(_, enabled))
+        | Message::Expander(Expander::Enabled(_, enabled))
+        | Message::Equaliser(Equaliser::Enabled(_, _, enabled)) => flag(enabled),
+
+        Message::Suppressor(Suppressor::Amount(Percent(amount)))
+        | Message::DeEsser(DeEsser::Amount(Percent(amount)))
+        | Message::Exciter(Exciter::Amount(Percent(amount))) => format!("{amount:.0}%"),
+        Message::Exciter(Exciter::Frequency(ExciterFreq(freq)))
+        | Message::Equaliser(Equaliser::Frequency(_, _, EQFrequency(freq))) => {
+            format!("{freq:.0}Hz")
+        }
+
+        Message::Compressor(Compressor::Threshold(_, CompressorThreshold(db)))
+        | Message::Expander(Expander::Threshold(_, ExpanderThreshold(db))) => format!("{db:.0}dB"),
+        Message::Compressor(Compressor::MakeupGain(_, MakeUpGain(db)))
+        | Message::HeadphoneEQ(HeadphoneEQ::Amount(_, HPEQValue(db)))
+        | Message::Equaliser(Equaliser::Gain(_, _, EQGain(db))) => format!("{db:.1}dB"),
+        Message::Equaliser(Equaliser::Q(_, _, EQQ(q))) => format!("{q:.2}"),
+        Message::Equaliser(Equaliser::Type(_, _, band_type)) => format!("{band_type:?}"),
+        _ => String::from("?"),
+    }
+}
+
+impl ConfigPage for WindowsImportPage {
+    fn title(&self) -> String {
+        tr!("config-tab-windows-import")
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        self.poll_browse(ui, state);
+        ui.vertical(|ui| {
+            self.draw_input(ui, state);
+            ui.add_space(8.0);
+            self.draw_preview(ui, state);
+        });
+    }
+}
@@ -0,0 +1,138 @@
+use crate::i18n::tr;
+use crate::settings;
+use crate::ui::audio_pages::AudioPage;
+use crate::ui::shared_pages::console::ConsoleLog;
+use crate::ui::states::audio_state::BeacnAudioState;
+use anyhow::anyhow;
+use beacn_lib::audio::messages::Message;
+use egui::{ComboBox, TextEdit, Ui};
+use serde_json::Value;
+
+// Allows composing raw messages (as JSON) and sending them straight to the device, this is
+// only shown when developer mode is enabled in the app settings.
+pub struct DeveloperPage {
+    editor: String,
+    log: ConsoleLog,
+}
+
+impl DeveloperPage {
+    pub fn new() -> Self {
+        Self {
+            editor: String::new(),
+            log: ConsoleLog::default(),
+        }
+    }
+}
+
+impl AudioPage for DeveloperPage {
+    fn icon(&self) -> &'static str {
+        "terminal"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-developer")
+    }
+
+    fn should_show(&self, _: &BeacnAudioState) -> bool {
+        settings::get().developer_mode
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        ui.heading(tr!("page-developer"));
+        ui.add_space(10.0);
+        ui.label(tr!("developer-warning"));
+        ui.add_space(10.0);
+
+        ComboBox::from_id_salt("developer_template")
+            .selected_text(tr!("developer-template"))
+            .height(400.0)
+            .show_ui(ui, |ui| {
+                let device_type = state.device_definition.device_type;
+                for message in Message::generate_fetch_message(device_type) {
+                    if ui.selectable_label(false, format!("{message:?}")).clicked() {
+                        self.editor = serde_json::to_string_pretty(&message).unwrap_or_default();
+                    }
+                }
+            });
+
+        ui.add_space(5.0);
+        ui.add(
+            TextEdit::multiline(&mut self.editor)
+                .code_editor()
+                .desired_rows(6)
+                .desired_width(f32::INFINITY),
+        );
+
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            if ui.button(tr!("developer-send")).clicked() {
+                self.send(state);
+            }
+            if ui.button(tr!("developer-clear")).clicked() {
+                self.log.clear();
+            }
+        });
+
+        ui.add_space(5.0);
+        ui.separator();
+        if let Some(source) = self.log.ui(ui) {
+            self.editor = source;
+        }
+    }
+}
+
+impl DeveloperPage {
+    fn send(&mut self, state: &mut BeacnAudioState) {
+        let message = match serde_json::from_str::<Message>(&self.editor) {
+            Ok(message) => message,
+            Err(e) => {
+                let request = self.editor.trim().to_string();
+                self.log
+                    .push(request, Err(anyhow!(tr!("developer-invalid", error = e))));
+                return;
+            }
+        };
+
+        let source = Some(self.editor.clone());
+        let mut raw = tr!("developer-raw-sent", bytes = Self::as_hex(&message));
+
+        let response = state.handle_message(message).map(|response| {
+            let json = serde_json::to_string(&response).unwrap_or_default();
+            let received = tr!("developer-raw-received", bytes = Self::as_hex(&response));
+            raw = format!("{raw}\n{received}\n{json}");
+            format!("{response:?}")
+        });
+        self.log
+            .push_raw(format!("{message:?}"), source, response, Some(raw));
+    }
+
+    // The values in a message as 4 byte little endian words, which is how the device sees them
+    fn as_hex(message: &Message) -> String {
+        let mut words = vec![];
+        Self::collect_words(
+            &serde_json::to_value(message).unwrap_or_default(),
+            &mut words,
+        );
+        words
+            .iter()
+            .map(|word| word.map(|b| format!("{b:02X}")).join(" "))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    fn collect_words(value: &Value, words: &mut Vec<[u8; 4]>) {
+        match value {
+            Value::Bool(value) => words.push((*value as u32).to_le_bytes()),
+            Value::Number(number) => {
+                if let Some(value) = number.as_i64() {
+                    words.push((value as i32).to_le_bytes());
+                } else if let Some(value) = number.as_f64() {
+                    words.push((value as f32).to_le_bytes());
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|v| Self::collect_words(v, words)),
+            Value::Object(values) => values.values().for_each(|v| Self::collect_words(v, words)),
+            Value::Null | Value::String(_) => {}
+        }
+    }
+}
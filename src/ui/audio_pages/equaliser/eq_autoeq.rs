@@ -0,0 +1,290 @@
+/*
+  Imports AutoEQ (EqualizerAPO style) parametric EQ files, and fits them to the Headphone EQ.
+
+  The Headphone EQ only has three fixed bands, so there's no way to apply a published curve
+  directly. Instead, we build the frequency response of the imported filters and find the
+  Bass, Mids and Treble values which get closest to it. The overall level is ignored when
+  fitting (that's what the headphone level is for), so the preamp line is only informational.
+
+  Ref: https://github.com/jaakkopasanen/AutoEq
+*/
+
+use crate::ui::audio_pages::equaliser::eq_common::{MAX_FREQUENCY, MIN_FREQUENCY};
+use crate::ui::audio_pages::equaliser::eq_util::{BiquadCoefficient, EQUtil};
+use anyhow::{Result, anyhow, bail};
+use beacn_lib::audio::messages::headphone_eq::HPEQType;
+use enum_map::{EnumMap, enum_map};
+
+// The headphone EQ range, matching the sliders on the Headphones page
+pub const HP_EQ_MIN: f32 = -12.0;
+pub const HP_EQ_MAX: f32 = 12.0;
+
+// The device doesn't expose the shape of its Headphone EQ bands, these approximate them.
+const BASS_FILTER: (f32, f32) = (100.0, 0.707);
+const MIDS_FILTER: (f32, f32) = (1000.0, 0.7);
+const TREBLE_FILTER: (f32, f32) = (8000.0, 0.707);
+
+// Used when a shelf doesn't specify a Q
+const DEFAULT_SHELF_Q: f32 = 0.707;
+
+// How many points across the spectrum we compare when fitting
+const FIT_POINTS: usize = 96;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FilterType {
+    Peak,
+    LowShelf,
+    HighShelf,
+    LowPass,
+    HighPass,
+    Notch,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParametricFilter {
+    pub filter_type: FilterType,
+    pub frequency: f32,
+    pub gain: f32,
+    pub q: f32,
+}
+
+impl ParametricFilter {
+    fn coefficient(&self) -> BiquadCoefficient {
+        match self.filter_type {
+            FilterType::Peak => EQUtil::bell_coefficient(self.frequency, self.gain, self.q),
+            FilterType::LowShelf => {
+                EQUtil::low_shelf_coefficient(self.frequency, self.gain, self.q)
+            }
+            FilterType::HighShelf => {
+                EQUtil::high_shelf_coefficient(self.frequency, self.gain, self.q)
+            }
+            FilterType::LowPass => EQUtil::low_pass_coefficient(self.frequency, self.q),
+            FilterType::HighPass => EQUtil::high_pass_coefficient(self.frequency, self.q),
+            FilterType::Notch => EQUtil::notch_coefficient(self.frequency, self.q),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParametricProfile {
+    pub preamp: f32,
+    pub filters: Vec<ParametricFilter>,
+}
+
+impl ParametricProfile {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut profile = Self::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if let Some(preamp) = line.strip_prefix("Preamp:") {
+                profile.preamp = Self::value_before(preamp, "dB")
+                    .ok_or_else(|| anyhow!("Invalid preamp on line {}", number + 1))?;
+            } else if line.starts_with("Filter") {
+                match Self::parse_filter(line) {
+                    Ok(Some(filter)) => profile.filters.push(filter),
+                    Ok(None) => {}
+                    Err(e) => bail!("Line {}: {e}", number + 1),
+                }
+            }
+        }
+
+        if profile.filters.is_empty() {
+            bail!("No filters found, is this a parametric EQ file?");
+        }
+        Ok(profile)
+    }
+
+    // Filter 1: ON PK Fc 105 Hz Gain 5.5 dB Q 0.70
+    fn parse_filter(line: &str) -> Result<Option<ParametricFilter>> {
+        let Some((_, definition)) = line.split_once(':') else {
+            bail!("Missing ':' in filter definition");
+        };
+
+        let mut tokens = definition.split_whitespace();
+        match tokens.next() {
+            Some("ON") => {}
+            Some("OFF") => return Ok(None),
+            _ => bail!("Filter should be ON or OFF"),
+        }
+
+        let filter_type = match tokens.next() {
+            Some("PK" | "PEQ") => FilterType::Peak,
+            Some("LS" | "LSC" | "LSQ") => FilterType::LowShelf,
+            Some("HS" | "HSC" | "HSQ") => FilterType::HighShelf,
+            Some("LP" | "LPQ") => FilterType::LowPass,
+            Some("HP" | "HPQ") => FilterType::HighPass,
+            Some("NO") => FilterType::Notch,
+            Some(other) => bail!("Unsupported filter type {other}"),
+            None => bail!("Missing filter type"),
+        };
+
+        // Each value follows its name, with units (Hz / dB) in between
+        let tokens: Vec<&str> = tokens.collect();
+        let value = |name: &str| -> Result<Option<f32>> {
+            let Some(index) = tokens.iter().position(|t| *t == name) else {
+                return Ok(None);
+            };
+            match tokens.get(index + 1).and_then(|v| v.parse::<f32>().ok()) {
+                Some(value) => Ok(Some(value)),
+                None => bail!("Invalid value for {name}"),
+            }
+        };
+
+        let frequency = value("Fc")?;
+        let gain = value("Gain")?.unwrap_or(0.0);
+        let q = value("Q")?.unwrap_or(DEFAULT_SHELF_Q);
+        if q <= 0.0 {
+            bail!("Q must be above zero");
+        }
+
+        let Some(frequency) = frequency else {
+            bail!("Missing filter frequency");
+        };
+        Ok(Some(ParametricFilter {
+            filter_type,
+            frequency,
+            gain,
+            q,
+        }))
+    }
+
+    fn value_before(text: &str, unit: &str) -> Option<f32> {
+        text.trim().trim_end_matches(unit).trim().parse().ok()
+    }
+
+    /// The combined response of all the filters, in dB, at each frequency
+    pub fn response(&self, frequencies: &[f32]) -> Vec<f32> {
+        let coefficients: Vec<_> = self.filters.iter().map(|f| f.coefficient()).collect();
+        frequencies
+            .iter()
+            .map(|&freq| {
+                coefficients
+                    .iter()
+                    .map(|c| EQUtil::freq_response_scalar(freq, c))
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// The closest Headphone EQ settings to an imported profile
+#[derive(Debug, Clone)]
+pub struct HeadphoneFit {
+    pub values: EnumMap<HPEQType, f32>,
+
+    /// The root mean square difference between the fit and the profile, in dB
+    pub error: f32,
+
+    /// The frequencies the fit was measured at, with the target and fitted responses
+    pub frequencies: Vec<f32>,
+    pub target: Vec<f32>,
+    pub fitted: Vec<f32>,
+}
+
+impl HeadphoneFit {
+    pub fn fit(profile: &ParametricProfile) -> Self {
+        let frequencies = response_frequencies();
+        let target = profile.response(&frequencies);
+
+        // With only three parameters, a simple coordinate search with decreasing steps is
+        // plenty, and it lets us evaluate the real response at each step.
+        let mut values: EnumMap<HPEQType, f32> = EnumMap::default();
+        let mut error = fit_error(&target, &headphone_response(&values, &frequencies));
+        for step in [4.0, 1.0, 0.25, 0.1] {
+            let mut improved = true;
+            while improved {
+                improved = false;
+                for band in [HPEQType::Bass, HPEQType::Mids, HPEQType::Treble] {
+                    for direction in [-step, step] {
+                        let mut candidate = values;
+                        candidate[band] = (candidate[band] + direction).clamp(HP_EQ_MIN, HP_EQ_MAX);
+                        if candidate[band] == values[band] {
+                            continue;
+                        }
+
+                        let candidate_error =
+                            fit_error(&target, &headphone_response(&candidate, &frequencies));
+                        if candidate_error < error {
+                            values = candidate;
+                            error = candidate_error;
+                            improved = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Round to the precision the sliders work in
+        for value in values.values_mut() {
+            *value = (*value * 10.0).round() / 10.0;
+        }
+
+        let fitted = headphone_response(&values, &frequencies);
+        let error = fit_error(&target, &fitted);
+        Self {
+            values,
+            error,
+            frequencies,
+            target,
+            fitted,
+        }
+    }
+
+    /// How far the target sits above the fit overall, which the fit doesn't try to match
+    pub fn offset(&self) -> f32 {
+        level_offset(&self.target, &self.fitted)
+    }
+}
+
+/// The approximate response of the Headphone EQ with the given settings
+pub fn headphone_response(values: &EnumMap<HPEQType, f32>, frequencies: &[f32]) -> Vec<f32> {
+    let coefficients = enum_map! {
+        HPEQType::Bass => EQUtil::low_shelf_coefficient(
+            BASS_FILTER.0, values[HPEQType::Bass], BASS_FILTER.1
+        ),
+        HPEQType::Mids => EQUtil::bell_coefficient(
+            MIDS_FILTER.0, values[HPEQType::Mids], MIDS_FILTER.1
+        ),
+        HPEQType::Treble => EQUtil::high_shelf_coefficient(
+            TREBLE_FILTER.0, values[HPEQType::Treble], TREBLE_FILTER.1
+        ),
+    };
+
+    frequencies
+        .iter()
+        .map(|&freq| {
+            coefficients
+                .values()
+                .map(|c| EQUtil::freq_response_scalar(freq, c))
+                .sum()
+        })
+        .collect()
+}
+
+/// Frequencies across the audible range, logarithmically spaced so every octave counts the same
+pub fn response_frequencies() -> Vec<f32> {
+    let min = (MIN_FREQUENCY as f32).ln();
+    let max = (MAX_FREQUENCY as f32).ln();
+    (0..FIT_POINTS)
+        .map(|i| (min + (max - min) * i as f32 / (FIT_POINTS - 1) as f32).exp())
+        .collect()
+}
+
+// The overall level difference between two responses
+fn level_offset(target: &[f32], fitted: &[f32]) -> f32 {
+    let count = target.len() as f32;
+    target.iter().zip(fitted).map(|(t, f)| t - f).sum::<f32>() / count
+}
+
+// The RMS difference between two responses, after removing any overall level difference
+fn fit_error(target: &[f32], fitted: &[f32]) -> f32 {
+    let count = target.len() as f32;
+    let offset = level_offset(target, fitted);
+    let squares: f32 = target
+        .iter()
+        .zip(fitted)
+        .map(|(t, f)| (t - f - offset).powi(2))
+        .sum();
+    (squares / count).sqrt()
+}
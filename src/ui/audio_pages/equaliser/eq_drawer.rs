@@ -4,6 +4,7 @@ use crate::ui::audio_pages::equaliser::eq_common::{
 use crate::ui::audio_pages::equaliser::eq_util::{BiquadCoefficient, EQUtil};
 use crate::ui::states::audio_state::EqualiserBandType::*;
 use crate::ui::states::audio_state::{EqualiserBand, EqualiserBandConfig};
+use crate::ui::theme;
 use egui::{
     Color32, CornerRadius, FontId, Mesh, Pos2, Rect, Response, Sense, Shape, Stroke, StrokeKind,
     Ui, Vec2, pos2, vec2,
@@ -66,6 +67,7 @@ pub struct EqDrawView {
     // Cache of the main curve, and rect size (used to know when to
     // invalidate the caches above on resize)
     curve_mesh: Option<Arc<Mesh>>,
+    curve_colour: Color32,
     rect: Rect,
 }
 
@@ -75,6 +77,7 @@ impl EqDrawView {
             band_freq_response: Default::default(),
             band_mesh: Default::default(),
             curve_mesh: None,
+            curve_colour: Color32::TRANSPARENT,
             rect: Rect::NOTHING,
         }
     }
@@ -172,11 +175,12 @@ impl EqDrawView {
         plot_rect: Rect,
         border_colour: Option<Color32>,
     ) {
-        let axis_stroke_colour = border_colour.unwrap_or(Color32::from_rgb(170, 170, 170));
+        let theme = theme::current(painter.ctx());
+        let axis_stroke_colour = border_colour.unwrap_or(theme.graph_text);
 
-        let background = Color32::from_rgb(34, 34, 34);
-        let grid_color = Color32::from_rgb(102, 102, 102);
-        let text_color = Color32::from_rgb(170, 170, 170);
+        let background = theme.graph_background;
+        let grid_color = theme.graph_grid;
+        let text_color = theme.graph_text;
         let grid_stroke = Stroke::new(1.0, grid_color);
         let axis_stroke = Stroke::new(2.0, axis_stroke_colour);
         let freq_ticks = [30, 50, 100, 250, 500, 1000, 2000, 5000, 10000, 16000];
@@ -224,13 +228,15 @@ impl EqDrawView {
     }
 
     fn draw_eq_curve(&mut self, painter: &egui::Painter, plot_rect: Rect, bands: &Bands) {
-        if let Some(mesh) = &self.curve_mesh {
+        // The cached curve is only good for as long as the theme's curve colour stays the same
+        let curve_color = theme::current(painter.ctx()).curve;
+        if let Some(mesh) = &self.curve_mesh
+            && self.curve_colour == curve_color
+        {
             painter.add(Shape::mesh(mesh.clone()));
             return;
         }
 
-        let curve_color = Color32::from_rgb(255, 255, 255);
-
         let sources: Vec<Vec<f32>> = EqualiserBand::iter()
             .filter(|&band| bands[band].enabled)
             .map(|band| self.get_eq_frequency_response(plot_rect, band, bands, EQ_CURVE_RESOLUTION))
@@ -263,6 +269,7 @@ impl EqDrawView {
         let mesh = Arc::new(Self::build_curve_mesh(&points, 3.0, curve_color));
         painter.add(Shape::mesh(mesh.clone()));
         self.curve_mesh = Some(mesh);
+        self.curve_colour = curve_color;
     }
 
     fn build_curve_mesh(points: &[Pos2], stroke_width: f32, color: Color32) -> Mesh {
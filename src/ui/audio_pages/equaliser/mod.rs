@@ -1,3 +1,4 @@
+pub(crate) mod eq_autoeq;
 pub(crate) mod eq_common;
 pub(crate) mod eq_drawer;
 pub(crate) mod eq_util;
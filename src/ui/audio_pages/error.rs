@@ -1,13 +1,21 @@
+use crate::diagnostics;
+use crate::i18n::tr;
 use crate::ui::audio_pages::AudioPage;
-use crate::ui::shared_pages::errors::display_errors;
+use crate::ui::shared_pages::errors::{ErrorAction, display_errors};
+use crate::ui::shared_pages::permissions::PermissionDiagnostics;
+use crate::ui::states::LoadState;
 use crate::ui::states::audio_state::BeacnAudioState;
 use egui::Ui;
 
-pub struct ErrorPage {}
+pub struct ErrorPage {
+    diagnostics: PermissionDiagnostics,
+}
 
 impl ErrorPage {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            diagnostics: PermissionDiagnostics::default(),
+        }
     }
 }
 
@@ -16,16 +24,39 @@ impl AudioPage for ErrorPage {
         "error"
     }
 
+    fn title(&self) -> String {
+        tr!("page-error")
+    }
+
     fn show_on_error(&self) -> bool {
         true
     }
 
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
-        display_errors(
+        let denied = state.device_state.state == LoadState::PermissionDenied;
+        if self.diagnostics.is_open() && denied {
+            self.diagnostics.ui(ui, &state.device_definition);
+            return;
+        }
+
+        let action = display_errors(
             ui,
             &state.device_state.state,
             &state.device_definition.location,
             &state.device_state.errors,
         );
+        let definition = &state.device_definition;
+        match action {
+            Some(ErrorAction::Diagnose) => self.diagnostics.open(definition.location),
+            Some(ErrorAction::RetryOpen) => match state.device_state.state {
+                LoadState::ResourceBusy => {
+                    diagnostics::request_busy_retry(definition.location, definition.device_type)
+                }
+                _ => diagnostics::request_reprobe(definition.location, definition.device_type),
+            },
+            Some(ErrorAction::Resend(index)) => state.resend_failed(index),
+            Some(ErrorAction::Continue) => state.ignore_failed(),
+            None => {}
+        }
     }
 }
@@ -1,4 +1,6 @@
+use crate::i18n::tr;
 use crate::ui::audio_pages::AudioPage;
+use crate::ui::audio_pages::lighting_preview::lighting_preview;
 use crate::ui::states::audio_state::BeacnAudioState;
 use crate::ui::states::audio_state::Lighting as LightingState;
 use beacn_lib::audio::messages::Message;
@@ -31,6 +33,14 @@ impl AudioPage for LightingPage {
         "bulb"
     }
 
+    fn title(&self) -> String {
+        tr!("page-lighting")
+    }
+
+    fn covers(&self, message: &Message) -> bool {
+        matches!(message, Message::Lighting(_))
+    }
+
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
         let device_type = state.device_definition.device_type;
         let mut lighting = state.lighting;
@@ -47,16 +57,23 @@ impl AudioPage for LightingPage {
                                 ui.add_space(10.0);
                                 ui.horizontal(|ui| {
                                     ui.add_space(15.);
-                                    ui.vertical(|ui| match device_type {
-                                        DeviceType::BeacnMic => {
-                                            self.draw_types_mic(ui, state, &mut lighting)
-                                        }
-                                        DeviceType::BeacnStudio => {
-                                            self.draw_types_studio(ui, state, &mut lighting)
-                                        }
-                                        _ => {
-                                            ui.label("You shouldn't see this :)");
+                                    ui.vertical(|ui| {
+                                        match device_type {
+                                            DeviceType::BeacnMic => {
+                                                self.draw_types_mic(ui, state, &mut lighting)
+                                            }
+                                            DeviceType::BeacnStudio => {
+                                                self.draw_types_studio(ui, state, &mut lighting)
+                                            }
+                                            _ => {
+                                                ui.label("You shouldn't see this :)");
+                                            }
                                         }
+
+                                        ui.add_space(20.0);
+                                        ui.label(RichText::new("Preview").strong());
+                                        ui.add_space(10.0);
+                                        lighting_preview(ui, device_type, &state.lighting);
                                     });
                                 })
                                 .inner
@@ -0,0 +1,162 @@
+// A rough simulation of the LED ring, drawn next to the lighting options so changes can be seen
+// without looking over at the device. It's not an exact match for the firmware, the meter modes
+// are driven by a made up signal rather than the real mic, but it's close enough to pick colours
+// and speeds with.
+
+use crate::ui::states::audio_state::Lighting;
+use beacn_lib::audio::messages::lighting::LightingMode;
+use beacn_lib::audio::messages::lighting::StudioLightingMode;
+use beacn_lib::manager::DeviceType;
+use egui::ecolor::Hsva;
+use egui::{Color32, Response, Sense, Stroke, Ui, vec2};
+use std::f32::consts::TAU;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::Duration;
+
+const LED_COUNT: usize = 24;
+const RING_SIZE: f32 = 100.0;
+const LED_RADIUS: f32 = 4.0;
+
+// Roughly 30fps, smooth enough without keeping the UI busy
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+// How many full turns a second the ring makes at full speed
+const MAX_TURNS: f32 = 0.5;
+
+pub(crate) fn lighting_preview(
+    ui: &mut Ui,
+    device_type: DeviceType,
+    lighting: &Lighting,
+) -> Response {
+    let (response, painter) = ui.allocate_painter(vec2(RING_SIZE, RING_SIZE), Sense::hover());
+    let time = ui.input(|i| i.time) as f32;
+    let colours = led_colours(device_type, lighting, time);
+
+    let centre = response.rect.center();
+    let radius = RING_SIZE / 2.0 - LED_RADIUS - 1.0;
+    let outline = Stroke::new(1.0, ui.visuals().widgets.noninteractive.bg_stroke.color);
+    for (index, colour) in colours.iter().enumerate() {
+        let position = centre + led_offset(index) * radius;
+        painter.circle(position, LED_RADIUS, *colour, outline);
+    }
+
+    if is_animated(device_type, lighting) {
+        ui.ctx().request_repaint_after(FRAME_INTERVAL);
+    }
+    response
+}
+
+// The first LED is at the bottom of the ring, and they go round clockwise
+fn led_offset(index: usize) -> egui::Vec2 {
+    let angle = TAU / 4.0 + TAU * index as f32 / LED_COUNT as f32;
+    vec2(angle.cos(), angle.sin())
+}
+
+// How far up the ring an LED is, 0 at the bottom and 1 at the top
+fn led_height(index: usize) -> f32 {
+    (1.0 - led_offset(index).y) / 2.0
+}
+
+fn is_animated(device_type: DeviceType, lighting: &Lighting) -> bool {
+    match device_type {
+        DeviceType::BeacnStudio => lighting.studio_mode != StudioLightingMode::Solid,
+        _ => match lighting.mic_mode {
+            LightingMode::Solid => false,
+            LightingMode::Gradient | LightingMode::Spectrum => lighting.speed != 0,
+            _ => true,
+        },
+    }
+}
+
+fn led_colours(device_type: DeviceType, lighting: &Lighting, time: f32) -> [Color32; LED_COUNT] {
+    let primary = Color32::from_rgb(
+        lighting.colour1[0],
+        lighting.colour1[1],
+        lighting.colour1[2],
+    );
+    let secondary = Color32::from_rgb(
+        lighting.colour2[0],
+        lighting.colour2[1],
+        lighting.colour2[2],
+    );
+    let turns = time * MAX_TURNS * lighting.speed as f32 / 10.0;
+    let level = simulated_level(time, lighting.sensitivity);
+
+    let colour = |index: usize| match device_type {
+        DeviceType::BeacnStudio => match lighting.studio_mode {
+            StudioLightingMode::Solid => primary,
+            StudioLightingMode::PeakMeter => meter(primary, secondary, led_height(index), level),
+            StudioLightingMode::SolidSpectrum => hue(time * MAX_TURNS / 2.0),
+        },
+        _ => {
+            let position = index as f32 / LED_COUNT as f32;
+            match lighting.mic_mode {
+                LightingMode::Solid => primary,
+                LightingMode::Gradient => {
+                    // Blend there and back, so the ring doesn't have a hard edge
+                    let blend = ((position - turns) * TAU).cos() / 2.0 + 0.5;
+                    mix(secondary, primary, blend)
+                }
+                LightingMode::Spectrum => hue(position - turns),
+                LightingMode::ReactiveRing => mix(secondary, primary, level),
+                LightingMode::ReactiveMeterUp => {
+                    meter(primary, secondary, led_height(index), level)
+                }
+                LightingMode::ReactiveMeterDown => {
+                    meter(primary, secondary, 1.0 - led_height(index), level)
+                }
+                LightingMode::SparkleRandom | LightingMode::SparkleMeter => {
+                    // Random sparkles are always going, the meter ones come with the level
+                    let chance = match lighting.mic_mode {
+                        LightingMode::SparkleMeter => level,
+                        _ => 0.25,
+                    };
+                    match sparkles(index, time, lighting.speed, chance) {
+                        true => primary,
+                        false => secondary,
+                    }
+                }
+            }
+        }
+    };
+
+    let brightness = lighting.brightness.clamp(0, 100) as f32 / 100.0;
+    std::array::from_fn(|index| dim(colour(index), brightness))
+}
+
+// Something which looks a bit like someone talking, scaled by the meter sensitivity
+fn simulated_level(time: f32, sensitivity: f32) -> f32 {
+    let syllables = ((time * 7.0).sin() / 2.0 + 0.5).powi(2);
+    let phrases = ((time * 0.9).sin() / 2.0 + 0.5).max(0.2);
+    (syllables * phrases * sensitivity / 5.0).clamp(0.0, 1.0)
+}
+
+fn meter(lit: Color32, unlit: Color32, height: f32, level: f32) -> Color32 {
+    match height <= level {
+        true => lit,
+        false => unlit,
+    }
+}
+
+fn sparkles(index: usize, time: f32, speed: i32, chance: f32) -> bool {
+    // Each LED gets a fresh roll of the dice every 'slot', which gets shorter as the speed goes up
+    let rate = 2.0 + speed.unsigned_abs() as f32;
+    let slot = (time * rate) as u64;
+
+    let mut hasher = DefaultHasher::new();
+    (index, slot).hash(&mut hasher);
+    let roll = (hasher.finish() % 1000) as f32 / 1000.0;
+    roll < chance
+}
+
+fn hue(position: f32) -> Color32 {
+    Hsva::new(position.rem_euclid(1.0), 1.0, 1.0, 1.0).into()
+}
+
+fn mix(from: Color32, to: Color32, amount: f32) -> Color32 {
+    from.lerp_to_gamma(to, amount.clamp(0.0, 1.0))
+}
+
+fn dim(colour: Color32, brightness: f32) -> Color32 {
+    mix(Color32::BLACK, colour, brightness)
+}
@@ -1,10 +1,15 @@
+use crate::i18n::tr;
 use crate::ui::audio_pages::AudioPage;
 use crate::ui::states::audio_state::BeacnAudioState;
-use beacn_lib::audio::LinkChannel;
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::headphones::Headphones;
+use beacn_lib::audio::{LinkChannel, LinkedApp};
 use beacn_lib::manager::DeviceType;
-use egui::{ComboBox, Ui};
+use egui::{Grid, RichText, Ui};
 use strum::IntoEnumIterator;
 
+// The Studio's routing, which apps on the PC2 port go to which of the four Link submixes, along
+// with the settings which decide whether that routing is available at all
 pub struct Linked {}
 
 impl Linked {
@@ -18,62 +23,123 @@ impl AudioPage for Linked {
         "left_right"
     }
 
+    fn title(&self) -> String {
+        tr!("page-routing")
+    }
+
     fn should_show(&self, state: &BeacnAudioState) -> bool {
         state.device_definition.device_type == DeviceType::BeacnStudio
-            && state.headphones.studio_driverless == Some(false)
+    }
+
+    fn covers(&self, message: &Message) -> bool {
+        matches!(
+            message,
+            Message::Headphones(Headphones::StudioDriverless(_))
+                | Message::Headphones(Headphones::StudioChannelsLinked(_))
+        )
     }
 
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState) {
-        ui.label("This page requires the PC2 USB port to be plugged into a Windows PC with the Beacn Link app running.");
+        ui.heading(tr!("page-routing"));
+        ui.add_space(10.);
+
+        self.settings_ui(ui, state);
+
+        ui.add_space(10.);
+        ui.separator();
+        ui.add_space(10.);
+
+        // Compliancy mode turns the PC2 port into a plain USB audio device, without the submixes
+        if state.headphones.studio_driverless != Some(false) {
+            ui.label(tr!("link-compliancy-on"));
+            return;
+        }
+
+        ui.label(tr!("link-requirements"));
         ui.add_space(10.);
 
         let mut changed_apps = Vec::new();
-        if let Some(apps) = &mut state.linked {
-            if apps.is_empty() {
-                ui.label("No Apps playing audio detected");
-            } else {
-                for app in apps {
-                    ComboBox::from_label(&app.name)
-                        .selected_text(self.display_name(app.channel))
-                        .show_ui(ui, |ui| {
-                            for channel in LinkChannel::iter() {
-                                ui.add_enabled_ui(channel != LinkChannel::System, |ui| {
-                                    if ui
-                                        .selectable_value(
-                                            &mut app.channel,
-                                            channel,
-                                            self.display_name(channel),
-                                        )
-                                        .clicked()
-                                    {
-                                        changed_apps.push(app.clone());
-                                    }
-                                });
-                            }
-                        });
-                }
+        match &mut state.linked {
+            Some(apps) if apps.is_empty() => {
+                ui.label(tr!("link-no-apps"));
+            }
+            Some(apps) => self.matrix_ui(ui, apps, &mut changed_apps),
+            None => {
+                ui.label(tr!("link-unavailable"));
             }
-        } else {
-            ui.label("Unable to communicate with the Beacn Link App");
         }
         for app in changed_apps {
             let _ = state.set_link(app);
         }
 
-        if ui.button("Refresh").clicked() {
+        ui.add_space(10.);
+        if ui.button(tr!("link-refresh")).clicked() {
             let _ = state.get_linked();
         }
     }
 }
 
 impl Linked {
-    fn display_name(&self, channel: LinkChannel) -> &'static str {
+    fn settings_ui(&self, ui: &mut Ui, state: &mut BeacnAudioState) {
+        if let Some(driverless) = state.headphones.studio_driverless {
+            let mut enabled = driverless;
+            if ui
+                .checkbox(&mut enabled, tr!("link-compliancy"))
+                .on_hover_text(tr!("link-compliancy-hover"))
+                .changed()
+            {
+                state.headphones.studio_driverless = Some(enabled);
+                let message = Message::Headphones(Headphones::StudioDriverless(enabled));
+                state.handle_message(message).expect("Failed!");
+            }
+        }
+
+        let mut linked = state.headphones.linked;
+        if ui
+            .checkbox(&mut linked, tr!("link-headphones"))
+            .on_hover_text(tr!("link-headphones-hover"))
+            .changed()
+        {
+            let message = Message::Headphones(Headphones::StudioChannelsLinked(linked));
+            state.handle_message(message).expect("Failed!");
+        }
+    }
+
+    // One row per app, and one column per submix, with the app's submix selected
+    fn matrix_ui(&self, ui: &mut Ui, apps: &mut [LinkedApp], changed: &mut Vec<LinkedApp>) {
+        Grid::new("studio_link_matrix")
+            .num_columns(LinkChannel::iter().count() + 1)
+            .spacing([20.0, 6.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(RichText::new(tr!("link-app")).strong());
+                for channel in LinkChannel::iter() {
+                    ui.label(RichText::new(self.display_name(channel)).strong());
+                }
+                ui.end_row();
+
+                for app in apps {
+                    ui.label(&app.name);
+                    for channel in LinkChannel::iter() {
+                        // Apps can be moved between the submixes, but not back to System
+                        ui.add_enabled_ui(channel != LinkChannel::System, |ui| {
+                            if ui.radio_value(&mut app.channel, channel, "").clicked() {
+                                changed.push(app.clone());
+                            }
+                        });
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn display_name(&self, channel: LinkChannel) -> String {
         match channel {
-            LinkChannel::System => "System",
-            LinkChannel::Link1 => "Link 1",
-            LinkChannel::Link2 => "Link 2",
-            LinkChannel::Link3 => "Link 3",
-            LinkChannel::Link4 => "Link 4",
+            LinkChannel::System => tr!("link-system"),
+            LinkChannel::Link1 => tr!("link-submix", number = 1),
+            LinkChannel::Link2 => tr!("link-submix", number = 2),
+            LinkChannel::Link3 => tr!("link-submix", number = 3),
+            LinkChannel::Link4 => tr!("link-submix", number = 4),
         }
     }
 }
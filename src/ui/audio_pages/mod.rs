@@ -1,17 +1,25 @@
 use crate::ui::states::audio_state::BeacnAudioState;
+use beacn_lib::audio::messages::Message;
 use egui::{Context, Ui};
 
 pub(crate) mod about;
 pub(crate) mod config;
+pub(crate) mod developer;
 pub(crate) mod equaliser;
 pub(crate) mod error;
 pub(crate) mod lighting;
 pub(crate) mod link;
+pub(crate) mod windows_profile;
 
 mod config_pages;
+mod lighting_preview;
 
 pub trait AudioPage {
     fn icon(&self) -> &'static str;
+
+    /// The name of the page, for screen readers as the nav button is only an icon
+    fn title(&self) -> String;
+
     fn show_on_error(&self) -> bool {
         false
     }
@@ -21,6 +29,11 @@ pub trait AudioPage {
     }
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnAudioState);
 
+    /// Whether this page controls the setting, used to flag ones the firmware doesn't support
+    fn covers(&self, _: &Message) -> bool {
+        false
+    }
+
     fn on_close(&mut self) {}
 
     fn on_page_open(&mut self, _: &Context) {}
@@ -0,0 +1,292 @@
+/*
+  Imports presets saved by the official Beacn app on Windows, and maps them onto the messages
+  we'd send for the same settings.
+
+  The Windows app stores presets as JSON, but the format isn't documented and key names have
+  varied between versions. Rather than expecting an exact layout, every value in the file is
+  flattened to a path of names (lowercased, with spaces and punctuation removed) and matched
+  against the settings we know about. Anything we don't recognise is reported back, rather
+  than silently dropped, so the user can see what didn't come across.
+
+  The file could hold anything, so every number is clamped to the range the device accepts
+  before it's turned into a message, and any which had to be are flagged in the preview.
+*/
+
+use crate::ui::audio_pages::config_pages::mic_setup::{EXCITER_FREQ, gain_range};
+use crate::ui::audio_pages::equaliser::eq_autoeq::{HP_EQ_MAX, HP_EQ_MIN};
+use crate::ui::audio_pages::equaliser::eq_common::{
+    MAX_FREQUENCY, MAX_GAIN, MIN_FREQUENCY, MIN_GAIN,
+};
+use anyhow::{Result, bail};
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::compressor::{Compressor, CompressorMode, CompressorThreshold};
+use beacn_lib::audio::messages::deesser::DeEsser;
+use beacn_lib::audio::messages::equaliser::{
+    EQBand, EQBandType, EQFrequency, EQGain, EQMode, EQQ, Equaliser,
+};
+use beacn_lib::audio::messages::exciter::{Exciter, ExciterFreq};
+use beacn_lib::audio::messages::expander::{Expander, ExpanderMode, ExpanderThreshold};
+use beacn_lib::audio::messages::headphone_eq::{HPEQType, HPEQValue, HeadphoneEQ};
+use beacn_lib::audio::messages::headphones::{HPLevel, Headphones};
+use beacn_lib::audio::messages::mic_setup::{MicGain, MicSetup, StudioMicGain};
+use beacn_lib::audio::messages::suppressor::Suppressor;
+use beacn_lib::manager::DeviceType;
+use beacn_lib::types::{HasRange, MakeUpGain, Percent};
+use serde_json::Value;
+use std::ops::RangeInclusive;
+use strum::IntoEnumIterator;
+
+// The ranges the device accepts, matching the sliders on each page
+const PERCENT: RangeInclusive<f32> = 0.0..=100.0;
+const THRESHOLD: RangeInclusive<f32> = -90.0..=0.0;
+const MAKEUP_GAIN: RangeInclusive<f32> = 0.0..=12.0;
+const EQ_Q: RangeInclusive<f32> = 0.1..=10.0;
+
+const EQ_BANDS: [EQBand; 8] = [
+    EQBand::Band1,
+    EQBand::Band2,
+    EQBand::Band3,
+    EQBand::Band4,
+    EQBand::Band5,
+    EQBand::Band6,
+    EQBand::Band7,
+    EQBand::Band8,
+];
+
+/// The device being imported to, some settings depend on which mode is currently in use
+pub struct ImportTarget {
+    pub device_type: DeviceType,
+    pub compressor_mode: CompressorMode,
+    pub expander_mode: ExpanderMode,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedSetting {
+    /// The path the setting was read from
+    pub name: String,
+    pub message: Message,
+
+    /// The value in the file, if it was outside the device's range and had to be clamped
+    pub clamped_from: Option<f32>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct WindowsProfile {
+    /// Each recognised setting
+    pub settings: Vec<ImportedSetting>,
+
+    /// Paths in the file which didn't match anything we know about
+    pub skipped: Vec<String>,
+}
+
+impl WindowsProfile {
+    pub fn parse(text: &str, target: &ImportTarget) -> Result<Self> {
+        let root: Value = serde_json::from_str(text)?;
+        if !root.is_object() {
+            bail!("Not a Beacn preset, expected a JSON object");
+        }
+
+        let mut values = vec![];
+        flatten(&root, &mut vec![], &mut values);
+
+        let mut profile = Self::default();
+        for (path, value) in values {
+            let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+            let name = path.join(".");
+            let mut clamped_from = None;
+            match map_setting(&segments, &value, target, &mut clamped_from) {
+                Some(messages) if !messages.is_empty() => {
+                    for message in messages {
+                        profile.settings.push(ImportedSetting {
+                            name: name.clone(),
+                            message,
+                            clamped_from,
+                        });
+                    }
+                }
+                _ => profile.skipped.push(name),
+            }
+        }
+
+        if profile.settings.is_empty() {
+            bail!("No settings in this file were recognised");
+        }
+        Ok(profile)
+    }
+}
+
+// Builds a list of every leaf value in the document, with its normalised path
+fn flatten(value: &Value, path: &mut Vec<String>, output: &mut Vec<(Vec<String>, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                path.push(normalise(key));
+                flatten(value, path, output);
+                path.pop();
+            }
+        }
+        Value::Array(list) => {
+            for (index, value) in list.iter().enumerate() {
+                path.push(index.to_string());
+                flatten(value, path, output);
+                path.pop();
+            }
+        }
+        _ => output.push((path.clone(), value.clone())),
+    }
+}
+
+fn normalise(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn map_setting(
+    path: &[&str],
+    value: &Value,
+    target: &ImportTarget,
+    clamped: &mut Option<f32>,
+) -> Option<Vec<Message>> {
+    let mut ranged = |range: RangeInclusive<f32>| -> Option<f32> {
+        let number = number(value)?;
+        let ranged = number.clamp(*range.start(), *range.end());
+        if ranged != number {
+            *clamped = Some(number);
+        }
+        Some(ranged)
+    };
+
+    // Presets are sometimes wrapped in a top level 'preset' or 'settings' object
+    let path = match path {
+        ["preset" | "settings" | "profile", rest @ ..] => rest,
+        path => path,
+    };
+
+    let message = match path {
+        ["micgain"] | ["micsetup" | "mic", "gain"] => {
+            let range = gain_range(target.device_type)?;
+            let gain = ranged(*range.start() as f32..=*range.end() as f32)?.round() as u32;
+            Message::MicSetup(match target.device_type {
+                DeviceType::BeacnStudio => MicSetup::StudioMicGain(StudioMicGain(gain)),
+                _ => MicSetup::MicGain(MicGain(gain)),
+            })
+        }
+        ["headphonelevel"] | ["headphones", "level" | "volume"] => Message::Headphones(
+            Headphones::HeadphoneLevel(HPLevel(ranged(HPLevel::range())?)),
+        ),
+
+        ["noisesuppression" | "suppressor", "enabled"] => {
+            Message::Suppressor(Suppressor::Enabled(flag(value)?))
+        }
+        ["noisesuppression" | "suppressor", "amount"] => {
+            Message::Suppressor(Suppressor::Amount(Percent(ranged(PERCENT)?)))
+        }
+
+        ["deesser", "enabled"] => Message::DeEsser(DeEsser::Enabled(flag(value)?)),
+        ["deesser", "amount"] => Message::DeEsser(DeEsser::Amount(Percent(ranged(PERCENT)?))),
+
+        ["exciter", "enabled"] => Message::Exciter(Exciter::Enabled(flag(value)?)),
+        ["exciter", "amount"] => Message::Exciter(Exciter::Amount(Percent(ranged(PERCENT)?))),
+        ["exciter", "frequency" | "freq"] => {
+            let range = *EXCITER_FREQ.start() as f32..=*EXCITER_FREQ.end() as f32;
+            Message::Exciter(Exciter::Frequency(ExciterFreq(ranged(range)?)))
+        }
+
+        // Enabling applies to both modes, matching the Compressor and Expander pages
+        ["compressor", "enabled"] => {
+            let enabled = flag(value)?;
+            let modes = CompressorMode::iter();
+            let enable = |mode| Message::Compressor(Compressor::Enabled(mode, enabled));
+            return Some(modes.map(enable).collect());
+        }
+        ["compressor", "threshold"] => {
+            let threshold = CompressorThreshold(ranged(THRESHOLD)?);
+            Message::Compressor(Compressor::Threshold(target.compressor_mode, threshold))
+        }
+        ["compressor", "makeupgain" | "makeup"] => {
+            let makeup = MakeUpGain(ranged(MAKEUP_GAIN)?);
+            Message::Compressor(Compressor::MakeupGain(target.compressor_mode, makeup))
+        }
+        ["expander" | "noisegate" | "gate", "enabled"] => {
+            let enabled = flag(value)?;
+            let modes = ExpanderMode::iter();
+            let enable = |mode| Message::Expander(Expander::Enabled(mode, enabled));
+            return Some(modes.map(enable).collect());
+        }
+        ["expander" | "noisegate" | "gate", "threshold"] => {
+            let threshold = ExpanderThreshold(ranged(THRESHOLD)?);
+            Message::Expander(Expander::Threshold(target.expander_mode, threshold))
+        }
+
+        ["headphoneeq" | "hpeq", band, "amount" | "gain"] | ["headphoneeq" | "hpeq", band] => {
+            let band = match *band {
+                "bass" | "low" => HPEQType::Bass,
+                "mids" | "mid" => HPEQType::Mids,
+                "treble" | "high" => HPEQType::Treble,
+                _ => return None,
+            };
+            Message::HeadphoneEQ(HeadphoneEQ::Amount(
+                band,
+                HPEQValue(ranged(HP_EQ_MIN..=HP_EQ_MAX)?),
+            ))
+        }
+
+        // Parametric bands always go to the Advanced EQ, the Simple EQ only has fixed bands
+        ["equalizer" | "equaliser" | "eq", "bands", index, property] => {
+            let band = *EQ_BANDS.get(index.parse::<usize>().ok()?)?;
+            let mode = EQMode::Advanced;
+            Message::Equaliser(match *property {
+                "enabled" => Equaliser::Enabled(mode, band, flag(value)?),
+                "frequency" | "freq" => {
+                    let range = MIN_FREQUENCY as f32..=MAX_FREQUENCY as f32;
+                    Equaliser::Frequency(mode, band, EQFrequency(ranged(range)?))
+                }
+                "gain" => Equaliser::Gain(mode, band, EQGain(ranged(MIN_GAIN..=MAX_GAIN)?)),
+                "q" => Equaliser::Q(mode, band, EQQ(ranged(EQ_Q)?)),
+                "type" => Equaliser::Type(mode, band, band_type(value)?),
+                _ => return None,
+            })
+        }
+        _ => return None,
+    };
+    Some(vec![message])
+}
+
+fn number(value: &Value) -> Option<f32> {
+    let number: f32 = match value {
+        Value::Number(number) => number.as_f64()? as f32,
+        Value::String(text) => text.trim().parse().ok()?,
+        _ => return None,
+    };
+    number.is_finite().then_some(number)
+}
+
+fn flag(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(flag) => Some(*flag),
+        Value::Number(number) => number.as_f64().map(|n| n != 0.0),
+        Value::String(text) => match normalise(text).as_str() {
+            "true" | "on" | "yes" => Some(true),
+            "false" | "off" | "no" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn band_type(value: &Value) -> Option<EQBandType> {
+    let Value::String(text) = value else {
+        return None;
+    };
+    Some(match normalise(text).as_str() {
+        "bell" | "peak" | "peaking" | "bellband" => EQBandType::BellBand,
+        "lowshelf" => EQBandType::LowShelf,
+        "highshelf" => EQBandType::HighShelf,
+        "lowpass" | "lowpassfilter" => EQBandType::LowPassFilter,
+        "highpass" | "highpassfilter" => EQBandType::HighPassFilter,
+        "notch" | "notchfilter" => EQBandType::NotchFilter,
+        _ => return None,
+    })
+}
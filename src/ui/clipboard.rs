@@ -0,0 +1,33 @@
+// A simple in-app clipboard, used for copying settings between connected devices. This isn't
+// the system clipboard, it only lives for as long as the app is running.
+
+use crate::ui::audio_pages::equaliser::eq_common::Bands;
+use beacn_lib::audio::messages::equaliser::EQMode;
+use std::sync::{LazyLock, Mutex};
+
+pub static CLIPBOARD: LazyLock<Mutex<Clipboard>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Default, Clone)]
+pub struct Clipboard {
+    pub equaliser: Option<CopiedEqualiser>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CopiedEqualiser {
+    /// The serial of the device this was copied from
+    pub serial: String,
+
+    /// The mode which was active when copied
+    pub mode: EQMode,
+    pub bands: Bands,
+}
+
+pub fn copy_equaliser(equaliser: CopiedEqualiser) {
+    if let Ok(mut clipboard) = CLIPBOARD.lock() {
+        clipboard.equaliser = Some(equaliser);
+    }
+}
+
+pub fn get_equaliser() -> Option<CopiedEqualiser> {
+    CLIPBOARD.lock().ok().and_then(|c| c.equaliser.clone())
+}
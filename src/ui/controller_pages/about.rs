@@ -1,11 +1,11 @@
+use crate::i18n::tr;
 use crate::ui::controller_pages::ControllerPage;
+use crate::ui::shared_pages::identity::identity_ui;
+use crate::ui::shared_pages::ignore::ignore_ui;
+use crate::ui::shared_pages::stats::display_stats;
 use crate::ui::states::controller_state::BeacnControllerState;
 use beacn_lib::manager::DeviceType;
-use egui::{Align, Layout, RichText, Slider, Ui};
-use std::time::Duration;
-
-const LABEL_WIDTH: f32 = 120.0;
-const CONTROL_WIDTH: f32 = 260.0;
+use egui::{RichText, Ui};
 
 pub struct About {}
 
@@ -17,7 +17,11 @@ impl About {
 
 impl ControllerPage for About {
     fn icon(&self) -> &'static str {
-        "gear"
+        "info"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-about")
     }
 
     fn show_on_error(&self) -> bool {
@@ -26,8 +30,8 @@ impl ControllerPage for About {
 
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
         match state.device_definition.device_type {
-            DeviceType::BeacnMix => ui.heading("About Beacn Mix"),
-            DeviceType::BeacnMixCreate => ui.heading("About Beacn Mix Create"),
+            DeviceType::BeacnMix => ui.heading(tr!("about-mix")),
+            DeviceType::BeacnMixCreate => ui.heading(tr!("about-mix-create")),
             _ => ui.heading("ERROR"),
         };
 
@@ -38,9 +42,9 @@ impl ControllerPage for About {
             state.device_definition.location.bus_number, state.device_definition.location.address
         );
 
-        let location = RichText::new("USB Location: ").strong().size(14.0);
-        let serial = RichText::new("Serial: ").strong().size(14.0);
-        let version = RichText::new("Version: ").strong().size(14.0);
+        let location = RichText::new(tr!("about-location")).strong().size(14.0);
+        let serial = RichText::new(tr!("about-serial")).strong().size(14.0);
+        let version = RichText::new(tr!("about-version")).strong().size(14.0);
 
         let location_value = RichText::new(location_text).size(14.0);
         let serial_value = RichText::new(serial_text).size(14.0);
@@ -60,59 +64,10 @@ impl ControllerPage for About {
             ui.label(version);
             ui.label(version_value)
         });
-        ui.add_space(5.0);
-        ui.separator();
-        ui.add_space(5.0);
-
-        let mut display_brightness = state.saved_settings.display_brightness;
-        let slider = Slider::new(&mut display_brightness, 1..=100)
-            .suffix("%")
-            .trailing_fill(true);
-        if self.draw_slider(ui, "Display Brightness:", slider) {
-            let _ = state.set_display_brightness(display_brightness, true);
-        }
-
-        if state.device_definition.device_type != DeviceType::BeacnMix {
-            let mut button_brightness = state.saved_settings.button_brightness;
-            let slider = Slider::new(&mut button_brightness, 0..=10).trailing_fill(true);
-            if self.draw_slider(ui, "Button Brightness:", slider) {
-                let _ = state.set_button_brightness(button_brightness, true);
-            }
-        }
-
-        let mut display_timeout = state.saved_settings.display_dim.as_secs();
-        let slider = Slider::new(&mut display_timeout, 30..=300)
-            .suffix("s")
-            .trailing_fill(true);
-        if self.draw_slider(ui, "Display Timeout:", slider) {
-            let _ = state.set_display_dim(Duration::from_secs(display_timeout), true);
+        display_stats(ui, &state.device_definition);
+        if let Some(identity) = identity_ui(ui, &state.saved_settings.identity) {
+            state.set_identity(identity);
         }
-    }
-}
-
-impl About {
-    fn draw_slider(&mut self, ui: &mut Ui, label: &str, slider: Slider) -> bool {
-        let mut changed = false;
-        ui.horizontal(|ui| {
-            ui.allocate_ui_with_layout(
-                egui::vec2(LABEL_WIDTH, ui.spacing().interact_size.y),
-                Layout::left_to_right(Align::Center),
-                |ui| {
-                    ui.set_width(LABEL_WIDTH);
-                    ui.label(label);
-                },
-            );
-
-            ui.allocate_ui_with_layout(
-                egui::vec2(CONTROL_WIDTH, ui.spacing().interact_size.y),
-                Layout::left_to_right(Align::Center),
-                |ui| {
-                    ui.spacing_mut().slider_width = CONTROL_WIDTH;
-                    changed = ui.add(slider).changed();
-                },
-            );
-        });
-        ui.add_space(4.);
-        changed
+        ignore_ui(ui, &state.device_definition);
     }
 }
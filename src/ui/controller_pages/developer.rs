@@ -0,0 +1,174 @@
+use crate::device_manager::ControlMessage;
+use crate::i18n::tr;
+use crate::settings;
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::shared_pages::console::ConsoleLog;
+use crate::ui::states::controller_state::BeacnControllerState;
+use anyhow::Result;
+use beacn_lib::controller::ButtonLighting;
+use beacn_lib::types::RGBA;
+use egui::{ComboBox, DragValue, Ui};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display, EnumIter)]
+enum RawCommand {
+    Enabled,
+    KeepAlive,
+    DisplayBrightness,
+    ButtonBrightness,
+    DimTimeout,
+    ButtonColour,
+}
+
+// Allows sending control messages directly to the device, bypassing the normal settings. Note
+// that the display integration may overwrite anything sent here. This is only shown when
+// developer mode is enabled in the app settings.
+pub struct DeveloperPage {
+    command: RawCommand,
+    enabled: bool,
+    value: u32,
+    button: ButtonLighting,
+    colour: [u8; 3],
+
+    log: ConsoleLog,
+}
+
+impl DeveloperPage {
+    pub fn new() -> Self {
+        Self {
+            command: RawCommand::Enabled,
+            enabled: true,
+            value: 0,
+            button: ButtonLighting::Dial1,
+            colour: [255, 255, 255],
+
+            log: ConsoleLog::default(),
+        }
+    }
+}
+
+impl ControllerPage for DeveloperPage {
+    fn icon(&self) -> &'static str {
+        "terminal"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-developer")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn should_show(&self, _: &BeacnControllerState) -> bool {
+        settings::get().developer_mode
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.heading(tr!("page-developer"));
+        ui.add_space(10.0);
+        ui.label(tr!("developer-warning"));
+        ui.add_space(10.0);
+
+        ui.horizontal(|ui| {
+            ComboBox::from_id_salt("developer_command")
+                .selected_text(self.command.to_string())
+                .show_ui(ui, |ui| {
+                    for command in RawCommand::iter() {
+                        let label = command.to_string();
+                        ui.selectable_value(&mut self.command, command, label);
+                    }
+                });
+
+            match self.command {
+                RawCommand::Enabled => {
+                    ui.checkbox(&mut self.enabled, tr!("developer-enabled"));
+                }
+                RawCommand::KeepAlive => {}
+                RawCommand::DisplayBrightness | RawCommand::ButtonBrightness => {
+                    ui.add(DragValue::new(&mut self.value).range(0..=255));
+                }
+                RawCommand::DimTimeout => {
+                    ui.add(DragValue::new(&mut self.value).suffix("s"));
+                }
+                RawCommand::ButtonColour => {
+                    ComboBox::from_id_salt("developer_button")
+                        .selected_text(format!("{:?}", self.button))
+                        .show_ui(ui, |ui| {
+                            for button in ButtonLighting::iter() {
+                                let label = format!("{button:?}");
+                                ui.selectable_value(&mut self.button, button, label);
+                            }
+                        });
+                    ui.color_edit_button_srgb(&mut self.colour);
+                }
+            }
+        });
+
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            if ui.button(tr!("developer-send")).clicked() {
+                self.send(state);
+            }
+            if ui.button(tr!("developer-clear")).clicked() {
+                self.log.clear();
+            }
+        });
+
+        ui.add_space(5.0);
+        ui.separator();
+        self.log.ui(ui);
+    }
+}
+
+impl DeveloperPage {
+    fn send(&mut self, state: &BeacnControllerState) {
+        let (tx, rx) = oneshot::channel();
+
+        let value = self.value.min(u8::MAX as u32) as u8;
+        let (request, message) = match self.command {
+            RawCommand::Enabled => (
+                format!("Enabled({})", self.enabled),
+                ControlMessage::Enabled(self.enabled, tx),
+            ),
+            RawCommand::KeepAlive => (String::from("KeepAlive"), ControlMessage::KeepAlive(tx)),
+            RawCommand::DisplayBrightness => (
+                format!("DisplayBrightness({value})"),
+                ControlMessage::DisplayBrightness(value, tx),
+            ),
+            RawCommand::ButtonBrightness => (
+                format!("ButtonBrightness({value})"),
+                ControlMessage::ButtonBrightness(value, tx),
+            ),
+            RawCommand::DimTimeout => {
+                let timeout = Duration::from_secs(self.value as u64);
+                (
+                    format!("DimTimeout({timeout:?})"),
+                    ControlMessage::DimTimeout(timeout, tx),
+                )
+            }
+            RawCommand::ButtonColour => {
+                let [red, green, blue] = self.colour;
+                let colour = RGBA {
+                    red,
+                    green,
+                    blue,
+                    alpha: 255,
+                };
+                (
+                    format!("ButtonColour({:?}, {:?})", self.button, self.colour),
+                    ControlMessage::ButtonColour(self.button, colour, tx),
+                )
+            }
+        };
+
+        let response = || -> Result<String> {
+            state.send_control(message)?;
+            rx.recv()??;
+            Ok(tr!("developer-ok"))
+        };
+        self.log.push(request, response());
+    }
+}
@@ -0,0 +1,316 @@
+use crate::i18n::tr;
+use crate::integrations::pipeweaver::layout::{ScreenLayout, VolumeUnit};
+use crate::integrations::{AssignableChannel, DisplayBackend};
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::states::controller_state::BeacnControllerState;
+use egui::{ComboBox, Frame, Grid, Id, ScrollArea, Ui, Vec2, vec2};
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+use ulid::Ulid;
+
+const SLOT_SIZE: Vec2 = vec2(150.0, 50.0);
+
+// How often we ask the display handler for an updated channel list
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+// Things which can be dragged onto a dial, either a channel (or 'Empty') from the channel
+// list, or an already assigned dial, which swaps the two.
+#[derive(Debug, Copy, Clone)]
+enum DragPayload {
+    Channel(Option<Ulid>),
+    Dial(usize),
+}
+
+pub struct DialsPage {
+    channels: Vec<AssignableChannel>,
+    last_refresh: Option<Instant>,
+}
+
+impl DialsPage {
+    pub fn new() -> Self {
+        Self {
+            channels: vec![],
+            last_refresh: None,
+        }
+    }
+}
+
+impl ControllerPage for DialsPage {
+    fn icon(&self) -> &'static str {
+        "pipeweaver"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-dials")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.heading(tr!("page-dials"));
+        ui.add_space(20.0);
+
+        if state.saved_settings.display_backend != DisplayBackend::Pipeweaver {
+            ui.label(tr!("dials-pipeweaver-only"));
+            return;
+        }
+
+        self.refresh_channels(ui, state);
+
+        ScrollArea::vertical().show(ui, |ui| {
+            self.draw_assignment(ui, state);
+
+            ui.add_space(15.0);
+            ui.separator();
+            ui.add_space(10.0);
+
+            ui.heading(tr!("dials-colours"));
+            ui.add_space(10.0);
+            self.draw_colours(ui, state);
+
+            ui.add_space(15.0);
+            ui.separator();
+            ui.add_space(10.0);
+
+            ui.heading(tr!("dials-volume"));
+            ui.add_space(10.0);
+            self.draw_volume_units(ui, state);
+
+            ui.add_space(15.0);
+            ui.separator();
+            ui.add_space(10.0);
+
+            ui.heading(tr!("dials-layout"));
+            ui.add_space(10.0);
+            self.draw_screen_layout(ui, state);
+        });
+    }
+}
+
+impl DialsPage {
+    fn draw_assignment(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        let mut follow_order = state.saved_settings.dial_assignment.is_none();
+        if ui
+            .checkbox(&mut follow_order, tr!("dials-follow-order"))
+            .changed()
+        {
+            let assignment = match follow_order {
+                true => None,
+                false => Some(self.default_assignment()),
+            };
+            let _ = state.set_dial_assignment(assignment, true);
+        }
+
+        let Some(mut assignment) = state.saved_settings.dial_assignment else {
+            ui.add_space(10.0);
+            ui.label(tr!("dials-follow-order-note"));
+            return;
+        };
+
+        ui.add_space(10.0);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            for index in 0..assignment.len() {
+                changed |= self.draw_slot(ui, index, &mut assignment);
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.label(tr!("dials-drag"));
+        ui.add_space(4.0);
+        ui.horizontal_wrapped(|ui| {
+            self.draw_channel(ui, &tr!("dials-empty"), None);
+            for channel in self.channels.iter().filter(|c| !c.is_output) {
+                self.draw_channel(ui, &channel.name, Some(channel.id));
+            }
+        });
+        if self.channels.is_empty() {
+            ui.label(tr!("dials-waiting"));
+        }
+
+        if changed {
+            let _ = state.set_dial_assignment(Some(assignment), true);
+        }
+
+        ui.add_space(10.0);
+        ui.label(tr!("dials-assignment-note"));
+    }
+
+    fn draw_colours(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.label(tr!("dials-colours-intro"));
+        ui.add_space(4.0);
+
+        let mut colours = state.saved_settings.colour_overrides.clone();
+        let mut changed = false;
+        Grid::new("channel_colours")
+            .num_columns(3)
+            .spacing([10.0, 4.0])
+            .show(ui, |ui| {
+                for (is_output, label) in
+                    [(false, tr!("dials-inputs")), (true, tr!("dials-outputs"))]
+                {
+                    ui.strong(label);
+                    ui.end_row();
+
+                    for channel in self.channels.iter().filter(|c| c.is_output == is_output) {
+                        let current = colours.get(&channel.id).copied();
+                        let mut colour = current.unwrap_or(channel.colour);
+
+                        ui.label(&channel.name);
+                        if ui.color_edit_button_srgb(&mut colour).changed() {
+                            colours.insert(channel.id, colour);
+                            changed = true;
+                        }
+                        if current.is_some() && ui.button(tr!("dials-reset")).clicked() {
+                            colours.remove(&channel.id);
+                            changed = true;
+                        }
+                        ui.end_row();
+                    }
+                }
+            });
+
+        // The colour pickers are dragged, so wait until they're let go to save
+        if changed && state.set_colour_overrides(colours, false).is_ok() {
+            state.save_when_released();
+        }
+    }
+
+    fn draw_volume_units(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.label(tr!("dials-volume-intro"));
+        ui.add_space(4.0);
+
+        let mut units = state.saved_settings.volume_units.clone();
+        let mut changed = false;
+        Grid::new("channel_volume_units")
+            .num_columns(2)
+            .spacing([10.0, 4.0])
+            .show(ui, |ui| {
+                for (is_output, label) in
+                    [(false, tr!("dials-inputs")), (true, tr!("dials-outputs"))]
+                {
+                    ui.strong(label);
+                    ui.end_row();
+
+                    for channel in self.channels.iter().filter(|c| c.is_output == is_output) {
+                        let mut unit = units.get(&channel.id).copied().unwrap_or_default();
+
+                        ui.label(&channel.name);
+                        ComboBox::from_id_salt(("volume_unit", channel.id))
+                            .selected_text(unit.to_string())
+                            .show_ui(ui, |ui| {
+                                for variant in VolumeUnit::iter() {
+                                    let label = variant.to_string();
+                                    if ui.selectable_value(&mut unit, variant, label).changed() {
+                                        changed = true;
+                                    }
+                                }
+                            });
+
+                        // Percent is the default, so there's no need to store it
+                        match unit {
+                            VolumeUnit::Percent => units.remove(&channel.id),
+                            unit => units.insert(channel.id, unit),
+                        };
+                        ui.end_row();
+                    }
+                }
+            });
+
+        if changed {
+            let _ = state.set_volume_units(units, true);
+        }
+    }
+
+    fn draw_screen_layout(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.label(tr!("dials-layout-intro"));
+        ui.label(tr!("dials-layout-note"));
+        ui.add_space(4.0);
+
+        let mut layout = state.saved_settings.screen_layout;
+        let mut changed = false;
+        ComboBox::from_id_salt("screen_layout")
+            .selected_text(layout.to_string())
+            .show_ui(ui, |ui| {
+                for variant in ScreenLayout::iter() {
+                    let label = variant.to_string();
+                    changed |= ui.selectable_value(&mut layout, variant, label).changed();
+                }
+            });
+
+        if changed {
+            let _ = state.set_screen_layout(layout, true);
+        }
+    }
+
+    fn refresh_channels(&mut self, ui: &Ui, state: &BeacnControllerState) {
+        if self
+            .last_refresh
+            .is_none_or(|last| last.elapsed() >= REFRESH_INTERVAL)
+        {
+            if let Ok(channels) = state.get_assignable_channels() {
+                self.channels = channels;
+            }
+            self.last_refresh = Some(Instant::now());
+        }
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+    }
+
+    // When switching from the channel order, start with the first four channels we know about
+    fn default_assignment(&self) -> [Option<Ulid>; 4] {
+        let mut assignment = [None; 4];
+        let inputs = self.channels.iter().filter(|c| !c.is_output);
+        for (slot, channel) in assignment.iter_mut().zip(inputs) {
+            *slot = Some(channel.id);
+        }
+        assignment
+    }
+
+    fn channel_name(&self, channel: Option<Ulid>) -> String {
+        match channel {
+            None => tr!("dials-empty"),
+            Some(id) => self
+                .channels
+                .iter()
+                .find(|c| c.id == id)
+                .map(|c| c.name.clone())
+                .unwrap_or_else(|| tr!("dials-unknown")),
+        }
+    }
+
+    fn draw_slot(&self, ui: &mut Ui, index: usize, assignment: &mut [Option<Ulid>; 4]) -> bool {
+        let name = self.channel_name(assignment[index]);
+
+        let frame = Frame::group(ui.style());
+        let (_, payload) = ui.dnd_drop_zone::<DragPayload, ()>(frame, |ui| {
+            ui.set_min_size(SLOT_SIZE);
+            ui.vertical_centered(|ui| {
+                ui.strong(tr!("dials-dial", number = index + 1));
+                let id = Id::new("dial_slot").with(index);
+                ui.dnd_drag_source(id, DragPayload::Dial(index), |ui| {
+                    ui.label(name);
+                });
+            });
+        });
+
+        let Some(payload) = payload else {
+            return false;
+        };
+        match *payload {
+            DragPayload::Channel(channel) => assignment[index] = channel,
+            DragPayload::Dial(from) => assignment.swap(from, index),
+        }
+        true
+    }
+
+    fn draw_channel(&self, ui: &mut Ui, name: &str, channel: Option<Ulid>) {
+        let id = Id::new("dial_channel").with(channel);
+        ui.dnd_drag_source(id, DragPayload::Channel(channel), |ui| {
+            Frame::group(ui.style()).show(ui, |ui| {
+                ui.label(name);
+            });
+        });
+    }
+}
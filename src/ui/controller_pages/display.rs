@@ -0,0 +1,505 @@
+use crate::i18n::tr;
+use crate::integrations::DisplayBackend;
+use crate::integrations::header_widgets::{HeaderSlot, HeaderWidget};
+use crate::integrations::orientation::Orientation;
+use crate::integrations::pipeweaver::discovery::{DEFAULT_ADDRESS, DiscoveredDaemon, discover};
+use crate::runtime;
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::states::controller_state::BeacnControllerState;
+use anyhow::Result;
+use beacn_lib::controller::ButtonLighting;
+use beacn_lib::manager::DeviceType;
+use egui::{Align, Layout, Slider, TextEdit, Ui};
+use log::warn;
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+const LABEL_WIDTH: f32 = 140.0;
+const CONTROL_WIDTH: f32 = 260.0;
+const DISCOVERY_TIME: Duration = Duration::from_secs(3);
+
+// The Mix will only accept dim timeouts up to 4 minutes, so rather than a slider we offer
+// a fixed set of sensible values (in seconds).
+const DIM_TIMEOUTS: [u32; 6] = [30, 60, 90, 120, 180, 240];
+
+// How often the screen is moved, and how long before the header fades, for burn-in protection
+// (in minutes)
+const SHIFT_INTERVALS: [u32; 5] = [5, 10, 15, 30, 60];
+const FADE_DELAYS: [u32; 5] = [0, 10, 30, 60, 120];
+
+// How often a keepalive is sent (in seconds), and how long without interaction before they stop
+// so the device can go to sleep (in minutes)
+const KEEP_ALIVE_INTERVALS: [u32; 4] = [5, 10, 30, 60];
+const SLEEP_DELAYS: [u32; 5] = [0, 15, 30, 60, 120];
+
+type DiscoveryResult = Result<Vec<DiscoveredDaemon>>;
+
+pub struct DisplayPage {
+    // The address being edited, loaded from the settings when first drawn
+    address: Option<String>,
+
+    discovery: Option<oneshot::Receiver<DiscoveryResult>>,
+    discovered: Option<DiscoveryResult>,
+}
+
+impl DisplayPage {
+    pub fn new() -> Self {
+        Self {
+            address: None,
+            discovery: None,
+            discovered: None,
+        }
+    }
+}
+
+impl ControllerPage for DisplayPage {
+    fn icon(&self) -> &'static str {
+        "bulb"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-display")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.heading(tr!("page-display"));
+        ui.add_space(20.0);
+
+        let mut display_brightness = state.saved_settings.display_brightness;
+        let slider = Slider::new(&mut display_brightness, 1..=100)
+            .suffix("%")
+            .trailing_fill(true);
+        if self.draw_slider(ui, &tr!("display-screen-brightness"), slider) {
+            let _ = state.set_display_brightness(display_brightness, false);
+            state.save_when_released();
+        }
+
+        // The Mix doesn't have any button lighting, only the Mix Create does.
+        if state.device_definition.device_type != DeviceType::BeacnMix {
+            let mut button_brightness = state.saved_settings.button_brightness;
+            let slider = Slider::new(&mut button_brightness, 0..=10).trailing_fill(true);
+            if self.draw_slider(ui, &tr!("display-button-brightness"), slider) {
+                let _ = state.set_button_brightness(button_brightness, false);
+                state.save_when_released();
+            }
+        }
+
+        let dim = state.saved_settings.display_dim.as_secs();
+        let mut timeout = u32::try_from(dim).unwrap_or(u32::MAX);
+        let label = tr!("display-dim");
+        if self.draw_choice(
+            ui,
+            &label,
+            "dim_timeout",
+            &mut timeout,
+            &DIM_TIMEOUTS,
+            seconds,
+        ) {
+            let _ = state.set_display_dim(Duration::from_secs(timeout.into()), true);
+        }
+
+        let mut orientation = state.saved_settings.orientation;
+        if self.draw_orientation(ui, &mut orientation) {
+            let _ = state.set_orientation(orientation, true);
+        }
+
+        let mut enabled = state.saved_settings.screen_enabled;
+        let (label, text) = (
+            tr!("display-screen-enabled"),
+            tr!("display-screen-enabled-text"),
+        );
+        if self.draw_checkbox(ui, &label, &mut enabled, &text) {
+            let _ = state.set_screen_enabled(enabled, true);
+        }
+
+        if state.device_definition.device_type != DeviceType::BeacnMix {
+            ui.add_space(5.0);
+            ui.separator();
+            ui.add_space(5.0);
+            self.draw_button_colours(ui, state);
+        }
+
+        ui.add_space(5.0);
+        ui.separator();
+        ui.add_space(5.0);
+
+        let mut coalesce = state.saved_settings.coalesce_updates;
+        let (label, text) = (tr!("display-smooth"), tr!("display-smooth-text"));
+        if self.draw_checkbox(ui, &label, &mut coalesce, &text) {
+            let _ = state.set_coalesce_updates(coalesce, true);
+        }
+
+        let mut performance = state.saved_settings.performance_mode;
+        let (label, text) = (tr!("display-performance"), tr!("display-performance-text"));
+        if self.draw_checkbox(ui, &label, &mut performance, &text) {
+            let _ = state.set_performance_mode(performance, true);
+        }
+
+        let mut burn_in = state.saved_settings.burn_in;
+        let (label, text) = (tr!("display-burn-in"), tr!("display-burn-in-text"));
+        let mut changed = self.draw_checkbox(ui, &label, &mut burn_in.enabled, &text);
+        if burn_in.enabled {
+            let (id, value) = ("burn_in_shift", &mut burn_in.shift_minutes);
+            let label = tr!("display-burn-in-shift");
+            changed |= self.draw_choice(ui, &label, id, value, &SHIFT_INTERVALS, minutes);
+
+            let (id, value) = ("burn_in_fade", &mut burn_in.fade_minutes);
+            let label = tr!("display-burn-in-fade");
+            changed |= self.draw_choice(ui, &label, id, value, &FADE_DELAYS, minutes);
+        }
+        if changed {
+            let _ = state.set_burn_in(burn_in, true);
+        }
+
+        let mut keep_alive = state.saved_settings.keep_alive;
+        let (label, text) = (tr!("display-keep-alive"), tr!("display-keep-alive-text"));
+        let mut changed = self.draw_checkbox(ui, &label, &mut keep_alive.enabled, &text);
+        if keep_alive.enabled {
+            let (id, value) = ("keep_alive_interval", &mut keep_alive.interval_secs);
+            let label = tr!("display-keep-alive-interval");
+            changed |= self.draw_choice(ui, &label, id, value, &KEEP_ALIVE_INTERVALS, seconds);
+
+            let (id, value) = ("keep_alive_sleep", &mut keep_alive.sleep_minutes);
+            let label = tr!("display-keep-alive-sleep");
+            changed |= self.draw_choice(ui, &label, id, value, &SLEEP_DELAYS, minutes);
+        }
+        if changed {
+            let _ = state.set_keep_alive(keep_alive, true);
+        }
+
+        let mut widgets = state.saved_settings.header_widgets;
+        let mut changed = false;
+        for slot in HeaderSlot::iter() {
+            changed |= self.draw_header_widget(ui, slot, widgets.get_mut(slot));
+        }
+        if changed {
+            let _ = state.set_header_widgets(widgets, true);
+        }
+
+        let mut backend = state.saved_settings.display_backend;
+        if self.draw_backend(ui, &mut backend) {
+            let _ = state.set_display_backend(backend, true);
+        }
+        match backend {
+            DisplayBackend::Pipeweaver => self.draw_address(ui, state),
+            DisplayBackend::Pipewire => {
+                ui.label(tr!("display-backend-pipewire"));
+            }
+            DisplayBackend::Demo => {
+                ui.label(tr!("display-backend-demo"));
+            }
+        }
+
+        ui.add_space(10.0);
+        ui.label(tr!("display-saved"));
+    }
+}
+
+impl DisplayPage {
+    fn draw_label(&self, ui: &mut Ui, label: &str) {
+        ui.allocate_ui_with_layout(
+            egui::vec2(LABEL_WIDTH, ui.spacing().interact_size.y),
+            Layout::left_to_right(Align::Center),
+            |ui| {
+                ui.set_width(LABEL_WIDTH);
+                ui.label(label);
+            },
+        );
+    }
+
+    fn draw_slider(&mut self, ui: &mut Ui, label: &str, slider: Slider) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            self.draw_label(ui, label);
+            ui.allocate_ui_with_layout(
+                egui::vec2(CONTROL_WIDTH, ui.spacing().interact_size.y),
+                Layout::left_to_right(Align::Center),
+                |ui| {
+                    ui.spacing_mut().slider_width = CONTROL_WIDTH;
+                    changed = ui.add(slider).changed();
+                },
+            );
+        });
+        ui.add_space(4.);
+        changed
+    }
+
+    fn draw_checkbox(&mut self, ui: &mut Ui, label: &str, value: &mut bool, text: &str) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            self.draw_label(ui, label);
+            changed = ui.checkbox(value, text).changed();
+        });
+        ui.add_space(4.);
+        changed
+    }
+
+    fn draw_backend(&mut self, ui: &mut Ui, backend: &mut DisplayBackend) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            self.draw_label(ui, &tr!("display-backend"));
+            ui.allocate_ui_with_layout(
+                egui::vec2(CONTROL_WIDTH, ui.spacing().interact_size.y),
+                Layout::left_to_right(Align::Center),
+                |ui| {
+                    ui.spacing_mut().combo_width = CONTROL_WIDTH;
+                    egui::ComboBox::from_id_salt("display_backend")
+                        .selected_text(backend.to_string())
+                        .show_ui(ui, |ui| {
+                            for variant in DisplayBackend::iter() {
+                                let label = variant.to_string();
+                                if ui.selectable_value(backend, variant, label).changed() {
+                                    changed = true;
+                                }
+                            }
+                        });
+                },
+            );
+        });
+        ui.add_space(4.);
+        changed
+    }
+
+    fn draw_header_widget(
+        &mut self,
+        ui: &mut Ui,
+        slot: HeaderSlot,
+        widget: &mut HeaderWidget,
+    ) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            self.draw_label(ui, &tr!("display-header", slot = slot));
+            ui.allocate_ui_with_layout(
+                egui::vec2(CONTROL_WIDTH, ui.spacing().interact_size.y),
+                Layout::left_to_right(Align::Center),
+                |ui| {
+                    ui.spacing_mut().combo_width = CONTROL_WIDTH;
+                    egui::ComboBox::from_id_salt(format!("header_widget_{slot}"))
+                        .selected_text(widget.to_string())
+                        .show_ui(ui, |ui| {
+                            for variant in HeaderWidget::iter() {
+                                let label = variant.to_string();
+                                if ui.selectable_value(widget, variant, label).changed() {
+                                    changed = true;
+                                }
+                            }
+                        });
+                },
+            );
+        });
+        ui.add_space(4.);
+        changed
+    }
+
+    fn draw_orientation(&mut self, ui: &mut Ui, orientation: &mut Orientation) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            self.draw_label(ui, &tr!("display-orientation"));
+            ui.allocate_ui_with_layout(
+                egui::vec2(CONTROL_WIDTH, ui.spacing().interact_size.y),
+                Layout::left_to_right(Align::Center),
+                |ui| {
+                    ui.spacing_mut().combo_width = CONTROL_WIDTH;
+                    egui::ComboBox::from_id_salt("orientation")
+                        .selected_text(orientation.to_string())
+                        .show_ui(ui, |ui| {
+                            for variant in Orientation::iter() {
+                                let label = variant.to_string();
+                                if ui.selectable_value(orientation, variant, label).changed() {
+                                    changed = true;
+                                }
+                            }
+                        });
+                },
+            );
+        });
+        ui.add_space(4.);
+        changed
+    }
+
+    fn draw_button_colours(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        let mut colours = state.saved_settings.button_colours.clone();
+        let mut changed = false;
+
+        for button in ButtonLighting::iter() {
+            let key = format!("{button:?}");
+            ui.horizontal(|ui| {
+                self.draw_label(ui, &tr!("display-button", button = key));
+
+                // Until a colour is picked, the button follows the dial integration
+                let mut colour = colours.get(&key).copied().unwrap_or([255, 255, 255]);
+                if ui.color_edit_button_srgb(&mut colour).changed() {
+                    colours.insert(key.clone(), colour);
+                    changed = true;
+                }
+                if colours.contains_key(&key) {
+                    if ui.button(tr!("display-button-reset")).clicked() {
+                        colours.remove(&key);
+                        changed = true;
+                    }
+                } else {
+                    ui.label(tr!("display-button-default"));
+                }
+            });
+            ui.add_space(4.);
+        }
+        ui.label(tr!("display-button-note"));
+
+        // The colour pickers are dragged, so wait until they're let go to save
+        if changed {
+            match state.set_button_colours(colours, false) {
+                Ok(()) => state.save_when_released(),
+                Err(e) => warn!("Failed to set button colours: {e}"),
+            }
+        }
+    }
+
+    // Values which aren't an option (from a hand edited config) are still shown as they are
+    fn draw_choice(
+        &mut self,
+        ui: &mut Ui,
+        label: &str,
+        id: &str,
+        value: &mut u32,
+        options: &[u32],
+        describe: fn(u32) -> String,
+    ) -> bool {
+        let mut changed = false;
+        let selected = describe(*value);
+
+        ui.horizontal(|ui| {
+            self.draw_label(ui, label);
+            ui.allocate_ui_with_layout(
+                egui::vec2(CONTROL_WIDTH, ui.spacing().interact_size.y),
+                Layout::left_to_right(Align::Center),
+                |ui| {
+                    ui.spacing_mut().combo_width = CONTROL_WIDTH;
+                    egui::ComboBox::from_id_salt(id)
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for option in options {
+                                let label = describe(*option);
+                                if ui.selectable_value(value, *option, label).changed() {
+                                    changed = true;
+                                }
+                            }
+                        });
+                },
+            );
+        });
+        ui.add_space(4.);
+        changed
+    }
+
+    fn draw_address(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        let saved = &state.saved_settings.pipeweaver_address;
+        let mut address = self
+            .address
+            .take()
+            .unwrap_or_else(|| saved.clone().unwrap_or_default());
+
+        let mut apply = false;
+        ui.horizontal(|ui| {
+            self.draw_label(ui, &tr!("display-pipeweaver-address"));
+            let edit = TextEdit::singleline(&mut address)
+                .hint_text(DEFAULT_ADDRESS)
+                .desired_width(CONTROL_WIDTH);
+            let response = ui.add(edit);
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                apply = true;
+            }
+            if ui.button(tr!("display-pipeweaver-apply")).clicked() {
+                apply = true;
+            }
+        });
+        self.address = Some(address);
+        ui.add_space(4.);
+
+        ui.horizontal(|ui| {
+            self.draw_label(ui, "");
+            if self.discovery.is_some() {
+                ui.spinner();
+                ui.label(tr!("display-pipeweaver-searching"));
+            } else if ui.button(tr!("display-pipeweaver-search")).clicked() {
+                let (tx, rx) = oneshot::channel();
+                runtime().spawn_blocking(move || {
+                    let _ = tx.send(discover(DISCOVERY_TIME));
+                });
+                self.discovery = Some(rx);
+                self.discovered = None;
+            }
+        });
+
+        if let Some(rx) = &self.discovery {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.discovery = None;
+                    self.discovered = Some(result);
+                }
+                Err(oneshot::TryRecvError::Disconnected) => self.discovery = None,
+                Err(oneshot::TryRecvError::Empty) => {
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                }
+            }
+        }
+
+        let mut picked = None;
+        match &self.discovered {
+            Some(Ok(daemons)) if daemons.is_empty() => {
+                ui.label(tr!("display-pipeweaver-none"));
+            }
+            Some(Ok(daemons)) => {
+                for daemon in daemons {
+                    ui.horizontal(|ui| {
+                        self.draw_label(ui, "");
+                        if ui.button(tr!("display-pipeweaver-use")).clicked() {
+                            picked = Some(daemon.address.clone());
+                        }
+                        ui.label(format!("{} ({})", daemon.name, daemon.address));
+                    });
+                }
+            }
+            Some(Err(e)) => {
+                ui.label(tr!("display-pipeweaver-failed", error = e));
+            }
+            None => {}
+        }
+
+        if let Some(picked) = picked {
+            self.address = Some(picked);
+            apply = true;
+        }
+
+        if apply {
+            // An empty address falls back to the local daemon
+            let address = self.address.as_deref().map(str::trim).unwrap_or_default();
+            let address = (!address.is_empty()).then(|| address.to_string());
+            if let Err(e) = state.set_pipeweaver_address(address, true) {
+                warn!("Failed to set Pipeweaver address: {e}");
+            }
+        }
+    }
+}
+
+// The timeouts are whole numbers of each unit, 0 means the timeout is off
+fn seconds(value: u32) -> String {
+    match value {
+        0 => tr!("display-never"),
+        value if value % 60 == 0 => minutes(value / 60),
+        value => tr!("display-seconds", count = value),
+    }
+}
+
+fn minutes(value: u32) -> String {
+    match value {
+        0 => tr!("display-never"),
+        1 => tr!("display-minute"),
+        60 => tr!("display-hour"),
+        value if value % 60 == 0 => tr!("display-hours", count = value / 60),
+        value => tr!("display-minutes", count = value),
+    }
+}
@@ -0,0 +1,126 @@
+use crate::i18n::tr;
+use crate::integrations::DisplayBackend;
+use crate::integrations::acceleration::DialCurve;
+use crate::integrations::gestures::{DialAction, DialGesture, DialGestures};
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::states::controller_state::BeacnControllerState;
+use egui::{ComboBox, Grid, Slider, Ui};
+use log::warn;
+use strum::IntoEnumIterator;
+
+// Lets the user choose what pressing, double pressing and holding a dial does
+pub struct GesturesPage {}
+
+impl GesturesPage {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ControllerPage for GesturesPage {
+    fn icon(&self) -> &'static str {
+        "gear"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-gestures")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.heading(tr!("page-gestures"));
+        ui.add_space(20.0);
+
+        let backend = state.saved_settings.display_backend;
+        if backend == DisplayBackend::Demo {
+            ui.label(tr!("gestures-unavailable"));
+            return;
+        }
+
+        let mut gestures = state.saved_settings.dial_gestures;
+        Grid::new("dial_gestures")
+            .num_columns(2)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                for gesture in DialGesture::iter() {
+                    ui.label(gesture.to_string());
+                    let action = gestures.action_mut(gesture);
+                    ComboBox::from_id_salt(gesture.to_string())
+                        .selected_text(action.to_string())
+                        .width(200.0)
+                        .show_ui(ui, |ui| {
+                            for option in DialAction::iter() {
+                                ui.selectable_value(action, option, option.to_string());
+                            }
+                        });
+                    ui.end_row();
+                }
+
+                let uses_volume = DialGesture::iter()
+                    .any(|gesture| gestures.action(gesture) == DialAction::DefaultVolume);
+                if uses_volume {
+                    ui.label(tr!("gestures-default-volume"));
+                    ui.add(Slider::new(&mut gestures.default_volume, 0..=100).suffix("%"));
+                    ui.end_row();
+                }
+
+                if backend == DisplayBackend::Pipeweaver {
+                    ui.label(tr!("gestures-crossfade"));
+                    ui.add(Slider::new(&mut gestures.mix_crossfade, 0..=5000).suffix("ms"))
+                        .on_hover_text(tr!("gestures-crossfade-hover"));
+                    ui.end_row();
+                }
+
+                let acceleration = &mut gestures.acceleration;
+                ui.label(tr!("gestures-curve"));
+                ComboBox::from_id_salt("dial_curve")
+                    .selected_text(acceleration.curve.to_string())
+                    .width(200.0)
+                    .show_ui(ui, |ui| {
+                        for curve in DialCurve::iter() {
+                            ui.selectable_value(&mut acceleration.curve, curve, curve.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(tr!("gestures-step"));
+                ui.add(Slider::new(&mut acceleration.step, 1..=10).suffix("%"))
+                    .on_hover_text(tr!("gestures-step-hover"));
+                ui.end_row();
+
+                if acceleration.curve == DialCurve::Accelerated {
+                    ui.label(tr!("gestures-max-speed"));
+                    ui.add(Slider::new(&mut acceleration.max_multiplier, 1..=10).suffix("x"))
+                        .on_hover_text(tr!("gestures-max-speed-hover"));
+                    ui.end_row();
+                }
+
+                ui.label(tr!("gestures-fine"));
+                ui.checkbox(&mut acceleration.fine_mode, tr!("gestures-fine-text"));
+                ui.end_row();
+            });
+
+        ui.add_space(10.0);
+        ui.label(tr!("gestures-note"));
+        if backend == DisplayBackend::Pipewire {
+            ui.label(tr!("gestures-pipewire-note"));
+        }
+        if gestures.double_press != DialAction::None {
+            ui.label(tr!("gestures-double-press-note"));
+        }
+
+        ui.add_space(10.0);
+        if ui.button(tr!("gestures-reset")).clicked() {
+            gestures = DialGestures::default();
+        }
+
+        if gestures != state.saved_settings.dial_gestures
+            && let Err(e) = state.set_dial_gestures(gestures, true)
+        {
+            warn!("Failed to update Dial Gestures: {e}");
+        }
+    }
+}
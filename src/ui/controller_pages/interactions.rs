@@ -0,0 +1,141 @@
+use crate::device_manager::{InteractionEvent, MonitoredInteraction};
+use crate::i18n::tr;
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::states::controller_state::BeacnControllerState;
+use beacn_lib::crossbeam::channel::{self, Receiver};
+use egui::{Context, Grid, RichText, ScrollArea, Ui};
+use log::warn;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// Events arrive as the buttons and dials are used, so this only needs to be quick enough to
+// feel live
+const REFRESH_INTERVAL: Duration = Duration::from_millis(50);
+
+// Enough to scroll back through a burst of dial movement
+const MAX_EVENTS: usize = 500;
+
+// Shows the Mix's button presses and dial movements as they arrive, for checking whether the
+// device or the integration is at fault when something doesn't respond. The device manager only
+// copies them over while this page is open.
+pub struct InteractionsPage {
+    receiver: Option<Receiver<MonitoredInteraction>>,
+    events: VecDeque<MonitoredInteraction>,
+    paused: bool,
+}
+
+impl InteractionsPage {
+    pub fn new() -> Self {
+        Self {
+            receiver: None,
+            events: VecDeque::new(),
+            paused: false,
+        }
+    }
+
+    fn receive(&mut self, state: &BeacnControllerState) {
+        if self.receiver.is_none() {
+            let (tx, rx) = channel::unbounded();
+            if let Err(e) = state.monitor_interactions(Some(tx)) {
+                warn!("Unable to monitor interactions: {e}");
+                return;
+            }
+            self.receiver = Some(rx);
+        }
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+
+        for interaction in receiver.try_iter() {
+            if self.paused {
+                continue;
+            }
+            if self.events.len() == MAX_EVENTS {
+                self.events.pop_back();
+            }
+            self.events.push_front(interaction);
+        }
+    }
+
+    fn events_ui(&self, ui: &mut Ui) {
+        if self.events.is_empty() {
+            ui.label(tr!("interactions-waiting"));
+            return;
+        }
+
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("interaction_monitor")
+                .num_columns(3)
+                .spacing([30.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label(RichText::new(tr!("interactions-time")).strong());
+                    ui.label(RichText::new(tr!("interactions-input")).strong());
+                    ui.label(RichText::new(tr!("interactions-event")).strong());
+                    ui.end_row();
+
+                    for interaction in &self.events {
+                        let (input, event) = match interaction.event {
+                            InteractionEvent::Button(button, true) => {
+                                (format!("{button:?}"), tr!("interactions-pressed"))
+                            }
+                            InteractionEvent::Button(button, false) => {
+                                (format!("{button:?}"), tr!("interactions-released"))
+                            }
+                            InteractionEvent::Dial(dial, change) => {
+                                (format!("{dial:?}"), format!("{change:+}"))
+                            }
+                        };
+                        ui.label(interaction.time.format("%H:%M:%S%.3f").to_string());
+                        ui.label(input);
+                        ui.label(event);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+impl ControllerPage for InteractionsPage {
+    fn icon(&self) -> &'static str {
+        "pulse"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-interactions")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.heading(tr!("page-interactions"));
+        ui.add_space(10.0);
+        ui.label(tr!("interactions-intro"));
+        ui.add_space(10.0);
+
+        self.receive(state);
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.paused, tr!("interactions-pause"));
+            if ui.button(tr!("interactions-clear")).clicked() {
+                self.events.clear();
+            }
+        });
+        ui.add_space(10.0);
+
+        self.events_ui(ui);
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+    }
+
+    fn on_page_close(&mut self, _: &Context) {
+        // The device manager stops copying interactions the next time one fails to send here,
+        // and the next visit starts with a fresh receiver
+        self.receiver = None;
+    }
+
+    fn on_close(&mut self) {
+        self.receiver = None;
+    }
+}
@@ -0,0 +1,104 @@
+use crate::i18n::tr;
+use crate::integrations::{DisplayBackend, StaticButton};
+use crate::safe_mode;
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::states::controller_state::BeacnControllerState;
+use beacn_lib::controller::ButtonLighting;
+use beacn_lib::manager::DeviceType;
+use egui::{Grid, Slider, Ui};
+use log::warn;
+use strum::IntoEnumIterator;
+
+// Lets the user light the Mix Create's buttons with fixed colours, for when there's no
+// Pipeweaver to light them based on what's happening
+pub struct LightingPage {}
+
+impl LightingPage {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ControllerPage for LightingPage {
+    fn icon(&self) -> &'static str {
+        "bulb"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-button-lighting")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn should_show(&self, state: &BeacnControllerState) -> bool {
+        state.device_definition.device_type == DeviceType::BeacnMixCreate
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.heading(tr!("page-button-lighting"));
+        ui.add_space(20.0);
+
+        // Safe mode doesn't run Pipeweaver, so the colours here are used instead
+        if state.saved_settings.display_backend == DisplayBackend::Pipeweaver && !safe_mode() {
+            ui.label(tr!("lighting-pipeweaver"));
+            ui.add_space(10.0);
+        }
+
+        let mut lighting = state.saved_settings.static_lighting.clone();
+        let mut changed = false;
+
+        Grid::new("static_lighting")
+            .num_columns(4)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                for button in ButtonLighting::iter() {
+                    let key = format!("{button:?}");
+                    ui.label(tr!("lighting-button", button = key));
+
+                    // Until something is set, the button follows the integration
+                    let mut value = lighting.get(&key).copied().unwrap_or_default();
+                    let colour = ui.color_edit_button_srgb(&mut value.colour).changed();
+                    let slider = Slider::new(&mut value.brightness, 0..=100)
+                        .suffix("%")
+                        .trailing_fill(true);
+                    let brightness = ui.add(slider).changed();
+                    if colour || brightness {
+                        lighting.insert(key.clone(), value);
+                        changed = true;
+                    }
+
+                    if lighting.contains_key(&key) {
+                        if ui.button(tr!("lighting-reset")).clicked() {
+                            lighting.remove(&key);
+                            changed = true;
+                        }
+                    } else {
+                        ui.label(tr!("lighting-default"));
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(10.0);
+        if ui.button(tr!("lighting-all-off")).clicked() {
+            for button in ButtonLighting::iter() {
+                let off = StaticButton {
+                    brightness: 0,
+                    ..Default::default()
+                };
+                lighting.insert(format!("{button:?}"), off);
+            }
+            changed = true;
+        }
+
+        // The colours and brightness are dragged, so wait until they're let go to save
+        if changed {
+            match state.set_static_lighting(lighting, false) {
+                Ok(()) => state.save_when_released(),
+                Err(e) => warn!("Failed to set button lighting: {e}"),
+            }
+        }
+    }
+}
@@ -1,12 +1,29 @@
 pub(crate) mod about;
+pub(crate) mod developer;
+pub(crate) mod dials;
+pub(crate) mod display;
 pub(crate) mod error;
+pub(crate) mod gestures;
+pub(crate) mod interactions;
+pub(crate) mod lighting;
+pub(crate) mod preview;
+pub(crate) mod screen_image;
+pub(crate) mod session;
 
 use crate::ui::states::controller_state::BeacnControllerState;
 use egui::{Context, Ui};
 
 pub trait ControllerPage {
     fn icon(&self) -> &'static str;
+
+    /// The name of the page, for screen readers as the nav button is only an icon
+    fn title(&self) -> String;
+
     fn show_on_error(&self) -> bool;
+
+    fn should_show(&self, _: &BeacnControllerState) -> bool {
+        true
+    }
     fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState);
 
     fn on_page_open(&mut self, _: &Context) {}
@@ -0,0 +1,106 @@
+use crate::i18n::tr;
+use crate::integrations::pipeweaver::layout::DISPLAY_DIMENSIONS;
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::states::controller_state::BeacnControllerState;
+use egui::{ColorImage, Context, TextureHandle, TextureOptions, Ui};
+use log::warn;
+use std::time::Duration;
+
+// How often we check the device for changes, roughly matching the display's own update rate
+const REFRESH_INTERVAL: Duration = Duration::from_millis(40);
+
+// Shows a copy of what's on the Mix's screen, built from the exact images sent to the device,
+// so it reflects whatever integration is currently running.
+pub struct PreviewPage {
+    serial: Option<String>,
+    generation: u64,
+    texture: Option<TextureHandle>,
+}
+
+impl PreviewPage {
+    pub fn new() -> Self {
+        Self {
+            serial: None,
+            generation: u64::MAX,
+            texture: None,
+        }
+    }
+}
+
+impl ControllerPage for PreviewPage {
+    fn icon(&self) -> &'static str {
+        "display"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-preview")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.heading(tr!("page-preview"));
+        ui.add_space(20.0);
+
+        // If we've switched devices, start again
+        let serial = &state.device_definition.device_info.serial;
+        if self.serial.as_ref() != Some(serial) {
+            self.serial = Some(serial.clone());
+            self.generation = u64::MAX;
+            self.texture = None;
+        }
+
+        match state.get_preview(self.generation) {
+            Ok(Some((generation, image))) => {
+                self.generation = generation;
+
+                let size = [image.width() as usize, image.height() as usize];
+                let image = ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+                match &mut self.texture {
+                    Some(texture) => texture.set(image, TextureOptions::LINEAR),
+                    None => {
+                        let texture =
+                            ui.ctx()
+                                .load_texture("mix_preview", image, TextureOptions::LINEAR);
+                        self.texture = Some(texture);
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Unable to fetch display preview: {e}"),
+        }
+
+        match &self.texture {
+            Some(texture) => {
+                // Scale to fit the window, but never beyond the real size of the screen
+                let width = ui.available_width().min(DISPLAY_DIMENSIONS.0 as f32);
+                let size = texture.size_vec2() * (width / texture.size_vec2().x);
+                ui.image((texture.id(), size));
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(tr!("preview-waiting"));
+                });
+            }
+        }
+
+        ui.add_space(10.0);
+        ui.label(tr!("preview-note"));
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+    }
+
+    fn on_page_close(&mut self, _: &Context) {
+        // The device manager stops building the preview once we stop asking for it
+        self.serial = None;
+        self.texture = None;
+    }
+
+    fn on_close(&mut self) {
+        // The texture belongs to the window's context, so is rebuilt when it's reopened
+        self.serial = None;
+        self.texture = None;
+    }
+}
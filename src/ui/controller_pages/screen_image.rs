@@ -0,0 +1,427 @@
+use crate::i18n::tr;
+use crate::integrations::pipeweaver::layout::{
+    BG_COLOUR, DISPLAY_DIMENSIONS, DrawingUtils, JPEG_QUALITY,
+};
+use crate::runtime;
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::states::controller_state::BeacnControllerState;
+use crate::ui::theme;
+use anyhow::{Result, anyhow, bail};
+use ashpd::desktop::file_chooser::{FileFilter, SelectedFiles};
+use egui::{
+    Color32, ColorImage, Context, DragValue, Pos2, Rect, RichText, Sense, Slider, TextureHandle,
+    TextureOptions, Ui, Vec2, pos2, vec2,
+};
+use image::imageops::{FilterType, crop_imm, resize};
+use image::{ImageFormat, RgbaImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Anything bigger than this is almost certainly a photo straight off a camera, and decoding it
+// would use a lot of memory for something that's going to be shrunk to fit the screen anyway
+const MAX_SOURCE_DIMENSION: u32 = 8192;
+
+const MIN_SCALE: f32 = 0.05;
+const MAX_SCALE: f32 = 4.0;
+
+// The image loaded into the page, along with where it's been placed on the screen
+struct Placement {
+    name: String,
+    image: RgbaImage,
+    texture: TextureHandle,
+
+    // The top left corner, in screen pixels, this can be off screen
+    position: Vec2,
+    scale: f32,
+}
+
+impl Placement {
+    fn size(&self) -> Vec2 {
+        vec2(self.image.width() as f32, self.image.height() as f32) * self.scale
+    }
+
+    // Scales the image so all of it is on the screen, and centres it
+    fn fit(&mut self) {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        let scale_x = width as f32 / self.image.width() as f32;
+        let scale_y = height as f32 / self.image.height() as f32;
+        self.scale = scale_x.min(scale_y).clamp(MIN_SCALE, MAX_SCALE);
+        self.centre();
+    }
+
+    // Grows the image until it covers the whole screen, and centres it
+    fn fill(&mut self) {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        let scale_x = width as f32 / self.image.width() as f32;
+        let scale_y = height as f32 / self.image.height() as f32;
+        self.scale = scale_x.max(scale_y).clamp(MIN_SCALE, MAX_SCALE);
+        self.centre();
+    }
+
+    fn centre(&mut self) {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        self.position = (vec2(width as f32, height as f32) - self.size()) / 2.0;
+    }
+
+    // Crops the image to the part which is on the screen and scales it, returning it with its
+    // position on the screen
+    fn render(&self) -> Result<(RgbaImage, u32, u32)> {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        let size = self.size().round();
+        if size.x < 1.0 || size.y < 1.0 {
+            bail!(tr!("image-too-small"));
+        }
+
+        let screen = Rect::from_min_size(Pos2::ZERO, vec2(width as f32, height as f32));
+        let placed = Rect::from_min_size(self.position.round().to_pos2(), size);
+        let visible = screen.intersect(placed);
+        if !visible.is_positive() {
+            bail!(tr!("image-off-screen"));
+        }
+
+        // Only the part of the source which ends up on the screen is scaled, a large image
+        // zoomed in would otherwise be scaled to many times the size of the screen first
+        let factor = size / vec2(self.image.width() as f32, self.image.height() as f32);
+        let source_min = ((visible.min - placed.min) / factor).floor();
+        let source_max = ((visible.max - placed.min) / factor).ceil();
+        let x = (source_min.x as u32).min(self.image.width() - 1);
+        let y = (source_min.y as u32).min(self.image.height() - 1);
+        let source_width = (source_max.x as u32).clamp(x + 1, self.image.width()) - x;
+        let source_height = (source_max.y as u32).clamp(y + 1, self.image.height()) - y;
+
+        let source = crop_imm(&self.image, x, y, source_width, source_height).to_image();
+        let visible_size = visible.size();
+        let image = resize(
+            &source,
+            visible_size.x as u32,
+            visible_size.y as u32,
+            FilterType::Triangle,
+        );
+
+        Ok((image, visible.min.x as u32, visible.min.y as u32))
+    }
+}
+
+// Lets the user drop an image onto a copy of the Mix screen, move and scale it, then send it
+// to the device. The image is drawn once, so whatever integration is running will draw over it
+// when something on the screen changes.
+pub struct ImagePage {
+    placement: Option<Placement>,
+    browsing: Option<oneshot::Receiver<Result<Option<PathBuf>>>>,
+    status: Option<Result<String, String>>,
+}
+
+impl ImagePage {
+    pub fn new() -> Self {
+        Self {
+            placement: None,
+            browsing: None,
+            status: None,
+        }
+    }
+
+    fn browse(&mut self) {
+        let (tx, rx) = oneshot::channel();
+        runtime().spawn(async move {
+            let result = async {
+                let title = tr!("image-chooser-title");
+                let response = SelectedFiles::open_file()
+                    .title(title.as_str())
+                    .modal(true)
+                    .multiple(false)
+                    .filter(
+                        FileFilter::new(&tr!("image-chooser-filter"))
+                            .glob("*.png")
+                            .glob("*.jpg")
+                            .glob("*.jpeg"),
+                    )
+                    .send()
+                    .await?
+                    .response()?;
+
+                let uri = response.uris().first().cloned();
+                Ok(uri.and_then(|uri| uri.to_file_path().ok()))
+            };
+            let _ = tx.send(result.await);
+        });
+        self.browsing = Some(rx);
+    }
+
+    fn poll_browse(&mut self, ctx: &Context) {
+        let Some(browsing) = &self.browsing else {
+            return;
+        };
+
+        let result = match browsing.try_recv() {
+            Ok(result) => result,
+            Err(oneshot::TryRecvError::Empty) => {
+                ctx.request_repaint_after(Duration::from_millis(100));
+                return;
+            }
+            Err(oneshot::TryRecvError::Disconnected) => Err(anyhow!(tr!("image-chooser-closed"))),
+        };
+
+        self.browsing = None;
+        match result {
+            Ok(Some(path)) => self.load_file(ctx, &path),
+            Ok(None) => {}
+            Err(e) => self.status = Some(Err(tr!("image-chooser-failed", error = e))),
+        }
+    }
+
+    fn poll_dropped(&mut self, ctx: &Context) {
+        let dropped = ctx.input(|i| i.raw.dropped_files.first().cloned());
+        let Some(file) = dropped else {
+            return;
+        };
+
+        if let Some(bytes) = &file.bytes {
+            let name = file.path.as_ref().map(|p| p.display().to_string());
+            let name = name.unwrap_or(file.name.clone());
+            self.load(ctx, name, bytes);
+        } else if let Some(path) = &file.path {
+            self.load_file(ctx, path);
+        }
+    }
+
+    fn load_file(&mut self, ctx: &Context, path: &Path) {
+        match fs::read(path) {
+            Ok(bytes) => self.load(ctx, path.display().to_string(), &bytes),
+            Err(e) => {
+                let path = path.display();
+                self.status = Some(Err(tr!("image-read-failed", path = path, error = e)));
+            }
+        }
+    }
+
+    fn load(&mut self, ctx: &Context, name: String, bytes: &[u8]) {
+        match Self::decode(bytes) {
+            Ok(image) => {
+                let size = [image.width() as usize, image.height() as usize];
+                let colour = ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+                let texture = ctx.load_texture("screen_upload", colour, TextureOptions::LINEAR);
+
+                let mut placement = Placement {
+                    name,
+                    image,
+                    texture,
+                    position: Vec2::ZERO,
+                    scale: 1.0,
+                };
+                placement.fit();
+                self.placement = Some(placement);
+                self.status = None;
+            }
+            Err(e) => self.status = Some(Err(e.to_string())),
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<RgbaImage> {
+        let format =
+            image::guess_format(bytes).map_err(|_| anyhow!(tr!("image-unknown-format")))?;
+        if !matches!(format, ImageFormat::Jpeg | ImageFormat::Png) {
+            bail!(tr!("image-unsupported-format"));
+        }
+
+        let reader = image::ImageReader::with_format(std::io::Cursor::new(bytes), format);
+        let (width, height) = reader.into_dimensions()?;
+        if width > MAX_SOURCE_DIMENSION || height > MAX_SOURCE_DIMENSION {
+            bail!(tr!(
+                "image-too-large",
+                width = width,
+                height = height,
+                limit = MAX_SOURCE_DIMENSION
+            ));
+        }
+        Ok(image::load_from_memory_with_format(bytes, format)?.into_rgba8())
+    }
+
+    fn send(&mut self, state: &BeacnControllerState) {
+        let Some(placement) = &self.placement else {
+            return;
+        };
+
+        let result = placement.render().and_then(|(image, x, y)| {
+            // The image is positioned on the landscape screen, so needs to follow any rotation
+            let orientation = state.saved_settings.orientation;
+            let (image, x, y) = orientation.transform(image, x, y);
+            let (width, height) = (image.width(), image.height());
+            let jpeg = DrawingUtils::image_as_jpeg(image, BG_COLOUR, JPEG_QUALITY)?;
+            state.send_image(jpeg, x, y)?;
+            Ok(tr!("image-sent", width = width, height = height))
+        });
+        self.status = Some(result.map_err(|e| tr!("image-send-failed", error = e)));
+    }
+
+    fn draw_screen(&mut self, ui: &mut Ui) {
+        let (width, height) = DISPLAY_DIMENSIONS;
+        let screen = vec2(width as f32, height as f32);
+        let zoom = (ui.available_width().min(screen.x)) / screen.x;
+
+        let (rect, response) = ui.allocate_exact_size(screen * zoom, Sense::drag());
+        let painter = ui.painter_at(rect);
+        let [red, green, blue, _] = BG_COLOUR.0;
+        painter.rect_filled(rect, 0.0, Color32::from_rgb(red, green, blue));
+
+        // Highlight the screen while something is being dragged over the window
+        let hovering = ui.ctx().input(|i| !i.raw.hovered_files.is_empty());
+        let stroke = match hovering {
+            true => ui.visuals().selection.stroke,
+            false => ui.visuals().widgets.noninteractive.bg_stroke,
+        };
+
+        match &mut self.placement {
+            Some(placement) => {
+                if response.dragged() {
+                    placement.position += response.drag_delta() / zoom;
+                }
+
+                // Scrolling over the screen scales around the pointer
+                if let Some(pointer) = response.hover_pos() {
+                    let scroll = ui.ctx().input(|i| i.smooth_scroll_delta.y);
+                    if scroll != 0.0 {
+                        let anchor = (pointer - rect.min) / zoom;
+                        let previous = placement.scale;
+                        let scale = previous * (1.0 + scroll / 500.0);
+                        placement.scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+                        let ratio = placement.scale / previous;
+                        placement.position = anchor + (placement.position - anchor) * ratio;
+                    }
+                }
+
+                let min = rect.min + placement.position * zoom;
+                let image_rect = Rect::from_min_size(min, placement.size() * zoom);
+                let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+                painter.image(placement.texture.id(), image_rect, uv, Color32::WHITE);
+            }
+            None => {
+                painter.text(
+                    rect.center(),
+                    egui::Align2::CENTER_CENTER,
+                    tr!("image-drop"),
+                    egui::FontId::proportional(18.0),
+                    ui.visuals().weak_text_color(),
+                );
+            }
+        }
+        painter.rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Inside);
+    }
+
+    fn draw_controls(&mut self, ui: &mut Ui, state: &BeacnControllerState) {
+        let Some(placement) = &mut self.placement else {
+            return;
+        };
+
+        ui.label(RichText::new(&placement.name).strong());
+        let shown = placement.size().round();
+        ui.label(tr!(
+            "image-size",
+            width = placement.image.width(),
+            height = placement.image.height(),
+            shown_width = shown.x,
+            shown_height = shown.y
+        ));
+        ui.add_space(5.0);
+
+        ui.horizontal(|ui| {
+            ui.label(tr!("image-scale"));
+            let mut percent = placement.scale * 100.0;
+            let range = MIN_SCALE * 100.0..=MAX_SCALE * 100.0;
+            let slider = Slider::new(&mut percent, range)
+                .suffix("%")
+                .fixed_decimals(0);
+            if ui.add(slider).changed() {
+                // Keep the centre of the image where it was
+                let centre = placement.position + placement.size() / 2.0;
+                placement.scale = percent / 100.0;
+                placement.position = centre - placement.size() / 2.0;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("image-position"));
+            ui.add(
+                DragValue::new(&mut placement.position.x)
+                    .prefix("x: ")
+                    .speed(1.0),
+            );
+            ui.add(
+                DragValue::new(&mut placement.position.y)
+                    .prefix("y: ")
+                    .speed(1.0),
+            );
+        });
+        ui.horizontal(|ui| {
+            if ui.button(tr!("image-fit")).clicked() {
+                placement.fit();
+            }
+            if ui.button(tr!("image-fill")).clicked() {
+                placement.fill();
+            }
+            if ui.button(tr!("image-centre")).clicked() {
+                placement.centre();
+            }
+        });
+
+        ui.add_space(10.0);
+        if ui.button(tr!("image-send")).clicked() {
+            self.send(state);
+        }
+    }
+}
+
+impl ControllerPage for ImagePage {
+    fn icon(&self) -> &'static str {
+        "image"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-screen-image")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut Ui, state: &mut BeacnControllerState) {
+        ui.heading(tr!("page-screen-image"));
+        ui.add_space(20.0);
+
+        self.poll_browse(ui.ctx());
+        self.poll_dropped(ui.ctx());
+
+        self.draw_screen(ui);
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            ui.label(tr!("image-hint"));
+            let browse = ui.add_enabled(
+                self.browsing.is_none(),
+                egui::Button::new(tr!("image-browse")),
+            );
+            if browse.clicked() {
+                self.browse();
+            }
+        });
+
+        ui.add_space(10.0);
+        self.draw_controls(ui, state);
+
+        if let Some(status) = &self.status {
+            let theme = theme::current(ui.ctx());
+            ui.add_space(5.0);
+            match status {
+                Ok(message) => ui.label(RichText::new(message).color(theme.success)),
+                Err(message) => ui.label(RichText::new(message).color(theme.failure)),
+            };
+        }
+
+        ui.add_space(10.0);
+        ui.label(tr!("image-note"));
+    }
+
+    fn on_page_close(&mut self, _: &Context) {
+        // Don't hold on to a possibly large image while the page isn't showing
+        self.placement = None;
+        self.status = None;
+    }
+}
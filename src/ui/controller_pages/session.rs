@@ -0,0 +1,279 @@
+use crate::i18n::tr;
+use crate::session::{self, SessionStats};
+use crate::settings;
+use crate::ui::controller_pages::ControllerPage;
+use crate::ui::shared_pages::stats::format_uptime;
+use crate::ui::states::audit::{csv_field, export_path};
+use crate::ui::states::controller_state::BeacnControllerState;
+use crate::ui::theme;
+use anyhow::Result;
+use beacn_lib::manager::DeviceType;
+use chrono::Local;
+use egui::{
+    Align2, Color32, CornerRadius, FontId, Grid, Pos2, Rect, RichText, Sense, Stroke, Ui, pos2,
+    vec2,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const CHART_HEIGHT: f32 = 180.0;
+
+// Channels cycle through these in the volume chart
+const CHANNEL_COLOURS: [Color32; 6] = [
+    Color32::from_rgb(52, 152, 219),
+    Color32::from_rgb(46, 204, 113),
+    Color32::from_rgb(241, 196, 15),
+    Color32::from_rgb(155, 89, 182),
+    Color32::from_rgb(230, 126, 34),
+    Color32::from_rgb(26, 188, 156),
+];
+
+// Talk time, dial use and volume history for the session, for looking back over a stream
+pub struct SessionPage {
+    // Where the stats were last exported to, or why they couldn't be
+    export: Option<Result<PathBuf, String>>,
+}
+
+impl SessionPage {
+    pub fn new() -> Self {
+        Self { export: None }
+    }
+
+    fn summary_ui(&self, ui: &mut Ui, stats: &SessionStats) {
+        let rows = [
+            (tr!("session-length"), format_uptime(stats.duration())),
+            (tr!("session-talk-time"), format_uptime(stats.talk_time())),
+            (tr!("session-muted-time"), format_uptime(stats.muted_time())),
+            (tr!("session-mutes"), stats.mutes.to_string()),
+        ];
+        for (label, value) in rows {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(label).strong().size(14.0));
+                ui.label(RichText::new(value).size(14.0));
+            });
+        }
+    }
+
+    fn dials_ui(&self, ui: &mut Ui, stats: &SessionStats) {
+        ui.label(RichText::new(tr!("session-dials")).strong());
+        ui.add_space(5.0);
+        if stats.dials.is_empty() {
+            ui.label(tr!("session-no-dials"));
+            return;
+        }
+
+        Grid::new("session_dials")
+            .num_columns(3)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(RichText::new(tr!("session-dial")).strong());
+                ui.label(RichText::new(tr!("session-moves")).strong());
+                ui.label(RichText::new(tr!("session-steps")).strong());
+                ui.end_row();
+
+                for (dial, activity) in &stats.dials {
+                    ui.label(dial);
+                    ui.label(activity.moves.to_string());
+                    ui.label(activity.steps.to_string());
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn volumes_ui(&self, ui: &mut Ui, stats: &SessionStats) {
+        ui.label(RichText::new(tr!("session-volumes")).strong());
+        ui.add_space(5.0);
+
+        let size = vec2(ui.available_width(), CHART_HEIGHT);
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        let theme = theme::current(ui.ctx());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, CornerRadius::default(), theme.graph_background);
+
+        let length = stats.duration().as_secs_f32().max(1.0);
+        let x = |offset: Duration| rect.min.x + rect.width() * offset.as_secs_f32() / length;
+        let y = |volume: u8| rect.max.y - rect.height() * volume as f32 / 100.0;
+
+        // Shade the times the mic was muted
+        let now = stats.duration();
+        let current = stats.muted_since().map(|since| (since, now));
+        for (from, to) in stats.mute_periods.iter().copied().chain(current) {
+            let shaded = Rect::from_min_max(pos2(x(from), rect.min.y), pos2(x(to), rect.max.y));
+            let colour = theme.failure.gamma_multiply(0.2);
+            painter.rect_filled(shaded, CornerRadius::default(), colour);
+        }
+
+        let grid = Stroke::new(1.0, theme.graph_grid);
+        painter.line_segment([pos2(rect.min.x, y(50)), pos2(rect.max.x, y(50))], grid);
+
+        // Volumes hold until they next change, so these are drawn as steps
+        for (index, (_, history)) in stats.volumes.iter().enumerate() {
+            let colour = CHANNEL_COLOURS[index % CHANNEL_COLOURS.len()];
+            let mut points: Vec<Pos2> = vec![];
+            for point in history {
+                if let Some(last) = points.last().copied() {
+                    points.push(pos2(x(point.offset), last.y));
+                }
+                points.push(pos2(x(point.offset), y(point.volume)));
+            }
+            if let Some(last) = points.last().copied() {
+                points.push(pos2(x(now), last.y));
+            }
+            painter.line(points, Stroke::new(2.0, colour));
+        }
+
+        let legend = rect.left_top() + vec2(8.0, 6.0);
+        for (index, channel) in stats.volumes.keys().enumerate() {
+            painter.text(
+                legend + vec2(0.0, 14.0 * index as f32),
+                Align2::LEFT_TOP,
+                channel,
+                FontId::proportional(12.0),
+                CHANNEL_COLOURS[index % CHANNEL_COLOURS.len()],
+            );
+        }
+        if stats.volumes.is_empty() {
+            painter.text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                tr!("session-no-volumes"),
+                FontId::proportional(12.0),
+                theme.graph_text,
+            );
+        }
+    }
+}
+
+impl ControllerPage for SessionPage {
+    fn icon(&self) -> &'static str {
+        "chart"
+    }
+
+    fn title(&self) -> String {
+        tr!("page-session")
+    }
+
+    fn show_on_error(&self) -> bool {
+        false
+    }
+
+    fn should_show(&self, state: &BeacnControllerState) -> bool {
+        matches!(
+            state.device_definition.device_type,
+            DeviceType::BeacnMix | DeviceType::BeacnMixCreate
+        )
+    }
+
+    fn ui(&mut self, ui: &mut Ui, _: &mut BeacnControllerState) {
+        ui.heading(tr!("page-session"));
+        ui.add_space(20.0);
+
+        let mut enabled = settings::get().session_stats;
+        if ui
+            .checkbox(&mut enabled, tr!("session-collect"))
+            .on_hover_text(tr!("session-collect-hover"))
+            .changed()
+        {
+            settings::update(|s| s.session_stats = enabled);
+            session::set_enabled(enabled);
+        }
+
+        let Some(stats) = session::get() else {
+            ui.add_space(10.0);
+            ui.label(tr!("session-disabled"));
+            return;
+        };
+
+        ui.add_space(10.0);
+        self.summary_ui(ui, &stats);
+        ui.add_space(10.0);
+        self.dials_ui(ui, &stats);
+        ui.add_space(10.0);
+        self.volumes_ui(ui, &stats);
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            if ui.button(tr!("session-export")).clicked() {
+                self.export = Some(export_csv(&stats).map_err(|e| e.to_string()));
+            }
+            if ui.button(tr!("session-reset")).clicked() {
+                session::reset();
+                self.export = None;
+            }
+        });
+        match &self.export {
+            Some(Ok(path)) => {
+                ui.label(tr!("session-exported", path = path.display()));
+            }
+            Some(Err(e)) => {
+                ui.label(tr!("session-export-failed", error = e));
+            }
+            None => {}
+        }
+
+        // Keep the times and chart moving
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+    }
+}
+
+// Writes one row per event, with the totals (which don't belong to a time) at the top
+fn export_csv(stats: &SessionStats) -> Result<PathBuf> {
+    let mut rows = vec![
+        (
+            None,
+            "length",
+            String::new(),
+            stats.duration().as_secs().to_string(),
+        ),
+        (
+            None,
+            "talk_time",
+            String::new(),
+            stats.talk_time().as_secs().to_string(),
+        ),
+        (
+            None,
+            "muted_time",
+            String::new(),
+            stats.muted_time().as_secs().to_string(),
+        ),
+        (None, "mutes", String::new(), stats.mutes.to_string()),
+    ];
+    for (dial, activity) in &stats.dials {
+        rows.push((None, "dial_moves", dial.clone(), activity.moves.to_string()));
+        rows.push((None, "dial_steps", dial.clone(), activity.steps.to_string()));
+    }
+    for (from, to) in &stats.mute_periods {
+        let length = (*to - *from).as_secs().to_string();
+        rows.push((Some(*from), "muted", String::new(), length));
+    }
+    for (channel, history) in &stats.volumes {
+        for point in history {
+            let volume = point.volume.to_string();
+            rows.push((Some(point.offset), "volume", channel.clone(), volume));
+        }
+    }
+
+    let mut csv = String::from("time,seconds,kind,name,value\n");
+    for (offset, kind, name, value) in rows {
+        let (time, seconds) = match offset {
+            Some(offset) => {
+                let time = stats.started + offset;
+                let time = time.format("%Y-%m-%d %H:%M:%S").to_string();
+                (time, offset.as_secs().to_string())
+            }
+            None => (String::new(), String::new()),
+        };
+        let fields = [time.as_str(), &seconds, kind, &name, &value];
+        let fields: Vec<_> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let path = export_path(&format!("session-{stamp}.csv"));
+    fs::write(&path, csv)?;
+    Ok(path)
+}
@@ -0,0 +1,131 @@
+// Shows the recent log lines kept by the log buffer, so problems can be looked into (or copied
+// into a bug report) without digging out the log file.
+
+use crate::i18n::tr;
+use crate::log_buffer::{self, LogLine, LogSubsystem};
+use crate::ui::theme;
+use egui::{ComboBox, Label, RichText, ScrollArea, TextEdit, TextStyle, Ui};
+use log::Level;
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+// How often to look for new lines while following the log
+const TAIL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(crate) struct LogViewer {
+    subsystem: Option<LogSubsystem>,
+    search: String,
+    follow: bool,
+
+    // The filtered lines, and how many lines had been logged when they were fetched
+    lines: Vec<LogLine>,
+    fetched: Option<u64>,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self {
+            subsystem: None,
+            search: String::new(),
+            follow: true,
+            lines: vec![],
+            fetched: None,
+        }
+    }
+}
+
+impl LogViewer {
+    /// Drops the fetched lines, they're fetched again when next shown
+    pub(crate) fn clear_cache(&mut self) {
+        self.lines = vec![];
+        self.fetched = None;
+    }
+
+    fn refresh(&mut self) {
+        let written = log_buffer::written();
+        if self.fetched == Some(written) {
+            return;
+        }
+
+        let subsystem = self.subsystem;
+        let search = self.search.to_lowercase();
+        self.lines = log_buffer::lines(|line| {
+            subsystem.is_none_or(|s| s == line.subsystem)
+                && (search.is_empty() || line.message.to_lowercase().contains(&search))
+        });
+        self.fetched = Some(written);
+    }
+}
+
+pub(crate) fn log_viewer_ui(ui: &mut Ui, viewer: &mut LogViewer) {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        let selected = subsystem_label(viewer.subsystem);
+        ComboBox::from_id_salt("log_subsystem")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                let current = viewer.subsystem;
+                ui.selectable_value(&mut viewer.subsystem, None, subsystem_label(None));
+                for subsystem in LogSubsystem::iter() {
+                    let label = subsystem_label(Some(subsystem));
+                    ui.selectable_value(&mut viewer.subsystem, Some(subsystem), label);
+                }
+                changed |= current != viewer.subsystem;
+            });
+
+        let search = TextEdit::singleline(&mut viewer.search)
+            .hint_text(tr!("settings-logs-search"))
+            .desired_width(160.0);
+        changed |= ui.add(search).changed();
+
+        ui.checkbox(&mut viewer.follow, tr!("settings-logs-follow"));
+        if ui.button(tr!("settings-logs-copy")).clicked() {
+            let text: Vec<_> = viewer.lines.iter().map(LogLine::to_text).collect();
+            ui.ctx().copy_text(text.join("\n"));
+        }
+    });
+
+    // Changing the filter needs a fresh fetch, otherwise only fetch new lines when following
+    if changed {
+        viewer.fetched = None;
+    }
+    if viewer.follow || viewer.fetched.is_none() {
+        viewer.refresh();
+    }
+    if viewer.follow {
+        ui.ctx().request_repaint_after(TAIL_INTERVAL);
+    }
+
+    ui.add_space(5.0);
+    let theme = theme::current(ui.ctx());
+    let row_height = ui.text_style_height(&TextStyle::Monospace);
+    ScrollArea::both()
+        .max_height(300.0)
+        .auto_shrink([false, true])
+        .stick_to_bottom(viewer.follow)
+        .show_rows(ui, row_height, viewer.lines.len(), |ui, rows| {
+            for line in &viewer.lines[rows] {
+                let text = RichText::new(line.to_text()).monospace();
+                let text = match line.level {
+                    Level::Error => text.color(theme.failure),
+                    Level::Warn => text.color(theme.highlight),
+                    _ => text,
+                };
+                ui.add(Label::new(text).extend());
+            }
+        });
+
+    if viewer.lines.is_empty() {
+        ui.label(tr!("settings-logs-empty"));
+    }
+}
+
+fn subsystem_label(subsystem: Option<LogSubsystem>) -> String {
+    match subsystem {
+        None => tr!("settings-logs-all"),
+        Some(LogSubsystem::DeviceManager) => tr!("settings-logs-devices"),
+        Some(LogSubsystem::Pipeweaver) => tr!("settings-logs-pipeweaver"),
+        Some(LogSubsystem::Ipc) => tr!("settings-logs-ipc"),
+        Some(LogSubsystem::Other) => tr!("settings-logs-other"),
+    }
+}
@@ -4,11 +4,16 @@ use std::sync::LazyLock;
 
 pub(crate) mod app;
 mod audio_pages;
+mod clipboard;
 mod controller_pages;
+mod log_viewer;
 mod numbers;
+mod onboarding;
 mod pages;
 mod shared_pages;
+mod shortcuts;
 mod states;
+pub(crate) mod theme;
 mod widgets;
 
 // SVG Images
@@ -27,6 +32,7 @@ pub static SVG: LazyLock<HashMap<&'static str, ImageSource>> = LazyLock::new(||
         include_image!("../../resources/ui/icons/lightbulb.svg"),
     );
     map.insert("gear", include_image!("../../resources/ui/icons/gear.svg"));
+    map.insert("plug", include_image!("../../resources/ui/icons/plug.svg"));
     map.insert(
         "left_right",
         include_image!("../../resources/ui/icons/left-right.svg"),
@@ -36,6 +42,26 @@ pub static SVG: LazyLock<HashMap<&'static str, ImageSource>> = LazyLock::new(||
         include_image!("../../resources/ui/icons/error.svg"),
     );
     map.insert("info", include_image!("../../resources/ui/icons/info.svg"));
+    map.insert(
+        "display",
+        include_image!("../../resources/ui/icons/display.svg"),
+    );
+    map.insert(
+        "terminal",
+        include_image!("../../resources/ui/icons/terminal.svg"),
+    );
+    map.insert(
+        "image",
+        include_image!("../../resources/ui/icons/image.svg"),
+    );
+    map.insert(
+        "chart",
+        include_image!("../../resources/ui/icons/chart.svg"),
+    );
+    map.insert(
+        "pulse",
+        include_image!("../../resources/ui/icons/pulse.svg"),
+    );
 
     // EQ Modes
     map.insert("eq_bell", include_image!("../../resources/ui/eq/bell.svg"));
@@ -0,0 +1,256 @@
+// A short setup guide shown the first time the utility is run (when there's no settings file),
+// covering the things people most often trip over, then a quick tour of where everything is. It
+// can be run again from the settings page.
+
+use crate::diagnostics::{find_rules, install_rules, manual_instructions};
+use crate::i18n::tr;
+use crate::integrations::DisplayBackend;
+use crate::integrations::pipeweaver::get_pipeweaver_socket_path;
+use crate::ui::pages::autostart_ui;
+use crate::ui::theme;
+use crate::{runtime, settings};
+use anyhow::Result;
+use egui::{Align, Layout, RichText, ScrollArea, TextEdit, Ui};
+use std::path::PathBuf;
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, EnumIter)]
+enum Step {
+    #[default]
+    Welcome,
+    AutoStart,
+    Permissions,
+    Pipeweaver,
+    Tour,
+}
+
+#[derive(Default)]
+pub(crate) struct Onboarding {
+    step: Step,
+
+    // Where the udev rules were found, checked when the step is shown
+    rules: Option<Option<PathBuf>>,
+    installing: Option<oneshot::Receiver<Result<()>>>,
+    install_result: Option<Result<(), String>>,
+}
+
+impl Onboarding {
+    /// Draws the current step, returning true once the guide has been finished or skipped
+    pub(crate) fn ui(&mut self, ui: &mut Ui) -> bool {
+        let steps: Vec<_> = Step::iter().collect();
+        let index = steps
+            .iter()
+            .position(|s| *s == self.step)
+            .unwrap_or_default();
+
+        ui.heading(tr!("onboarding-heading"));
+        ui.label(
+            RichText::new(tr!(
+                "onboarding-progress",
+                step = index + 1,
+                total = steps.len()
+            ))
+            .weak(),
+        );
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        let footer = ui.spacing().interact_size.y + 20.0;
+        ScrollArea::vertical()
+            .max_height(ui.available_height() - footer)
+            .show(ui, |ui| match self.step {
+                Step::Welcome => self.welcome_ui(ui),
+                Step::AutoStart => self.autostart_ui(ui),
+                Step::Permissions => self.permissions_ui(ui),
+                Step::Pipeweaver => self.pipeweaver_ui(ui),
+                Step::Tour => self.tour_ui(ui),
+            });
+
+        ui.add_space(10.0);
+        ui.separator();
+        let mut finished = false;
+        ui.horizontal(|ui| {
+            if ui.button(tr!("onboarding-skip")).clicked() {
+                finished = true;
+            }
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                match steps.get(index + 1) {
+                    Some(next) => {
+                        if ui.button(tr!("onboarding-next")).clicked() {
+                            self.step = *next;
+                        }
+                    }
+                    None => {
+                        if ui.button(tr!("onboarding-finish")).clicked() {
+                            finished = true;
+                        }
+                    }
+                }
+                if index > 0 && ui.button(tr!("onboarding-back")).clicked() {
+                    self.step = steps[index - 1];
+                }
+            });
+        });
+
+        if finished {
+            settings::update(|s| s.onboarded = true);
+        }
+        finished
+    }
+
+    fn welcome_ui(&mut self, ui: &mut Ui) {
+        ui.label(RichText::new(tr!("onboarding-welcome")).strong().size(16.0));
+        ui.add_space(10.0);
+        ui.label(tr!("onboarding-welcome-intro"));
+    }
+
+    fn autostart_ui(&mut self, ui: &mut Ui) {
+        ui.label(
+            RichText::new(tr!("onboarding-autostart"))
+                .strong()
+                .size(16.0),
+        );
+        ui.add_space(10.0);
+        ui.label(tr!("onboarding-autostart-intro"));
+        ui.add_space(10.0);
+        autostart_ui(ui);
+    }
+
+    fn permissions_ui(&mut self, ui: &mut Ui) {
+        ui.label(
+            RichText::new(tr!("onboarding-permissions"))
+                .strong()
+                .size(16.0),
+        );
+        ui.add_space(10.0);
+        ui.label(tr!("onboarding-permissions-intro"));
+        ui.add_space(10.0);
+
+        if let Some(rx) = &self.installing {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.installing = None;
+                    self.install_result = Some(result.map_err(|e| e.to_string()));
+                    self.rules = None;
+                }
+                Err(oneshot::TryRecvError::Disconnected) => self.installing = None,
+                Err(oneshot::TryRecvError::Empty) => {
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                }
+            }
+        }
+
+        let rules = self.rules.get_or_insert_with(find_rules).clone();
+        let theme = theme::current(ui.ctx());
+        match rules {
+            Some(path) => {
+                let found = tr!(
+                    "onboarding-permissions-found",
+                    path = path.display().to_string()
+                );
+                ui.label(RichText::new(found).color(theme.success));
+            }
+            None if ashpd::is_sandboxed() => {
+                ui.label(tr!("onboarding-permissions-flatpak"));
+                ui.add_space(5.0);
+                Self::instructions_ui(ui);
+            }
+            None => {
+                ui.label(RichText::new(tr!("onboarding-permissions-missing")).color(theme.failure));
+                ui.add_space(5.0);
+                if self.installing.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(tr!("onboarding-permissions-installing"));
+                    });
+                } else if ui.button(tr!("onboarding-permissions-install")).clicked() {
+                    let (tx, rx) = oneshot::channel();
+                    runtime().spawn_blocking(move || {
+                        let _ = tx.send(install_rules());
+                    });
+                    self.installing = Some(rx);
+                    self.install_result = None;
+                }
+
+                if let Some(Err(e)) = &self.install_result {
+                    ui.add_space(5.0);
+                    ui.label(tr!("onboarding-permissions-failed", error = e));
+                    Self::instructions_ui(ui);
+                }
+            }
+        }
+    }
+
+    fn instructions_ui(ui: &mut Ui) {
+        let instructions = manual_instructions();
+        ui.add(
+            TextEdit::multiline(&mut instructions.as_str())
+                .code_editor()
+                .desired_width(f32::INFINITY),
+        );
+        if ui.button(tr!("onboarding-permissions-copy")).clicked() {
+            ui.ctx().copy_text(instructions);
+        }
+    }
+
+    fn pipeweaver_ui(&mut self, ui: &mut Ui) {
+        ui.label(
+            RichText::new(tr!("onboarding-pipeweaver"))
+                .strong()
+                .size(16.0),
+        );
+        ui.add_space(10.0);
+        ui.label(tr!("onboarding-pipeweaver-intro"));
+        ui.add_space(10.0);
+
+        let current = settings::get().default_backend;
+        let mut enabled = current == DisplayBackend::Pipeweaver;
+        if ui
+            .checkbox(&mut enabled, tr!("onboarding-pipeweaver-enabled"))
+            .changed()
+        {
+            let backend = match enabled {
+                true => DisplayBackend::Pipeweaver,
+                false => DisplayBackend::Pipewire,
+            };
+            settings::update(|s| s.default_backend = backend);
+        }
+
+        ui.add_space(5.0);
+        let running = get_pipeweaver_socket_path().is_ok_and(|path| path.exists());
+        match running {
+            true => ui.label(tr!("onboarding-pipeweaver-running")),
+            false => ui.label(tr!("onboarding-pipeweaver-missing")),
+        };
+        if !running && ui.button(tr!("pipeweaver-get")).clicked() {
+            ui.ctx().open_url(egui::OpenUrl::new_tab(
+                "https://github.com/pipeweaver/pipeweaver",
+            ));
+        }
+    }
+
+    fn tour_ui(&mut self, ui: &mut Ui) {
+        ui.label(RichText::new(tr!("onboarding-tour")).strong().size(16.0));
+        ui.add_space(10.0);
+
+        let stops = [
+            tr!("onboarding-tour-devices"),
+            tr!("onboarding-tour-pages"),
+            tr!("onboarding-tour-pin"),
+            tr!("onboarding-tour-pipeweaver"),
+            tr!("onboarding-tour-integrations"),
+            tr!("onboarding-tour-settings"),
+            tr!("onboarding-tour-tray"),
+        ];
+        for stop in stops {
+            ui.horizontal_wrapped(|ui| {
+                ui.label("•");
+                ui.label(stop);
+            });
+            ui.add_space(4.0);
+        }
+    }
+}
@@ -1,11 +1,47 @@
+use crate::hooks::{Hook, HookAction, HookEvent};
+use crate::i18n::tr;
+use crate::integrations::companion;
+use crate::integrations::events;
+use crate::integrations::obs::{self, ObsStatus, SceneColour};
+use crate::integrations::websocket::ServerStatus;
+use crate::managers::schedule::{ScheduleTime, ScheduledProfile, scheduled_profile};
+use crate::settings::UI_SCALES;
+use crate::support::{DeviceReport, create_bundle};
+use crate::ui::log_viewer::{LogViewer, log_viewer_ui};
+use crate::ui::onboarding::Onboarding;
+use crate::ui::shared_pages::ignore::ignored_devices_ui;
+use crate::ui::shortcuts::{self, Shortcut};
+use crate::ui::theme::ThemeMode;
 use crate::window_handle::{UserEvent, send_user_event};
 use crate::{AUTO_START_KEY, VERSION};
-use egui::{Id, RichText, Ui};
+use crate::{i18n, runtime, settings};
+use anyhow::Result;
+use egui::{CollapsingHeader, ComboBox, DragValue, Grid, Id, RichText, Slider, TextEdit, Ui};
+use std::mem;
+use std::path::PathBuf;
+use std::time::Duration;
+use strum::IntoEnumIterator;
 
-pub(crate) fn settings_ui(ui: &mut Ui) {
-    ui.heading("About Beacn Utility");
+// A support bundle being built in the background, and where the last one ended up
+#[derive(Default)]
+pub(crate) struct SupportBundle {
+    pending: Option<oneshot::Receiver<Result<PathBuf>>>,
+    result: Option<Result<PathBuf, String>>,
+}
+
+// Where the colour picker starts when a custom accent is first turned on
+const DEFAULT_ACCENT: [u8; 3] = [79, 215, 255];
 
-    let version = RichText::new("Version: ").strong().size(14.0);
+pub(crate) fn settings_ui(
+    ui: &mut Ui,
+    bundle: &mut SupportBundle,
+    logs: &mut LogViewer,
+    onboarding: &mut Option<Onboarding>,
+    devices: impl FnOnce() -> Vec<DeviceReport>,
+) {
+    ui.heading(tr!("settings-heading"));
+
+    let version = RichText::new(tr!("settings-version")).strong().size(14.0);
     let version_value = RichText::new(VERSION).size(14.0);
 
     ui.add_space(20.0);
@@ -18,41 +54,728 @@ pub(crate) fn settings_ui(ui: &mut Ui) {
     ui.separator();
     ui.add_space(10.0);
 
+    autostart_ui(ui);
+
+    let mut developer_mode = settings::get().developer_mode;
+    if ui
+        .checkbox(&mut developer_mode, tr!("settings-developer-mode"))
+        .changed()
+    {
+        settings::update(|s| s.developer_mode = developer_mode);
+    }
+
+    let mut battery_saver = settings::get().battery_saver;
+    if ui
+        .checkbox(&mut battery_saver, tr!("settings-battery-saver"))
+        .on_hover_text(tr!("settings-battery-saver-hover"))
+        .changed()
+    {
+        settings::update(|s| s.battery_saver = battery_saver);
+    }
+
+    if battery_saver {
+        let mut threshold = settings::get().battery_threshold;
+        ui.horizontal(|ui| {
+            ui.label(tr!("settings-battery-threshold"));
+            let slider = Slider::new(&mut threshold, 5..=50).suffix("%");
+            if ui.add(slider).changed() {
+                settings::update(|s| s.battery_threshold = threshold);
+            }
+        });
+    }
+
+    ui.add_space(10.0);
+    let current = settings::get().language;
+    let selected = match &current {
+        Some(_) => i18n::languages()
+            .find(|(code, _)| *code == i18n::current())
+            .map_or_else(|| i18n::current().to_string(), |(_, name)| name.to_string()),
+        None => tr!("settings-language-system"),
+    };
+    ui.horizontal(|ui| {
+        ui.label(tr!("settings-language"));
+        ComboBox::from_id_salt("language")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                let mut choice = current.clone();
+                ui.selectable_value(&mut choice, None, tr!("settings-language-system"));
+                for (code, name) in i18n::languages() {
+                    ui.selectable_value(&mut choice, Some(code.to_string()), name);
+                }
+                if choice != current {
+                    i18n::set_language(choice);
+                    send_user_event(ui.ctx(), UserEvent::LanguageChanged);
+                }
+            });
+    });
+
+    let scale = settings::get().ui_scale;
+    ui.horizontal(|ui| {
+        ui.label(tr!("settings-ui-scale"));
+        ComboBox::from_id_salt("ui_scale")
+            .selected_text(format!("{scale}x"))
+            .show_ui(ui, |ui| {
+                let mut choice = scale;
+                for value in UI_SCALES {
+                    ui.selectable_value(&mut choice, value, format!("{value}x"));
+                }
+                if choice != scale {
+                    settings::update(|s| s.ui_scale = choice);
+                    ui.ctx().set_zoom_factor(choice);
+                }
+            });
+    });
+
+    // The theme is applied at the start of the next frame
+    let current = settings::get().theme;
+    let mut theme = current.clone();
+    ui.horizontal(|ui| {
+        ui.label(tr!("settings-theme"));
+        ComboBox::from_id_salt("theme_mode")
+            .selected_text(theme_mode_label(theme.mode))
+            .show_ui(ui, |ui| {
+                for mode in ThemeMode::iter() {
+                    ui.selectable_value(&mut theme.mode, mode, theme_mode_label(mode));
+                }
+            });
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr!("settings-accent"));
+        let mut custom = theme.accent.is_some();
+        if ui
+            .checkbox(&mut custom, tr!("settings-accent-custom"))
+            .changed()
+        {
+            theme.accent = custom.then_some(DEFAULT_ACCENT);
+        }
+        if let Some(accent) = &mut theme.accent {
+            ui.color_edit_button_srgb(accent);
+        }
+    });
+    if theme != current {
+        settings::update(|s| s.theme = theme);
+    }
+
+    ui.add_space(10.0);
+    accessibility_ui(ui);
+
+    ui.add_space(10.0);
+    if ui.button(tr!("settings-onboarding")).clicked() {
+        *onboarding = Some(Onboarding::default());
+    }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    shortcuts_ui(ui);
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    hooks_ui(ui);
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    schedule_ui(ui);
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    display_sleep_ui(ui);
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    ignored_devices_ui(ui);
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    support_bundle_ui(ui, bundle, devices);
+
+    ui.add_space(10.0);
+    CollapsingHeader::new(RichText::new(tr!("settings-logs")).strong().size(14.0))
+        .id_salt("logs")
+        .show(ui, |ui| log_viewer_ui(ui, logs));
+}
+
+fn accessibility_ui(ui: &mut Ui) {
+    // Like the theme, the targets and contrast are applied at the start of the next frame
+    let current = settings::get().accessibility;
+    let mut accessibility = current.clone();
+
+    ui.label(RichText::new(tr!("settings-accessibility")).strong());
+    ui.checkbox(
+        &mut accessibility.screen_reader,
+        tr!("settings-accessibility-screen-reader"),
+    )
+    .on_hover_text(tr!("settings-accessibility-screen-reader-hover"));
+    ui.checkbox(
+        &mut accessibility.large_targets,
+        tr!("settings-accessibility-large-targets"),
+    );
+    ui.checkbox(
+        &mut accessibility.high_contrast,
+        tr!("settings-accessibility-high-contrast"),
+    );
+
+    if accessibility != current {
+        settings::update(|s| s.accessibility = accessibility);
+    }
+}
+
+/// The auto-start checkbox, the current state is looked up by the window handler
+pub(crate) fn autostart_ui(ui: &mut Ui) {
     let id = Id::new(AUTO_START_KEY);
     let value: Option<Option<bool>> = ui.ctx().memory(|mem| mem.data.get_temp::<Option<bool>>(id));
     if let Some(lookup) = value {
         if let Some(value) = lookup {
-            const LABEL: &str = "Auto-Start the Beacn Utility on Login";
             let mut current = value;
 
             // Change AutoStart settings
-            if ui.checkbox(&mut current, LABEL).changed() {
+            if ui
+                .checkbox(&mut current, tr!("settings-autostart"))
+                .changed()
+            {
                 send_user_event(ui.ctx(), UserEvent::SetAutoStart(current));
             }
         }
     } else {
-        ui.label("Unable to Handle Auto-Start");
+        ui.label(tr!("settings-autostart-unavailable"));
     }
 }
 
-pub(crate) fn pipeweaver_ui(ui: &mut Ui) {
+fn hooks_ui(ui: &mut Ui) {
+    ui.label(RichText::new(tr!("settings-hooks")).strong().size(14.0));
+    ui.add_space(5.0);
+    ui.label(tr!("settings-hooks-intro"));
+    ui.add_space(5.0);
+
+    let current = settings::get().hooks;
+    let mut hooks = current.clone();
+
+    let mut remove = None;
+    for (index, hook) in hooks.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut hook.enabled, "");
+            ComboBox::from_id_salt(("hook_event", index))
+                .selected_text(hook_event_label(hook.event))
+                .show_ui(ui, |ui| {
+                    for event in HookEvent::iter() {
+                        ui.selectable_value(&mut hook.event, event, hook_event_label(event));
+                    }
+                });
+
+            let kinds = [
+                HookAction::Command(String::new()),
+                HookAction::TogglePanicMute,
+                HookAction::SuppressorProfile(String::new()),
+                HookAction::StreamTimer(String::from("toggle")),
+            ];
+            ComboBox::from_id_salt(("hook_action", index))
+                .selected_text(hook_action_label(&hook.action))
+                .show_ui(ui, |ui| {
+                    for kind in kinds {
+                        let selected = mem::discriminant(&kind) == mem::discriminant(&hook.action);
+                        let label = hook_action_label(&kind);
+                        if ui.selectable_label(selected, label).clicked() && !selected {
+                            hook.action = kind;
+                        }
+                    }
+                });
+
+            match &mut hook.action {
+                HookAction::Command(value)
+                | HookAction::SuppressorProfile(value)
+                | HookAction::StreamTimer(value) => {
+                    ui.text_edit_singleline(value);
+                }
+                HookAction::TogglePanicMute => {}
+            }
+
+            if ui.button(tr!("settings-hooks-remove")).clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        hooks.remove(index);
+    }
+
+    if ui.button(tr!("settings-hooks-add")).clicked() {
+        hooks.push(Hook::default());
+    }
+
+    if hooks != current {
+        settings::update(|s| s.hooks = hooks);
+    }
+}
+
+fn schedule_ui(ui: &mut Ui) {
+    ui.label(RichText::new(tr!("settings-schedule")).strong().size(14.0));
+    ui.add_space(5.0);
+    ui.label(tr!("settings-schedule-intro"));
+    ui.add_space(5.0);
+
+    let current = settings::get().profile_schedule;
+    let mut schedule = current.clone();
+
+    let mut remove = None;
+    for (index, entry) in schedule.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut entry.enabled, "");
+            ui.add(
+                TextEdit::singleline(&mut entry.profile)
+                    .hint_text(tr!("settings-schedule-profile"))
+                    .desired_width(150.0),
+            );
+            ui.label(tr!("settings-schedule-from"));
+            schedule_time_ui(ui, &mut entry.start);
+            ui.label(tr!("settings-schedule-to"));
+            schedule_time_ui(ui, &mut entry.end);
+
+            if ui.button(tr!("settings-schedule-remove")).clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        schedule.remove(index);
+    }
+
+    if ui.button(tr!("settings-schedule-add")).clicked() {
+        schedule.push(ScheduledProfile::default());
+    }
+
+    let now = ScheduleTime::now();
+    let active = match scheduled_profile(now) {
+        Some(name) => tr!("settings-schedule-active", time = now, profile = name),
+        None => tr!("settings-schedule-none", time = now),
+    };
+    ui.label(RichText::new(active).weak());
+
+    if schedule != current {
+        settings::update(|s| s.profile_schedule = schedule);
+    }
+}
+
+fn display_sleep_ui(ui: &mut Ui) {
     ui.label(
-        RichText::new("Enhance your Beacn on Linux experience with Pipeweaver")
+        RichText::new(tr!("settings-display-sleep"))
             .strong()
-            .size(22.0),
+            .size(14.0),
+    );
+    ui.add_space(5.0);
+    ui.label(tr!("settings-display-sleep-intro"));
+    ui.add_space(5.0);
+
+    // The device manager picks up changes on its next check
+    let current = settings::get().display_sleep;
+    let mut sleep = current;
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut sleep.enabled, tr!("settings-display-sleep-enabled"));
+        ui.add_enabled_ui(sleep.enabled, |ui| {
+            ui.label(tr!("settings-schedule-from"));
+            schedule_time_ui(ui, &mut sleep.start);
+            ui.label(tr!("settings-schedule-to"));
+            schedule_time_ui(ui, &mut sleep.end);
+        });
+    });
+
+    if sleep != current {
+        settings::update(|s| s.display_sleep = sleep);
+    }
+}
+
+fn schedule_time_ui(ui: &mut Ui, time: &mut ScheduleTime) {
+    ui.spacing_mut().item_spacing.x = 2.0;
+    ui.add(
+        DragValue::new(&mut time.hour)
+            .range(0..=23)
+            .custom_formatter(|v, _| format!("{v:02}")),
+    );
+    ui.label(":");
+    ui.add(
+        DragValue::new(&mut time.minute)
+            .range(0..=59)
+            .custom_formatter(|v, _| format!("{v:02}")),
     );
+}
+
+fn shortcuts_ui(ui: &mut Ui) {
+    ui.label(RichText::new(tr!("settings-shortcuts")).strong().size(14.0));
+    ui.add_space(5.0);
+
+    let registry = shortcuts::registry();
+    let keys = |shortcut| {
+        let found = registry.iter().find(|(_, s)| *s == shortcut);
+        found.map_or_else(String::new, |(keys, _)| ui.ctx().format_shortcut(keys))
+    };
+
+    // The page shortcuts are shown as a single range, rather than one row each
+    let pages = registry
+        .iter()
+        .filter(|(_, s)| matches!(s, Shortcut::Page(_)));
+    let last_page = pages.last().map(|(_, s)| *s).unwrap_or(Shortcut::Page(0));
+    let rows = [
+        (
+            format!("{} – {}", keys(Shortcut::Page(0)), keys(last_page)),
+            tr!("settings-shortcuts-page"),
+        ),
+        (keys(Shortcut::NextDevice), tr!("settings-shortcuts-next")),
+        (
+            keys(Shortcut::PreviousDevice),
+            tr!("settings-shortcuts-previous"),
+        ),
+        (keys(Shortcut::ToggleFx), tr!("settings-shortcuts-fx")),
+        (keys(Shortcut::MicMute), tr!("settings-shortcuts-mute")),
+    ];
+
+    Grid::new("shortcuts")
+        .num_columns(2)
+        .spacing([20.0, 4.0])
+        .show(ui, |ui| {
+            for (keys, action) in rows {
+                ui.monospace(keys);
+                ui.label(action);
+                ui.end_row();
+            }
+        });
+    ui.add_space(5.0);
+    ui.label(RichText::new(tr!("settings-shortcuts-tab")).weak());
+}
+
+fn hook_event_label(event: HookEvent) -> String {
+    match event {
+        HookEvent::DeviceConnected => tr!("settings-hooks-connected"),
+        HookEvent::DeviceRemoved => tr!("settings-hooks-removed"),
+        HookEvent::MicMuted => tr!("settings-hooks-mic-muted"),
+        HookEvent::MicUnmuted => tr!("settings-hooks-mic-unmuted"),
+        HookEvent::PanicMuted => tr!("settings-hooks-muted"),
+        HookEvent::PanicUnmuted => tr!("settings-hooks-unmuted"),
+        HookEvent::ProfileSwitched => tr!("settings-hooks-profile"),
+    }
+}
+
+fn hook_action_label(action: &HookAction) -> String {
+    match action {
+        HookAction::Command(_) => tr!("settings-hooks-command"),
+        HookAction::TogglePanicMute => tr!("settings-hooks-panic-mute"),
+        HookAction::SuppressorProfile(_) => tr!("settings-hooks-suppressor"),
+        HookAction::StreamTimer(_) => tr!("settings-hooks-timer"),
+    }
+}
+
+fn support_bundle_ui(
+    ui: &mut Ui,
+    bundle: &mut SupportBundle,
+    devices: impl FnOnce() -> Vec<DeviceReport>,
+) {
+    if let Some(rx) = &bundle.pending {
+        match rx.try_recv() {
+            Ok(result) => {
+                bundle.pending = None;
+                bundle.result = Some(result.map_err(|e| e.to_string()));
+            }
+            Err(oneshot::TryRecvError::Disconnected) => bundle.pending = None,
+            Err(oneshot::TryRecvError::Empty) => {
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+            }
+        }
+    }
+
+    ui.horizontal(|ui| {
+        if bundle.pending.is_some() {
+            ui.spinner();
+            ui.label(tr!("settings-support-creating"));
+        } else if ui
+            .button(tr!("settings-support-create"))
+            .on_hover_text(tr!("settings-support-hover"))
+            .clicked()
+        {
+            // The device states are captured here, the rest is gathered in the background
+            let devices = devices();
+            let (tx, rx) = oneshot::channel();
+            runtime().spawn_blocking(move || {
+                let _ = tx.send(create_bundle(devices));
+            });
+            bundle.pending = Some(rx);
+            bundle.result = None;
+        }
+    });
+
+    match &bundle.result {
+        Some(Ok(path)) => {
+            let path = path.display().to_string();
+            ui.label(tr!("settings-support-saved", path = path));
+            if ui.button(tr!("settings-support-copy")).clicked() {
+                ui.ctx().copy_text(path);
+            }
+        }
+        Some(Err(e)) => {
+            ui.label(tr!("settings-support-failed", error = e));
+        }
+        None => {}
+    }
+}
+
+fn theme_mode_label(mode: ThemeMode) -> String {
+    match mode {
+        ThemeMode::System => tr!("settings-theme-system"),
+        ThemeMode::Dark => tr!("settings-theme-dark"),
+        ThemeMode::Light => tr!("settings-theme-light"),
+    }
+}
+
+pub(crate) fn integrations_ui(ui: &mut Ui) {
+    ui.heading(tr!("integrations-heading"));
+    ui.add_space(20.0);
+
+    ui.label(RichText::new(tr!("integrations-obs")).strong().size(14.0));
+    ui.add_space(5.0);
+    ui.label(tr!("integrations-obs-intro"));
+    ui.add_space(10.0);
+
+    // Changes are saved straight away, the OBS handler picks them up on its next check
+    let current = settings::get().obs;
+    let mut obs = current.clone();
+
+    ui.checkbox(&mut obs.enabled, tr!("integrations-obs-enabled"));
+    ui.add_enabled_ui(obs.enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(tr!("integrations-obs-address"));
+            ui.text_edit_singleline(&mut obs.address);
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("integrations-obs-password"));
+            ui.add(TextEdit::singleline(&mut obs.password).password(true));
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("integrations-obs-mute-scene"))
+                .on_hover_text(tr!("integrations-obs-mute-scene-hover"));
+            ui.text_edit_singleline(&mut obs.mute_scene);
+        });
+
+        let status = match obs::status() {
+            ObsStatus::Disabled => tr!("integrations-obs-disabled"),
+            ObsStatus::Connecting => tr!("integrations-obs-connecting"),
+            ObsStatus::Connected => match obs::current_scene() {
+                Some(scene) => tr!("integrations-obs-connected-scene", scene = scene),
+                None => tr!("integrations-obs-connected"),
+            },
+            ObsStatus::Failed(error) => tr!("integrations-obs-failed", error = error),
+            ObsStatus::Paused => tr!("integrations-obs-paused"),
+        };
+        ui.add_space(5.0);
+        ui.label(status);
+
+        ui.add_space(10.0);
+        ui.label(RichText::new(tr!("integrations-obs-colours")).strong());
+        ui.label(tr!("integrations-obs-colours-intro"));
+
+        let mut remove = None;
+        for (index, scene) in obs.scene_colours.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut scene.scene);
+                ui.color_edit_button_srgb(&mut scene.colour);
+                if ui.button(tr!("integrations-obs-remove")).clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove {
+            obs.scene_colours.remove(index);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button(tr!("integrations-obs-add")).clicked() {
+                obs.scene_colours.push(SceneColour {
+                    scene: String::new(),
+                    colour: [255, 255, 255],
+                });
+            }
+
+            let scene = obs::current_scene();
+            let known = scene
+                .as_ref()
+                .is_some_and(|s| obs.scene_colours.iter().any(|c| &c.scene == s));
+            if let Some(scene) = scene.filter(|_| !known)
+                && ui.button(tr!("integrations-obs-add-current")).clicked()
+            {
+                obs.scene_colours.push(SceneColour {
+                    scene,
+                    colour: [255, 255, 255],
+                });
+            }
+        });
+    });
+
+    if obs != current {
+        settings::update(|s| s.obs = obs);
+    }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    companion_ui(ui);
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    event_stream_ui(ui);
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.add_space(10.0);
+    header_meter_ui(ui);
+
+    // The connection state changes in the background
+    ui.ctx().request_repaint_after(Duration::from_secs(1));
+}
+
+fn companion_ui(ui: &mut Ui) {
+    ui.label(
+        RichText::new(tr!("integrations-companion"))
+            .strong()
+            .size(14.0),
+    );
+    ui.add_space(5.0);
+    ui.label(tr!("integrations-companion-intro"));
+    ui.add_space(10.0);
+
+    // As with OBS, the handler picks up any changes on its next check
+    let current = settings::get().companion;
+    let mut companion = current.clone();
+
+    if ui
+        .checkbox(
+            &mut companion.enabled,
+            tr!("integrations-companion-enabled"),
+        )
+        .changed()
+        && companion.token.is_empty()
+    {
+        companion.token = companion::generate_token();
+    }
+
+    ui.add_enabled_ui(companion.enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(tr!("integrations-companion-port"));
+            ui.add(DragValue::new(&mut companion.port).range(1024..=65535));
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("integrations-companion-token"));
+            ui.add(TextEdit::singleline(&mut companion.token.as_str()).password(true));
+            if ui.button(tr!("integrations-companion-copy")).clicked() {
+                ui.ctx().copy_text(companion.token.clone());
+            }
+            if ui
+                .button(tr!("integrations-companion-regenerate"))
+                .on_hover_text(tr!("integrations-companion-regenerate-hover"))
+                .clicked()
+            {
+                companion.token = companion::generate_token();
+            }
+        });
+
+        let status = match companion::status() {
+            ServerStatus::Disabled => tr!("integrations-companion-disabled"),
+            ServerStatus::Listening => tr!(
+                "integrations-companion-listening",
+                port = companion.port,
+                clients = companion::clients()
+            ),
+            ServerStatus::Failed(error) => tr!("integrations-companion-failed", error = error),
+        };
+        ui.add_space(5.0);
+        ui.label(status);
+    });
+
+    if companion != current {
+        settings::update(|s| s.companion = companion);
+    }
+}
+
+fn event_stream_ui(ui: &mut Ui) {
+    ui.label(
+        RichText::new(tr!("integrations-events"))
+            .strong()
+            .size(14.0),
+    );
+    ui.add_space(5.0);
+    ui.label(tr!("integrations-events-intro"));
+    ui.add_space(10.0);
+
+    let current = settings::get().event_stream;
+    let mut stream = current.clone();
+
+    ui.checkbox(&mut stream.enabled, tr!("integrations-events-enabled"));
+    ui.add_enabled_ui(stream.enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(tr!("integrations-companion-port"));
+            ui.add(DragValue::new(&mut stream.port).range(1024..=65535));
+        });
+
+        let listening = events::status() == ServerStatus::Listening;
+        let status = match events::status() {
+            ServerStatus::Disabled => tr!("integrations-companion-disabled"),
+            ServerStatus::Listening => tr!(
+                "integrations-companion-listening",
+                port = current.port,
+                clients = events::clients()
+            ),
+            ServerStatus::Failed(error) => tr!("integrations-companion-failed", error = error),
+        };
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            ui.label(status);
+            if listening && ui.button(tr!("integrations-events-test")).clicked() {
+                let url = format!("http://localhost:{}/", current.port);
+                ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+            }
+        });
+    });
+
+    if stream != current {
+        settings::update(|s| s.event_stream = stream);
+    }
+}
+
+fn header_meter_ui(ui: &mut Ui) {
+    ui.label(
+        RichText::new(tr!("integrations-header-meter"))
+            .strong()
+            .size(14.0),
+    );
+    ui.add_space(5.0);
+    ui.label(tr!("integrations-header-meter-intro"));
+    ui.add_space(10.0);
+
+    // The display handler picks this up on its next header update
+    let mut enabled = settings::get().header_meter;
+    if ui
+        .checkbox(&mut enabled, tr!("integrations-header-meter-enabled"))
+        .changed()
+    {
+        settings::update(|s| s.header_meter = enabled);
+    }
+}
+
+pub(crate) fn pipeweaver_ui(ui: &mut Ui) {
+    ui.label(RichText::new(tr!("pipeweaver-heading")).strong().size(22.0));
     ui.add_space(20.0);
-    ui.label("Pipeweaver brings streaming-focused audio control to Linux, with mixing, routing, and separate personal and stream outputs.");
+    ui.label(tr!("pipeweaver-intro"));
     ui.add_space(10.0);
-    ui.label("If you have a Mix / Mix Create, the Beacn Utility will talk to Pipeweaver to bring volume and mix control to your devices, similar to how you've used them on Windows.");
+    ui.label(tr!("pipeweaver-controllers"));
     ui.add_space(10.0);
-    ui.label("Pipeweaver isn’t running right now. If you’ve already installed it, just start it up. If not, hit the button below and give it an install!");
+    ui.label(tr!("pipeweaver-not-running"));
     ui.add_space(20.0);
 
     // CTA BUTTON (make it feel like a button, not a link)
     let btn = ui.add_sized(
         [160.0, 32.0],
-        egui::Button::new(RichText::new("Get Pipeweaver").strong()),
+        egui::Button::new(RichText::new(tr!("pipeweaver-get")).strong()),
     );
 
     if btn.hovered() {
@@ -0,0 +1,100 @@
+use crate::i18n::tr;
+use crate::ui::theme;
+use anyhow::Result;
+use egui::{RichText, ScrollArea, Ui};
+use std::collections::VecDeque;
+
+const MAX_ENTRIES: usize = 50;
+
+struct ConsoleEntry {
+    request: String,
+    response: String,
+    success: bool,
+
+    // What the user typed to send this, so it can be sent again
+    source: Option<String>,
+
+    // A lower level view of the request and response, shown when 'Show Raw' is ticked
+    raw: Option<String>,
+}
+
+// A history of requests sent from the developer console, and what came back
+#[derive(Default)]
+pub struct ConsoleLog {
+    entries: VecDeque<ConsoleEntry>,
+    show_raw: bool,
+}
+
+impl ConsoleLog {
+    pub fn push(&mut self, request: String, response: Result<String>) {
+        self.push_raw(request, None, response, None);
+    }
+
+    pub fn push_raw(
+        &mut self,
+        request: String,
+        source: Option<String>,
+        response: Result<String>,
+        raw: Option<String>,
+    ) {
+        let (response, success) = match response {
+            Ok(response) => (response, true),
+            Err(e) => (format!("{e:#}"), false),
+        };
+
+        self.entries.push_front(ConsoleEntry {
+            request,
+            response,
+            success,
+            source,
+            raw,
+        });
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Draws the history, returning the source of an entry if the user wants to reuse it
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<String> {
+        if self.entries.is_empty() {
+            ui.label(tr!("developer-empty"));
+            return None;
+        }
+
+        if self.entries.iter().any(|entry| entry.raw.is_some()) {
+            ui.checkbox(&mut self.show_raw, tr!("developer-show-raw"));
+        }
+
+        let theme = theme::current(ui.ctx());
+        let mut reuse = None;
+        ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for entry in &self.entries {
+                    let colour = match entry.success {
+                        true => theme.success,
+                        false => theme.failure,
+                    };
+
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("> {}", entry.request)).monospace());
+                        if let Some(source) = &entry.source
+                            && ui.small_button(tr!("developer-edit")).clicked()
+                        {
+                            reuse = Some(source.clone());
+                        }
+                    });
+                    ui.label(RichText::new(&entry.response).monospace().color(colour));
+                    if self.show_raw
+                        && let Some(raw) = &entry.raw
+                    {
+                        ui.label(RichText::new(raw).monospace().weak());
+                    }
+                    ui.add_space(6.0);
+                }
+            });
+        reuse
+    }
+}
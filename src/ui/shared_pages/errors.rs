@@ -2,12 +2,29 @@ use crate::ui::states::{ErrorMessage, LoadState};
 use beacn_lib::manager::DeviceLocation;
 use egui::{RichText, Ui};
 
+/// Something the user asked for from the error page, to be handled by the device's page
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Open the permissions diagnostics
+    Diagnose,
+
+    /// Try opening the device again (now, if it's busy)
+    RetryOpen,
+
+    /// Send the failed message at this index again
+    Resend(usize),
+
+    /// Carry on using the device without the values which failed to load
+    Continue,
+}
+
 pub fn display_errors(
     ui: &mut Ui,
     load_state: &LoadState,
     device_location: &DeviceLocation,
     errors: &Vec<ErrorMessage>,
-) {
+) -> Option<ErrorAction> {
+    let mut action = None;
     ui.add_sized(
         [ui.available_width(), ui.available_height()],
         |ui: &mut Ui| {
@@ -21,26 +38,55 @@ pub fn display_errors(
                         ui.label("The application does not have permission to access the connected device.");
                         ui.add_space(5.0);
                         ui.hyperlink_to("Please visit this wiki page for help.", "https://github.com/beacn-on-linux/beacn-permissions/wiki/Installing-Device-Permission");
+                        ui.add_space(10.0);
+                        if ui.button("Diagnose Permissions").clicked() {
+                            action = Some(ErrorAction::Diagnose);
+                        }
                     }
                     LoadState::ResourceBusy => {
                         ui.label("Resource Busy");
                         ui.label("The connected device is currently in use by another application. Please close any other applications that may be using the device and try again.");
+                        ui.add_space(5.0);
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("We'll keep trying to connect in the background, this page will go away once the device is free.");
+                        });
                         ui.add_space(10.0);
                         ui.horizontal(|ui| {
                             ui.label(RichText::new("Note:").strong());
                             ui.label("This problem may be caused by older firmware, please ensure your device is up-to-date");
                         });
+                        ui.add_space(10.0);
+                        if ui.button("Retry Now").clicked() {
+                            action = Some(ErrorAction::RetryOpen);
+                        }
                     }
                     LoadState::Error => {
                         ui.label("Device in Error State");
-                        for message in errors {
+                        for (index, message) in errors.iter().enumerate() {
                             ui.add_space(15.0);
                             if let Some(error) = &message.error_text {
                                 ui.label(format!("Error: {error:?}"));
                             }
                             if let Some(message) = &message.failed_message {
-                                ui.label(format!("Message: {message:?}"));
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("Message: {message:?}"));
+                                    if ui.button("Resend").clicked() {
+                                        action = Some(ErrorAction::Resend(index));
+                                    }
+                                });
+                            }
+                        }
+
+                        // Messages which failed while loading can be retried, or skipped, but
+                        // anything else means the device never opened
+                        ui.add_space(15.0);
+                        if errors.iter().any(|e| e.failed_message.is_some()) {
+                            if ui.button("Continue Anyway").on_hover_text("Use the device without the values which failed to load").clicked() {
+                                action = Some(ErrorAction::Continue);
                             }
+                        } else if ui.button("Try Opening Again").clicked() {
+                            action = Some(ErrorAction::RetryOpen);
                         }
                     }
                     _ => {
@@ -49,4 +95,5 @@ pub fn display_errors(
                 }
             }).response
         });
+    action
 }
@@ -0,0 +1,35 @@
+use crate::i18n::tr;
+use crate::ui::states::DeviceIdentity;
+use egui::{RichText, Ui};
+
+/// Lets the user name a device and give it an accent colour, returns the new identity if it's
+/// been changed
+pub fn identity_ui(ui: &mut Ui, identity: &DeviceIdentity) -> Option<DeviceIdentity> {
+    let mut changed = identity.clone();
+
+    ui.add_space(10.0);
+    ui.label(RichText::new(tr!("identity-heading")).strong());
+    ui.add_space(5.0);
+
+    ui.horizontal(|ui| {
+        ui.label(tr!("identity-nickname"));
+        let mut nickname = changed.nickname.clone().unwrap_or_default();
+        if ui.text_edit_singleline(&mut nickname).changed() {
+            // Trimmed when shown, so spaces can still be typed between words
+            changed.nickname = (!nickname.trim().is_empty()).then_some(nickname);
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr!("identity-accent"));
+        let mut colour = changed.accent.unwrap_or([255, 255, 255]);
+        if ui.color_edit_button_srgb(&mut colour).changed() {
+            changed.accent = Some(colour);
+        }
+        if changed.accent.is_some() && ui.button(tr!("identity-accent-reset")).clicked() {
+            changed.accent = None;
+        }
+    });
+
+    (changed != *identity).then_some(changed)
+}
@@ -0,0 +1,53 @@
+use crate::device_manager::{DeviceDefinition, ignored_devices};
+use crate::i18n::tr;
+use crate::{diagnostics, settings};
+use egui::{RichText, Ui};
+
+/// Lets the user tell us to leave a device alone, it's closed and disappears from the sidebar
+pub fn ignore_ui(ui: &mut Ui, definition: &DeviceDefinition) {
+    ui.add_space(10.0);
+    if ui
+        .button(tr!("ignore-device"))
+        .on_hover_text(tr!("ignore-device-hover"))
+        .clicked()
+    {
+        let serial = definition.device_info.serial.clone();
+        settings::update(|s| {
+            if !s.ignored_devices.contains(&serial) {
+                s.ignored_devices.push(serial);
+            }
+        });
+
+        // Reopening it is what closes it, as it'll now be ignored
+        diagnostics::request_reconnect(definition.location, definition.device_type);
+    }
+}
+
+/// The devices being ignored, any which are attached are opened if they're no longer ignored
+pub fn ignored_devices_ui(ui: &mut Ui) {
+    ui.label(RichText::new(tr!("ignore-heading")).strong().size(14.0));
+    ui.add_space(5.0);
+
+    let serials = settings::get().ignored_devices;
+    if serials.is_empty() {
+        ui.label(tr!("ignore-none"));
+        return;
+    }
+
+    let attached = ignored_devices();
+    for serial in serials {
+        let device = attached.iter().find(|d| d.serial == serial);
+        ui.horizontal(|ui| {
+            match device {
+                Some(device) => ui.label(format!("{serial} ({:?})", device.device_type)),
+                None => ui.label(RichText::new(&serial).weak()),
+            };
+            if ui.button(tr!("ignore-stop")).clicked() {
+                settings::update(|s| s.ignored_devices.retain(|s| *s != serial));
+                if let Some(device) = device {
+                    diagnostics::request_reprobe(device.location, device.device_type);
+                }
+            }
+        });
+    }
+}
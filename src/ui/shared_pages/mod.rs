@@ -1 +1,6 @@
+pub(crate) mod console;
 pub(crate) mod errors;
+pub(crate) mod identity;
+pub(crate) mod ignore;
+pub(crate) mod permissions;
+pub(crate) mod stats;
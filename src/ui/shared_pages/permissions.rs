@@ -0,0 +1,186 @@
+use crate::device_manager::DeviceDefinition;
+use crate::diagnostics::{PermissionReport, check_permissions, install_rules, manual_instructions};
+use crate::i18n::tr;
+use crate::ui::theme;
+use crate::{diagnostics, runtime};
+use anyhow::Result;
+use beacn_lib::manager::DeviceLocation;
+use egui::{RichText, ScrollArea, TextEdit, Ui};
+use std::time::Duration;
+
+// Walks through why a device couldn't be opened, and how to fix it. This is opened from the
+// error page when a device reports Permission Denied.
+#[derive(Default)]
+pub struct PermissionDiagnostics {
+    location: Option<DeviceLocation>,
+    report: Option<PermissionReport>,
+
+    installing: Option<oneshot::Receiver<Result<()>>>,
+    install_result: Option<Result<()>>,
+}
+
+impl PermissionDiagnostics {
+    pub fn is_open(&self) -> bool {
+        self.location.is_some()
+    }
+
+    pub fn open(&mut self, location: DeviceLocation) {
+        *self = Self {
+            location: Some(location),
+            report: Some(check_permissions(location)),
+            ..Default::default()
+        };
+    }
+
+    pub fn close(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, definition: &DeviceDefinition) {
+        // The user may have swapped to a different broken device
+        if self.location != Some(definition.location) {
+            self.open(definition.location);
+        }
+
+        ui.horizontal(|ui| {
+            ui.heading(tr!("permissions-heading"));
+            if ui.button(tr!("permissions-back")).clicked() {
+                self.close();
+            }
+        });
+        ui.add_space(10.0);
+
+        let Some(report) = self.report.clone() else {
+            return;
+        };
+
+        ScrollArea::vertical().show(ui, |ui| {
+            self.draw_checks(ui, &report);
+
+            ui.add_space(15.0);
+            ui.separator();
+            ui.add_space(10.0);
+
+            if report.node_accessible {
+                ui.label(tr!("permissions-accessible"));
+            } else if report.sandboxed {
+                ui.label(tr!("permissions-flatpak"));
+                self.draw_instructions(ui);
+            } else if report.rules_file.is_none() {
+                self.draw_install(ui);
+            } else {
+                ui.label(tr!("permissions-still-denied"));
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr!("permissions-check")).clicked() {
+                    self.report = Some(check_permissions(definition.location));
+                }
+                if ui.button(tr!("permissions-retry")).clicked() {
+                    diagnostics::request_reprobe(definition.location, definition.device_type);
+                }
+            });
+        });
+    }
+
+    fn draw_checks(&self, ui: &mut Ui, report: &PermissionReport) {
+        let rules = match &report.rules_file {
+            Some(path) => tr!("permissions-rules-found", path = path.display()),
+            None if report.sandboxed => tr!("permissions-rules-sandboxed"),
+            None => tr!("permissions-rules-missing"),
+        };
+        let label = tr!("permissions-rules");
+        Self::draw_check(ui, &label, report.rules_file.is_some(), &rules);
+
+        let node = report.device_node.display().to_string();
+        let detail = match report.node_exists {
+            true => node,
+            false => tr!("permissions-node-missing", node = node),
+        };
+        let label = tr!("permissions-node");
+        Self::draw_check(ui, &label, report.node_accessible, &detail);
+
+        if let Some(group) = &report.node_group {
+            let detail = match report.in_node_group() {
+                true => tr!("permissions-group-member", group = group),
+                false => tr!("permissions-group-not-member", group = group),
+            };
+
+            // Group membership isn't needed with the uaccess tag, so this is informational
+            let label = tr!("permissions-group");
+            Self::draw_check(ui, &label, report.in_node_group(), &detail);
+        }
+    }
+
+    fn draw_check(ui: &mut Ui, label: &str, passed: bool, detail: &str) {
+        let theme = theme::current(ui.ctx());
+        ui.horizontal(|ui| {
+            let (icon, colour) = match passed {
+                true => ("✔", theme.success),
+                false => ("✖", theme.failure),
+            };
+            ui.label(RichText::new(icon).color(colour).strong());
+            ui.label(RichText::new(label).strong());
+            ui.label(detail);
+        });
+    }
+
+    fn draw_install(&mut self, ui: &mut Ui) {
+        ui.label(tr!("permissions-install-intro"));
+        ui.add_space(5.0);
+
+        if let Some(rx) = &self.installing {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.installing = None;
+                    self.install_result = Some(result);
+                    self.report = self.location.map(check_permissions);
+                }
+                Err(oneshot::TryRecvError::Disconnected) => self.installing = None,
+                Err(oneshot::TryRecvError::Empty) => {
+                    ui.ctx().request_repaint_after(Duration::from_millis(100));
+                }
+            }
+        }
+
+        if self.installing.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(tr!("permissions-installing"));
+            });
+        } else if ui.button(tr!("permissions-install")).clicked() {
+            let (tx, rx) = oneshot::channel();
+            runtime().spawn_blocking(move || {
+                let _ = tx.send(install_rules());
+            });
+            self.installing = Some(rx);
+            self.install_result = None;
+        }
+
+        match &self.install_result {
+            Some(Ok(())) => {
+                ui.label(tr!("permissions-installed"));
+            }
+            Some(Err(e)) => {
+                ui.label(tr!("permissions-install-failed", error = e));
+                ui.add_space(5.0);
+                ui.label(tr!("permissions-manual"));
+                self.draw_instructions(ui);
+            }
+            None => {}
+        }
+    }
+
+    fn draw_instructions(&self, ui: &mut Ui) {
+        let instructions = manual_instructions();
+        ui.add(
+            TextEdit::multiline(&mut instructions.as_str())
+                .code_editor()
+                .desired_width(f32::INFINITY),
+        );
+        if ui.button(tr!("permissions-copy")).clicked() {
+            ui.ctx().copy_text(instructions);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::device_manager::DeviceDefinition;
+use crate::i18n::tr;
+use crate::{diagnostics, stats};
+use egui::{RichText, Ui};
+use std::time::Duration;
+
+pub fn display_stats(ui: &mut Ui, definition: &DeviceDefinition) {
+    let Some(stats) = stats::get(&definition.device_info.serial) else {
+        return;
+    };
+
+    ui.add_space(10.0);
+    ui.label(RichText::new("Connection Statistics (This Session)").strong());
+    ui.add_space(5.0);
+
+    let rows = [
+        ("Connections: ", stats.connects.to_string()),
+        ("Disconnections: ", stats.disconnects.to_string()),
+        ("Errors: ", stats.errors.to_string()),
+        ("Failed Messages: ", stats.failed_messages.to_string()),
+        ("Uptime: ", format_uptime(stats.uptime())),
+    ];
+    for (label, value) in rows {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(label).strong().size(14.0));
+            ui.label(RichText::new(value).size(14.0));
+        });
+    }
+
+    let keepalive = match stats.last_keepalive {
+        Some((at, success)) => {
+            let result = if success { "OK" } else { "Failed" };
+            format!("{result}, {} ago", format_uptime(at.elapsed()))
+        }
+        None => String::from("Not Sent"),
+    };
+    let port = diagnostics::usb_port(definition.location);
+    let rows = [
+        ("Last Keepalive: ", keepalive),
+        (
+            "USB Path: ",
+            port.as_ref()
+                .map_or(String::from("Unknown"), |p| p.path.clone()),
+        ),
+        (
+            "USB Speed: ",
+            port.as_ref()
+                .map_or(String::from("Unknown"), |p| p.speed_name()),
+        ),
+    ];
+    ui.add_space(5.0);
+    for (label, value) in rows {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(label).strong().size(14.0));
+            ui.label(RichText::new(value).size(14.0));
+        });
+    }
+
+    ui.add_space(5.0);
+    if ui
+        .button(tr!("stats-reconnect"))
+        .on_hover_text(tr!("stats-reconnect-hover"))
+        .clicked()
+    {
+        diagnostics::request_reconnect(definition.location, definition.device_type);
+    }
+
+    // Keep the uptime ticking
+    ui.ctx().request_repaint_after(Duration::from_secs(1));
+}
+
+pub(crate) fn format_uptime(uptime: Duration) -> String {
+    let seconds = uptime.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, (seconds / 60) % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    } else {
+        format!("{minutes}m {seconds:02}s")
+    }
+}
@@ -0,0 +1,66 @@
+/*
+  Keyboard shortcuts for the main window. Every shortcut is registered here, so the app checks
+  them in one place each frame, and the settings page can list them without repeating the keys.
+
+  Shortcuts are consumed when matched, so the focused widget doesn't also act on the key.
+*/
+
+use egui::{Context, Key, KeyboardShortcut, Modifiers};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Shortcut {
+    /// Switch to a page of the current device, counting only the pages shown in the sidebar
+    Page(usize),
+    NextDevice,
+    PreviousDevice,
+    ToggleFx,
+    MicMute,
+}
+
+const PAGE_KEYS: [Key; 9] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+/// Every shortcut, and the keys which trigger it
+pub fn registry() -> Vec<(KeyboardShortcut, Shortcut)> {
+    let command = Modifiers::COMMAND;
+    let shift = Modifiers::COMMAND | Modifiers::SHIFT;
+
+    // Shift is ignored unless asked for, so anything using it needs to be checked first
+    let mut shortcuts = vec![
+        (
+            KeyboardShortcut::new(shift, Key::Tab),
+            Shortcut::PreviousDevice,
+        ),
+        (KeyboardShortcut::new(shift, Key::M), Shortcut::MicMute),
+        (
+            KeyboardShortcut::new(command, Key::Tab),
+            Shortcut::NextDevice,
+        ),
+        (KeyboardShortcut::new(command, Key::B), Shortcut::ToggleFx),
+    ];
+    for (index, key) in PAGE_KEYS.into_iter().enumerate() {
+        shortcuts.push((KeyboardShortcut::new(command, key), Shortcut::Page(index)));
+    }
+    shortcuts
+}
+
+/// Takes any shortcuts pressed since the last frame out of the input
+pub fn pressed(ctx: &Context) -> Vec<Shortcut> {
+    let registry = registry();
+    ctx.input_mut(|input| {
+        registry
+            .iter()
+            .filter(|(keys, _)| input.consume_shortcut(keys))
+            .map(|(_, shortcut)| *shortcut)
+            .collect()
+    })
+}
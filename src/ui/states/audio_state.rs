@@ -6,18 +6,28 @@ use beacn_lib::audio::messages::compressor::CompressorMode;
 use beacn_lib::audio::messages::equaliser::{EQBand, EQBandType, EQMode};
 use beacn_lib::audio::messages::expander::ExpanderMode;
 use beacn_lib::audio::messages::headphone_eq::HPEQType;
-use beacn_lib::audio::messages::headphones::HeadphoneTypes;
+use beacn_lib::audio::messages::headphones::{HPLevel, HeadphoneTypes};
 use beacn_lib::audio::messages::lighting::{
-    LightingMeterSource, LightingMode, LightingMuteMode, LightingSuspendMode, StudioLightingMode,
+    LightingBrightness, LightingMeterSource, LightingMode, LightingMuteMode, LightingSuspendMode,
+    StudioLightingMode,
 };
 use beacn_lib::audio::messages::suppressor::SuppressorStyle;
-use beacn_lib::types::ToInner;
+use beacn_lib::types::{RGBA, ToInner};
 use enum_map::{Enum, EnumMap};
 
 use crate::device_manager::{
     AudioMessage, DefinitionState, DeviceDefinition, ErrorType, LinkedCommands,
 };
-use crate::ui::states::{DeviceState, ErrorMessage, LoadState};
+use crate::hooks::{self, HookEvent};
+use crate::integrations::events::{self, Event};
+use crate::integrations::headphone_sync::{HeadphoneSync, HeadphoneSyncSettings};
+use crate::integrations::mute_sync::MuteSync;
+use crate::storage;
+use crate::ui::states::audit::AuditTrail;
+use crate::ui::states::conflict::ConflictMonitor;
+use crate::ui::states::diff::{ApplyPlan, ApplyProgress};
+use crate::ui::states::identify::{Identify, IdentifyStep};
+use crate::ui::states::{DeviceIdentity, DeviceState, ErrorMessage, LoadState};
 use beacn_lib::audio::messages::bass_enhancement::BassEnhancement as MicBaseEnhancement;
 use beacn_lib::audio::messages::compressor::Compressor as MicCompressor;
 use beacn_lib::audio::messages::deesser::DeEsser as MicDeEsser;
@@ -32,11 +42,19 @@ use beacn_lib::audio::messages::subwoofer::Subwoofer as MicSubwoofer;
 use beacn_lib::audio::messages::suppressor::Suppressor as MicSuppressor;
 use beacn_lib::crossbeam::channel::Sender;
 use beacn_lib::manager::DeviceType;
-use log::debug;
+use beacn_lib::version::VersionNumber;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::mem;
 use strum_macros::EnumIter;
+use ulid::Ulid;
 
 type Rgb = [u8; 3];
 
+// After this many messages in a row fail, the affected part of the state is re-read from the
+// device, as what we're showing has probably drifted from what it's actually doing
+const RESYNC_AFTER_FAILURES: u32 = 3;
+
 #[derive(Debug, Default, Clone)]
 pub struct BeacnAudioState {
     pub device_definition: DeviceDefinition,
@@ -57,9 +75,71 @@ pub struct BeacnAudioState {
     pub subwoofer: Subwoofer,
 
     pub linked: Option<Vec<LinkedApp>>,
+    pub snapshots: Snapshots,
+    pub saved_settings: SavedSettings,
+    pub conflicts: ConflictMonitor,
+    pub audit: AuditTrail,
+    pub identify: Identify,
+    pub headphone_sync: HeadphoneSync,
+    pub mute_sync: MuteSync,
+
+    // The last profile or snapshot applied, and how much of it reached the device
+    pub last_apply: Option<ApplyProgress>,
+
+    // Messages skipped when loading, because the firmware is too old for them
+    pub unsupported: Vec<Message>,
+
+    // How many messages in a row have failed to send
+    failed_writes: u32,
+
+    // While the processing is bypassed from the keyboard, the values which turn it back on
+    fx_restore: Option<Vec<Message>>,
+
+    // The max headphone level has been dragged, so is saved once it's let go
+    save_pending: bool,
+
+    // While a page has the processing bypassed to listen to the raw mic, the values which turn
+    // it back on. Kept here rather than on the page, so it's put back however the page goes away.
+    page_restore: Option<Vec<Message>>,
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+// Settings which belong to the app rather than the device, stored per serial
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SavedSettings {
+    /// The loudest the headphones are allowed to go, in dB
+    pub max_headphone_level: Option<f32>,
+
+    /// Named noise suppression setups, for quickly switching between rooms / environments
+    pub suppressor_profiles: Vec<SuppressorProfile>,
+
+    /// How the device is shown in the sidebar and window title
+    pub identity: DeviceIdentity,
+
+    /// The Pipeweaver target the headphone level is kept in step with (Studio only)
+    pub headphone_sync_target: Option<Ulid>,
+
+    /// The Pipeweaver source the hardware mute is kept in step with (Mic only)
+    pub mute_sync_source: Option<Ulid>,
+
+    /// Copies compressor and expander changes made in Simple mode over to Advanced mode
+    pub link_dynamics: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SuppressorProfile {
+    pub name: String,
+    pub values: Vec<Message>,
+}
+
+// Two slots for quickly comparing different processing setups
+#[derive(Debug, Default, Clone)]
+pub struct Snapshots {
+    pub slots: [Option<Vec<Message>>; 2],
+    pub active: Option<usize>,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Headphones {
     pub level: f32,       // [-70.0...=0.0]
     pub mic_monitor: f32, // [-100.0..=6.0]
@@ -73,7 +153,7 @@ pub struct Headphones {
     pub mic_class_compliant: Option<bool>,
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Lighting {
     pub mic_mode: LightingMode,
     pub studio_mode: StudioLightingMode,
@@ -89,13 +169,13 @@ pub struct Lighting {
     pub suspend_brightness: u32,
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Equaliser {
     pub mode: EQMode,
     pub bands: EnumMap<EQMode, EnumMap<EqualiserBand, EqualiserBandConfig>>,
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct EqualiserBandConfig {
     pub enabled: bool,
     pub band_type: EqualiserBandType,
@@ -104,12 +184,12 @@ pub struct EqualiserBandConfig {
     pub q: f32,         // [0.1..=10.0]
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct HeadphoneEq {
     pub eq: EnumMap<HPEQType, HeadphoneEQValue>,
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct HeadphoneEQValue {
     pub enabled: bool,
     pub amount: f32, // [-12.0..=12.0]
@@ -117,20 +197,20 @@ pub struct HeadphoneEQValue {
 
 // We don't need any additional values here, when the preset changes we just
 // grab and apply the values from the lib
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct BassEnhancement {
     pub enabled: bool,
     pub preset: BassPreset,
     pub amount: i8, // [0..=10]
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Compressor {
     pub mode: CompressorMode,
     pub values: EnumMap<CompressorMode, CompressorValue>,
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct CompressorValue {
     pub enabled: bool,
     pub attack: u16,   // [1..=2000]ms
@@ -140,26 +220,26 @@ pub struct CompressorValue {
     pub makeup: f32,   // [0.0..=12.0]dB
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct DeEsser {
     pub enabled: bool,
     pub amount: u8, // [0..=100]
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Exciter {
     pub enabled: bool,
     pub amount: u8, // [0..=100]
     pub freq: u16,  // [600..=5000]
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Expander {
     pub mode: ExpanderMode,
     pub values: EnumMap<ExpanderMode, ExpanderValue>,
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct ExpanderValue {
     pub enabled: bool,
     pub attack: u16,   // [0..=2000]ms
@@ -168,7 +248,7 @@ pub struct ExpanderValue {
     pub ratio: f32,    // [0.0..=10.0]:1
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Suppressor {
     pub enabled: bool,
     pub amount: u8, // [0..=100]%
@@ -176,13 +256,13 @@ pub struct Suppressor {
     pub sense: u8, // [0..=100]%
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct MicSetup {
     pub gain: u8,      // [3..=20]dB
     pub phantom: bool, // Phantom Power (Studio)
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct Subwoofer {
     pub enabled: bool,
     pub amount: u8, // [0..=10]
@@ -190,6 +270,53 @@ pub struct Subwoofer {
 
 impl BeacnAudioState {
     pub fn handle_message(&mut self, message: Message) -> Result<Message> {
+        // Only changes are limited, fetches have to see the device's real value
+        let message = self.apply_limits(message);
+        self.conflicts.record_write();
+        let result = self.send(message);
+
+        match &result {
+            Ok(_) => self.failed_writes = 0,
+            Err(e) => {
+                warn!("Failed to send {message:?}: {e}");
+                self.failed_writes += 1;
+                if self.failed_writes >= RESYNC_AFTER_FAILURES {
+                    self.failed_writes = 0;
+                    if let Err(e) = self.resync(Some(message)) {
+                        warn!("Unable to resync from device: {e}");
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Re-reads the current values from the device, replacing what we have locally. If a
+    /// message is given, only the subsystem it belongs to (Lighting, Compressor, etc) is read.
+    pub fn resync(&mut self, subsystem: Option<Message>) -> Result<()> {
+        let device_type = self.device_definition.device_type;
+        let version = self.device_definition.device_info.version.clone();
+        match subsystem {
+            Some(message) => debug!("Resyncing from device, following {message:?}"),
+            None => debug!("Resyncing everything from device"),
+        }
+        let subsystem = subsystem.map(|message| mem::discriminant(&message));
+
+        for message in Message::generate_fetch_message(device_type) {
+            if message.get_message_minimum_version() > version {
+                continue;
+            }
+            if subsystem.is_some_and(|s| s != mem::discriminant(&message)) {
+                continue;
+            }
+
+            // Sending a fetch updates the local value with the response
+            self.send(message)?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, message: Message) -> Result<Message> {
         let (tx, rx) = oneshot::channel();
         let message = AudioMessage::Handle(message, tx);
 
@@ -202,6 +329,10 @@ impl BeacnAudioState {
                 // Quickly intercept the message, and set our local value
                 if let Ok(message) = message {
                     self.set_local_value(message);
+                    self.audit.observe(&message);
+                    if let Message::Lighting(_) = message {
+                        self.publish_lighting();
+                    }
                 }
                 Ok(message?)
             }
@@ -209,6 +340,162 @@ impl BeacnAudioState {
         }
     }
 
+    // Sends a temporary change, leaving our local value as it was so it can be put back
+    fn send_untracked(&self, message: Message) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        match &self.device_sender {
+            Some(sender) => {
+                sender.send(AudioMessage::Handle(message, tx))?;
+                rx.recv()??;
+                Ok(())
+            }
+            None => bail!("Device Sender not Ready"),
+        }
+    }
+
+    /// Moves any identify flashing of the LED ring along
+    pub fn poll_identify(&mut self) {
+        if let Some(step) = self.identify.poll() {
+            self.flash(step);
+        }
+    }
+
+    /// Stops any identify flashing, putting the ring's brightness back
+    pub fn stop_identify(&mut self) {
+        if self.identify.stop() {
+            self.flash(IdentifyStep::Restore);
+        }
+    }
+
+    fn flash(&self, step: IdentifyStep) {
+        let brightness = match step {
+            IdentifyStep::On => 100,
+            IdentifyStep::Off => 0,
+            IdentifyStep::Restore => self.lighting.brightness,
+        };
+
+        let message = Message::Lighting(MicLighting::Brightness(LightingBrightness(brightness)));
+        if let Err(e) = self.send_untracked(message) {
+            debug!("Unable to flash device: {e}");
+        }
+    }
+
+    /// Re-reads a few of the device's values, watching for anything else changing them
+    pub fn poll_conflicts(&mut self) {
+        if self.device_state.state != LoadState::Running {
+            return;
+        }
+
+        for (slot, message) in self.conflicts.next_batch() {
+            match self.send(message) {
+                Ok(value) => {
+                    self.conflicts.observe(slot, value);
+                }
+                Err(e) => {
+                    debug!("Unable to poll device value: {e}");
+                    return;
+                }
+            }
+        }
+    }
+
+    // Caps any headphone level change at the user's configured maximum
+    fn apply_limits(&self, message: Message) -> Message {
+        if let Some(max) = self.saved_settings.max_headphone_level
+            && let Message::Headphones(MicHeadphones::HeadphoneLevel(level)) = message
+            && level.to_inner() > max
+        {
+            debug!(
+                "Clamping Headphone Level from {} to {max}",
+                level.to_inner()
+            );
+            return Message::Headphones(MicHeadphones::HeadphoneLevel(HPLevel(max)));
+        }
+        message
+    }
+
+    pub fn set_identity(&mut self, identity: DeviceIdentity) {
+        self.saved_settings.identity = identity;
+        self.save_to_file();
+    }
+
+    pub fn set_max_headphone_level(&mut self, max: Option<f32>) {
+        self.saved_settings.max_headphone_level = max;
+        self.headphone_sync
+            .set_settings(self.headphone_sync_settings());
+
+        // Bring the current level down with it, rather than waiting for the next change
+        if let Some(max) = max
+            && self.headphones.level > max
+        {
+            let message = Message::Headphones(MicHeadphones::HeadphoneLevel(HPLevel(max)));
+            if self.handle_message(message).is_ok() {
+                self.headphones.level = max;
+            }
+        }
+
+        // This is set from a slider, so wait until it's let go
+        self.save_pending = true;
+    }
+
+    pub fn set_link_dynamics(&mut self, linked: bool) {
+        self.saved_settings.link_dynamics = linked;
+        self.save_to_file();
+    }
+
+    pub fn set_headphone_sync_target(&mut self, target: Option<Ulid>) {
+        self.saved_settings.headphone_sync_target = target;
+        self.headphone_sync
+            .set_settings(self.headphone_sync_settings());
+        self.save_to_file();
+    }
+
+    pub fn set_mute_sync_source(&mut self, source: Option<Ulid>) {
+        self.saved_settings.mute_sync_source = source;
+        self.mute_sync.set_source(source);
+        self.save_to_file();
+    }
+
+    /// Picks up any change the headphone sync has made to the headphone level
+    pub fn poll_headphone_sync(&mut self) {
+        if let Some(level) = self.headphone_sync.changed_level() {
+            self.headphones.level = level;
+        }
+    }
+
+    fn headphone_sync_settings(&self) -> HeadphoneSyncSettings {
+        HeadphoneSyncSettings {
+            target: self.saved_settings.headphone_sync_target,
+            max_level: self.saved_settings.max_headphone_level,
+        }
+    }
+
+    pub fn load_from_file(&mut self) {
+        let file_name = format!("{}.json", self.device_definition.device_info.serial);
+        if let Ok(file) = storage::config_path(&file_name)
+            && let Ok(config) = storage::load_json(file)
+        {
+            self.saved_settings = config;
+        }
+    }
+
+    /// Writes out a save held back while something was being dragged, called every frame
+    pub fn poll_save(&mut self, dragging: bool) {
+        if self.save_pending && !dragging {
+            self.save_pending = false;
+            self.save_to_file();
+        }
+    }
+
+    pub fn save_to_file(&self) {
+        let file_name = format!("{}.json", self.device_definition.device_info.serial);
+        if let Ok(file) = storage::config_path(&file_name)
+            && let Err(e) = storage::save_json(file, &self.saved_settings)
+        {
+            warn!("Config Saving Failed: {e}");
+        }
+    }
+
     pub fn get_linked(&mut self) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         let message = AudioMessage::Linked(LinkedCommands::GetLinked(tx));
@@ -250,8 +537,338 @@ impl BeacnAudioState {
         Ok(())
     }
 
+    /// Fetches the current values of everything which affects how the mic sounds
+    pub fn capture_processing(&mut self) -> Result<Vec<Message>> {
+        self.capture(|message| {
+            matches!(
+                message,
+                Message::BassEnhancement(_)
+                    | Message::Compressor(_)
+                    | Message::DeEsser(_)
+                    | Message::Equaliser(_)
+                    | Message::Exciter(_)
+                    | Message::Expander(_)
+                    | Message::Subwoofer(_)
+                    | Message::Suppressor(_)
+            )
+        })
+    }
+
+    /// Fetches the current values for the same kinds of setting as the given messages
+    pub fn capture_matching(&mut self, messages: &[Message]) -> Result<Vec<Message>> {
+        let kinds: Vec<_> = messages.iter().map(mem::discriminant).collect();
+        self.capture(|message| kinds.contains(&mem::discriminant(message)))
+    }
+
+    /// Features the firmware is too old for, along with the version which added them
+    pub fn unavailable_features(
+        &self,
+        filter: impl Fn(&Message) -> bool,
+    ) -> Vec<(String, VersionNumber)> {
+        let mut features: Vec<(String, VersionNumber)> = vec![];
+        for message in self.unsupported.iter().filter(|m| filter(m)) {
+            let name = Self::feature_name(message);
+            if !features.iter().any(|(n, _)| *n == name) {
+                features.push((name, message.get_message_minimum_version()));
+            }
+        }
+        features
+    }
+
+    // Messages don't carry a display name, so we build one from the variant, for example
+    // Headphones(MicClassCompliant(false)) becomes 'Mic Class Compliant'
+    fn feature_name(message: &Message) -> String {
+        let debug = format!("{message:?}");
+        let mut parts = debug.split(['(', ')', ',', ' ']).filter(|p| !p.is_empty());
+        let group = parts.next().unwrap_or_default();
+        let variant = parts.next().unwrap_or(group);
+
+        let mut name = String::new();
+        for (index, char) in variant.char_indices() {
+            if index > 0 && char.is_uppercase() {
+                name.push(' ');
+            }
+            name.push(char);
+        }
+        name
+    }
+
+    /// Turns off every stage of the mic processing, returning the values which restore it. If a
+    /// stage can't be turned off, the ones already turned off are put back.
+    pub fn bypass_processing(&mut self) -> Result<Vec<Message>> {
+        let restore: Vec<Message> = self
+            .capture_processing()?
+            .into_iter()
+            .filter(|message| Self::bypassed(*message).is_some())
+            .collect();
+
+        debug!("Bypassing {} processing stages", restore.len());
+        for (index, message) in restore.iter().enumerate() {
+            let Some(bypassed) = Self::bypassed(*message) else {
+                continue;
+            };
+            if let Err(e) = self.handle_message(bypassed) {
+                self.restore_processing(&restore[..index]);
+                return Err(e);
+            }
+        }
+        Ok(restore)
+    }
+
+    fn restore_processing(&mut self, restore: &[Message]) {
+        debug!("Restoring {} processing stages", restore.len());
+        for message in restore {
+            if let Err(e) = self.handle_message(*message) {
+                warn!("Failed to restore {message:?}: {e}");
+            }
+        }
+    }
+
+    /// Bypasses all processing, or puts it back if it's already bypassed. Returns whether the
+    /// processing is now bypassed.
+    pub fn toggle_fx(&mut self) -> Result<bool> {
+        if self.page_restore.is_some() {
+            bail!("The processing is already bypassed while the mic is being measured");
+        }
+
+        if let Some(restore) = self.fx_restore.take() {
+            debug!("Restoring {} processing stages", restore.len());
+            ApplyPlan::new(self, &restore).apply(self, |_| {})?;
+            return Ok(false);
+        }
+
+        self.fx_restore = Some(self.bypass_processing()?);
+        Ok(true)
+    }
+
+    /// Bypasses the processing while a page listens to the raw mic. It stays bypassed until
+    /// `end_page_bypass` is called, which also happens when the page is closed.
+    pub fn begin_page_bypass(&mut self) -> Result<()> {
+        if self.page_restore.is_some() || self.fx_restore.is_some() {
+            bail!("The processing is already bypassed");
+        }
+        self.page_restore = Some(self.bypass_processing()?);
+        Ok(())
+    }
+
+    /// Puts back processing bypassed by a page, if there is any
+    pub fn end_page_bypass(&mut self) {
+        if let Some(restore) = self.page_restore.take() {
+            self.restore_processing(&restore);
+        }
+    }
+
+    /// Whether a page still has the processing bypassed, pages check this so they know to stop
+    /// listening if it's been put back while they weren't looking
+    pub fn is_page_bypassed(&self) -> bool {
+        self.page_restore.is_some()
+    }
+
+    // The 'disabled' version of a processing stage's enabled flag
+    fn bypassed(message: Message) -> Option<Message> {
+        let message = match message {
+            Message::BassEnhancement(MicBaseEnhancement::Enabled(_)) => {
+                Message::BassEnhancement(MicBaseEnhancement::Enabled(false))
+            }
+            Message::Compressor(MicCompressor::Enabled(mode, _)) => {
+                Message::Compressor(MicCompressor::Enabled(mode, false))
+            }
+            Message::DeEsser(MicDeEsser::Enabled(_)) => {
+                Message::DeEsser(MicDeEsser::Enabled(false))
+            }
+            Message::Equaliser(MicEqualiser::Enabled(mode, band, _)) => {
+                Message::Equaliser(MicEqualiser::Enabled(mode, band, false))
+            }
+            Message::Exciter(MicExciter::Enabled(_)) => {
+                Message::Exciter(MicExciter::Enabled(false))
+            }
+            Message::Expander(MicExpander::Enabled(mode, _)) => {
+                Message::Expander(MicExpander::Enabled(mode, false))
+            }
+            Message::Suppressor(MicSuppressor::Enabled(_)) => {
+                Message::Suppressor(MicSuppressor::Enabled(false))
+            }
+            _ => return None,
+        };
+        Some(message)
+    }
+
+    // Fetches the current values of all messages matching the filter
+    fn capture(&mut self, filter: impl Fn(&Message) -> bool) -> Result<Vec<Message>> {
+        let device_type = self.device_definition.device_type;
+        let version = self.device_definition.device_info.version.clone();
+
+        let mut values = vec![];
+        for message in Message::generate_fetch_message(device_type) {
+            if !filter(&message) || message.get_message_minimum_version() > version {
+                continue;
+            }
+            values.push(self.handle_message(message)?);
+        }
+        Ok(values)
+    }
+
+    /// Stores the current noise suppression settings under a name, replacing any existing
+    /// profile with the same name.
+    pub fn save_suppressor_profile(&mut self, name: &str) -> Result<()> {
+        let values = self.capture(|message| matches!(message, Message::Suppressor(_)))?;
+
+        let profiles = &mut self.saved_settings.suppressor_profiles;
+        let profile = SuppressorProfile {
+            name: name.to_string(),
+            values,
+        };
+        match profiles.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = profile,
+            None => profiles.push(profile),
+        }
+        self.save_to_file();
+        Ok(())
+    }
+
+    pub fn apply_suppressor_profile(&mut self, name: &str) -> Result<()> {
+        let profiles = &self.saved_settings.suppressor_profiles;
+        let Some(profile) = profiles.iter().find(|p| p.name == name).cloned() else {
+            bail!("No Suppressor Profile named {name}");
+        };
+
+        debug!("Applying Suppressor Profile {name}");
+        let plan = ApplyPlan::new(self, &profile.values);
+        plan.apply(self, name)?;
+        self.fire_profile_hook(name);
+        Ok(())
+    }
+
+    pub fn remove_suppressor_profile(&mut self, name: &str) {
+        self.saved_settings
+            .suppressor_profiles
+            .retain(|p| p.name != name);
+        self.save_to_file();
+    }
+
+    /// The basic lighting styles for this device, and whether each is the one in use. These
+    /// match the styles at the top of the Lighting page.
+    pub fn lighting_modes(&self) -> Vec<(&'static str, bool)> {
+        let modes = self.lighting_mode_messages().into_iter();
+        modes.map(|(name, active, _)| (name, active)).collect()
+    }
+
+    pub fn apply_lighting_mode(&mut self, name: &str) -> Result<()> {
+        let modes = self.lighting_mode_messages();
+        let Some((_, active, message)) = modes.into_iter().find(|(n, _, _)| *n == name) else {
+            bail!("No Lighting Mode named {name}");
+        };
+
+        // Reactive and Sparkle have variants, so don't reset them if one is already in use
+        if !active {
+            debug!("Switching Lighting Mode to {name}");
+            self.handle_message(message)?;
+        }
+        Ok(())
+    }
+
+    // Lets overlays watching the event stream follow the lighting
+    fn publish_lighting(&self) {
+        let mode = self
+            .lighting_modes()
+            .into_iter()
+            .find(|(_, active)| *active);
+        events::publish(Event::Lighting {
+            serial: self.device_definition.device_info.serial.clone(),
+            mode: mode.map_or("", |(name, _)| name).to_string(),
+            colour: self.lighting.colour1,
+            brightness: self.lighting.brightness,
+        });
+    }
+
+    /// Sets the primary lighting colour, used when something outside the UI changes it
+    pub fn set_primary_colour(&mut self, colour: [u8; 3]) -> Result<()> {
+        if self.lighting.colour1 == colour {
+            return Ok(());
+        }
+
+        let rgba = RGBA {
+            red: colour[0],
+            green: colour[1],
+            blue: colour[2],
+            alpha: 0,
+        };
+        self.handle_message(Message::Lighting(MicLighting::Colour1(rgba)))?;
+        Ok(())
+    }
+
+    fn lighting_mode_messages(&self) -> Vec<(&'static str, bool, Message)> {
+        use LightingMode::*;
+
+        if self.device_definition.device_type == DeviceType::BeacnStudio {
+            let mode = self.lighting.studio_mode;
+            return [
+                ("Solid Colour", StudioLightingMode::Solid),
+                ("Peak Meter", StudioLightingMode::PeakMeter),
+                ("Solid Spectrum", StudioLightingMode::SolidSpectrum),
+            ]
+            .into_iter()
+            .map(|(name, m)| {
+                (
+                    name,
+                    mode == m,
+                    Message::Lighting(MicLighting::StudioMode(m)),
+                )
+            })
+            .collect();
+        }
+
+        let mode = self.lighting.mic_mode;
+        let reactive = matches!(mode, ReactiveRing | ReactiveMeterUp | ReactiveMeterDown);
+        let sparkle = matches!(mode, SparkleMeter | SparkleRandom);
+        [
+            ("Solid Colour", mode == Solid, Solid),
+            ("Gradient", mode == Gradient, Gradient),
+            ("Reactive Meter", reactive, ReactiveRing),
+            ("Sparkle", sparkle, SparkleRandom),
+            ("Spectrum Cycle", mode == Spectrum, Spectrum),
+        ]
+        .into_iter()
+        .map(|(name, active, m)| (name, active, Message::Lighting(MicLighting::Mode(m))))
+        .collect()
+    }
+
+    /// Switches to a snapshot slot, storing the current state into the active slot first. Only
+    /// values which differ from the current state are sent to the device.
+    pub fn switch_snapshot(&mut self, slot: usize) -> Result<()> {
+        let current = self.capture_processing()?;
+        if let Some(active) = self.snapshots.active {
+            self.snapshots.slots[active] = Some(current.clone());
+        }
+
+        // Snapshots are labelled A and B in the UI
+        let label = format!("Snapshot {}", char::from(b'A' + slot as u8));
+        if let Some(target) = self.snapshots.slots[slot].clone() {
+            // Capturing the current values has just refreshed the local state to compare against
+            let plan = ApplyPlan::new(self, &target);
+            plan.apply(self, &label)?;
+        } else {
+            // Nothing stored here yet, so it starts as a copy of what we have now
+            self.snapshots.slots[slot] = Some(current);
+        }
+
+        self.snapshots.active = Some(slot);
+        self.fire_profile_hook(&label);
+        Ok(())
+    }
+
+    fn fire_profile_hook(&self, profile: &str) {
+        let serial = &self.device_definition.device_info.serial;
+        let variables = [
+            ("BEACN_SERIAL", serial.as_str()),
+            ("BEACN_PROFILE", profile),
+        ];
+        hooks::fire(HookEvent::ProfileSwitched, &variables);
+    }
+
     pub fn load_settings(definition: DeviceDefinition, sender: Sender<AudioMessage>) -> Self {
         let device_type = definition.device_type;
+        let sync_sender = sender.clone();
 
         let mut state = BeacnAudioState {
             device_definition: definition,
@@ -295,15 +912,20 @@ impl BeacnAudioState {
 
         // Ok, grab all the variables from the mic
         let messages = Message::generate_fetch_message(device_type);
+        let mut fetched = vec![];
         for message in messages {
             // Skip this message if it's not valid for this version
             if message.get_message_minimum_version() > state.device_definition.device_info.version {
+                state.unsupported.push(message);
                 continue;
             }
+            fetched.push(message);
 
-            let value = state.handle_message(message);
+            let value = state.send(message);
             match value {
-                Ok(value) => state.set_local_value(value),
+                Ok(value) => {
+                    state.set_local_value(value);
+                }
                 Err(value) => {
                     // fetch_value didn't panic, but it did error
                     state.device_state.state = LoadState::Error;
@@ -318,17 +940,69 @@ impl BeacnAudioState {
         if state.device_definition.device_type == DeviceType::BeacnStudio {
             let _ = state.get_linked();
         }
-        state.device_state.state = LoadState::Running;
+
+        state.conflicts = ConflictMonitor::new(fetched);
+
+        // Loaded after the fetch, so limits don't interfere with reading the current values
+        state.load_from_file();
+
+        // The Studio's headphone knob can be kept in step with a Pipeweaver target
+        if device_type == DeviceType::BeacnStudio {
+            let settings = state.headphone_sync_settings();
+            state.headphone_sync = HeadphoneSync::spawn(sync_sender, device_type, settings);
+        }
+
+        // And the Mic's hardware mute with a Pipeweaver source
+        if device_type == DeviceType::BeacnMic {
+            let serial = state.device_definition.device_info.serial.clone();
+            let source = state.saved_settings.mute_sync_source;
+            state.mute_sync = MuteSync::spawn(serial, source);
+        }
+
+        // Anything which failed to fetch is left on the error page, to be resent or skipped
+        if state.device_state.errors.is_empty() {
+            state.device_state.state = LoadState::Running;
+        }
         state
     }
 
-    pub(crate) fn set_local_value(&mut self, value: Message) {
+    /// Sends a message which failed while loading again, finishing the load if it was the last
+    pub fn resend_failed(&mut self, index: usize) {
+        let Some(error) = self.device_state.errors.get(index) else {
+            return;
+        };
+        let Some(message) = error.failed_message else {
+            return;
+        };
+
+        match self.send(message) {
+            Ok(_) => {
+                self.device_state.errors.remove(index);
+                if self.device_state.errors.is_empty() {
+                    self.device_state.state = LoadState::Running;
+                }
+            }
+            Err(e) => {
+                warn!("Resending {message:?} failed: {e}");
+                self.device_state.errors[index].error_text = Some(format!("{e:?}"));
+            }
+        }
+    }
+
+    /// Carries on without the values which failed to load, leaving them at their defaults
+    pub fn ignore_failed(&mut self) {
+        self.device_state.errors.clear();
+        self.device_state.state = LoadState::Running;
+    }
+
+    /// Stores a value from the device locally, returning false if it's not one we keep track of
+    pub(crate) fn set_local_value(&mut self, value: Message) -> bool {
         match value {
             Message::BassEnhancement(b) => match b {
                 MicBaseEnhancement::Enabled(v) => self.bass_enhancement.enabled = v,
                 MicBaseEnhancement::Preset(v) => self.bass_enhancement.preset = v,
                 MicBaseEnhancement::Amount(v) => self.bass_enhancement.amount = v.to_inner() as i8,
-                _ => {}
+                _ => return false,
             },
             Message::Compressor(c) => match c {
                 MicCompressor::Mode(mode) => self.compressor.mode = mode,
@@ -348,12 +1022,12 @@ impl BeacnAudioState {
                     self.compressor.values[mode].makeup = value.to_inner()
                 }
                 MicCompressor::Enabled(mode, value) => self.compressor.values[mode].enabled = value,
-                _ => {}
+                _ => return false,
             },
             Message::DeEsser(d) => match d {
                 MicDeEsser::Amount(value) => self.de_esser.amount = value.to_inner() as u8,
                 MicDeEsser::Enabled(value) => self.de_esser.enabled = value,
-                _ => {}
+                _ => return false,
             },
             Message::Equaliser(e) => match e {
                 MicEqualiser::Mode(mode) => self.equaliser.mode = mode,
@@ -372,13 +1046,13 @@ impl BeacnAudioState {
                 MicEqualiser::Enabled(mode, band, value) => {
                     self.equaliser.bands[mode][band.into()].enabled = value
                 }
-                _ => {}
+                _ => return false,
             },
             Message::Exciter(e) => match e {
                 MicExciter::Amount(value) => self.exciter.amount = value.to_inner() as u8,
                 MicExciter::Frequency(value) => self.exciter.freq = value.to_inner() as u16,
                 MicExciter::Enabled(value) => self.exciter.enabled = value,
-                _ => {}
+                _ => return false,
             },
             Message::Expander(e) => match e {
                 MicExpander::Mode(mode) => self.expander.mode = mode,
@@ -395,7 +1069,7 @@ impl BeacnAudioState {
                 MicExpander::Release(mode, value) => {
                     self.expander.values[mode].release = value.to_inner() as u16
                 }
-                _ => {}
+                _ => return false,
             },
             Message::HeadphoneEQ(h) => match h {
                 MicHeadphoneEQ::Amount(eq_type, value) => {
@@ -404,7 +1078,7 @@ impl BeacnAudioState {
                 MicHeadphoneEQ::Enabled(eq_type, value) => {
                     self.headphone_eq.eq[eq_type].enabled = value
                 }
-                _ => {}
+                _ => return false,
             },
             Message::Headphones(h) => match h {
                 MicHeadphones::HeadphoneLevel(v) => self.headphones.level = v.to_inner(),
@@ -419,7 +1093,7 @@ impl BeacnAudioState {
                 MicHeadphones::MicClassCompliant(t) => {
                     self.headphones.mic_class_compliant = Some(t)
                 }
-                _ => {}
+                _ => return false,
             },
             Message::Lighting(l) => match l {
                 MicLighting::Mode(m) => self.lighting.mic_mode = m,
@@ -436,18 +1110,18 @@ impl BeacnAudioState {
                 MicLighting::SuspendBrightness(b) => {
                     self.lighting.suspend_brightness = b.to_inner()
                 }
-                _ => {}
+                _ => return false,
             },
             Message::MicSetup(m) => match m {
                 MicMicSetup::MicGain(g) => self.mic_setup.gain = g.to_inner() as u8,
                 MicMicSetup::StudioMicGain(g) => self.mic_setup.gain = g.to_inner() as u8,
                 MicMicSetup::StudioPhantomPower(p) => self.mic_setup.phantom = p,
-                _ => {}
+                _ => return false,
             },
             Message::Subwoofer(s) => match s {
                 MicSubwoofer::Enabled(e) => self.subwoofer.enabled = e,
                 MicSubwoofer::Amount(a) => self.subwoofer.amount = a.to_inner() as u8,
-                _ => {}
+                _ => return false,
             },
             Message::Suppressor(s) => match s {
                 MicSuppressor::Enabled(e) => self.suppressor.enabled = e,
@@ -458,9 +1132,10 @@ impl BeacnAudioState {
                     let percent = ((s.to_inner() + 120.0) / 60.0) * 100.0;
                     self.suppressor.sense = percent as u8
                 }
-                _ => {}
+                _ => return false,
             },
         }
+        true
     }
 }
 
@@ -546,3 +1221,68 @@ impl From<EqualiserBandType> for EQBandType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacn_lib::crossbeam::channel;
+    use std::thread;
+
+    // A state whose device echoes back whatever it's sent, returning the values it received
+    fn echo_state(max: f32) -> (BeacnAudioState, thread::JoinHandle<Vec<Message>>) {
+        let (tx, rx) = channel::unbounded();
+        let device = thread::spawn(move || {
+            let mut received = vec![];
+            for message in rx {
+                if let AudioMessage::Handle(message, reply) = message {
+                    received.push(message);
+                    let _ = reply.send(Ok(message));
+                }
+            }
+            received
+        });
+
+        let mut state = BeacnAudioState {
+            device_sender: Some(tx),
+            ..Default::default()
+        };
+        state.saved_settings.max_headphone_level = Some(max);
+        (state, device)
+    }
+
+    fn level(message: &Message) -> Option<f32> {
+        match message {
+            Message::Headphones(MicHeadphones::HeadphoneLevel(level)) => Some(level.to_inner()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn changes_are_clamped_to_the_max_level() {
+        let (mut state, device) = echo_state(-20.0);
+        let message = Message::Headphones(MicHeadphones::HeadphoneLevel(HPLevel(-10.0)));
+        state.handle_message(message).unwrap();
+
+        drop(state);
+        let received = device.join().unwrap();
+        assert_eq!(
+            received.iter().map(level).collect::<Vec<_>>(),
+            [Some(-20.0)]
+        );
+    }
+
+    #[test]
+    fn fetches_pass_through_unchanged() {
+        let (mut state, device) = echo_state(-20.0);
+        let message = Message::Headphones(MicHeadphones::HeadphoneLevel(HPLevel(-10.0)));
+        state.send(message).unwrap();
+        assert_eq!(state.headphones.level, -10.0);
+
+        drop(state);
+        let received = device.join().unwrap();
+        assert_eq!(
+            received.iter().map(level).collect::<Vec<_>>(),
+            [Some(-10.0)]
+        );
+    }
+}
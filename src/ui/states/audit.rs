@@ -0,0 +1,163 @@
+/*
+  A record of every setting which changed on a device during this session, with what it changed
+  from and to. Useful for tracking down 'who changed my gain' when hooks, scripts or the IPC
+  socket are also making changes.
+
+  Messages don't carry a name for the setting they hold, so we work it out from their serialised
+  form, where the value is always the last thing in the message. For example:
+    {"Compressor":{"Attack":["Simple",10.0]}}
+  is the 'Attack (Simple)' setting of the Compressor, with a value of 10.0. Every response from
+  the device is compared against the last value seen for that setting, and only actual changes
+  are recorded, so fetches which return what we already knew are ignored.
+*/
+
+use anyhow::Result;
+use beacn_lib::audio::messages::Message;
+use chrono::{DateTime, Local};
+use directories::UserDirs;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// More than enough for a session, without growing forever if something's looping
+const CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub time: DateTime<Local>,
+    pub subsystem: String,
+    pub setting: String,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct AuditTrail {
+    // The last value seen for each setting, keyed by its path
+    known: HashMap<Vec<String>, String>,
+    entries: VecDeque<AuditEntry>,
+}
+
+impl AuditTrail {
+    /// Notes a value returned by the device, recording it if it's different to the last one
+    pub fn observe(&mut self, message: &Message) {
+        let Ok(value) = serde_json::to_value(message) else {
+            return;
+        };
+        let (path, new) = split_value(value);
+        let Some(subsystem) = path.first().cloned() else {
+            return;
+        };
+
+        // The first value seen for a setting is what it was loaded as, not a change
+        let Some(old) = self.known.insert(path.clone(), new.clone()) else {
+            return;
+        };
+        if old == new {
+            return;
+        }
+
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(AuditEntry {
+            time: Local::now(),
+            subsystem,
+            setting: setting_name(&path[1..]),
+            old,
+            new,
+        });
+    }
+
+    /// Every recorded change, oldest first
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &AuditEntry> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the changes to a CSV file in the user's downloads, returning where it was saved
+    pub fn export_csv(&self, serial: &str) -> Result<PathBuf> {
+        let mut csv = String::from("time,subsystem,setting,old,new\n");
+        for entry in &self.entries {
+            let time = entry.time.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+            let fields = [
+                &time,
+                &entry.subsystem,
+                &entry.setting,
+                &entry.old,
+                &entry.new,
+            ];
+            let fields: Vec<_> = fields.iter().map(|f| csv_field(f)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+
+        let stamp = Local::now().format("%Y%m%d-%H%M%S");
+        let path = export_path(&format!("{serial}-changes-{stamp}.csv"));
+
+        fs::write(&path, csv)?;
+        Ok(path)
+    }
+}
+
+/// Where an exported file should go, the user's downloads if they have them
+pub(crate) fn export_path(file_name: &str) -> PathBuf {
+    let dirs = UserDirs::new();
+    let target = dirs
+        .as_ref()
+        .and_then(|d| d.download_dir().or(Some(d.home_dir())))
+        .map(Path::to_path_buf)
+        .unwrap_or_else(env::temp_dir);
+    target.join(file_name)
+}
+
+// Walks down the message to its value, returning the path taken and the value as text
+fn split_value(mut value: Value) -> (Vec<String>, String) {
+    let mut path = vec![];
+    loop {
+        match value {
+            Value::Object(map) if map.len() == 1 => {
+                let Some((key, inner)) = map.into_iter().next() else {
+                    return (path, String::new());
+                };
+                path.push(key);
+                value = inner;
+            }
+            Value::Array(mut values) => {
+                let last = values.pop().unwrap_or(Value::Null);
+                path.extend(values.into_iter().map(value_text));
+                return (path, value_text(last));
+            }
+            value => return (path, value_text(value)),
+        }
+    }
+}
+
+fn value_text(value: Value) -> String {
+    match value {
+        Value::String(text) => text,
+        value => value.to_string(),
+    }
+}
+
+// Turns ["Attack", "Simple"] into 'Attack (Simple)'
+fn setting_name(path: &[String]) -> String {
+    match path {
+        [] => String::new(),
+        [name] => name.clone(),
+        [name, qualifiers @ ..] => format!("{name} ({})", qualifiers.join(", ")),
+    }
+}
+
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,117 @@
+/*
+  Watches for settings changing on the device when we didn't ask them to.
+
+  Nothing else should be talking to the device while we're running, but it can happen, most
+  commonly when the device is passed through to a virtual machine running the official app, or
+  when another copy of the utility is running under a different user. When that happens the two
+  end up fighting, and changes made here get quietly undone.
+
+  To spot this, we slowly re-read the device's values in the background. A value changing when
+  we haven't sent anything since it was last read is unexpected, and if that keeps happening, we
+  flag a likely conflict so the user can be warned.
+*/
+
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::headphones::Headphones;
+use log::debug;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// How often a batch of values is re-read, and how many are in each batch
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const POLL_BATCH: usize = 6;
+
+// How many unexpected changes within the window before we consider it a conflict
+const CHANGE_THRESHOLD: usize = 3;
+const CHANGE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default, Clone)]
+pub struct ConflictMonitor {
+    messages: Vec<Message>,
+    known: Vec<Option<(Message, Instant)>>,
+    cursor: usize,
+
+    last_poll: Option<Instant>,
+    last_write: Option<Instant>,
+    changes: VecDeque<Instant>,
+
+    detected: bool,
+    dismissed: bool,
+}
+
+impl ConflictMonitor {
+    pub fn new(messages: Vec<Message>) -> Self {
+        // The headphone level is changed by the dial on the device itself, so isn't a sign of
+        // anything else being connected
+        let messages: Vec<Message> = messages
+            .into_iter()
+            .filter(|m| !matches!(m, Message::Headphones(Headphones::HeadphoneLevel(_))))
+            .collect();
+
+        Self {
+            known: vec![None; messages.len()],
+            messages,
+            ..Default::default()
+        }
+    }
+
+    /// Notes that we've sent something, so values read before now may legitimately change
+    pub fn record_write(&mut self) {
+        self.last_write = Some(Instant::now());
+    }
+
+    /// The next set of messages to re-read, if a poll is due
+    pub fn next_batch(&mut self) -> Vec<(usize, Message)> {
+        if self.messages.is_empty() || self.last_poll.is_some_and(|p| p.elapsed() < POLL_INTERVAL) {
+            return vec![];
+        }
+        self.last_poll = Some(Instant::now());
+
+        let count = POLL_BATCH.min(self.messages.len());
+        (0..count)
+            .map(|_| {
+                let slot = self.cursor;
+                self.cursor = (self.cursor + 1) % self.messages.len();
+                (slot, self.messages[slot])
+            })
+            .collect()
+    }
+
+    /// Records a value read back from the device, returning true if it changed unexpectedly
+    pub fn observe(&mut self, slot: usize, value: Message) -> bool {
+        let now = Instant::now();
+        let Some((previous, read_at)) = self.known[slot].replace((value, now)) else {
+            return false;
+        };
+
+        // If we've sent anything since this was last read, the change is probably ours
+        if previous == value || self.last_write.is_some_and(|write| write >= read_at) {
+            return false;
+        }
+
+        debug!("Unexpected value change on device: {previous:?} -> {value:?}");
+        self.changes.push_back(now);
+        while self
+            .changes
+            .front()
+            .is_some_and(|c| now - *c > CHANGE_WINDOW)
+        {
+            self.changes.pop_front();
+        }
+
+        if self.changes.len() >= CHANGE_THRESHOLD && !self.dismissed {
+            self.detected = true;
+        }
+        true
+    }
+
+    pub fn detected(&self) -> bool {
+        self.detected
+    }
+
+    /// Hides the warning for the rest of this session
+    pub fn dismiss(&mut self) {
+        self.detected = false;
+        self.dismissed = true;
+    }
+}
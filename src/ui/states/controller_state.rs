@@ -1,13 +1,25 @@
-use crate::APP_NAME;
-use crate::device_manager::{ControlMessage, DefinitionState, DeviceDefinition, ErrorType};
-use crate::ui::states::{DeviceState, ErrorMessage, LoadState};
+use crate::device_manager::{
+    ControlMessage, DefinitionState, DeviceDefinition, ErrorType, MonitoredInteraction,
+};
+use crate::integrations::burn_in::BurnInSettings;
+use crate::integrations::gestures::DialGestures;
+use crate::integrations::header_widgets::HeaderWidgets;
+use crate::integrations::keep_alive::KeepAliveSettings;
+use crate::integrations::orientation::Orientation;
+use crate::integrations::pipeweaver::layout::ScreenLayout;
+use crate::integrations::{
+    AssignableChannel, ButtonColours, ColourOverrides, DialAssignment, DisplayBackend,
+    StaticLighting, VolumeUnits,
+};
+use crate::ui::states::identify::{Identify, IdentifyStep};
+use crate::ui::states::{DeviceIdentity, DeviceState, ErrorMessage, LoadState};
+use crate::{settings, storage};
 use anyhow::Result;
 use beacn_lib::crossbeam::channel::Sender;
+use image::RgbaImage;
 use log::{debug, warn};
 use serde::{Deserialize, Serialize};
-use std::fs::File;
 use std::time::Duration;
-use xdg::BaseDirectories;
 
 // Literally nothing to do here right now
 #[derive(Debug, Default, Clone)]
@@ -17,6 +29,10 @@ pub struct BeacnControllerState {
     pub device_sender: Option<Sender<ControlMessage>>,
 
     pub saved_settings: SavedSettings,
+    pub identify: Identify,
+
+    // Changed by something being dragged, so saved once it's let go
+    save_pending: bool,
 }
 
 impl BeacnControllerState {
@@ -57,6 +73,27 @@ impl BeacnControllerState {
         let _ = state.set_display_brightness(state.saved_settings.display_brightness, false);
         let _ = state.set_button_brightness(state.saved_settings.button_brightness, false);
         let _ = state.set_display_dim(state.saved_settings.display_dim, false);
+        let _ = state.set_display_backend(state.saved_settings.display_backend, false);
+        let _ = state.set_dial_assignment(state.saved_settings.dial_assignment, false);
+        let _ = state.set_dial_gestures(state.saved_settings.dial_gestures, false);
+        let _ = state.set_burn_in(state.saved_settings.burn_in, false);
+        let _ = state.set_header_widgets(state.saved_settings.header_widgets, false);
+        let _ = state.set_keep_alive(state.saved_settings.keep_alive, false);
+        let _ = state.set_coalesce_updates(state.saved_settings.coalesce_updates, false);
+        let _ = state.set_performance_mode(state.saved_settings.performance_mode, false);
+        let address = state.saved_settings.pipeweaver_address.clone();
+        let _ = state.set_pipeweaver_address(address, false);
+        let _ = state.set_orientation(state.saved_settings.orientation, false);
+        let colours = state.saved_settings.colour_overrides.clone();
+        let _ = state.set_colour_overrides(colours, false);
+        let units = state.saved_settings.volume_units.clone();
+        let _ = state.set_volume_units(units, false);
+        let _ = state.set_screen_layout(state.saved_settings.screen_layout, false);
+        let buttons = state.saved_settings.button_colours.clone();
+        let _ = state.set_button_colours(buttons, false);
+        let lighting = state.saved_settings.static_lighting.clone();
+        let _ = state.set_static_lighting(lighting, false);
+        let _ = state.set_screen_enabled(state.saved_settings.screen_enabled, false);
 
         state
     }
@@ -86,6 +123,47 @@ impl BeacnControllerState {
         Ok(())
     }
 
+    /// Moves any identify flashing along, the saved brightness is left alone so it can be put
+    /// back afterwards
+    pub fn poll_identify(&mut self) {
+        if let Some(step) = self.identify.poll() {
+            self.flash(step);
+        }
+    }
+
+    /// Stops any identify flashing, putting the brightness back
+    pub fn stop_identify(&mut self) {
+        if self.identify.stop() {
+            self.flash(IdentifyStep::Restore);
+        }
+    }
+
+    fn flash(&self, step: IdentifyStep) {
+        let (display, buttons) = match step {
+            IdentifyStep::On => (100, 100),
+            IdentifyStep::Off => (0, 0),
+            IdentifyStep::Restore => (
+                self.saved_settings.display_brightness,
+                self.saved_settings.button_brightness,
+            ),
+        };
+        if let Err(e) = self.send_brightness(display, buttons) {
+            debug!("Unable to flash device: {e}");
+        }
+    }
+
+    // Changes the brightness without touching the saved values
+    fn send_brightness(&self, display: u8, buttons: u8) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.send_control(ControlMessage::DisplayBrightness(display, tx))?;
+        rx.recv()??;
+
+        let (tx, rx) = oneshot::channel();
+        self.send_control(ControlMessage::ButtonBrightness(buttons, tx))?;
+        rx.recv()??;
+        Ok(())
+    }
+
     pub fn set_display_dim(&mut self, timeout: Duration, save: bool) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.saved_settings.display_dim = timeout;
@@ -98,7 +176,233 @@ impl BeacnControllerState {
         Ok(())
     }
 
-    fn send_control(&self, message: ControlMessage) -> Result<()> {
+    pub fn set_display_backend(&mut self, backend: DisplayBackend, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.display_backend = backend;
+        let message = ControlMessage::SetBackend(backend, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_dial_assignment(&mut self, assignment: DialAssignment, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.dial_assignment = assignment;
+        let message = ControlMessage::SetDialAssignment(assignment, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_dial_gestures(&mut self, gestures: DialGestures, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.dial_gestures = gestures;
+        let message = ControlMessage::SetDialGestures(gestures, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_burn_in(&mut self, settings: BurnInSettings, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.burn_in = settings;
+        let message = ControlMessage::SetBurnIn(settings, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_header_widgets(&mut self, widgets: HeaderWidgets, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.header_widgets = widgets;
+        let message = ControlMessage::SetHeaderWidgets(widgets, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_keep_alive(&mut self, settings: KeepAliveSettings, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.keep_alive = settings;
+        let message = ControlMessage::SetKeepAlive(settings, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_coalesce_updates(&mut self, enabled: bool, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.coalesce_updates = enabled;
+        let message = ControlMessage::CoalesceUpdates(enabled, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_performance_mode(&mut self, enabled: bool, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.performance_mode = enabled;
+        let message = ControlMessage::PerformanceMode(enabled, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_pipeweaver_address(&mut self, address: Option<String>, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.pipeweaver_address = address.clone();
+        let message = ControlMessage::PipeweaverAddress(address, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.orientation = orientation;
+        let message = ControlMessage::SetOrientation(orientation, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_colour_overrides(&mut self, colours: ColourOverrides, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.colour_overrides = colours.clone();
+        let message = ControlMessage::SetColourOverrides(colours, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_volume_units(&mut self, units: VolumeUnits, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.volume_units = units.clone();
+        let message = ControlMessage::SetVolumeUnits(units, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_screen_layout(&mut self, layout: ScreenLayout, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.screen_layout = layout;
+        let message = ControlMessage::SetScreenLayout(layout, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_button_colours(&mut self, colours: ButtonColours, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.button_colours = colours.clone();
+        let message = ControlMessage::SetButtonColours(colours, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_static_lighting(&mut self, lighting: StaticLighting, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.static_lighting = lighting.clone();
+        let message = ControlMessage::SetStaticLighting(lighting, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn set_identity(&mut self, identity: DeviceIdentity) {
+        self.saved_settings.identity = identity;
+        self.save_to_file();
+    }
+
+    pub fn set_screen_enabled(&mut self, enabled: bool, save: bool) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.saved_settings.screen_enabled = enabled;
+        let message = ControlMessage::SetScreenEnabled(enabled, tx);
+        self.send_control(message)?;
+        rx.recv()??;
+        if save {
+            self.save_to_file();
+        }
+        Ok(())
+    }
+
+    pub fn get_assignable_channels(&self) -> Result<Vec<AssignableChannel>> {
+        let (tx, rx) = oneshot::channel();
+        self.send_control(ControlMessage::GetAssignableChannels(tx))?;
+        Ok(rx.recv()?)
+    }
+
+    /// Fetches what's currently on the device's screen, if it's changed since `generation`
+    pub fn get_preview(&self, generation: u64) -> Result<Option<(u64, RgbaImage)>> {
+        let (tx, rx) = oneshot::channel();
+        self.send_control(ControlMessage::GetPreview(generation, tx))?;
+        Ok(rx.recv()?)
+    }
+
+    /// Starts copying the device's button and dial events to `monitor`, or stops with `None`
+    pub fn monitor_interactions(
+        &self,
+        monitor: Option<Sender<MonitoredInteraction>>,
+    ) -> Result<()> {
+        self.send_control(ControlMessage::MonitorInteractions(monitor))
+    }
+
+    /// Draws a JPEG straight onto the screen, the display integration may later draw over it
+    pub fn send_image(&self, jpeg: Vec<u8>, x: u32, y: u32) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.send_control(ControlMessage::SendImage(jpeg, x, y, tx))?;
+        rx.recv()??;
+        Ok(())
+    }
+
+    pub(crate) fn send_control(&self, message: ControlMessage) -> Result<()> {
         if let Some(tx) = &self.device_sender {
             tx.send(message)?;
         }
@@ -107,19 +411,15 @@ impl BeacnControllerState {
 
     pub fn load_from_file(&mut self) {
         let file_name = format!("{}.json", self.device_definition.device_info.serial);
-        let xdg_dirs = BaseDirectories::with_prefix(APP_NAME);
-        let config_file = xdg_dirs.find_config_file(file_name);
+        let config_file = storage::config_path(&file_name);
 
         debug!("Attempting to load Config from {config_file:?}");
-        #[allow(clippy::collapsible_if)]
-        if let Some(file) = config_file {
-            if let Ok(file) = File::open(file) {
-                if let Ok(config) = serde_json::from_reader(file) {
-                    debug!("Load Successful");
-                    self.saved_settings = config;
-                    return;
-                }
-            }
+        if let Ok(file) = config_file
+            && let Ok(config) = storage::load_json(file)
+        {
+            debug!("Load Successful");
+            self.saved_settings = config;
+            return;
         }
 
         debug!("Config Load Failed, Setting Defaults");
@@ -128,18 +428,28 @@ impl BeacnControllerState {
         self.save_to_file();
     }
 
+    /// Saves once nothing is being dragged, so a slider is saved where it's let go rather than
+    /// on every step along the way
+    pub fn save_when_released(&mut self) {
+        self.save_pending = true;
+    }
+
+    /// Writes out a save held back by `save_when_released`, called every frame
+    pub fn poll_save(&mut self, dragging: bool) {
+        if self.save_pending && !dragging {
+            self.save_pending = false;
+            self.save_to_file();
+        }
+    }
+
     pub fn save_to_file(&self) {
         let file_name = format!("{}.json", self.device_definition.device_info.serial);
-        let xdg_dirs = BaseDirectories::with_prefix(APP_NAME);
-        let config_file = xdg_dirs.place_config_file(file_name);
-
-        #[allow(clippy::collapsible_if)]
-        if let Ok(file) = config_file {
-            if let Ok(file) = File::create(file) {
-                if let Err(e) = serde_json::to_writer_pretty(file, &self.saved_settings) {
-                    warn!("Config Saving Failed: {e}");
-                }
-            }
+        let config_file = storage::config_path(&file_name);
+
+        if let Ok(file) = config_file
+            && let Err(e) = storage::save_json(file, &self.saved_settings)
+        {
+            warn!("Config Saving Failed: {e}");
         }
     }
 }
@@ -154,6 +464,57 @@ pub struct SavedSettings {
 
     #[serde(deserialize_with = "validate_button_brightness")]
     pub button_brightness: u8,
+
+    #[serde(default)]
+    pub display_backend: DisplayBackend,
+
+    #[serde(default)]
+    pub dial_assignment: DialAssignment,
+
+    #[serde(default)]
+    pub dial_gestures: DialGestures,
+
+    #[serde(default)]
+    pub burn_in: BurnInSettings,
+
+    #[serde(default)]
+    pub header_widgets: HeaderWidgets,
+
+    #[serde(default)]
+    pub keep_alive: KeepAliveSettings,
+
+    #[serde(default)]
+    pub coalesce_updates: bool,
+
+    #[serde(default)]
+    pub performance_mode: bool,
+
+    #[serde(default)]
+    pub pipeweaver_address: Option<String>,
+
+    #[serde(default)]
+    pub orientation: Orientation,
+
+    #[serde(default)]
+    pub colour_overrides: ColourOverrides,
+
+    #[serde(default)]
+    pub volume_units: VolumeUnits,
+
+    #[serde(default)]
+    pub screen_layout: ScreenLayout,
+
+    #[serde(default)]
+    pub button_colours: ButtonColours,
+
+    #[serde(default)]
+    pub static_lighting: StaticLighting,
+
+    #[serde(default = "default_screen_enabled")]
+    pub screen_enabled: bool,
+
+    #[serde(default)]
+    pub identity: DeviceIdentity,
 }
 
 impl Default for SavedSettings {
@@ -162,10 +523,31 @@ impl Default for SavedSettings {
             display_brightness: 40,
             display_dim: Duration::from_secs(60 * 3),
             button_brightness: 5,
+            display_backend: settings::get().default_backend,
+            dial_assignment: None,
+            dial_gestures: DialGestures::default(),
+            burn_in: BurnInSettings::default(),
+            header_widgets: HeaderWidgets::default(),
+            keep_alive: KeepAliveSettings::default(),
+            coalesce_updates: false,
+            performance_mode: false,
+            pipeweaver_address: None,
+            orientation: Orientation::default(),
+            colour_overrides: ColourOverrides::new(),
+            volume_units: VolumeUnits::new(),
+            screen_layout: ScreenLayout::default(),
+            button_colours: ButtonColours::new(),
+            static_lighting: StaticLighting::new(),
+            screen_enabled: true,
+            identity: DeviceIdentity::default(),
         }
     }
 }
 
+fn default_screen_enabled() -> bool {
+    true
+}
+
 // This should never be a problem, but we'll validate the input fully.
 fn validate_screen_percent<'de, D>(deserializer: D) -> Result<u8, D::Error>
 where
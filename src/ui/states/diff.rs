@@ -0,0 +1,139 @@
+/*
+  Works out which messages actually need sending when a profile or snapshot is applied, rather
+  than sending every value it holds.
+
+  The local state already mirrors what the device is doing, so each of the target values is
+  applied to a scratch copy of it, and only kept if it changes something there. Messages which
+  aren't tracked locally can't be compared, so they're always kept.
+
+  The device reads some values in the context of others (the compressor values depend on which
+  mode is active, and the bass enhancement amount on the preset), so modes and presets are sent
+  before anything else, with the rest keeping the order they were given in.
+*/
+
+use crate::ui::states::audio_state::BeacnAudioState;
+use anyhow::Result;
+use beacn_lib::audio::messages::Message;
+use beacn_lib::audio::messages::bass_enhancement::BassEnhancement;
+use beacn_lib::audio::messages::compressor::Compressor;
+use beacn_lib::audio::messages::equaliser::Equaliser;
+use beacn_lib::audio::messages::expander::Expander;
+use beacn_lib::audio::messages::lighting::Lighting;
+use log::debug;
+
+/// How far through applying a plan we got, kept on the state so the UI can show it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ApplyProgress {
+    pub name: String,
+    pub sent: usize,
+    pub total: usize,
+    pub unchanged: usize,
+}
+
+impl ApplyProgress {
+    pub fn is_complete(&self) -> bool {
+        self.sent == self.total
+    }
+}
+
+/// The messages needed to take the device from its current state to a target one
+#[derive(Debug, Default, Clone)]
+pub struct ApplyPlan {
+    messages: Vec<Message>,
+    unchanged: usize,
+}
+
+impl ApplyPlan {
+    pub fn new(state: &BeacnAudioState, target: &[Message]) -> Self {
+        let mut ordered = target.to_vec();
+        ordered.sort_by_key(|message| !is_selector(message));
+
+        let mut scratch = values_of(state);
+        let mut plan = Self::default();
+        for message in ordered {
+            let mut next = scratch.clone();
+            let tracked = next.set_local_value(message);
+
+            if !tracked || differs(&scratch, &next, &message) {
+                plan.messages.push(message);
+                scratch = next;
+            } else {
+                plan.unchanged += 1;
+            }
+        }
+        plan
+    }
+
+    /// Sends the plan to the device, recording how far it got in `state.last_apply` after each
+    /// message. Stops at the first failure, leaving anything already sent in place.
+    pub fn apply(self, state: &mut BeacnAudioState, name: &str) -> Result<()> {
+        let total = self.messages.len();
+        debug!(
+            "Applying {name}: {total} changes, {} already set",
+            self.unchanged
+        );
+
+        state.last_apply = Some(ApplyProgress {
+            name: name.to_string(),
+            sent: 0,
+            total,
+            unchanged: self.unchanged,
+        });
+        for (index, message) in self.messages.into_iter().enumerate() {
+            state.handle_message(message)?;
+            if let Some(progress) = &mut state.last_apply {
+                progress.sent = index + 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Messages which change how other values in the same section are read
+fn is_selector(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::BassEnhancement(BassEnhancement::Preset(_))
+            | Message::Compressor(Compressor::Mode(_))
+            | Message::Equaliser(Equaliser::Mode(_))
+            | Message::Expander(Expander::Mode(_))
+            | Message::Lighting(Lighting::Mode(_))
+            | Message::Lighting(Lighting::StudioMode(_))
+    )
+}
+
+// A copy of just the device's values, without anything tied to the device itself
+fn values_of(state: &BeacnAudioState) -> BeacnAudioState {
+    let mut values = BeacnAudioState::default();
+    values.headphones = state.headphones;
+    values.lighting = state.lighting;
+    values.equaliser = state.equaliser;
+    values.headphone_eq = state.headphone_eq;
+    values.bass_enhancement = state.bass_enhancement;
+    values.compressor = state.compressor;
+    values.de_esser = state.de_esser;
+    values.exciter = state.exciter;
+    values.expander = state.expander;
+    values.suppressor = state.suppressor;
+    values.mic_setup = state.mic_setup;
+    values.subwoofer = state.subwoofer;
+    values
+}
+
+// Whether the section of the state the message belongs to has changed
+fn differs(before: &BeacnAudioState, after: &BeacnAudioState, message: &Message) -> bool {
+    match message {
+        Message::BassEnhancement(_) => before.bass_enhancement != after.bass_enhancement,
+        Message::Compressor(_) => before.compressor != after.compressor,
+        Message::DeEsser(_) => before.de_esser != after.de_esser,
+        Message::Equaliser(_) => before.equaliser != after.equaliser,
+        Message::Exciter(_) => before.exciter != after.exciter,
+        Message::Expander(_) => before.expander != after.expander,
+        Message::HeadphoneEQ(_) => before.headphone_eq != after.headphone_eq,
+        Message::Headphones(_) => before.headphones != after.headphones,
+        Message::Lighting(_) => before.lighting != after.lighting,
+        Message::MicSetup(_) => before.mic_setup != after.mic_setup,
+        Message::Subwoofer(_) => before.subwoofer != after.subwoofer,
+        Message::Suppressor(_) => before.suppressor != after.suppressor,
+    }
+}
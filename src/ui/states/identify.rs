@@ -0,0 +1,59 @@
+// Flashes a device's lights on and off for a moment, so it's obvious which physical device a
+// sidebar entry belongs to when several of the same type are connected.
+
+use std::time::{Duration, Instant};
+
+/// How long the lights stay on (or off) for each flash
+pub(crate) const FLASH_INTERVAL: Duration = Duration::from_millis(250);
+
+// The number of on / off changes, ending with the lights back on
+const FLASH_STEPS: u32 = 8;
+
+#[derive(Debug, Default, Clone)]
+pub struct Identify {
+    started: Option<Instant>,
+    step: u32,
+}
+
+impl Identify {
+    pub fn start(&mut self) {
+        self.started = Some(Instant::now());
+        self.step = 0;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Stops flashing early, returning whether the brightness needs putting back
+    pub fn stop(&mut self) -> bool {
+        self.started.take().is_some()
+    }
+
+    /// What the lights should change to, if it's time for them to change. Once the flashing
+    /// has finished, the original brightness should be put back.
+    pub fn poll(&mut self) -> Option<IdentifyStep> {
+        let started = self.started?;
+        let due = (started.elapsed().as_millis() / FLASH_INTERVAL.as_millis()) as u32;
+        if self.step > 0 && due < self.step {
+            return None;
+        }
+
+        self.step += 1;
+        if self.step > FLASH_STEPS {
+            self.started = None;
+            return Some(IdentifyStep::Restore);
+        }
+        match self.step % 2 {
+            1 => Some(IdentifyStep::Off),
+            _ => Some(IdentifyStep::On),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IdentifyStep {
+    On,
+    Off,
+    Restore,
+}
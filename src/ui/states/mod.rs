@@ -1,7 +1,12 @@
 use beacn_lib::audio::messages::Message;
+use serde::{Deserialize, Serialize};
 
 pub(crate) mod audio_state;
+pub(crate) mod audit;
+pub(crate) mod conflict;
 pub(crate) mod controller_state;
+pub(crate) mod diff;
+pub(crate) mod identify;
 
 #[derive(Debug, Default, Clone)]
 pub struct DeviceState {
@@ -9,6 +14,14 @@ pub struct DeviceState {
     pub errors: Vec<ErrorMessage>,
 }
 
+/// A name and colour the user has given a device, to tell apart several of the same type
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct DeviceIdentity {
+    pub nickname: Option<String>,
+    pub accent: Option<[u8; 3]>,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct ErrorMessage {
     pub error_text: Option<String>,
@@ -0,0 +1,302 @@
+/*
+  The colours used across the UI, so pages don't each pick their own and can follow the dark /
+  light mode and accent colour chosen in the settings.
+
+  By default the UI follows the desktop's colour scheme preference, which is read from the
+  Settings portal (and watched for changes), falling back to what the windowing system reports,
+  then to dark. Without a custom accent, egui's own selection colour is used for widgets.
+
+  The accessibility settings are applied here too. High contrast swaps the backgrounds for pure
+  black or white with outlines around every widget, and large targets scales up the sizes egui
+  works out buttons, sliders and checkboxes from, along with how close the pointer has to be.
+
+  Refs:
+  https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html
+*/
+
+use crate::{ToMainMessages, settings};
+use anyhow::Result;
+use ashpd::desktop::settings::{ColorScheme, Settings};
+use beacn_lib::crossbeam;
+use egui::{Color32, Context, Id, Stroke, Style, Visuals};
+use futures_util::StreamExt;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::sync::{LazyLock, RwLock};
+use strum_macros::EnumIter;
+
+const THEME_KEY: &str = "app_theme";
+
+// How much bigger widgets are drawn with large targets turned on
+const LARGE_TARGET_SCALE: f32 = 1.5;
+
+// The desktop's preference, None if it doesn't have one (or we couldn't ask)
+static SYSTEM_DARK: LazyLock<RwLock<Option<bool>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+pub enum ThemeMode {
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub mode: ThemeMode,
+
+    /// Used for selected nav buttons, sliders and EQ curves, egui's default when not set
+    pub accent: Option<[u8; 3]>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Exposes the UI to screen readers through AccessKit, picked up when the window next opens
+    pub screen_reader: bool,
+
+    /// Draws buttons, sliders and checkboxes larger, so they're easier to hit
+    pub large_targets: bool,
+
+    /// Black or white backgrounds, with solid outlines around every widget
+    pub high_contrast: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Theme {
+    pub dark: bool,
+    pub accent: Option<Color32>,
+
+    pub success: Color32,
+    pub failure: Color32,
+
+    /// Draws attention to something, such as a fitted curve or a changed value
+    pub highlight: Color32,
+
+    pub nav_active: Color32,
+    pub nav_inactive: Color32,
+
+    pub graph_background: Color32,
+    pub graph_grid: Color32,
+    pub graph_text: Color32,
+
+    /// The combined response on the EQ graphs
+    pub curve: Color32,
+
+    high_contrast: bool,
+    large_targets: bool,
+}
+
+impl Theme {
+    fn new(dark: bool, accent: Option<Color32>, accessibility: &AccessibilitySettings) -> Self {
+        let high_contrast = accessibility.high_contrast;
+        let large_targets = accessibility.large_targets;
+
+        let palette = match dark {
+            true => Self {
+                dark,
+                accent,
+                high_contrast,
+                large_targets,
+                success: Color32::from_rgb(46, 204, 113),
+                failure: Color32::from_rgb(231, 76, 60),
+                highlight: Color32::from_rgb(79, 215, 255),
+                nav_active: Color32::WHITE,
+                nav_inactive: Color32::from_rgb(120, 120, 120),
+                graph_background: Color32::from_rgb(34, 34, 34),
+                graph_grid: Color32::from_rgb(102, 102, 102),
+                graph_text: Color32::from_rgb(170, 170, 170),
+                curve: Color32::WHITE,
+            },
+            false => Self {
+                dark,
+                accent,
+                high_contrast,
+                large_targets,
+                success: Color32::from_rgb(30, 150, 80),
+                failure: Color32::from_rgb(200, 50, 40),
+                highlight: Color32::from_rgb(0, 130, 180),
+                nav_active: Color32::from_rgb(30, 30, 30),
+                nav_inactive: Color32::from_rgb(140, 140, 140),
+                graph_background: Color32::from_rgb(245, 245, 245),
+                graph_grid: Color32::from_rgb(200, 200, 200),
+                graph_text: Color32::from_rgb(90, 90, 90),
+                curve: Color32::from_rgb(30, 30, 30),
+            },
+        };
+
+        let palette = match (high_contrast, dark) {
+            (true, true) => Self {
+                nav_inactive: Color32::from_gray(200),
+                graph_background: Color32::BLACK,
+                graph_grid: Color32::from_gray(170),
+                graph_text: Color32::WHITE,
+                ..palette
+            },
+            (true, false) => Self {
+                nav_inactive: Color32::from_gray(60),
+                graph_background: Color32::WHITE,
+                graph_grid: Color32::from_gray(90),
+                graph_text: Color32::BLACK,
+                ..palette
+            },
+            (false, _) => palette,
+        };
+
+        match accent {
+            Some(accent) => Self {
+                highlight: accent,
+                curve: accent,
+                ..palette
+            },
+            None => palette,
+        }
+    }
+
+    fn visuals(&self) -> Visuals {
+        let mut visuals = match self.dark {
+            true => Visuals::dark(),
+            false => Visuals::light(),
+        };
+
+        if let Some(accent) = self.accent {
+            visuals.selection.bg_fill = accent;
+            visuals.selection.stroke.color = contrasting(accent);
+            visuals.hyperlink_color = accent;
+        }
+        if self.high_contrast {
+            high_contrast(&mut visuals);
+        }
+        visuals
+    }
+
+    fn apply_targets(&self, style: &mut Style) {
+        let defaults = Style::default();
+        style.spacing = defaults.spacing;
+        style.interaction.interact_radius = defaults.interaction.interact_radius;
+        if !self.large_targets {
+            return;
+        }
+
+        let spacing = &mut style.spacing;
+        spacing.interact_size *= LARGE_TARGET_SCALE;
+        spacing.button_padding *= LARGE_TARGET_SCALE;
+        spacing.icon_width *= LARGE_TARGET_SCALE;
+        spacing.icon_width_inner *= LARGE_TARGET_SCALE;
+        style.interaction.interact_radius *= LARGE_TARGET_SCALE;
+    }
+}
+
+/// The theme in use for this frame
+pub fn current(ctx: &Context) -> Theme {
+    let theme = ctx.data(|data| data.get_temp::<Theme>(Id::new(THEME_KEY)));
+    let dark = ctx.theme() == egui::Theme::Dark;
+    theme.unwrap_or_else(|| Theme::new(dark, None, &AccessibilitySettings::default()))
+}
+
+/// Works out the theme from the settings and desktop, and updates egui if it's changed. Called
+/// at the start of every frame.
+pub fn apply(ctx: &Context) {
+    let app_settings = settings::get();
+    let settings = app_settings.theme;
+    let dark = match settings.mode {
+        ThemeMode::Dark => true,
+        ThemeMode::Light => false,
+        ThemeMode::System => system_dark()
+            .or(ctx.system_theme().map(|theme| theme == egui::Theme::Dark))
+            .unwrap_or(true),
+    };
+    let accent = settings.accent.map(|[r, g, b]| Color32::from_rgb(r, g, b));
+
+    let theme = Theme::new(dark, accent, &app_settings.accessibility);
+    let id = Id::new(THEME_KEY);
+    if ctx.data(|data| data.get_temp::<Theme>(id)) == Some(theme) {
+        return;
+    }
+
+    // Pinning egui to one side stops it switching on its own when the window system's preference changes
+    let accessibility = &app_settings.accessibility;
+    debug!("Applying Theme (dark: {dark}, accent: {accent:?}, {accessibility:?})");
+    let side = match dark {
+        true => egui::Theme::Dark,
+        false => egui::Theme::Light,
+    };
+    ctx.set_theme(side);
+    ctx.set_visuals_of(side, theme.visuals());
+    ctx.all_styles_mut(|style| theme.apply_targets(style));
+    ctx.data_mut(|data| data.insert_temp(id, theme));
+    ctx.request_repaint();
+}
+
+fn system_dark() -> Option<bool> {
+    SYSTEM_DARK.read().ok().and_then(|dark| *dark)
+}
+
+fn set_system_scheme(scheme: ColorScheme) {
+    let dark = match scheme {
+        ColorScheme::PreferDark => Some(true),
+        ColorScheme::PreferLight => Some(false),
+        ColorScheme::NoPreference => None,
+    };
+    if let Ok(mut current) = SYSTEM_DARK.write() {
+        *current = dark;
+    }
+}
+
+/// Follows the desktop's colour scheme preference, asking the UI to redraw when it changes
+pub async fn watch_system_scheme(tx: crossbeam::channel::Sender<ToMainMessages>) {
+    if let Err(e) = watch_portal(&tx).await {
+        debug!("Colour scheme preference unavailable: {e}");
+    }
+}
+
+async fn watch_portal(tx: &crossbeam::channel::Sender<ToMainMessages>) -> Result<()> {
+    let portal = Settings::new().await?;
+    set_system_scheme(portal.color_scheme().await?);
+    let _ = tx.send(ToMainMessages::RequestRedraw);
+
+    let mut changes = portal.receive_color_scheme_changed().await?;
+    while let Some(scheme) = changes.next().await {
+        debug!("Desktop colour scheme changed: {scheme:?}");
+        set_system_scheme(scheme);
+        let _ = tx.send(ToMainMessages::RequestRedraw);
+    }
+    Ok(())
+}
+
+// Pure black or white behind everything, with text and outlines in the opposite
+fn high_contrast(visuals: &mut Visuals) {
+    let (background, foreground) = match visuals.dark_mode {
+        true => (Color32::BLACK, Color32::WHITE),
+        false => (Color32::WHITE, Color32::BLACK),
+    };
+    visuals.override_text_color = Some(foreground);
+    visuals.panel_fill = background;
+    visuals.window_fill = background;
+    visuals.extreme_bg_color = background;
+    visuals.faint_bg_color = background;
+    visuals.window_stroke = Stroke::new(1.0, foreground);
+
+    let widgets = &mut visuals.widgets;
+    for widget in [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ] {
+        widget.bg_stroke = Stroke::new(widget.bg_stroke.width.max(1.0), foreground);
+        widget.fg_stroke.color = foreground;
+    }
+}
+
+// Black or white, whichever is easier to read on top of this colour
+fn contrasting(colour: Color32) -> Color32 {
+    let [r, g, b, _] = colour.to_array();
+    let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    match luminance > 150.0 {
+        true => Color32::BLACK,
+        false => Color32::WHITE,
+    }
+}
@@ -1,56 +1,84 @@
+use crate::i18n::tr;
 use crate::ui::SVG;
 use crate::ui::numbers::NumericType;
+use crate::ui::states::diff::ApplyProgress;
+use crate::ui::theme;
 use egui::emath::Numeric;
 use egui::{
-    Align, Button, Color32, CornerRadius, DragValue, Image, Layout, Response, RichText, Slider, Ui,
-    Visuals, vec2,
+    Align, Button, CornerRadius, DragValue, Image, Layout, Response, RichText, Slider, StrokeKind,
+    Ui, Visuals, WidgetInfo, WidgetType, vec2,
 };
 
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
 
-pub fn round_nav_button(ui: &mut Ui, img: &str, active: bool) -> Response {
-    let tint_colour = if active {
-        Color32::WHITE
-    } else {
-        Color32::from_rgb(120, 120, 120)
+pub fn round_nav_button(ui: &mut Ui, img: &str, label: &str, active: bool) -> Response {
+    let theme = theme::current(ui.ctx());
+    let tint_colour = match active {
+        true => theme.nav_active,
+        false => theme.nav_inactive,
     };
 
     // We might need to do caching here..
     let image = SVG.get(img).unwrap().clone();
 
-    ui.scope(|ui| {
-        ui.style_mut().spacing.button_padding = vec2(0.0, 0.0);
-        ui.add_sized(
-            [40.0, 40.0],
-            Button::image(
-                Image::new(image)
-                    .tint(tint_colour)
-                    .fit_to_exact_size(vec2(20., 20.)),
+    let response = ui
+        .scope(|ui| {
+            ui.style_mut().spacing.button_padding = vec2(0.0, 0.0);
+            ui.add_sized(
+                [40.0, 40.0],
+                Button::image(
+                    Image::new(image)
+                        .tint(tint_colour)
+                        .fit_to_exact_size(vec2(20., 20.)),
+                )
+                .corner_radius(CornerRadius::same(5))
+                .selected(active),
             )
-            .corner_radius(CornerRadius::same(5))
-            .selected(active),
-        )
-    })
-    .inner
+        })
+        .inner;
+    focus_outline(ui, &response);
+    nav_info(ui, &response, label, active);
+    response
 }
 
 // So the pipeweaver button is the same as a basic button, but because it's already coloured
 // we don't need to add a tint to it, and also because of it's size we need far less padding
-pub fn pipeweaver_button(ui: &mut Ui, img: &str, active: bool) -> Response {
+pub fn pipeweaver_button(ui: &mut Ui, img: &str, label: &str, active: bool) -> Response {
     // We might need to do caching here..
     let image = SVG.get(img).unwrap().clone();
 
-    ui.scope(|ui| {
-        ui.style_mut().spacing.button_padding = vec2(0.0, 0.0);
-        ui.add_sized(
-            [40.0, 40.0],
-            Button::image(Image::new(image).fit_to_exact_size(vec2(35., 35.)))
-                .corner_radius(CornerRadius::same(5))
-                .selected(active),
-        )
-    })
-    .inner
+    let response = ui
+        .scope(|ui| {
+            ui.style_mut().spacing.button_padding = vec2(0.0, 0.0);
+            ui.add_sized(
+                [40.0, 40.0],
+                Button::image(Image::new(image).fit_to_exact_size(vec2(35., 35.)))
+                    .corner_radius(CornerRadius::same(5))
+                    .selected(active),
+            )
+        })
+        .inner;
+    focus_outline(ui, &response);
+    nav_info(ui, &response, label, active);
+    response
+}
+
+// The nav buttons are image only, so screen readers need telling what they're for
+fn nav_info(ui: &Ui, response: &Response, label: &str, active: bool) {
+    let enabled = ui.is_enabled();
+    response.widget_info(|| WidgetInfo::selected(WidgetType::Button, enabled, active, label));
+}
+
+/// Draws an outline around a widget while it has keyboard focus, so it can be found when moving
+/// around with Tab. The nav buttons are image only, and egui's own focus styling is easy to miss.
+pub fn focus_outline(ui: &Ui, response: &Response) {
+    if response.has_focus() {
+        let stroke = ui.visuals().selection.stroke;
+        let rect = response.rect.expand(2.0);
+        ui.painter()
+            .rect_stroke(rect, CornerRadius::same(6), stroke, StrokeKind::Outside);
+    }
 }
 
 pub fn draw_range<T>(
@@ -70,8 +98,8 @@ where
     let mut changed = false;
     ui.add_sized([80.0, ui.available_height()], |ui: &mut egui::Ui| {
         ui.vertical_centered(|ui| {
-            // Title above the field
-            ui.label(title);
+            // Title above the field, which also names the slider for screen readers
+            let title = ui.label(title).id;
             ui.add_space(5.0);
 
             let slider_response = ui
@@ -90,7 +118,9 @@ where
 
                     ui.add_sized([20.0, ui.available_height()], slider)
                 })
-                .inner;
+                .inner
+                .labelled_by(title);
+            focus_outline(ui, &slider_response);
             if slider_response.changed() {
                 changed = true;
             }
@@ -107,7 +137,10 @@ where
                 drag = drag.fixed_decimals(1);
             }
 
-            let drag_response = ui.add_sized([ui.available_width(), 0.0], drag);
+            let drag_response = ui
+                .add_sized([ui.available_width(), 0.0], drag)
+                .labelled_by(title);
+            focus_outline(ui, &drag_response);
             if drag_response.changed() {
                 changed = true;
             }
@@ -181,9 +214,10 @@ where
     T: Numeric + NumericType,
 {
     ui.horizontal_centered(|ui| {
+        let mut label = None;
         ui.add_sized([60.0, 0.], |ui: &mut Ui| {
             ui.with_layout(Layout::right_to_left(Align::Center), |ui: &mut Ui| {
-                ui.label(format!("{title}: "));
+                label = Some(ui.label(format!("{title}: ")).id);
             })
             .response
         });
@@ -191,11 +225,65 @@ where
         if T::IS_FLOAT {
             slider = slider.fixed_decimals(1);
         }
-        ui.add(slider)
+        let mut response = ui.add(slider);
+        if let Some(label) = label {
+            response = response.labelled_by(label);
+        }
+        focus_outline(ui, &response);
+        response
     })
     .inner
 }
 
+/// Draws the steps of a wizard in a row, with the current step highlighted and the completed
+/// steps dimmed
+pub fn stepper(ui: &mut Ui, steps: &[String], current: usize) {
+    ui.horizontal(|ui| {
+        for (index, step) in steps.iter().enumerate() {
+            if index > 0 {
+                ui.label(RichText::new("›").weak());
+            }
+
+            let text = RichText::new(format!("{}. {step}", index + 1));
+            let text = match index.cmp(&current) {
+                std::cmp::Ordering::Less => text.weak(),
+                std::cmp::Ordering::Equal => text.strong(),
+                std::cmp::Ordering::Greater => text,
+            };
+            ui.label(text);
+        }
+    });
+}
+
+/// Summarises how much of a profile or snapshot reached the device, highlighting one which
+/// stopped part way
+pub fn apply_summary(ui: &mut Ui, progress: &ApplyProgress) {
+    let ApplyProgress {
+        name,
+        sent,
+        total,
+        unchanged,
+    } = progress;
+    if progress.is_complete() {
+        let text = tr!(
+            "config-apply-complete",
+            name = name,
+            sent = sent,
+            unchanged = unchanged
+        );
+        ui.label(RichText::new(text).weak());
+    } else {
+        let theme = theme::current(ui.ctx());
+        let text = tr!(
+            "config-apply-partial",
+            name = name,
+            sent = sent,
+            total = total
+        );
+        ui.label(RichText::new(text).color(theme.failure));
+    }
+}
+
 /// Create a slider which has a trail moving from a fixed position
 #[allow(unused)]
 pub fn zero_trail_slider(
@@ -212,6 +300,7 @@ pub fn zero_trail_slider(
     let (rect, _) = ui.allocate_exact_size(desired_size, egui::Sense::hover());
 
     let response = ui.put(rect, egui::Slider::new(value, range).show_value(false));
+    focus_outline(ui, &response);
 
     let current_value = *value;
 
@@ -1,7 +1,7 @@
 use crate::device_manager::DeviceMessage;
 use crate::{
     APP_NAME, AUTO_START_KEY, BACKGROUND_PARAM, ToMainMessages, get_autostart_file,
-    prepare_context, run_async_blocking,
+    prepare_context, run_async_blocking, safe_mode, settings,
 };
 use anyhow::{Result, anyhow};
 use ashpd::WindowIdentifier;
@@ -10,6 +10,7 @@ use beacn_lib::crossbeam::channel::Sender;
 use egui::{Context, Id, Ui};
 use egui_glow::glow;
 use egui_glow::glow::HasContext;
+use egui_winit::accesskit_winit;
 use egui_winit::winit;
 use egui_winit::winit::event::StartCause;
 use egui_winit::winit::event_loop::EventLoopProxy;
@@ -27,6 +28,7 @@ use glutin::prelude::GlSurface;
 use ini::Ini;
 use log::{debug, warn};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 use std::{env, fs};
 
@@ -36,6 +38,10 @@ const FRAME_TIME: std::time::Duration = std::time::Duration::from_micros(16_667)
 //const FRAME_TIME: std::time::Duration = std::time::Duration::from_micros(4_167);
 const EVENT_PROXY: &str = "event_proxy";
 
+// Whether there's a window on screen. While closed to the tray there's nothing to draw, so
+// repaint requests are dropped rather than waking up the event loop for nothing.
+static WINDOW_VISIBLE: AtomicBool = AtomicBool::new(false);
+
 // These are events we can send into winit to trigger an update
 #[derive(Debug, Clone)]
 #[allow(unused)]
@@ -43,11 +49,38 @@ pub enum UserEvent {
     RequestRedraw,
     FocusWindow,
     DeviceMessage(DeviceMessage),
+    SuppressorProfile(Option<String>, String),
+    LightingMode(String, String),
+    MicColour([u8; 3]),
+    SetTitle(String),
     SetAutoStart(bool),
     SetMinimumRefreshRate(bool),
+    LanguageChanged,
+    AccessKit(AccessKitEvent),
     Quit,
 }
 
+// What a screen reader is asking of the window, AccessKit's own events can't be cloned
+#[derive(Debug, Clone)]
+pub enum AccessKitEvent {
+    TreeRequested,
+    Action(egui::accesskit::ActionRequest),
+    Deactivated,
+}
+
+impl From<accesskit_winit::Event> for UserEvent {
+    fn from(event: accesskit_winit::Event) -> Self {
+        let event = match event.window_event {
+            accesskit_winit::WindowEvent::InitialTreeRequested => AccessKitEvent::TreeRequested,
+            accesskit_winit::WindowEvent::ActionRequested(request) => {
+                AccessKitEvent::Action(request)
+            }
+            accesskit_winit::WindowEvent::AccessibilityDeactivated => AccessKitEvent::Deactivated,
+        };
+        UserEvent::AccessKit(event)
+    }
+}
+
 // This is a reference to the Event Proxy, which we can store inside the context
 #[derive(Clone)]
 struct EventProxy(Arc<EventLoopProxy<UserEvent>>);
@@ -60,6 +93,9 @@ pub trait App {
 
     // I don't like this being here, but it's easiest this way
     fn handle_device_message(&mut self, msg: DeviceMessage);
+    fn apply_suppressor_profile(&mut self, serial: Option<&str>, name: &str);
+    fn apply_lighting_mode(&mut self, serial: &str, name: &str);
+    fn apply_mic_colour(&mut self, colour: [u8; 3]);
 }
 
 pub struct WindowRunner {
@@ -179,18 +215,33 @@ impl WindowRunner {
             // Create a new context for the window
             self.create_new_context();
 
+            // AccessKit has to be attached before the window is first shown
+            let screen_reader = !safe_mode() && settings::get().accessibility.screen_reader;
+
             // Now try creating the Window
             let attributes = self.window_attributes.clone();
+            let attributes = attributes.with_visible(!screen_reader);
             match event_loop.create_window(attributes) {
                 Err(e) => {
                     panic!("Failed to Create Event Loop Window: {}", e);
                 }
                 Ok(window) => {
                     let window = Arc::new(window);
-                    let renderer = GlowRenderer::new(Arc::clone(&window), &self.context);
+                    let mut renderer = GlowRenderer::new(Arc::clone(&window), &self.context);
+                    if screen_reader {
+                        if let Some(proxy) = &self.event_loop_proxy {
+                            debug!("Enabling Screen Reader Support");
+                            let proxy = proxy.clone();
+                            renderer
+                                .winit_state
+                                .init_accesskit(event_loop, &window, proxy);
+                        }
+                        window.set_visible(true);
+                    }
 
                     self.window = Some(window);
                     self.renderer = Some(renderer);
+                    WINDOW_VISIBLE.store(true, Ordering::Relaxed);
                 }
             }
         }
@@ -199,6 +250,11 @@ impl WindowRunner {
     fn create_new_context(&mut self) {
         // Prepare a new context for the window
         self.context = Context::default();
+        let scale = match safe_mode() {
+            true => 1.0,
+            false => settings::get().ui_scale,
+        };
+        self.context.set_zoom_factor(scale);
         prepare_context(&mut self.context);
         self.app.with_context(&self.context);
 
@@ -212,7 +268,9 @@ impl WindowRunner {
         // Set up the Redraw Handler
         let proxy = self.event_loop_proxy.as_ref().unwrap().clone();
         self.context.set_request_repaint_callback(move |_info| {
-            let _ = proxy.send_event(UserEvent::RequestRedraw);
+            if window_visible() {
+                let _ = proxy.send_event(UserEvent::RequestRedraw);
+            }
         });
 
         // Update the main thread with the new context
@@ -222,9 +280,14 @@ impl WindowRunner {
     }
 
     fn destroy_window(&mut self) {
+        WINDOW_VISIBLE.store(false, Ordering::Relaxed);
         self.window = None;
         self.renderer = None;
+        self.redraw_pending = false;
         self.app.on_close();
+
+        // The textures for icons and images are rebuilt with the next window's context
+        self.context.forget_all_images();
     }
 
     fn schedule_redraw(&mut self, event_loop: &ActiveEventLoop) {
@@ -245,6 +308,11 @@ impl WindowRunner {
     }
 }
 
+/// Whether the window is currently open, if not, there's no point asking it to redraw
+pub fn window_visible() -> bool {
+    WINDOW_VISIBLE.load(Ordering::Relaxed)
+}
+
 // This is a helper function which lets the app send a UserEvent into the context
 pub fn send_user_event(ctx: &egui::Context, event: UserEvent) {
     ctx.data(|data| {
@@ -286,6 +354,27 @@ impl ApplicationHandler<UserEvent> for WindowRunner {
             UserEvent::DeviceMessage(msg) => {
                 self.app.handle_device_message(msg);
             }
+            UserEvent::SuppressorProfile(serial, name) => {
+                self.app.apply_suppressor_profile(serial.as_deref(), &name);
+            }
+            UserEvent::LightingMode(serial, name) => {
+                self.app.apply_lighting_mode(&serial, &name);
+            }
+            UserEvent::MicColour(colour) => {
+                self.app.apply_mic_colour(colour);
+            }
+            UserEvent::SetTitle(title) => {
+                if let Some(window) = &self.window {
+                    window.set_title(&title);
+                }
+
+                // Kept for when the window is recreated
+                self.window_attributes.title = title;
+            }
+            UserEvent::LanguageChanged => {
+                // The tray builds its menu separately, so needs to know to rebuild it
+                let _ = self.sender.send(ToMainMessages::LanguageChanged);
+            }
             UserEvent::SetAutoStart(create) => {
                 let key = Id::new(AUTO_START_KEY);
                 if let Some(window) = &self.window {
@@ -384,6 +473,20 @@ impl ApplicationHandler<UserEvent> for WindowRunner {
                     self.schedule_redraw(event_loop);
                 }
             }
+            UserEvent::AccessKit(event) => {
+                match event {
+                    AccessKitEvent::TreeRequested => self.context.enable_accesskit(),
+                    AccessKitEvent::Action(request) => {
+                        if let Some(renderer) = &mut self.renderer {
+                            renderer.winit_state.on_accesskit_action_request(request);
+                        }
+                    }
+                    AccessKitEvent::Deactivated => self.context.disable_accesskit(),
+                }
+                if self.window.is_some() {
+                    self.schedule_redraw(event_loop);
+                }
+            }
             UserEvent::Quit => {
                 debug!("Quit Event Received, closing window");
                 self.destroy_window();